@@ -0,0 +1,475 @@
+//! Computes the combinatorial [automorphism group](https://polytope.miraheze.org/wiki/Automorphism)
+//! of a polytope.
+//!
+//! An automorphism of a polytope commutes with every flag change. In a
+//! flag-connected polytope, it's then determined by the image of any single
+//! flag, so we find the automorphisms by trying to send a base flag to every
+//! other flag and propagating the map through flag changes.
+
+use super::{
+    flag::{Flag, FlagTable},
+    rank::Rank,
+    Abstract,
+};
+use crate::Polytope;
+
+use vec_like::VecLike;
+
+/// The automorphism group of a polytope, stored as the permutations that it
+/// induces on the flags.
+pub struct AutomorphismGroup {
+    /// The flags of the polytope.
+    pub flags: FlagTable,
+
+    /// The automorphisms of the polytope, where the flag with index `i` gets
+    /// sent to the flag with index `perm[i]`. The first one is always the
+    /// identity.
+    pub perms: Vec<Vec<usize>>,
+}
+
+impl AutomorphismGroup {
+    /// Returns the number of automorphisms.
+    pub fn order(&self) -> usize {
+        self.perms.len()
+    }
+
+    /// Returns the number of flag orbits of the polytope under its
+    /// automorphisms. This is `1` precisely when the polytope is regular.
+    pub fn flag_orbit_count(&self) -> usize {
+        self.flags.len() / self.order()
+    }
+
+    /// Returns the permutation that an automorphism induces on the elements
+    /// of a given rank, or `None` if the polytope has no elements of that rank
+    /// in its flags.
+    pub fn element_perm(&self, idx: usize, rank: Rank) -> Option<Vec<usize>> {
+        let r = rank.try_usize()?;
+        let perm = &self.perms[idx];
+
+        let mut element_perm = Vec::new();
+        for (flag, &image) in self.flags.flags.iter().zip(perm) {
+            let el = *flag.get(r)?;
+            if el >= element_perm.len() {
+                element_perm.resize(el + 1, usize::MAX);
+            }
+            element_perm[el] = self.flags.flags[image][r];
+        }
+
+        Some(element_perm)
+    }
+
+    /// Labels the elements of a given rank by their orbits under the
+    /// automorphisms, and returns the labels along with the number of orbits.
+    /// Orbits are numbered by their first element.
+    pub fn element_orbits(&self, rank: Rank) -> Option<(Vec<usize>, usize)> {
+        let perms: Vec<_> = (0..self.order())
+            .map(|idx| self.element_perm(idx, rank))
+            .collect::<Option<_>>()?;
+        let len = perms.first().map(Vec::len).unwrap_or_default();
+
+        let mut labels = vec![usize::MAX; len];
+        let mut count = 0;
+        for el in 0..len {
+            if labels[el] == usize::MAX {
+                for perm in &perms {
+                    labels[perm[el]] = count;
+                }
+                count += 1;
+            }
+        }
+
+        Some((labels, count))
+    }
+
+    /// Labels the flags by their orbits under the automorphisms, and returns
+    /// the labels along with the number of orbits. Orbits are numbered by
+    /// their first flag.
+    pub fn flag_orbits(&self) -> (Vec<usize>, usize) {
+        let mut labels = vec![usize::MAX; self.flags.len()];
+        let mut count = 0;
+        for flag in 0..labels.len() {
+            if labels[flag] == usize::MAX {
+                for perm in &self.perms {
+                    labels[perm[flag]] = count;
+                }
+                count += 1;
+            }
+        }
+
+        (labels, count)
+    }
+
+    /// Returns the indices of the first flag in each flag orbit, in the order
+    /// in which the orbits are numbered by [`Self::flag_orbits`].
+    pub fn flag_orbit_representatives(&self) -> Vec<usize> {
+        let (labels, count) = self.flag_orbits();
+        let mut reps = Vec::with_capacity(count);
+        for (flag, label) in labels.into_iter().enumerate() {
+            if label == reps.len() {
+                reps.push(flag);
+            }
+        }
+
+        reps
+    }
+}
+
+/// Attempts to extend the map sending the first flag of one polytope to a
+/// given flag of another into a map that commutes with every flag change.
+/// Returns the images of the flags, or `None` if there's no such map. Flags
+/// that aren't connected to the first one get sent to `usize::MAX`.
+fn extend_map(from: &FlagTable, to: &FlagTable, target: usize, rank: usize) -> Option<Vec<usize>> {
+    let mut map = vec![usize::MAX; from.len()];
+    let mut used = vec![false; to.len()];
+    map[0] = target;
+    used[target] = true;
+
+    let mut stack = vec![0];
+    while let Some(flag) = stack.pop() {
+        for r in 0..rank {
+            let next = from.change(flag, r);
+            let image = to.change(map[flag], r);
+
+            if map[next] == usize::MAX {
+                // The map must be injective.
+                if used[image] {
+                    return None;
+                }
+
+                map[next] = image;
+                used[image] = true;
+                stack.push(next);
+            } else if map[next] != image {
+                return None;
+            }
+        }
+    }
+
+    Some(map)
+}
+
+impl Abstract {
+    /// Computes the automorphism group of the polytope. Returns `None` if the
+    /// polytope isn't flag-connected, as happens with compounds.
+    ///
+    /// This takes time proportional to the square of the number of flags.
+    ///
+    /// # Panics
+    /// This method will panic if the polytope isn't sorted.
+    pub fn automorphisms(&self) -> Option<AutomorphismGroup> {
+        trace_span!("automorphisms", rank = self.rank().into_isize());
+        let flags = FlagTable::new(self);
+        let n = flags.len();
+        let rank = self.rank().try_usize().unwrap_or(0);
+
+        // The nullitope only has the identity.
+        if n == 0 {
+            return Some(AutomorphismGroup {
+                flags,
+                perms: vec![Vec::new()],
+            });
+        }
+
+        let mut perms = Vec::new();
+        for target in 0..n {
+            if let Some(perm) = extend_map(&flags, &flags, target, rank) {
+                // Flags that weren't reached aren't connected to the base flag.
+                if perm.contains(&usize::MAX) {
+                    return None;
+                }
+                perms.push(perm);
+            }
+        }
+
+        trace_event!(order = perms.len(), "found automorphisms");
+        Some(AutomorphismGroup { flags, perms })
+    }
+
+    /// Determines whether the polytope is
+    /// [regular](https://polytope.miraheze.org/wiki/Regular_polytope), i.e.
+    /// whether its automorphisms act transitively on its flags. Returns `None`
+    /// if the polytope isn't flag-connected.
+    ///
+    /// This is faster than computing the automorphism group, since it stops as
+    /// soon as some flag can't be mapped to the base flag.
+    ///
+    /// # Panics
+    /// This method will panic if the polytope isn't sorted.
+    pub fn is_regular(&self) -> Option<bool> {
+        let flags = FlagTable::new(self);
+        let rank = self.rank().try_usize().unwrap_or(0);
+        if flags.is_empty() {
+            return Some(true);
+        }
+
+        let identity = extend_map(&flags, &flags, 0, rank).unwrap();
+        if identity.contains(&usize::MAX) {
+            return None;
+        }
+
+        Some((1..flags.len()).all(|target| extend_map(&flags, &flags, target, rank).is_some()))
+    }
+
+    /// Returns the number of flag orbits of the polytope under its
+    /// automorphisms, so that regular polytopes have a single one, and
+    /// two-orbit polytopes like the cuboctahedron have two. Returns `None` if
+    /// the polytope isn't flag-connected.
+    ///
+    /// # Panics
+    /// This method will panic if the polytope isn't sorted.
+    pub fn flag_orbit_count(&self) -> Option<usize> {
+        Some(self.automorphisms()?.flag_orbit_count())
+    }
+
+    /// Returns one flag from each orbit of the flags under the automorphisms
+    /// of the polytope. Every flag is the image of exactly one of these, so
+    /// they're enough to run algorithms that only care about flags up to
+    /// symmetry. Returns `None` if the polytope isn't flag-connected.
+    ///
+    /// # Panics
+    /// This method will panic if the polytope isn't sorted.
+    pub fn flag_orbit_representatives(&self) -> Option<Vec<Flag>> {
+        let group = self.automorphisms()?;
+        Some(
+            group
+                .flag_orbit_representatives()
+                .into_iter()
+                .map(|flag| group.flags.flags[flag].clone())
+                .collect(),
+        )
+    }
+
+    /// Determines whether two polytopes are isomorphic. Returns `None` if
+    /// either of them isn't flag-connected, in which case this can't be
+    /// decided by following flag changes.
+    ///
+    /// # Panics
+    /// This method will panic if either polytope isn't sorted.
+    pub fn is_isomorphic(&self, other: &Self) -> Option<bool> {
+        if self.rank() != other.rank() {
+            return Some(false);
+        }
+
+        let from = FlagTable::new(self);
+        let to = FlagTable::new(other);
+        let rank = self.rank().try_usize().unwrap_or(0);
+        if from.len() != to.len() {
+            return Some(false);
+        }
+        if from.is_empty() {
+            return Some(true);
+        }
+
+        // The first polytope must be flag-connected.
+        let identity = extend_map(&from, &from, 0, rank).unwrap();
+        if identity.contains(&usize::MAX) {
+            return None;
+        }
+
+        // Since both polytopes have the same number of flags, an injective map
+        // between them that commutes with flag changes is a bijection.
+        Some((0..to.len()).any(|target| extend_map(&from, &to, target, rank).is_some()))
+    }
+
+    /// Returns an isomorphism from the polytope to another, given by the
+    /// images of the elements of every rank, from the vertices up to the
+    /// facets. Returns `None` if the polytopes aren't isomorphic, or if the
+    /// first one isn't flag-connected.
+    ///
+    /// # Panics
+    /// This method will panic if either polytope isn't sorted.
+    pub fn isomorphism(&self, other: &Self) -> Option<Vec<Vec<usize>>> {
+        if self.rank() != other.rank() {
+            return None;
+        }
+
+        let from = FlagTable::new(self);
+        let to = FlagTable::new(other);
+        let rank = self.rank().try_usize().unwrap_or(0);
+        if from.len() != to.len() {
+            return None;
+        }
+
+        let map = if from.is_empty() {
+            Vec::new()
+        } else {
+            (0..to.len()).find_map(|target| extend_map(&from, &to, target, rank))?
+        };
+        if map.contains(&usize::MAX) {
+            return None;
+        }
+
+        let mut elements: Vec<_> = (0..rank)
+            .map(|r| vec![0; self.el_count(Rank::from(r))])
+            .collect();
+        for (flag, &image) in from.flags.iter().zip(&map) {
+            for (r, images) in elements.iter_mut().enumerate() {
+                images[flag[r]] = to.flags[image][r];
+            }
+        }
+
+        Some(elements)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Checks the order of the automorphism group of a polytope.
+    fn test(mut poly: Abstract, order: usize) -> AutomorphismGroup {
+        poly.abs_sort();
+        let group = poly.automorphisms().unwrap();
+        assert_eq!(group.order(), order);
+
+        // The first automorphism is the identity.
+        assert!(group.perms[0].iter().enumerate().all(|(i, &j)| i == j));
+        group
+    }
+
+    #[test]
+    fn small() {
+        test(Abstract::nullitope(), 1);
+        test(Abstract::point(), 1);
+        test(Abstract::dyad(), 2);
+    }
+
+    #[test]
+    fn regular() {
+        for n in 2..8 {
+            test(Abstract::polygon(n), 2 * n);
+        }
+
+        test(Abstract::simplex(Rank::new(3)), 24);
+        test(Abstract::simplex(Rank::new(4)), 120);
+        let group = test(Abstract::hypercube(Rank::new(3)), 48);
+        assert_eq!(group.flag_orbit_count(), 1);
+        test(Abstract::orthoplex(Rank::new(4)), 384);
+    }
+
+    #[test]
+    fn orbits() {
+        let prism = Abstract::duoprism(&Abstract::polygon(5), &Abstract::dyad());
+        let group = test(prism, 20);
+        assert_eq!(group.flag_orbit_count(), 3);
+
+        // The vertices and edges of a pentagonal prism.
+        assert_eq!(group.element_orbits(Rank::new(0)).unwrap().1, 1);
+        assert_eq!(group.element_orbits(Rank::new(1)).unwrap().1, 2);
+
+        // The faces of a duoprism.
+        let duoprism = Abstract::duoprism(&Abstract::polygon(3), &Abstract::polygon(4));
+        let group = test(duoprism, 48);
+        let (labels, count) = group.element_orbits(Rank::new(3)).unwrap();
+        assert_eq!(count, 2);
+        assert_eq!(labels.len(), 7);
+    }
+
+    #[test]
+    fn flag_orbits() {
+        let mut cube = Abstract::hypercube(Rank::new(3));
+        cube.abs_sort();
+        let mut cuboctahedron = cube.truncate(&[1]);
+        cuboctahedron.abs_sort();
+        let group = cuboctahedron.automorphisms().unwrap();
+        let (labels, count) = group.flag_orbits();
+        assert_eq!(count, 2);
+
+        // Each orbit has as many flags as there are automorphisms, and flags
+        // in the same orbit are sent into each other.
+        for orbit in 0..count {
+            assert_eq!(labels.iter().filter(|&&l| l == orbit).count(), 48);
+        }
+        for perm in &group.perms {
+            assert!((0..labels.len()).all(|flag| labels[perm[flag]] == labels[flag]));
+        }
+
+        let reps = group.flag_orbit_representatives();
+        assert_eq!(reps.len(), 2);
+        assert_eq!(labels[reps[0]], 0);
+        assert_eq!(labels[reps[1]], 1);
+
+        // The two orbits of flags of a cuboctahedron either start at a
+        // triangle or a square.
+        let reps = cuboctahedron.flag_orbit_representatives().unwrap();
+        let mut sides: Vec<_> = reps
+            .iter()
+            .map(|flag| cuboctahedron[Rank::new(2)][flag[2]].subs.len())
+            .collect();
+        sides.sort_unstable();
+        assert_eq!(sides, vec![3, 4]);
+
+        let mut compound = Abstract::hypercube(Rank::new(3));
+        compound.comp_append(Abstract::hypercube(Rank::new(3)));
+        compound.abs_sort();
+        assert!(compound.flag_orbit_representatives().is_none());
+    }
+
+    #[test]
+    fn regularity() {
+        let mut cube = Abstract::hypercube(Rank::new(3));
+        cube.abs_sort();
+        assert_eq!(cube.is_regular(), Some(true));
+        assert_eq!(cube.flag_orbit_count(), Some(1));
+
+        let mut prism = Abstract::duoprism(&Abstract::polygon(5), &Abstract::dyad());
+        prism.abs_sort();
+        assert_eq!(prism.is_regular(), Some(false));
+        assert_eq!(prism.flag_orbit_count(), Some(3));
+
+        // The cuboctahedron is a two-orbit polytope.
+        let mut co = cube.truncate(&[1]);
+        co.abs_sort();
+        assert_eq!(co.is_regular(), Some(false));
+        assert_eq!(co.flag_orbit_count(), Some(2));
+
+        let mut compound = cube.clone();
+        compound.comp_append(cube);
+        compound.abs_sort();
+        assert_eq!(compound.is_regular(), None);
+        assert_eq!(compound.flag_orbit_count(), None);
+    }
+
+    #[test]
+    fn isomorphism() {
+        let mut cube = Abstract::hypercube(Rank::new(3));
+        cube.abs_sort();
+        let mut prism = Abstract::duoprism(&Abstract::polygon(4), &Abstract::dyad());
+        prism.abs_sort();
+        let mut octahedron = cube.dual();
+        octahedron.abs_sort();
+
+        assert_eq!(cube.is_isomorphic(&prism), Some(true));
+        assert_eq!(cube.is_isomorphic(&octahedron), Some(false));
+        assert_eq!(octahedron.is_isomorphic(&octahedron), Some(true));
+    }
+
+    #[test]
+    fn isomorphism_map() {
+        let mut cube = Abstract::hypercube(Rank::new(3));
+        cube.abs_sort();
+        let mut prism = Abstract::polygon(4).prism();
+        prism.abs_sort();
+
+        // The isomorphism preserves incidences.
+        let map = cube.isomorphism(&prism).unwrap();
+        for (idx, edge) in cube[Rank::new(1)].iter().enumerate() {
+            let image = &prism[Rank::new(1)][map[1][idx]];
+            for &v in &edge.subs {
+                assert!(image.subs.contains(&map[0][v]));
+            }
+        }
+
+        let mut oct = Abstract::orthoplex(Rank::new(3));
+        oct.abs_sort();
+        assert!(cube.isomorphism(&oct).is_none());
+    }
+
+    #[test]
+    fn compound() {
+        let mut compound = Abstract::simplex(Rank::new(3));
+        compound.comp_append(Abstract::simplex(Rank::new(3)));
+        compound.abs_sort();
+        assert!(compound.automorphisms().is_none());
+    }
+}
@@ -0,0 +1,295 @@
+//! Computes the signed boundary operators of the boundary complex of a
+//! polytope, and the homology they determine.
+//!
+//! Every edge is oriented from its first vertex to its second. Every other
+//! element is then oriented by choosing signs for its facets, so that the
+//! ridges between them cancel out. This can be done precisely when the
+//! element is orientable.
+
+use std::collections::HashMap;
+
+use super::{rank::Rank, Abstract};
+use crate::Polytope;
+
+use gcd::Gcd;
+use vec_like::VecLike;
+
+/// A sparse integer matrix, stored as the list of nonzero entries of each
+/// column. The entries of each column are sorted by row.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SparseMatrix {
+    /// The number of rows of the matrix.
+    rows: usize,
+
+    /// The nonzero entries of each column, as pairs of a row and a value.
+    columns: Vec<Vec<(usize, i64)>>,
+}
+
+impl SparseMatrix {
+    /// Returns the number of rows of the matrix.
+    pub fn nrows(&self) -> usize {
+        self.rows
+    }
+
+    /// Returns the number of columns of the matrix.
+    pub fn ncols(&self) -> usize {
+        self.columns.len()
+    }
+
+    /// Returns the nonzero entries of a column, as pairs of a row and a value
+    /// sorted by row.
+    pub fn column(&self, idx: usize) -> &[(usize, i64)] {
+        &self.columns[idx]
+    }
+
+    /// Returns the rank of the matrix over the rationals.
+    ///
+    /// This is computed exactly, by reducing the columns from left to right
+    /// so that no two of them end at the same row. A column is reduced by an
+    /// earlier one by taking the integer combination of both that cancels out
+    /// the last entry, and then dividing by the gcd of its entries to keep
+    /// them small.
+    pub fn rank(&self) -> usize {
+        // The reduced column that ends at each row.
+        let mut pivots: HashMap<usize, Vec<(usize, i128)>> = HashMap::new();
+
+        for column in &self.columns {
+            let mut column: Vec<_> = column.iter().map(|&(r, x)| (r, x as i128)).collect();
+
+            while let Some(&(row, x)) = column.last() {
+                match pivots.get(&row) {
+                    Some(pivot) => {
+                        let y = pivot.last().unwrap().1;
+                        column = combine(y, &column, -x, pivot);
+                    }
+                    None => {
+                        pivots.insert(row, column);
+                        break;
+                    }
+                }
+            }
+        }
+
+        pivots.len()
+    }
+}
+
+/// Returns the combination `a * u + b * v` of two sparse columns, divided by
+/// the gcd of its entries.
+fn combine(a: i128, u: &[(usize, i128)], b: i128, v: &[(usize, i128)]) -> Vec<(usize, i128)> {
+    let mut res = Vec::with_capacity(u.len() + v.len());
+    let (mut i, mut j) = (0, 0);
+
+    while i < u.len() || j < v.len() {
+        let (row, x) = match (u.get(i), v.get(j)) {
+            (Some(&(r, x)), Some(&(s, y))) if r == s => {
+                i += 1;
+                j += 1;
+                (r, a * x + b * y)
+            }
+            (Some(&(r, x)), Some(&(s, _))) if r < s => {
+                i += 1;
+                (r, a * x)
+            }
+            (Some(&(r, x)), None) => {
+                i += 1;
+                (r, a * x)
+            }
+            (_, Some(&(s, y))) => {
+                j += 1;
+                (s, b * y)
+            }
+            (None, None) => unreachable!(),
+        };
+
+        if x != 0 {
+            res.push((row, x));
+        }
+    }
+
+    let content = res.iter().fold(0, |g, &(_, x)| x.unsigned_abs().gcd(g));
+    if content > 1 {
+        for (_, x) in &mut res {
+            *x /= content as i128;
+        }
+    }
+
+    res
+}
+
+impl Abstract {
+    /// Returns the boundary operators of the boundary complex of the polytope,
+    /// i.e. of its proper elements. The entry with index `k` is the matrix of
+    /// the operator from the elements of rank `k + 1` to those of rank `k`,
+    /// with a row for each element of rank `k` and a column for each element
+    /// of rank `k + 1`.
+    ///
+    /// Returns `None` if some proper element isn't orientable.
+    pub fn boundary_matrices(&self) -> Option<Vec<SparseMatrix>> {
+        let rank = self.rank();
+        let mut matrices: Vec<SparseMatrix> = Vec::new();
+
+        for r in Rank::range_iter(1, rank) {
+            let mut columns = Vec::with_capacity(self.el_count(r));
+
+            for el in self[r].iter() {
+                // Edges go from their first vertex to their second.
+                let mut column = if r == Rank::new(1) {
+                    vec![(el.subs[0], -1), (el.subs[1], 1)]
+                } else {
+                    let prev = matrices.last().unwrap();
+                    let signs = orient(&el.subs.0, prev)?;
+                    el.subs.iter().copied().zip(signs).collect()
+                };
+
+                column.sort_unstable();
+                columns.push(column);
+            }
+
+            matrices.push(SparseMatrix {
+                rows: self.el_count(r.minus_one()),
+                columns,
+            });
+        }
+
+        Some(matrices)
+    }
+
+    /// Returns the Betti numbers of the boundary complex of the polytope with
+    /// rational coefficients, from rank 0 up to the rank of the facets.
+    /// Returns `None` if some proper element isn't orientable.
+    pub fn betti_numbers(&self) -> Option<Vec<usize>> {
+        let matrices = self.boundary_matrices()?;
+        let ranks: Vec<usize> = matrices.iter().map(SparseMatrix::rank).collect();
+
+        let facet_rank = self.rank().try_usize()?.checked_sub(1)?;
+        Some(
+            (0..=facet_rank)
+                .map(|k| {
+                    let boundary_rank = k.checked_sub(1).map_or(0, |k| ranks[k]);
+                    let coboundary_rank = ranks.get(k).copied().unwrap_or_default();
+                    self.el_count(Rank::from(k)) - boundary_rank - coboundary_rank
+                })
+                .collect(),
+        )
+    }
+}
+
+/// Chooses signs for the facets of an element, so that the boundaries of the
+/// facets add up to zero. The signs of the subelements of the facets are read
+/// from the boundary matrix of the previous rank. Returns `None` if there's no
+/// way to do this.
+fn orient(subs: &[usize], prev: &SparseMatrix) -> Option<Vec<i64>> {
+    // The facets through each ridge, along with their incidence signs.
+    let mut ridges: HashMap<usize, Vec<(usize, i64)>> = HashMap::new();
+    for (pos, &sub) in subs.iter().enumerate() {
+        for &(ridge, sign) in prev.column(sub) {
+            ridges.entry(ridge).or_default().push((pos, sign));
+        }
+    }
+
+    // Propagates the signs from one facet to the next through their ridges.
+    let mut signs = vec![0; subs.len()];
+    for start in 0..subs.len() {
+        if signs[start] != 0 {
+            continue;
+        }
+
+        signs[start] = 1;
+        let mut stack = vec![start];
+        while let Some(pos) = stack.pop() {
+            for &(ridge, sign) in prev.column(subs[pos]) {
+                for &(other, other_sign) in &ridges[&ridge] {
+                    if other == pos {
+                        continue;
+                    }
+
+                    // The ridge must cancel out.
+                    let other_orientation = -signs[pos] * sign * other_sign;
+                    if signs[other] == 0 {
+                        signs[other] = other_orientation;
+                        stack.push(other);
+                    } else if signs[other] != other_orientation {
+                        return None;
+                    }
+                }
+            }
+        }
+    }
+
+    Some(signs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conc::{file::FromFile, Concrete};
+
+    /// Checks that the boundary of a boundary is zero.
+    fn test_boundaries(poly: &Abstract) -> Vec<SparseMatrix> {
+        let matrices = poly.boundary_matrices().unwrap();
+        for pair in matrices.windows(2) {
+            for col in 0..pair[1].ncols() {
+                let mut sum = vec![0; pair[0].nrows()];
+                for &(mid, x) in pair[1].column(col) {
+                    for &(row, y) in pair[0].column(mid) {
+                        sum[row] += x * y;
+                    }
+                }
+                assert!(sum.iter().all(|&x| x == 0));
+            }
+        }
+        matrices
+    }
+
+    #[test]
+    fn boundaries() {
+        for poly in [
+            Abstract::polygon(5),
+            Abstract::simplex(Rank::new(4)),
+            Abstract::hypercube(Rank::new(4)),
+            Abstract::orthoplex(Rank::new(5)),
+            Abstract::duoprism(&Abstract::polygon(3), &Abstract::polygon(5)),
+            Abstract::polygon(4).antiprism(),
+        ] {
+            test_boundaries(&poly);
+        }
+
+        // Edges go from one vertex to another.
+        let matrices = test_boundaries(&Abstract::hypercube(Rank::new(3)));
+        assert_eq!((matrices[0].nrows(), matrices[0].ncols()), (8, 12));
+        for col in 0..12 {
+            assert_eq!(matrices[0].column(col).iter().map(|x| x.1).sum::<i64>(), 0);
+        }
+    }
+
+    /// Checks the homology of spheres.
+    #[test]
+    fn spheres() {
+        assert_eq!(Abstract::polygon(7).betti_numbers(), Some(vec![1, 1]));
+        assert_eq!(
+            Abstract::hypercube(Rank::new(3)).betti_numbers(),
+            Some(vec![1, 0, 1])
+        );
+        assert_eq!(
+            Abstract::simplex(Rank::new(5)).betti_numbers(),
+            Some(vec![1, 0, 0, 0, 1])
+        );
+    }
+
+    /// Checks the homology of a torus and of a projective plane.
+    #[test]
+    fn surfaces() {
+        let torus = Abstract::duocomb(&Abstract::polygon(4), &Abstract::polygon(5));
+        assert_eq!(torus.betti_numbers(), Some(vec![1, 2, 1]));
+
+        // The tetrahemihexahedron is a projective plane, which has no rational
+        // homology besides that of a point.
+        let path = format!(
+            "{}/../lib/3D/uniform/cat3/hemi/thah.off",
+            env!("CARGO_MANIFEST_DIR")
+        );
+        let thah = Concrete::from_path(&path).unwrap();
+        assert_eq!(thah.abs.betti_numbers(), Some(vec![1, 0, 0]));
+    }
+}
@@ -0,0 +1,186 @@
+//! Builds the [chamfer](https://en.wikipedia.org/wiki/Chamfer_(geometry)) and
+//! the loft of a polyhedron.
+//!
+//! Both operators place a smaller copy of every face inside of it. The chamfer
+//! then replaces every edge by a hexagon, joining the edge's vertices to the
+//! copies of the two faces through it. The loft instead keeps the edges, and
+//! joins every face to its copy through a ring of quadrilaterals.
+
+use std::collections::HashMap;
+
+use super::{
+    elements::{AbstractBuilder, SubelementList, Subelements},
+    rank::Rank,
+    Abstract,
+};
+use crate::Polytope;
+
+use vec_like::VecLike;
+
+impl Abstract {
+    /// Returns the vertices of every face of a polyhedron, sorted by index.
+    /// In the chamfer and the loft, the copy of each of these vertices gets a
+    /// new index, in this order, after the original vertices.
+    pub fn face_vertex_lists(&self) -> Vec<Vec<usize>> {
+        self[Rank::new(2)]
+            .iter()
+            .map(|face| {
+                let mut vertices: Vec<usize> = face
+                    .subs
+                    .iter()
+                    .flat_map(|&edge| self[Rank::new(1)][edge].subs.iter().copied())
+                    .collect();
+                vertices.sort_unstable();
+                vertices.dedup();
+                vertices
+            })
+            .collect()
+    }
+
+    /// Builds the chamfer or the loft of a polyhedron, or returns `None` if
+    /// the polytope isn't a polyhedron.
+    fn chamfer_or_loft(&self, loft: bool) -> Option<Self> {
+        if self.rank() != Rank::new(3) {
+            return None;
+        }
+
+        let vertex_count = self.vertex_count();
+        let edges = &self[Rank::new(1)];
+        let faces = &self[Rank::new(2)];
+        let face_vertices = self.face_vertex_lists();
+
+        // The copy of each vertex of each face.
+        let mut copies = HashMap::new();
+        for (f, vertices) in face_vertices.iter().enumerate() {
+            for &v in vertices {
+                let len = vertex_count + copies.len();
+                copies.insert((f, v), len);
+            }
+        }
+
+        // The original edges are kept in the loft.
+        let mut new_edges = SubelementList::new();
+        if loft {
+            for edge in edges.iter() {
+                new_edges.push(edge.subs.clone());
+            }
+        }
+
+        // The copy of each edge of each face.
+        let mut edge_copies = HashMap::new();
+        for (f, face) in faces.iter().enumerate() {
+            for &e in &face.subs {
+                let subs = &edges[e].subs;
+                edge_copies.insert((f, e), new_edges.len());
+                new_edges.push(Subelements(vec![
+                    copies[&(f, subs[0])],
+                    copies[&(f, subs[1])],
+                ]));
+            }
+        }
+
+        // The edges from each vertex of each face to its copy.
+        let mut joins = HashMap::new();
+        for (f, vertices) in face_vertices.iter().enumerate() {
+            for &v in vertices {
+                joins.insert((f, v), new_edges.len());
+                new_edges.push(Subelements(vec![v, copies[&(f, v)]]));
+            }
+        }
+
+        // The copies of the faces.
+        let mut new_faces = SubelementList::new();
+        for (f, face) in faces.iter().enumerate() {
+            new_faces.push(Subelements(
+                face.subs.iter().map(|&e| edge_copies[&(f, e)]).collect(),
+            ));
+        }
+
+        if loft {
+            // A quadrilateral between every edge of every face and its copy.
+            for (f, face) in faces.iter().enumerate() {
+                for &e in &face.subs {
+                    let subs = &edges[e].subs;
+                    new_faces.push(Subelements(vec![
+                        e,
+                        edge_copies[&(f, e)],
+                        joins[&(f, subs[0])],
+                        joins[&(f, subs[1])],
+                    ]));
+                }
+            }
+        } else {
+            // A hexagon for every edge, between the copies of its two faces.
+            for (e, edge) in edges.iter().enumerate() {
+                let mut subs = Vec::with_capacity(6);
+                for &f in &edge.sups {
+                    subs.push(edge_copies[&(f, e)]);
+                    subs.push(joins[&(f, edge.subs[0])]);
+                    subs.push(joins[&(f, edge.subs[1])]);
+                }
+                new_faces.push(Subelements(subs));
+            }
+        }
+
+        let mut builder = AbstractBuilder::with_capacity(Rank::new(3));
+        builder.push_min();
+        builder.push_vertices(vertex_count + copies.len());
+        builder.push(new_edges);
+        builder.push(new_faces);
+        builder.push_max();
+        Some(builder.build())
+    }
+
+    /// Returns the [chamfer](https://en.wikipedia.org/wiki/Chamfer_(geometry))
+    /// of a polyhedron, which replaces every edge by a hexagon, or `None` if
+    /// the polytope isn't a polyhedron. The vertices are ordered as described
+    /// in [`Self::face_vertex_lists`].
+    pub fn chamfer(&self) -> Option<Self> {
+        self.chamfer_or_loft(false)
+    }
+
+    /// Returns the loft of a polyhedron, which joins every face to a copy of
+    /// itself through a ring of quadrilaterals, or `None` if the polytope isn't
+    /// a polyhedron. The vertices are ordered as described in
+    /// [`Self::face_vertex_lists`].
+    pub fn loft(&self) -> Option<Self> {
+        self.chamfer_or_loft(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Checks the element counts of the chamfer and the loft of a polyhedron,
+    /// given its number of vertices, edges, and faces.
+    fn test(poly: &Abstract, [v, e, f]: [usize; 3]) {
+        let chamfer = poly.chamfer().unwrap();
+        assert_eq!(
+            chamfer.el_counts(),
+            vec![1, v + 2 * e, 4 * e, f + e, 1].into()
+        );
+        chamfer.is_valid().unwrap();
+
+        let loft = poly.loft().unwrap();
+        assert_eq!(
+            loft.el_counts(),
+            vec![1, v + 2 * e, 5 * e, f + 2 * e, 1].into()
+        );
+        loft.is_valid().unwrap();
+    }
+
+    #[test]
+    fn polyhedra() {
+        test(&Abstract::simplex(Rank::new(3)), [4, 6, 4]);
+        test(&Abstract::hypercube(Rank::new(3)), [8, 12, 6]);
+        test(&Abstract::orthoplex(Rank::new(3)), [6, 12, 8]);
+        test(&Abstract::polygon(5).antiprism(), [10, 20, 12]);
+    }
+
+    #[test]
+    fn not_polyhedra() {
+        assert!(Abstract::polygon(4).chamfer().is_none());
+        assert!(Abstract::hypercube(Rank::new(4)).loft().is_none());
+    }
+}
@@ -0,0 +1,265 @@
+//! Declares the [`Complex`] type, which stores polytope-like structures that
+//! don't have a single maximal element, such as solid tori or the tiles of a
+//! tiling.
+
+use std::collections::HashMap;
+
+use super::{
+    elements::{AbstractBuilder, ElementList, ElementRef, SectionRef, SubelementList, Subelements},
+    rank::{Rank, RankVec},
+    Abstract, AbstractError, AbstractResult,
+};
+
+use vec_like::VecLike;
+
+/// A complex of elements with a single minimal element, but with any number of
+/// maximal elements. These are the elements of its highest rank, which we call
+/// its facets.
+///
+/// Complexes share their element lists with [`Abstract`] polytopes, but are
+/// subject to weaker validity conditions: every ridge may lie on either one or
+/// two facets, so that complexes may have a boundary. This allows us to store
+/// things like rings of prisms, whose boundaries are toroidal polyhedra.
+#[derive(Debug, Default, Clone)]
+pub struct Complex {
+    /// The list of element lists in the complex, ordered by [`Rank`].
+    pub ranks: RankVec<ElementList>,
+}
+
+impl From<Abstract> for Complex {
+    fn from(abs: Abstract) -> Self {
+        Self { ranks: abs.ranks }
+    }
+}
+
+impl std::ops::Index<Rank> for Complex {
+    type Output = ElementList;
+
+    fn index(&self, index: Rank) -> &Self::Output {
+        &self.ranks[index]
+    }
+}
+
+impl Complex {
+    /// The rank of the facets of the complex.
+    pub fn rank(&self) -> Rank {
+        self.ranks.rank()
+    }
+
+    /// Returns the number of elements of a given rank.
+    pub fn el_count(&self, rank: Rank) -> usize {
+        self.ranks.get(rank).map(ElementList::len).unwrap_or(0)
+    }
+
+    /// Returns the element counts of the complex.
+    pub fn el_counts(&self) -> RankVec<usize> {
+        self.ranks
+            .iter()
+            .map(ElementList::len)
+            .collect::<Vec<_>>()
+            .into()
+    }
+
+    /// Returns the polytope whose maximal element has every facet of the
+    /// complex as a subelement. This polytope will generally not be valid, but
+    /// it can still be rendered.
+    pub fn into_abstract(self) -> Abstract {
+        let mut abs = Abstract::from(self.ranks);
+        abs.push_max();
+        abs
+    }
+
+    /// Checks whether the complex is valid, i.e. whether it has a single
+    /// minimal element, its elements are consistent and ranked, every section
+    /// of height 1 below a facet has two middle elements, and every ridge lies
+    /// on one or two facets. Sections are given as in the polytope returned by
+    /// [`Self::into_abstract`].
+    pub fn is_valid(&self) -> AbstractResult<()> {
+        let min_count = self.el_count(Rank::new(-1));
+        if min_count != 1 {
+            return Err(AbstractError::Bounded {
+                min_count,
+                max_count: self.el_count(self.rank()),
+            });
+        }
+
+        let rank = self.rank();
+        let abs = self.clone().into_abstract();
+        abs.check_incidences()?;
+        abs.is_dyadic_up_to(rank)?;
+
+        if let Some(ridge_rank) = rank.try_sub(Rank::new(1)) {
+            for (idx, ridge) in self[ridge_rank].iter().enumerate() {
+                let sups = ridge.sups.len();
+                if sups > 2 {
+                    return Err(AbstractError::Dyadic {
+                        section: SectionRef::new(
+                            ElementRef::new(ridge_rank, idx),
+                            ElementRef::new(rank.plus_one(), 0),
+                        ),
+                        more: true,
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the boundary of the complex, i.e. the complex whose facets are
+    /// the ridges on a single facet, along with all of their subelements. For
+    /// instance, the boundary of a ring of prisms is a toroidal polyhedron.
+    /// The elements of every rank keep their relative order.
+    ///
+    /// # Panics
+    /// This method will panic if the complex has rank less than 1.
+    pub fn boundary(&self) -> Self {
+        let ridge_rank = self
+            .rank()
+            .try_sub(Rank::new(1))
+            .expect("Complex has no ridges.");
+
+        // The ridges on a single facet, and the elements below them.
+        let mut elements: Vec<_> = self[ridge_rank]
+            .iter()
+            .enumerate()
+            .filter(|(_, ridge)| ridge.sups.len() == 1)
+            .map(|(idx, _)| idx)
+            .collect();
+
+        let mut lists = Vec::new();
+        for r in Rank::range_inclusive_iter(0, ridge_rank).rev() {
+            let mut lower: Vec<_> = elements
+                .iter()
+                .flat_map(|&idx| self[r][idx].subs.iter().copied())
+                .collect();
+            lower.sort_unstable();
+            lower.dedup();
+
+            let new_idx: HashMap<_, _> = lower.iter().enumerate().map(|(i, &s)| (s, i)).collect();
+            let mut list = SubelementList::with_capacity(elements.len());
+            for idx in elements {
+                list.push(Subelements(
+                    self[r][idx].subs.iter().map(|s| new_idx[s]).collect(),
+                ));
+            }
+
+            lists.push(list);
+            elements = lower;
+        }
+
+        let mut builder = AbstractBuilder::with_capacity(ridge_rank);
+        builder.push_min();
+        for list in lists.into_iter().rev() {
+            builder.push(list);
+        }
+
+        builder.build().into()
+    }
+
+    /// Builds a ring of `n` prisms with `m`-gonal bases, each of which shares
+    /// its bases with the prisms next to it. Its boundary is a toroidal
+    /// polyhedron made out of `n * m` squares.
+    ///
+    /// The vertex with index `i * m + j` is the `j`-th vertex of the `i`-th
+    /// base, and the `i`-th prism is that between the `i`-th base and the
+    /// next.
+    ///
+    /// # Panics
+    /// This method will panic if either `n` or `m` is less than 3.
+    pub fn prism_ring(n: usize, m: usize) -> Self {
+        assert!(
+            n >= 3 && m >= 3,
+            "A ring of prisms needs at least 3 prisms and bases with at least 3 sides."
+        );
+
+        // The vertices, the edges of the bases, the edges between the bases,
+        // and the squares are all indexed by the same scheme.
+        let idx = |i: usize, j: usize| (i % n) * m + j % m;
+        let mut builder = AbstractBuilder::with_capacity(Rank::new(3));
+        builder.push_min();
+        builder.push_vertices(n * m);
+
+        // The edges of the bases, followed by the edges between them.
+        let mut edges = SubelementList::with_capacity(2 * n * m);
+        for i in 0..n {
+            for j in 0..m {
+                edges.push(vec![idx(i, j), idx(i, j + 1)].into());
+            }
+        }
+        for i in 0..n {
+            for j in 0..m {
+                edges.push(vec![idx(i, j), idx(i + 1, j)].into());
+            }
+        }
+        builder.push(edges);
+
+        // The bases, followed by the squares.
+        let mut faces = SubelementList::with_capacity(n * (m + 1));
+        for i in 0..n {
+            faces.push((0..m).map(|j| idx(i, j)).collect::<Vec<_>>().into());
+        }
+        for i in 0..n {
+            for j in 0..m {
+                faces.push(
+                    vec![
+                        idx(i, j),
+                        idx(i + 1, j),
+                        n * m + idx(i, j),
+                        n * m + idx(i, j + 1),
+                    ]
+                    .into(),
+                );
+            }
+        }
+        builder.push(faces);
+
+        // The prisms.
+        let mut prisms = SubelementList::with_capacity(n);
+        for i in 0..n {
+            let mut subs = vec![i, (i + 1) % n];
+            subs.extend((0..m).map(|j| n + idx(i, j)));
+            prisms.push(subs.into());
+        }
+        builder.push(prisms);
+
+        builder.build().into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Polytope;
+
+    #[test]
+    fn prism_ring() {
+        let ring = Complex::prism_ring(5, 4);
+        ring.is_valid().unwrap();
+        assert_eq!(ring.el_counts(), vec![1, 20, 40, 25, 5].into());
+
+        // The ring isn't a polytope, since the squares are on a single prism.
+        assert!(matches!(
+            ring.clone().into_abstract().is_valid(),
+            Err(AbstractError::Dyadic { more: false, .. })
+        ));
+
+        // Its boundary is a toroidal polyhedron.
+        let torus = ring.boundary();
+        torus.is_valid().unwrap();
+        assert_eq!(torus.el_counts(), vec![1, 20, 40, 20].into());
+        torus.into_abstract().is_valid().unwrap();
+    }
+
+    #[test]
+    fn polytope() {
+        let cube = Complex::from(Abstract::hypercube(Rank::new(3)));
+        cube.is_valid().unwrap();
+        assert_eq!(cube.el_count(Rank::new(3)), 1);
+
+        // The boundary of a polytope is its surface.
+        let boundary = cube.boundary();
+        boundary.is_valid().unwrap();
+        assert_eq!(boundary.el_counts(), vec![1, 8, 12, 6].into());
+    }
+}
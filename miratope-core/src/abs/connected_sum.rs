@@ -0,0 +1,158 @@
+//! Glues two polytopes along isomorphic facets, building their
+//! [connected sum](https://en.wikipedia.org/wiki/Connected_sum).
+
+use std::collections::HashMap;
+
+use super::{
+    elements::{AbstractBuilder, ElementHash, ElementRef, SubelementList, Subelements},
+    rank::Rank,
+    Abstract,
+};
+use crate::Polytope;
+
+use vec_like::VecLike;
+
+/// Returns the map from the indices of the elements of every rank of a facet,
+/// seen as a polytope, to their indices in the original polytope, along with
+/// the sorted facet.
+fn facet_elements(poly: &Abstract, facet: usize) -> Option<(Vec<Vec<usize>>, Abstract)> {
+    let facet_rank = poly.rank().try_sub(Rank::new(1))?;
+    let hash = ElementHash::new(poly, ElementRef::new(facet_rank, facet))?;
+    let mut element = hash.to_polytope(poly);
+    element.abs_sort();
+
+    let maps = Rank::range_iter(0, facet_rank)
+        .map(|r| {
+            let map = hash.get(r).unwrap();
+            let mut elements = vec![0; map.len()];
+            for (&idx, &new_idx) in map {
+                elements[new_idx] = idx;
+            }
+            elements
+        })
+        .collect();
+
+    Some((maps, element))
+}
+
+impl Abstract {
+    /// Glues two polytopes of the same rank along a facet of each, which must
+    /// be isomorphic. Both facets are removed, and the elements on the
+    /// boundary of the second facet are identified with those on the first one
+    /// through an isomorphism between them. For instance, gluing two
+    /// tetrahedra along a face gives a triangular bipyramid, and gluing a
+    /// square pyramid onto a cube gives an augmented cube.
+    ///
+    /// The elements of the first polytope keep their indices, and are followed
+    /// by the remaining elements of the second polytope. Returns `None` if
+    /// the polytopes don't have the same rank of at least 1, if some facet
+    /// doesn't exist, or if the facets aren't isomorphic.
+    pub fn connected_sum(&self, other: &Self, facet: usize, other_facet: usize) -> Option<Self> {
+        let rank = self.rank();
+        if other.rank() != rank || rank < Rank::new(1) {
+            return None;
+        }
+
+        let (maps, element) = facet_elements(self, facet)?;
+        let (other_maps, other_element) = facet_elements(other, other_facet)?;
+        let isomorphism = other_element.isomorphism(&element)?;
+
+        // The elements of the second polytope on its facet, sent to those of
+        // the first polytope they're identified with.
+        let shared: Vec<HashMap<usize, usize>> = other_maps
+            .iter()
+            .zip(&isomorphism)
+            .zip(&maps)
+            .map(|((other_map, iso), map)| {
+                other_map
+                    .iter()
+                    .enumerate()
+                    .map(|(new_idx, &idx)| (idx, map[iso[new_idx]]))
+                    .collect()
+            })
+            .collect();
+
+        let facet_rank = rank.minus_one();
+        let mut builder = AbstractBuilder::with_capacity(rank);
+        builder.push_min();
+
+        // The indices of the elements of the previous rank of the second
+        // polytope in the result.
+        let mut prev_indices = vec![0];
+        for r in Rank::range_inclusive_iter(0, facet_rank) {
+            let shared = shared.get(r.into_usize());
+            let is_facet = r == facet_rank;
+            let mut subs_list = SubelementList::new();
+            let mut indices = Vec::with_capacity(other.el_count(r));
+
+            for (idx, el) in self[r].iter().enumerate() {
+                if !(is_facet && idx == facet) {
+                    subs_list.push(el.subs.clone());
+                }
+            }
+
+            for (idx, el) in other[r].iter().enumerate() {
+                if let Some(&shared_idx) = shared.and_then(|shared| shared.get(&idx)) {
+                    indices.push(shared_idx);
+                } else if is_facet && idx == other_facet {
+                    indices.push(usize::MAX);
+                } else {
+                    indices.push(subs_list.len());
+                    subs_list.push(Subelements(
+                        el.subs.iter().map(|&s| prev_indices[s]).collect(),
+                    ));
+                }
+            }
+
+            builder.push(subs_list);
+            prev_indices = indices;
+        }
+
+        builder.push_max();
+        Some(builder.build())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bipyramid() {
+        let tet = Abstract::simplex(Rank::new(3));
+        let mut sum = tet.connected_sum(&tet, 0, 1).unwrap();
+        sum.is_valid().unwrap();
+        assert_eq!(sum.el_counts(), vec![1, 5, 9, 6, 1].into());
+
+        let mut bipyramid = Abstract::duotegum(&Abstract::polygon(3), &Abstract::dyad());
+        sum.abs_sort();
+        bipyramid.abs_sort();
+        assert_eq!(sum.is_isomorphic(&bipyramid), Some(true));
+    }
+
+    #[test]
+    fn cubes() {
+        let cube = Abstract::hypercube(Rank::new(3));
+        let sum = cube.connected_sum(&cube, 0, 5).unwrap();
+        sum.is_valid().unwrap();
+        assert_eq!(sum.el_counts(), vec![1, 12, 20, 10, 1].into());
+
+        // Facets must be isomorphic.
+        let prism = Abstract::polygon(3).prism();
+        let triangle = (0..5)
+            .find(|&idx| prism[Rank::new(2)][idx].subs.len() == 3)
+            .unwrap();
+        assert!(cube.connected_sum(&prism, 0, triangle).is_none());
+        assert!(cube.connected_sum(&cube, 6, 0).is_none());
+    }
+
+    #[test]
+    fn polygons() {
+        // Gluing two polygons along an edge adds their edge counts.
+        let sum = Abstract::polygon(3)
+            .connected_sum(&Abstract::polygon(4), 0, 0)
+            .unwrap();
+        sum.is_valid().unwrap();
+        assert_eq!(sum.el_counts(), vec![1, 5, 5, 1].into());
+    }
+}
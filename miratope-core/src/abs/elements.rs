@@ -1,19 +1,25 @@
 //! A bunch of wrappers and helper structs that make dealing with abstract
 //! polytopes much less confusing.
 
-use std::{collections::HashMap, iter::IntoIterator};
+use std::{
+    collections::{HashMap, HashSet},
+    iter::IntoIterator,
+};
 
 use super::{
     rank::{Rank, RankVec},
-    Abstract,
+    Abstract, AbstractError, AbstractResult, IncidenceType,
 };
 use crate::Polytope;
 
+#[cfg(feature = "serde-serialize")]
+use serde::{Deserialize, Serialize};
 use vec_like::*;
 
 /// A bundled rank and index, which can be used as coordinates to refer to an
 /// element in an abstract polytope.
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
 pub struct ElementRef {
     /// The rank of the element.
     pub rank: Rank,
@@ -56,6 +62,11 @@ pub trait Subsupelements: Sized + VecLike<VecItem = usize> {
 ///
 /// Internally, this is just a wrapper around a `Vec<usize>`.
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde-serialize",
+    derive(Serialize, Deserialize),
+    serde(transparent)
+)]
 pub struct Subelements(pub Vec<usize>);
 impl_veclike!(Subelements, Item = usize, Index = usize);
 impl Subsupelements for Subelements {}
@@ -66,6 +77,11 @@ impl Subsupelements for Subelements {}
 ///
 /// Internally, this is just a wrapper around a `Vec<usize>`.
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde-serialize",
+    derive(Serialize, Deserialize),
+    serde(transparent)
+)]
 pub struct Superelements(pub Vec<usize>);
 impl_veclike!(Superelements, Item = usize, Index = usize);
 impl Subsupelements for Superelements {}
@@ -78,6 +94,7 @@ impl Subsupelements for Superelements {}
 /// element in an abstract polytope, we often are in need to use both of them.
 /// To avoid recalculating them every single time, we just store them both.
 #[derive(Default, Debug, Clone, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
 pub struct Element {
     /// The indices of the subelements of the previous rank.
     pub subs: Subelements,
@@ -138,9 +155,15 @@ impl Element {
 ///
 /// Internally, this is just a wrapper around `Vec<Element>`.
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde-serialize",
+    derive(Serialize, Deserialize),
+    serde(transparent)
+)]
 pub struct ElementList(Vec<Element>);
 impl_veclike!(ElementList, Item = Element, Index = usize);
 
+#[cfg(feature = "parallel")]
 impl<'a> rayon::iter::IntoParallelIterator for &'a mut ElementList {
     type Iter = rayon::slice::IterMut<'a, Element>;
 
@@ -262,10 +285,91 @@ impl AbstractBuilder {
         self.0.push_max();
     }
 
+    /// Pushes a new [`SubelementList`] onto the polytope, after checking that
+    /// every subelement refers to an existing element of the previous rank,
+    /// and that no element lists the same subelement twice. The list isn't
+    /// pushed if any of these checks fail.
+    pub fn try_push(&mut self, subelements: SubelementList) -> AbstractResult<()> {
+        let (rank, prev_count) = if self.is_empty() {
+            (Rank::new(-1), 0)
+        } else {
+            let rank = self.0.rank();
+            (rank.plus_one(), self.0.el_count(rank))
+        };
+
+        for (idx, subs) in subelements.iter().enumerate() {
+            let el = ElementRef::new(rank, idx);
+            let mut found = HashSet::new();
+
+            for &sub in subs {
+                if sub >= prev_count {
+                    return Err(AbstractError::Index {
+                        el,
+                        incidence_type: IncidenceType::Subelement,
+                        index: sub,
+                    });
+                }
+                if !found.insert(sub) {
+                    return Err(AbstractError::Repeated { el, index: sub });
+                }
+            }
+        }
+
+        self.push(subelements);
+        Ok(())
+    }
+
     /// Returns the built polytope, consuming the builder in the process.
     pub fn build(self) -> Abstract {
         self.0
     }
+
+    /// Returns the built polytope after checking that it's valid in the sense
+    /// of [`Abstract::validate`], consuming the builder in the process.
+    ///
+    /// The checks are made rank by rank from the bottom up, so that the error
+    /// returned is always found at the lowest possible rank.
+    pub fn try_build(self) -> AbstractResult<Abstract> {
+        let poly = self.0;
+        if poly.is_empty() {
+            return Err(AbstractError::Bounded {
+                min_count: 0,
+                max_count: 0,
+            });
+        }
+        poly.bounded()?;
+
+        for r in Rank::range_inclusive_iter(-1, poly.rank()) {
+            poly.check_incidences_at(r)?;
+            if r >= Rank::new(1) {
+                poly.is_dyadic_at(r)?;
+            }
+            if r >= Rank::new(2) {
+                poly.check_sections_at(r)?;
+            }
+        }
+
+        Ok(poly)
+    }
+}
+
+impl Abstract {
+    /// Builds an abstract polytope from the lists of subelements of every
+    /// rank, starting from the minimal element and ending at the maximal
+    /// element. Each element is given by the indices of its subelements.
+    ///
+    /// Returns an error if the lists don't describe a valid polytope, in the
+    /// sense of [`Abstract::validate`].
+    pub fn try_from_subs(lists: Vec<Vec<Vec<usize>>>) -> AbstractResult<Self> {
+        let mut builder = AbstractBuilder::new();
+
+        for list in lists {
+            let list: Vec<_> = list.into_iter().map(Subelements::from).collect();
+            builder.try_push(list.into())?;
+        }
+
+        builder.try_build()
+    }
 }
 
 /// Maps each recursive subelement of an abstract polytope's element to a
@@ -306,10 +410,16 @@ impl ElementHash {
     }
 
     /// Gets the `HashMap` corresponding to elements of a given rank.
-    fn get(&self, idx: Rank) -> Option<&HashMap<usize, usize>> {
+    pub fn get(&self, idx: Rank) -> Option<&HashMap<usize, usize>> {
         self.0.get(idx)
     }
 
+    /// Gets the index that an element of the original polytope gets in the new
+    /// polytope, or `None` if it isn't a subelement of the element.
+    pub fn get_idx(&self, el: ElementRef) -> Option<usize> {
+        self.get(el.rank)?.get(&el.idx).copied()
+    }
+
     /// Gets the indices of the elements of a given rank in the original
     /// polytope.
     fn to_elements(&self, rank: Rank) -> Vec<usize> {
@@ -476,3 +586,80 @@ impl SectionHash {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The subelement lists of a square, with a given list of edges.
+    fn square(edges: Vec<Vec<usize>>) -> Vec<Vec<Vec<usize>>> {
+        vec![
+            vec![vec![]],
+            vec![vec![0]; 4],
+            edges,
+            vec![vec![0, 1, 2, 3]],
+        ]
+    }
+
+    #[test]
+    fn try_from_subs() {
+        let edges = vec![vec![0, 1], vec![1, 2], vec![2, 3], vec![3, 0]];
+        let poly = Abstract::try_from_subs(square(edges)).unwrap();
+        assert_eq!(poly.el_counts(), vec![1, 4, 4, 1].into());
+        assert!(poly.validate().is_ok());
+
+        // The point and the nullitope.
+        assert!(Abstract::try_from_subs(vec![vec![vec![]], vec![vec![0]]]).is_ok());
+        assert!(Abstract::try_from_subs(vec![vec![vec![]]]).is_ok());
+    }
+
+    #[test]
+    fn try_from_subs_errors() {
+        let err = |lists| Abstract::try_from_subs(lists).err();
+
+        assert!(matches!(
+            err(square(vec![vec![0, 1], vec![1, 2], vec![2, 3], vec![3, 4]])),
+            Some(AbstractError::Index { index: 4, .. })
+        ));
+        assert!(matches!(
+            err(square(vec![vec![0, 1], vec![1, 2], vec![2, 3], vec![3, 3]])),
+            Some(AbstractError::Repeated { index: 3, .. })
+        ));
+        assert!(matches!(
+            err(square(vec![vec![0, 1], vec![1, 2], vec![2, 0], vec![3, 0]])),
+            Some(AbstractError::Dyadic { .. })
+        ));
+        assert!(matches!(
+            err(Vec::new()),
+            Some(AbstractError::Bounded { .. })
+        ));
+
+        // Two triangles sharing no vertices aren't connected.
+        let edges = vec![
+            vec![0, 1],
+            vec![1, 2],
+            vec![2, 0],
+            vec![3, 4],
+            vec![4, 5],
+            vec![5, 3],
+        ];
+        let lists = vec![
+            vec![vec![]],
+            vec![vec![0]; 6],
+            edges,
+            vec![(0..6).collect()],
+        ];
+        assert!(matches!(err(lists), Some(AbstractError::Connected(_))));
+
+        // A vertex that isn't in any edge.
+        let mut lists = square(vec![vec![0, 1], vec![1, 2], vec![2, 0]]);
+        lists[3] = vec![vec![0, 1, 2]];
+        assert!(matches!(
+            err(lists),
+            Some(AbstractError::Ranked {
+                incidence_type: IncidenceType::Superelement,
+                ..
+            })
+        ));
+    }
+}
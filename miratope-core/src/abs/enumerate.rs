@@ -0,0 +1,475 @@
+//! Enumerates the small abstract polyhedra up to isomorphism.
+//!
+//! Every abstract polyhedron is determined by its flag graph, in which the
+//! flags are joined by three fixed-point-free involutions `r0`, `r1`, and `r2`,
+//! which change the vertex, the edge, and the face of a flag. Since `r0` and
+//! `r2` commute, the flags come in squares, one for each edge. We build every
+//! connected graph of this form by joining squares through `r1`, turn each of
+//! them into a poset, and keep the ones that are valid polytopes.
+//!
+//! The flags get labeled in the order in which the search finds them, so that
+//! every graph is built once for every flag it could have started from. Only
+//! the labeling with the smallest list of `r1` values is kept, and since this
+//! list is decided from the start, any partial graph that can't lead to it
+//! gets pruned right away.
+//!
+//! The search keeps the graphs that are yet to be completed in an explicit
+//! frontier, so that it can be saved to a checkpoint and resumed later when the
+//! `serde-serialize` feature is enabled.
+
+#[cfg(feature = "serde-serialize")]
+use std::io;
+use std::{
+    cmp::Ordering,
+    collections::{HashSet, VecDeque},
+};
+
+use super::{
+    elements::{AbstractBuilder, SubelementList, Subelements},
+    Abstract,
+};
+#[cfg(feature = "serde-serialize")]
+use crate::checkpoint::Checkpoint;
+
+#[cfg(feature = "serde-serialize")]
+use serde::{Deserialize, Serialize};
+use vec_like::VecLike;
+
+/// A flag graph that's being built. Flag `4 * s + i` belongs to the square
+/// `s`, and `r0` and `r2` swap the two lowest bits of its index, respectively.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+struct FlagGraph {
+    /// The flag that each flag changes into under `r1`, if it's been decided.
+    r1: Vec<Option<usize>>,
+}
+
+/// Applies `r0` to a flag.
+fn r0(flag: usize) -> usize {
+    flag ^ 1
+}
+
+/// Applies `r2` to a flag.
+fn r2(flag: usize) -> usize {
+    flag ^ 2
+}
+
+impl FlagGraph {
+    /// Returns the number of flags of the graph.
+    fn len(&self) -> usize {
+        self.r1.len()
+    }
+
+    /// Applies the flag change of a given rank to a flag.
+    fn change(&self, flag: usize, r: usize) -> usize {
+        match r {
+            0 => r0(flag),
+            1 => self.r1[flag].unwrap(),
+            _ => r2(flag),
+        }
+    }
+
+    /// Adds a new square of flags.
+    fn push_square(&mut self) {
+        self.r1.extend([None; 4]);
+    }
+
+    /// Joins two flags through `r1`.
+    fn join(&mut self, f: usize, g: usize) {
+        self.r1[f] = Some(g);
+        self.r1[g] = Some(f);
+    }
+
+    /// Labels the flags of the square of a given flag with the next labels, in
+    /// the same order in which the search adds them.
+    fn label_square(flag: usize, labels: &mut [usize], order: &mut Vec<usize>) {
+        for g in [flag, r0(flag), r2(flag), r0(r2(flag))] {
+            labels[g] = order.len();
+            order.push(g);
+        }
+    }
+
+    /// Relabels the flags in the order in which the search would find them if
+    /// it started from a given flag, and compares the resulting values of `r1`
+    /// to a given list, for as long as they're decided.
+    fn cmp_relabeled(&self, root: usize, code: &[usize]) -> Ordering {
+        let mut labels = vec![usize::MAX; self.len()];
+        let mut order = Vec::with_capacity(self.len());
+        Self::label_square(root, &mut labels, &mut order);
+
+        for (idx, &value) in code.iter().enumerate() {
+            let partner = match order.get(idx).and_then(|&flag| self.r1[flag]) {
+                Some(partner) => partner,
+                None => return Ordering::Equal,
+            };
+
+            if labels[partner] == usize::MAX {
+                Self::label_square(partner, &mut labels, &mut order);
+            }
+
+            match labels[partner].cmp(&value) {
+                Ordering::Equal => {}
+                ordering => return ordering,
+            }
+        }
+
+        Ordering::Equal
+    }
+
+    /// Returns whether the graph can still be completed into the labeling of
+    /// its completion with the smallest list of `r1` values.
+    ///
+    /// Since the flags are labeled in the order in which the search finds
+    /// them, the values of `r1` that have been decided so far are the start of
+    /// this list. The values decided when starting from any other flag never
+    /// change as the graph gets completed, so if any of them compare smaller,
+    /// so will every completion.
+    fn is_canonical(&self) -> bool {
+        let code: Vec<_> = self.r1.iter().map_while(|&g| g).collect();
+        (1..self.len()).all(|root| self.cmp_relabeled(root, &code) != Ordering::Less)
+    }
+
+    /// Returns the canonical form of a complete flag graph. This is the
+    /// smallest list of flag changes over all relabelings of the flags in the
+    /// order they're found by a breadth-first search, which tries out the
+    /// flag changes in order.
+    fn canonical_form(&self) -> Vec<usize> {
+        let n = self.len();
+        let mut best: Option<Vec<usize>> = None;
+
+        for root in 0..n {
+            let mut labels = vec![usize::MAX; n];
+            let mut order = Vec::with_capacity(n);
+            let mut queue = VecDeque::new();
+            labels[root] = 0;
+            order.push(root);
+            queue.push_back(root);
+
+            while let Some(flag) = queue.pop_front() {
+                for r in 0..3 {
+                    let next = self.change(flag, r);
+                    if labels[next] == usize::MAX {
+                        labels[next] = order.len();
+                        order.push(next);
+                        queue.push_back(next);
+                    }
+                }
+            }
+
+            let code: Vec<_> = order
+                .iter()
+                .flat_map(|&flag| (0..3).map(move |r| (flag, r)))
+                .map(|(flag, r)| labels[self.change(flag, r)])
+                .collect();
+
+            if !matches!(&best, Some(best) if *best <= code) {
+                best = Some(code);
+            }
+        }
+
+        best.unwrap_or_default()
+    }
+
+    /// Labels the flags of a complete graph by their orbits under two flag
+    /// changes. Returns the labels, along with the number of orbits.
+    fn orbits(&self, changes: [usize; 2]) -> (Vec<usize>, usize) {
+        let n = self.len();
+        let mut labels = vec![usize::MAX; n];
+        let mut count = 0;
+
+        for start in 0..n {
+            if labels[start] != usize::MAX {
+                continue;
+            }
+
+            let mut stack = vec![start];
+            labels[start] = count;
+            while let Some(flag) = stack.pop() {
+                for &r in &changes {
+                    let next = self.change(flag, r);
+                    if labels[next] == usize::MAX {
+                        labels[next] = count;
+                        stack.push(next);
+                    }
+                }
+            }
+
+            count += 1;
+        }
+
+        (labels, count)
+    }
+
+    /// Builds the poset whose elements are the orbits of the flags of a
+    /// complete graph. This is a polyhedron whenever the graph comes from one.
+    fn poset(&self) -> Abstract {
+        let (vertices, vertex_count) = self.orbits([1, 2]);
+        let edge_count = self.len() / 4;
+        let (faces, face_count) = self.orbits([0, 1]);
+
+        let mut edge_subs = vec![Vec::new(); edge_count];
+        let mut face_subs = vec![Vec::new(); face_count];
+        for flag in 0..self.len() {
+            edge_subs[flag / 4].push(vertices[flag]);
+            face_subs[faces[flag]].push(flag / 4);
+        }
+
+        let mut builder = AbstractBuilder::new();
+        builder.push_min();
+        builder.push_vertices(vertex_count);
+        for subs in [edge_subs, face_subs] {
+            let mut list = SubelementList::with_capacity(subs.len());
+            for mut sub in subs {
+                sub.sort_unstable();
+                sub.dedup();
+                list.push(Subelements(sub));
+            }
+            builder.push(list);
+        }
+        builder.push_max();
+        builder.build()
+    }
+
+    /// Returns the graphs that result from deciding the first undecided value
+    /// of `r1` in every possible way, in the order they should be explored.
+    /// Those that aren't [canonical](Self::is_canonical) are left out. Returns
+    /// `None` if the graph is already complete.
+    fn children(&self, max_flags: usize) -> Option<Vec<Self>> {
+        let f = self.r1.iter().position(Option::is_none)?;
+        let mut children = Vec::new();
+
+        // Joins the flag to another flag that's already there. A flag can't
+        // be joined to a flag on its own edge, or else there would be
+        // monogons, or vertices on a single edge.
+        for g in (f + 1)..self.len() {
+            if self.r1[g].is_none() && g / 4 != f / 4 {
+                let mut child = self.clone();
+                child.join(f, g);
+                if child.is_canonical() {
+                    children.push(child);
+                }
+            }
+        }
+
+        // Joins the flag to a new square. By symmetry, we only need to try out
+        // one of its flags.
+        if self.len() + 4 <= max_flags {
+            let mut child = self.clone();
+            let g = child.len();
+            child.push_square();
+            child.join(f, g);
+            if child.is_canonical() {
+                children.push(child);
+            }
+        }
+
+        Some(children)
+    }
+}
+
+/// The state of the search for polyhedra, which can be saved and resumed.
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+struct Enumeration {
+    /// The maximum number of flags of the polyhedra.
+    max_flags: usize,
+
+    /// The graphs that are yet to be completed, the last of which gets
+    /// explored first.
+    frontier: Vec<FlagGraph>,
+
+    /// The canonical forms of the flag graphs that have been found.
+    seen: HashSet<Vec<usize>>,
+
+    /// The flag graphs of the polyhedra that have been found.
+    polyhedra: Vec<FlagGraph>,
+}
+
+impl Enumeration {
+    /// Starts a search for the polyhedra with at most a given number of flags.
+    fn new(max_flags: usize) -> Self {
+        let mut frontier = Vec::new();
+        if max_flags >= 4 {
+            let mut graph = FlagGraph { r1: Vec::new() };
+            graph.push_square();
+            frontier.push(graph);
+        }
+
+        Self {
+            max_flags,
+            frontier,
+            seen: HashSet::new(),
+            polyhedra: Vec::new(),
+        }
+    }
+
+    /// Adds the polyhedron with a given flag graph, unless it isn't valid or
+    /// it's already been found.
+    fn add(&mut self, graph: FlagGraph) {
+        if self.seen.insert(graph.canonical_form()) && graph.poset().is_valid().is_ok() {
+            self.polyhedra.push(graph);
+        }
+    }
+
+    /// Explores the next graph in the frontier. Returns `false` if the search
+    /// is over.
+    fn step(&mut self) -> bool {
+        let graph = match self.frontier.pop() {
+            Some(graph) => graph,
+            None => return false,
+        };
+
+        match graph.children(self.max_flags) {
+            Some(children) => self.frontier.extend(children.into_iter().rev()),
+            None => self.add(graph),
+        }
+
+        true
+    }
+
+    /// Returns the polyhedra that have been found, sorted by their number of
+    /// flags.
+    fn polyhedra(&self) -> Vec<Abstract> {
+        let mut polyhedra: Vec<_> = self.polyhedra.iter().collect();
+        polyhedra.sort_by_key(|graph| graph.len());
+        polyhedra.into_iter().map(FlagGraph::poset).collect()
+    }
+}
+
+impl Abstract {
+    /// Returns every abstract polyhedron with at most a given number of flags,
+    /// up to isomorphism. These are sorted by their number of flags.
+    ///
+    /// The number of flag graphs that have to be tried out grows very quickly,
+    /// so this is only practical for up to about 24 flags, which is where the
+    /// tetrahedron, the hemicube, and the hemioctahedron first show up.
+    pub fn enumerate_polyhedra(max_flags: usize) -> Vec<Self> {
+        let mut search = Enumeration::new(max_flags);
+        while search.step() {}
+        search.polyhedra()
+    }
+
+    /// Returns every abstract polyhedron with at most a given number of flags,
+    /// just as [`Self::enumerate_polyhedra`], but saves the progress of the
+    /// search to a checkpoint every so many graphs explored. If the checkpoint
+    /// already holds the progress of a search with the same number of flags,
+    /// it resumes from there.
+    #[cfg(feature = "serde-serialize")]
+    pub fn enumerate_polyhedra_with_checkpoint(
+        max_flags: usize,
+        checkpoint: &mut Checkpoint,
+    ) -> io::Result<Vec<Self>> {
+        let mut search = match checkpoint.load::<Enumeration>()? {
+            Some(search) if search.max_flags == max_flags => search,
+            Some(_) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "checkpoint is for a different number of flags",
+                ))
+            }
+            None => Enumeration::new(max_flags),
+        };
+
+        while search.step() {
+            checkpoint.step(&search)?;
+        }
+
+        checkpoint.save(&search)?;
+        Ok(search.polyhedra())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Polytope;
+
+    /// Returns the element counts of a polyhedron, from the vertices to the
+    /// faces.
+    fn counts(poly: &Abstract) -> Vec<usize> {
+        poly.el_counts().into_iter().skip(1).take(3).collect()
+    }
+
+    /// Checks the polyhedra with the fewest flags.
+    #[test]
+    fn smallest() {
+        let polyhedra: Vec<_> = Abstract::enumerate_polyhedra(12)
+            .iter()
+            .map(counts)
+            .collect();
+
+        // The digonal dihedron, then the trigonal hosohedron and dihedron.
+        assert_eq!(polyhedra[0], vec![2, 2, 2]);
+        let mut rest = polyhedra[1..].to_vec();
+        rest.sort();
+        assert_eq!(rest, vec![vec![2, 3, 3], vec![3, 3, 2]]);
+    }
+
+    /// Checks that the polyhedra found are valid, and that they come in dual
+    /// pairs.
+    #[test]
+    fn duals() {
+        let mut polyhedra = Abstract::enumerate_polyhedra(16);
+        assert_eq!(polyhedra.len(), 6);
+
+        let mut el_counts = Vec::new();
+        let mut dual_el_counts = Vec::new();
+        for poly in polyhedra.iter_mut() {
+            poly.is_valid().unwrap();
+            poly.abs_sort();
+            assert!(poly.flags().count() <= 16);
+
+            el_counts.push(counts(poly));
+            let mut dual = counts(poly);
+            dual.reverse();
+            dual_el_counts.push(dual);
+        }
+
+        el_counts.sort();
+        dual_el_counts.sort();
+        assert_eq!(el_counts, dual_el_counts);
+    }
+
+    /// Checks that partial graphs that aren't canonical are pruned, so that no
+    /// two of the complete graphs that are reached are isomorphic.
+    #[test]
+    fn pruning() {
+        let mut search = Enumeration::new(16);
+        let mut complete = 0;
+
+        while let Some(graph) = search.frontier.last() {
+            if graph.children(16).is_none() {
+                complete += 1;
+            }
+            search.step();
+        }
+
+        assert_eq!(complete, search.seen.len());
+    }
+
+    /// Checks that an interrupted search finds the same polyhedra once it's
+    /// resumed.
+    #[test]
+    #[cfg(feature = "serde-serialize")]
+    fn checkpoint() {
+        let path = std::env::temp_dir().join("miratope_enumerate_checkpoint_test.ron");
+        let mut checkpoint = Checkpoint::new(&path, 1000);
+
+        // Pretends that a previous run stopped midway.
+        let mut search = Enumeration::new(16);
+        for _ in 0..500 {
+            assert!(search.step());
+        }
+        checkpoint.save(&search).unwrap();
+
+        let resumed = Abstract::enumerate_polyhedra_with_checkpoint(16, &mut checkpoint).unwrap();
+        let expected = Abstract::enumerate_polyhedra(16);
+        assert_eq!(
+            resumed.iter().map(counts).collect::<Vec<_>>(),
+            expected.iter().map(counts).collect::<Vec<_>>()
+        );
+
+        // A search with a different number of flags can't be resumed.
+        assert!(Abstract::enumerate_polyhedra_with_checkpoint(12, &mut checkpoint).is_err());
+        checkpoint.clear().unwrap();
+    }
+}
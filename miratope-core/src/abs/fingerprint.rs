@@ -0,0 +1,366 @@
+//! Computes fingerprints of the combinatorial types of polytopes, and uses
+//! them to recognize well-known polytopes.
+//!
+//! A fingerprint is found by repeatedly recoloring every element according to
+//! the colors of its subelements and superelements, until no more elements
+//! can be told apart, and then hashing the colors that remain. Isomorphic
+//! polytopes always have the same fingerprint, whatever the order of their
+//! elements. Polytopes that aren't isomorphic could in principle share a
+//! fingerprint, but this doesn't happen for any of the polytopes in
+//! [`KNOWN_POLYTOPES`].
+
+use std::collections::HashSet;
+
+use super::Abstract;
+use crate::Polytope;
+
+use vec_like::VecLike;
+
+/// The fingerprint of the combinatorial type of a polytope. See the
+/// [module documentation](self) for how these are computed.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Fingerprint(pub u64);
+
+impl std::fmt::Display for Fingerprint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+/// A 64-bit [FNV-1a](https://en.wikipedia.org/wiki/Fowler%E2%80%93Noll%E2%80%93Vo_hash_function)
+/// hasher. Unlike the hasher from the standard library, its output is
+/// guaranteed never to change, so that fingerprints can be stored.
+struct Fnv(u64);
+
+impl Fnv {
+    /// Initializes a new hasher.
+    fn new() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+
+    /// Feeds a number into the hasher.
+    fn write(&mut self, n: u64) {
+        for byte in n.to_le_bytes() {
+            self.0 ^= byte as u64;
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+
+    /// Feeds a multiset of numbers into the hasher, in sorted order.
+    fn write_multiset(&mut self, mut ns: Vec<u64>) {
+        ns.sort_unstable();
+        self.write(ns.len() as u64);
+        for n in ns {
+            self.write(n);
+        }
+    }
+}
+
+impl Abstract {
+    /// Computes the [`Fingerprint`] of the polytope.
+    pub fn fingerprint(&self) -> Fingerprint {
+        // Elements start out colored by their rank.
+        let mut colors: Vec<Vec<u64>> = self
+            .iter()
+            .enumerate()
+            .map(|(r, elements)| vec![r as u64; elements.len()])
+            .collect();
+        let mut color_count = colors.len();
+
+        loop {
+            let mut new_colors = Vec::with_capacity(colors.len());
+            for (r, elements) in self.iter().enumerate() {
+                let new: Vec<u64> = elements
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, el)| {
+                        let mut hasher = Fnv::new();
+                        hasher.write(colors[r][idx]);
+
+                        let subs = match r.checked_sub(1) {
+                            Some(r) => el.subs.iter().map(|&sub| colors[r][sub]).collect(),
+                            None => Vec::new(),
+                        };
+                        hasher.write_multiset(subs);
+
+                        let sups = match colors.get(r + 1) {
+                            Some(sup_colors) => {
+                                el.sups.iter().map(|&sup| sup_colors[sup]).collect()
+                            }
+                            None => Vec::new(),
+                        };
+                        hasher.write_multiset(sups);
+
+                        hasher.0
+                    })
+                    .collect();
+
+                new_colors.push(new);
+            }
+
+            // Each recoloring can only tell more elements apart, so we stop
+            // once the number of colors doesn't change.
+            let new_count = new_colors
+                .iter()
+                .flatten()
+                .copied()
+                .collect::<HashSet<_>>()
+                .len();
+            colors = new_colors;
+            if new_count == color_count {
+                break;
+            }
+            color_count = new_count;
+        }
+
+        let mut hasher = Fnv::new();
+        for rank_colors in colors {
+            hasher.write_multiset(rank_colors);
+        }
+        Fingerprint(hasher.0)
+    }
+
+    /// Returns the well-known polytope with the same combinatorial type as
+    /// this one, if there is any. The fingerprint is only computed when some
+    /// well-known polytope has the same number of vertices, so this is cheap
+    /// for most polytopes.
+    pub fn identify(&self) -> Option<&'static KnownPolytope> {
+        let vertex_count = self.vertex_count();
+        let mut candidates = KNOWN_POLYTOPES
+            .iter()
+            .filter(|known| known.vertex_count == vertex_count)
+            .peekable();
+        candidates.peek()?;
+
+        let fingerprint = self.fingerprint();
+        candidates.find(|known| known.fingerprint == fingerprint)
+    }
+}
+
+/// A well-known polytope, identified by the fingerprint of its combinatorial
+/// type.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KnownPolytope {
+    /// The fingerprint of the polytope.
+    pub fingerprint: Fingerprint,
+
+    /// The number of vertices of the polytope.
+    pub vertex_count: usize,
+
+    /// The name of the polytope.
+    pub name: &'static str,
+
+    /// The [Bowers acronym](https://polytope.miraheze.org/wiki/Bowers_acronym)
+    /// of the polytope.
+    pub acronym: &'static str,
+}
+
+/// Shorthand to declare an entry of [`KNOWN_POLYTOPES`].
+const fn known(
+    fingerprint: u64,
+    vertex_count: usize,
+    name: &'static str,
+    acronym: &'static str,
+) -> KnownPolytope {
+    KnownPolytope {
+        fingerprint: Fingerprint(fingerprint),
+        vertex_count,
+        name,
+        acronym,
+    }
+}
+
+/// The well-known polytopes that can be recognized. Nonconvex polytopes with
+/// the same combinatorial type as some convex polytope, such as the great
+/// icosidodecahedron, get recognized as the convex one.
+pub const KNOWN_POLYTOPES: &[KnownPolytope] = &[
+    // Polytopes of rank at most 2.
+    known(0x2e7ef7c4be27a005, 1, "point", "point"),
+    known(0xb5896e1fafd13076, 2, "dyad", "dyad"),
+    known(0xd64c2cef37e58827, 3, "triangle", "trig"),
+    known(0xf8a95e8cfcd51da2, 4, "square", "square"),
+    known(0xb6d94dda583ad087, 5, "pentagon", "peg"),
+    known(0xd1562e0827c7876e, 6, "hexagon", "hig"),
+    known(0x78271ef8dd37c7bd, 7, "heptagon", "heg"),
+    known(0x3581ae55b50e9736, 8, "octagon", "oc"),
+    known(0xfe6430d886ae95b4, 10, "decagon", "dec"),
+    known(0xfe87c162c57c4f5a, 12, "dodecagon", "dog"),
+    // Platonic solids.
+    known(0x9c8b88eecbaa1454, 4, "tetrahedron", "tet"),
+    known(0x9cc3f463872b2f75, 8, "cube", "cube"),
+    known(0x5256a6ffacaeed1d, 6, "octahedron", "oct"),
+    known(0x5329147d96b58dcc, 20, "dodecahedron", "doe"),
+    known(0xba88b4d84231e51c, 12, "icosahedron", "ike"),
+    // Archimedean solids.
+    known(0x32587b686a20a965, 12, "truncated tetrahedron", "tut"),
+    known(0x798af2b46edc90bb, 24, "truncated cube", "tic"),
+    known(0x41c380d0121dd32e, 24, "truncated octahedron", "toe"),
+    known(0x36bf774d3b571cc9, 60, "truncated dodecahedron", "tid"),
+    known(0xcf46e06dadc7c49e, 60, "truncated icosahedron", "ti"),
+    known(0xae00766f51d81819, 12, "cuboctahedron", "co"),
+    known(0x65ae3df2e213a309, 30, "icosidodecahedron", "id"),
+    known(0xa1e2df6038309e69, 24, "rhombicuboctahedron", "sirco"),
+    known(0x67cb5f886858748d, 60, "rhombicosidodecahedron", "srid"),
+    known(0x36573ec2c833aca2, 48, "truncated cuboctahedron", "girco"),
+    known(
+        0xee9b5d8a11c0e8b3,
+        120,
+        "truncated icosidodecahedron",
+        "grid",
+    ),
+    known(0x6410d095fbc8c7fb, 24, "snub cube", "snic"),
+    known(0x0d78b2b63b44f381, 60, "snub dodecahedron", "snid"),
+    // Prisms and antiprisms.
+    known(0x08e4d452d6443835, 6, "triangular prism", "trip"),
+    known(0x2bdd3ebe179c5a5c, 10, "pentagonal prism", "pip"),
+    known(0x82222cd0f4ed0475, 12, "hexagonal prism", "hip"),
+    known(0xf08c3423c0341f7c, 8, "square antiprism", "squap"),
+    known(0x06017aec59977e01, 10, "pentagonal antiprism", "pap"),
+    // Regular polychora.
+    known(0xcff09c55a5902614, 5, "pentachoron", "pen"),
+    known(0x063d0d1bc086d6ed, 16, "tesseract", "tes"),
+    known(0x9da957bb802d1e00, 8, "hexadecachoron", "hex"),
+    known(0x69607715cf690cda, 24, "icositetrachoron", "ico"),
+    known(0xfb6abd2251f078b2, 600, "hecatonicosachoron", "hi"),
+    known(0x59ba8d4b0afdf279, 120, "hexacosichoron", "ex"),
+    // Regular polytera.
+    known(0x4a4c19bd69116a07, 6, "hexateron", "hix"),
+    known(0x2637dea3425e1604, 32, "penteract", "pent"),
+    known(0x391e76fc1ee9d215, 10, "triacontaditeron", "tac"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        abs::rank::Rank,
+        conc::{file::FromFile, Concrete},
+    };
+
+    /// Returns the name of the well-known polytope that a polytope is
+    /// recognized as.
+    fn identify(poly: &Abstract) -> Option<&'static str> {
+        poly.identify().map(|known| known.name)
+    }
+
+    /// Checks that no two well-known polytopes share a fingerprint.
+    #[test]
+    fn distinct() {
+        let fingerprints: HashSet<_> = KNOWN_POLYTOPES
+            .iter()
+            .map(|known| known.fingerprint)
+            .collect();
+        assert_eq!(fingerprints.len(), KNOWN_POLYTOPES.len());
+    }
+
+    /// Loads a polytope from the library.
+    fn lib(path: &str) -> Abstract {
+        let path = format!("{}/../lib/{}", env!("CARGO_MANIFEST_DIR"), path);
+        Concrete::from_path(&path).unwrap().abs
+    }
+
+    /// Recomputes the vertex count and the fingerprint of every well-known
+    /// polytope from its constructor or from its file in the library.
+    #[test]
+    fn known_polytopes() {
+        let simplex = |r| Abstract::simplex(Rank::new(r));
+        let hypercube = |r| Abstract::hypercube(Rank::new(r));
+        let orthoplex = |r| Abstract::orthoplex(Rank::new(r));
+        let uniform = |path: &str| lib(&format!("3D/uniform/{}.off", path));
+        let regular = |name: &str| lib(&format!("4D/regular/{}.off", name));
+
+        let polytopes = vec![
+            ("point", Abstract::point()),
+            ("dyad", Abstract::dyad()),
+            ("trig", Abstract::polygon(3)),
+            ("square", Abstract::polygon(4)),
+            ("peg", Abstract::polygon(5)),
+            ("hig", Abstract::polygon(6)),
+            ("heg", Abstract::polygon(7)),
+            ("oc", Abstract::polygon(8)),
+            ("dec", Abstract::polygon(10)),
+            ("dog", Abstract::polygon(12)),
+            ("tet", simplex(3)),
+            ("cube", hypercube(3)),
+            ("oct", orthoplex(3)),
+            ("doe", uniform("cat1/convex/doe")),
+            ("ike", uniform("cat1/convex/ike")),
+            ("tut", uniform("cat2/normal/tut")),
+            ("tic", uniform("cat2/normal/tic")),
+            ("toe", uniform("cat2/normal/toe")),
+            ("tid", uniform("cat2/normal/tid")),
+            ("ti", uniform("cat2/normal/ti")),
+            ("co", uniform("cat3/rectates/co")),
+            ("id", uniform("cat3/rectates/id")),
+            ("sirco", uniform("cat4/cantellates/sirco")),
+            ("srid", uniform("cat4/cantellates/srid")),
+            ("girco", uniform("cat5/girco")),
+            ("grid", uniform("cat5/grid")),
+            ("snic", uniform("cat6/snic")),
+            ("snid", uniform("cat6/snid")),
+            ("trip", Abstract::polygon(3).prism()),
+            ("pip", Abstract::polygon(5).prism()),
+            ("hip", Abstract::polygon(6).prism()),
+            ("squap", Abstract::polygon(4).antiprism()),
+            ("pap", Abstract::polygon(5).antiprism()),
+            ("pen", simplex(4)),
+            ("tes", hypercube(4)),
+            ("hex", orthoplex(4)),
+            ("ico", regular("Icositetrachoron")),
+            ("hi", regular("Hecatonicosachoron")),
+            ("ex", regular("Hexacosichoron")),
+            ("hix", simplex(5)),
+            ("pent", hypercube(5)),
+            ("tac", orthoplex(5)),
+        ];
+
+        assert_eq!(polytopes.len(), KNOWN_POLYTOPES.len());
+        for (known, (acronym, mut poly)) in KNOWN_POLYTOPES.iter().zip(polytopes) {
+            assert_eq!(known.acronym, acronym);
+            assert_eq!(poly.vertex_count(), known.vertex_count);
+            poly.abs_sort();
+            assert_eq!(
+                poly.fingerprint(),
+                known.fingerprint,
+                "Wrong fingerprint for the {}.",
+                known.name
+            );
+        }
+    }
+
+    /// Checks that polytopes are recognized however they're built.
+    #[test]
+    fn recognize() {
+        let mut cube = Abstract::hypercube(Rank::new(3));
+        cube.abs_sort();
+        assert_eq!(identify(&cube), Some("cube"));
+        assert_eq!(identify(&cube.dual()), Some("octahedron"));
+        assert_eq!(identify(&cube.truncate(&[1])), Some("cuboctahedron"));
+        assert_eq!(
+            identify(&Abstract::duoprism(
+                &Abstract::polygon(4),
+                &Abstract::polygon(4)
+            )),
+            Some("tesseract")
+        );
+        assert_eq!(identify(&Abstract::polygon(9)), None);
+
+        // The great icosidodecahedron has the same combinatorial type as the
+        // icosidodecahedron.
+        let gid = lib("3D/uniform/cat3/rectates/gid.off");
+        assert_eq!(identify(&gid), Some("icosidodecahedron"));
+    }
+
+    /// Checks that the order of the elements doesn't matter.
+    #[test]
+    fn relabel() {
+        let mut poly = Abstract::polygon(5).antiprism();
+        let fingerprint = poly.fingerprint();
+
+        let rank = Rank::new(1);
+        let count = poly[rank].len();
+        let perm: Vec<_> = (0..count).rev().collect();
+        poly.permute(rank, &perm);
+        assert_eq!(poly.fingerprint(), fingerprint);
+    }
+}
@@ -5,6 +5,8 @@
 //! polytope. For convenience, we omit the minimal and maximal elements from our
 //! flags, though we sometimes pretend like they're still there for convenience.
 
+#[cfg(feature = "serde-serialize")]
+use std::io;
 use std::{
     cmp::Ordering,
     collections::{hash_map::Entry, HashMap, HashSet, VecDeque},
@@ -12,9 +14,23 @@ use std::{
     ops::{Index, IndexMut},
 };
 
-use super::{elements::ElementRef, rank::Rank, Abstract};
+use super::{
+    elements::{AbstractBuilder, ElementRef, SectionRef, SubelementList, Subelements},
+    rank::Rank,
+    Abstract, AbstractError, AbstractResult,
+};
+#[cfg(feature = "serde-serialize")]
+use crate::checkpoint::Checkpoint;
 use crate::{Float, Polytope};
 
+#[cfg(feature = "petgraph")]
+use petgraph::{
+    graph::{Graph, NodeIndex},
+    Undirected,
+};
+#[cfg(feature = "serde-serialize")]
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
 use vec_like::*;
 
 /// Represents a [flag](https://polytope.miraheze.org/wiki/Flag) in a polytope.
@@ -135,7 +151,9 @@ impl Default for Orientation {
 }
 
 /// An iterator over all [`Flags`](Flag) of a polytope. This iterator works even
-/// if the polytope is a compound polytope.
+/// if the polytope is a compound polytope. It won't notice if some section of
+/// the polytope isn't connected, so use
+/// [`Abstract::is_strongly_flag_connected`] to rule that out.
 ///
 /// Each flag is associated with a sequence whose k-th entry stores the index of
 /// the k-th element as a subelement of its superelement. We iterate over flags
@@ -175,6 +193,67 @@ impl<'a> FlagIter<'a> {
     }
 }
 
+impl<'a> FlagIter<'a> {
+    /// Returns the current position of the iterator, which can be stored and
+    /// later passed to [`Self::resume`] to pick up where it left off.
+    pub fn cursor(&self) -> FlagCursor {
+        FlagCursor {
+            indices: self.flag.as_ref().map(|_| self.indices.clone()),
+        }
+    }
+
+    /// Resumes iterating over the flags of a polytope from a position
+    /// returned by [`Self::cursor`]. The polytope must be the same one, sorted
+    /// in the same way.
+    ///
+    /// Returns `None` if the cursor doesn't describe a flag of the polytope.
+    pub fn resume(polytope: &'a Abstract, cursor: FlagCursor) -> Option<Self> {
+        assert!(
+            polytope.sorted,
+            "You must make sure that the polytope is sorted before iterating over its flags."
+        );
+
+        let r = polytope.rank().try_usize().unwrap_or(0);
+        let indices = match cursor.indices {
+            Some(indices) => indices,
+            None => {
+                return Some(Self {
+                    polytope,
+                    flag: None,
+                    indices: vec![0; r],
+                })
+            }
+        };
+        if indices.len() != r || polytope.rank() == Rank::new(-1) {
+            return None;
+        }
+
+        // Each element of the flag is found from the one above it.
+        let mut flag = vec![0; r];
+        let mut element = &polytope[polytope.rank()][0];
+        for rank in (0..r).rev() {
+            flag[rank] = *element.subs.get(indices[rank])?;
+            element = &polytope[Rank::from(rank)][flag[rank]];
+        }
+
+        Some(Self {
+            polytope,
+            flag: Some(flag.into()),
+            indices,
+        })
+    }
+
+    /// Returns an iterator over the flags in chunks of a given size. The
+    /// last chunk might be smaller.
+    ///
+    /// # Panics
+    /// This method will panic if the chunk size is zero.
+    pub fn by_chunks(self, size: usize) -> FlagChunks<'a> {
+        assert_ne!(size, 0, "Chunks must be nonempty.");
+        FlagChunks { iter: self, size }
+    }
+}
+
 impl<'a> Iterator for FlagIter<'a> {
     type Item = Flag;
 
@@ -224,6 +303,55 @@ impl<'a> Iterator for FlagIter<'a> {
     }
 }
 
+/// The position of a [`FlagIter`], which can be serialized to resume a long
+/// iteration later on, possibly in another session.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub struct FlagCursor {
+    /// The indices of each element of the next flag as subelements of their
+    /// superelements, or `None` if the iteration is over. These determine the
+    /// flag.
+    indices: Option<Vec<usize>>,
+}
+
+impl FlagCursor {
+    /// Returns whether the iteration is over.
+    pub fn is_done(&self) -> bool {
+        self.indices.is_none()
+    }
+}
+
+/// An iterator over the [`Flags`](Flag) of a polytope in chunks, returned by
+/// [`FlagIter::by_chunks`]. Between chunks, its [cursor](Self::cursor) can be
+/// stored so that the iteration can be resumed.
+pub struct FlagChunks<'a> {
+    /// The underlying iterator.
+    iter: FlagIter<'a>,
+
+    /// The number of flags in each chunk.
+    size: usize,
+}
+
+impl<'a> FlagChunks<'a> {
+    /// Returns the position of the iterator after the last chunk.
+    pub fn cursor(&self) -> FlagCursor {
+        self.iter.cursor()
+    }
+}
+
+impl<'a> Iterator for FlagChunks<'a> {
+    type Item = Vec<Flag>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let chunk: Vec<_> = self.iter.by_ref().take(self.size).collect();
+        if chunk.is_empty() {
+            None
+        } else {
+            Some(chunk)
+        }
+    }
+}
+
 #[derive(Clone, Default, Eq)]
 /// A flag together with an orientation. Any flag change flips the orientation.
 /// If the polytope associated to the flag is non-orientable, the orientation
@@ -707,14 +835,19 @@ impl FlagSet {
     }
 
     /// Returns the set of all flag sets obtained from this one after removing
-    /// exactly one element.
+    /// exactly one element. These are always returned in the same order.
     pub fn subsets(&self, polytope: &Abstract) -> Vec<Self> {
         let mut subsets = Vec::new();
 
+        // The flags are stored in a hash set, whose order changes between
+        // runs.
+        let mut sorted_flags: Vec<_> = self.flags.iter().collect();
+        sorted_flags.sort_unstable();
+
         for flag_changes in self.flag_changes.subsets() {
             let mut flags = HashSet::new();
 
-            for flag in &self.flags {
+            for &flag in &sorted_flags {
                 if flags.insert(flag.clone()) {
                     let subset = Self::with_flags(&polytope, flag_changes.clone(), flag.clone());
 
@@ -731,6 +864,308 @@ impl FlagSet {
     }
 }
 
+/// Lists the flags of a polytope, and records the flag that each of them
+/// changes into under each flag change. This allows flags to be handled
+/// through their indices.
+pub struct FlagTable {
+    /// The flags of the polytope, in the order of a [`FlagIter`].
+    pub flags: Vec<Flag>,
+
+    /// The index of the flag that each flag changes into, for each flag
+    /// change.
+    changes: Vec<Vec<usize>>,
+}
+
+impl FlagTable {
+    /// Builds the flag table of a polytope.
+    ///
+    /// # Panics
+    /// This method will panic if the polytope isn't sorted.
+    pub fn new(polytope: &Abstract) -> Self {
+        trace_span!("flag_table", rank = polytope.rank().into_isize());
+        let flags: Vec<Flag> = polytope.flags().collect();
+        let rank = polytope.rank().try_usize().unwrap_or(0);
+        trace_event!(flags = flags.len(), "enumerated flags");
+
+        let flag_idx: HashMap<&Flag, usize> = flags
+            .iter()
+            .enumerate()
+            .map(|(idx, flag)| (flag, idx))
+            .collect();
+        let changes = flags
+            .iter()
+            .map(|flag| {
+                (0..rank)
+                    .map(|r| flag_idx[&flag.change(polytope, r)])
+                    .collect()
+            })
+            .collect();
+
+        Self { flags, changes }
+    }
+
+    /// Returns the number of flags of the polytope.
+    pub fn len(&self) -> usize {
+        self.flags.len()
+    }
+
+    /// Returns whether the polytope has no flags.
+    pub fn is_empty(&self) -> bool {
+        self.flags.is_empty()
+    }
+
+    /// Returns the index of the flag that a flag changes into under the flag
+    /// change of a given rank.
+    pub fn change(&self, idx: usize, r: usize) -> usize {
+        self.changes[idx][r]
+    }
+
+    /// Returns the flag changes of every flag, indexed first by flag and then
+    /// by rank.
+    pub fn changes(&self) -> &[Vec<usize>] {
+        &self.changes
+    }
+
+    /// Returns the flag graph of the polytope, whose nodes are its flags, with
+    /// an edge labeled *r* joining any two flags that differ by the flag
+    /// change of rank *r*. Each node has the same index as its flag on the
+    /// table.
+    #[cfg(feature = "petgraph")]
+    pub fn graph(&self) -> Graph<Flag, usize, Undirected> {
+        let rank = self.changes.first().map_or(0, Vec::len);
+        let mut graph = Graph::with_capacity(self.len(), self.len() * rank / 2);
+
+        for flag in &self.flags {
+            graph.add_node(flag.clone());
+        }
+
+        for (idx, changes) in self.changes.iter().enumerate() {
+            for (r, &other) in changes.iter().enumerate() {
+                if idx < other {
+                    graph.add_edge(NodeIndex::new(idx), NodeIndex::new(other), r);
+                }
+            }
+        }
+
+        graph
+    }
+}
+
+/// Labels every flag by its orbit under the flag changes of every rank other
+/// than a given one, and returns the labels together with the number of
+/// orbits.
+fn orbits(changes: &[Vec<usize>], rank: usize) -> (Vec<usize>, usize) {
+    let mut labels = vec![usize::MAX; changes.len()];
+    let mut count = 0;
+
+    for start in 0..changes.len() {
+        if labels[start] != usize::MAX {
+            continue;
+        }
+
+        labels[start] = count;
+        let mut stack = vec![start];
+        while let Some(flag) = stack.pop() {
+            for (r, &next) in changes[flag].iter().enumerate() {
+                if r != rank && labels[next] == usize::MAX {
+                    labels[next] = count;
+                    stack.push(next);
+                }
+            }
+        }
+
+        count += 1;
+    }
+
+    (labels, count)
+}
+
+impl Abstract {
+    /// Builds the structure whose flags have a given list of flag changes,
+    /// which are given as for a [`FlagTable`]. Its elements of rank *r* are
+    /// the orbits of the flags under the flag changes of every rank other than
+    /// *r*, and two elements of consecutive ranks are incident when their
+    /// orbits share a flag.
+    ///
+    /// The result won't be a valid polytope unless the flag changes satisfy
+    /// the intersection property, which should be checked afterwards.
+    ///
+    /// # Panics
+    /// This method will panic if some flag doesn't have one flag change for
+    /// each rank from 0 up to `rank - 1`.
+    pub fn from_flag_changes(rank: Rank, changes: &[Vec<usize>]) -> Self {
+        let n = match rank.try_usize() {
+            None => return Self::nullitope(),
+            Some(0) => return Self::point(),
+            Some(n) => n,
+        };
+        assert!(
+            changes.iter().all(|flag_changes| flag_changes.len() == n),
+            "Every flag must have a flag change for each rank."
+        );
+
+        // The elements of each rank.
+        let labels: Vec<_> = (0..n).map(|r| orbits(changes, r)).collect();
+        let mut builder = AbstractBuilder::with_capacity(rank);
+        builder.push_min();
+        builder.push_vertices(labels[0].1);
+
+        // An element contains the elements of the rank below whose orbits share
+        // a flag with it.
+        for r in 1..n {
+            let (upper, count) = &labels[r];
+            let lower = &labels[r - 1].0;
+            let mut subelements = vec![Vec::new(); *count];
+            for (flag, &label) in upper.iter().enumerate() {
+                subelements[label].push(lower[flag]);
+            }
+
+            let mut list = SubelementList::with_capacity(*count);
+            for mut subs in subelements {
+                subs.sort_unstable();
+                subs.dedup();
+                list.push(Subelements(subs));
+            }
+            builder.push(list);
+        }
+
+        builder.push_max();
+        builder.build()
+    }
+
+    /// Folds a function over the flags of the polytope, saving the progress to
+    /// a checkpoint every so often. If the checkpoint already holds some
+    /// progress, the iteration resumes from there and the initial value is
+    /// ignored. The result also gets saved, so running this again with the
+    /// same checkpoint returns it right away.
+    ///
+    /// # Panics
+    /// This method will panic if the polytope isn't sorted.
+    #[cfg(feature = "serde-serialize")]
+    pub fn fold_flags_with_checkpoint<S, F>(
+        &self,
+        checkpoint: &mut Checkpoint,
+        init: S,
+        mut f: F,
+    ) -> io::Result<S>
+    where
+        S: Serialize + DeserializeOwned,
+        F: FnMut(S, Flag) -> S,
+    {
+        let (cursor, mut acc) = match checkpoint.load::<(FlagCursor, S)>()? {
+            Some(state) => state,
+            None => (self.flags().cursor(), init),
+        };
+        let mut iter = FlagIter::resume(self, cursor).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                "checkpoint doesn't match the polytope",
+            )
+        })?;
+
+        while let Some(flag) = iter.next() {
+            acc = f(acc, flag);
+            checkpoint.step(&(iter.cursor(), &acc))?;
+        }
+
+        checkpoint.save(&(iter.cursor(), &acc))?;
+        Ok(acc)
+    }
+
+    /// Checks whether every section of the polytope, including the polytope
+    /// itself, is flag-connected, meaning that any two of its flags can be
+    /// joined by a sequence of flag changes within the section. Unlike
+    /// [`Self::is_strongly_connected`], this works directly on the flags
+    /// rather than on the graphs of vertices and edges.
+    ///
+    /// The polytope is first checked to be bounded, consistent and dyadic, as
+    /// flag changes don't make sense otherwise. Sections are then checked by
+    /// increasing rank, so that the first section reported doesn't contain
+    /// any smaller disconnected section.
+    pub fn is_strongly_flag_connected(&self) -> AbstractResult<()> {
+        self.bounded()?;
+        self.check_incidences()?;
+        self.is_dyadic()?;
+
+        let n = match self.rank().try_usize() {
+            Some(n) if n >= 2 => n,
+            _ => return Ok(()),
+        };
+
+        let sorted;
+        let poly = if self.sorted {
+            self
+        } else {
+            let mut clone = self.clone();
+            clone.abs_sort();
+            sorted = clone;
+            &sorted
+        };
+        let table = FlagTable::new(poly);
+
+        // The index of the element of a given rank in a flag, where the
+        // minimal and maximal elements are included.
+        let element =
+            |flag: &Flag, r: isize| ElementRef::new(Rank::new(r), flag.get_or_zero(Rank::new(r)));
+
+        // A section between elements of ranks lo and hi is connected when the
+        // flags through both of them that coincide outside of the section
+        // belong to a single orbit under the flag changes of the ranks
+        // strictly between them.
+        for height in 3..=(n as isize + 1) {
+            for lo in -1..=(n as isize - height) {
+                let hi = lo + height;
+                let mut labels = vec![usize::MAX; table.len()];
+                let mut orbits: HashMap<Vec<usize>, usize> = HashMap::new();
+
+                for start in 0..table.len() {
+                    if labels[start] != usize::MAX {
+                        continue;
+                    }
+
+                    let flag = &table.flags[start];
+                    let outside: Vec<_> = (0..n as isize)
+                        .filter(|&r| r <= lo || r >= hi)
+                        .map(|r| flag[r as usize])
+                        .collect();
+
+                    // We've already found another orbit through this section.
+                    if orbits.insert(outside, start).is_some() {
+                        return Err(AbstractError::Connected(SectionRef::new(
+                            element(flag, lo),
+                            element(flag, hi),
+                        )));
+                    }
+
+                    labels[start] = start;
+                    let mut stack = vec![start];
+                    while let Some(idx) = stack.pop() {
+                        for r in (lo + 1)..hi {
+                            let next = table.change(idx, r as usize);
+                            if labels[next] == usize::MAX {
+                                labels[next] = start;
+                                stack.push(next);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the flag graph of the polytope. See [`FlagTable::graph`] for
+    /// more details.
+    ///
+    /// # Panics
+    /// This method will panic if the polytope isn't sorted.
+    #[cfg(feature = "petgraph")]
+    pub fn flag_graph(&self) -> Graph<Flag, usize, Undirected> {
+        FlagTable::new(self).graph()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -753,6 +1188,13 @@ mod tests {
             "Expected {} oriented flags, found {}.",
             expected, flag_count
         );
+
+        let flag_count = polytope.flag_count();
+        assert_eq!(
+            expected, flag_count,
+            "Expected {} counted flags, found {}.",
+            expected, flag_count
+        );
     }
 
     #[test]
@@ -806,4 +1248,140 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    #[cfg(feature = "petgraph")]
+    fn flag_graph() {
+        let mut cube = Abstract::hypercube(Rank::new(3));
+        cube.abs_sort();
+        let graph = cube.flag_graph();
+
+        assert_eq!(graph.node_count(), 48);
+        assert_eq!(graph.edge_count(), 72);
+        assert_eq!(petgraph::algo::connected_components(&graph), 1);
+        for r in 0..3 {
+            assert_eq!(graph.edge_indices().filter(|&e| graph[e] == r).count(), 24);
+        }
+
+        // Adjacent flags differ by the flag change of the edge's label.
+        for edge in graph.edge_indices() {
+            let (a, b) = graph.edge_endpoints(edge).unwrap();
+            assert!(graph[a].change(&cube, graph[edge]) == graph[b]);
+        }
+
+        // The flags of a compound split into one component for each polytope.
+        let mut compound = cube.clone();
+        compound.comp_append(Abstract::simplex(Rank::new(3)));
+        compound.abs_sort();
+        let graph = compound.flag_graph();
+        assert_eq!(graph.node_count(), 48 + 24);
+        assert_eq!(petgraph::algo::connected_components(&graph), 2);
+    }
+
+    #[test]
+    #[cfg(feature = "serde-serialize")]
+    fn resume() {
+        let mut tesseract = Abstract::hypercube(Rank::new(4));
+        tesseract.abs_sort();
+        let flags: Vec<_> = tesseract.flags().collect();
+
+        // Stops every few chunks and resumes from a serialized cursor.
+        let mut resumed = Vec::new();
+        let mut cursor = tesseract.flags().cursor();
+        while !cursor.is_done() {
+            let mut chunks = FlagIter::resume(&tesseract, cursor).unwrap().by_chunks(50);
+            for chunk in chunks.by_ref().take(3) {
+                assert!(chunk.len() <= 50);
+                resumed.extend(chunk);
+            }
+
+            let ron = ron::to_string(&chunks.cursor()).unwrap();
+            cursor = ron::from_str(&ron).unwrap();
+        }
+        assert!(flags == resumed);
+
+        // A cursor from another polytope might not fit.
+        let mut cube = Abstract::hypercube(Rank::new(3));
+        cube.abs_sort();
+        assert!(FlagIter::resume(&cube, tesseract.flags().cursor()).is_none());
+    }
+
+    #[test]
+    #[cfg(feature = "serde-serialize")]
+    fn checkpoint() {
+        let mut tesseract = Abstract::hypercube(Rank::new(4));
+        tesseract.abs_sort();
+        let path = std::env::temp_dir().join("miratope_flag_checkpoint_test.ron");
+        let mut checkpoint = Checkpoint::new(&path, 50);
+
+        // Pretends that a previous run stopped after 100 flags.
+        let mut flags = tesseract.flags();
+        flags.by_ref().take(100).for_each(drop);
+        checkpoint.save(&(flags.cursor(), 100usize)).unwrap();
+
+        let count = |checkpoint: &mut Checkpoint| {
+            tesseract
+                .fold_flags_with_checkpoint(checkpoint, 0usize, |count, _| count + 1)
+                .unwrap()
+        };
+        assert_eq!(count(&mut checkpoint), 384);
+
+        // The result is stored once the iteration is over.
+        assert_eq!(count(&mut checkpoint), 384);
+        checkpoint.clear().unwrap();
+        assert_eq!(count(&mut checkpoint), 384);
+        checkpoint.clear().unwrap();
+    }
+
+    #[test]
+    fn flag_count() {
+        // Counting flags doesn't need the polytope to be sorted.
+        let cube = Abstract::hypercube(Rank::new(3));
+        assert_eq!(cube.flag_count(), 48);
+
+        let mut compound = cube;
+        compound.comp_append(Abstract::simplex(Rank::new(3)));
+        assert_eq!(compound.flag_count(), 48 + 24);
+
+        // A flag count that would take a while to enumerate.
+        let octeract = Abstract::hypercube(Rank::new(8));
+        assert_eq!(octeract.flag_count(), 10_321_920);
+    }
+
+    #[test]
+    fn strong_flag_connectivity() {
+        use crate::conc::{file::FromFile, Concrete};
+
+        for rank in -1..5 {
+            Abstract::hypercube(Rank::new(rank))
+                .is_strongly_flag_connected()
+                .unwrap();
+        }
+        Abstract::polygon(5).is_strongly_flag_connected().unwrap();
+
+        // A compound is only disconnected as a whole.
+        let mut compound = Abstract::hypercube(Rank::new(3));
+        compound.comp_append(Abstract::simplex(Rank::new(3)));
+        let min = ElementRef::new(Rank::new(-1), 0);
+        let max = ElementRef::new(Rank::new(3), 0);
+        assert!(matches!(
+            compound.is_strongly_flag_connected(),
+            Err(AbstractError::Connected(section)) if section == SectionRef::new(min, max)
+        ));
+
+        // Two tetrahedra sharing a vertex, whose vertex figure is disconnected.
+        // This is found before the disconnected polytope itself.
+        let tets = Concrete::from_off(
+            "OFF 7 8 0
+            0 0 0  1 0 0  0 1 0  0 0 1  -1 0 0  0 -1 0  0 0 -1
+            3 0 1 2  3 0 1 3  3 0 2 3  3 1 2 3
+            3 0 4 5  3 0 4 6  3 0 5 6  3 4 5 6",
+        )
+        .unwrap();
+        let vertex = ElementRef::new(Rank::new(0), 0);
+        assert!(matches!(
+            tets.abs.is_strongly_flag_connected(),
+            Err(AbstractError::Connected(section)) if section == SectionRef::new(vertex, max)
+        ));
+    }
 }
@@ -0,0 +1,119 @@
+//! Computes the [flag vector](https://en.wikipedia.org/wiki/Flag_(geometry))
+//! of a polytope, which counts its chains of elements of every given set of
+//! ranks.
+//!
+//! Rather than enumerating flags, we count chains rank by rank: the number of
+//! chains ending at some element is the sum of the numbers of chains ending at
+//! the elements of the previous rank in the chain that lie below it.
+
+use super::{rank::Rank, Abstract};
+use crate::Polytope;
+
+use vec_like::VecLike;
+
+impl Abstract {
+    /// Returns, for every element of rank `b` and every rank `a` below it, the
+    /// indices of the elements of rank `a` that lie below it. These are
+    /// indexed as `below[b][a][idx]`, for ranks starting at 0.
+    fn elements_below(&self, rank: usize) -> Vec<Vec<Vec<Vec<usize>>>> {
+        let mut below: Vec<Vec<Vec<Vec<usize>>>> = Vec::with_capacity(rank);
+
+        for b in 0..rank {
+            let elements = &self[Rank::from(b)];
+
+            // The elements below those of the previous rank are found through
+            // the subelements.
+            let mut lists: Vec<Vec<Vec<usize>>> = below
+                .last()
+                .map(|prev: &Vec<Vec<Vec<usize>>>| {
+                    prev.iter()
+                        .map(|prev_list| {
+                            elements
+                                .iter()
+                                .map(|el| {
+                                    let mut els: Vec<_> = el
+                                        .subs
+                                        .iter()
+                                        .flat_map(|&s| prev_list[s].iter().copied())
+                                        .collect();
+                                    els.sort_unstable();
+                                    els.dedup();
+                                    els
+                                })
+                                .collect()
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            if b > 0 {
+                lists.push(elements.iter().map(|el| el.subs.0.clone()).collect());
+            }
+            below.push(lists);
+        }
+
+        below
+    }
+
+    /// Returns the flag vector of the polytope. Its entry with index `s`
+    /// counts the chains of proper elements whose ranks are those `r` for
+    /// which the bit `1 << r` is set in `s`. For instance, the first entry is
+    /// always 1, the entry with index `1 << r` is the number of elements of
+    /// rank `r`, and the last entry is the number of flags.
+    ///
+    /// The flag vector of a polytope of rank *n* has 2<sup>*n*</sup> entries,
+    /// so this is only practical for polytopes of small rank.
+    pub fn flag_vector(&self) -> Vec<usize> {
+        let rank = self.rank().try_usize().unwrap_or(0);
+        let below = self.elements_below(rank);
+        let mut flag_vector = vec![0; 1 << rank];
+        flag_vector[0] = 1;
+
+        // Every chain is extended by elements of higher ranks, starting from
+        // the chains with a single element.
+        let mut stack: Vec<(usize, usize, Vec<usize>)> = (0..rank)
+            .map(|r| (1 << r, r, vec![1; self.el_count(Rank::from(r))]))
+            .collect();
+
+        while let Some((set, top, counts)) = stack.pop() {
+            flag_vector[set] = counts.iter().sum();
+
+            for (b, below_b) in below.iter().enumerate().skip(top + 1) {
+                let new_counts = below_b[top]
+                    .iter()
+                    .map(|els| els.iter().map(|&x| counts[x]).sum())
+                    .collect();
+                stack.push((set | 1 << b, b, new_counts));
+            }
+        }
+
+        flag_vector
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn polygon() {
+        assert_eq!(Abstract::polygon(5).flag_vector(), vec![1, 5, 5, 10]);
+        assert_eq!(Abstract::point().flag_vector(), vec![1]);
+    }
+
+    #[test]
+    fn cube() {
+        let cube = Abstract::hypercube(Rank::new(3));
+        assert_eq!(cube.flag_vector(), vec![1, 8, 12, 24, 6, 24, 24, 48]);
+
+        // The flag vector of a prism over a tetrahedron.
+        let mut prism = Abstract::simplex(Rank::new(3)).prism();
+        prism.abs_sort();
+        let flag_vector = prism.flag_vector();
+        assert_eq!(flag_vector[0b1111], prism.flags().count());
+
+        // Every vertex lies on a tetrahedron and three triangular prisms.
+        assert_eq!(flag_vector[0b1001], 8 * 4);
+        // The vertices on the triangles, plus those on the squares.
+        assert_eq!(flag_vector[0b0101], 8 * 3 + 6 * 4);
+    }
+}
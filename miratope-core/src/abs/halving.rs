@@ -0,0 +1,236 @@
+//! Implements the [halving](https://polytope.miraheze.org/wiki/Alternation)
+//! operation on abstract polytopes whose vertices and edges form a bipartite
+//! graph, which keeps every other vertex. For instance, this turns a cube into
+//! a tetrahedron, and more generally a hypercube into a demihypercube.
+
+use std::collections::HashMap;
+
+use super::{
+    elements::{AbstractBuilder, SubelementList, Subelements},
+    rank::Rank,
+    Abstract,
+};
+use crate::Polytope;
+
+use vec_like::VecLike;
+
+impl Abstract {
+    /// Returns the sorted vertex indices of every element of rank at least 0,
+    /// indexed first by rank and then by element.
+    fn vertex_lists(&self) -> Vec<Vec<Vec<usize>>> {
+        let mut lists: Vec<Vec<Vec<usize>>> =
+            vec![(0..self.vertex_count()).map(|v| vec![v]).collect()];
+
+        for r in 1..=self.rank().into_usize() {
+            let lower = &lists[r - 1];
+            let list = self[Rank::from(r)]
+                .iter()
+                .map(|el| {
+                    let mut vertices: Vec<_> = el
+                        .subs
+                        .iter()
+                        .flat_map(|&s| lower[s].iter().copied())
+                        .collect();
+                    vertices.sort_unstable();
+                    vertices.dedup();
+                    vertices
+                })
+                .collect();
+            lists.push(list);
+        }
+
+        lists
+    }
+
+    /// Colors the vertices of the polytope with two colors so that no edge
+    /// joins two vertices of the same color, and returns whether each vertex
+    /// has the color of the first vertex in its connected component. Returns
+    /// `None` if the edges don't form a bipartite graph.
+    pub fn bipartition(&self) -> Option<Vec<bool>> {
+        let vertex_count = self.vertex_count();
+        let mut neighbors = vec![Vec::new(); vertex_count];
+        if self.rank() >= Rank::new(1) {
+            for edge in self[Rank::new(1)].iter() {
+                if let [v, w] = edge.subs.0[..] {
+                    neighbors[v].push(w);
+                    neighbors[w].push(v);
+                }
+            }
+        }
+
+        let mut colors: Vec<Option<bool>> = vec![None; vertex_count];
+        for start in 0..vertex_count {
+            if colors[start].is_some() {
+                continue;
+            }
+
+            colors[start] = Some(true);
+            let mut stack = vec![start];
+            while let Some(v) = stack.pop() {
+                let color = colors[v].unwrap();
+                for &w in &neighbors[v] {
+                    match colors[w] {
+                        Some(other) if other == color => return None,
+                        Some(_) => {}
+                        None => {
+                            colors[w] = Some(!color);
+                            stack.push(w);
+                        }
+                    }
+                }
+            }
+        }
+
+        colors.into_iter().collect()
+    }
+
+    /// Builds the half of the polytope, whose vertices are those of one of
+    /// the colors of the [bipartition](Self::bipartition) of its vertices. Its
+    /// elements come in two kinds: the halves of the elements of rank at
+    /// least 2, made out of their kept vertices, and the elements made out of
+    /// the neighbors of a removed vertex within an element of rank at least 2
+    /// through it. These are then ordered by the inclusion of their vertices.
+    ///
+    /// Halving a cube gives a tetrahedron, while halving a tesseract gives a
+    /// 16-cell. The halves of squares become edges, so halving a square gives
+    /// a dyad. Returns `None` if the edges of the polytope don't form a
+    /// bipartite graph, or if the result isn't a valid polytope.
+    pub fn halve(&self) -> Option<Self> {
+        if self.rank() < Rank::new(2) {
+            return None;
+        }
+
+        let kept = self.bipartition()?;
+        let lists = self.vertex_lists();
+
+        // The new index of every kept vertex, and the neighbors of every
+        // removed vertex.
+        let mut new_idx = vec![None; kept.len()];
+        let mut vertex_count = 0;
+        for (v, &keep) in kept.iter().enumerate() {
+            if keep {
+                new_idx[v] = Some(vertex_count);
+                vertex_count += 1;
+            }
+        }
+        let mut neighbors = vec![Vec::new(); kept.len()];
+        for edge in &lists[1] {
+            for (v, w) in [(edge[0], edge[1]), (edge[1], edge[0])] {
+                if !kept[v] {
+                    neighbors[v].push(w);
+                }
+            }
+        }
+
+        // The vertex sets of the new elements.
+        let mut sets = Vec::new();
+        for list in &lists[2..] {
+            for vertices in list {
+                sets.push(
+                    vertices
+                        .iter()
+                        .filter_map(|&v| new_idx[v])
+                        .collect::<Vec<_>>(),
+                );
+
+                for &v in vertices.iter().filter(|&&v| !kept[v]) {
+                    let mut set: Vec<_> = neighbors[v]
+                        .iter()
+                        .filter(|w| vertices.binary_search(w).is_ok())
+                        .map(|&w| new_idx[w].unwrap())
+                        .collect();
+                    set.sort_unstable();
+                    sets.push(set);
+                }
+            }
+        }
+        sets.retain(|set| set.len() >= 2);
+        sets.sort_unstable_by(|a, b| a.len().cmp(&b.len()).then_with(|| a.cmp(b)));
+        sets.dedup();
+
+        // Each set is ranked by the longest chain of sets below it, and its
+        // subelements are the sets of the rank below that it contains.
+        let contains =
+            |big: &[usize], small: &[usize]| small.iter().all(|v| big.binary_search(v).is_ok());
+        let mut ranks = Vec::with_capacity(sets.len());
+        for (i, set) in sets.iter().enumerate() {
+            let rank = (0..i)
+                .filter(|&j| sets[j].len() < set.len() && contains(set, &sets[j]))
+                .map(|j| ranks[j] + 1)
+                .max()
+                .unwrap_or(1);
+            ranks.push(rank);
+        }
+
+        let rank_count = *ranks.iter().max()?;
+        let mut indices: HashMap<usize, usize> = HashMap::new();
+        let mut rank_lists: Vec<_> = (0..rank_count).map(|_| SubelementList::new()).collect();
+        for (i, set) in sets.iter().enumerate() {
+            let rank = ranks[i];
+            let subs = if rank == 1 {
+                set.clone()
+            } else {
+                (0..i)
+                    .filter(|&j| ranks[j] + 1 == rank && contains(set, &sets[j]))
+                    .map(|j| indices[&j])
+                    .collect()
+            };
+
+            indices.insert(i, rank_lists[rank - 1].len());
+            rank_lists[rank - 1].push(Subelements(subs));
+        }
+
+        let mut builder = AbstractBuilder::with_capacity(Rank::from(rank_count));
+        builder.push_min();
+        builder.push_vertices(vertex_count);
+        for list in rank_lists {
+            builder.push(list);
+        }
+
+        let half = builder.build();
+        half.is_valid().ok().map(|_| half)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hypercubes() {
+        let cube = Abstract::hypercube(Rank::new(3));
+        let mut tet = cube.halve().unwrap();
+        assert_eq!(tet.el_counts(), vec![1, 4, 6, 4, 1].into());
+        let mut simplex = Abstract::simplex(Rank::new(3));
+        tet.abs_sort();
+        simplex.abs_sort();
+        assert_eq!(tet.is_isomorphic(&simplex), Some(true));
+
+        // The half of a tesseract is a 16-cell.
+        let hex = Abstract::hypercube(Rank::new(4)).halve().unwrap();
+        assert_eq!(hex.el_counts(), vec![1, 8, 24, 32, 16, 1].into());
+
+        // The half of a 5-cube has ten 16-cells and sixteen 5-cells as facets.
+        let demipenteract = Abstract::hypercube(Rank::new(5)).halve().unwrap();
+        assert_eq!(
+            demipenteract.el_counts(),
+            vec![1, 16, 80, 160, 120, 26, 1].into()
+        );
+    }
+
+    #[test]
+    fn polygons() {
+        assert_eq!(
+            Abstract::polygon(4).halve().unwrap().el_counts(),
+            vec![1, 2, 1].into()
+        );
+        assert_eq!(
+            Abstract::polygon(6).halve().unwrap().el_counts(),
+            vec![1, 3, 3, 1].into()
+        );
+
+        // Odd polygons aren't bipartite.
+        assert!(Abstract::polygon(5).bipartition().is_none());
+        assert!(Abstract::polygon(5).halve().is_none());
+    }
+}
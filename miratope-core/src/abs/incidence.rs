@@ -0,0 +1,174 @@
+//! Converts abstract polytopes into incidence matrices and back, so that
+//! incidence data from external sources can be loaded directly.
+//!
+//! A polytope of rank *n* is described by *n* + 1 incidence matrices, one
+//! for every pair of consecutive ranks, from the vertices against the minimal
+//! element up to the maximal element against the facets. The rows of the
+//! matrix for rank *r* correspond to the elements of rank *r*, its columns
+//! correspond to the elements of rank *r* − 1, and every entry records whether
+//! these elements are incident.
+
+use super::{
+    elements::{AbstractBuilder, SubelementList, Subelements},
+    rank::Rank,
+    Abstract, AbstractError,
+};
+use crate::Polytope;
+
+use vec_like::VecLike;
+
+/// A matrix recording the incidences between the elements of two
+/// consecutive ranks, stored as a list of rows.
+pub type IncidenceMatrix = Vec<Vec<bool>>;
+
+/// Any error encountered while building a polytope from incidence matrices.
+#[derive(Debug)]
+pub enum IncidenceError {
+    /// A row of an incidence matrix doesn't have as many entries as there are
+    /// elements in the rank below.
+    Size {
+        /// The rank of the elements indexing the rows of the matrix.
+        rank: Rank,
+
+        /// The number of elements in the rank below.
+        expected: usize,
+
+        /// The number of entries found in the row.
+        found: usize,
+    },
+
+    /// The incidence matrices don't describe a valid polytope.
+    Invalid(AbstractError),
+}
+
+impl std::fmt::Display for IncidenceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Size {
+                rank,
+                expected,
+                found,
+            } => write!(
+                f,
+                "incidence matrix for rank {} has a row with {} entries, expected {}",
+                rank, found, expected
+            ),
+            Self::Invalid(err) => write!(f, "invalid polytope: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for IncidenceError {}
+
+impl From<AbstractError> for IncidenceError {
+    fn from(err: AbstractError) -> Self {
+        Self::Invalid(err)
+    }
+}
+
+/// The result of building a polytope from incidence matrices.
+pub type IncidenceResult<T> = Result<T, IncidenceError>;
+
+impl Abstract {
+    /// Returns the incidence matrices of the polytope, from the one between
+    /// the vertices and the minimal element up to the one between the maximal
+    /// element and the facets.
+    pub fn to_incidence_matrices(&self) -> Vec<IncidenceMatrix> {
+        Rank::range_inclusive_iter(0, self.rank())
+            .map(|r| {
+                let lower_count = self.el_count(r.minus_one());
+                self[r]
+                    .iter()
+                    .map(|el| {
+                        let mut row = vec![false; lower_count];
+                        for &sub in &el.subs {
+                            row[sub] = true;
+                        }
+                        row
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Builds a polytope from its incidence matrices, given in the order
+    /// returned by [`Self::to_incidence_matrices`], and checks that it's
+    /// valid. An empty list of matrices gives the nullitope.
+    pub fn from_incidence(matrices: &[IncidenceMatrix]) -> IncidenceResult<Self> {
+        let mut builder = AbstractBuilder::with_capacity(Rank::from(matrices.len()).minus_one());
+        builder.push_min();
+
+        let mut lower_count = 1;
+        for (r, matrix) in matrices.iter().enumerate() {
+            let mut subs_list = SubelementList::with_capacity(matrix.len());
+            for row in matrix {
+                if row.len() != lower_count {
+                    return Err(IncidenceError::Size {
+                        rank: Rank::from(r),
+                        expected: lower_count,
+                        found: row.len(),
+                    });
+                }
+
+                subs_list.push(Subelements(
+                    row.iter()
+                        .enumerate()
+                        .filter(|(_, &incident)| incident)
+                        .map(|(idx, _)| idx)
+                        .collect(),
+                ));
+            }
+
+            lower_count = matrix.len();
+            builder.push(subs_list);
+        }
+
+        let poly = builder.build();
+        poly.is_valid()?;
+        Ok(poly)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let mut cube = Abstract::hypercube(Rank::new(3));
+        let matrices = cube.to_incidence_matrices();
+        let sizes: Vec<_> = matrices.iter().map(|m| (m.len(), m[0].len())).collect();
+        assert_eq!(sizes, vec![(8, 1), (12, 8), (6, 12), (1, 6)]);
+
+        let mut new_cube = Abstract::from_incidence(&matrices).unwrap();
+        cube.abs_sort();
+        new_cube.abs_sort();
+        assert_eq!(cube.is_isomorphic(&new_cube), Some(true));
+
+        assert_eq!(Abstract::from_incidence(&[]).unwrap().rank(), Rank::new(-1));
+    }
+
+    #[test]
+    fn invalid() {
+        let mut matrices = Abstract::polygon(4).to_incidence_matrices();
+        matrices[1][0].pop();
+        assert!(matches!(
+            Abstract::from_incidence(&matrices),
+            Err(IncidenceError::Size {
+                expected: 4,
+                found: 3,
+                ..
+            })
+        ));
+
+        // An edge with a single vertex.
+        let mut matrices = Abstract::polygon(4).to_incidence_matrices();
+        let row = &mut matrices[1][0];
+        let vertex = row.iter().position(|&incident| incident).unwrap();
+        row[vertex] = false;
+        assert!(matches!(
+            Abstract::from_incidence(&matrices),
+            Err(IncidenceError::Invalid(_))
+        ));
+    }
+}
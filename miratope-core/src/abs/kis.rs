@@ -0,0 +1,160 @@
+//! Builds the [kis](https://en.wikipedia.org/wiki/Conway_polyhedron_notation)
+//! of a polytope, which raises a pyramid over each of its facets at once.
+//!
+//! The proper elements of the kis are the elements of the original polytope
+//! other than its facets, together with a pyramid over every element of every
+//! facet, the nullitope included. The apex of the pyramids over the elements
+//! of a facet is a new vertex, and the pyramids over its ridges become the new
+//! facets.
+
+use std::collections::HashMap;
+
+use super::{
+    elements::{AbstractBuilder, SubelementList, Subelements},
+    rank::Rank,
+    Abstract,
+};
+use crate::Polytope;
+
+use vec_like::VecLike;
+
+impl Abstract {
+    /// Returns the elements of each rank under a given facet, from the minimal
+    /// element up to the facet, sorted by index.
+    fn facet_closure(&self, facet: usize) -> Vec<Vec<usize>> {
+        let facet_rank = self.rank().minus_one();
+        let mut closure = vec![vec![facet]];
+
+        for r in Rank::range_inclusive_iter(0, facet_rank).rev() {
+            let mut subs: Vec<usize> = closure
+                .last()
+                .unwrap()
+                .iter()
+                .flat_map(|&idx| self[r][idx].subs.iter().copied())
+                .collect();
+            subs.sort_unstable();
+            subs.dedup();
+            closure.push(subs);
+        }
+
+        closure.reverse();
+        closure
+    }
+
+    /// Returns the kis of the polytope, which raises a pyramid over every
+    /// facet. The new vertices come after the original ones, in the same order
+    /// as the facets they lie over. Polytopes of rank at most 0 have no
+    /// proper facets, so they're returned unchanged.
+    pub fn kis(&self) -> Self {
+        let rank = match self.rank().try_usize() {
+            Some(rank) if rank >= 1 => rank,
+            _ => return self.clone(),
+        };
+        let facet_count = self.facet_count();
+
+        // The elements of each facet, indexed by their rank plus one.
+        let closures: Vec<_> = (0..facet_count)
+            .map(|facet| self.facet_closure(facet))
+            .collect();
+
+        let mut builder = AbstractBuilder::with_capacity(self.rank());
+        builder.push_min();
+
+        // The indices of the pyramids over the elements of each facet of the
+        // previous rank.
+        let mut prev_pyramids: Vec<HashMap<usize, usize>> = Vec::new();
+
+        for r in 0..rank {
+            let new_rank = Rank::from(r);
+
+            // The elements of the original polytope, other than the facets.
+            let mut list = if r + 1 < rank {
+                SubelementList::from(
+                    self[new_rank]
+                        .iter()
+                        .map(|el| el.subs.clone())
+                        .collect::<Vec<_>>(),
+                )
+            } else {
+                SubelementList::new()
+            };
+
+            // The pyramids over the elements of rank r - 1 of each facet.
+            let mut pyramids = Vec::with_capacity(facet_count);
+            for (facet, closure) in closures.iter().enumerate() {
+                let mut indices = HashMap::new();
+
+                for &base in &closure[r] {
+                    indices.insert(base, list.len());
+
+                    let subs = if r == 0 {
+                        Subelements::new()
+                    } else {
+                        let base_subs = &self[Rank::from(r).minus_one()][base].subs;
+                        let mut subs = Vec::with_capacity(base_subs.len() + 1);
+                        subs.push(base);
+                        subs.extend(base_subs.iter().map(|sub| prev_pyramids[facet][sub]));
+                        Subelements(subs)
+                    };
+
+                    list.push(subs);
+                }
+
+                pyramids.push(indices);
+            }
+
+            if r == 0 {
+                builder.push_vertices(list.len());
+            } else {
+                builder.push(list);
+            }
+            prev_pyramids = pyramids;
+        }
+
+        builder.push_max();
+        builder.build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abs::rank::RankVec;
+
+    /// Checks the element counts of the kis of a polytope, and its validity.
+    fn test<T: Into<RankVec<usize>>>(poly: &Abstract, el_counts: T) {
+        let kis = poly.kis();
+        assert_eq!(kis.el_counts(), el_counts.into());
+        kis.is_valid().unwrap();
+    }
+
+    #[test]
+    fn small() {
+        test(&Abstract::nullitope(), vec![1]);
+        test(&Abstract::point(), vec![1, 1]);
+        test(&Abstract::dyad(), vec![1, 2, 1]);
+    }
+
+    #[test]
+    fn polygons() {
+        for n in 2..8 {
+            test(&Abstract::polygon(n), vec![1, 2 * n, 2 * n, 1]);
+        }
+    }
+
+    /// The kis of a cube is the tetrakis hexahedron, and the kis of a tesseract
+    /// has a pyramid over each of the faces of each cube.
+    #[test]
+    fn hypercubes() {
+        test(&Abstract::hypercube(Rank::new(3)), vec![1, 14, 36, 24, 1]);
+        test(
+            &Abstract::hypercube(Rank::new(4)),
+            vec![1, 24, 96, 120, 48, 1],
+        );
+    }
+
+    #[test]
+    fn simplex() {
+        test(&Abstract::simplex(Rank::new(3)), vec![1, 8, 18, 12, 1]);
+    }
+}
@@ -0,0 +1,130 @@
+//! Computes meets and joins of elements of a polytope, seen as a partially
+//! ordered set.
+//!
+//! The meet of two elements is their greatest common subelement, and their
+//! join is their least common superelement. These always exist in a
+//! [lattice](https://en.wikipedia.org/wiki/Lattice_(order)), such as the face
+//! lattice of a convex polytope, but might not in a general polytope.
+
+use std::collections::HashSet;
+
+use super::{elements::ElementRef, rank::Rank, Abstract};
+
+impl Abstract {
+    /// Returns all elements that are incident to a given element and lie
+    /// below it, or above it if `up` is set, including the element itself.
+    /// Returns `None` if the element doesn't exist.
+    fn closure(&self, el: ElementRef, up: bool) -> Option<HashSet<ElementRef>> {
+        self.get_element(el)?;
+
+        let mut closure = HashSet::new();
+        closure.insert(el);
+        let mut stack = vec![el];
+
+        while let Some(el) = stack.pop() {
+            let element = &self[el.rank][el.idx];
+            let (rank, indices) = if up {
+                (el.rank.plus_one(), &element.sups.0)
+            } else if let Some(rank) = el.rank.try_sub(Rank::new(1)) {
+                (rank, &element.subs.0)
+            } else {
+                continue;
+            };
+
+            for &idx in indices {
+                let next = ElementRef::new(rank, idx);
+                if closure.insert(next) {
+                    stack.push(next);
+                }
+            }
+        }
+
+        Some(closure)
+    }
+
+    /// Returns the common lower bound of two elements that lies above all
+    /// others, or the common upper bound that lies below all others if `up`
+    /// is set, if it exists.
+    fn bound(&self, el1: ElementRef, el2: ElementRef, up: bool) -> Option<ElementRef> {
+        let closure1 = self.closure(el1, up)?;
+        let closure2 = self.closure(el2, up)?;
+        let common: Vec<_> = closure1.intersection(&closure2).copied().collect();
+
+        // The candidates are the common bounds closest to the elements.
+        let rank = if up {
+            common.iter().map(|el| el.rank).min()?
+        } else {
+            common.iter().map(|el| el.rank).max()?
+        };
+        let mut candidates = common.iter().filter(|el| el.rank == rank);
+        let candidate = *candidates.next()?;
+        if candidates.next().is_some() {
+            return None;
+        }
+
+        // Every other common bound must be incident to the candidate.
+        let closure = self.closure(candidate, up)?;
+        if common.iter().all(|el| closure.contains(el)) {
+            Some(candidate)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the meet of two elements, i.e. the greatest element that's
+    /// incident to and below both. Returns `None` if there's no such element,
+    /// or if either element doesn't exist.
+    pub fn meet(&self, el1: ElementRef, el2: ElementRef) -> Option<ElementRef> {
+        self.bound(el1, el2, false)
+    }
+
+    /// Returns the join of two elements, i.e. the least element that's
+    /// incident to and above both. Returns `None` if there's no such element,
+    /// or if either element doesn't exist.
+    pub fn join(&self, el1: ElementRef, el2: ElementRef) -> Option<ElementRef> {
+        self.bound(el1, el2, true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Polytope;
+
+    /// Shorthand for an element reference.
+    fn el(rank: isize, idx: usize) -> ElementRef {
+        ElementRef::new(Rank::new(rank), idx)
+    }
+
+    #[test]
+    fn cube() {
+        let cube = Abstract::hypercube(Rank::new(3));
+        let edge = &cube[Rank::new(1)][0];
+        let (v0, v1) = (edge.subs[0], edge.subs[1]);
+
+        // The vertices of an edge.
+        assert_eq!(cube.join(el(0, v0), el(0, v1)), Some(el(1, 0)));
+        assert_eq!(cube.meet(el(0, v0), el(0, v1)), Some(el(-1, 0)));
+
+        // The two faces through an edge.
+        let (f0, f1) = (edge.sups[0], edge.sups[1]);
+        assert_eq!(cube.meet(el(2, f0), el(2, f1)), Some(el(1, 0)));
+        assert_eq!(cube.join(el(2, f0), el(2, f1)), Some(el(3, 0)));
+
+        // Comparable elements.
+        assert_eq!(cube.meet(el(0, v0), el(2, f0)), Some(el(0, v0)));
+        assert_eq!(cube.join(el(0, v0), el(2, f0)), Some(el(2, f0)));
+        assert_eq!(cube.join(el(1, 0), el(1, 0)), Some(el(1, 0)));
+
+        // Nonexistent elements.
+        assert_eq!(cube.meet(el(0, 8), el(0, 0)), None);
+    }
+
+    /// The two vertices of a digon lie on two edges.
+    #[test]
+    fn digon() {
+        let digon = Abstract::polygon(2);
+        assert_eq!(digon.join(el(0, 0), el(0, 1)), None);
+        assert_eq!(digon.meet(el(1, 0), el(1, 1)), None);
+    }
+}
@@ -0,0 +1,107 @@
+//! Builds the [mix](https://polytope.miraheze.org/wiki/Mix) of two abstract
+//! polytopes.
+//!
+//! The flags of the mix of two polytopes of rank *n* are the pairs of flags
+//! of both that can be reached from the pair of their base flags by applying
+//! the same flag changes to both. The mix is then built out of these using
+//! [`Abstract::from_flag_changes`].
+
+use std::collections::HashMap;
+
+use super::{flag::FlagTable, Abstract};
+use crate::Polytope;
+
+impl Abstract {
+    /// Builds the mix of two polytopes of the same rank. This is the smallest
+    /// polytope that covers both of them, in the sense that its flags map
+    /// onto the flags of both while commuting with flag changes. For
+    /// instance, the mix of a square and a hexagon is a dodecagon, and the
+    /// mix of a polytope with itself is the polytope again.
+    ///
+    /// Only the flags connected to the base flags are taken into account, so
+    /// the mix of two compounds only includes one component of each. The
+    /// result is guaranteed to be a polytope only when the polytopes are flag
+    /// connected and their mix satisfies the intersection property, which
+    /// holds for instance for polygons.
+    ///
+    /// # Panics
+    /// This method will panic if the polytopes don't have the same rank, or if
+    /// they aren't sorted.
+    pub fn mix(&self, other: &Self) -> Self {
+        let rank = self.rank();
+        assert_eq!(
+            rank,
+            other.rank(),
+            "Can't mix polytopes of different ranks."
+        );
+
+        let n = match rank.try_usize() {
+            None => return Self::nullitope(),
+            Some(0) => return Self::point(),
+            Some(n) => n,
+        };
+
+        // Finds the flags of the mix, along with their flag changes.
+        let flags_p = FlagTable::new(self);
+        let flags_q = FlagTable::new(other);
+        let mut indices = HashMap::new();
+        let mut pairs = vec![(0, 0)];
+        let mut changes: Vec<Vec<usize>> = Vec::new();
+        indices.insert((0, 0), 0);
+
+        while changes.len() < pairs.len() {
+            let (p, q) = pairs[changes.len()];
+            let flag_changes = (0..n)
+                .map(|r| {
+                    let next = (flags_p.change(p, r), flags_q.change(q, r));
+                    let len = pairs.len();
+                    let idx = *indices.entry(next).or_insert(len);
+                    if idx == len {
+                        pairs.push(next);
+                    }
+                    idx
+                })
+                .collect();
+            changes.push(flag_changes);
+        }
+
+        Self::from_flag_changes(rank, &changes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abs::rank::Rank;
+
+    use vec_like::VecLike;
+
+    #[test]
+    fn polygons() {
+        for (p, q, lcm) in [(4, 6, 12), (3, 5, 15), (6, 6, 6)] {
+            let mut mix = Abstract::polygon(p).mix(&Abstract::polygon(q));
+            mix.abs_sort();
+            assert_eq!(mix.is_isomorphic(&Abstract::polygon(lcm)), Some(true));
+        }
+    }
+
+    #[test]
+    fn mix() {
+        let mut cube = Abstract::hypercube(Rank::new(3));
+        cube.abs_sort();
+        let mut mix = cube.mix(&cube);
+        mix.abs_sort();
+        assert_eq!(mix.is_isomorphic(&cube), Some(true));
+
+        // The mix of a cube and an octahedron is regular, and its faces and
+        // vertex figures are dodecagons.
+        let mut oct = Abstract::orthoplex(Rank::new(3));
+        oct.abs_sort();
+        let mut mix = cube.mix(&oct);
+        mix.abs_sort();
+        mix.is_valid().unwrap();
+        assert_eq!(mix.is_regular(), Some(true));
+        assert_eq!(mix[Rank::new(2)][0].subs.len(), 12);
+        assert_eq!(mix[Rank::new(0)][0].sups.len(), 12);
+    }
+}
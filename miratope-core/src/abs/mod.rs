@@ -1,8 +1,28 @@
 //! Declares the [`Abstract`] polytope type and all associated data structures.
 
+pub mod automorphism;
+pub mod boundary;
+pub mod chamfer;
+pub mod complex;
+pub mod connected_sum;
 pub mod elements;
+pub mod enumerate;
+pub mod fingerprint;
 pub mod flag;
+pub mod flag_vector;
+pub mod halving;
+pub mod incidence;
+pub mod kis;
+pub mod lattice;
+pub mod mix;
+pub mod order;
+pub mod random;
 pub mod rank;
+pub mod sections;
+pub mod shelling;
+pub mod skeleton;
+pub mod truncate;
+pub mod wilson;
 
 use std::collections::{BTreeSet, HashMap, HashSet};
 
@@ -14,9 +34,14 @@ use self::{
     flag::{Flag, FlagSet},
     rank::{Rank, RankVec},
 };
+#[cfg(feature = "serde-serialize")]
+use super::versioned::Versioned;
 use super::{DualResult, Polytope};
 
+#[cfg(feature = "parallel")]
 use rayon::prelude::*;
+#[cfg(feature = "serde-serialize")]
+use serde::{Deserialize, Serialize};
 use strum_macros::Display;
 use vec_like::VecLike;
 
@@ -73,6 +98,25 @@ pub enum AbstractError {
         index: usize,
     },
 
+    /// Some element lists the same subelement more than once.
+    Repeated {
+        /// The coordinates of the element at fault.
+        el: ElementRef,
+
+        /// The repeated index.
+        index: usize,
+    },
+
+    /// Some element's subelements or superelements aren't sorted, even though
+    /// the polytope claims to be.
+    Sorted {
+        /// The coordinates of the element at fault.
+        el: ElementRef,
+
+        /// Whether the unsorted list is of subelements or superelements.
+        incidence_type: IncidenceType,
+    },
+
     /// The polytope is not ranked, i.e. some element that's not minimal or not
     /// maximal lacks a subelement or superelement, respectively.
     Ranked {
@@ -132,6 +176,20 @@ impl std::fmt::Display for AbstractError {
                 el, incidence_type, index
             ),
 
+            // Some element has a repeated subelement.
+            AbstractError::Repeated { el, index } => write!(
+                f,
+                "Polytope has a repeated index: {} has the subelement with index {} more than once",
+                el, index
+            ),
+
+            // Some element's incidences are unsorted.
+            AbstractError::Sorted { el, incidence_type } => write!(
+                f,
+                "Polytope is not sorted: {} has unsorted {}s",
+                el, incidence_type
+            ),
+
             // The polytope is not ranked.
             AbstractError::Ranked { el, incidence_type } => write!(
                 f,
@@ -220,6 +278,12 @@ pub type AbstractResult<T> = Result<T, AbstractError>;
 /// [`Abstract::push_subs`] method, which will push a list of subelements and
 /// automatically set the superelements of the previous rank, under the
 /// assumption that they're empty.
+///
+/// # Serialization
+/// An `Abstract` is serialized as its [`RankVec`] of element lists. When
+/// deserializing, the polytope is checked to be sorted, bounded, and to have
+/// matching subelements and superelements, so that no malformed input can
+/// build a polytope that crashes later on.
 #[derive(Debug, Default, Clone)]
 pub struct Abstract {
     /// The list of element lists in the polytope, ordered by [`Rank`].
@@ -256,6 +320,52 @@ impl From<Vec<ElementList>> for Abstract {
     }
 }
 
+/// Serializes an [`Abstract`] as its element lists, which get sorted first if
+/// they aren't already, so that they can always be deserialized.
+#[cfg(feature = "serde-serialize")]
+impl Serialize for Abstract {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        if self.sorted {
+            self.ranks.serialize(serializer)
+        } else {
+            let mut abs = self.clone();
+            abs.abs_sort();
+            abs.ranks.serialize(serializer)
+        }
+    }
+}
+
+/// Deserializes an [`Abstract`] from its element lists, as long as these are
+/// sorted, bounded, and their incidences match up.
+#[cfg(feature = "serde-serialize")]
+impl<'de> Deserialize<'de> for Abstract {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let abs = Self {
+            ranks: RankVec::deserialize(deserializer)?,
+            sorted: true,
+        };
+
+        abs.check_sorted()
+            .and_then(|_| abs.bounded())
+            .and_then(|_| abs.check_incidences())
+            .map_err(serde::de::Error::custom)?;
+        Ok(abs)
+    }
+}
+
+/// The element lists of an [`Abstract`] have been stored the same way since
+/// they were first serialized.
+#[cfg(feature = "serde-serialize")]
+impl Versioned for Abstract {
+    const VERSION: u32 = 1;
+}
+
 impl VecLike for Abstract {
     type VecItem = ElementList;
     type VecIndex = Rank;
@@ -384,6 +494,50 @@ impl Abstract {
         self.ranks.get_mut(el.rank)?.get_mut(el.idx)
     }
 
+    /// Reorders the elements of a given rank, so that the element with index
+    /// `i` gets moved to index `perm[i]`. The subelements and superelements of
+    /// the adjacent ranks are updated accordingly.
+    ///
+    /// # Panics
+    /// Panics if `perm` isn't a permutation of the indices of the elements of
+    /// the given rank.
+    pub fn permute(&mut self, rank: Rank, perm: &[usize]) {
+        let len = self[rank].len();
+        assert_eq!(len, perm.len(), "permutation has the wrong length");
+
+        // Moves every element into its new position.
+        let mut new_elements = vec![None; len];
+        for (el, &new_idx) in std::mem::take(&mut self[rank]).into_iter().zip(perm) {
+            new_elements[new_idx] = Some(el);
+        }
+
+        self[rank] = new_elements
+            .into_iter()
+            .map(|el| el.expect("invalid permutation"))
+            .collect::<Vec<_>>()
+            .into();
+
+        // Updates the superelements of the elements of the previous rank.
+        if let Some(prev_rank) = rank.try_minus_one() {
+            for el in self[prev_rank].iter_mut() {
+                for sup in el.sups.iter_mut() {
+                    *sup = perm[*sup];
+                }
+            }
+        }
+
+        // Updates the subelements of the elements of the next rank.
+        if rank < self.rank() {
+            for el in self[rank.plus_one()].iter_mut() {
+                for sub in el.subs.iter_mut() {
+                    *sub = perm[*sub];
+                }
+            }
+        }
+
+        self.sorted = false;
+    }
+
     /// Gets the indices of the vertices of an element in the polytope, if it
     /// exists.
     pub fn element_vertices(&self, el: ElementRef) -> Option<Vec<usize>> {
@@ -444,8 +598,18 @@ impl Abstract {
     /// Builds an [antiprism](https://polytope.miraheze.org/wiki/Antiprism)
     /// based on a given polytope. Also returns the indices of the vertices that
     /// form the base and the dual base, in that order.
+    ///
+    /// The elements of the antiprism correspond to the sections of the
+    /// polytope, ordered by reverse inclusion. This means that it can be built
+    /// for any polytope, without needing a dual.
     pub fn antiprism_and_vertices(&self) -> (Self, Vec<usize>, Vec<usize>) {
         let rank = self.rank();
+
+        // The only section of the nullitope gives a point, which belongs to
+        // neither base.
+        if rank == Rank::new(-1) {
+            return (Self::point(), Vec::new(), Vec::new());
+        }
         let mut section_hash = SectionHash::singletons(self);
 
         // We actually build the elements backwards, which is as awkward as it
@@ -538,11 +702,13 @@ impl Abstract {
     }
 
     /// Returns the omnitruncate of a polytope, along with the flags that make
-    /// up its vertices.
+    /// up its vertices. The elements always come out in the same order, even
+    /// when built in parallel.
     ///
     /// # Panics
     /// This method will panic if the polytope isn't sorted.
     pub fn omnitruncate_and_flags(&self) -> (Self, Vec<Flag>) {
+        trace_span!("omnitruncate", rank = self.rank().into_isize());
         let mut flag_sets = vec![FlagSet::new(self)];
         let mut new_flag_sets = Vec::new();
         let rank = self.rank();
@@ -554,12 +720,25 @@ impl Abstract {
         for _ in 0..rank.into() {
             let mut subelements = SubelementList::new();
 
+            // The subsets of each flag set are found on their own, then
+            // merged in the order of the flag sets. This way, the elements
+            // are numbered the same however the work is split between
+            // threads.
+            #[cfg(feature = "parallel")]
+            let flag_set_iter = flag_sets.par_iter();
+            #[cfg(not(feature = "parallel"))]
+            let flag_set_iter = flag_sets.iter();
+
+            let subsets: Vec<_> = flag_set_iter
+                .map(|flag_set| flag_set.subsets(self))
+                .collect();
+
             // Gets the subelements of each element.
-            for flag_set in flag_sets {
+            for flag_set_subsets in subsets {
                 let mut subs = Subelements::new();
 
                 // Each subset represents a new element.
-                for subset in flag_set.subsets(self) {
+                for subset in flag_set_subsets {
                     // We do a brute-force check to see if we've found this
                     // element before.
                     //
@@ -605,18 +784,32 @@ impl Abstract {
             abs.push(subelements);
         }
 
+        trace_event!(flags = flags.len(), "built omnitruncate");
         (abs.build(), flags)
     }
 
     /// Checks whether the polytope is valid, i.e. whether the polytope is
-    /// bounded, dyadic, and all of its indices refer to valid elements.
+    /// bounded, dyadic, and all of its indices refer to valid elements. This
+    /// doesn't check whether any sections are connected, see
+    /// [`Self::validate`] for a stricter check.
     pub fn is_valid(&self) -> AbstractResult<()> {
         self.bounded()?;
         self.check_incidences()?;
-        self.is_dyadic()?;
+        self.is_dyadic()
+    }
 
-        Ok(())
-        // && self.is_strongly_connected()
+    /// Checks whether the polytope is valid in the sense of
+    /// [`Self::is_valid`], and moreover whether it's strongly connected. This
+    /// rules out compounds, as well as polytopes with a disconnected section
+    /// such as a pinched vertex.
+    ///
+    /// Every section gets walked through, so this is much slower than
+    /// [`Self::is_valid`] on large polytopes.
+    pub fn validate(&self) -> AbstractResult<()> {
+        self.bounded()?;
+        self.check_incidences()?;
+        self.is_dyadic()?;
+        self.is_strongly_connected()
     }
 
     /// Determines whether the polytope is bounded, i.e. whether it has a single
@@ -636,80 +829,121 @@ impl Abstract {
         }
     }
 
-    /// Checks whether subelements and superelements match up, and whether they
-    /// all refer to valid elements in the polytope. If this returns `false`,
-    /// then either the polytope hasn't fully built up, or there's something
-    /// seriously wrong.
-    pub fn check_incidences(&self) -> AbstractResult<()> {
-        // Iterates over elements of every rank.
+    /// Checks whether the subelements and superelements of every element are
+    /// sorted, and whether no subelement is listed twice.
+    pub fn check_sorted(&self) -> AbstractResult<()> {
         for (r, elements) in self.ranks.rank_iter().rank_enumerate() {
-            // Iterates over all such elements.
             for (idx, el) in elements.iter().enumerate() {
-                // Only the minimal element can have no subelements.
-                if r != Rank::new(-1) && el.subs.len() == 0 {
-                    return Err(AbstractError::Ranked {
-                        el: ElementRef::new(r, idx),
-                        incidence_type: IncidenceType::Subelement,
-                    });
-                }
+                let el_ref = ElementRef::new(r, idx);
 
-                // Iterates over the element's subelements.
-                for &sub in &el.subs {
-                    // Attempts to get the subelement's superelements.
-                    if let Some(r_minus_one) = r.try_sub(Rank::new(1)) {
-                        if let Some(sub_el) = self.get_element(ElementRef::new(r_minus_one, sub)) {
-                            if sub_el.sups.contains(&idx) {
-                                continue;
-                            } else {
-                                // The element contains a subelement, but not viceversa.
-                                return Err(AbstractError::Consistency {
-                                    el: ElementRef::new(r, idx),
-                                    index: sub,
-                                    incidence_type: IncidenceType::Subelement,
-                                });
-                            }
-                        }
+                for pair in el.subs.0.windows(2) {
+                    if pair[0] == pair[1] {
+                        return Err(AbstractError::Repeated {
+                            el: el_ref,
+                            index: pair[0],
+                        });
+                    } else if pair[0] > pair[1] {
+                        return Err(AbstractError::Sorted {
+                            el: el_ref,
+                            incidence_type: IncidenceType::Subelement,
+                        });
                     }
-
-                    // We got ourselves an invalid index.
-                    return Err(AbstractError::Index {
-                        el: ElementRef::new(r, idx),
-                        index: sub,
-                        incidence_type: IncidenceType::Subelement,
-                    });
                 }
 
-                // Only the maximal element can have no superelements.
-                if r != self.rank() && el.sups.len() == 0 {
-                    return Err(AbstractError::Ranked {
-                        el: ElementRef::new(r, idx),
+                if el.sups.0.windows(2).any(|pair| pair[0] >= pair[1]) {
+                    return Err(AbstractError::Sorted {
+                        el: el_ref,
                         incidence_type: IncidenceType::Superelement,
                     });
                 }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks whether subelements and superelements match up, and whether they
+    /// all refer to valid elements in the polytope. If this returns `false`,
+    /// then either the polytope hasn't fully built up, or there's something
+    /// seriously wrong.
+    pub fn check_incidences(&self) -> AbstractResult<()> {
+        for r in Rank::range_inclusive_iter(-1, self.rank()) {
+            self.check_incidences_at(r)?;
+        }
+
+        Ok(())
+    }
+
+    /// Checks whether the subelements and superelements of the elements of a
+    /// given rank match up, and whether they all refer to valid elements in
+    /// the polytope.
+    pub(crate) fn check_incidences_at(&self, r: Rank) -> AbstractResult<()> {
+        // Iterates over all elements of the rank.
+        for (idx, el) in self[r].iter().enumerate() {
+            // Only the minimal element can have no subelements.
+            if r != Rank::new(-1) && el.subs.len() == 0 {
+                return Err(AbstractError::Ranked {
+                    el: ElementRef::new(r, idx),
+                    incidence_type: IncidenceType::Subelement,
+                });
+            }
 
-                // Iterates over the element's superelements.
-                for &sup in &el.sups {
-                    // Attempts to get the subelement's superelements.
-                    if let Some(sub_el) = self.get_element(ElementRef::new(r.plus_one(), sup)) {
-                        if sub_el.subs.contains(&idx) {
+            // Iterates over the element's subelements.
+            for &sub in &el.subs {
+                // Attempts to get the subelement's superelements.
+                if let Some(r_minus_one) = r.try_sub(Rank::new(1)) {
+                    if let Some(sub_el) = self.get_element(ElementRef::new(r_minus_one, sub)) {
+                        if sub_el.sups.contains(&idx) {
                             continue;
                         } else {
-                            // The element contains a superelement, but not viceversa.
+                            // The element contains a subelement, but not viceversa.
                             return Err(AbstractError::Consistency {
                                 el: ElementRef::new(r, idx),
-                                index: sup,
-                                incidence_type: IncidenceType::Superelement,
+                                index: sub,
+                                incidence_type: IncidenceType::Subelement,
                             });
                         }
                     }
+                }
 
-                    // We got ourselves an invalid index.
-                    return Err(AbstractError::Index {
-                        el: ElementRef::new(r, idx),
-                        index: sup,
-                        incidence_type: IncidenceType::Superelement,
-                    });
+                // We got ourselves an invalid index.
+                return Err(AbstractError::Index {
+                    el: ElementRef::new(r, idx),
+                    index: sub,
+                    incidence_type: IncidenceType::Subelement,
+                });
+            }
+
+            // Only the maximal element can have no superelements.
+            if r != self.rank() && el.sups.len() == 0 {
+                return Err(AbstractError::Ranked {
+                    el: ElementRef::new(r, idx),
+                    incidence_type: IncidenceType::Superelement,
+                });
+            }
+
+            // Iterates over the element's superelements.
+            for &sup in &el.sups {
+                // Attempts to get the subelement's superelements.
+                if let Some(sub_el) = self.get_element(ElementRef::new(r.plus_one(), sup)) {
+                    if sub_el.subs.contains(&idx) {
+                        continue;
+                    } else {
+                        // The element contains a superelement, but not viceversa.
+                        return Err(AbstractError::Consistency {
+                            el: ElementRef::new(r, idx),
+                            index: sup,
+                            incidence_type: IncidenceType::Superelement,
+                        });
+                    }
                 }
+
+                // We got ourselves an invalid index.
+                return Err(AbstractError::Index {
+                    el: ElementRef::new(r, idx),
+                    index: sup,
+                    incidence_type: IncidenceType::Superelement,
+                });
             }
         }
 
@@ -719,6 +953,23 @@ impl Abstract {
     /// Determines whether the polytope satisfies the diamond property. A valid
     /// non-fissary polytope should always return `true`.
     pub fn is_dyadic(&self) -> AbstractResult<()> {
+        self.is_dyadic_up_to(self.rank())
+    }
+
+    /// Determines whether every section of height 1 whose upper element has at
+    /// most a given rank has exactly two middle elements.
+    pub(crate) fn is_dyadic_up_to(&self, rank: Rank) -> AbstractResult<()> {
+        for r in 1..=rank.into_isize() {
+            self.is_dyadic_at(Rank::new(r))?;
+        }
+
+        Ok(())
+    }
+
+    /// Determines whether every section of height 1 whose upper element has a
+    /// given rank has exactly two middle elements. The rank must be at least
+    /// 1.
+    pub(crate) fn is_dyadic_at(&self, r: Rank) -> AbstractResult<()> {
         /// The number of times we've found an element.
         #[derive(PartialEq)]
         enum Count {
@@ -731,69 +982,214 @@ impl Abstract {
 
         // For every element, by looking through the subelements of its
         // subelements, we need to find each exactly twice.
-        for r in 1..self.rank().into_isize() {
-            let r = Rank::new(r);
-
-            for (idx, el) in self[r].iter().enumerate() {
-                let mut hash_sub_subs = HashMap::new();
-
-                for &sub in &el.subs {
-                    let sub_el = &self[r.minus_one()][sub];
-
-                    for &sub_sub in &sub_el.subs {
-                        match hash_sub_subs.get(&sub_sub) {
-                            // Found for the first time.
-                            None => hash_sub_subs.insert(sub_sub, Count::Once),
-
-                            // Found for the second time.
-                            Some(Count::Once) => hash_sub_subs.insert(sub_sub, Count::Twice),
-
-                            // Found for the third time?! Abort!
-                            Some(Count::Twice) => {
-                                return Err(AbstractError::Dyadic {
-                                    section: SectionRef::new(
-                                        ElementRef::new(r - Rank::new(2), sub_sub),
-                                        ElementRef::new(r, idx),
-                                    ),
-                                    more: true,
-                                });
-                            }
-                        };
+        for (idx, el) in self[r].iter().enumerate() {
+            let mut hash_sub_subs = HashMap::new();
+
+            for &sub in &el.subs {
+                let sub_el = &self[r.minus_one()][sub];
+
+                for &sub_sub in &sub_el.subs {
+                    match hash_sub_subs.get(&sub_sub) {
+                        // Found for the first time.
+                        None => hash_sub_subs.insert(sub_sub, Count::Once),
+
+                        // Found for the second time.
+                        Some(Count::Once) => hash_sub_subs.insert(sub_sub, Count::Twice),
+
+                        // Found for the third time?! Abort!
+                        Some(Count::Twice) => {
+                            return Err(AbstractError::Dyadic {
+                                section: SectionRef::new(
+                                    ElementRef::new(r - Rank::new(2), sub_sub),
+                                    ElementRef::new(r, idx),
+                                ),
+                                more: true,
+                            });
+                        }
+                    };
+                }
+            }
+
+            // If any subsubelement was found only once, this also
+            // violates the diamond property.
+            for (sub_sub, count) in hash_sub_subs.into_iter() {
+                if count == Count::Once {
+                    return Err(AbstractError::Dyadic {
+                        section: SectionRef::new(
+                            ElementRef::new(r - Rank::new(2), sub_sub),
+                            ElementRef::new(r, idx),
+                        ),
+                        more: false,
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Determines whether a section of the polytope is connected, i.e.
+    /// whether its proper elements form a connected graph under incidence.
+    /// Sections of rank at most 1 are always connected. Returns `false` if the
+    /// section doesn't exist.
+    pub fn is_connected(&self, section: SectionRef) -> bool {
+        match ElementHash::new(self, section.hi) {
+            Some(hash) => self.section_connected(&hash, section.lo),
+            None => false,
+        }
+    }
+
+    /// Returns the indices of the facets of every component of the polytope,
+    /// i.e. of every maximal set of proper elements that's connected under
+    /// incidence. The facets of each component are sorted, and the components
+    /// are sorted by their first facet. Polytopes of rank at most 1 have a
+    /// single component.
+    pub fn facet_components(&self) -> Vec<Vec<usize>> {
+        let rank = self.rank();
+        if rank < Rank::new(2) {
+            return vec![(0..self.facet_count()).collect()];
+        }
+
+        // The proper elements that have been visited, by rank.
+        let facet_rank = usize::from(rank) - 1;
+        let mut visited: Vec<_> = (0..=facet_rank)
+            .map(|r| vec![false; self[Rank::from(r)].len()])
+            .collect();
+
+        let mut components = Vec::new();
+        for f in 0..visited[facet_rank].len() {
+            if visited[facet_rank][f] {
+                continue;
+            }
+
+            // Traverses the component from this facet.
+            let mut facets = Vec::new();
+            let mut stack = vec![(facet_rank, f)];
+            visited[facet_rank][f] = true;
+            while let Some((r, idx)) = stack.pop() {
+                if r == facet_rank {
+                    facets.push(idx);
+                }
+
+                let el = &self[Rank::from(r)][idx];
+                let subs = el.subs.iter().filter(|_| r > 0).map(|&sub| (r - 1, sub));
+                let sups = el
+                    .sups
+                    .iter()
+                    .filter(|_| r < facet_rank)
+                    .map(|&sup| (r + 1, sup));
+                for (r, idx) in subs.chain(sups) {
+                    if !visited[r][idx] {
+                        visited[r][idx] = true;
+                        stack.push((r, idx));
                     }
                 }
+            }
 
-                // If any subsubelement was found only once, this also
-                // violates the diamond property.
-                for (sub_sub, count) in hash_sub_subs.into_iter() {
-                    if count == Count::Once {
-                        return Err(AbstractError::Dyadic {
-                            section: SectionRef::new(
-                                ElementRef::new(r - Rank::new(2), sub_sub),
-                                ElementRef::new(r, idx),
-                            ),
-                            more: false,
-                        });
+            facets.sort_unstable();
+            components.push(facets);
+        }
+
+        components
+    }
+
+    /// Determines whether the section between an element and the element whose
+    /// [`ElementHash`] is given is connected. The section is connected
+    /// precisely when its vertices and edges form a connected graph, assuming
+    /// that all of its own sections are connected.
+    fn section_connected(&self, hash: &ElementHash, lo: ElementRef) -> bool {
+        if hash.get_idx(lo).is_none() {
+            return false;
+        }
+
+        // Sections of rank at most 1 are always connected.
+        let vertex_rank = lo.rank.plus_one();
+        let edge_rank = vertex_rank.plus_one();
+        let edges = match hash.get(edge_rank.plus_one()) {
+            Some(_) => hash.get(edge_rank).unwrap(),
+            None => return true,
+        };
+
+        // The vertices of the section.
+        let mut vertices = HashMap::new();
+        for &v in hash.get(vertex_rank).unwrap().keys() {
+            if self[vertex_rank][v].subs.contains(&lo.idx) {
+                let len = vertices.len();
+                vertices.insert(v, len);
+            }
+        }
+
+        // The edges of the section, as pairs of vertices.
+        let mut adj = vec![Vec::new(); vertices.len()];
+        for &e in edges.keys() {
+            let ends: Vec<_> = self[edge_rank][e]
+                .subs
+                .iter()
+                .filter_map(|v| vertices.get(v).copied())
+                .collect();
+
+            for &v in &ends {
+                for &w in &ends {
+                    if v != w {
+                        adj[v].push(w);
                     }
                 }
             }
         }
 
+        // Traverses the graph from any vertex.
+        let mut visited = vec![false; vertices.len()];
+        let mut stack = vec![0];
+        let mut count = 0;
+        while let Some(v) = stack.pop() {
+            if !visited[v] {
+                visited[v] = true;
+                count += 1;
+                stack.extend(adj[v].iter().copied());
+            }
+        }
+
+        count == vertices.len()
+    }
+
+    /// Checks that all sections of the polytope are connected. Sections are
+    /// checked from the bottom up, so that the first section that's reported
+    /// doesn't contain another disconnected section.
+    fn check_sections(&self) -> AbstractResult<()> {
+        for r in Rank::range_inclusive_iter(2, self.rank()) {
+            self.check_sections_at(r)?;
+        }
+
         Ok(())
     }
 
-    /// Determines whether the polytope is connected. A valid non-compound
-    /// polytope should always return `true`.
-    pub fn is_connected(&self, _section: SectionRef) -> bool {
-        todo!()
-        /*
-        let section = self.get_section(section).unwrap();
-        section.flags().count() == section.oriented_flags().count() */
+    /// Checks that all sections whose upper element has a given rank are
+    /// connected. The rank must be at least 2.
+    pub(crate) fn check_sections_at(&self, r: Rank) -> AbstractResult<()> {
+        for idx in 0..self.el_count(r) {
+            let hi = ElementRef::new(r, idx);
+            let hash = ElementHash::new(self, hi).unwrap();
+
+            for lo_rank in Rank::range_inclusive_iter(-1, r - Rank::new(3)).rev() {
+                for &lo_idx in hash.get(lo_rank).unwrap().keys() {
+                    let lo = ElementRef::new(lo_rank, lo_idx);
+                    if !self.section_connected(&hash, lo) {
+                        return Err(AbstractError::Connected(SectionRef::new(lo, hi)));
+                    }
+                }
+            }
+        }
+
+        Ok(())
     }
 
-    /// Determines whether the polytope is strongly connected. A valid
-    /// non-compound polytope should always return `true`.
-    pub fn is_strongly_connected(&self) -> bool {
-        todo!()
+    /// Determines whether the polytope is strongly connected, i.e. whether all
+    /// of its sections are connected. A valid non-compound polytope should
+    /// always pass this check.
+    ///
+    /// This is equivalent to the polytope being strongly flag-connected.
+    pub fn is_strongly_connected(&self) -> AbstractResult<()> {
+        self.check_sections()
     }
 
     /// Takes the [direct product](https://en.wikipedia.org/wiki/Direct_product#Direct_product_of_binary_relations)
@@ -804,11 +1200,19 @@ impl Abstract {
     /// The elements of this product are in one to one correspondence to pairs
     /// of elements in the set of polytopes. The elements of a specific rank are
     /// sorted first by lexicographic order of the ranks, then by lexicographic
-    /// order of the elements.
+    /// order of the elements. This order doesn't depend on whether the
+    /// product was built in parallel.
     pub fn product(p: &Self, q: &Self, min: bool, max: bool) -> Self {
         // The ranks of p and q.
         let p_rank = p.rank();
         let q_rank = q.rank();
+        trace_span!(
+            "product",
+            p_rank = p_rank.into_isize(),
+            q_rank = q_rank.into_isize(),
+            min,
+            max
+        );
 
         // The lowest and highest ranks we'll use to take products in p and q.
         let p_low = Rank::new(-(min as isize));
@@ -887,37 +1291,47 @@ impl Abstract {
 
                     // Takes the product of every element in p with rank p_els_rank,
                     // with every element in q with rank q_els_rank.
-                    for (p_idx, p_el) in p[p_els_rank].iter().enumerate() {
-                        for (q_idx, q_el) in q[q_els_rank].iter().enumerate() {
-                            let mut subs = Subelements::new();
-
-                            // Products of p's subelements with q.
-                            if min || p_els_rank != Rank::new(0) {
-                                for &s in &p_el.subs {
-                                    subs.push(get_element_index(
-                                        p_els_rank.minus_one(),
-                                        s,
-                                        q_els_rank,
-                                        q_idx,
-                                    ))
-                                }
+                    let q_count = q.el_count(q_els_rank);
+                    let product_el = |i: usize| {
+                        let (p_idx, q_idx) = (i / q_count, i % q_count);
+                        let mut subs = Subelements::new();
+
+                        // Products of p's subelements with q.
+                        if min || p_els_rank != Rank::new(0) {
+                            for &s in &p[p_els_rank][p_idx].subs {
+                                subs.push(get_element_index(
+                                    p_els_rank.minus_one(),
+                                    s,
+                                    q_els_rank,
+                                    q_idx,
+                                ))
                             }
+                        }
 
-                            // Products of q's subelements with p.
-                            if min || q_els_rank != Rank::new(0) {
-                                for &s in &q_el.subs {
-                                    subs.push(get_element_index(
-                                        p_els_rank,
-                                        p_idx,
-                                        q_els_rank.minus_one(),
-                                        s,
-                                    ))
-                                }
+                        // Products of q's subelements with p.
+                        if min || q_els_rank != Rank::new(0) {
+                            for &s in &q[q_els_rank][q_idx].subs {
+                                subs.push(get_element_index(
+                                    p_els_rank,
+                                    p_idx,
+                                    q_els_rank.minus_one(),
+                                    s,
+                                ))
                             }
-
-                            element_lists[prod_rank].push(subs)
                         }
-                    }
+
+                        subs
+                    };
+
+                    // Each element is built on its own, and they're collected
+                    // by index, so the order is the same however the work is
+                    // split between threads.
+                    let indices = 0..p.el_count(p_els_rank) * q_count;
+                    #[cfg(feature = "parallel")]
+                    let indices = indices.into_par_iter();
+
+                    let els: Vec<_> = indices.map(product_el).collect();
+                    element_lists[prod_rank].append(&mut els.into());
                 }
             }
         }
@@ -940,7 +1354,9 @@ impl Abstract {
             product.push(elements);
         }
 
-        product.build()
+        let product = product.build();
+        trace_event!(elements = ?product.el_counts(), "built product");
+        product
     }
 }
 
@@ -1025,7 +1441,10 @@ impl Polytope for Abstract {
     /// this method can never fail.
     fn try_dual_mut(&mut self) -> DualResult<()> {
         for elements in self.ranks.iter_mut() {
+            #[cfg(feature = "parallel")]
             elements.par_iter_mut().for_each(Element::swap_mut);
+            #[cfg(not(feature = "parallel"))]
+            elements.iter_mut().for_each(Element::swap_mut);
         }
 
         self.ranks.reverse();
@@ -1111,7 +1530,7 @@ impl Polytope for Abstract {
     /// based on a given polytope. Use [`Self::antiprism`] instead, as this
     /// method can never fail.
     fn try_antiprism(&self) -> DualResult<Self> {
-        Ok(self.antiprism())
+        Ok(self.antiprism_and_vertices().0)
     }
 
     /// Returns the flag omnitruncate of a polytope.
@@ -1119,6 +1538,11 @@ impl Polytope for Abstract {
         self.omnitruncate_and_flags().0
     }
 
+    /// Returns the rectate of a polytope.
+    fn rectify(&self) -> Self {
+        self.medial()
+    }
+
     /// "Appends" a polytope into another, creating a compound polytope.
     ///
     /// # Panics
@@ -1247,7 +1671,14 @@ impl IntoIterator for Abstract {
 
 #[cfg(test)]
 mod tests {
-    use super::{super::Polytope, rank::Rank, Abstract};
+    #[cfg(feature = "serde-serialize")]
+    use super::super::versioned;
+    use super::{
+        super::Polytope,
+        elements::{ElementRef, SectionRef},
+        rank::Rank,
+        Abstract, AbstractError,
+    };
 
     /// Returns a bunch of varied polytopes to run general tests on. Use only
     /// for tests that should work on **everything** you give it!
@@ -1492,4 +1923,215 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    /// Checks that the diamond property is checked below the maximal element.
+    fn open_surface() {
+        use super::elements::{AbstractBuilder, SubelementList, Subelements};
+        use vec_like::VecLike;
+
+        // A single digon, whose edges belong to no other face.
+        let mut builder = AbstractBuilder::new();
+        builder.push_min();
+        builder.push_vertices(2);
+        for count in [2, 1] {
+            let mut subs = SubelementList::new();
+            for _ in 0..count {
+                subs.push(Subelements(vec![0, 1]));
+            }
+            builder.push(subs);
+        }
+        builder.push_max();
+
+        assert!(builder.build().is_dyadic().is_err());
+    }
+
+    #[test]
+    /// Checks that antiprisms are generated correctly.
+    fn antiprism_check() {
+        test(&Abstract::nullitope().antiprism(), vec![1, 1]);
+        test(&Abstract::polygon(5).antiprism(), vec![1, 10, 20, 12, 1]);
+        test(
+            &Abstract::hypercube(Rank::new(3)).antiprism(),
+            vec![1, 14, 48, 62, 28, 1],
+        );
+
+        for poly in test_polytopes().iter() {
+            let antiprism = poly.antiprism();
+            assert_eq!(antiprism.rank(), poly.rank().plus_one());
+            assert!(
+                antiprism.is_valid().is_ok(),
+                "Antiprism of polytope {} is invalid.",
+                "TBA: name"
+            );
+        }
+    }
+
+    #[test]
+    /// Checks that sections are taken between elements with global indices.
+    fn section() {
+        use super::elements::{ElementRef, SectionRef};
+        use vec_like::VecLike;
+
+        let cube = Abstract::hypercube(Rank::new(3));
+        let face = ElementRef::new(Rank::new(2), 3);
+        let face_vertices: Vec<_> = cube[Rank::new(2)][3]
+            .subs
+            .iter()
+            .flat_map(|&e| cube[Rank::new(1)][e].subs.iter().copied())
+            .collect();
+
+        for v in 0..8 {
+            let vertex = ElementRef::new(Rank::new(0), v);
+            let section = cube.section(SectionRef::new(vertex, face)).unwrap();
+
+            if face_vertices.contains(&v) {
+                let section = section.expect("Section between a face and its vertex not found.");
+                assert_eq!(section.el_counts(), vec![1, 2, 1].into());
+            } else {
+                assert!(
+                    section.is_none(),
+                    "Section between unrelated elements found."
+                );
+            }
+        }
+    }
+
+    #[test]
+    /// Checks the f-vectors and Euler characteristics of a few polytopes.
+    fn euler_characteristic() {
+        assert_eq!(Abstract::nullitope().f_vector(), Vec::<usize>::new());
+        assert_eq!(Abstract::point().euler_characteristic(), 0);
+        assert_eq!(Abstract::polygon(5).f_vector(), vec![5, 5]);
+
+        // Spheres of even and odd dimension.
+        for r in 2..=6 {
+            let rank = Rank::new(r);
+            let expected = if r % 2 == 0 { 0 } else { 2 };
+            assert_eq!(Abstract::simplex(rank).euler_characteristic(), expected);
+            assert_eq!(Abstract::hypercube(rank).euler_characteristic(), expected);
+        }
+
+        let cube = Abstract::hypercube(Rank::new(3));
+        assert_eq!(cube.f_vector(), vec![8, 12, 6]);
+
+        // A torus.
+        let torus = Abstract::duocomb(&Abstract::polygon(3), &Abstract::polygon(4));
+        assert_eq!(torus.euler_characteristic(), 0);
+    }
+
+    #[test]
+    /// Checks that compounds and polytopes with disconnected sections are
+    /// valid but not strongly connected.
+    fn connectivity() {
+        use crate::conc::{file::FromFile, Concrete};
+
+        let mut compound = Abstract::hypercube(Rank::new(3));
+        compound.comp_append(Abstract::simplex(Rank::new(3)));
+        compound.is_valid().unwrap();
+        assert!(matches!(
+            compound.validate(),
+            Err(AbstractError::Connected(section))
+                if section.lo.rank == Rank::new(-1) && section.hi.rank == Rank::new(3)
+        ));
+
+        for poly in test_polytopes().iter() {
+            poly.validate().unwrap();
+        }
+
+        // Two tetrahedra sharing a vertex, whose vertex figure is disconnected.
+        let tets = Concrete::from_off(
+            "OFF 7 8 0
+            0 0 0  1 0 0  0 1 0  0 0 1  -1 0 0  0 -1 0  0 0 -1
+            3 0 1 2  3 0 1 3  3 0 2 3  3 1 2 3
+            3 0 4 5  3 0 4 6  3 0 5 6  3 4 5 6",
+        )
+        .unwrap();
+        let lo = ElementRef::new(Rank::new(0), 0);
+        let hi = ElementRef::new(Rank::new(3), 0);
+        assert!(!tets.abs.is_connected(SectionRef::new(lo, hi)));
+        assert!(tets
+            .abs
+            .is_connected(SectionRef::new(ElementRef::new(Rank::new(-1), 0), hi)));
+        tets.abs.is_valid().unwrap();
+        assert!(matches!(
+            tets.abs.validate(),
+            Err(AbstractError::Connected(section)) if section == SectionRef::new(lo, hi)
+        ));
+    }
+
+    /// Checks that compounds are split into their components, and that other
+    /// polytopes are left as they are.
+    #[test]
+    fn components() {
+        let compound = Abstract::compound(vec![Abstract::polygon(3), Abstract::polygon(4)]);
+        assert_eq!(
+            compound.facet_components(),
+            vec![vec![0, 1, 2], vec![3, 4, 5, 6]]
+        );
+
+        let components: Vec<_> = compound.components().collect();
+        assert_eq!(components.len(), 2);
+        for (component, n) in components.iter().zip([3, 4]) {
+            component.is_valid().unwrap();
+            assert_eq!(component.el_counts(), Abstract::polygon(n).el_counts());
+        }
+
+        for poly in test_polytopes() {
+            assert_eq!(poly.components().count(), 1);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "serde-serialize")]
+    /// Checks that polytopes survive a round trip through serialization, and
+    /// that malformed polytopes can't be deserialized.
+    fn serde() {
+        // Polytopes don't need to be sorted to be serialized.
+        let mut polys = test_polytopes().to_vec();
+        polys.push(Abstract::polygon(5).antiprism());
+        let mut cube = Abstract::hypercube(Rank::new(3));
+        cube.permute(Rank::new(1), &[11, 10, 9, 8, 7, 6, 5, 4, 3, 2, 1, 0]);
+        polys.push(cube);
+
+        for poly in polys {
+            let new: Abstract = ron::from_str(&ron::to_string(&poly).unwrap()).unwrap();
+            let tagged: Abstract = versioned::from_ron(&versioned::to_ron(&poly).unwrap()).unwrap();
+            assert_eq!(new.el_counts(), tagged.el_counts());
+
+            new.is_valid().unwrap();
+            assert!(new.sorted);
+            for r in Rank::range_inclusive_iter(-1, poly.rank()) {
+                assert_eq!(poly.el_count(r), new.el_count(r));
+            }
+        }
+
+        /// A dyad with a given maximal element.
+        fn dyad(max: &str) -> String {
+            format!(
+                "[[(subs: [], sups: [0, 1])], \
+                [(subs: [0], sups: [0]), (subs: [0], sups: [0])], \
+                [{}]]",
+                max
+            )
+        }
+
+        ron::from_str::<Abstract>(&dyad("(subs: [0, 1], sups: [])")).unwrap();
+        for max in [
+            // Unsorted.
+            "(subs: [1, 0], sups: [])",
+            // Repeated.
+            "(subs: [0, 0], sups: [])",
+            // Not matching the vertices.
+            "(subs: [0], sups: [])",
+            // Out of bounds.
+            "(subs: [0, 1, 2], sups: [])",
+        ] {
+            assert!(ron::from_str::<Abstract>(&dyad(max)).is_err());
+        }
+
+        // Unbounded, or without any ranks.
+        assert!(ron::from_str::<Abstract>("[[], []]").is_err());
+        assert!(ron::from_str::<Abstract>("[]").is_err());
+    }
 }
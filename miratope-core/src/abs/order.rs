@@ -0,0 +1,411 @@
+//! Order-theoretic properties of the face lattice of a polytope, such as the
+//! number of its chains, its largest antichains, or its Möbius function.
+
+use std::collections::VecDeque;
+
+use super::{elements::ElementRef, rank::Rank, Abstract};
+
+use vec_like::VecLike;
+
+/// Stores the elements strictly below every element of a polytope. Elements
+/// are identified by their position in a single list, which goes through the
+/// elements of the polytope rank by rank, starting from the minimal element.
+pub(super) struct Downsets {
+    /// The position of the first element of each rank.
+    offsets: Vec<usize>,
+
+    /// The sorted positions of the elements strictly below each element.
+    pub(super) below: Vec<Vec<usize>>,
+}
+
+impl Downsets {
+    /// Computes the downsets of every element of a polytope.
+    pub(super) fn new(poly: &Abstract) -> Self {
+        let mut offsets = Vec::with_capacity(poly.len());
+        let mut below: Vec<Vec<usize>> = Vec::new();
+
+        for elements in poly.iter() {
+            // The position of the first element of the previous rank.
+            let prev = offsets.last().copied().unwrap_or_default();
+            offsets.push(below.len());
+
+            for el in elements.iter() {
+                let mut downset = Vec::new();
+                for &sub in el.subs.iter() {
+                    downset.push(prev + sub);
+                    downset.extend_from_slice(&below[prev + sub]);
+                }
+
+                downset.sort_unstable();
+                downset.dedup();
+                below.push(downset);
+            }
+        }
+
+        Self { offsets, below }
+    }
+
+    /// Returns the number of elements of the polytope.
+    pub(super) fn len(&self) -> usize {
+        self.below.len()
+    }
+
+    /// Returns the position of an element.
+    pub(super) fn pos(&self, el: ElementRef) -> usize {
+        self.offsets[el.rank.plus_one_usize()] + el.idx
+    }
+
+    /// Returns the element at a given position.
+    fn el(&self, pos: usize) -> ElementRef {
+        // The last rank starting at or before the position.
+        let r = self.offsets.partition_point(|&offset| offset <= pos) - 1;
+        ElementRef::new(Rank::new(r as isize - 1), pos - self.offsets[r])
+    }
+
+    /// Returns the elements strictly above every element.
+    fn upsets(&self) -> Vec<Vec<usize>> {
+        let mut above = vec![Vec::new(); self.len()];
+        for (hi, downset) in self.below.iter().enumerate() {
+            for &lo in downset {
+                above[lo].push(hi);
+            }
+        }
+
+        above
+    }
+
+    /// Computes the Möbius function between an element and every element
+    /// above it, given the upsets of every element. Returns the pairs of
+    /// positions and values, sorted by position.
+    fn mobius_from(&self, above: &[Vec<usize>], lo: usize) -> Vec<(usize, isize)> {
+        let mut mobius = vec![0; self.len()];
+        let mut in_interval = vec![false; self.len()];
+        mobius[lo] = 1;
+        in_interval[lo] = true;
+
+        let mut values = vec![(lo, 1)];
+
+        // Positions go rank by rank, so every element in the interval is
+        // dealt with after everything below it.
+        for &hi in &above[lo] {
+            let value = -self.below[hi]
+                .iter()
+                .filter(|&&pos| in_interval[pos])
+                .map(|&pos| mobius[pos])
+                .sum::<isize>();
+
+            mobius[hi] = value;
+            in_interval[hi] = true;
+            values.push((hi, value));
+        }
+
+        values
+    }
+}
+
+/// The values of the Möbius function of the face lattice of a polytope, for
+/// every pair of incident elements. Computing this table once is much faster
+/// than calling [`Abstract::mobius`] for every pair of elements.
+pub struct MobiusTable {
+    /// The position of the first element of each rank.
+    offsets: Vec<usize>,
+
+    /// The positions of the elements above each element, together with the
+    /// values of the Möbius function between both, sorted by position.
+    values: Vec<Vec<(usize, isize)>>,
+}
+
+impl MobiusTable {
+    /// Returns the position of an element, if it exists.
+    fn pos(&self, el: ElementRef) -> Option<usize> {
+        let start = *self.offsets.get(el.rank.plus_one_usize())?;
+        let end = self
+            .offsets
+            .get(el.rank.plus_one_usize() + 1)
+            .copied()
+            .unwrap_or(self.values.len());
+
+        (start + el.idx < end).then(|| start + el.idx)
+    }
+
+    /// Returns the value of the Möbius function between two elements, or
+    /// `None` if the first element isn't below or equal to the second.
+    pub fn get(&self, lo: ElementRef, hi: ElementRef) -> Option<isize> {
+        let (lo, hi) = (self.pos(lo)?, self.pos(hi)?);
+        let values = &self.values[lo];
+
+        values
+            .binary_search_by_key(&hi, |&(pos, _)| pos)
+            .ok()
+            .map(|idx| values[idx].1)
+    }
+}
+
+impl Abstract {
+    /// Returns the value of the Möbius function of the face lattice between
+    /// two elements, or `None` if the first element isn't below or equal to
+    /// the second. This is `(-1)ⁿ` for valid polytopes, where `n` is the
+    /// difference between the ranks of the elements, but it can be anything
+    /// for more general posets.
+    ///
+    /// To evaluate the function on many pairs of elements, use
+    /// [`Self::mobius_table`] instead.
+    pub fn mobius(&self, lo: ElementRef, hi: ElementRef) -> Option<isize> {
+        self.get_element(lo)?;
+        self.get_element(hi)?;
+
+        let downsets = Downsets::new(self);
+        let (lo, hi) = (downsets.pos(lo), downsets.pos(hi));
+        if lo != hi && downsets.below[hi].binary_search(&lo).is_err() {
+            return None;
+        }
+
+        // Only the elements within the interval matter.
+        let above: Vec<_> = (lo + 1..=hi)
+            .filter(|&pos| {
+                downsets.below[pos].binary_search(&lo).is_ok()
+                    && (pos == hi || downsets.below[hi].binary_search(&pos).is_ok())
+            })
+            .collect();
+
+        let mut interval = vec![Vec::new(); downsets.len()];
+        interval[lo] = above;
+
+        downsets
+            .mobius_from(&interval, lo)
+            .into_iter()
+            .find(|&(pos, _)| pos == hi)
+            .map(|(_, value)| value)
+    }
+
+    /// Computes the Möbius function of the face lattice between every pair of
+    /// incident elements.
+    pub fn mobius_table(&self) -> MobiusTable {
+        let downsets = Downsets::new(self);
+        let above = downsets.upsets();
+        let values = (0..downsets.len())
+            .map(|lo| downsets.mobius_from(&above, lo))
+            .collect();
+
+        MobiusTable {
+            offsets: downsets.offsets,
+            values,
+        }
+    }
+
+    /// Returns the number of chains in the face lattice with a given number of
+    /// elements, counting the minimal and maximal elements. In particular, the
+    /// chains with as many elements as the rank plus 2 are the flags of the
+    /// polytope.
+    pub fn count_chains(&self, len: usize) -> usize {
+        if len == 0 {
+            return 1;
+        }
+
+        let downsets = Downsets::new(self);
+
+        // The number of chains of the current length with each element on top.
+        let mut chains = vec![1; downsets.len()];
+        for _ in 1..len {
+            chains = downsets
+                .below
+                .iter()
+                .map(|downset| downset.iter().map(|&pos| chains[pos]).sum())
+                .collect();
+        }
+
+        chains.into_iter().sum()
+    }
+
+    /// Returns whether no two elements in a list are incident.
+    pub fn is_antichain(&self, els: &[ElementRef]) -> bool {
+        let downsets = Downsets::new(self);
+        let positions: Vec<_> = els.iter().map(|&el| downsets.pos(el)).collect();
+
+        positions.iter().enumerate().all(|(i, &p)| {
+            positions[i + 1..].iter().all(|&q| {
+                p != q
+                    && downsets.below[p].binary_search(&q).is_err()
+                    && downsets.below[q].binary_search(&p).is_err()
+            })
+        })
+    }
+
+    /// Returns an antichain of the face lattice with as many elements as
+    /// possible, which is in particular a maximal antichain. Its size is the
+    /// width of the face lattice.
+    ///
+    /// By Dilworth's theorem, the width is the least number of chains that
+    /// cover the face lattice. These are found through a maximum matching
+    /// between the elements and the elements above them, from which the
+    /// antichain is then read off, following the proof of Kőnig's theorem.
+    pub fn maximum_antichain(&self) -> Vec<ElementRef> {
+        let downsets = Downsets::new(self);
+        let above = downsets.upsets();
+        let len = downsets.len();
+
+        // The element matched to each element from below and from above.
+        let mut match_up: Vec<Option<usize>> = vec![None; len];
+        let mut match_down: Vec<Option<usize>> = vec![None; len];
+
+        /// Looks for an augmenting path starting at an element, and flips it
+        /// if found.
+        fn augment(
+            lo: usize,
+            above: &[Vec<usize>],
+            visited: &mut [bool],
+            match_up: &mut [Option<usize>],
+            match_down: &mut [Option<usize>],
+        ) -> bool {
+            for &hi in &above[lo] {
+                if visited[hi] {
+                    continue;
+                }
+                visited[hi] = true;
+
+                let free = match match_down[hi] {
+                    Some(prev) => augment(prev, above, visited, match_up, match_down),
+                    None => true,
+                };
+
+                if free {
+                    match_up[lo] = Some(hi);
+                    match_down[hi] = Some(lo);
+                    return true;
+                }
+            }
+
+            false
+        }
+
+        for lo in 0..len {
+            let mut visited = vec![false; len];
+            augment(lo, &above, &mut visited, &mut match_up, &mut match_down);
+        }
+
+        // The elements reachable through alternating paths from the elements
+        // that weren't matched upwards, as lower and as upper elements.
+        let mut reached_lo = vec![false; len];
+        let mut reached_hi = vec![false; len];
+        let mut queue: VecDeque<_> = (0..len).filter(|&lo| match_up[lo].is_none()).collect();
+        for &lo in &queue {
+            reached_lo[lo] = true;
+        }
+
+        while let Some(lo) = queue.pop_front() {
+            for &hi in &above[lo] {
+                if reached_hi[hi] || match_up[lo] == Some(hi) {
+                    continue;
+                }
+                reached_hi[hi] = true;
+
+                if let Some(next) = match_down[hi] {
+                    if !reached_lo[next] {
+                        reached_lo[next] = true;
+                        queue.push_back(next);
+                    }
+                }
+            }
+        }
+
+        // The complement of the minimum vertex cover.
+        (0..len)
+            .filter(|&pos| reached_lo[pos] && !reached_hi[pos])
+            .map(|pos| downsets.el(pos))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{super::Polytope, elements::ElementRef, rank::Rank, Abstract};
+    use vec_like::VecLike;
+
+    /// Checks the chains of a cube.
+    #[test]
+    fn cube_chains() {
+        let cube = Abstract::hypercube(Rank::new(3));
+
+        assert_eq!(cube.count_chains(0), 1);
+        assert_eq!(cube.count_chains(1), 28);
+        assert_eq!(cube.count_chains(5), 48);
+        assert_eq!(cube.count_chains(6), 0);
+    }
+
+    /// Checks that the flags of some polytopes are their longest chains.
+    #[test]
+    fn flags() {
+        for mut poly in [
+            Abstract::simplex(Rank::new(4)),
+            Abstract::orthoplex(Rank::new(4)),
+            Abstract::polygon(7).prism(),
+        ] {
+            poly.abs_sort();
+            let len = poly.rank().plus_one_usize() + 1;
+            assert_eq!(poly.count_chains(len), poly.flags().count());
+        }
+    }
+
+    /// Checks that the Möbius function of some polytopes only depends on the
+    /// ranks of the elements, as their face lattices are Eulerian.
+    #[test]
+    fn mobius() {
+        for poly in [
+            Abstract::hypercube(Rank::new(3)),
+            Abstract::simplex(Rank::new(4)),
+            Abstract::polygon(5).antiprism(),
+        ] {
+            let table = poly.mobius_table();
+            let rank = poly.rank();
+
+            // The Euler relation.
+            let sign = if rank.plus_one_usize() % 2 == 0 {
+                1
+            } else {
+                -1
+            };
+            let min = ElementRef::new(Rank::new(-1), 0);
+            let max = ElementRef::new(rank, 0);
+            assert_eq!(poly.mobius(min, max), Some(sign));
+
+            // Every pair of incident elements has a value.
+            let mut incident = 0;
+            for (r, elements) in poly.iter().enumerate() {
+                for (s, others) in poly.iter().enumerate() {
+                    for lo in 0..elements.len() {
+                        for hi in 0..others.len() {
+                            let lo = ElementRef::new(Rank::new(r as isize - 1), lo);
+                            let hi = ElementRef::new(Rank::new(s as isize - 1), hi);
+
+                            if let Some(value) = table.get(lo, hi) {
+                                let sign = if (s - r) % 2 == 0 { 1 } else { -1 };
+                                assert_eq!(value, sign, "Möbius function of {} and {}", lo, hi);
+                                incident += 1;
+                            }
+                        }
+                    }
+                }
+            }
+            assert_eq!(incident, poly.count_chains(1) + poly.count_chains(2));
+
+            // Distinct vertices aren't incident.
+            let v0 = ElementRef::new(Rank::new(0), 0);
+            let v1 = ElementRef::new(Rank::new(0), 1);
+            assert_eq!(poly.mobius(v0, v1), None);
+        }
+    }
+
+    /// Checks that the widest ranks of some polytopes are maximum antichains.
+    #[test]
+    fn maximum_antichain() {
+        for (poly, width) in [
+            (Abstract::hypercube(Rank::new(3)), 12),
+            (Abstract::simplex(Rank::new(4)), 10),
+            (Abstract::polygon(5).pyramid(), 10),
+        ] {
+            let antichain = poly.maximum_antichain();
+            assert_eq!(antichain.len(), width);
+            assert!(poly.is_antichain(&antichain));
+        }
+    }
+}
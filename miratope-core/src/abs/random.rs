@@ -0,0 +1,278 @@
+//! Generates random abstract polytopes, which are useful to fuzz the
+//! algorithms on polytopes and to run statistical experiments.
+//!
+//! A random polytope starts out either as a simplex, or as a pyramid, prism
+//! or tegum product of smaller random polytopes. Its facets are then stacked,
+//! by gluing pyramids onto them, and its vertices are truncated, which is the
+//! dual operation, until it has the requested number of vertices. Each of
+//! these steps keeps the polytope valid.
+
+use super::{rank::Rank, Abstract};
+use crate::{rng::SeededRng, Polytope};
+
+use vec_like::VecLike;
+
+/// The options for generating [random](Abstract::random_with_options)
+/// polytopes.
+#[derive(Clone, Debug, Default)]
+pub struct RandomOptions {
+    /// The generator for every random choice. It gets advanced with each
+    /// polytope generated, so that successive polytopes differ, but are still
+    /// determined by its seed.
+    pub rng: SeededRng,
+}
+
+impl RandomOptions {
+    /// Initializes the options with a generator with a given seed.
+    pub fn from_seed(seed: u64) -> Self {
+        Self {
+            rng: SeededRng::new(seed),
+        }
+    }
+}
+
+/// Returns the least number of vertices of a polytope of a given rank, not
+/// counting the degenerate digon.
+fn min_vertices(rank: isize) -> usize {
+    match rank {
+        -1 => 0,
+        0 => 1,
+        1 => 2,
+        _ => rank as usize + 1,
+    }
+}
+
+impl Abstract {
+    /// Glues a pyramid onto a facet of the polytope, adding a single vertex.
+    /// Returns `None` if the facet doesn't exist.
+    pub fn stack_facet(&self, facet: usize) -> Option<Self> {
+        let base = self.facet(facet)?;
+        let base_vertices = base.vertex_count();
+        let pyramid = base.pyramid();
+
+        // Any facet of the pyramid isomorphic to its base will do.
+        (0..pyramid.facet_count())
+            .filter(
+                |&idx| matches!(pyramid.facet(idx), Some(f) if f.vertex_count() == base_vertices),
+            )
+            .find_map(|idx| self.connected_sum(&pyramid, facet, idx))
+    }
+
+    /// Truncates a vertex of the polytope, which is the dual operation to
+    /// [stacking](Self::stack_facet) a facet. Returns `None` if the vertex
+    /// doesn't exist.
+    pub fn truncate_vertex(&self, vertex: usize) -> Option<Self> {
+        Some(self.dual().stack_facet(vertex)?.dual())
+    }
+
+    /// Generates a random polytope of a given rank with a given number of
+    /// vertices. The same seed always gives the same polytope.
+    ///
+    /// Returns `None` if there's no polytope of that rank with that many
+    /// vertices, which happens when a polytope of rank at least 2 has less
+    /// than the rank plus one vertices (digons aside), or when a polytope of
+    /// rank at most 1 doesn't have exactly the vertices of its only option.
+    pub fn random(rank: Rank, vertices: usize, seed: u64) -> Option<Self> {
+        Self::random_with_options(rank, vertices, &mut RandomOptions::from_seed(seed))
+    }
+
+    /// Generates a random polytope of a given rank with a given number of
+    /// vertices, advancing the generator in the options. Returns `None` in the
+    /// same cases as [`Self::random`].
+    pub fn random_with_options(
+        rank: Rank,
+        vertices: usize,
+        options: &mut RandomOptions,
+    ) -> Option<Self> {
+        Self::random_with(rank.into_isize(), vertices, &mut options.rng)
+    }
+
+    /// Generates a random polytope of a given rank with a given number of
+    /// vertices, using a given generator.
+    fn random_with(rank: isize, vertices: usize, rng: &mut SeededRng) -> Option<Self> {
+        match rank {
+            -1..=1 => {
+                return if vertices == min_vertices(rank) {
+                    Some(Self::simplex(Rank::new(rank)))
+                } else {
+                    None
+                };
+            }
+            2 => {
+                return if vertices >= 2 {
+                    Some(Self::polygon(vertices))
+                } else {
+                    None
+                };
+            }
+            _ => {}
+        }
+
+        if vertices < min_vertices(rank) {
+            return None;
+        }
+
+        let mut poly = Self::random_base(rank, vertices, rng);
+        while poly.vertex_count() < vertices {
+            // Truncating a vertex adds one less vertex than its degree.
+            let vertex = rng.below(poly.vertex_count());
+            let degree = poly[Rank::new(0)][vertex].sups.len();
+            let truncate = rng.below(2) == 0 && poly.vertex_count() + degree - 1 <= vertices;
+
+            poly = if truncate {
+                poly.truncate_vertex(vertex)?
+            } else {
+                let facet = rng.below(poly.facet_count());
+                poly.stack_facet(facet)?
+            };
+        }
+
+        Some(poly)
+    }
+
+    /// Returns a random polytope of a given rank, at least 3, with at most a
+    /// given number of vertices, to be used as a starting point. This is
+    /// either a simplex or a random product of smaller polytopes.
+    fn random_base(rank: isize, vertices: usize, rng: &mut SeededRng) -> Self {
+        let simplex = || Self::simplex(Rank::new(rank));
+        let factor = |rank: isize, rng: &mut SeededRng| {
+            let extra = if rank >= 2 { rng.below(3) } else { 0 };
+            Self::random_with(rank, min_vertices(rank) + extra, rng)
+        };
+
+        let product = match rng.below(4) {
+            // A pyramid product, whose ranks add up to one less.
+            0 => {
+                let a = rng.below(rank as usize) as isize;
+                let (p, q) = (factor(a, rng), factor(rank - 1 - a, rng));
+                p.zip(q).map(|(p, q)| Self::duopyramid(&p, &q))
+            }
+
+            // A prism or tegum product.
+            1 | 2 => {
+                let a = 1 + rng.below(rank as usize - 1) as isize;
+                let (p, q) = (factor(a, rng), factor(rank - a, rng));
+                let prism = rng.below(2) == 0;
+                p.zip(q).map(|(p, q)| {
+                    if prism {
+                        Self::duoprism(&p, &q)
+                    } else {
+                        Self::duotegum(&p, &q)
+                    }
+                })
+            }
+
+            _ => None,
+        };
+
+        match product {
+            Some(product) if product.vertex_count() <= vertices => product,
+            _ => simplex(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn random() {
+        for rank in 3..6 {
+            for seed in 0..5 {
+                let vertices = 2 * rank as usize + 4;
+                let poly = Abstract::random(Rank::new(rank), vertices, seed).unwrap();
+
+                assert_eq!(poly.rank(), Rank::new(rank));
+                assert_eq!(poly.vertex_count(), vertices);
+                assert!(poly.validate().is_ok(), "rank {}, seed {}", rank, seed);
+                assert!(
+                    poly.dual().validate().is_ok(),
+                    "rank {}, seed {}",
+                    rank,
+                    seed
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn fuzz() {
+        for seed in 0..5 {
+            let mut poly = Abstract::random(Rank::new(4), 10, seed).unwrap();
+            let mut dual = poly.dual();
+            poly.abs_sort();
+            dual.abs_sort();
+
+            let mut prism = poly.prism();
+            prism.abs_sort();
+
+            // A prism over a polytope of rank 4 has 10 flags for every flag of
+            // its base.
+            assert_eq!(poly.flags().count(), dual.flags().count());
+            assert_eq!(poly.flags().count() * 10, prism.flags().count());
+            assert!(poly.pyramid().validate().is_ok());
+        }
+    }
+
+    #[test]
+    fn deterministic() {
+        let counts = |seed| {
+            Abstract::random(Rank::new(4), 12, seed)
+                .unwrap()
+                .el_counts()
+        };
+        assert_eq!(counts(7), counts(7));
+
+        // Different seeds should eventually give different polytopes.
+        assert!((0..10).any(|seed| counts(seed) != counts(0)));
+    }
+
+    #[test]
+    fn options() {
+        // A shared generator gives different polytopes that are still
+        // reproducible.
+        let generate = || {
+            let mut options = RandomOptions::from_seed(3);
+            let mut counts = Vec::new();
+            for _ in 0..5 {
+                let poly = Abstract::random_with_options(Rank::new(4), 12, &mut options).unwrap();
+                counts.push(poly.el_counts());
+            }
+            counts
+        };
+
+        let counts = generate();
+        assert_eq!(counts, generate());
+        assert_eq!(
+            counts[0],
+            Abstract::random(Rank::new(4), 12, 3).unwrap().el_counts()
+        );
+        assert!(counts.iter().any(|count| count != &counts[0]));
+    }
+
+    #[test]
+    fn small() {
+        assert!(Abstract::random(Rank::new(3), 3, 0).is_none());
+        assert!(Abstract::random(Rank::new(1), 3, 0).is_none());
+        assert_eq!(
+            Abstract::random(Rank::new(2), 5, 0).unwrap().el_counts(),
+            vec![1, 5, 5, 1].into()
+        );
+        assert_eq!(
+            Abstract::random(Rank::new(3), 4, 0).unwrap().el_counts(),
+            vec![1, 4, 6, 4, 1].into()
+        );
+    }
+
+    #[test]
+    fn stack_and_truncate() {
+        let cube = Abstract::hypercube(Rank::new(3));
+        let stacked = cube.stack_facet(0).unwrap();
+        assert_eq!(stacked.el_counts(), vec![1, 9, 16, 9, 1].into());
+
+        let truncated = cube.truncate_vertex(0).unwrap();
+        assert_eq!(truncated.el_counts(), vec![1, 10, 15, 7, 1].into());
+        assert!(truncated.validate().is_ok());
+    }
+}
@@ -1,33 +1,38 @@
 //! Declares the [`Rank`] type, along with a few other related types.
 
-use std::{fmt::Display, hash::Hash, iter, slice, vec};
+use std::{convert::TryFrom, fmt::Display, hash::Hash, iter, num::ParseIntError, slice, vec};
 
-use serde::{de::Visitor, Deserialize, Serialize};
+#[cfg(feature = "serde-serialize")]
+use serde::{
+    de::{self, Visitor},
+    Deserialize, Serialize,
+};
 use vec_like::*;
 
 /// Represents the [rank](https://polytope.miraheze.org/w/index.php?title=Rank)
 /// of a polytope.
 ///
-/// Externally, it behaves as a number from -1 onwards. Internally, it contains
-/// an unsigned integer, representing the rank plus 1.
-///
-/// # Todo
-/// We might want to store this as a `u8` in order to save space.
+/// Externally, it behaves as a number from -1 up to 254. Internally, it
+/// contains a `u8`, representing the rank plus 1, which keeps the types
+/// storing ranks small.
 #[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Default, Debug, Hash)]
-pub struct Rank(usize);
+pub struct Rank(u8);
 
-/// Serializes a [`Rank`] as an `i8`.
+/// Serializes a [`Rank`] as an `i16`, since it might not fit in an `i8`.
+#[cfg(feature = "serde-serialize")]
 impl Serialize for Rank {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
     {
-        serializer.serialize_i8((*self).into())
+        serializer.serialize_i16((*self).into())
     }
 }
 
+#[cfg(feature = "serde-serialize")]
 struct RankVisitor;
 
+#[cfg(feature = "serde-serialize")]
 impl<'de> Visitor<'de> for RankVisitor {
     type Value = Rank;
 
@@ -35,49 +40,94 @@ impl<'de> Visitor<'de> for RankVisitor {
         formatter.write_str("an integer between -1 and 254")
     }
 
-    fn visit_i8<E>(self, value: i8) -> Result<Self::Value, E> {
-        Ok(Rank::from(value))
+    fn visit_i8<E: de::Error>(self, value: i8) -> Result<Self::Value, E> {
+        self.visit_i64(value.into())
+    }
+
+    fn visit_i16<E: de::Error>(self, value: i16) -> Result<Self::Value, E> {
+        self.visit_i64(value.into())
     }
 
-    fn visit_i32<E>(self, value: i32) -> Result<Self::Value, E> {
-        Ok(Rank::from(value))
+    fn visit_i32<E: de::Error>(self, value: i32) -> Result<Self::Value, E> {
+        self.visit_i64(value.into())
     }
 
-    fn visit_i64<E>(self, value: i64) -> Result<Self::Value, E> {
-        Ok(Rank::from(value))
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<Self::Value, E> {
+        if (-1..u8::MAX as i64).contains(&value) {
+            Ok(Rank::from(value))
+        } else {
+            Err(E::invalid_value(de::Unexpected::Signed(value), &self))
+        }
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Self::Value, E> {
+        if value < u8::MAX as u64 {
+            Ok(Rank::from(value))
+        } else {
+            Err(E::invalid_value(de::Unexpected::Unsigned(value), &self))
+        }
     }
 }
 
-/// Deserializes a [`Rank`] as an `i8`.
+/// Deserializes a [`Rank`] as an `i16`, rejecting anything out of bounds.
+#[cfg(feature = "serde-serialize")]
 impl<'de> Deserialize<'de> for Rank {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: serde::Deserializer<'de>,
     {
-        deserializer.deserialize_i8(RankVisitor)
+        deserializer.deserialize_i16(RankVisitor)
     }
 }
 
 impl Rank {
+    /// The largest rank that can be stored.
+    pub const MAX: Self = Self(u8::MAX);
+
     /// Initializes a `Rank` from an `isize`.
+    ///
+    /// # Panics
+    /// This method panics if the number is less than -1 or greater than 254.
+    /// Use [`Self::try_new`] for numbers that might be out of bounds.
+    #[inline]
     pub const fn new(num: isize) -> Self {
-        Self((num + 1) as usize)
+        match Self::try_new(num) {
+            Some(rank) => rank,
+            None => panic!("Rank out of bounds."),
+        }
+    }
+
+    /// Initializes a `Rank` from an `isize`, or returns `None` if the number
+    /// is less than -1 or greater than 254.
+    #[inline]
+    pub const fn try_new(num: isize) -> Option<Self> {
+        if num >= -1 && num < u8::MAX as isize {
+            Some(Self((num + 1) as u8))
+        } else {
+            None
+        }
     }
 
     /// Casts the `Rank` into an `usize`, or panics if `self` is `-1`. This
     /// value is **not** the same as the internal value. Use `.0` for that.
+    #[inline]
     pub const fn into_usize(self) -> usize {
-        self.0 as usize - 1
+        match self.try_usize() {
+            Some(n) => n,
+            None => panic!("Rank -1 can't be cast into an usize."),
+        }
     }
 
     /// Casts the `Rank` into an `isize`.
+    #[inline]
     pub const fn into_isize(self) -> isize {
         self.0 as isize - 1
     }
 
     /// Casts the `Rank` into an `u32`, or panics if `self` is `-1`.
+    #[inline]
     pub const fn into_u32(self) -> u32 {
-        self.0 as u32 - 1
+        self.into_usize() as u32
     }
 
     /// Casts the `Rank` into an `f64`.
@@ -86,31 +136,44 @@ impl Rank {
     }
 
     /// Casts the `Rank` into an `usize`, or returns `None` if `self` is `-1`.
+    #[inline]
     pub const fn try_usize(self) -> Option<usize> {
         if self.0 == 0 {
             None
         } else {
-            Some(self.0 - 1)
+            Some(self.0 as usize - 1)
         }
     }
 
     /// Adds one to the rank.
+    ///
+    /// # Panics
+    /// Panics if `self` is [`Self::MAX`].
+    #[inline]
     pub const fn plus_one(self) -> Self {
+        assert!(self.0 < u8::MAX, "Rank out of bounds.");
         Self(self.0 + 1)
     }
 
     /// Adds one to the rank, returns it as a `usize`. This is equivalent to
     /// simply getting the internal value.
+    #[inline]
     pub const fn plus_one_usize(self) -> usize {
-        self.0
+        self.0 as usize
     }
 
     /// Subtracts one from the rank.
+    ///
+    /// # Panics
+    /// Panics if `self` is `-1`.
+    #[inline]
     pub const fn minus_one(self) -> Self {
+        assert!(self.0 > 0, "Rank out of bounds.");
         Self(self.0 - 1)
     }
 
     /// Subtracts one from the rank, or returns `None` if out of bounds.
+    #[inline]
     pub const fn try_minus_one(self) -> Option<Self> {
         if self.0 == 0 {
             None
@@ -124,8 +187,8 @@ impl Rank {
     pub fn range_iter<T: Into<Rank>, U: Into<Rank>>(
         lo: T,
         hi: U,
-    ) -> std::iter::Map<std::ops::Range<usize>, impl FnMut(usize) -> Rank> {
-        (lo.into().0..hi.into().0).into_iter().map(Rank)
+    ) -> std::iter::Map<std::ops::Range<u8>, impl FnMut(u8) -> Rank> {
+        (lo.into().0..hi.into().0).map(Rank)
     }
 
     /// Returns an iterator over `lo..=hi`. A workaround until `Step` is
@@ -133,43 +196,74 @@ impl Rank {
     pub fn range_inclusive_iter<T: Into<Rank>, U: Into<Rank>>(
         lo: T,
         hi: U,
-    ) -> std::iter::Map<std::ops::RangeInclusive<usize>, impl FnMut(usize) -> Rank> {
-        (lo.into().0..=hi.into().0).into_iter().map(Rank)
+    ) -> std::iter::Map<std::ops::RangeInclusive<u8>, impl FnMut(u8) -> Rank> {
+        (lo.into().0..=hi.into().0).map(Rank)
     }
 
     /// Subtraction with bounds checking.
     pub fn try_sub<T: Into<Rank>>(&self, rhs: T) -> Option<Self> {
-        let lhs = self.0 + 1;
-        let rhs = rhs.into().0;
+        Self::try_new(self.into_isize() - rhs.into().into_isize())
+    }
+}
 
-        if lhs < rhs {
-            None
-        } else {
-            Some(Self(lhs - rhs))
+/// The error returned when a [`Rank`] can't be parsed from a string.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseRankError {
+    /// The string isn't an integer.
+    Int(ParseIntError),
+
+    /// The integer is less than -1 or greater than 254.
+    OutOfBounds(i64),
+}
+
+impl Display for ParseRankError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Int(err) => err.fmt(f),
+            Self::OutOfBounds(n) => write!(f, "rank {} out of bounds", n),
         }
     }
 }
 
+impl std::error::Error for ParseRankError {}
+
+/// [`ParseIntError`] is a type of [`ParseRankError`].
+impl From<ParseIntError> for ParseRankError {
+    fn from(err: ParseIntError) -> Self {
+        Self::Int(err)
+    }
+}
+
 impl std::str::FromStr for Rank {
-    type Err = std::num::ParseIntError;
+    type Err = ParseRankError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(i32::from_str(s)?.into())
+        let n = i64::from_str(s)?;
+        isize::try_from(n)
+            .ok()
+            .and_then(Self::try_new)
+            .ok_or(ParseRankError::OutOfBounds(n))
     }
 }
 
 /// Implements `From<T> for Rank` and `From<Rank> for T` for any given type `T`.
+/// Both conversions panic if the number doesn't fit in the other type.
 macro_rules! impl_rank {
     ($T:ty) => {
         impl From<$T> for Rank {
+            #[inline]
             fn from(n: $T) -> Self {
-                Self((n + 1) as usize)
+                isize::try_from(n)
+                    .ok()
+                    .and_then(Self::try_new)
+                    .expect("Rank out of bounds.")
             }
         }
 
         impl From<Rank> for $T {
+            #[inline]
             fn from(rank: Rank) -> Self {
-                rank.0 as $T - 1
+                <$T>::try_from(rank.into_isize()).expect("Rank out of bounds.")
             }
         }
     };
@@ -196,15 +290,14 @@ impl std::ops::Add for Rank {
     type Output = Self;
 
     fn add(self, rhs: Self) -> Self::Output {
-        Self(self.0 + rhs.0 - 1)
+        Self::new(self.into_isize() + rhs.into_isize())
     }
 }
 
 /// Adds a rank to another.
 impl std::ops::AddAssign for Rank {
     fn add_assign(&mut self, rhs: Self) {
-        self.0 += rhs.0;
-        self.0 -= 1;
+        *self = *self + rhs;
     }
 }
 
@@ -213,7 +306,7 @@ impl std::ops::Sub for Rank {
     type Output = Self;
 
     fn sub(self, rhs: Self) -> Self::Output {
-        Self(self.0 + 1 - rhs.0)
+        Self::new(self.into_isize() - rhs.into_isize())
     }
 }
 
@@ -229,14 +322,14 @@ impl Display for Rank {
 impl bevy_egui::egui::emath::Numeric for Rank {
     const INTEGRAL: bool = true;
     const MIN: Self = Self(0);
-    const MAX: Self = Self(usize::MAX);
+    const MAX: Self = Self::MAX;
 
     fn to_f64(self) -> f64 {
         self.into_f64()
     }
 
     fn from_f64(num: f64) -> Self {
-        Self::new(num as isize)
+        Self::new((num as isize).clamp(-1, u8::MAX as isize - 1))
     }
 }
 
@@ -250,13 +343,47 @@ impl bevy_egui::egui::emath::Numeric for Rank {
 pub struct RankVec<T>(Vec<T>);
 impl_veclike!(@for [T] RankVec<T>, Item = T, Index = Rank);
 
+/// Serializes a [`RankVec`] as a sequence, starting from rank -1.
+#[cfg(feature = "serde-serialize")]
+impl<T: Serialize> Serialize for RankVec<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.0.serialize(serializer)
+    }
+}
+
+/// Deserializes a [`RankVec`] as a sequence, starting from rank -1. The
+/// sequence can't be empty, and can't have more entries than there are ranks.
+#[cfg(feature = "serde-serialize")]
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for RankVec<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let vec = Vec::<T>::deserialize(deserializer)?;
+        let len = vec.len();
+
+        if len == 0 || len > Rank::MAX.plus_one_usize() + 1 {
+            Err(de::Error::invalid_length(
+                len,
+                &"between 1 and 256 entries, one for each rank",
+            ))
+        } else {
+            Ok(Self(vec))
+        }
+    }
+}
+
 impl<T> RankVec<T> {
     /// Returns the greatest rank stored in the array.
     ///
     /// # Panics
-    /// Panics if the `RankVec<T>` is empty.
+    /// Panics if the `RankVec<T>` is empty, or has more entries than there
+    /// are ranks.
     pub fn rank(&self) -> Rank {
-        (self.0.len() as isize - 2).into()
+        Rank::new(self.0.len() as isize - 2)
     }
 
     /// Initializes a new empty `Self` with the capacity to store elements up to
@@ -286,8 +413,11 @@ impl<T> RankVec<T> {
     }
 }
 
-/// Allows for [`Rank`] to be used as an index in a [`RankVec`].
+/// Allows for [`Rank`] to be used as an index in a [`RankVec`]. Since every
+/// `Rank` is in bounds, this is just the internal value, and only the bounds
+/// check of the `Vec` remains.
 impl VecIndex for Rank {
+    #[inline]
     fn index(self) -> usize {
         self.plus_one_usize()
     }
@@ -311,7 +441,9 @@ impl<'a, T> Iter<'a, T> {
         self,
     ) -> iter::Map<iter::Enumerate<slice::Iter<'a, T>>, impl FnMut((usize, &'a T)) -> (Rank, &'a T)>
     {
-        self.0.enumerate().map(|(idx, t)| (Rank(idx), t))
+        self.0
+            .enumerate()
+            .map(|(idx, t)| (Rank::new(idx as isize - 1), t))
     }
 }
 
@@ -335,7 +467,9 @@ impl<'a, T> IterMut<'a, T> {
         iter::Enumerate<slice::IterMut<'a, T>>,
         impl FnMut((usize, &'a mut T)) -> (Rank, &'a mut T),
     > {
-        self.0.enumerate().map(|(idx, t)| (Rank(idx), t))
+        self.0
+            .enumerate()
+            .map(|(idx, t)| (Rank::new(idx as isize - 1), t))
     }
 }
 
@@ -362,7 +496,9 @@ impl<T> IntoIter<T> {
     pub fn rank_enumerate(
         self,
     ) -> iter::Map<iter::Enumerate<vec::IntoIter<T>>, impl FnMut((usize, T)) -> (Rank, T)> {
-        self.0.enumerate().map(|(idx, t)| (Rank(idx), t))
+        self.0
+            .enumerate()
+            .map(|(idx, t)| (Rank::new(idx as isize - 1), t))
     }
 }
 
@@ -380,4 +516,68 @@ mod tests {
         assert_eq!(Rank::new(0).minus_one(), Rank::new(-1));
         assert_eq!(Rank::new(-1).plus_one_usize(), 0);
     }
+
+    #[test]
+    /// Checks that ranks are stored in a single byte, and convert back and
+    /// forth.
+    fn rank_size() {
+        assert_eq!(std::mem::size_of::<Rank>(), 1);
+        assert_eq!(Rank::from(253usize), Rank::new(253));
+        assert_eq!(isize::from(Rank::new(-1)), -1);
+        assert_eq!(usize::from(Rank::MAX.minus_one()), 253);
+
+        let ranks: RankVec<()> = vec![(); 5].into();
+        assert_eq!(ranks.rank(), Rank::new(3));
+    }
+
+    #[test]
+    /// Checks that ranks out of bounds are rejected rather than wrapped.
+    fn rank_bounds() {
+        assert_eq!(Rank::try_new(-2), None);
+        assert_eq!(Rank::try_new(255), None);
+        assert_eq!(Rank::try_new(254), Some(Rank::MAX));
+        assert_eq!(Rank::MAX.try_sub(Rank::new(-1)), None);
+        assert_eq!(Rank::MAX.try_sub(Rank::new(0)), Some(Rank::MAX));
+        assert_eq!("254".parse::<Rank>(), Ok(Rank::MAX));
+        assert_eq!("-2".parse::<Rank>(), Err(ParseRankError::OutOfBounds(-2)));
+        assert_eq!("300".parse::<Rank>(), Err(ParseRankError::OutOfBounds(300)));
+        assert!("x".parse::<Rank>().is_err());
+
+        assert!(std::panic::catch_unwind(|| Rank::from(255u8)).is_err());
+        assert!(std::panic::catch_unwind(|| Rank::from(u128::MAX)).is_err());
+        assert!(std::panic::catch_unwind(|| i8::from(Rank::new(200))).is_err());
+        assert!(std::panic::catch_unwind(|| Rank::MAX.plus_one()).is_err());
+        assert!(std::panic::catch_unwind(|| Rank::new(-1).minus_one()).is_err());
+        assert!(std::panic::catch_unwind(|| Rank::MAX + Rank::new(1)).is_err());
+
+        let ranks: RankVec<()> = vec![(); 257].into();
+        assert!(std::panic::catch_unwind(|| ranks.rank()).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "serde-serialize")]
+    /// Checks that ranks and rank vectors out of bounds can't be deserialized.
+    fn rank_serde() {
+        for rank in [-1, 0, 5, 200, 254] {
+            let rank = Rank::new(rank);
+            assert_eq!(
+                ron::from_str::<Rank>(&ron::to_string(&rank).unwrap()).unwrap(),
+                rank
+            );
+        }
+
+        assert!(ron::from_str::<Rank>("-2").is_err());
+        assert!(ron::from_str::<Rank>("255").is_err());
+        assert!(ron::from_str::<RankVec<u8>>("[]").is_err());
+        assert_eq!(
+            ron::from_str::<RankVec<u8>>("[1, 2]").unwrap().rank(),
+            Rank::new(0)
+        );
+
+        // The largest rank vector that can be built can also be loaded back.
+        let ranks: RankVec<u8> = vec![0; Rank::MAX.plus_one_usize() + 1].into();
+        let ranks = ron::from_str::<RankVec<u8>>(&ron::to_string(&ranks).unwrap()).unwrap();
+        assert_eq!(ranks.rank(), Rank::MAX);
+        assert!(ron::from_str::<RankVec<u8>>(&format!("{:?}", vec![0; 257])).is_err());
+    }
 }
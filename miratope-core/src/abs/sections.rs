@@ -0,0 +1,131 @@
+//! Enumerates the [sections](https://polytope.miraheze.org/wiki/Section) of a
+//! polytope, i.e. the polytopes made out of the elements between a pair of
+//! incident elements.
+
+use super::{
+    elements::{ElementHash, ElementRef, SectionRef},
+    fingerprint::Fingerprint,
+    rank::Rank,
+    Abstract,
+};
+use crate::Polytope;
+
+use vec_like::VecLike;
+
+impl Abstract {
+    /// Returns all of the sections of the polytope, including those of a single
+    /// element. These are sorted first by their highest element, and then by
+    /// their lowest element, both by rank and then by index.
+    pub fn section_refs(&self) -> Vec<SectionRef> {
+        let mut sections = Vec::new();
+
+        for (rank, elements) in self.ranks.rank_iter().rank_enumerate() {
+            for idx in 0..elements.len() {
+                let hi = ElementRef::new(rank, idx);
+                let hash = ElementHash::new(self, hi).unwrap();
+
+                for lo_rank in Rank::range_inclusive_iter(-1, rank) {
+                    let mut lo: Vec<_> = hash.get(lo_rank).unwrap().keys().copied().collect();
+                    lo.sort_unstable();
+                    sections.extend(
+                        lo.into_iter()
+                            .map(|lo| SectionRef::new(ElementRef::new(lo_rank, lo), hi)),
+                    );
+                }
+            }
+        }
+
+        sections
+    }
+
+    /// Returns an iterator over all of the sections of the polytope, in the
+    /// order given by [`Self::section_refs`], together with the polytopes they
+    /// induce. The polytopes are built lazily.
+    pub fn sections(&self) -> impl Iterator<Item = (SectionRef, Self)> + '_ {
+        self.section_refs().into_iter().map(move |section| {
+            let poly = self.section(section).unwrap().unwrap();
+            (section, poly)
+        })
+    }
+
+    /// Returns a representative of every section of the polytope up to
+    /// isomorphism, together with the number of sections isomorphic to it.
+    /// Representatives are the first section of their kind, in the order given
+    /// by [`Self::section_refs`], and they're returned sorted.
+    ///
+    /// Sections that aren't flag-connected, such as the polytope itself when
+    /// it's a compound, are told apart by their
+    /// [`Fingerprint`](super::fingerprint::Fingerprint) alone.
+    pub fn distinct_sections(&self) -> Vec<(SectionRef, Self, usize)> {
+        let mut distinct: Vec<(SectionRef, Self, usize)> = Vec::new();
+        let mut fingerprints: Vec<Fingerprint> = Vec::new();
+
+        for (section, mut poly) in self.sections() {
+            poly.abs_sort();
+            let fingerprint = poly.fingerprint();
+
+            let found = distinct.iter_mut().zip(&fingerprints).find(
+                |((_, other, _), &other_fingerprint)| {
+                    other_fingerprint == fingerprint && poly.is_isomorphic(other).unwrap_or(true)
+                },
+            );
+
+            if let Some(((_, _, count), _)) = found {
+                *count += 1;
+            } else {
+                distinct.push((section, poly, 1));
+                fingerprints.push(fingerprint);
+            }
+        }
+
+        distinct
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Checks the number of sections of a polytope, and of distinct sections.
+    fn test(
+        poly: &Abstract,
+        count: usize,
+        distinct_count: usize,
+    ) -> Vec<(SectionRef, Abstract, usize)> {
+        assert_eq!(poly.section_refs().len(), count);
+        let distinct = poly.distinct_sections();
+        assert_eq!(distinct.len(), distinct_count);
+        assert_eq!(distinct.iter().map(|(_, _, n)| n).sum::<usize>(), count);
+        distinct
+    }
+
+    #[test]
+    fn small() {
+        test(&Abstract::nullitope(), 1, 1);
+        test(&Abstract::point(), 3, 2);
+        test(&Abstract::dyad(), 9, 3);
+    }
+
+    /// The sections of a simplex are simplices, of which there are as many as
+    /// there are pairs of nested subsets of its vertices.
+    #[test]
+    fn simplex() {
+        let distinct = test(&Abstract::simplex(Rank::new(3)), 81, 5);
+        for (section, poly, _) in distinct {
+            assert_eq!(poly.rank(), section.hi.rank - section.lo.rank.plus_one());
+            assert_eq!(poly.el_counts(), Abstract::simplex(poly.rank()).el_counts());
+        }
+    }
+
+    /// The sections of a cube are points, dyads, squares, triangles, and the
+    /// cube itself, besides the nullitope.
+    #[test]
+    fn cube() {
+        let cube = Abstract::hypercube(Rank::new(3));
+        let distinct = test(&cube, 153, 6);
+        assert!(cube.sections().all(|(section, poly)| {
+            poly.rank() == section.hi.rank - section.lo.rank.plus_one()
+        }));
+        assert_eq!(distinct[0].2, 1 + 8 + 12 + 6 + 1);
+    }
+}
@@ -0,0 +1,291 @@
+//! Checks and searches for [shellings](https://en.wikipedia.org/wiki/Shelling_(topology))
+//! of polytopes, i.e. orders of their facets in which each facet is glued onto
+//! the previous ones along the beginning of a shelling of its own boundary.
+
+use super::{elements::ElementRef, order::Downsets, rank::Rank, Abstract};
+use crate::Polytope;
+
+use vec_like::VecLike;
+
+/// The maximum number of partial shellings tried by
+/// [`Abstract::is_shellable`] before giving up.
+const SHELLING_BUDGET: usize = 10_000;
+
+/// Keeps track of the facets placed so far in a shelling.
+struct Shelling<'a> {
+    /// The polytope whose facets are being shelled.
+    poly: &'a Abstract,
+
+    /// The elements below each element of the polytope.
+    downsets: Downsets,
+
+    /// The number of placed facets that each element belongs to.
+    covered: Vec<usize>,
+
+    /// Whether each facet has been placed.
+    placed: Vec<bool>,
+
+    /// The number of placed facets.
+    count: usize,
+}
+
+impl<'a> Shelling<'a> {
+    /// Initializes a shelling with no facets of a polytope of rank at least 2.
+    fn new(poly: &'a Abstract) -> Self {
+        let downsets = Downsets::new(poly);
+        let len = downsets.len();
+
+        Self {
+            poly,
+            downsets,
+            covered: vec![0; len],
+            placed: vec![false; poly.facet_count()],
+            count: 0,
+        }
+    }
+
+    /// Returns the rank of the facets.
+    fn facet_rank(&self) -> Rank {
+        self.poly.rank().minus_one()
+    }
+
+    /// Returns the rank of the ridges.
+    fn ridge_rank(&self) -> Rank {
+        self.facet_rank().minus_one()
+    }
+
+    /// Returns the ridges of a facet that belong to a placed facet.
+    fn shared_ridges(&self, facet: usize) -> Vec<usize> {
+        let ridge_rank = self.ridge_rank();
+
+        self.poly[self.facet_rank()][facet]
+            .subs
+            .iter()
+            .copied()
+            .filter(|&ridge| {
+                self.covered[self.downsets.pos(ElementRef::new(ridge_rank, ridge))] > 0
+            })
+            .collect()
+    }
+
+    /// Returns whether a facet can be placed next. Its intersection with the
+    /// placed facets has to be a nonempty union of ridges, which can't be its
+    /// entire boundary unless it's the last facet. Each element that the
+    /// facet shares with the placed facets must lie on one of these ridges,
+    /// and these ridges must be connected to one another.
+    ///
+    /// These conditions are necessary, and suffice for polytopes of rank at
+    /// most 3. In higher ranks, the ridges would also have to form the
+    /// beginning of a shelling of the boundary of the facet.
+    fn can_place(&self, facet: usize) -> bool {
+        if self.placed[facet] {
+            return false;
+        }
+        if self.count == 0 {
+            return true;
+        }
+
+        let facet_rank = self.facet_rank();
+        let ridge_rank = self.ridge_rank();
+        let shared = self.shared_ridges(facet);
+        let ridge_count = self.poly[facet_rank][facet].subs.len();
+
+        if shared.is_empty() || (shared.len() == ridge_count && self.count + 1 < self.placed.len())
+        {
+            return false;
+        }
+
+        // The elements in the union of the shared ridges.
+        let mut union = vec![false; self.downsets.len()];
+        for &ridge in &shared {
+            let pos = self.downsets.pos(ElementRef::new(ridge_rank, ridge));
+            union[pos] = true;
+            for &below in &self.downsets.below[pos] {
+                union[below] = true;
+            }
+        }
+
+        // Every shared element of the facet must be on a shared ridge.
+        let pos = self.downsets.pos(ElementRef::new(facet_rank, facet));
+        if self.downsets.below[pos]
+            .iter()
+            .any(|&below| self.covered[below] > 0 && !union[below])
+        {
+            return false;
+        }
+
+        // The shared ridges must be connected through their own subelements.
+        if ridge_rank > Rank::new(0) {
+            let mut reached = vec![shared[0]];
+            let mut idx = 0;
+
+            while let Some(&ridge) = reached.get(idx) {
+                let subs = &self.poly[ridge_rank][ridge].subs;
+
+                for &other in &shared {
+                    if !reached.contains(&other)
+                        && self.poly[ridge_rank][other]
+                            .subs
+                            .iter()
+                            .any(|sub| subs.contains(sub))
+                    {
+                        reached.push(other);
+                    }
+                }
+
+                idx += 1;
+            }
+
+            if reached.len() != shared.len() {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    /// Places or removes a facet, depending on whether `add` is set.
+    fn set(&mut self, facet: usize, add: bool) {
+        let pos = self.downsets.pos(ElementRef::new(self.facet_rank(), facet));
+
+        let Self {
+            downsets, covered, ..
+        } = self;
+        for &p in downsets.below[pos].iter().chain(std::iter::once(&pos)) {
+            if add {
+                covered[p] += 1;
+            } else {
+                covered[p] -= 1;
+            }
+        }
+
+        self.placed[facet] = add;
+        if add {
+            self.count += 1;
+        } else {
+            self.count -= 1;
+        }
+    }
+
+    /// Tries to complete the shelling by backtracking, decreasing the budget
+    /// for every partial shelling tried.
+    fn complete(&mut self, budget: &mut usize) -> bool {
+        if self.count == self.placed.len() {
+            return true;
+        }
+        if *budget == 0 {
+            return false;
+        }
+        *budget -= 1;
+
+        // Facets glued along more ridges are tried first.
+        let mut candidates: Vec<_> = (0..self.placed.len())
+            .filter(|&facet| self.can_place(facet))
+            .map(|facet| (self.shared_ridges(facet).len(), facet))
+            .collect();
+        candidates.sort_unstable_by(|a, b| b.cmp(a));
+
+        for (_, facet) in candidates {
+            self.set(facet, true);
+            if self.complete(budget) {
+                return true;
+            }
+            self.set(facet, false);
+        }
+
+        false
+    }
+}
+
+impl Abstract {
+    /// Returns whether an order of the facets of the polytope is a shelling.
+    /// See [`Shelling::can_place`] for the conditions that are checked.
+    pub fn is_shelling(&self, order: &[usize]) -> bool {
+        if self.rank() < Rank::new(2) {
+            return true;
+        }
+
+        let mut shelling = Shelling::new(self);
+        if order.len() != shelling.placed.len() {
+            return false;
+        }
+
+        for &facet in order {
+            if facet >= shelling.placed.len() || !shelling.can_place(facet) {
+                return false;
+            }
+            shelling.set(facet, true);
+        }
+
+        true
+    }
+
+    /// Searches for a shelling of the polytope, trying out a limited number of
+    /// partial shellings. Convex polytopes are always shellable, but a
+    /// polytope that isn't a sphere, such as a compound, never is.
+    ///
+    /// Since the search is limited, this may return `false` for polytopes that
+    /// are actually shellable.
+    pub fn is_shellable(&self) -> bool {
+        if self.rank() < Rank::new(2) {
+            return true;
+        }
+
+        let mut shelling = Shelling::new(self);
+        let mut budget = SHELLING_BUDGET;
+        shelling.complete(&mut budget)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{super::Polytope, rank::Rank, Abstract};
+    use vec_like::VecLike;
+
+    /// Checks that some polytopes are shellable.
+    #[test]
+    fn shellable() {
+        for poly in [
+            Abstract::polygon(5),
+            Abstract::hypercube(Rank::new(3)),
+            Abstract::simplex(Rank::new(4)),
+            Abstract::duoprism(&Abstract::polygon(4), &Abstract::polygon(5)),
+        ] {
+            assert!(poly.is_shellable());
+        }
+    }
+
+    /// Checks that a compound isn't shellable.
+    #[test]
+    fn compound() {
+        let mut compound = Abstract::simplex(Rank::new(3));
+        compound.comp_append(Abstract::simplex(Rank::new(3)));
+        assert!(!compound.is_shellable());
+    }
+
+    /// Checks that orders of facets of a cube are told apart.
+    #[test]
+    fn cube_shellings() {
+        let cube = Abstract::hypercube(Rank::new(3));
+        let faces = &cube[Rank::new(2)];
+
+        // The face opposite to the first one shares no edges with it.
+        let opposite = (1..6)
+            .find(|&f| faces[f].subs.iter().all(|e| !faces[0].subs.contains(e)))
+            .unwrap();
+        let sides: Vec<_> = (1..6).filter(|&f| f != opposite).collect();
+
+        let mut order = vec![0];
+        order.extend(&sides);
+        order.push(opposite);
+        assert!(cube.is_shelling(&order));
+
+        // Opposite faces can't come one after the other.
+        let mut order = vec![0, opposite];
+        order.extend(&sides);
+        assert!(!cube.is_shelling(&order));
+
+        // Every face must be used.
+        assert!(!cube.is_shelling(&[0]));
+    }
+}
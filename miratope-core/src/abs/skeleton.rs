@@ -0,0 +1,110 @@
+//! Measures distances in the 1-skeleton of a polytope, the graph made out of
+//! its vertices and edges. Its diameter is the subject of the
+//! [Hirsch conjecture](https://en.wikipedia.org/wiki/Hirsch_conjecture) and
+//! its relatives.
+
+use std::collections::VecDeque;
+
+use super::{rank::Rank, Abstract};
+use crate::Polytope;
+
+use vec_like::VecLike;
+
+impl Abstract {
+    /// Returns the vertices joined to a given vertex by an edge. Degenerate
+    /// edges with a single vertex are ignored.
+    fn skeleton_neighbors(&self, vertex: usize) -> impl Iterator<Item = usize> + '_ {
+        self[Rank::new(0)][vertex]
+            .sups
+            .iter()
+            .flat_map(move |&edge| self[Rank::new(1)][edge].subs.iter().copied())
+            .filter(move |&v| v != vertex)
+    }
+
+    /// Returns the number of edges in a shortest path from a given vertex to
+    /// every other vertex, or `None` for the vertices that can't be reached.
+    ///
+    /// # Panics
+    /// This method will panic if the vertex doesn't exist.
+    pub fn skeleton_distances(&self, vertex: usize) -> Vec<Option<usize>> {
+        let mut distances = vec![None; self.vertex_count()];
+        distances[vertex] = Some(0);
+        if self.rank() < Rank::new(1) {
+            return distances;
+        }
+
+        let mut queue = VecDeque::new();
+        queue.push_back((vertex, 0));
+        while let Some((v, distance)) = queue.pop_front() {
+            for w in self.skeleton_neighbors(v) {
+                if distances[w].is_none() {
+                    distances[w] = Some(distance + 1);
+                    queue.push_back((w, distance + 1));
+                }
+            }
+        }
+
+        distances
+    }
+
+    /// Returns the number of edges in a shortest path between two vertices,
+    /// or `None` if either vertex doesn't exist or if they aren't connected.
+    pub fn skeleton_distance(&self, v0: usize, v1: usize) -> Option<usize> {
+        if v0 >= self.vertex_count() || v1 >= self.vertex_count() {
+            return None;
+        }
+
+        self.skeleton_distances(v0)[v1]
+    }
+
+    /// Returns the [combinatorial diameter](https://en.wikipedia.org/wiki/Distance_(graph_theory))
+    /// of the polytope, the largest distance between two of its vertices in
+    /// its 1-skeleton. Returns `None` if the polytope has no vertices or if
+    /// its 1-skeleton isn't connected.
+    pub fn combinatorial_diameter(&self) -> Option<usize> {
+        let mut diameter = None;
+        for vertex in 0..self.vertex_count() {
+            for distance in self.skeleton_distances(vertex) {
+                diameter = diameter.max(Some(distance?));
+            }
+        }
+
+        diameter
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hypercube() {
+        for rank in 0..6 {
+            let cube = Abstract::hypercube(Rank::new(rank));
+            assert_eq!(cube.combinatorial_diameter(), Some(rank as usize));
+        }
+
+        // Opposite vertices of a tesseract.
+        let tesseract = Abstract::hypercube(Rank::new(4));
+        let distances = tesseract.skeleton_distances(0);
+        let far = distances.iter().position(|&d| d == Some(4)).unwrap();
+        assert_eq!(tesseract.skeleton_distance(far, 0), Some(4));
+        assert_eq!(tesseract.skeleton_distance(0, 16), None);
+    }
+
+    #[test]
+    fn diameter() {
+        assert_eq!(Abstract::polygon(7).combinatorial_diameter(), Some(3));
+        assert_eq!(
+            Abstract::simplex(Rank::new(5)).combinatorial_diameter(),
+            Some(1)
+        );
+        assert_eq!(Abstract::nullitope().combinatorial_diameter(), None);
+
+        // A compound has a disconnected 1-skeleton.
+        let mut compound = Abstract::polygon(4);
+        compound.comp_append(Abstract::polygon(3));
+        assert_eq!(compound.combinatorial_diameter(), None);
+        assert_eq!(compound.skeleton_distance(0, 5), None);
+    }
+}
@@ -0,0 +1,389 @@
+//! Builds the abstract [Wythoffians](https://polytope.miraheze.org/wiki/Wythoffian)
+//! of a polytope, such as its truncate, rectate, or runcinate, out of orbits of
+//! its flags under flag changes.
+
+use super::{
+    elements::{AbstractBuilder, SubelementList, Subelements},
+    flag::{Flag, FlagTable},
+    rank::Rank,
+    Abstract,
+};
+use crate::Polytope;
+
+use vec_like::VecLike;
+
+/// Returns whether a set of flag changes can be the type of an element of a
+/// Wythoffian, i.e. whether every run of consecutive flag changes in it
+/// contains a ringed one.
+fn is_valid_type(ty: &[bool], ringed: &[bool]) -> bool {
+    let mut has_ring = true;
+
+    for (r, &in_ty) in ty.iter().enumerate() {
+        if in_ty {
+            // Starts a new run.
+            if r == 0 || !ty[r - 1] {
+                if !has_ring {
+                    return false;
+                }
+                has_ring = false;
+            }
+            has_ring |= ringed[r];
+        }
+    }
+
+    has_ring
+}
+
+/// Returns the flag changes that fix an element of a given type. These are the
+/// changes in the type, together with the unringed changes that commute with
+/// all of them.
+fn stabilizer(ty: &[bool], ringed: &[bool]) -> Vec<usize> {
+    (0..ty.len())
+        .filter(|&r| {
+            ty[r] || !(ringed[r] || (r > 0 && ty[r - 1]) || ty.get(r + 1).copied().unwrap_or(false))
+        })
+        .collect()
+}
+
+/// Labels every flag by its orbit under a set of flag changes, and returns the
+/// labels together with the number of orbits.
+fn orbits(flags: &FlagTable, generators: &[usize]) -> (Vec<usize>, usize) {
+    let mut labels = vec![usize::MAX; flags.len()];
+    let mut count = 0;
+
+    for start in 0..flags.len() {
+        if labels[start] != usize::MAX {
+            continue;
+        }
+
+        labels[start] = count;
+        let mut queue = vec![start];
+        while let Some(flag) = queue.pop() {
+            for &r in generators {
+                let next = flags.change(flag, r);
+                if labels[next] == usize::MAX {
+                    labels[next] = count;
+                    queue.push(next);
+                }
+            }
+        }
+
+        count += 1;
+    }
+
+    (labels, count)
+}
+
+/// The elements of a given type in a Wythoffian.
+struct ElementType {
+    /// Which flag changes belong to the type.
+    ty: Vec<bool>,
+
+    /// The orbit that each flag belongs to.
+    labels: Vec<usize>,
+
+    /// The index of the first element of this type within its rank.
+    offset: usize,
+}
+
+impl Abstract {
+    /// Builds the Wythoffian of the polytope with a given set of ringed flag
+    /// changes, where the flag change `r` swaps out the element of rank `r`.
+    /// Ringing only the vertices returns a copy of the polytope, ringing
+    /// `[0, 1]` gives its truncate, `[1]` its rectate, `[0, 3]` the runcinate
+    /// of a polychoron, and ringing every flag change gives its omnitruncate.
+    ///
+    /// The elements of rank `k` of the result correspond to sets of `k` flag
+    /// changes in which every run of consecutive changes has a ringed one,
+    /// together with an orbit of the flags under the flag changes that fix
+    /// such an element. Two elements are incident whenever their sets of flag
+    /// changes are nested and their orbits share a flag.
+    ///
+    /// # Panics
+    /// This method will panic if no flag changes are ringed, if any ringed flag
+    /// change is out of range, if the polytope has rank less than 1, or if it
+    /// isn't sorted.
+    pub fn truncate(&self, ringed: &[usize]) -> Self {
+        self.truncate_and_flags(ringed).0
+    }
+
+    /// Builds the Wythoffian of the polytope with a given set of ringed flag
+    /// changes, just as [`Self::truncate`] does, along with a flag for each of
+    /// its vertices, out of the orbit of flags that makes it up.
+    ///
+    /// # Panics
+    /// This method will panic under the same conditions as [`Self::truncate`].
+    pub fn truncate_and_flags(&self, ringed: &[usize]) -> (Self, Vec<Flag>) {
+        let rank = self.rank();
+        let n = rank.try_usize().expect("Can't truncate a nullitope.");
+        assert!(n > 0, "Can't truncate a point.");
+        assert!(
+            !ringed.is_empty(),
+            "At least one flag change must be ringed."
+        );
+
+        let mut ringed_mask = vec![false; n];
+        for &r in ringed {
+            assert!(r < n, "Flag change {} out of range.", r);
+            ringed_mask[r] = true;
+        }
+
+        trace_span!("truncate", rank = n, ?ringed);
+        let flags = FlagTable::new(self);
+
+        // The types of the elements of each rank, along with their orbits.
+        let mut types: Vec<Vec<ElementType>> = (0..n).map(|_| Vec::new()).collect();
+        let mut counts = vec![0; n];
+        for mask in 0..(1usize << n) {
+            let ty: Vec<bool> = (0..n).map(|r| mask & (1 << r) != 0).collect();
+            if !is_valid_type(&ty, &ringed_mask) {
+                continue;
+            }
+
+            let k = ty.iter().filter(|&&in_ty| in_ty).count();
+            if k == n {
+                continue;
+            }
+
+            let (labels, count) = orbits(&flags, &stabilizer(&ty, &ringed_mask));
+            types[k].push(ElementType {
+                ty,
+                labels,
+                offset: counts[k],
+            });
+            counts[k] += count;
+        }
+
+        let mut builder = AbstractBuilder::with_capacity(rank);
+        builder.push_min();
+        builder.push_vertices(counts[0]);
+
+        // An element contains the elements of the types right below its own,
+        // whose orbits share a flag with it.
+        for k in 1..n {
+            let mut subelements = vec![Vec::new(); counts[k]];
+
+            for el_type in &types[k] {
+                for sub_type in &types[k - 1] {
+                    if sub_type
+                        .ty
+                        .iter()
+                        .zip(&el_type.ty)
+                        .any(|(&sub, &el)| sub && !el)
+                    {
+                        continue;
+                    }
+
+                    for flag in 0..flags.len() {
+                        subelements[el_type.offset + el_type.labels[flag]]
+                            .push(sub_type.offset + sub_type.labels[flag]);
+                    }
+                }
+            }
+
+            let mut list = SubelementList::with_capacity(counts[k]);
+            for mut subs in subelements {
+                subs.sort_unstable();
+                subs.dedup();
+                list.push(Subelements(subs));
+            }
+            builder.push(list);
+        }
+
+        builder.push_max();
+
+        // The vertices are the orbits of the first type.
+        let mut vertex_flags = vec![None; counts[0]];
+        for (flag, &label) in flags.flags.iter().zip(&types[0][0].labels) {
+            if vertex_flags[label].is_none() {
+                vertex_flags[label] = Some(flag.clone());
+            }
+        }
+
+        (
+            builder.build(),
+            vertex_flags.into_iter().map(Option::unwrap).collect(),
+        )
+    }
+}
+
+impl Abstract {
+    /// Builds the [medial](https://en.wikipedia.org/wiki/Medial_graph) of the
+    /// polytope, i.e. its rectate, directly from its face lattice. Unlike
+    /// [`Self::truncate`], this doesn't require the polytope to be sorted, and
+    /// the vertex with index `i` of the result corresponds to the edge with
+    /// index `i` of the original polytope.
+    ///
+    /// Besides these vertices, the result has an element for every element of
+    /// rank at least 2 of the polytope, of the same rank, and an element for
+    /// every vertex on every such element, whose rank is one less. The latter
+    /// are the element figures of the elements at their vertices. The medial
+    /// of a point or of the nullitope is the nullitope.
+    pub fn medial(&self) -> Self {
+        let n = match self.rank().try_usize() {
+            Some(n) if n >= 2 => n,
+            Some(1) => return Self::point(),
+            _ => return Self::nullitope(),
+        };
+
+        // The sorted vertices of every element of rank at least 1.
+        let mut vertices: Vec<Vec<Vec<usize>>> = vec![Vec::new()];
+        for r in 1..=n {
+            let lower = &vertices[r - 1];
+            let rank_vertices = self[Rank::from(r)]
+                .iter()
+                .map(|el| {
+                    let mut el_vertices: Vec<_> = if r == 1 {
+                        el.subs.0.clone()
+                    } else {
+                        el.subs
+                            .iter()
+                            .flat_map(|&s| lower[s].iter().copied())
+                            .collect()
+                    };
+                    el_vertices.sort_unstable();
+                    el_vertices.dedup();
+                    el_vertices
+                })
+                .collect();
+            vertices.push(rank_vertices);
+        }
+
+        // The index of the element figure of every element of rank at least
+        // 2 at its first vertex. The others come right after it.
+        let mut fig_offsets: Vec<Vec<usize>> = vec![Vec::new(); n + 1];
+        for r in 2..=n {
+            let mut offset = if r >= 3 {
+                self.el_count(Rank::from(r - 1))
+            } else {
+                0
+            };
+            for el_vertices in &vertices[r] {
+                fig_offsets[r].push(offset);
+                offset += el_vertices.len();
+            }
+        }
+        let fig_idx = |r: usize, idx: usize, v: usize| {
+            fig_offsets[r][idx] + vertices[r][idx].binary_search(&v).unwrap()
+        };
+
+        let mut builder = AbstractBuilder::with_capacity(self.rank());
+        builder.push_min();
+        builder.push_vertices(self.el_count(Rank::new(1)));
+
+        for k in 1..n {
+            let mut list = SubelementList::new();
+
+            // The medials of the elements of rank k.
+            if k >= 2 {
+                for (idx, el) in self[Rank::from(k)].iter().enumerate() {
+                    let mut subs = Subelements::new();
+                    if k >= 3 {
+                        for &s in &el.subs {
+                            subs.push(s);
+                        }
+                    }
+                    for &v in &vertices[k][idx] {
+                        subs.push(fig_idx(k, idx, v));
+                    }
+                    list.push(subs);
+                }
+            }
+
+            // The element figures of the elements of rank k + 1.
+            for (idx, el) in self[Rank::from(k + 1)].iter().enumerate() {
+                for &v in &vertices[k + 1][idx] {
+                    let subs = el
+                        .subs
+                        .iter()
+                        .filter(|&&s| vertices[k][s].binary_search(&v).is_ok())
+                        .map(|&s| if k == 1 { s } else { fig_idx(k, s, v) })
+                        .collect();
+                    list.push(Subelements(subs));
+                }
+            }
+
+            builder.push(list);
+        }
+
+        builder.push_max();
+        let truncated = builder.build();
+        trace_event!(elements = ?truncated.el_counts(), "built truncation");
+        truncated
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{
+        rank::{Rank, RankVec},
+        Abstract,
+    };
+    use crate::Polytope;
+
+    /// Checks that a Wythoffian is valid and has the expected element counts.
+    fn test<T: Into<RankVec<usize>>>(poly: &Abstract, ringed: &[usize], el_counts: T) {
+        let wythoffian = poly.truncate(ringed);
+        wythoffian.is_valid().unwrap();
+        assert_eq!(wythoffian.el_counts(), el_counts.into());
+    }
+
+    #[test]
+    fn polygon() {
+        let mut pentagon = Abstract::polygon(5);
+        pentagon.abs_sort();
+        test(&pentagon, &[0], vec![1, 5, 5, 1]);
+        test(&pentagon, &[1], vec![1, 5, 5, 1]);
+        test(&pentagon, &[0, 1], vec![1, 10, 10, 1]);
+    }
+
+    #[test]
+    fn cube() {
+        let mut cube = Abstract::hypercube(Rank::new(3));
+        cube.abs_sort();
+        test(&cube, &[0], vec![1, 8, 12, 6, 1]);
+        test(&cube, &[2], vec![1, 6, 12, 8, 1]);
+        test(&cube, &[1], vec![1, 12, 24, 14, 1]);
+        test(&cube, &[0, 1], vec![1, 24, 36, 14, 1]);
+        test(&cube, &[0, 2], vec![1, 24, 48, 26, 1]);
+        test(&cube, &[0, 1, 2], cube.omnitruncate().el_counts());
+    }
+
+    #[test]
+    fn tesseract() {
+        let mut tesseract = Abstract::hypercube(Rank::new(4));
+        tesseract.abs_sort();
+        test(&tesseract, &[1], vec![1, 32, 96, 88, 24, 1]);
+        test(&tesseract, &[0, 3], vec![1, 64, 192, 208, 80, 1]);
+    }
+
+    /// Checks that the medial of a polytope matches its rectate.
+    #[test]
+    fn medial() {
+        for mut poly in [
+            Abstract::polygon(5),
+            Abstract::hypercube(Rank::new(3)),
+            Abstract::hypercube(Rank::new(4)),
+            Abstract::orthoplex(Rank::new(4)),
+            Abstract::simplex(Rank::new(5)),
+            Abstract::duoprism(&Abstract::polygon(3), &Abstract::polygon(4)),
+        ] {
+            let medial = poly.medial();
+            medial.is_valid().unwrap();
+            poly.abs_sort();
+            assert_eq!(medial.el_counts(), poly.truncate(&[1]).el_counts());
+        }
+
+        assert_eq!(Abstract::dyad().medial().el_counts(), vec![1, 1].into());
+        assert_eq!(Abstract::point().medial().el_counts(), vec![1].into());
+    }
+
+    /// Checks that nonregular polytopes are handled.
+    #[test]
+    fn prism() {
+        let mut prism = Abstract::duoprism(&Abstract::polygon(3), &Abstract::dyad());
+        prism.abs_sort();
+        test(&prism, &[0], prism.el_counts());
+        test(&prism, &[0, 1, 2], prism.omnitruncate().el_counts());
+    }
+}
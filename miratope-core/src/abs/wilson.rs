@@ -0,0 +1,204 @@
+//! Implements the [Wilson operations](https://polytope.miraheze.org/wiki/Wilson_operation)
+//! on polytopes, like Petrie duality.
+//!
+//! These work by replacing the flag changes of a polytope by certain products
+//! of them, and then building the polytope whose flags have these as their
+//! flag changes.
+
+use super::{flag::FlagTable, Abstract};
+use crate::Polytope;
+
+/// One of the operations that can be applied through
+/// [`Abstract::wilson`]. We write *ρ*<sub>*i*</sub> for the flag change of rank
+/// *i*, and *n* for the rank of the polytope.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WilsonOp {
+    /// Takes the dual, by reversing the order of the flag changes.
+    Dual,
+
+    /// Takes the Petrie dual, by replacing *ρ*<sub>*n* − 3</sub> with
+    /// *ρ*<sub>*n* − 3</sub>*ρ*<sub>*n* − 1</sub>. For polyhedra, this is
+    /// the Petrial, whose faces are the Petrie polygons of the original. Only
+    /// defined for rank at least 3.
+    Petrie,
+
+    /// Takes the opposite, by replacing *ρ*<sub>2</sub> with
+    /// *ρ*<sub>2</sub>*ρ*<sub>0</sub>. This is the dual of the Petrie dual of
+    /// the dual. Only defined for rank at least 3.
+    Opposite,
+
+    /// Takes the faces to be the *k*-holes of the polyhedron, i.e. the paths
+    /// of edges that leave *k* − 1 edges to the same side at every vertex, by
+    /// replacing *ρ*<sub>1</sub> with *ρ*<sub>1</sub>(*ρ*<sub>2</sub>
+    /// *ρ*<sub>1</sub>)<sup>*k* − 1</sup>. Only defined for polyhedra.
+    Hole(usize),
+}
+
+/// Composes two permutations, applying the first one and then the second.
+fn compose(p: &[usize], q: &[usize]) -> Vec<usize> {
+    p.iter().map(|&i| q[i]).collect()
+}
+
+impl WilsonOp {
+    /// Applies the operation to a list of flag changes, indexed first by rank
+    /// and then by flag. Returns `None` if it isn't defined in this rank.
+    fn apply(self, gens: &mut [Vec<usize>]) -> Option<()> {
+        let n = gens.len();
+
+        match self {
+            Self::Dual => gens.reverse(),
+            Self::Petrie => {
+                let r = n.checked_sub(3)?;
+                gens[r] = compose(&gens[r], &gens[n - 1]);
+            }
+            Self::Opposite => {
+                if n < 3 {
+                    return None;
+                }
+                gens[2] = compose(&gens[2], &gens[0]);
+            }
+            Self::Hole(k) => {
+                if n != 3 || k == 0 {
+                    return None;
+                }
+
+                let rot = compose(&gens[2], &gens[1]);
+                let mut new = gens[1].clone();
+                for _ in 1..k {
+                    new = compose(&new, &rot);
+                }
+                gens[1] = new;
+            }
+        }
+
+        Some(())
+    }
+}
+
+impl Abstract {
+    /// Applies a sequence of Wilson operations to the polytope, in order.
+    /// Returns `None` if any of them isn't defined in the rank of the
+    /// polytope, or if the result isn't a valid polytope. Since the flag
+    /// changes are only turned back into a polytope at the end, the
+    /// intermediate results don't need to be valid.
+    ///
+    /// # Panics
+    /// This method will panic if the polytope isn't sorted.
+    pub fn wilson(&self, ops: &[WilsonOp]) -> Option<Self> {
+        let rank = self.rank();
+        let n = rank.try_usize()?;
+        let table = FlagTable::new(self);
+
+        let mut gens: Vec<Vec<usize>> = (0..n)
+            .map(|r| (0..table.len()).map(|idx| table.change(idx, r)).collect())
+            .collect();
+        for op in ops {
+            op.apply(&mut gens)?;
+        }
+
+        let changes: Vec<Vec<usize>> = (0..table.len())
+            .map(|idx| gens.iter().map(|gen| gen[idx]).collect())
+            .collect();
+        let poly = Self::from_flag_changes(rank, &changes);
+        poly.is_valid().ok().map(|_| poly)
+    }
+
+    /// Builds the Petrie dual of the polytope, as in [`WilsonOp::Petrie`].
+    /// Unlike [`Polytope::petrial`], this is defined in any rank of at least 3.
+    /// Returns `None` if the result isn't a valid polytope.
+    ///
+    /// # Panics
+    /// This method will panic if the polytope isn't sorted.
+    pub fn petrie_dual(&self) -> Option<Self> {
+        self.wilson(&[WilsonOp::Petrie])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        abs::rank::Rank,
+        conc::{convex::convex_hull, Concrete},
+        geometry::Point,
+        Float,
+    };
+
+    use vec_like::VecLike;
+
+    /// Returns a sorted cube.
+    fn cube() -> Abstract {
+        let mut cube = Abstract::hypercube(Rank::new(3));
+        cube.abs_sort();
+        cube
+    }
+
+    /// Sorts two polytopes and checks whether they're isomorphic.
+    fn assert_isomorphic(mut poly: Abstract, mut other: Abstract) {
+        poly.abs_sort();
+        other.abs_sort();
+        assert_eq!(poly.is_isomorphic(&other), Some(true));
+    }
+
+    #[test]
+    fn petrie() {
+        let cube = cube();
+        let petrial = cube.petrie_dual().unwrap();
+        assert_eq!(petrial.el_counts(), vec![1, 8, 12, 4, 1].into());
+        assert_isomorphic(petrial, cube.petrial().unwrap());
+
+        // The Petrie dual is an involution.
+        assert_isomorphic(
+            cube.wilson(&[WilsonOp::Petrie, WilsonOp::Petrie]).unwrap(),
+            cube,
+        );
+
+        // The Petrie dual of a tesseract.
+        let mut tes = Abstract::hypercube(Rank::new(4));
+        tes.abs_sort();
+        let mut petrie = tes.petrie_dual().unwrap();
+        assert_eq!(petrie.el_count(Rank::new(0)), 16);
+        petrie.abs_sort();
+        assert_isomorphic(petrie.petrie_dual().unwrap(), tes);
+    }
+
+    #[test]
+    fn dual_opposite() {
+        let cube = cube();
+        let mut oct = Abstract::orthoplex(Rank::new(3));
+        oct.abs_sort();
+        assert_isomorphic(cube.wilson(&[WilsonOp::Dual]).unwrap(), oct);
+
+        // The opposite is the dual of the Petrie dual of the dual.
+        let opposite = cube.wilson(&[WilsonOp::Opposite]).unwrap();
+        assert_isomorphic(
+            opposite,
+            cube.wilson(&[WilsonOp::Dual, WilsonOp::Petrie, WilsonOp::Dual])
+                .unwrap(),
+        );
+    }
+
+    #[test]
+    fn hole() {
+        // The 2-holes of an icosahedron are the faces of a great dodecahedron.
+        let phi = (1.0 + (5.0 as Float).sqrt()) / 2.0;
+        let mut points = Vec::new();
+        for a in [-1.0, 1.0] {
+            for b in [-phi, phi] {
+                points.push(Point::from(vec![0.0, a, b]));
+                points.push(Point::from(vec![a, b, 0.0]));
+                points.push(Point::from(vec![b, 0.0, a]));
+            }
+        }
+
+        let mut ike: Concrete = convex_hull(points);
+        ike.abs_sort();
+        let gad = ike.abs.wilson(&[WilsonOp::Hole(2)]).unwrap();
+        assert_eq!(gad.el_counts(), vec![1, 12, 30, 12, 1].into());
+        assert_eq!(gad[Rank::new(2)][0].subs.len(), 5);
+
+        // Holes aren't defined outside of polyhedra.
+        assert!(Abstract::polygon(4).wilson(&[WilsonOp::Hole(2)]).is_none());
+        assert_isomorphic(ike.abs.wilson(&[WilsonOp::Hole(1)]).unwrap(), ike.abs);
+    }
+}
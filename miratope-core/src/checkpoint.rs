@@ -0,0 +1,126 @@
+//! Periodically saves the state of long computations to disk, so that they
+//! can be resumed after a crash or a restart.
+//!
+//! A computation that supports checkpoints loads its state from the
+//! checkpoint when it starts, if there's any, and then calls
+//! [`Checkpoint::step`] after every unit of work. The state is stored as RON,
+//! and is first written to a temporary file that then replaces the old one, so
+//! that a crash midway through saving doesn't corrupt it.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+/// A file where the state of a computation gets saved every so many steps.
+#[derive(Clone, Debug)]
+pub struct Checkpoint {
+    /// The path to the file.
+    path: PathBuf,
+
+    /// The number of steps between saves.
+    interval: usize,
+
+    /// The number of steps since the last save.
+    steps: usize,
+}
+
+impl Checkpoint {
+    /// Initializes a checkpoint at a given path, which saves the state every
+    /// given number of steps.
+    ///
+    /// # Panics
+    /// This method will panic if the interval is zero.
+    pub fn new<T: AsRef<Path>>(path: T, interval: usize) -> Self {
+        assert_ne!(interval, 0, "The interval between saves must be nonzero.");
+
+        Self {
+            path: path.as_ref().to_path_buf(),
+            interval,
+            steps: 0,
+        }
+    }
+
+    /// Returns the path to the file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the path to the temporary file that's written before replacing
+    /// the checkpoint.
+    fn tmp_path(&self) -> PathBuf {
+        let mut name = self.path.file_name().unwrap_or_default().to_os_string();
+        name.push(".tmp");
+        self.path.with_file_name(name)
+    }
+
+    /// Loads the saved state, or returns `None` if nothing has been saved yet.
+    pub fn load<S: DeserializeOwned>(&self) -> io::Result<Option<S>> {
+        let ron = match fs::read_to_string(&self.path) {
+            Ok(ron) => ron,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err),
+        };
+
+        ron::from_str(&ron)
+            .map(Some)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    /// Saves the state right away.
+    pub fn save<S: Serialize>(&mut self, state: &S) -> io::Result<()> {
+        let ron =
+            ron::to_string(state).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+
+        let tmp_path = self.tmp_path();
+        fs::write(&tmp_path, ron)?;
+        fs::rename(&tmp_path, &self.path)?;
+
+        self.steps = 0;
+        Ok(())
+    }
+
+    /// Records a step of the computation, and saves the state if enough steps
+    /// have gone by since the last save.
+    pub fn step<S: Serialize>(&mut self, state: &S) -> io::Result<()> {
+        self.steps += 1;
+
+        if self.steps >= self.interval {
+            self.save(state)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Deletes the saved state, if there's any.
+    pub fn clear(&self) -> io::Result<()> {
+        match fs::remove_file(&self.path) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn save_and_load() {
+        let path = std::env::temp_dir().join("miratope_checkpoint_test.ron");
+        let mut checkpoint = Checkpoint::new(&path, 3);
+        checkpoint.clear().unwrap();
+        assert_eq!(checkpoint.load::<Vec<usize>>().unwrap(), None);
+
+        // Only every third step gets saved.
+        for i in 0..5 {
+            checkpoint.step(&vec![i]).unwrap();
+        }
+        assert_eq!(checkpoint.load::<Vec<usize>>().unwrap(), Some(vec![2]));
+
+        checkpoint.clear().unwrap();
+        assert!(!path.exists());
+    }
+}
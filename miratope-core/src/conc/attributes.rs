@@ -0,0 +1,416 @@
+//! Attaches arbitrary key-value attributes to the elements of a polytope, and
+//! carries them through the operations that build new polytopes out of old
+//! ones, so that one can keep track of where each element of a construction
+//! came from.
+//!
+//! The attributes are stored in the [`Metadata`](super::metadata::Metadata) of
+//! a polytope, and get propagated as follows:
+//!
+//! * In a dual, each element keeps the attributes of the element it's dual to.
+//! * In a product, each element gets the attributes of both of the elements
+//!   it's the product of. If both set the same key, the first factor wins.
+//! * In a cross-section, each element gets the attributes of the element it's
+//!   a section of.
+//!
+//! Any other operation drops them.
+
+use std::collections::BTreeMap;
+
+use super::Concrete;
+use crate::{
+    abs::{elements::ElementRef, rank::Rank, Abstract},
+    Polytope,
+};
+
+#[cfg(feature = "serde-serialize")]
+use serde::{Deserialize, Serialize};
+
+/// The attributes of a single element.
+pub type Attributes = BTreeMap<String, String>;
+
+/// The attributes of all of the elements of a polytope. Elements without any
+/// attributes aren't stored.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub struct ElementAttributes(BTreeMap<ElementRef, Attributes>);
+
+impl ElementAttributes {
+    /// Returns whether no element has any attributes.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the attributes of an element, if it has any.
+    pub fn get(&self, el: ElementRef) -> Option<&Attributes> {
+        self.0.get(&el)
+    }
+
+    /// Returns the value of an attribute of an element, if it's set.
+    pub fn get_value(&self, el: ElementRef, key: &str) -> Option<&str> {
+        self.get(el)?.get(key).map(String::as_str)
+    }
+
+    /// Sets an attribute of an element, and returns its old value.
+    pub fn insert(&mut self, el: ElementRef, key: String, value: String) -> Option<String> {
+        self.0.entry(el).or_default().insert(key, value)
+    }
+
+    /// Removes an attribute of an element, and returns its old value.
+    pub fn remove(&mut self, el: ElementRef, key: &str) -> Option<String> {
+        let attributes = self.0.get_mut(&el)?;
+        let value = attributes.remove(key);
+        if attributes.is_empty() {
+            self.0.remove(&el);
+        }
+        value
+    }
+
+    /// Returns an iterator over the elements with attributes, along with them.
+    pub fn iter(&self) -> impl Iterator<Item = (&ElementRef, &Attributes)> {
+        self.0.iter()
+    }
+
+    /// Adds some attributes to an element, overwriting any that are already
+    /// set.
+    fn extend(&mut self, el: ElementRef, attributes: &Attributes) {
+        self.0
+            .entry(el)
+            .or_default()
+            .extend(attributes.iter().map(|(k, v)| (k.clone(), v.clone())));
+    }
+
+    /// Returns the attributes of the dual of a polytope of a given rank.
+    pub fn dual(&self, rank: Rank) -> Self {
+        Self(
+            self.0
+                .iter()
+                .map(|(el, attributes)| {
+                    let dual_rank = Rank::new(rank.into_isize() - 1 - el.rank.into_isize());
+                    (ElementRef::new(dual_rank, el.idx), attributes.clone())
+                })
+                .collect(),
+        )
+    }
+
+    /// Returns the attributes of the [product](Abstract::product) of two
+    /// polytopes, with the same `min` and `max` flags.
+    pub fn product(
+        p_attributes: &Self,
+        q_attributes: &Self,
+        p: &Abstract,
+        q: &Abstract,
+        min: bool,
+        max: bool,
+    ) -> Self {
+        let index = ProductIndex::new(p, q, min, max);
+        let mut product = Self::default();
+
+        // The first factor is added last, so that it overwrites the second.
+        for (q_el, attributes) in q_attributes.iter() {
+            for p_rank in Rank::range_inclusive_iter(index.p_low, index.p_hi) {
+                for p_idx in 0..p.el_count(p_rank) {
+                    if let Some(el) = index.get(ElementRef::new(p_rank, p_idx), *q_el) {
+                        product.extend(el, attributes);
+                    }
+                }
+            }
+        }
+
+        for (p_el, attributes) in p_attributes.iter() {
+            for q_rank in Rank::range_inclusive_iter(index.q_low, index.q_hi) {
+                for q_idx in 0..q.el_count(q_rank) {
+                    if let Some(el) = index.get(*p_el, ElementRef::new(q_rank, q_idx)) {
+                        product.extend(el, attributes);
+                    }
+                }
+            }
+        }
+
+        product
+    }
+
+    /// Returns the attributes of a cross-section of a polytope of a given
+    /// rank, given the index of the element that each element of the
+    /// cross-section is a section of. The elements of rank `r` of the
+    /// cross-section come from elements of rank `r + 1`, save for the maximal
+    /// element, which comes from the maximal element.
+    pub fn cross_section(&self, rank: Rank, origins: &[Vec<usize>]) -> Self {
+        let mut section = Self::default();
+
+        for (r, rank_origins) in origins.iter().enumerate() {
+            for (idx, &origin) in rank_origins.iter().enumerate() {
+                let origin_rank = Rank::from(r + 1);
+                if let Some(attributes) = self.get(ElementRef::new(origin_rank, origin)) {
+                    section.extend(ElementRef::new(Rank::from(r), idx), attributes);
+                }
+            }
+        }
+
+        if let Some(attributes) = self.get(ElementRef::new(rank, 0)) {
+            section.extend(ElementRef::new(rank.minus_one(), 0), attributes);
+        }
+
+        section
+    }
+}
+
+/// Finds the indices of the elements of a product of two polytopes, which are
+/// sorted first by the ranks of the factors and then by their indices.
+struct ProductIndex<'a> {
+    /// The first factor.
+    p: &'a Abstract,
+
+    /// The second factor.
+    q: &'a Abstract,
+
+    /// Whether the minimal elements are multiplied.
+    min: bool,
+
+    /// The lowest rank of the elements of p that get multiplied.
+    p_low: Rank,
+
+    /// The highest rank of the elements of p that get multiplied.
+    p_hi: Rank,
+
+    /// The lowest rank of the elements of q that get multiplied.
+    q_low: Rank,
+
+    /// The highest rank of the elements of q that get multiplied.
+    q_hi: Rank,
+}
+
+impl<'a> ProductIndex<'a> {
+    /// Initializes the index for a product with the given flags.
+    fn new(p: &'a Abstract, q: &'a Abstract, min: bool, max: bool) -> Self {
+        let low = Rank::new(-(min as isize));
+        Self {
+            p,
+            q,
+            min,
+            p_low: low,
+            p_hi: p.rank() - Rank::new(!max as isize),
+            q_low: low,
+            q_hi: q.rank() - Rank::new(!max as isize),
+        }
+    }
+
+    /// Returns the element of the product corresponding to a pair of elements,
+    /// or `None` if they don't get multiplied.
+    fn get(&self, p_el: ElementRef, q_el: ElementRef) -> Option<ElementRef> {
+        if p_el.rank < self.p_low
+            || p_el.rank > self.p_hi
+            || q_el.rank < self.q_low
+            || q_el.rank > self.q_hi
+        {
+            return None;
+        }
+
+        // The elements of the same rank that come from pairs of lower ranks in
+        // the first factor are added first.
+        let rank = p_el.rank.into_isize() + q_el.rank.into_isize() + self.min as isize;
+        let mut offset = 0;
+        for p_rank in Rank::range_iter(self.p_low, p_el.rank) {
+            let q_rank = Rank::new(rank - p_rank.into_isize() - self.min as isize);
+            if q_rank >= self.q_low && q_rank <= self.q_hi {
+                offset += self.p.el_count(p_rank) * self.q.el_count(q_rank);
+            }
+        }
+
+        Some(ElementRef::new(
+            Rank::new(rank),
+            offset + p_el.idx * self.q.el_count(q_el.rank) + q_el.idx,
+        ))
+    }
+}
+
+impl Concrete {
+    /// Returns the attributes of the elements of the polytope, if any.
+    pub fn attributes(&self) -> Option<&ElementAttributes> {
+        self.metadata()
+            .map(|metadata| &metadata.attributes)
+            .filter(|attributes| !attributes.is_empty())
+    }
+
+    /// Returns a mutable reference to the attributes of the elements of the
+    /// polytope.
+    pub fn attributes_mut(&mut self) -> &mut ElementAttributes {
+        &mut self.metadata_mut().attributes
+    }
+
+    /// Sets the attributes of the elements of the polytope. Any other metadata
+    /// is kept.
+    pub fn set_attributes(&mut self, attributes: ElementAttributes) {
+        let mut metadata = self.take_metadata().unwrap_or_default();
+        metadata.attributes = attributes;
+        self.set_metadata(metadata);
+    }
+
+    /// Gives a product of two polytopes the attributes of its factors.
+    pub(super) fn with_product_attributes(
+        mut self,
+        p: &Self,
+        q: &Self,
+        min: bool,
+        max: bool,
+    ) -> Self {
+        if p.attributes().is_some() || q.attributes().is_some() {
+            let empty = ElementAttributes::default();
+            self.set_attributes(ElementAttributes::product(
+                p.attributes().unwrap_or(&empty),
+                q.attributes().unwrap_or(&empty),
+                &p.abs,
+                &q.abs,
+                min,
+                max,
+            ));
+        }
+
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        conc::ConcretePolytope,
+        geometry::{Hyperplane, Vector},
+    };
+
+    /// Tags an element of a polytope with a given source.
+    fn tag(poly: &mut Concrete, rank: isize, idx: usize, source: &str) {
+        poly.attributes_mut().insert(
+            ElementRef::new(Rank::new(rank), idx),
+            "source".to_string(),
+            source.to_string(),
+        );
+    }
+
+    /// Returns the elements of a polytope with a given source.
+    fn tagged(poly: &Concrete, source: &str) -> Vec<ElementRef> {
+        poly.attributes()
+            .unwrap()
+            .iter()
+            .filter(|(_, attributes)| attributes.get("source").map(String::as_str) == Some(source))
+            .map(|(el, _)| *el)
+            .collect()
+    }
+
+    #[test]
+    fn dual() {
+        let mut cube = Concrete::hypercube(Rank::new(3));
+        tag(&mut cube, 2, 4, "face");
+        tag(&mut cube, 0, 1, "vertex");
+
+        let octahedron = cube.dual();
+        assert_eq!(
+            tagged(&octahedron, "face"),
+            vec![ElementRef::new(Rank::new(0), 4)]
+        );
+        assert_eq!(
+            tagged(&octahedron, "vertex"),
+            vec![ElementRef::new(Rank::new(2), 1)]
+        );
+    }
+
+    #[test]
+    fn product() {
+        let mut triangle = Concrete::polygon(3);
+        let mut square = Concrete::polygon(4);
+        tag(&mut triangle, 2, 0, "A");
+        tag(&mut square, 2, 0, "B");
+        tag(&mut square, 1, 2, "edge");
+
+        for (min, max) in [(false, true), (true, true), (true, false), (false, false)] {
+            let product = match (min, max) {
+                (false, true) => Concrete::duoprism(&triangle, &square),
+                (true, true) => Concrete::duopyramid(&triangle, &square),
+                (true, false) => Concrete::duotegum(&triangle, &square),
+                (false, false) => Concrete::duocomb(&triangle, &square),
+            };
+
+            // Every element of the product should get the attributes of the
+            // factors, as the product of their element vertices.
+            for (el, attributes) in product.attributes().unwrap().iter() {
+                assert!(el.idx < product.el_count(el.rank), "{}", el);
+                assert!(!attributes.is_empty());
+            }
+
+            // The products of the edge of the square with the elements of the
+            // triangle, save for its face, whose attributes take precedence.
+            let edges = tagged(&product, "edge").len();
+            assert_eq!(edges, if min { 7 } else { 6 });
+        }
+
+        // The triangles of a triangular-square duoprism come from the
+        // square's vertices. The duoprism itself comes from both faces, and
+        // the triangle wins.
+        let duoprism = Concrete::duoprism(&triangle, &square);
+        let a = tagged(&duoprism, "A");
+        assert_eq!(a.len(), 9);
+        let triangles: Vec<_> = a.iter().filter(|el| el.rank == Rank::new(2)).collect();
+        assert_eq!(triangles.len(), 4);
+        for &&el in &triangles {
+            assert_eq!(duoprism.abs.element_vertices(el).unwrap().len(), 3);
+        }
+        assert!(a.contains(&ElementRef::new(Rank::new(4), 0)));
+        assert_eq!(tagged(&duoprism, "B").len(), 6);
+
+        // Prisms keep the attributes of their base, on both bases and on the
+        // prism itself.
+        let mut cube = Concrete::hypercube(Rank::new(3));
+        tag(&mut cube, 3, 0, "cube");
+        let prism = cube.prism();
+        assert_eq!(tagged(&prism, "cube").len(), 3);
+    }
+
+    #[test]
+    fn cross_section() {
+        let mut cube = Concrete::hypercube(Rank::new(3));
+        for idx in 0..6 {
+            tag(&mut cube, 2, idx, &idx.to_string());
+        }
+        tag(&mut cube, 3, 0, "cube");
+
+        // Each side of the square comes from a face of the cube.
+        let slice = Hyperplane::new(Vector::from(vec![0.0, 0.0, 1.0]), 0.0);
+        let square = cube.cross_section(&slice);
+        let attributes = square.attributes().unwrap();
+        let mut sources = Vec::new();
+        for idx in 0..4 {
+            let source = attributes
+                .get_value(ElementRef::new(Rank::new(1), idx), "source")
+                .unwrap();
+            sources.push(source.parse::<usize>().unwrap());
+        }
+        assert_eq!(
+            tagged(&square, "cube"),
+            vec![ElementRef::new(Rank::new(2), 0)]
+        );
+
+        // These are the faces that aren't horizontal.
+        sources.sort_unstable();
+        sources.dedup();
+        assert_eq!(sources.len(), 4);
+        for source in sources {
+            let vertices = cube
+                .abs
+                .element_vertices(ElementRef::new(Rank::new(2), source))
+                .unwrap();
+            let zs: Vec<_> = vertices.iter().map(|&v| cube.vertices[v][2]).collect();
+            assert!(zs.iter().any(|&z| z > 0.0) && zs.iter().any(|&z| z < 0.0));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "serde-serialize")]
+    fn off_roundtrip() {
+        use crate::conc::file::FromFile;
+
+        let mut cube = Concrete::hypercube(Rank::new(3));
+        tag(&mut cube, 2, 3, "face");
+        let off = cube.to_off(Default::default());
+        let loaded = Concrete::from_off(&off).unwrap();
+        assert_eq!(loaded.attributes(), cube.attributes());
+    }
+}
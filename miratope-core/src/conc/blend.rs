@@ -0,0 +1,290 @@
+//! Blends two polytopes that share a facet, i.e. glues them along it and
+//! removes it.
+
+use super::{Concrete, ConcretePolytope};
+use crate::{
+    abs::{elements::ElementRef, rank::Rank},
+    geometry::{Point, Subspace, Vector},
+    Consts, Float, Polytope,
+};
+
+use vec_like::VecLike;
+
+/// Any error encountered while blending two polytopes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlendError {
+    /// The polytopes don't have the same rank, or they have rank less than 2.
+    Rank,
+
+    /// One of the polytopes has no facet with the given index.
+    InvalidFacet(usize),
+
+    /// The facets don't coincide.
+    Mismatch,
+
+    /// The polytopes lie on the same side of the shared facet.
+    Overlap,
+
+    /// The facets on both sides of the ridge with a given index on the first
+    /// polytope would be coplanar.
+    Coplanar(usize),
+
+    /// The facets on both sides of the ridge with a given index on the first
+    /// polytope would form a reflex angle.
+    Reflex(usize),
+}
+
+impl std::fmt::Display for BlendError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Rank => write!(f, "polytopes don't have the same rank"),
+            Self::InvalidFacet(idx) => write!(f, "no facet with index {}", idx),
+            Self::Mismatch => write!(f, "facets don't coincide"),
+            Self::Overlap => write!(f, "polytopes lie on the same side of the facet"),
+            Self::Coplanar(idx) => write!(f, "facets at ridge {} would be coplanar", idx),
+            Self::Reflex(idx) => write!(f, "facets at ridge {} would form a reflex angle", idx),
+        }
+    }
+}
+
+impl std::error::Error for BlendError {}
+
+/// The result of blending two polytopes.
+pub type BlendResult<T> = Result<T, BlendError>;
+
+impl Concrete {
+    /// Returns the subspace through some vertices of the polytope.
+    fn subspace_through(&self, vertices: &[usize]) -> Subspace {
+        Subspace::from_points(vertices.iter().map(|&v| &self.vertices[v]))
+    }
+
+    /// Returns the unit normal to a facet pointing away from a given point.
+    fn outer_normal(&self, facet: &[usize], center: &Point) -> Option<Vector> {
+        Some(-self.subspace_through(facet).normal(center)?)
+    }
+
+    /// Blends two polytopes that share a facet, i.e. glues them along the
+    /// facet with index `facet_p` of `p` and the facet with index `facet_q` of
+    /// `q`, which must coincide, and then removes both. The polytopes must lie
+    /// on opposite sides of the facet.
+    ///
+    /// Fails if the facets at some ridge of the shared facet would be coplanar
+    /// or would form a reflex angle, which assumes both polytopes are convex.
+    /// Use [`Self::blend_with`] to allow these.
+    pub fn blend(p: &Self, q: &Self, facet_p: usize, facet_q: usize) -> BlendResult<Self> {
+        Self::blend_with(p, q, facet_p, facet_q, false)
+    }
+
+    /// Blends two polytopes that share a facet, as in [`Self::blend`]. If
+    /// `allow_degenerate` is set, ridges at which the facets are coplanar or
+    /// form a reflex angle are allowed. Coplanar facets can be then merged
+    /// with [`Self::merge_coplanar_facets`].
+    pub fn blend_with(
+        p: &Self,
+        q: &Self,
+        facet_p: usize,
+        facet_q: usize,
+        allow_degenerate: bool,
+    ) -> BlendResult<Self> {
+        let rank = p.rank();
+        if q.rank() != rank || rank < Rank::new(2) {
+            return Err(BlendError::Rank);
+        }
+        for (poly, facet) in [(p, facet_p), (q, facet_q)] {
+            if facet >= poly.facet_count() {
+                return Err(BlendError::InvalidFacet(facet));
+            }
+        }
+
+        let facet_rank = rank.minus_one();
+        let ridge_rank = facet_rank.minus_one();
+        let shared = p
+            .coinciding_vertices(facet_p, q, facet_q)
+            .ok_or(BlendError::Mismatch)?;
+
+        // Checks that the polytopes lie on opposite sides of the facet.
+        let center_p = p.gravicenter().unwrap();
+        let center_q = q.gravicenter().unwrap();
+        let facet_vertices = p
+            .abs
+            .element_vertices(ElementRef::new(facet_rank, facet_p))
+            .unwrap();
+        let normal = p
+            .outer_normal(&facet_vertices, &center_p)
+            .ok_or(BlendError::Overlap)?;
+        let offset = &p.vertices[facet_vertices[0]];
+        if (&center_q - offset).dot(&normal) < Float::EPS {
+            return Err(BlendError::Overlap);
+        }
+
+        // Checks the angle between the facets at every ridge of the facet.
+        if !allow_degenerate {
+            for &ridge in &p[facet_rank][facet_p].subs {
+                let ridge_ref = ElementRef::new(ridge_rank, ridge);
+                let mut ridge_vertices = p.abs.element_vertices(ridge_ref).unwrap();
+                ridge_vertices.sort_unstable();
+
+                // The facets next to the shared one on either polytope.
+                let other_facet = |poly: &Self, facet: usize, ridge: usize| {
+                    poly[ridge_rank][ridge]
+                        .sups
+                        .iter()
+                        .copied()
+                        .find(|&f| f != facet)
+                };
+                let facet_a = other_facet(p, facet_p, ridge).ok_or(BlendError::Mismatch)?;
+                let ridge_q = q[facet_rank][facet_q]
+                    .subs
+                    .iter()
+                    .copied()
+                    .find(|&r| {
+                        let mut vertices: Vec<_> = q
+                            .abs
+                            .element_vertices(ElementRef::new(ridge_rank, r))
+                            .unwrap()
+                            .into_iter()
+                            .map(|v| shared[&v])
+                            .collect();
+                        vertices.sort_unstable();
+                        vertices == ridge_vertices
+                    })
+                    .ok_or(BlendError::Mismatch)?;
+                let facet_b = other_facet(q, facet_q, ridge_q).ok_or(BlendError::Mismatch)?;
+
+                // The direction from the ridge into the facet of q.
+                let subspace = p.subspace_through(&ridge_vertices);
+                let b_vertices = q
+                    .abs
+                    .element_vertices(ElementRef::new(facet_rank, facet_b))
+                    .unwrap();
+                let dir = b_vertices
+                    .iter()
+                    .find_map(|&v| subspace.normal(&q.vertices[v]))
+                    .ok_or(BlendError::Mismatch)?;
+
+                let a_vertices = p
+                    .abs
+                    .element_vertices(ElementRef::new(facet_rank, facet_a))
+                    .unwrap();
+                let normal_a = p
+                    .outer_normal(&a_vertices, &center_p)
+                    .ok_or(BlendError::Mismatch)?;
+
+                let dot = dir.dot(&normal_a);
+                if dot > Float::EPS {
+                    return Err(BlendError::Reflex(ridge));
+                } else if dot > -Float::EPS {
+                    return Err(BlendError::Coplanar(ridge));
+                }
+            }
+        }
+
+        // Glues the polytopes.
+        let mut vertices = p.vertices.clone();
+        let mut vertex_map = Vec::with_capacity(q.vertices.len());
+        for (v, vertex) in q.vertices.iter().enumerate() {
+            if let Some(&w) = shared.get(&v) {
+                vertex_map.push(w);
+            } else {
+                vertex_map.push(vertices.len());
+                vertices.push(vertex.clone());
+            }
+        }
+
+        let abs = p
+            .glue_lattice(q, &[(facet_p, facet_q)], &vertex_map, vertices.len())
+            .ok_or(BlendError::Mismatch)?;
+        Ok(Self::new(vertices, abs))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conc::convex::convex_hull;
+
+    /// Returns the index of the facet of a polytope whose vertices all have a
+    /// given first coordinate.
+    fn facet_at(poly: &Concrete, x: Float) -> usize {
+        (0..poly.facet_count())
+            .find(|&idx| {
+                poly.element_vertices_ref(ElementRef::new(poly.rank().minus_one(), idx))
+                    .unwrap()
+                    .iter()
+                    .all(|v| (v[0] - x).abs() < Float::EPS)
+            })
+            .unwrap()
+    }
+
+    /// Returns the convex hull of two squares on the planes `x = x0` and
+    /// `x = x1`, with sides `s0` and `s1`.
+    fn frustum(x0: Float, s0: Float, x1: Float, s1: Float) -> Concrete {
+        let mut points = Vec::new();
+        for (x, s) in [(x0, s0), (x1, s1)] {
+            for (y, z) in [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)] {
+                points.push(Point::from(vec![x, y * s / 2.0, z * s / 2.0]));
+            }
+        }
+        convex_hull(points)
+    }
+
+    #[test]
+    fn pyramid() {
+        let cube = Concrete::hypercube(Rank::new(3));
+        let pyramid = convex_hull(vec![
+            Point::from(vec![-0.8, 0.0, 0.0]),
+            Point::from(vec![-0.5, -0.5, -0.5]),
+            Point::from(vec![-0.5, 0.5, -0.5]),
+            Point::from(vec![-0.5, 0.5, 0.5]),
+            Point::from(vec![-0.5, -0.5, 0.5]),
+        ]);
+
+        let blend = Concrete::blend(
+            &cube,
+            &pyramid,
+            facet_at(&cube, -0.5),
+            facet_at(&pyramid, -0.5),
+        )
+        .unwrap();
+        blend.abs.is_valid().unwrap();
+        assert_eq!(blend.el_counts(), vec![1, 9, 16, 9, 1].into());
+    }
+
+    #[test]
+    fn degenerate() {
+        let cube = Concrete::hypercube(Rank::new(3));
+        let facet = facet_at(&cube, -0.5);
+
+        // Two cubes make a cuboid.
+        let other = frustum(-1.5, 1.0, -0.5, 1.0);
+        let other_facet = facet_at(&other, -0.5);
+        assert!(matches!(
+            Concrete::blend(&cube, &other, facet, other_facet),
+            Err(BlendError::Coplanar(_))
+        ));
+
+        let mut cuboid = Concrete::blend_with(&cube, &other, facet, other_facet, true).unwrap();
+        cuboid.abs.is_valid().unwrap();
+        assert_eq!(cuboid.el_counts(), vec![1, 12, 20, 10, 1].into());
+        cuboid.merge_coplanar_facets(Float::EPS);
+        assert_eq!(cuboid.el_counts(), vec![1, 8, 12, 6, 1].into());
+
+        // A frustum that widens away from the cube.
+        let other = frustum(-1.0, 2.0, -0.5, 1.0);
+        assert!(matches!(
+            Concrete::blend(&cube, &other, facet, facet_at(&other, -0.5)),
+            Err(BlendError::Reflex(_))
+        ));
+
+        // A polytope on the same side of the facet.
+        let other = frustum(0.0, 1.0, -0.5, 1.0);
+        assert_eq!(
+            Concrete::blend(&cube, &other, facet, facet_at(&other, -0.5)).err(),
+            Some(BlendError::Overlap)
+        );
+        assert_eq!(
+            Concrete::blend(&cube, &other, facet, 8).err(),
+            Some(BlendError::InvalidFacet(8))
+        );
+    }
+}
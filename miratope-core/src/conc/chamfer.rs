@@ -0,0 +1,160 @@
+//! Builds the concrete chamfer and loft of a polyhedron. See
+//! [`Abstract::chamfer`](crate::abs::Abstract::chamfer) for the combinatorial
+//! side of the constructions.
+
+use super::{Concrete, ConcretePolytope};
+use crate::{
+    abs::rank::Rank,
+    geometry::{Point, Subspace, Vector},
+    Consts, Float,
+};
+
+use vec_like::VecLike;
+
+impl Concrete {
+    /// Returns the centroid of the vertices of every face, along with the
+    /// unit normal pointing away from the gravicenter, if it can be found.
+    fn face_centroids_and_normals(&self, face_vertices: &[Vec<usize>]) -> Vec<(Point, Vector)> {
+        let center = self.gravicenter().unwrap();
+        let dim = center.len();
+
+        face_vertices
+            .iter()
+            .map(|vertices| {
+                let mut centroid = Point::zeros(dim);
+                for &v in vertices {
+                    centroid += &self.vertices[v];
+                }
+                centroid /= vertices.len() as Float;
+
+                let normal = Subspace::from_points(vertices.iter().map(|&v| &self.vertices[v]))
+                    .normal(&center)
+                    .map(|normal| -normal)
+                    .unwrap_or_else(|| Vector::zeros(dim));
+
+                (centroid, normal)
+            })
+            .collect()
+    }
+
+    /// Returns the [chamfer](https://en.wikipedia.org/wiki/Chamfer_(geometry))
+    /// of a polyhedron, or `None` if the polytope isn't a polyhedron.
+    ///
+    /// The original vertices stay in place. Every face is shrunk towards its
+    /// centroid by a factor of `1 - t`, and then pushed outwards, by just
+    /// enough so that the hexagons are planar if the polyhedron is
+    /// edge-transitive.
+    pub fn chamfer(&self, t: Float) -> Option<Self> {
+        let abs = self.abs.chamfer()?;
+        let face_vertices = self.abs.face_vertex_lists();
+        let centers = self.face_centroids_and_normals(&face_vertices);
+        let center = self.gravicenter()?;
+
+        let mut vertices = self.vertices.clone();
+        for (f, face) in self[Rank::new(2)].iter().enumerate() {
+            let (centroid, normal) = &centers[f];
+
+            // The hexagon on an edge of the face should be perpendicular to
+            // the line from the center to the edge's midpoint.
+            let edge = &self[Rank::new(1)][face.subs[0]];
+            let v = &self.vertices[edge.subs[0]];
+            let midpoint = (v + &self.vertices[edge.subs[1]]) / 2.0 - &center;
+            let height = match normal.dot(&midpoint) {
+                dot if dot.abs() > Float::EPS => t * (v - centroid).dot(&midpoint) / dot,
+                _ => 0.0,
+            };
+
+            for &v in &face_vertices[f] {
+                let v = &self.vertices[v];
+                vertices.push(v + (centroid - v) * t + normal * height);
+            }
+        }
+
+        Some(Self::new(vertices, abs))
+    }
+
+    /// Returns the loft of a polyhedron, or `None` if the polytope isn't a
+    /// polyhedron. The copy of every face is the face shrunk towards its
+    /// centroid by a factor of `1 - t`, and then pushed outwards along its
+    /// normal by a distance of `height`.
+    ///
+    /// With a height of zero, every copy lies on the same plane as its face,
+    /// so the quadrilaterals between them are flattened.
+    pub fn loft(&self, t: Float, height: Float) -> Option<Self> {
+        let abs = self.abs.loft()?;
+        let face_vertices = self.abs.face_vertex_lists();
+        let centers = self.face_centroids_and_normals(&face_vertices);
+
+        let mut vertices = self.vertices.clone();
+        for (vertex_list, (centroid, normal)) in face_vertices.iter().zip(&centers) {
+            for &v in vertex_list {
+                let v = &self.vertices[v];
+                vertices.push(v + (centroid - v) * t + normal * height);
+            }
+        }
+
+        Some(Self::new(vertices, abs))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{abs::elements::ElementRef, Polytope};
+
+    /// Checks that every face of a polyhedron is planar.
+    fn assert_planar(poly: &Concrete) {
+        for idx in 0..poly.el_count(Rank::new(2)) {
+            let vertices = poly
+                .element_vertices_ref(ElementRef::new(Rank::new(2), idx))
+                .unwrap();
+            let subspace = Subspace::from_points(vertices.into_iter());
+            assert_eq!(subspace.rank(), 2, "Face {} isn't planar.", idx);
+        }
+    }
+
+    #[test]
+    fn chamfer() {
+        for poly in [
+            Concrete::simplex(Rank::new(3)),
+            Concrete::hypercube(Rank::new(3)),
+            Concrete::orthoplex(Rank::new(3)),
+        ] {
+            let chamfer = poly.chamfer(0.3).unwrap();
+            assert_eq!(
+                chamfer.el_count(Rank::new(2)),
+                poly.el_count(Rank::new(2)) + poly.el_count(Rank::new(1))
+            );
+            assert_planar(&chamfer);
+        }
+
+        assert!(Concrete::polygon(5).chamfer(0.3).is_none());
+    }
+
+    #[test]
+    fn loft() {
+        let cube = Concrete::hypercube(Rank::new(3));
+        let loft = cube.loft(0.5, 0.0).unwrap();
+        assert_planar(&loft);
+
+        // The copy of a face of a unit cube is a square of half the size.
+        let copy: Vec<_> = loft.vertices[8..12].to_vec();
+        let mut side = Float::INFINITY;
+        for v in &copy {
+            for w in &copy {
+                let dist = (v - w).norm();
+                if dist > 0.0 && dist < side {
+                    side = dist;
+                }
+            }
+        }
+        assert!((side - 0.5).abs() < 1e-9);
+
+        // Lifting the copies adds a square frustum onto every face, each of
+        // volume 7/48.
+        let mut loft = cube.loft(0.5, 0.25).unwrap();
+        assert_planar(&loft);
+        loft.abs_sort();
+        assert!((loft.volume().unwrap() - (1.0 + 6.0 * 7.0 / 48.0)).abs() < 1e-9);
+    }
+}
@@ -1,192 +1,875 @@
-use std::collections::BTreeSet;
-use std::f64::NEG_INFINITY;
-
-use super::Concrete;
-use petgraph::{graph::NodeIndex, Directed, Direction, Graph};
-
-/// An entry in the priority queue used in Shell.
-enum QueueEntry<'a> {
-    /// Represents the event where at a certain time, the first facet that
-    /// contains a certain point becomes visible. This facet will have the
-    /// specified vertices and normal vector.
-    Point {
-        time: f64,
-        normal: Vector,
-        point: Point,
-        vertices: Vec<Point>,
-    },
+//! Contains the code that builds the convex hull of a set of points.
+//!
+//! The facets are found through an incremental
+//! [quickhull](https://en.wikipedia.org/wiki/Quickhull), which works in any
+//! dimension. This first builds a hull whose facets are all simplices, and
+//! then merges those that lie on the same hyperplane, so that coplanar points
+//! are handled gracefully. Points that don't span the whole space are first
+//! flattened into the subspace they span. The rest of the face lattice is then
+//! recovered from the facets.
+
+use std::collections::{BTreeSet, HashMap};
 
-    /// Represents the event where at a certain time, a facet containing a
-    /// horizon peak and the horizon ridges specified by an element's neighbors
-    /// becomes visible. This facet will have the specified normal vector.
-    Peak {
-        time: f64,
-        normal: Vector,
-        element: ShellElement<'a>,
+use super::{coplanar::find, Concrete};
+use crate::{
+    abs::{
+        elements::{AbstractBuilder, SubelementList, Subelements},
+        rank::Rank,
     },
+    geometry::{Point, PointOrd, Subspace, Vector},
+    Consts, Float, Polytope,
+};
+
+use itertools::Itertools;
+use vec_like::VecLike;
+
+/// A simplicial facet of a hull that's being built.
+struct QuickFacet {
+    /// The sorted indices of the points that span the facet.
+    vertices: Vec<usize>,
+
+    /// The outwards unit normal of the facet.
+    normal: Vector,
+
+    /// The distance from the origin to the hyperplane of the facet, along the
+    /// normal.
+    offset: Float,
+
+    /// The points that lie outside of this facet, which haven't been assigned
+    /// to any other facet.
+    outside: Vec<usize>,
+
+    /// The points that lie on this facet without being any of its vertices,
+    /// and that haven't been assigned to any other facet.
+    coplanar: Vec<usize>,
+
+    /// Whether the facet is still part of the hull.
+    alive: bool,
 }
 
-impl<'a> QueueEntry<'a> {
-    /// Returns the time associated with an event.
-    pub fn time(&self) -> f64 {
-        match self {
-            QueueEntry::Point { time: t, .. } => *t,
-            QueueEntry::Peak { time: t, .. } => *t,
+impl QuickFacet {
+    /// Builds a facet through some points, with the normal pointing away from
+    /// a point in the interior of the hull.
+    fn new(points: &[Point], mut vertices: Vec<usize>, interior: &Point) -> Self {
+        vertices.sort_unstable();
+        let subspace = Subspace::from_points(vertices.iter().map(|&v| &points[v]));
+        let normal = -subspace
+            .normal(interior)
+            .expect("The interior of a hull can't lie on a facet.");
+        let offset = normal.dot(&subspace.offset);
+
+        Self {
+            vertices,
+            normal,
+            offset,
+            outside: Vec::new(),
+            coplanar: Vec::new(),
+            alive: true,
         }
     }
-}
 
-impl<'a> PartialEq for QueueEntry<'a> {
-    fn eq(&self, other: &Self) -> bool {
-        self.time() == other.time()
+    /// Returns the signed distance from a point to the facet, which is
+    /// positive outside of the hull.
+    fn distance(&self, p: &Point) -> Float {
+        self.normal.dot(p) - self.offset
+    }
+
+    /// Returns the ridges of the facet, as sorted sets of points.
+    fn ridges(&self) -> impl Iterator<Item = Vec<usize>> + '_ {
+        (0..self.vertices.len()).map(move |i| {
+            let mut ridge = self.vertices.clone();
+            ridge.remove(i);
+            ridge
+        })
     }
 }
 
-impl<'a> Eq for QueueEntry<'a> {}
+/// Finds a simplex of full rank among a set of points of full rank, by
+/// repeatedly taking the point that's farthest from the subspace spanned by
+/// the points found so far.
+fn initial_simplex(points: &[Point], dim: usize) -> Vec<usize> {
+    let mut simplex = vec![0];
+    let mut subspace = Subspace::new(points[0].clone());
 
-impl<'a> PartialOrd for QueueEntry<'a> {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        self.time().partial_cmp(&other.time())
+    while simplex.len() <= dim {
+        let (idx, _) = points
+            .iter()
+            .map(|p| subspace.distance(p))
+            .enumerate()
+            .max_by(|(_, x), (_, y)| x.partial_cmp(y).unwrap())
+            .unwrap();
+
+        subspace.add(&points[idx]);
+        simplex.push(idx);
     }
+
+    simplex
 }
 
-impl<'a> Ord for QueueEntry<'a> {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.partial_cmp(other).unwrap()
+/// Assigns each of a set of points to the facet it lies the farthest outside
+/// of, or otherwise to some facet it lies on, if any.
+fn assign_outside(
+    points: &[Point],
+    facets: &mut [QuickFacet],
+    candidates: &[usize],
+    new_facets: &[usize],
+) {
+    for &idx in candidates {
+        let best = new_facets
+            .iter()
+            .map(|&f| (f, facets[f].distance(&points[idx])))
+            .filter(|&(_, dist)| dist > Float::EPS)
+            .max_by(|(_, x), (_, y)| x.partial_cmp(y).unwrap());
+
+        if let Some((f, _)) = best {
+            facets[f].outside.push(idx);
+        } else if let Some(&f) = new_facets
+            .iter()
+            .find(|&&f| facets[f].distance(&points[idx]).abs() <= Float::EPS)
+        {
+            facets[f].coplanar.push(idx);
+        }
     }
 }
 
-/// The metadata returned after deleting an element from a [`ShellQueue`]. This
-/// data specifies the minimum time in the queue, the union of all
-/// [`Point`](ShellQueueEntry::Point) and [`Peak`](ShellQueueEntry::Peak)
-/// entries at this time, and their common normal vector.
-struct QueueData<'a> {
-    time: f64,
-    normal: Vector,
-    vertices: Vec<Point>,
-    elements: Vec<ShellElement<'a>>,
-    points: Vec<Point>,
-}
+/// Finds the facets of the convex hull of a set of points of full rank, as the
+/// sorted sets of indices of the points that lie on each of them. Points that
+/// lie on a facet but aren't vertices are also included.
+fn facets(points: &[Point], dim: usize) -> BTreeSet<Vec<usize>> {
+    let simplex = initial_simplex(points, dim);
+    let mut interior = Point::zeros(dim);
+    for &idx in &simplex {
+        interior += &points[idx];
+    }
+    interior /= simplex.len() as Float;
+
+    // The facets that contain each ridge.
+    let mut ridges: HashMap<Vec<usize>, Vec<usize>> = HashMap::new();
+    let mut facets: Vec<QuickFacet> = Vec::new();
 
-#[derive(Deref, DerefMut)]
-struct Queue<'a>(BTreeSet<QueueEntry<'a>>);
+    let add_facet = |facets: &mut Vec<QuickFacet>,
+                     ridges: &mut HashMap<Vec<usize>, Vec<usize>>,
+                     vertices: Vec<usize>| {
+        let facet = QuickFacet::new(points, vertices, &interior);
+        for ridge in facet.ridges() {
+            ridges.entry(ridge).or_default().push(facets.len());
+        }
+        facets.push(facet);
+        facets.len() - 1
+    };
 
-impl<'a> Queue<'a> {
-    pub fn new() -> Self {
-        Self(BTreeSet::new())
+    let mut new_facets = Vec::new();
+    for i in 0..simplex.len() {
+        let mut vertices = simplex.clone();
+        vertices.remove(i);
+        new_facets.push(add_facet(&mut facets, &mut ridges, vertices));
     }
 
-    pub fn delete_min() -> QueueData<'a> {
-        todo!()
+    let candidates: Vec<_> = (0..points.len())
+        .filter(|idx| !simplex.contains(idx))
+        .collect();
+    assign_outside(points, &mut facets, &candidates, &new_facets);
+
+    let mut stack = new_facets;
+    while let Some(f) = stack.pop() {
+        if !facets[f].alive || facets[f].outside.is_empty() {
+            continue;
+        }
+
+        // The point farthest outside of the facet.
+        let apex = *facets[f]
+            .outside
+            .iter()
+            .max_by(|&&x, &&y| {
+                let dist_x = facets[f].distance(&points[x]);
+                let dist_y = facets[f].distance(&points[y]);
+                dist_x.partial_cmp(&dist_y).unwrap()
+            })
+            .unwrap();
+
+        // Finds the facets that can see the apex, and the ridges on the
+        // boundary of that region.
+        let mut visible = vec![f];
+        let mut is_visible = HashMap::new();
+        is_visible.insert(f, true);
+        let mut horizon = Vec::new();
+        let mut idx = 0;
+        while let Some(&g) = visible.get(idx) {
+            idx += 1;
+
+            for ridge in facets[g].ridges() {
+                for &h in &ridges[&ridge] {
+                    if h == g {
+                        continue;
+                    }
+
+                    let h_visible = *is_visible
+                        .entry(h)
+                        .or_insert_with(|| facets[h].distance(&points[apex]) > Float::EPS);
+                    if h_visible {
+                        if !visible.contains(&h) {
+                            visible.push(h);
+                        }
+                    } else {
+                        horizon.push(ridge.clone());
+                    }
+                }
+            }
+        }
+
+        // Removes the visible facets.
+        let mut orphans = Vec::new();
+        for &g in &visible {
+            for ridge in facets[g].ridges() {
+                let ridge_facets = ridges.get_mut(&ridge).unwrap();
+                ridge_facets.retain(|&h| h != g);
+                if ridge_facets.is_empty() {
+                    ridges.remove(&ridge);
+                }
+            }
+
+            facets[g].alive = false;
+            orphans.append(&mut facets[g].outside);
+            orphans.append(&mut facets[g].coplanar);
+        }
+        orphans.retain(|&idx| idx != apex);
+
+        // Cones the horizon from the apex.
+        let mut new_facets = Vec::with_capacity(horizon.len());
+        for mut ridge in horizon {
+            ridge.push(apex);
+            new_facets.push(add_facet(&mut facets, &mut ridges, ridge));
+        }
+
+        assign_outside(points, &mut facets, &orphans, &new_facets);
+        stack.extend(new_facets);
+    }
+
+    // Groups the simplicial facets on the same hyperplane, by merging any two
+    // neighbors where each lies on the hyperplane of the other.
+    let lies_on = |f: usize, g: usize| {
+        facets[g]
+            .vertices
+            .iter()
+            .all(|&v| facets[f].distance(&points[v]).abs() <= Float::EPS)
+    };
+    let mut parent: Vec<_> = (0..facets.len()).collect();
+    for ridge_facets in ridges.values() {
+        if let [f, g] = *ridge_facets.as_slice() {
+            if lies_on(f, g) && lies_on(g, f) {
+                let f = find(&mut parent, f);
+                parent[f] = find(&mut parent, g);
+            }
+        }
+    }
+
+    // The simplicial facets in every group, and the groups next to each.
+    let mut groups = HashMap::new();
+    let mut members: Vec<Vec<usize>> = Vec::new();
+    for f in (0..facets.len()).filter(|&f| facets[f].alive) {
+        let len = members.len();
+        let group = *groups.entry(find(&mut parent, f)).or_insert(len);
+        if group == len {
+            members.push(Vec::new());
+        }
+        members[group].push(f);
     }
+
+    let mut neighbors = vec![Vec::new(); members.len()];
+    for ridge_facets in ridges.values() {
+        if let [f, g] = *ridge_facets.as_slice() {
+            let a = groups[&find(&mut parent, f)];
+            let b = groups[&find(&mut parent, g)];
+            if a != b {
+                neighbors[a].push(b);
+                neighbors[b].push(a);
+            }
+        }
+    }
+
+    // Every group gets the vertices of its facets and the points on them. A
+    // point on a facet might also lie on the hyperplanes of the groups around
+    // it, which are found by walking through neighbors.
+    let mut merged: Vec<Vec<usize>> = members
+        .iter()
+        .map(|group| {
+            group
+                .iter()
+                .flat_map(|&f| facets[f].vertices.iter().copied())
+                .collect()
+        })
+        .collect();
+
+    for (a, group) in members.iter().enumerate() {
+        for &idx in group.iter().flat_map(|&f| &facets[f].coplanar) {
+            let mut visited = vec![a];
+            let mut stack = vec![a];
+
+            while let Some(b) = stack.pop() {
+                merged[b].push(idx);
+
+                for &c in &neighbors[b] {
+                    if !visited.contains(&c)
+                        && facets[members[c][0]].distance(&points[idx]).abs() <= Float::EPS
+                    {
+                        visited.push(c);
+                        stack.push(c);
+                    }
+                }
+            }
+        }
+    }
+
+    merged
+        .into_iter()
+        .map(|mut group| {
+            group.sort_unstable();
+            group.dedup();
+            group
+        })
+        .collect()
 }
 
-/// An element produced by the Shell algorithm. Note that the data it contains
-/// is quite different from that of a [`Element`](super::Element).
-struct ShellElement<'a> {
-    normal: Vector,
-    neighbors: Vec<NodeIndex>,
-    queue: &'a Queue<'a>,
+/// Returns the intersection of two sorted sets of indices.
+fn intersect(a: &[usize], b: &[usize]) -> Vec<usize> {
+    let mut res = Vec::new();
+    let (mut i, mut j) = (0, 0);
+
+    while i < a.len() && j < b.len() {
+        match a[i].cmp(&b[j]) {
+            std::cmp::Ordering::Less => i += 1,
+            std::cmp::Ordering::Greater => j += 1,
+            std::cmp::Ordering::Equal => {
+                res.push(a[i]);
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+
+    res
 }
 
-struct ShellEdge<'a>(&'a Point);
+/// Returns whether the sorted set `a` is a subset of the sorted set `b`.
+fn is_subset(a: &[usize], b: &[usize]) -> bool {
+    intersect(a, b).len() == a.len()
+}
+
+/// Given the elements of some rank of a convex polytope as sorted sets of
+/// vertices, finds the elements of the rank below, and the subelements of each
+/// element.
+///
+/// This uses the fact that the subelements of an element are exactly the
+/// maximal intersections of it with the other elements of its rank.
+fn lower_rank(elements: &[Vec<usize>]) -> (Vec<Vec<usize>>, SubelementList) {
+    let mut lower = Vec::new();
+    let mut lower_idx = HashMap::new();
+    let mut subelements = SubelementList::with_capacity(elements.len());
 
-struct Line(Vector, Vector);
+    for (i, el) in elements.iter().enumerate() {
+        // All intersections of the element with others.
+        let intersections: BTreeSet<_> = elements
+            .iter()
+            .enumerate()
+            .filter(|&(j, _)| i != j)
+            .map(|(_, other)| intersect(el, other))
+            .filter(|int| !int.is_empty())
+            .collect();
 
-struct ShellPolytope<'a> {
+        // Keeps only the maximal ones.
+        let mut subs = Subelements::new();
+        for int in &intersections {
+            if intersections
+                .iter()
+                .any(|other| other.len() > int.len() && is_subset(int, other))
+            {
+                continue;
+            }
+
+            let len = lower.len();
+            let idx = *lower_idx.entry(int.clone()).or_insert(len);
+            if idx == len {
+                lower.push(int.clone());
+            }
+
+            subs.push(idx);
+        }
+
+        subelements.push(subs);
+    }
+
+    (lower, subelements)
+}
+
+/// Given the facets of a convex polytope as sorted sets of vertices, finds the
+/// elements of every rank from the vertices up to the facets as sorted sets of
+/// vertices, along with the subelements of every element of rank at least 1.
+/// These are returned as lists indexed by rank, where the list of
+/// subelements for the vertices is left empty.
+fn lattice(
+    facets: Vec<Vec<usize>>,
+    vertex_count: usize,
     dim: usize,
-    graph: Graph<ShellElement<'a>, ShellEdge<'a>, Directed>,
+) -> (Vec<Vec<Vec<usize>>>, Vec<SubelementList>) {
+    let mut elements = vec![facets];
+    let mut subelements = Vec::with_capacity(dim);
+
+    for _ in 2..dim {
+        let (lower, subs) = lower_rank(elements.last().unwrap());
+        subelements.push(subs);
+        elements.push(lower);
+    }
+
+    // The edges are given directly by their vertices.
+    subelements.push(
+        elements
+            .last()
+            .unwrap()
+            .iter()
+            .cloned()
+            .map(Subelements)
+            .collect::<Vec<_>>()
+            .into(),
+    );
+    subelements.push(SubelementList::new());
+    elements.push((0..vertex_count).map(|v| vec![v]).collect());
+
+    elements.reverse();
+    subelements.reverse();
+    (elements, subelements)
 }
 
-impl<'a> ShellPolytope<'a> {
-    fn new(dim: usize) -> Self {
-        todo!()
+/// Splits an element of a convex polytope into simplices, given as sorted sets
+/// of vertices. This is done by taking the cones from the first vertex of the
+/// element over the simplices of every subelement that doesn't contain it.
+///
+/// Since the first vertex of an element that contains the first vertex of
+/// some subelement is that same vertex, the simplices of any two elements
+/// always agree on their common subelements.
+fn triangulate(
+    elements: &[Vec<Vec<usize>>],
+    subelements: &[SubelementList],
+    rank: usize,
+    idx: usize,
+) -> Vec<Vec<usize>> {
+    let el = &elements[rank][idx];
+    if el.len() == rank + 1 {
+        return vec![el.clone()];
     }
 
-    fn convex_hull(vertices: Vec<Point>) -> Concrete {
-        let s = Subspace::from_points(&vertices);
+    let apex = el[0];
+    let mut simplices = Vec::new();
+    for &sub in subelements[rank][idx].iter() {
+        if elements[rank - 1][sub].binary_search(&apex).is_err() {
+            for mut simplex in triangulate(elements, subelements, rank - 1, sub) {
+                simplex.insert(0, apex);
+                simplices.push(simplex);
+            }
+        }
+    }
+
+    simplices
+}
+
+/// Determines how [`Hull::new`] reports the facets of a convex hull.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FacetMode {
+    /// Every facet of the convex hull is returned as a single facet.
+    #[default]
+    Merged,
+
+    /// Every facet of the convex hull is split into simplices, so that the
+    /// result is a simplicial polytope. This is done consistently, so that
+    /// simplices of adjacent facets meet along whole ridges.
+    Simplicial,
+}
+
+/// The convex hull of a set of points spanning at least a plane, stored as the
+/// elements of each rank as sets of vertices, together with their
+/// incidences. This allows querying the facets and ridges of the hull without
+/// building a polytope.
+pub struct Hull {
+    /// The vertices of the convex hull.
+    pub vertices: Vec<Point>,
+
+    /// The elements of each rank from the vertices up to the facets, as sorted
+    /// sets of vertices.
+    elements: Vec<Vec<Vec<usize>>>,
+
+    /// The subelements of the elements of each rank from the vertices up to
+    /// the facets. The list for the vertices is empty.
+    subelements: Vec<SubelementList>,
 
-        // A vector that is contained in s, but is in "general position."
-        let y = 0.57 * &s.basis[0] + 0.43 * &s.basis[1];
-        let a = s.orthogonal_comp();
-        let x: Point = vertices.iter().sum::<Point>() / vertices.len() as f64;
+    /// The facets that each ridge lies on.
+    ridge_facets: Vec<Vec<usize>>,
+}
+
+impl Hull {
+    /// Builds the convex hull of a set of points, reporting its facets
+    /// according to a given [`FacetMode`]. Returns `None` if the points don't
+    /// span at least a plane.
+    pub fn new(points: Vec<Point>, mode: FacetMode) -> Option<Self> {
+        // Removes duplicate points.
+        let points: Vec<_> = points
+            .into_iter()
+            .map(PointOrd::new)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .map(|p| p.0)
+            .collect();
+
+        if points.is_empty() {
+            return None;
+        }
+
+        let subspace = Subspace::from_points(points.iter());
+        let dim = subspace.rank();
+        if dim < 2 {
+            return None;
+        }
 
-        let vertices = vertices.iter().collect::<Vec<_>>();
+        let flat_points = subspace.flatten_vec(&points);
+        let mut facets: Vec<_> = facets(&flat_points, dim).into_iter().collect();
 
-        let mut poly = Self::new(vertices[0].nrows());
+        // A point is a vertex iff the intersection of the facets through it is
+        // the point itself.
+        let mut is_vertex = vec![false; points.len()];
+        for (idx, is_vertex) in is_vertex.iter_mut().enumerate() {
+            let mut int: Option<Vec<usize>> = None;
 
-        poly.shell(
-            Line(y, x),
-            NEG_INFINITY,
-            Vec::new(),
-            Vec::new(),
-            vertices.clone(),
+            for facet in facets
+                .iter()
+                .filter(|facet| facet.binary_search(&idx).is_ok())
+            {
+                int = Some(match int {
+                    Some(int) => intersect(&int, facet),
+                    None => facet.clone(),
+                });
+            }
+
+            *is_vertex = matches!(int, Some(int) if int.len() == 1);
+        }
+
+        // Removes all points that aren't vertices.
+        let mut new_idx = Vec::with_capacity(points.len());
+        let mut vertices = Vec::new();
+        for (p, &is_vertex) in points.into_iter().zip(&is_vertex) {
+            new_idx.push(vertices.len());
+            if is_vertex {
+                vertices.push(p);
+            }
+        }
+
+        for facet in &mut facets {
+            *facet = facet
+                .iter()
+                .filter(|&&idx| is_vertex[idx])
+                .map(|&idx| new_idx[idx])
+                .collect();
+        }
+
+        // Finds the elements of each rank, from the facets downwards.
+        let (mut elements, mut subelements) = lattice(facets, vertices.len(), dim);
+
+        if mode == FacetMode::Simplicial {
+            let facets = (0..elements[dim - 1].len())
+                .flat_map(|idx| triangulate(&elements, &subelements, dim - 1, idx))
+                .collect();
+
+            let simplicial = lattice(facets, vertices.len(), dim);
+            elements = simplicial.0;
+            subelements = simplicial.1;
+        }
+
+        let mut ridge_facets = vec![Vec::new(); elements[dim - 2].len()];
+        for (facet, subs) in subelements[dim - 1].iter().enumerate() {
+            for &ridge in subs {
+                ridge_facets[ridge].push(facet);
+            }
+        }
+
+        Some(Self {
             vertices,
-            a,
-        );
+            elements,
+            subelements,
+            ridge_facets,
+        })
+    }
+
+    /// Returns the rank of the convex hull.
+    pub fn rank(&self) -> Rank {
+        Rank::from(self.elements.len())
+    }
+
+    /// Returns the facets of the convex hull, as sorted sets of vertices.
+    pub fn facets(&self) -> &[Vec<usize>] {
+        self.elements.last().unwrap()
+    }
+
+    /// Returns the ridges of the convex hull, as sorted sets of vertices.
+    pub fn ridges(&self) -> &[Vec<usize>] {
+        &self.elements[self.elements.len() - 2]
+    }
+
+    /// Returns the indices of the ridges of a given facet.
+    pub fn facet_ridges(&self, facet: usize) -> &[usize] {
+        &self.subelements.last().unwrap()[facet].0
+    }
 
-        poly.into()
-    }
-
-    fn shell(
-        &mut self,
-        line: Line,
-        time: f64,
-        ff: Vec<NodeIndex>,
-        hr: Vec<NodeIndex>,
-        u: Vec<&'a Point>,
-        t: Vec<&'a Point>,
-        n: Vec<Vector>,
-    ) -> ShellElement<'a> {
-        // Step 1
-        let q = self.graph.add_node(ShellElement {
-            normal: vec![].into(),
-            neighbors: Vec::new(),
-            queue: &Queue::new(),
-        });
-
-        for f in ff {
-            self.graph.add_edge(f, q, weight);
-        }
-
-        // Step 2
-        if t.len() == 1 {
-            let p = t[0];
-
-            self.graph.add_edge(
-                q,
-                if let Some(&e) = ff.get(0) {
-                    e
-                } else {
-                    self.graph.add_node(ShellElement {
-                        normal: Vector::zeros(self.dim),
-                        neighbors: Vec::new(),
-                    })
-                },
-                ShellEdge(&p),
-            );
-        }
-
-        // Step 3
-        let mut hp = Vec::new();
-        for f in hr {
-            for g in self.graph.neighbors_directed(f, Direction::Outgoing) {
-                hp.push(g);
-                self.graph[g].neighbors.push(f);
+    /// Returns the indices of the two facets that a given ridge lies on.
+    pub fn ridge_facets(&self, ridge: usize) -> &[usize] {
+        &self.ridge_facets[ridge]
+    }
+
+    /// Returns the indices of the facets that share a ridge with a given
+    /// facet, in the same order as its ridges.
+    pub fn adjacent_facets(&self, facet: usize) -> impl Iterator<Item = usize> + '_ {
+        self.facet_ridges(facet).iter().map(move |&ridge| {
+            let facets = self.ridge_facets(ridge);
+            if facets[0] == facet {
+                facets[1]
+            } else {
+                facets[0]
             }
+        })
+    }
+
+    /// Builds the polytope out of the convex hull.
+    pub fn build(self) -> Concrete {
+        let mut abs = AbstractBuilder::with_capacity(self.rank());
+        abs.push_min();
+        abs.push_vertices(self.vertices.len());
+
+        for subs in self.subelements.into_iter().skip(1) {
+            abs.push(subs);
         }
-        todo!()
+
+        abs.push_max();
+        Concrete::new(self.vertices, abs.build())
     }
 }
 
-impl<'a> Into<Concrete> for ShellPolytope<'a> {
-    fn into(self) -> Concrete {
-        todo!()
+/// Builds the convex hull of a set of points. The resulting polytope lives in
+/// the same space as the points, and has as its rank the dimension of the
+/// subspace they span.
+pub fn convex_hull(points: Vec<Point>) -> Concrete {
+    convex_hull_with(points, FacetMode::Merged)
+}
+
+/// Builds the convex hull of a set of points, reporting its facets according
+/// to a given [`FacetMode`]. The resulting polytope lives in the same space as
+/// the points, and has as its rank the dimension of the subspace they span.
+pub fn convex_hull_with(points: Vec<Point>, mode: FacetMode) -> Concrete {
+    if points.is_empty() {
+        return Concrete::nullitope();
+    }
+
+    trace_span!("convex_hull", points = points.len(), ?mode);
+    let subspace = Subspace::from_points(points.iter());
+    match subspace.rank() {
+        0 => Concrete::new(vec![points[0].clone()], Concrete::point().abs),
+        1 => {
+            let (min, max) = points
+                .iter()
+                .map(|p| subspace.flatten(p)[0])
+                .enumerate()
+                .minmax_by(|(_, x), (_, y)| x.partial_cmp(y).unwrap())
+                .into_option()
+                .unwrap();
+
+            Concrete::new(
+                vec![points[min.0].clone(), points[max.0].clone()],
+                Concrete::dyad().abs,
+            )
+        }
+        _ => {
+            let hull = Hull::new(points, mode).unwrap().build();
+            trace_event!(elements = ?hull.el_counts(), "built convex hull");
+            hull
+        }
     }
 }
 
-impl Concrete {
-    pub fn convex_hull_plus(&self) -> Concrete {
-        convex_hull(self.vertices.clone())
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Checks that the convex hull of a polytope's vertices has the expected
+    /// element counts.
+    fn test(points: Vec<Point>, el_counts: Vec<usize>) {
+        let hull = convex_hull(points);
+        hull.abs.is_valid().unwrap();
+        assert_eq!(hull.el_counts(), el_counts.into());
+    }
+
+    #[test]
+    fn degenerate() {
+        test(Vec::new(), vec![1]);
+        test(vec![Point::zeros(3); 3], vec![1, 1]);
+        test(
+            vec![
+                Point::zeros(2),
+                nalgebra::dvector![1.0, 1.0],
+                nalgebra::dvector![0.5, 0.5],
+            ],
+            vec![1, 2, 1],
+        );
+    }
+
+    #[test]
+    fn polygon() {
+        let mut points = Concrete::polygon(7).vertices;
+
+        // Adds some points in the interior and on an edge.
+        points.push(Point::zeros(2));
+        points.push((&points[0] + &points[1]) / 2.0);
+        test(points, vec![1, 7, 7, 1]);
+    }
+
+    #[test]
+    fn cube() {
+        let mut points = Concrete::hypercube(Rank::new(3)).vertices;
+
+        // Adds a point in the center of a face.
+        points.push(nalgebra::dvector![0.5, 0.0, 0.0]);
+        test(points, vec![1, 8, 12, 6, 1]);
+    }
+
+    #[test]
+    fn tesseract() {
+        test(
+            Concrete::hypercube(Rank::new(4)).vertices,
+            vec![1, 16, 32, 24, 8, 1],
+        );
+    }
+
+    #[test]
+    fn orthoplex() {
+        test(
+            Concrete::orthoplex(Rank::new(4)).vertices,
+            vec![1, 8, 24, 32, 16, 1],
+        );
+    }
+
+    #[test]
+    fn simplicial() {
+        let cube = convex_hull_with(
+            Concrete::hypercube(Rank::new(3)).vertices,
+            FacetMode::Simplicial,
+        );
+        cube.abs.is_valid().unwrap();
+        assert_eq!(cube.el_counts(), vec![1, 8, 18, 12, 1].into());
+
+        // Every facet of a triangulated tesseract is a tetrahedron.
+        let hull = Hull::new(
+            Concrete::hypercube(Rank::new(4)).vertices,
+            FacetMode::Simplicial,
+        )
+        .unwrap();
+        assert!(hull.facets().iter().all(|facet| facet.len() == 4));
+        assert!(hull.ridges().iter().all(|ridge| ridge.len() == 3));
+
+        let tesseract = hull.build();
+        tesseract.abs.is_valid().unwrap();
+        assert_eq!(tesseract.vertex_count(), 16);
+    }
+
+    #[test]
+    fn adjacency() {
+        let hull = Hull::new(
+            Concrete::hypercube(Rank::new(3)).vertices,
+            FacetMode::Merged,
+        )
+        .unwrap();
+        assert_eq!(hull.rank(), Rank::new(3));
+        assert_eq!(hull.facets().len(), 6);
+        assert_eq!(hull.ridges().len(), 12);
+
+        for facet in 0..6 {
+            assert_eq!(hull.facet_ridges(facet).len(), 4);
+
+            // Every face of a cube is adjacent to all others but its opposite.
+            let mut adjacent: Vec<_> = hull.adjacent_facets(facet).collect();
+            adjacent.sort_unstable();
+            adjacent.dedup();
+            assert_eq!(adjacent.len(), 4);
+            assert!(!adjacent.contains(&facet));
+        }
+
+        for ridge in 0..12 {
+            assert_eq!(hull.ridge_facets(ridge).len(), 2);
+        }
+
+        assert!(Hull::new(vec![Point::zeros(3); 3], FacetMode::Merged).is_none());
+    }
+
+    #[test]
+    fn grid() {
+        // Every point of a 3 × 3 × 3 grid lies on the boundary of the cube,
+        // save for the center.
+        let mut points = Vec::new();
+        for x in 0..3 {
+            for y in 0..3 {
+                for z in 0..3 {
+                    points.push(nalgebra::dvector![x as Float, y as Float, z as Float]);
+                }
+            }
+        }
+        test(points.clone(), vec![1, 8, 12, 6, 1]);
+
+        // The same grid, on a plane in 4D.
+        let points = points
+            .iter()
+            .filter(|p| p[2] == 0.0)
+            .map(|p| nalgebra::dvector![p[0], p[1], p[0] + p[1], 1.0])
+            .collect();
+        test(points, vec![1, 4, 4, 1]);
+    }
+
+    #[test]
+    fn penteract() {
+        test(
+            Concrete::hypercube(Rank::new(5)).vertices,
+            vec![1, 32, 80, 80, 40, 10, 1],
+        );
+    }
+
+    #[test]
+    fn sphere() {
+        // Many random points on a sphere, and some in its interior.
+        let mut rng = crate::rng::SeededRng::new(1);
+        let mut points = Vec::new();
+        for i in 0..400 {
+            let p = nalgebra::dvector![
+                rng.next_float() - 0.5,
+                rng.next_float() - 0.5,
+                rng.next_float() - 0.5
+            ];
+            let p = p.normalize();
+            points.push(if i % 4 == 0 { p / 2.0 } else { p });
+        }
+
+        let hull = convex_hull(points);
+        hull.abs.is_valid().unwrap();
+        assert_eq!(hull.vertex_count(), 300);
+
+        // Euler's formula.
+        let counts = hull.el_counts();
+        assert_eq!(
+            counts[Rank::new(0)] + counts[Rank::new(2)],
+            counts[Rank::new(1)] + 2
+        );
+    }
+
+    #[test]
+    fn flat_simplex() {
+        // A triangle embedded in 3D space.
+        let triangle = Concrete::simplex(Rank::new(2)).vertices;
+        let points = triangle
+            .iter()
+            .map(|p| nalgebra::dvector![p[0], p[1], 0.0])
+            .collect();
+
+        test(points, vec![1, 3, 3, 1]);
     }
 }
@@ -0,0 +1,299 @@
+//! Merges adjacent facets of a polytope that lie on the same hyperplane, such
+//! as the triangles that result from triangulating a polygon.
+
+use super::Concrete;
+use crate::{
+    abs::{
+        elements::{AbstractBuilder, SubelementList, Subelements},
+        rank::Rank,
+    },
+    geometry::Subspace,
+    Float, Polytope,
+};
+
+use vec_like::VecLike;
+
+/// Finds the representative of the class of an element in a union-find
+/// structure, compressing the path to it along the way.
+pub(super) fn find(parent: &mut [usize], mut i: usize) -> usize {
+    while parent[i] != i {
+        parent[i] = parent[parent[i]];
+        i = parent[i];
+    }
+
+    i
+}
+
+impl Concrete {
+    /// Returns whether every vertex in a list lies within a given distance of
+    /// a subspace.
+    fn lies_on(&self, subspace: &Subspace, vertices: &[usize], eps: Float) -> bool {
+        vertices
+            .iter()
+            .all(|&v| subspace.distance(&self.vertices[v]) <= eps)
+    }
+
+    /// Merges every set of adjacent facets of the polytope that lie on the same
+    /// hyperplane, up to a distance of `eps`, into a single facet. The ridges
+    /// between merged facets are removed.
+    ///
+    /// The same is then done rank by rank for the elements below, down to the
+    /// edges, merging adjacent elements that span the same subspace and share
+    /// all of their superelements. This way, triangulated faces on a merged
+    /// facet are merged as well, and so are collinear edges. Elements
+    /// that end up on no facet, like the center of a triangulated face, are
+    /// removed along with their vertices.
+    pub fn merge_coplanar_facets(&mut self, eps: Float) {
+        let rank = match self.rank().try_usize() {
+            Some(rank) if rank >= 3 => rank,
+            _ => return,
+        };
+
+        // The subelements of every element of rank 1 up to the maximal one.
+        let mut subs: Vec<Vec<Vec<usize>>> = vec![Vec::new()];
+        let mut alive = vec![vec![true; self.vertex_count()]];
+        for r in 1..=rank {
+            let elements = &self.abs[Rank::from(r)];
+            subs.push(elements.iter().map(|el| el.subs.0.clone()).collect());
+            alive.push(vec![true; elements.len()]);
+        }
+
+        // The vertices of every element, as sorted lists.
+        let mut verts: Vec<Vec<Vec<usize>>> = vec![Vec::new(), subs[1].clone()];
+        for r in 2..rank {
+            let lower = &verts[r - 1];
+            let rank_verts = subs[r]
+                .iter()
+                .map(|el_subs| {
+                    let mut el_verts: Vec<_> = el_subs
+                        .iter()
+                        .flat_map(|&s| lower[s].iter().copied())
+                        .collect();
+                    el_verts.sort_unstable();
+                    el_verts.dedup();
+                    el_verts
+                })
+                .collect();
+            verts.push(rank_verts);
+        }
+        for edge in &mut verts[1] {
+            edge.sort_unstable();
+        }
+
+        for r in (1..rank).rev() {
+            // The superelements of the elements of this rank and the one below.
+            let mut sups = vec![Vec::new(); subs[r].len()];
+            for (j, el_subs) in subs[r + 1].iter().enumerate() {
+                if alive[r + 1][j] {
+                    for &s in el_subs {
+                        sups[s].push(j);
+                    }
+                }
+            }
+            for el_sups in &mut sups {
+                el_sups.sort_unstable();
+            }
+
+            let mut lower_sups = vec![Vec::new(); alive[r - 1].len()];
+            for (i, el_subs) in subs[r].iter().enumerate() {
+                if alive[r][i] {
+                    for &s in el_subs {
+                        lower_sups[s].push(i);
+                    }
+                }
+            }
+
+            // The subspace spanned by every element, if it has the right rank.
+            let subspaces: Vec<_> = verts[r]
+                .iter()
+                .map(|el_verts| {
+                    let subspace =
+                        Subspace::from_points(el_verts.iter().map(|&v| &self.vertices[v]));
+                    if subspace.rank() == r {
+                        Some(subspace)
+                    } else {
+                        None
+                    }
+                })
+                .collect();
+
+            // Joins the elements on both sides of a subelement whenever they're
+            // coplanar.
+            let mut parent: Vec<_> = (0..subs[r].len()).collect();
+            for el_sups in &lower_sups {
+                if let [a, b] = *el_sups.as_slice() {
+                    if sups[a] != sups[b] {
+                        continue;
+                    }
+
+                    if let (Some(sa), Some(sb)) = (&subspaces[a], &subspaces[b]) {
+                        if self.lies_on(sa, &verts[r][b], eps)
+                            && self.lies_on(sb, &verts[r][a], eps)
+                        {
+                            let (ra, rb) = (find(&mut parent, a), find(&mut parent, b));
+                            parent[ra.max(rb)] = ra.min(rb);
+                        }
+                    }
+                }
+            }
+
+            // Merges every class into its first element. Subelements shared by
+            // two elements of a class lie in its interior, and are removed.
+            let mut remap: Vec<_> = (0..subs[r].len()).collect();
+            let mut counts = vec![0usize; alive[r - 1].len()];
+            let mut merged = false;
+            for i in 0..subs[r].len() {
+                let root = find(&mut parent, i);
+                if root != i {
+                    remap[i] = root;
+                    alive[r][i] = false;
+                    merged = true;
+                }
+            }
+
+            if !merged {
+                continue;
+            }
+
+            let mut classes = vec![Vec::new(); subs[r].len()];
+            for (i, &root) in remap.iter().enumerate() {
+                classes[root].push(i);
+            }
+
+            for (root, class) in classes.into_iter().enumerate() {
+                if class.len() < 2 {
+                    continue;
+                }
+
+                for &i in &class {
+                    for &s in &subs[r][i] {
+                        counts[s] += 1;
+                    }
+                }
+
+                let mut new_subs = Vec::new();
+                for &i in &class {
+                    for &s in &subs[r][i] {
+                        match counts[s] {
+                            0 => {}
+                            1 => new_subs.push(s),
+                            _ => alive[r - 1][s] = false,
+                        }
+                        counts[s] = 0;
+                    }
+                }
+
+                new_subs.sort_unstable();
+                subs[r][root] = new_subs;
+            }
+
+            for el_subs in &mut subs[r + 1] {
+                for s in el_subs.iter_mut() {
+                    *s = remap[*s];
+                }
+                el_subs.sort_unstable();
+                el_subs.dedup();
+            }
+        }
+
+        // Removes the elements that are no longer on any superelement.
+        for r in (0..rank).rev() {
+            let mut used = vec![false; alive[r].len()];
+            for (j, el_subs) in subs[r + 1].iter().enumerate() {
+                if alive[r + 1][j] {
+                    for &s in el_subs {
+                        used[s] = true;
+                    }
+                }
+            }
+
+            for (alive, used) in alive[r].iter_mut().zip(used) {
+                *alive &= used;
+            }
+        }
+
+        // Rebuilds the polytope out of the remaining elements.
+        let new_idx: Vec<Vec<usize>> = alive
+            .iter()
+            .map(|rank_alive| {
+                let mut idx = Vec::with_capacity(rank_alive.len());
+                let mut count = 0;
+                for &alive in rank_alive {
+                    idx.push(count);
+                    if alive {
+                        count += 1;
+                    }
+                }
+                idx
+            })
+            .collect();
+
+        let vertices: Vec<_> = self
+            .vertices
+            .drain(..)
+            .zip(&alive[0])
+            .filter(|(_, &alive)| alive)
+            .map(|(v, _)| v)
+            .collect();
+
+        let mut abs = AbstractBuilder::with_capacity(Rank::from(rank));
+        abs.push_min();
+        abs.push_vertices(vertices.len());
+        for r in 1..=rank {
+            let elements: Vec<_> = subs[r]
+                .iter()
+                .zip(&alive[r])
+                .filter(|(_, &alive)| alive)
+                .map(|(el_subs, _)| {
+                    Subelements(el_subs.iter().map(|&s| new_idx[r - 1][s]).collect())
+                })
+                .collect();
+            abs.push(SubelementList::from(elements));
+        }
+
+        self.vertices = vertices;
+        self.abs = abs.build();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{abs::rank::RankVec, conc::file::FromFile};
+
+    /// Merges the coplanar facets of a polytope and checks its element counts.
+    fn test(mut poly: Concrete, el_counts: RankVec<usize>) {
+        poly.merge_coplanar_facets(1e-9);
+        poly.abs.is_valid().unwrap();
+        assert_eq!(poly.el_counts(), el_counts);
+    }
+
+    #[test]
+    fn triangulated_cube() {
+        let cube = Concrete::from_off(
+            "OFF 8 12 0
+            0 0 0  1 0 0  1 1 0  0 1 0  0 0 1  1 0 1  1 1 1  0 1 1
+            3 0 1 2  3 0 2 3  3 4 5 6  3 4 6 7  3 0 1 5  3 0 5 4
+            3 1 2 6  3 1 6 5  3 2 3 7  3 2 7 6  3 3 0 4  3 3 4 7",
+        )
+        .unwrap();
+
+        test(cube, vec![1, 8, 12, 6, 1].into());
+    }
+
+    /// Raising flat pyramids on the facets of a polytope and merging them back
+    /// gives the original polytope.
+    #[test]
+    fn kis() {
+        for n in 3..=4 {
+            let cube = Concrete::hypercube(Rank::new(n));
+            let el_counts = cube.el_counts();
+            test(cube.kis(0.0), el_counts.clone());
+            test(cube, el_counts);
+        }
+
+        // Nothing happens if the pyramids aren't flat.
+        let triakis = Concrete::simplex(Rank::new(3)).kis(0.1);
+        test(triakis, vec![1, 8, 18, 12, 1].into());
+    }
+}
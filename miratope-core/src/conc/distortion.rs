@@ -0,0 +1,260 @@
+//! Measures how far the faces of a polytope are from being regular polygons,
+//! which is useful when searching for
+//! [near-misses](https://en.wikipedia.org/wiki/Near-miss_Johnson_solid).
+//!
+//! All lengths are measured relative to the mean edge length of the polytope,
+//! so that the metrics don't depend on its scale.
+
+use super::{cycle::CycleBuilder, Concrete};
+use crate::{
+    abs::rank::Rank,
+    geometry::{Matrix, Point},
+    Float,
+};
+
+use vec_like::VecLike;
+
+/// How far a single face is from being a regular polygon.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FaceDistortion {
+    /// The mean squared difference between the lengths of the edges of the
+    /// face and the mean edge length of the polytope, relative to the square
+    /// of the latter.
+    pub edge_length_variance: Float,
+
+    /// The greatest distance from a vertex of the face to the plane that best
+    /// fits it, relative to the mean edge length of the polytope.
+    pub planarity_error: Float,
+
+    /// The greatest difference between an angle of the face and the mean of
+    /// its angles, in radians.
+    pub angle_deviation: Float,
+}
+
+impl FaceDistortion {
+    /// Returns the greatest value of each metric among two distortions.
+    fn max(self, other: Self) -> Self {
+        Self {
+            edge_length_variance: self.edge_length_variance.max(other.edge_length_variance),
+            planarity_error: self.planarity_error.max(other.planarity_error),
+            angle_deviation: self.angle_deviation.max(other.angle_deviation),
+        }
+    }
+}
+
+/// How far every face of a polytope is from being a regular polygon, as
+/// returned by [`Concrete::regularity_error`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RegularityError {
+    /// The mean edge length of the polytope, which all lengths are relative to.
+    pub edge_length: Float,
+
+    /// The distortion of each face, in the same order as the faces.
+    pub faces: Vec<FaceDistortion>,
+}
+
+impl RegularityError {
+    /// Returns the greatest value of each metric over all faces.
+    pub fn max(&self) -> FaceDistortion {
+        self.faces
+            .iter()
+            .fold(FaceDistortion::default(), |acc, &face| acc.max(face))
+    }
+
+    /// Returns whether every metric on every face is within a given
+    /// tolerance.
+    pub fn is_within(&self, tolerance: Float) -> bool {
+        let max = self.max();
+        max.edge_length_variance <= tolerance
+            && max.planarity_error <= tolerance
+            && max.angle_deviation <= tolerance
+    }
+}
+
+/// Returns the unsigned angle at a vertex between two of its neighbors.
+fn angle(prev: &Point, cur: &Point, next: &Point) -> Float {
+    let u = prev - cur;
+    let v = next - cur;
+    let cos = u.dot(&v) / (u.norm() * v.norm());
+    cos.clamp(-1.0, 1.0).acos()
+}
+
+impl Concrete {
+    /// Returns the mean length of the edges of the polytope, or `None` if it
+    /// has no edges.
+    fn mean_edge_length(&self) -> Option<Float> {
+        let edges = self.abs.ranks.get(Rank::new(1))?;
+        if edges.is_empty() {
+            return None;
+        }
+
+        let total: Float = edges
+            .iter()
+            .map(|edge| (&self.vertices[edge.subs[0]] - &self.vertices[edge.subs[1]]).norm())
+            .sum();
+        Some(total / edges.len() as Float)
+    }
+
+    /// Returns the greatest distance from a set of points to the plane that
+    /// best fits them in the least squares sense.
+    fn planarity_error(&self, vertices: &[usize]) -> Float {
+        let dim = self.vertices[vertices[0]].len();
+        if vertices.len() <= 3 || dim <= 2 {
+            return 0.0;
+        }
+
+        let mut centroid = Point::zeros(dim);
+        for &v in vertices {
+            centroid += &self.vertices[v];
+        }
+        centroid /= vertices.len() as Float;
+
+        // The two main directions of the points span the best fitting plane.
+        let points = Matrix::from_fn(vertices.len(), dim, |i, j| {
+            self.vertices[vertices[i]][j] - centroid[j]
+        });
+        let svd = points.svd(false, true);
+        let v_t = svd.v_t.as_ref().unwrap();
+        let mut order: Vec<_> = (0..svd.singular_values.len()).collect();
+        order.sort_by(|&i, &j| {
+            svd.singular_values[j]
+                .partial_cmp(&svd.singular_values[i])
+                .unwrap()
+        });
+        let basis: Vec<Point> = order
+            .iter()
+            .take(2)
+            .map(|&i| v_t.row(i).transpose())
+            .collect();
+
+        vertices
+            .iter()
+            .map(|&v| {
+                let p = &self.vertices[v] - &centroid;
+                let mut rejection = p.clone();
+                for b in &basis {
+                    rejection -= b * p.dot(b);
+                }
+                rejection.norm()
+            })
+            .fold(0.0, Float::max)
+    }
+
+    /// Measures how far every face of the polytope is from being a regular
+    /// polygon, possibly a star polygon. Returns `None` if the polytope has
+    /// no edges.
+    pub fn regularity_error(&self) -> Option<RegularityError> {
+        let edge_length = self.mean_edge_length()?;
+        let edges = &self.abs[Rank::new(1)];
+        let faces = match self.abs.ranks.get(Rank::new(2)) {
+            Some(faces) => faces,
+            None => {
+                return Some(RegularityError {
+                    edge_length,
+                    faces: Vec::new(),
+                })
+            }
+        };
+
+        let faces = faces
+            .iter()
+            .map(|face| {
+                let mut builder = CycleBuilder::with_capacity(face.subs.len());
+                let mut edge_length_variance = 0.0;
+                for &e in &face.subs {
+                    let (v0, v1) = (edges[e].subs[0], edges[e].subs[1]);
+                    builder.push(v0, v1);
+
+                    let len = (&self.vertices[v0] - &self.vertices[v1]).norm();
+                    edge_length_variance += (len / edge_length - 1.0).powi(2);
+                }
+                edge_length_variance /= face.subs.len() as Float;
+
+                let mut planarity_error: Float = 0.0;
+                let mut angle_deviation: Float = 0.0;
+                for cycle in builder.cycles() {
+                    let cycle: Vec<_> = cycle.iter().copied().collect();
+                    let n = cycle.len();
+                    planarity_error =
+                        planarity_error.max(self.planarity_error(&cycle) / edge_length);
+
+                    let angles: Vec<_> = (0..n)
+                        .map(|i| {
+                            angle(
+                                &self.vertices[cycle[(i + n - 1) % n]],
+                                &self.vertices[cycle[i]],
+                                &self.vertices[cycle[(i + 1) % n]],
+                            )
+                        })
+                        .collect();
+                    let mean = angles.iter().sum::<Float>() / n as Float;
+                    for angle in angles {
+                        angle_deviation = angle_deviation.max((angle - mean).abs());
+                    }
+                }
+
+                FaceDistortion {
+                    edge_length_variance,
+                    planarity_error,
+                    angle_deviation,
+                }
+            })
+            .collect();
+
+        Some(RegularityError { edge_length, faces })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        conc::{file::FromFile, ConcretePolytope},
+        Consts, Polytope,
+    };
+
+    #[test]
+    fn regular() {
+        for poly in [
+            Concrete::polygon(7),
+            Concrete::hypercube(Rank::new(3)),
+            Concrete::orthoplex(Rank::new(4)),
+            Concrete::uniform_prism(5, 2),
+        ] {
+            let error = poly.regularity_error().unwrap();
+            assert_eq!(error.faces.len(), poly.el_count(Rank::new(2)));
+            assert!(error.is_within(Float::EPS), "{:?}", error.max());
+        }
+
+        assert!(Concrete::point().regularity_error().is_none());
+    }
+
+    #[test]
+    fn distorted() {
+        // A cuboid has rectangular faces, with right angles.
+        let mut cuboid = Concrete::hypercube(Rank::new(3));
+        cuboid.scale(2.0);
+        for v in cuboid.vertices.iter_mut() {
+            v[0] *= 2.0;
+        }
+        let max = cuboid.regularity_error().unwrap().max();
+        assert!(max.edge_length_variance > 0.1);
+        assert!(max.planarity_error < Float::EPS);
+        assert!(max.angle_deviation < Float::EPS);
+
+        // Moving a single vertex of a cube makes its faces nonplanar.
+        let mut cube = Concrete::hypercube(Rank::new(3));
+        cube.vertices[0][0] += 0.1;
+        let max = cube.regularity_error().unwrap().max();
+        assert!(max.planarity_error > 0.01);
+        assert!(max.angle_deviation > 0.01);
+
+        // The great icosidodecahedron has regular faces.
+        let path = format!(
+            "{}/../lib/3D/uniform/cat3/rectates/gid.off",
+            env!("CARGO_MANIFEST_DIR")
+        );
+        let gid = Concrete::from_path(&path).unwrap();
+        assert!(gid.regularity_error().unwrap().is_within(1e-6));
+    }
+}
@@ -63,7 +63,7 @@ impl Concrete {
     - iterate over ranks backwards, use superelements instead of subelements
     - get number of types in total, if it's the same as previous loop, stop
     */
-    fn element_types(&self) -> RankVec<Vec<ElementType>> {
+    fn classify(&self) -> (RankVec<Vec<ElementType>>, RankVec<Vec<usize>>) {
         // Stores the different types, the counts of each, and the indices of
         // the types associated to each element.
         let mut types = RankVec::new();
@@ -173,7 +173,20 @@ impl Concrete {
             type_count = new_type_count;
         }
 
-        types
+        (types, type_of_element)
+    }
+
+    /// Returns the element types of every rank of the polytope.
+    fn element_types(&self) -> RankVec<Vec<ElementType>> {
+        self.classify().0
+    }
+
+    /// Returns the index of the type of every element of the polytope, rank by
+    /// rank. Two elements of the same rank have the same type whenever they
+    /// can't be told apart by the types of their subelements and
+    /// superelements.
+    pub fn element_type_indices(&self) -> RankVec<Vec<usize>> {
+        self.classify().1
     }
 
     /// Prints all element types of a polytope into the console.
@@ -216,3 +229,39 @@ impl Concrete {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{abs::rank::Rank, conc::Concrete, Polytope};
+
+    /// Returns the number of distinct element types of a given rank.
+    fn type_count(poly: &Concrete, rank: isize) -> usize {
+        let indices = poly.element_type_indices();
+        indices[Rank::new(rank)]
+            .iter()
+            .max()
+            .map_or(0, |&max| max + 1)
+    }
+
+    /// Checks that every element of a cube has the same type as the others of
+    /// its rank.
+    #[test]
+    fn cube() {
+        let cube = Concrete::hypercube(Rank::new(3));
+
+        for rank in 0..3 {
+            assert_eq!(type_count(&cube, rank), 1);
+        }
+    }
+
+    /// Checks that the bases and the sides of a triangular prism are told
+    /// apart.
+    #[test]
+    fn triangular_prism() {
+        let trip = Concrete::polygon(3).prism();
+
+        assert_eq!(type_count(&trip, 0), 1);
+        assert_eq!(type_count(&trip, 1), 2);
+        assert_eq!(type_count(&trip, 2), 2);
+    }
+}
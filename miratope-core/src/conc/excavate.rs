@@ -0,0 +1,445 @@
+//! Excavates a cap from a facet of a polytope, i.e. replaces the facet by the
+//! rest of the cap, placed inside of the polytope. This is the opposite of
+//! augmenting the polytope with the cap.
+
+use std::collections::HashMap;
+
+use super::{Concrete, ConcretePolytope};
+use crate::{
+    abs::{
+        elements::{AbstractBuilder, ElementHash, ElementRef, SubelementList, Subelements},
+        rank::Rank,
+        Abstract,
+    },
+    geometry::{Point, Subspace},
+    Consts, Float, Polytope,
+};
+
+use vec_like::VecLike;
+
+/// Any error encountered while excavating a polytope.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExcavationError {
+    /// The cap doesn't have the same rank as the polytope, or the polytope
+    /// has rank less than 2.
+    Rank,
+
+    /// The polytope has no facet with the given index.
+    InvalidFacet(usize),
+
+    /// No facet of the cap coincides with the facet to excavate.
+    NoBase,
+
+    /// The cap is flat, or it would intersect the rest of the polytope.
+    Intersection,
+}
+
+impl std::fmt::Display for ExcavationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Rank => write!(f, "cap doesn't have the same rank as the polytope"),
+            Self::InvalidFacet(idx) => write!(f, "polytope has no facet with index {}", idx),
+            Self::NoBase => write!(f, "no facet of the cap coincides with the facet"),
+            Self::Intersection => write!(f, "cap would intersect the polytope"),
+        }
+    }
+}
+
+impl std::error::Error for ExcavationError {}
+
+/// The result of excavating a polytope.
+pub type ExcavationResult<T> = Result<T, ExcavationError>;
+
+/// Returns the sorted vertex sets of the elements of some rank that lie on a
+/// given element, mapped to their indices.
+fn vertex_sets(
+    poly: &Concrete,
+    hash: &ElementHash,
+    rank: Rank,
+    vertex_map: impl Fn(usize) -> usize,
+) -> HashMap<Vec<usize>, usize> {
+    hash.get(rank)
+        .unwrap()
+        .keys()
+        .map(|&idx| {
+            let mut vertices: Vec<_> = poly
+                .abs
+                .element_vertices(ElementRef::new(rank, idx))
+                .unwrap()
+                .into_iter()
+                .map(&vertex_map)
+                .collect();
+            vertices.sort_unstable();
+            (vertices, idx)
+        })
+        .collect()
+}
+
+impl Concrete {
+    /// Returns the map sending every vertex on a facet of another polytope to
+    /// the vertex on a facet of this polytope at the same position, if these
+    /// facets coincide.
+    pub(super) fn coinciding_vertices(
+        &self,
+        facet_idx: usize,
+        other: &Concrete,
+        other_facet: usize,
+    ) -> Option<HashMap<usize, usize>> {
+        let facet_rank = self.rank().try_sub(Rank::new(1))?;
+        let facet_vertices = self
+            .abs
+            .element_vertices(ElementRef::new(facet_rank, facet_idx))?;
+        let other_vertices = other
+            .abs
+            .element_vertices(ElementRef::new(facet_rank, other_facet))?;
+        if other_vertices.len() != facet_vertices.len() {
+            return None;
+        }
+
+        let mut map = HashMap::new();
+        for c in other_vertices {
+            let v = *facet_vertices
+                .iter()
+                .find(|&&v| (&other.vertices[c] - &self.vertices[v]).norm() < Float::EPS)?;
+            map.insert(c, v);
+        }
+
+        Some(map)
+    }
+
+    /// Returns every pair of a facet of this polytope and a facet of another
+    /// polytope of the same rank that coincide.
+    pub(super) fn coinciding_facets(&self, other: &Concrete) -> Vec<(usize, usize)> {
+        let mut pairs = Vec::new();
+        for facet_idx in 0..self.facet_count() {
+            for other_facet in 0..other.facet_count() {
+                if self
+                    .coinciding_vertices(facet_idx, other, other_facet)
+                    .is_some()
+                {
+                    pairs.push((facet_idx, other_facet));
+                }
+            }
+        }
+
+        pairs
+    }
+
+    /// Builds the lattice of the polytope that results from gluing another
+    /// polytope of the same rank to this one along some pairs of facets, which
+    /// are all removed. Each pair consists of a facet of this polytope and a
+    /// facet of the other. The result has `vertex_count` vertices, and those of
+    /// the other polytope get sent to the indices given by `vertex_map`. The
+    /// elements on the facets of the other polytope are identified with those
+    /// on the facets of this one by their vertices. Returns `None` if some
+    /// element can't be identified.
+    pub(super) fn glue_lattice(
+        &self,
+        other: &Concrete,
+        pairs: &[(usize, usize)],
+        vertex_map: &[usize],
+        vertex_count: usize,
+    ) -> Option<Abstract> {
+        let rank = self.rank();
+        let rank_usize = rank.into_usize();
+        let facet_rank = rank.minus_one();
+        let mut hashes = Vec::with_capacity(pairs.len());
+        for &(facet_idx, other_facet) in pairs {
+            hashes.push((
+                ElementHash::new(&self.abs, ElementRef::new(facet_rank, facet_idx))?,
+                ElementHash::new(&other.abs, ElementRef::new(facet_rank, other_facet))?,
+            ));
+        }
+
+        let mut maps = vec![vertex_map.to_vec()];
+        let mut new_elements = Vec::with_capacity(rank_usize);
+        new_elements.push(SubelementList::new());
+
+        for r in 1..rank_usize {
+            let r_rank = Rank::from(r);
+            let mut shared = HashMap::new();
+            for (facet_hash, other_hash) in &hashes {
+                let facet_sets = vertex_sets(self, facet_hash, r_rank, |v| v);
+                let other_sets = vertex_sets(other, other_hash, r_rank, |c| maps[0][c]);
+
+                for (vertices, idx) in other_sets {
+                    shared.insert(idx, *facet_sets.get(&vertices)?);
+                }
+            }
+
+            let mut map = Vec::with_capacity(other.el_count(r_rank));
+            let mut subs_list = SubelementList::new();
+            for (idx, el) in other[r_rank].iter().enumerate() {
+                if let Some(&mapped) = shared.get(&idx) {
+                    map.push(mapped);
+                } else {
+                    map.push(self.el_count(r_rank) + subs_list.len());
+                    subs_list.push(Subelements(
+                        el.subs.iter().map(|&s| maps[r - 1][s]).collect(),
+                    ));
+                }
+            }
+
+            maps.push(map);
+            new_elements.push(subs_list);
+        }
+
+        let mut abs = AbstractBuilder::with_capacity(rank);
+        abs.push_min();
+        abs.push_vertices(vertex_count);
+
+        for (r, new_subs) in new_elements.into_iter().enumerate().skip(1) {
+            let mut subs_list = SubelementList::new();
+            for (idx, el) in self[Rank::from(r)].iter().enumerate() {
+                if r != rank_usize - 1 || pairs.iter().all(|&(facet_idx, _)| idx != facet_idx) {
+                    subs_list.push(el.subs.clone());
+                }
+            }
+            for subs in new_subs {
+                subs_list.push(subs);
+            }
+            abs.push(subs_list);
+        }
+
+        abs.push_max();
+        Some(abs.build())
+    }
+
+    /// Returns whether every point in a list lies strictly on the inner side
+    /// of every facet of the polytope. For a convex polytope, this means that
+    /// they all lie in its interior.
+    fn strictly_inside(&self, points: &[Point]) -> bool {
+        let facet_rank = self.rank().minus_one();
+        let center = self.gravicenter().unwrap();
+
+        (0..self.facet_count()).all(|idx| {
+            let vertices = self
+                .abs
+                .element_vertices(ElementRef::new(facet_rank, idx))
+                .unwrap();
+            let subspace = Subspace::from_points(vertices.iter().map(|&v| &self.vertices[v]));
+            match subspace.normal(&center) {
+                Some(normal) => points
+                    .iter()
+                    .all(|p| (p - &subspace.offset).dot(&normal) >= Float::EPS),
+                None => false,
+            }
+        })
+    }
+
+    /// Excavates a cap from a facet of the polytope, replacing the facet with
+    /// the other facets of the cap, reflected so that they lie inside the
+    /// polytope. This is how, for instance, the excavated CRF polyhedra are
+    /// built.
+    ///
+    /// The cap must have a facet whose vertices coincide with those of the
+    /// facet to excavate, and it can lie on either side of it. To check that
+    /// the result doesn't intersect itself, every new vertex must lie strictly
+    /// on the inner side of every facet of the polytope, which assumes that
+    /// the polytope is convex.
+    pub fn excavate(&mut self, facet_idx: usize, cap: &Concrete) -> ExcavationResult<()> {
+        let rank = self.rank();
+        if cap.rank() != rank || rank < Rank::new(2) {
+            return Err(ExcavationError::Rank);
+        }
+
+        let facet_rank = rank.minus_one();
+        if facet_idx >= self.facet_count() {
+            return Err(ExcavationError::InvalidFacet(facet_idx));
+        }
+
+        // Finds the facet of the cap that coincides with the facet, along
+        // with the map between their vertices.
+        let facet = ElementRef::new(facet_rank, facet_idx);
+        let facet_vertices = self.abs.element_vertices(facet).unwrap();
+        let (base, base_map) = (0..cap.facet_count())
+            .find_map(|idx| Some((idx, self.coinciding_vertices(facet_idx, cap, idx)?)))
+            .ok_or(ExcavationError::NoBase)?;
+
+        // The normal to the facet pointing inwards.
+        let center = self.gravicenter().unwrap();
+        let subspace = Subspace::from_points(facet_vertices.iter().map(|&v| &self.vertices[v]));
+        let normal = subspace
+            .normal(&center)
+            .ok_or(ExcavationError::Intersection)?;
+        let offset = subspace.offset;
+
+        // Places the rest of the cap inside the polytope.
+        let cap_height = (&cap.gravicenter().unwrap() - &offset).dot(&normal);
+        if cap_height.abs() < Float::EPS {
+            return Err(ExcavationError::Intersection);
+        }
+
+        let mut vertex_map = Vec::with_capacity(cap.vertices.len());
+        let mut new_vertices = Vec::new();
+        for (c, p) in cap.vertices.iter().enumerate() {
+            if let Some(&v) = base_map.get(&c) {
+                vertex_map.push(v);
+            } else {
+                vertex_map.push(self.vertices.len() + new_vertices.len());
+                if cap_height > 0.0 {
+                    new_vertices.push(p.clone());
+                } else {
+                    new_vertices.push(p - &normal * (2.0 * (p - &offset).dot(&normal)));
+                }
+            }
+        }
+
+        if !self.strictly_inside(&new_vertices) {
+            return Err(ExcavationError::Intersection);
+        }
+
+        self.abs = self
+            .glue_lattice(
+                cap,
+                &[(facet_idx, base)],
+                &vertex_map,
+                self.vertices.len() + new_vertices.len(),
+            )
+            .ok_or(ExcavationError::NoBase)?;
+        self.vertices.append(&mut new_vertices);
+        Ok(())
+    }
+
+    /// Drills a tunnel through the polytope, i.e. removes another polytope
+    /// from its interior, along with every pair of facets of both that
+    /// coincide. For instance, drilling a tunnel that meets two opposite
+    /// facets of a polyhedron gives a toroidal polyhedron.
+    ///
+    /// Unlike in [`Self::excavate`], the tunnel is used in place. Every vertex
+    /// of it that isn't on one of the removed facets must lie strictly on the
+    /// inner side of every facet of the polytope, which assumes that the
+    /// polytope is convex.
+    pub fn drill(&mut self, tunnel: &Concrete) -> ExcavationResult<()> {
+        let rank = self.rank();
+        if tunnel.rank() != rank || rank < Rank::new(2) {
+            return Err(ExcavationError::Rank);
+        }
+
+        let pairs = self.coinciding_facets(tunnel);
+        if pairs.is_empty() {
+            return Err(ExcavationError::NoBase);
+        }
+
+        let mut shared = HashMap::new();
+        for &(facet_idx, other_facet) in &pairs {
+            shared.extend(
+                self.coinciding_vertices(facet_idx, tunnel, other_facet)
+                    .unwrap(),
+            );
+        }
+
+        let mut vertex_map = Vec::with_capacity(tunnel.vertices.len());
+        let mut new_vertices = Vec::new();
+        for (c, p) in tunnel.vertices.iter().enumerate() {
+            if let Some(&v) = shared.get(&c) {
+                vertex_map.push(v);
+            } else {
+                vertex_map.push(self.vertices.len() + new_vertices.len());
+                new_vertices.push(p.clone());
+            }
+        }
+
+        if !self.strictly_inside(&new_vertices) {
+            return Err(ExcavationError::Intersection);
+        }
+
+        self.abs = self
+            .glue_lattice(
+                tunnel,
+                &pairs,
+                &vertex_map,
+                self.vertices.len() + new_vertices.len(),
+            )
+            .ok_or(ExcavationError::NoBase)?;
+        self.vertices.append(&mut new_vertices);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::Point;
+
+    /// Returns a cube along with the index of the face on which `x = -1/2`,
+    /// and a square pyramid of a given height on this face.
+    fn cube_and_pyramid(height: Float) -> (Concrete, usize, Concrete) {
+        let cube = Concrete::hypercube(Rank::new(3));
+        let facet = (0..6)
+            .find(|&idx| {
+                cube.element_vertices_ref(ElementRef::new(Rank::new(2), idx))
+                    .unwrap()
+                    .iter()
+                    .all(|v| v[0] < 0.0)
+            })
+            .unwrap();
+
+        // The vertices of the face, in cyclic order.
+        let mut vertices = vec![Point::from(vec![-0.5 - height, 0.0, 0.0])];
+        for (y, z) in [(-0.5, -0.5), (0.5, -0.5), (0.5, 0.5), (-0.5, 0.5)] {
+            vertices.push(Point::from(vec![-0.5, y, z]));
+        }
+
+        let pyramid = Concrete::new(vertices, Abstract::polygon(4).pyramid());
+        (cube, facet, pyramid)
+    }
+
+    #[test]
+    fn cube() {
+        let (mut cube, facet, pyramid) = cube_and_pyramid(0.3);
+        cube.excavate(facet, &pyramid).unwrap();
+        cube.abs.is_valid().unwrap();
+        assert_eq!(cube.el_counts(), vec![1, 9, 16, 9, 1].into());
+
+        // The apex is placed inside the cube.
+        assert!((cube.vertices[8][0] + 0.2).abs() < Float::EPS);
+    }
+
+    #[test]
+    fn intersection() {
+        let (mut cube, facet, pyramid) = cube_and_pyramid(1.5);
+        assert_eq!(
+            cube.excavate(facet, &pyramid),
+            Err(ExcavationError::Intersection)
+        );
+
+        let (mut cube, facet, pyramid) = cube_and_pyramid(0.3);
+        assert_eq!(
+            cube.excavate((facet + 1) % 6, &pyramid),
+            Err(ExcavationError::NoBase)
+        );
+        assert_eq!(
+            cube.excavate(6, &pyramid),
+            Err(ExcavationError::InvalidFacet(6))
+        );
+        assert_eq!(
+            cube.excavate(facet, &Concrete::polygon(4)),
+            Err(ExcavationError::Rank)
+        );
+    }
+
+    #[test]
+    fn drill() {
+        // A pyramid inside the cube is drilled in place.
+        let (mut cube, _, pyramid) = cube_and_pyramid(-0.3);
+        cube.drill(&pyramid).unwrap();
+        cube.abs.is_valid().unwrap();
+        assert_eq!(cube.el_counts(), vec![1, 9, 16, 9, 1].into());
+
+        // Unlike when excavating, a pyramid outside of the cube isn't moved.
+        let (mut cube, _, pyramid) = cube_and_pyramid(0.3);
+        assert_eq!(cube.drill(&pyramid), Err(ExcavationError::Intersection));
+        assert_eq!(
+            cube.drill(&Concrete::polygon(4)),
+            Err(ExcavationError::Rank)
+        );
+
+        // The tunnel must share some facet with the cube.
+        let (_, _, mut pyramid) = cube_and_pyramid(-0.3);
+        for v in &mut pyramid.vertices {
+            v[0] += 0.1;
+        }
+        assert_eq!(cube.drill(&pyramid), Err(ExcavationError::NoBase));
+    }
+}
@@ -0,0 +1,274 @@
+//! Extrudes a polytope along a map, i.e. builds a prism whose bases are a
+//! polytope and its image under the map. This covers twisted prisms, frusta,
+//! and any other polytope with the lattice of a prism, as well as gyroprisms,
+//! which have the lattice of an antiprism.
+
+use std::collections::HashMap;
+
+use super::{Concrete, ConcretePolytope};
+use crate::{
+    abs::{
+        elements::{AbstractBuilder, SubelementList, Subelements},
+        rank::Rank,
+        Abstract,
+    },
+    geometry::{Point, PointSet},
+    Consts, Float, Polytope,
+};
+
+use vec_like::VecLike;
+
+impl Concrete {
+    /// Builds a prism with the polytope as one base and its image under a map
+    /// as the other. The lateral elements join every element to its image, so
+    /// that the result has the same abstract structure as [`Polytope::prism`].
+    ///
+    /// The map may return points of a higher dimension than the vertices of
+    /// the polytope, in which case the base is padded with zeros. This lets
+    /// the map lift the cap off the base by itself. When the map is a
+    /// symmetry of the polytope followed by such a lift, this builds the
+    /// twisted prism in which every vertex is joined to the vertex it's sent
+    /// to, whose lateral faces will generally be skew.
+    ///
+    /// When the polytope is a polygon and the map is instead half of a
+    /// symmetry that sends every vertex to a neighbor, such as a square turned
+    /// by 45°, this builds the gyroprism with the lattice of an antiprism. Every
+    /// vertex is then joined to its image and to the image of the neighbor
+    /// before it, so that the lateral faces are triangles.
+    ///
+    /// # Panics
+    /// This method will panic if the map returns points of different
+    /// dimensions, or of a lower dimension than the vertices of the polytope.
+    pub fn prism_over_map<F: Fn(&Point) -> Point>(&self, f: F) -> Self {
+        let images: Vec<_> = self.vertices.iter().map(&f).collect();
+        let dim = self.dim_or();
+        let new_dim = images.first().map_or(dim, |v| v.len());
+        assert!(
+            new_dim >= dim && images.iter().all(|v| v.len() == new_dim),
+            "The map must return points of a single dimension, at least that of the polytope."
+        );
+
+        let next = self.half_symmetry(&images, &f);
+
+        // The vertices of a prism are sorted first by the vertex of the base,
+        // then by the vertex of the dyad. Those of a gyroprism are sorted the
+        // same way.
+        let vertices = self
+            .vertices
+            .iter()
+            .zip(images)
+            .flat_map(|(v, w)| [v.clone().resize_vertically(new_dim, 0.0), w])
+            .collect();
+
+        match next {
+            Some((next, edge_of)) => Self::new(vertices, self.gyroprism_lattice(&next, &edge_of)),
+            None => {
+                let mut poly = self.prism();
+                poly.vertices = vertices;
+                poly
+            }
+        }
+    }
+
+    /// If the polytope is a polygon, and the images of its vertices under a
+    /// map aren't vertices themselves but are sent to neighbors when the map
+    /// is applied again, returns the neighbor that every vertex gets sent to,
+    /// along with the edge joining them. Any coordinates added by the map are
+    /// dropped before applying it again.
+    fn half_symmetry<F: Fn(&Point) -> Point>(
+        &self,
+        images: &[Point],
+        f: &F,
+    ) -> Option<(Vec<usize>, Vec<usize>)> {
+        if self.rank() != Rank::new(2) {
+            return None;
+        }
+
+        let dim = self.dim_or();
+        let (set, _) = PointSet::dedup(self.vertices.iter().cloned(), Float::EPS);
+        if set.len() != self.vertices.len() {
+            return None;
+        }
+
+        // A symmetry gives a twisted prism instead.
+        let drop = |p: &Point| -> Point { p.rows(0, dim).into_owned() };
+        if images.iter().all(|w| set.find(&drop(w)).is_some()) {
+            return None;
+        }
+
+        let next = images
+            .iter()
+            .map(|w| set.find(&drop(&f(&drop(w)))))
+            .collect::<Option<Vec<_>>>()?;
+
+        // Every edge must join a vertex to the vertex it's sent to.
+        let edges = &self[Rank::new(1)];
+        let mut edge_idx = HashMap::new();
+        for (e, edge) in edges.iter().enumerate() {
+            let mut subs = edge.subs.clone();
+            subs.sort();
+            edge_idx.insert(subs, e);
+        }
+
+        let mut edge_of = Vec::with_capacity(next.len());
+        for (v, &u) in next.iter().enumerate() {
+            let mut pair = Subelements(vec![v, u]);
+            pair.sort();
+            edge_of.push(*edge_idx.get(&pair)?);
+        }
+
+        let mut used = edge_of.clone();
+        used.sort_unstable();
+        used.dedup();
+        if used.len() == edges.len() {
+            Some((next, edge_of))
+        } else {
+            None
+        }
+    }
+
+    /// Builds the lattice of the gyroprism of a polygon, given the neighbor
+    /// that every vertex is sent to and the edge joining them, as returned by
+    /// [`Self::half_symmetry`]. The vertex `2v` is a vertex of the base, and
+    /// `2v + 1` is its image.
+    fn gyroprism_lattice(&self, next: &[usize], edge_of: &[usize]) -> Abstract {
+        let n = next.len();
+        let m = self.el_count(Rank::new(1));
+        let sorted = |subs: Vec<usize>| {
+            let mut subs = Subelements(subs);
+            subs.sort();
+            subs
+        };
+
+        // The edges of the base, those of the cap, those joining every vertex
+        // to its image, and those joining the image of every vertex to its
+        // neighbor.
+        let mut edges = SubelementList::with_capacity(2 * m + 2 * n);
+        for offset in [0, 1] {
+            for edge in self[Rank::new(1)].iter() {
+                edges.push(sorted(edge.subs.iter().map(|&v| 2 * v + offset).collect()));
+            }
+        }
+        for v in 0..n {
+            edges.push(Subelements(vec![2 * v, 2 * v + 1]));
+        }
+        for (v, &u) in next.iter().enumerate() {
+            edges.push(sorted(vec![2 * u, 2 * v + 1]));
+        }
+
+        // The base, the cap, and two triangles for every edge of the base.
+        let mut faces = SubelementList::with_capacity(2 * n + 2);
+        faces.push(Subelements((0..m).collect()));
+        faces.push(Subelements((m..2 * m).collect()));
+        for (v, &u) in next.iter().enumerate() {
+            let e = edge_of[v];
+            faces.push(sorted(vec![e, 2 * m + v, 2 * m + n + v]));
+            faces.push(sorted(vec![m + e, 2 * m + n + v, 2 * m + u]));
+        }
+
+        let mut abs = AbstractBuilder::with_capacity(Rank::new(3));
+        abs.push_min();
+        abs.push_vertices(2 * n);
+        abs.push(edges);
+        abs.push(faces);
+        abs.push_max();
+        abs.build()
+    }
+
+    /// Builds a prism with the polytope as one base and its image under a map
+    /// as the other, with the bases placed at a given distance along a new
+    /// axis. With the identity map, this is the same as
+    /// [`ConcretePolytope::prism_with`].
+    ///
+    /// # Panics
+    /// This method will panic if the map doesn't preserve the dimension of the
+    /// vertices.
+    pub fn prism_over_map_with<F: Fn(&Point) -> Point>(&self, height: Float, f: F) -> Self {
+        let dim = self.dim_or();
+        let half_height = height / 2.0;
+
+        // Appends a coordinate to a point.
+        let lift = move |v: &Point, h: Float| {
+            assert_eq!(v.len(), dim, "The map must preserve the dimension.");
+            Point::from_iterator(dim + 1, v.iter().copied().chain(std::iter::once(h)))
+        };
+
+        let mut poly = self.prism_over_map(|v| lift(&f(v), half_height));
+        for v in poly.vertices.iter_mut().step_by(2) {
+            v[dim] = -half_height;
+        }
+
+        poly
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::Matrix;
+
+    #[test]
+    fn identity() {
+        let pentagon = Concrete::polygon(5);
+        let prism = pentagon.prism_over_map_with(2.0, Clone::clone);
+        let expected = pentagon.prism_with(2.0);
+
+        prism.abs.is_valid().unwrap();
+        assert_eq!(prism.vertices, expected.vertices);
+    }
+
+    /// Returns the rotation of the plane by an angle.
+    fn rotation(angle: Float) -> Matrix {
+        let (sin, cos) = angle.sin_cos();
+        Matrix::from_row_slice(2, 2, &[cos, -sin, sin, cos])
+    }
+
+    #[test]
+    fn twisted_prism() {
+        let square = Concrete::polygon(4);
+        let rotation = rotation(Float::TAU / 4.0);
+
+        let twisted = square.prism_over_map_with(1.0, |v| &rotation * v);
+        twisted.abs.is_valid().unwrap();
+        assert_eq!(twisted.el_counts(), vec![1, 8, 12, 6, 1].into());
+
+        // Every vertex on the cap lies above its base vertex, turned by 90°.
+        for pair in twisted.vertices.chunks(2) {
+            let (v, w) = (&pair[0], &pair[1]);
+            assert!((v[2] + 0.5).abs() < Float::EPS);
+            assert!((w[2] - 0.5).abs() < Float::EPS);
+            assert!(v.rows(0, 2).dot(&w.rows(0, 2)).abs() < Float::EPS);
+        }
+    }
+
+    #[test]
+    fn gyroprism() {
+        // The square antiprism.
+        let square = Concrete::polygon(4);
+        let rotation = rotation(Float::TAU / 8.0);
+
+        let gyroprism = square.prism_over_map_with(1.0, |v| &rotation * v);
+        gyroprism.abs.is_valid().unwrap();
+        assert_eq!(gyroprism.el_counts(), vec![1, 8, 16, 10, 1].into());
+
+        // The lateral faces are triangles.
+        assert_eq!(
+            gyroprism[Rank::new(2)]
+                .iter()
+                .filter(|face| face.subs.len() == 3)
+                .count(),
+            8
+        );
+    }
+
+    #[test]
+    fn lift() {
+        // The map may lift the cap by itself.
+        let triangle = Concrete::polygon(3);
+        let frustum = triangle.prism_over_map(|v| Point::from(vec![v[0] / 2.0, v[1] / 2.0, 1.0]));
+
+        frustum.abs.is_valid().unwrap();
+        assert_eq!(frustum.dim(), Some(3));
+        assert_eq!(frustum.vertices[0][2], 0.0);
+    }
+}
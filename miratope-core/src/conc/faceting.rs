@@ -0,0 +1,640 @@
+//! Enumerates the [facetings](https://polytope.miraheze.org/wiki/Faceting) of
+//! a set of points under a symmetry group, i.e. the polytopes whose vertices
+//! are exactly these points, and which have every symmetry in the group. This
+//! is how most star and noble polytopes are found.
+//!
+//! Only polyhedra are supported for now. The candidate faces are the cycles of
+//! candidate edges within every plane through three or more of the points, and
+//! are sorted into orbits under the group. A backtracking search then picks
+//! sets of orbits in which every edge belongs to exactly two faces, and keeps
+//! those that form valid, connected polyhedra.
+
+use std::collections::{HashMap, HashSet};
+
+use super::Concrete;
+use crate::{
+    abs::{
+        elements::{AbstractBuilder, SubelementList, Subelements},
+        rank::Rank,
+    },
+    geometry::{Point, PointSet},
+    group::Group,
+    Consts, Float,
+};
+
+use vec_like::VecLike;
+
+/// Represents an error while setting up a [`FacetingSearch`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FacetingError {
+    /// The points weren't three-dimensional.
+    Dimension(usize),
+
+    /// The group didn't send the points to themselves, or one of the given
+    /// permutations wasn't a permutation of the points.
+    Group,
+
+    /// The symmetries of the polytope couldn't be computed.
+    Symmetry,
+}
+
+impl std::fmt::Display for FacetingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Dimension(dim) => write!(
+                f,
+                "only facetings of 3D points are supported, found dimension {}",
+                dim
+            ),
+            Self::Group => write!(f, "the group doesn't permute the points"),
+            Self::Symmetry => write!(f, "the symmetries of the polytope couldn't be computed"),
+        }
+    }
+}
+
+impl std::error::Error for FacetingError {}
+
+/// The result of setting up a [`FacetingSearch`].
+pub type FacetingResult<T> = Result<T, FacetingError>;
+
+/// Restrictions on the facetings found by a [`FacetingSearch`]. By default,
+/// there are none, which can make the search take very long for large sets of
+/// points.
+#[derive(Clone, Debug, Default)]
+pub struct FacetingOptions {
+    /// If set, only pairs of points at one of these distances are joined by
+    /// edges.
+    pub edge_lengths: Option<Vec<Float>>,
+
+    /// If set, faces have at most this many vertices.
+    pub max_face_vertices: Option<usize>,
+
+    /// If set, the search stops after finding this many facetings.
+    pub max_facetings: Option<usize>,
+}
+
+/// A faceting found by a [`FacetingSearch`].
+#[derive(Clone, Debug)]
+pub struct Faceting {
+    /// The faceting as a polytope. Its vertices are the points of the search,
+    /// in the same order.
+    pub polytope: Concrete,
+
+    /// The indices of the orbits of faces that make up the faceting, in
+    /// increasing order.
+    pub face_orbits: Vec<usize>,
+}
+
+impl Faceting {
+    /// Returns whether the group acts transitively on the faces. If it also
+    /// acts transitively on the points, the faceting is noble.
+    pub fn is_isohedral(&self) -> bool {
+        self.face_orbits.len() == 1
+    }
+}
+
+/// Returns a normal vector to the plane through three points, or `None` if
+/// they're collinear.
+fn normal(p: &Point, q: &Point, r: &Point) -> Option<Point> {
+    let (u, v) = (q - p, r - p);
+    let mut n = Point::from(vec![
+        u[1] * v[2] - u[2] * v[1],
+        u[2] * v[0] - u[0] * v[2],
+        u[0] * v[1] - u[1] * v[0],
+    ]);
+
+    if n.normalize_mut() > Float::EPS {
+        Some(n)
+    } else {
+        None
+    }
+}
+
+/// Rotates and reflects a cycle of vertices so that it starts at its least
+/// vertex, followed by the least of its neighbors.
+fn canonical_cycle(mut cycle: Vec<usize>) -> Vec<usize> {
+    let (start, _) = cycle.iter().enumerate().min_by_key(|(_, &v)| v).unwrap();
+    cycle.rotate_left(start);
+    if cycle[1] > cycle[cycle.len() - 1] {
+        cycle[1..].reverse();
+    }
+
+    cycle
+}
+
+/// Returns the edges of a cycle of vertices, as sorted pairs.
+fn cycle_edges(cycle: &[usize]) -> impl Iterator<Item = (usize, usize)> + '_ {
+    cycle
+        .iter()
+        .zip(cycle.iter().cycle().skip(1))
+        .map(|(&v, &w)| if v < w { (v, w) } else { (w, v) })
+}
+
+/// Enumerates the facetings of a set of 3D points under a group of
+/// permutations of them.
+#[derive(Clone, Debug)]
+pub struct FacetingSearch {
+    /// The points to facet.
+    vertices: Vec<Point>,
+
+    /// The permutations of the points by the elements of the group.
+    perms: Vec<Vec<usize>>,
+
+    /// The restrictions on the facetings.
+    options: FacetingOptions,
+
+    /// The edges of the candidate faces, as sorted pairs of points.
+    edges: Vec<(usize, usize)>,
+
+    /// The candidate faces as cycles of points, sorted into orbits.
+    orbits: Vec<Vec<Vec<usize>>>,
+
+    /// For every orbit of faces, the indices of their edges together with the
+    /// number of faces through each.
+    orbit_edges: Vec<Vec<(usize, u8)>>,
+
+    /// For every edge, the orbits with some face through it.
+    edge_orbits: Vec<Vec<usize>>,
+}
+
+impl FacetingSearch {
+    /// Sets up a search for the facetings of a set of 3D points under a group,
+    /// given by the permutations of the points by each of its elements.
+    pub fn new(
+        vertices: Vec<Point>,
+        perms: Vec<Vec<usize>>,
+        options: FacetingOptions,
+    ) -> FacetingResult<Self> {
+        if let Some(v) = vertices.iter().find(|v| v.len() != 3) {
+            return Err(FacetingError::Dimension(v.len()));
+        }
+
+        let n = vertices.len();
+        for perm in &perms {
+            let mut seen = vec![false; n];
+            for &v in perm {
+                if v >= n || seen[v] {
+                    return Err(FacetingError::Group);
+                }
+                seen[v] = true;
+            }
+
+            if perm.len() != n {
+                return Err(FacetingError::Group);
+            }
+        }
+
+        let mut search = Self {
+            vertices,
+            perms,
+            options,
+            edges: Vec::new(),
+            orbits: Vec::new(),
+            orbit_edges: Vec::new(),
+            edge_orbits: Vec::new(),
+        };
+        search.find_faces();
+        Ok(search)
+    }
+
+    /// Sets up a search for the facetings of a set of 3D points under a group
+    /// of matrices, each of which must send the points to themselves. The
+    /// points must be distinct.
+    pub fn with_group(
+        vertices: Vec<Point>,
+        group: Group,
+        options: FacetingOptions,
+    ) -> FacetingResult<Self> {
+        if let Some(v) = vertices.iter().find(|v| v.len() != 3) {
+            return Err(FacetingError::Dimension(v.len()));
+        }
+
+        let (set, _) = PointSet::dedup(vertices.iter().cloned(), Float::EPS);
+        let mut perms = Vec::new();
+        for m in group {
+            if m.shape() != (3, 3) {
+                return Err(FacetingError::Group);
+            }
+
+            perms.push(
+                vertices
+                    .iter()
+                    .map(|v| set.find(&(&m * v)))
+                    .collect::<Option<_>>()
+                    .ok_or(FacetingError::Group)?,
+            );
+        }
+
+        Self::new(vertices, perms, options)
+    }
+
+    /// Returns the number of orbits of candidate faces.
+    pub fn orbit_count(&self) -> usize {
+        self.orbits.len()
+    }
+
+    /// Returns the candidate faces in an orbit, as cycles of points.
+    pub fn orbit(&self, idx: usize) -> &[Vec<usize>] {
+        &self.orbits[idx]
+    }
+
+    /// Returns whether two points can be joined by an edge.
+    fn is_edge(&self, v: usize, w: usize) -> bool {
+        let len = (&self.vertices[v] - &self.vertices[w]).norm();
+        len > Float::EPS
+            && match &self.options.edge_lengths {
+                Some(lens) => lens.iter().any(|l| (len - l).abs() < Float::EPS),
+                None => true,
+            }
+    }
+
+    /// Returns the sorted images of a set of points under the group.
+    fn images(&self, set: &[usize]) -> impl Iterator<Item = Vec<usize>> + '_ {
+        let set = set.to_vec();
+        self.perms.iter().map(move |perm| {
+            let mut image: Vec<_> = set.iter().map(|&v| perm[v]).collect();
+            image.sort_unstable();
+            image
+        })
+    }
+
+    /// Returns the points on every plane that contains two adjacent edges, up
+    /// to symmetry.
+    fn planes(&self, adj: &[Vec<usize>]) -> Vec<Vec<usize>> {
+        let mut seen = HashSet::new();
+        let mut planes = Vec::new();
+
+        for (v, neighbors) in adj.iter().enumerate() {
+            for (i, &u) in neighbors.iter().enumerate() {
+                for &w in &neighbors[i + 1..] {
+                    let p = &self.vertices[v];
+                    let n = match normal(p, &self.vertices[u], &self.vertices[w]) {
+                        Some(n) => n,
+                        None => continue,
+                    };
+
+                    let plane: Vec<_> = (0..self.vertices.len())
+                        .filter(|&x| (&self.vertices[x] - p).dot(&n).abs() < Float::EPS)
+                        .collect();
+                    if seen.contains(&plane) {
+                        continue;
+                    }
+
+                    seen.extend(self.images(&plane));
+                    planes.push(plane);
+                }
+            }
+        }
+
+        planes
+    }
+
+    /// Returns every non-degenerate cycle of edges between the points of a
+    /// plane, in canonical form.
+    fn cycles(&self, plane: &[usize], adj: &[Vec<usize>]) -> Vec<Vec<usize>> {
+        let max_len = self.options.max_face_vertices.unwrap_or(usize::MAX);
+        let in_plane: HashSet<_> = plane.iter().copied().collect();
+        let mut cycles = Vec::new();
+
+        for &start in plane {
+            // Each cycle is found from its least vertex, in the direction of
+            // its least neighbor.
+            let mut path = vec![start];
+            let mut branches = vec![adj[start].clone()];
+            while let Some(branch) = branches.last_mut() {
+                let w = match branch.pop() {
+                    Some(w) => w,
+                    None => {
+                        path.pop();
+                        branches.pop();
+                        continue;
+                    }
+                };
+
+                if w == start && path.len() >= 3 && path[1] < path[path.len() - 1] {
+                    if self.is_nondegenerate(&path) {
+                        cycles.push(path.clone());
+                    }
+                } else if w > start
+                    && in_plane.contains(&w)
+                    && !path.contains(&w)
+                    && path.len() < max_len
+                {
+                    path.push(w);
+                    branches.push(adj[w].clone());
+                }
+            }
+        }
+
+        cycles
+    }
+
+    /// Returns whether no two consecutive edges of a cycle are collinear.
+    fn is_nondegenerate(&self, cycle: &[usize]) -> bool {
+        let len = cycle.len();
+        (0..len).all(|i| {
+            normal(
+                &self.vertices[cycle[(i + len - 1) % len]],
+                &self.vertices[cycle[i]],
+                &self.vertices[cycle[(i + 1) % len]],
+            )
+            .is_some()
+        })
+    }
+
+    /// Finds the candidate faces and sorts them into orbits.
+    fn find_faces(&mut self) {
+        let n = self.vertices.len();
+        let adj: Vec<Vec<_>> = (0..n)
+            .map(|v| (0..n).filter(|&w| self.is_edge(v, w)).collect())
+            .collect();
+
+        let mut seen = HashSet::new();
+        let mut edge_indices = HashMap::new();
+        for plane in self.planes(&adj) {
+            for cycle in self.cycles(&plane, &adj) {
+                if seen.contains(&cycle) {
+                    continue;
+                }
+
+                // Finds the orbit of the face.
+                let mut orbit = Vec::new();
+                for perm in &self.perms {
+                    let image = canonical_cycle(cycle.iter().map(|&v| perm[v]).collect());
+                    if seen.insert(image.clone()) {
+                        orbit.push(image);
+                    }
+                }
+
+                let mut counts = HashMap::new();
+                for face in &orbit {
+                    for edge in cycle_edges(face) {
+                        let len = edge_indices.len();
+                        let idx = *edge_indices.entry(edge).or_insert(len);
+                        *counts.entry(idx).or_insert(0) += 1;
+                    }
+                }
+
+                // An orbit with more than two faces through an edge can't be
+                // part of any faceting.
+                if counts.values().all(|&count| count <= 2) {
+                    let mut counts: Vec<_> = counts.into_iter().collect();
+                    counts.sort_unstable();
+                    self.orbits.push(orbit);
+                    self.orbit_edges.push(counts);
+                }
+            }
+        }
+
+        self.edges = vec![(0, 0); edge_indices.len()];
+        for (edge, idx) in edge_indices {
+            self.edges[idx] = edge;
+        }
+
+        self.edge_orbits = vec![Vec::new(); self.edges.len()];
+        for (o, edges) in self.orbit_edges.iter().enumerate() {
+            for &(e, _) in edges {
+                self.edge_orbits[e].push(o);
+            }
+        }
+    }
+
+    /// Adds the faces of an orbit to the counts of faces through each edge.
+    /// Returns whether every edge is still in at most two faces.
+    fn add(&self, orbit: usize, counts: &mut [u8]) -> bool {
+        let mut valid = true;
+        for &(e, count) in &self.orbit_edges[orbit] {
+            counts[e] += count;
+            valid &= counts[e] <= 2;
+        }
+
+        valid
+    }
+
+    /// Removes the faces of an orbit from the counts of faces through each
+    /// edge.
+    fn remove(&self, orbit: usize, counts: &mut [u8]) {
+        for &(e, count) in &self.orbit_edges[orbit] {
+            counts[e] -= count;
+        }
+    }
+
+    /// Returns whether the search has found as many facetings as requested.
+    fn is_done(&self, found: &[Faceting]) -> bool {
+        matches!(self.options.max_facetings, Some(max) if found.len() >= max)
+    }
+
+    /// Finds every faceting whose least orbit is the first of the used ones,
+    /// and which contains all of them.
+    fn search(&self, used: &mut Vec<usize>, counts: &mut [u8], found: &mut Vec<Faceting>) {
+        if self.is_done(found) {
+            return;
+        }
+
+        // An edge in a single face must be in exactly one more, which belongs
+        // to exactly one other orbit, so every faceting is found only once.
+        let open = used
+            .iter()
+            .flat_map(|&o| &self.orbit_edges[o])
+            .map(|&(e, _)| e)
+            .find(|&e| counts[e] == 1);
+
+        let e = match open {
+            Some(e) => e,
+            None => {
+                found.extend(self.faceting(used, counts));
+                return;
+            }
+        };
+
+        for &o in &self.edge_orbits[e] {
+            if o > used[0] && !used.contains(&o) {
+                if self.add(o, counts) {
+                    used.push(o);
+                    self.search(used, counts, found);
+                    used.pop();
+                }
+                self.remove(o, counts);
+            }
+        }
+    }
+
+    /// Builds the faceting with the faces of a set of orbits, if it's valid.
+    fn faceting(&self, used: &[usize], counts: &[u8]) -> Option<Faceting> {
+        // Every point must be a vertex.
+        let mut is_vertex = vec![false; self.vertices.len()];
+        for (&(v, w), &count) in self.edges.iter().zip(counts) {
+            if count != 0 {
+                is_vertex[v] = true;
+                is_vertex[w] = true;
+            }
+        }
+        if is_vertex.contains(&false) {
+            return None;
+        }
+
+        let mut edges = SubelementList::new();
+        let mut edge_indices = HashMap::new();
+        for (&(v, w), &count) in self.edges.iter().zip(counts) {
+            if count != 0 {
+                edge_indices.insert((v, w), edges.len());
+                edges.push(Subelements(vec![v, w]));
+            }
+        }
+
+        let mut faces = SubelementList::new();
+        for &o in used {
+            for face in &self.orbits[o] {
+                let mut subs: Vec<_> = cycle_edges(face).map(|e| edge_indices[&e]).collect();
+                subs.sort_unstable();
+                faces.push(Subelements(subs));
+            }
+        }
+
+        let mut abs = AbstractBuilder::with_capacity(Rank::new(3));
+        abs.push_min();
+        abs.push_vertices(self.vertices.len());
+        abs.push(edges);
+        abs.push(faces);
+        abs.push_max();
+        let abs = abs.build();
+
+        if abs.validate().is_err() {
+            return None;
+        }
+
+        let mut face_orbits = used.to_vec();
+        face_orbits.sort_unstable();
+        Some(Faceting {
+            polytope: Concrete::new(self.vertices.clone(), abs),
+            face_orbits,
+        })
+    }
+
+    /// Returns every faceting of the points under the group, up to the
+    /// restrictions in the options. Compounds aren't included.
+    pub fn facetings(&self) -> Vec<Faceting> {
+        trace_span!(
+            "facetings",
+            vertices = self.vertices.len(),
+            orbits = self.orbits.len()
+        );
+
+        let mut found = Vec::new();
+        let mut counts = vec![0; self.edges.len()];
+        for o in 0..self.orbits.len() {
+            if self.is_done(&found) {
+                break;
+            }
+
+            if self.add(o, &mut counts) {
+                self.search(&mut vec![o], &mut counts, &mut found);
+            }
+            self.remove(o, &mut counts);
+        }
+
+        trace_event!(facetings = found.len(), "found facetings");
+        found
+    }
+}
+
+impl Concrete {
+    /// Returns the facetings of the vertices of a polyhedron under its own
+    /// symmetry group, up to the restrictions in the options. See
+    /// [`FacetingSearch`] to use other groups.
+    pub fn facetings(&self, options: FacetingOptions) -> FacetingResult<Vec<Faceting>> {
+        let group = self.symmetries().ok_or(FacetingError::Symmetry)?;
+        Ok(FacetingSearch::new(self.vertices.clone(), group.vertex_perms, options)?.facetings())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{conc::ConcretePolytope, Polytope};
+
+    #[test]
+    fn tetrahedron() {
+        let tet = Concrete::simplex(Rank::new(3));
+        let facetings = tet.facetings(FacetingOptions::default()).unwrap();
+        assert_eq!(facetings.len(), 1);
+        assert!(facetings[0].is_isohedral());
+        assert_eq!(facetings[0].polytope.el_counts(), tet.el_counts());
+    }
+
+    #[test]
+    fn cube() {
+        let cube = Concrete::hypercube(Rank::new(3));
+        let facetings = cube.facetings(FacetingOptions::default()).unwrap();
+
+        // The stella octangula is a compound, so it isn't found.
+        assert!(facetings
+            .iter()
+            .any(|f| f.polytope.el_counts() == cube.el_counts()));
+        for f in &facetings {
+            f.polytope.abs.validate().unwrap();
+            assert_eq!(f.polytope.components().count(), 1);
+        }
+    }
+
+    /// The tetrahemihexahedron is a faceting of the octahedron, but only has
+    /// tetrahedral symmetry.
+    #[test]
+    fn tetrahemihexahedron() {
+        let oct = Concrete::orthoplex(Rank::new(3));
+        let options = FacetingOptions {
+            edge_lengths: Some(vec![oct.edge_lengths()[0]]),
+            ..Default::default()
+        };
+
+        let facetings = oct.facetings(options.clone()).unwrap();
+        assert_eq!(facetings.len(), 1);
+        assert_eq!(facetings[0].polytope.el_counts(), oct.el_counts());
+
+        // The tetrahedral subgroup changes an even number of signs.
+        let matrices: Vec<_> = oct
+            .symmetries()
+            .unwrap()
+            .matrices
+            .into_iter()
+            .filter(|m| m.iter().filter(|&&x| x < -0.5).count() % 2 == 0)
+            .collect();
+        assert_eq!(matrices.len(), 24);
+
+        let search = FacetingSearch::with_group(
+            oct.vertices.clone(),
+            Group::new(3, matrices.into_iter()),
+            options,
+        )
+        .unwrap();
+        assert_eq!(search.orbit_count(), 3);
+
+        let facetings = search.facetings();
+        assert_eq!(facetings.len(), 3);
+        assert_eq!(
+            facetings
+                .iter()
+                .filter(|f| f.polytope.el_counts() == vec![1, 6, 12, 7, 1].into())
+                .count(),
+            2
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            Concrete::polygon(4)
+                .facetings(FacetingOptions::default())
+                .unwrap_err(),
+            FacetingError::Dimension(2)
+        );
+
+        let oct = Concrete::orthoplex(Rank::new(3));
+        let options = FacetingOptions::default();
+        assert_eq!(
+            FacetingSearch::new(oct.vertices.clone(), vec![vec![0, 0, 1, 2, 3, 4]], options)
+                .unwrap_err(),
+            FacetingError::Group
+        );
+    }
+}
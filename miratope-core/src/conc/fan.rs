@@ -0,0 +1,211 @@
+//! Computes the normal fan and the face fan of a convex polytope.
+//!
+//! The normal cone of a face is the set of directions in which that face is
+//! the one that sticks out the furthest, and it's spanned by the outer normals
+//! of the facets that contain it. These cones cover the whole space, and
+//! together make up the normal fan. The face fan is made out of the cones
+//! from a point inside of the polytope over each of its faces instead. The
+//! face fan of a polytope is the normal fan of its polar dual.
+
+use std::collections::{HashMap, HashSet};
+
+use super::{Concrete, ConcretePolytope};
+use crate::{
+    abs::{elements::ElementRef, rank::Rank},
+    geometry::{Subspace, Vector},
+    Polytope,
+};
+
+use vec_like::VecLike;
+
+/// A fan of polyhedral cones, with one cone for each proper face of a
+/// polytope other than the empty face. Each cone is spanned by some of a
+/// common set of rays.
+#[derive(Clone, Debug)]
+pub struct Fan {
+    /// The rays spanning the cones.
+    pub rays: Vec<Vector>,
+
+    /// The indices of the rays spanning the cone for each face.
+    pub cones: HashMap<ElementRef, Vec<usize>>,
+}
+
+impl Fan {
+    /// Returns the indices of the rays spanning the cone for a face, or `None`
+    /// if the fan has no cone for it.
+    pub fn cone(&self, el: ElementRef) -> Option<&[usize]> {
+        self.cones.get(&el).map(Vec::as_slice)
+    }
+
+    /// Returns the rays spanning the cone for a face, or `None` if the fan has
+    /// no cone for it.
+    pub fn cone_rays(&self, el: ElementRef) -> Option<impl Iterator<Item = &Vector>> {
+        Some(self.cone(el)?.iter().map(move |&ray| &self.rays[ray]))
+    }
+}
+
+impl Concrete {
+    /// Returns whether the polytope spans the space it lives in, so that its
+    /// facets have well-defined outer normals.
+    fn is_full_dimensional(&self) -> bool {
+        self.rank() >= Rank::new(1) && self.dim() == self.rank().try_usize()
+    }
+
+    /// Returns the indices of the facets containing each face of rank at
+    /// least 0 and less than the polytope's.
+    fn facets_containing(&self) -> Vec<Vec<Vec<usize>>> {
+        let facet_rank = self.rank().minus_one();
+        let mut facets = vec![Vec::new(); facet_rank.plus_one_usize()];
+        facets[facet_rank.into_usize()] = (0..self.facet_count()).map(|idx| vec![idx]).collect();
+
+        for r in (0..facet_rank.into_usize()).rev() {
+            let rank = Rank::from(r);
+            let list = self[rank]
+                .iter()
+                .map(|el| {
+                    let mut set = HashSet::new();
+                    for &sup in &el.sups {
+                        set.extend(facets[r + 1][sup].iter().copied());
+                    }
+
+                    let mut list: Vec<_> = set.into_iter().collect();
+                    list.sort_unstable();
+                    list
+                })
+                .collect();
+            facets[r] = list;
+        }
+
+        facets
+    }
+
+    /// Returns the normal fan of a convex polytope. Its rays are the outer
+    /// unit normals of the facets, in the same order, and the normal cone of
+    /// each face is spanned by the normals of the facets that contain it.
+    ///
+    /// Returns `None` if the polytope isn't full-dimensional.
+    pub fn normal_fan(&self) -> Option<Fan> {
+        if !self.is_full_dimensional() {
+            return None;
+        }
+
+        let center = self.gravicenter()?;
+        let facet_rank = self.rank().minus_one();
+        let rays = (0..self.facet_count())
+            .map(|idx| {
+                let vertices = self.element_vertices_ref(ElementRef::new(facet_rank, idx))?;
+                let hyperplane = Subspace::from_points(vertices.into_iter());
+                if !hyperplane.is_hyperplane() {
+                    return None;
+                }
+
+                Some(-hyperplane.normal(&center)?)
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        let mut cones = HashMap::new();
+        for (r, facets) in self.facets_containing().into_iter().enumerate() {
+            for (idx, facets) in facets.into_iter().enumerate() {
+                cones.insert(ElementRef::new(Rank::from(r), idx), facets);
+            }
+        }
+
+        Some(Fan { rays, cones })
+    }
+
+    /// Returns the face fan of a convex polytope, taken from its gravicenter.
+    /// Its rays point from the gravicenter to each vertex, in the same order,
+    /// and the cone over each face is spanned by the rays to its vertices.
+    ///
+    /// Returns `None` if the polytope isn't full-dimensional.
+    pub fn face_fan(&self) -> Option<Fan> {
+        if !self.is_full_dimensional() {
+            return None;
+        }
+
+        let center = self.gravicenter()?;
+        let rays = self.vertices.iter().map(|v| v - &center).collect();
+
+        let mut cones = HashMap::new();
+        for r in 0..self.rank().into_usize() {
+            for idx in 0..self[Rank::from(r)].len() {
+                let el = ElementRef::new(Rank::from(r), idx);
+                let mut vertices = self.abs.element_vertices(el)?;
+                vertices.sort_unstable();
+                cones.insert(el, vertices);
+            }
+        }
+
+        Some(Fan { rays, cones })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Consts, Float};
+
+    /// Checks that every face sticks out the furthest in the directions of
+    /// the rays spanning its normal cone.
+    fn check_normal_fan(poly: &Concrete) {
+        let fan = poly.normal_fan().unwrap();
+        assert_eq!(fan.rays.len(), poly.facet_count());
+
+        for &el in fan.cones.keys() {
+            let vertices = poly.abs.element_vertices(el).unwrap();
+            for ray in fan.cone_rays(el).unwrap() {
+                let max = poly
+                    .vertices
+                    .iter()
+                    .map(|v| v.dot(ray))
+                    .fold(Float::NEG_INFINITY, Float::max);
+
+                for &v in &vertices {
+                    assert!((poly.vertices[v].dot(ray) - max).abs() < Float::EPS);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn normal_fan() {
+        check_normal_fan(&Concrete::hypercube(Rank::new(3)));
+        check_normal_fan(&Concrete::simplex(Rank::new(4)));
+        check_normal_fan(&Concrete::polygon(5).prism());
+
+        // The normal cones of the vertices of a cube are spanned by three
+        // rays, and those of its edges by two.
+        let fan = Concrete::hypercube(Rank::new(3)).normal_fan().unwrap();
+        assert_eq!(fan.cones.len(), 8 + 12 + 6);
+        assert_eq!(fan.cone(ElementRef::new(Rank::new(0), 0)).unwrap().len(), 3);
+        assert_eq!(fan.cone(ElementRef::new(Rank::new(1), 0)).unwrap().len(), 2);
+        assert_eq!(fan.cone(ElementRef::new(Rank::new(2), 0)).unwrap(), &[0]);
+    }
+
+    #[test]
+    fn face_fan() {
+        let cube = Concrete::hypercube(Rank::new(3));
+        let fan = cube.face_fan().unwrap();
+        assert_eq!(fan.rays.len(), 8);
+        assert_eq!(fan.cone(ElementRef::new(Rank::new(2), 0)).unwrap().len(), 4);
+
+        // The face fan of the cube is the normal fan of the octahedron, so
+        // its rays point along the diagonals.
+        for ray in &fan.rays {
+            for x in ray.iter() {
+                assert!((x.abs() - 0.5).abs() < Float::EPS);
+            }
+        }
+    }
+
+    #[test]
+    fn degenerate() {
+        let mut square = Concrete::polygon(4);
+        square.vertices.iter_mut().for_each(|v| {
+            *v = Vector::from_vec(vec![v[0], v[1], 0.0]);
+        });
+        assert!(square.normal_fan().is_none());
+        assert!(square.face_fan().is_none());
+        assert!(Concrete::point().normal_fan().is_none());
+    }
+}
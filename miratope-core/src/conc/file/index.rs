@@ -0,0 +1,253 @@
+//! An index of a folder of polytope files, which stores their names, ranks,
+//! and element counts, so that they can be searched without having to open
+//! each of them.
+
+use std::{
+    collections::HashMap,
+    ffi::OsStr,
+    fs, io,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use super::off::OffReader;
+use crate::{abs::rank::Rank, conc::metadata::Metadata};
+
+use serde::{Deserialize, Serialize};
+
+/// The name of the file in which the index of a folder is stored.
+pub const INDEX_FILE: &str = ".index";
+
+/// The information stored in the index about a single file.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct IndexEntry {
+    /// The path of the file, relative to the indexed folder.
+    pub path: PathBuf,
+
+    /// The name of the polytope. This is taken from the file's metadata if
+    /// possible, and from the file name otherwise.
+    pub name: String,
+
+    /// The rank of the polytope.
+    pub rank: Rank,
+
+    /// The number of elements of each rank, from the vertices up to the
+    /// facets.
+    pub el_counts: Vec<usize>,
+
+    /// The last time the file was modified, if known. Used to avoid reading
+    /// unchanged files whenever the index is updated.
+    pub modified: Option<SystemTime>,
+}
+
+impl IndexEntry {
+    /// Reads the entry for an OFF file, given its path and its path relative
+    /// to the indexed folder. Returns `None` if the file couldn't be read.
+    pub fn from_off(path: &Path, rel_path: PathBuf) -> Option<Self> {
+        let src = fs::read_to_string(path).ok()?;
+        let header = OffReader::new(&src).header().ok()?;
+
+        let name = Metadata::from_off_src(&src)
+            .and_then(|metadata| metadata.name)
+            .unwrap_or_else(|| {
+                path.file_stem()
+                    .map(|stem| stem.to_string_lossy().into_owned())
+                    .unwrap_or_default()
+            });
+
+        Some(Self {
+            path: rel_path,
+            name,
+            rank: header.rank,
+            el_counts: header.el_counts,
+            modified: modified(path),
+        })
+    }
+
+    /// Returns whether the entry matches a given query.
+    pub fn matches(&self, query: &IndexQuery) -> bool {
+        if let Some(rank) = query.rank {
+            if rank != self.rank {
+                return false;
+            }
+        }
+
+        let text = query.text.to_lowercase();
+        text.split_whitespace().all(|word| {
+            self.name.to_lowercase().contains(word)
+                || self.path.to_string_lossy().to_lowercase().contains(word)
+        })
+    }
+}
+
+/// Returns the last time a file was modified, if known.
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).ok()?.modified().ok()
+}
+
+/// A search query over a [`LibraryIndex`].
+#[derive(Clone, Debug, Default)]
+pub struct IndexQuery {
+    /// Whitespace separated words, all of which must be contained either in
+    /// the name or in the path of a file. Case insensitive.
+    pub text: String,
+
+    /// The rank of the polytope, if we want to filter by it.
+    pub rank: Option<Rank>,
+}
+
+impl IndexQuery {
+    /// Returns whether the query is trivial, i.e. it matches everything.
+    pub fn is_empty(&self) -> bool {
+        self.text.trim().is_empty() && self.rank.is_none()
+    }
+}
+
+/// An index of all of the OFF files in a folder and its subfolders.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct LibraryIndex {
+    /// The entries of the index, sorted by path.
+    pub entries: Vec<IndexEntry>,
+}
+
+impl LibraryIndex {
+    /// Initializes a new empty index.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Builds the index of a folder from scratch.
+    pub fn scan<T: AsRef<Path>>(root: T) -> io::Result<Self> {
+        let mut index = Self::new();
+        index.update(root)?;
+        Ok(index)
+    }
+
+    /// Updates the index of a folder. Only the files that have been modified
+    /// since they were last indexed are read again.
+    pub fn update<T: AsRef<Path>>(&mut self, root: T) -> io::Result<()> {
+        let root = root.as_ref();
+
+        let mut old_entries: HashMap<_, _> = std::mem::take(&mut self.entries)
+            .into_iter()
+            .map(|entry| (entry.path.clone(), entry))
+            .collect();
+
+        // Walks through the folder tree.
+        let mut folders = vec![root.to_path_buf()];
+        while let Some(folder) = folders.pop() {
+            for entry in fs::read_dir(&folder)? {
+                let path = entry?.path();
+
+                // Skips hidden files, like the index itself.
+                let hidden = match path.file_name() {
+                    Some(name) => name.to_string_lossy().starts_with('.'),
+                    None => true,
+                };
+
+                if hidden {
+                    continue;
+                }
+
+                if path.is_dir() {
+                    folders.push(path);
+                } else if path.extension() == Some(OsStr::new("off")) {
+                    let rel_path = path.strip_prefix(root).unwrap_or(&path).to_path_buf();
+
+                    // Reuses the old entry if the file hasn't changed.
+                    if let Some(old_entry) = old_entries.remove(&rel_path) {
+                        if old_entry.modified.is_some() && old_entry.modified == modified(&path) {
+                            self.entries.push(old_entry);
+                            continue;
+                        }
+                    }
+
+                    if let Some(entry) = IndexEntry::from_off(&path, rel_path) {
+                        self.entries.push(entry);
+                    }
+                }
+            }
+        }
+
+        self.entries.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(())
+    }
+
+    /// Loads the index stored in a folder, if it exists.
+    pub fn load<T: AsRef<Path>>(root: T) -> Option<Self> {
+        ron::from_str(&fs::read_to_string(root.as_ref().join(INDEX_FILE)).ok()?).ok()
+    }
+
+    /// Stores the index in a folder.
+    pub fn save<T: AsRef<Path>>(&self, root: T) -> io::Result<()> {
+        let ron =
+            ron::to_string(self).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        fs::write(root.as_ref().join(INDEX_FILE), ron)
+    }
+
+    /// Returns an iterator over all entries matching a given query.
+    pub fn search<'a>(&'a self, query: &'a IndexQuery) -> impl Iterator<Item = &'a IndexEntry> {
+        self.entries
+            .iter()
+            .filter(move |entry| entry.matches(query))
+    }
+
+    /// Returns the number of indexed files.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns whether the index is empty.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        conc::{file::off::OffOptions, Concrete},
+        Polytope,
+    };
+
+    /// Checks that a folder gets correctly indexed and searched.
+    #[test]
+    fn index() {
+        let root = std::env::temp_dir().join(format!("miratope-index-{}", std::process::id()));
+        let sub = root.join("polychora");
+        fs::create_dir_all(&sub).unwrap();
+
+        let mut cube = Concrete::hypercube(Rank::new(3));
+        cube.metadata_mut().name = Some("Cube".to_string());
+        cube.to_path(&root.join("hexahedron.off"), OffOptions::default())
+            .unwrap();
+        Concrete::simplex(Rank::new(4))
+            .to_path(&sub.join("pen.off"), OffOptions::default())
+            .unwrap();
+        fs::write(root.join("notes.txt"), "not a polytope").unwrap();
+
+        let index = LibraryIndex::scan(&root).unwrap();
+        index.save(&root).unwrap();
+        let index = LibraryIndex::load(&root).unwrap();
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(index.len(), 2);
+        assert_eq!(index.entries[0].name, "Cube");
+        assert_eq!(index.entries[0].el_counts, vec![8, 12, 6]);
+        assert_eq!(index.entries[1].name, "pen");
+        assert_eq!(index.entries[1].rank, Rank::new(4));
+        assert_eq!(index.entries[1].el_counts, vec![5, 10, 10, 5]);
+
+        let query = |text: &str, rank: Option<isize>| IndexQuery {
+            text: text.to_string(),
+            rank: rank.map(Rank::new),
+        };
+
+        assert_eq!(index.search(&query("cube", None)).count(), 1);
+        assert_eq!(index.search(&query("hexa", None)).count(), 1);
+        assert_eq!(index.search(&query("polychora pen", None)).count(), 1);
+        assert_eq!(index.search(&query("", Some(4))).count(), 1);
+        assert_eq!(index.search(&query("cube", Some(4))).count(), 0);
+    }
+}
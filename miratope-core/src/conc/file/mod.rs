@@ -1,11 +1,17 @@
 //! Reading from and writing to files in various different formats.
 
 pub mod ggb;
+#[cfg(feature = "serde-serialize")]
+pub mod index;
+pub mod obj;
 pub mod off;
+pub mod text;
 
 use self::{
     ggb::{GgbError, GgbResult},
+    obj::{ObjError, ObjResult},
     off::{OffReader, OffResult},
+    text::TextResult,
 };
 
 use super::Concrete;
@@ -24,6 +30,9 @@ pub enum FileError<'a> {
     /// An error while reading a GGB file.
     GgbError(GgbError),
 
+    /// An error while reading an OBJ file.
+    ObjError(ObjError),
+
     /// Some generic I/O error occured.
     IoError(IoError),
 
@@ -43,6 +52,7 @@ impl<'a> std::fmt::Display for FileError<'a> {
         match self {
             FileError::OffError(err) => write!(f, "OFF error: {}", err),
             FileError::GgbError(err) => write!(f, "GGB error: {}", err),
+            FileError::ObjError(err) => write!(f, "OBJ error: {}", err),
             FileError::IoError(err) => write!(f, "IO error: {}", err),
             FileError::ZipError(err) => {
                 write!(f, "ZIP error encountered while opening GGB: {}", err)
@@ -69,6 +79,13 @@ impl<'a> From<GgbError> for FileError<'a> {
     }
 }
 
+/// [`ObjError`] is a type of [`FileError`].
+impl<'a> From<ObjError> for FileError<'a> {
+    fn from(err: ObjError) -> Self {
+        Self::ObjError(err)
+    }
+}
+
 /// [`FromUtf8Error`] is a type of [`FileError`].
 impl<'a> From<FromUtf8Error> for FileError<'a> {
     fn from(err: FromUtf8Error) -> Self {
@@ -93,7 +110,7 @@ impl<'a> From<ZipError> for FileError<'a> {
 /// The result of loading a polytope from a file.
 pub type FileResult<'a, T> = Result<T, FileError<'a>>;
 
-/// A trait for polytopes that can be read from an OFF file or a GGB file.
+/// A trait for polytopes that can be read from an OFF, GGB, or OBJ file.
 pub trait FromFile: Sized {
     /// Converts an OFF file into a new struct of type `Self`.
     ///
@@ -105,6 +122,22 @@ pub trait FromFile: Sized {
     /// 3D.
     fn from_ggb(file: File) -> GgbResult<Self>;
 
+    /// Attempts to read an OBJ file. If succesful, outputs a polyhedron.
+    fn from_obj(src: &str) -> ObjResult<Self>;
+
+    /// Reads a polytope from some text, as might be pasted from the
+    /// clipboard. See [`text`] for the supported formats.
+    fn from_text(src: &str) -> TextResult<Self>
+    where
+        Self: From<Concrete>,
+    {
+        if text::is_off(src) {
+            Ok(Self::from_off(src)?)
+        } else {
+            text::parse_text(src).map(Into::into)
+        }
+    }
+
     /// Loads a polytope from a file path.
     fn from_path<U: AsRef<std::path::Path>>(fp: &U) -> FileResult<Self> {
         use std::{ffi::OsStr, fs};
@@ -125,6 +158,12 @@ pub trait FromFile: Sized {
             // Reads the file as a GGB file.
             "ggb" => Ok(Self::from_ggb(fs::File::open(fp)?)?),
 
+            // Reads the file as an OBJ file.
+            "obj" => match String::from_utf8(fs::read(fp)?) {
+                Ok(src) => Ok(Self::from_obj(&src)?),
+                Err(err) => Err(err.into()),
+            },
+
             // Could not recognize the file extension.
             ext => Err(FileError::InvalidExtension(ext)),
         }
@@ -153,4 +192,8 @@ impl FromFile for Concrete {
             Err(GgbError::InvalidGgb)
         }
     }
+
+    fn from_obj(src: &str) -> ObjResult<Self> {
+        obj::parse_obj(src)
+    }
 }
@@ -0,0 +1,199 @@
+//! Contains the code that opens an OBJ file and parses it into a polytope.
+//!
+//! Only the vertex (`v`) and face (`f`) statements are read. Since an OBJ file
+//! can only store polyhedra, the result always has rank 3.
+
+use std::collections::HashMap;
+
+use crate::{
+    abs::elements::{AbstractBuilder, SubelementList, Subelements},
+    conc::Concrete,
+    geometry::Point,
+};
+
+use vec_like::VecLike;
+
+/// Any error encountered while parsing an OBJ file.
+#[derive(Clone, Copy, Debug)]
+pub enum ObjError {
+    /// The file has no faces.
+    Empty,
+
+    /// Could not parse a number in a given line.
+    Parsing(usize),
+
+    /// A vertex doesn't have the same number of coordinates as the first.
+    Dimension(usize),
+
+    /// A face refers to a vertex that doesn't exist.
+    InvalidIndex(usize),
+
+    /// A face has less than three vertices.
+    DegenerateFace(usize),
+}
+
+impl std::fmt::Display for ObjError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Empty => write!(f, "file has no faces"),
+            Self::Parsing(line) => write!(f, "could not parse number at line {}", line + 1),
+            Self::Dimension(line) => {
+                write!(f, "vertex at line {} has the wrong dimension", line + 1)
+            }
+            Self::InvalidIndex(line) => write!(f, "invalid vertex index at line {}", line + 1),
+            Self::DegenerateFace(line) => {
+                write!(f, "face at line {} has less than 3 vertices", line + 1)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ObjError {}
+
+/// The result of parsing an OBJ file.
+pub type ObjResult<T> = Result<T, ObjError>;
+
+/// Parses a vertex index in a face statement, which might be of the form
+/// `v`, `v/vt`, `v//vn` or `v/vt/vn`. Indices start at 1, and negative indices
+/// count backwards from the last vertex read.
+fn parse_index(token: &str, vertex_count: usize, line: usize) -> ObjResult<usize> {
+    let idx: isize = token
+        .split('/')
+        .next()
+        .unwrap_or_default()
+        .parse()
+        .map_err(|_| ObjError::Parsing(line))?;
+
+    let idx = if idx > 0 {
+        idx - 1
+    } else {
+        vertex_count as isize + idx
+    };
+
+    if idx >= 0 && (idx as usize) < vertex_count {
+        Ok(idx as usize)
+    } else {
+        Err(ObjError::InvalidIndex(line))
+    }
+}
+
+/// Builds a concrete polytope from the source of an OBJ file.
+pub fn parse_obj(src: &str) -> ObjResult<Concrete> {
+    let mut vertices: Vec<Point> = Vec::new();
+    let mut edges = SubelementList::new();
+    let mut faces = SubelementList::new();
+    let mut hash_edges = HashMap::new();
+
+    for (line, text) in src.lines().enumerate() {
+        // Removes comments.
+        let text = text.split('#').next().unwrap_or_default();
+        let mut tokens = text.split_whitespace();
+
+        match tokens.next() {
+            // Reads a vertex.
+            Some("v") => {
+                let vertex = tokens
+                    .map(|t| t.parse().map_err(|_| ObjError::Parsing(line)))
+                    .collect::<ObjResult<Vec<_>>>()?;
+
+                if let Some(first) = vertices.first() {
+                    if first.len() != vertex.len() {
+                        return Err(ObjError::Dimension(line));
+                    }
+                }
+
+                vertices.push(vertex.into());
+            }
+
+            // Reads a face, and the edges in it.
+            Some("f") => {
+                let face_verts = tokens
+                    .map(|t| parse_index(t, vertices.len(), line))
+                    .collect::<ObjResult<Vec<_>>>()?;
+
+                let n = face_verts.len();
+                if n < 3 {
+                    return Err(ObjError::DegenerateFace(line));
+                }
+
+                let mut face = Subelements::with_capacity(n);
+                for i in 0..n {
+                    let mut edge = Subelements(vec![face_verts[i], face_verts[(i + 1) % n]]);
+                    edge.sort();
+
+                    let len = edges.len();
+                    let idx = *hash_edges.entry(edge.clone()).or_insert(len);
+                    if idx == len {
+                        edges.push(edge);
+                    }
+
+                    face.push(idx);
+                }
+
+                faces.push(face);
+            }
+
+            // Any other statement is ignored.
+            _ => {}
+        }
+    }
+
+    if faces.is_empty() {
+        return Err(ObjError::Empty);
+    }
+
+    let mut abs = AbstractBuilder::new();
+    abs.push_min();
+    abs.push_vertices(vertices.len());
+    abs.push(edges);
+    abs.push(faces);
+    abs.push_max();
+
+    Ok(Concrete::new(vertices, abs.build()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Polytope;
+
+    #[test]
+    /// Checks that a cube is correctly read.
+    fn cube() {
+        let cube = parse_obj(
+            "# A cube.
+            o cube
+            v -1 -1 -1
+            v 1 -1 -1
+            v 1 1 -1
+            v -1 1 -1
+            v -1 -1 1
+            v 1 -1 1
+            v 1 1 1
+            v -1 1 1
+            vn 0 0 1
+            f 1 2 3 4
+            f 5/1 6/1 7/1 8/1
+            f 1//1 2//1 6//1 5//1
+            f 2 3 7 6
+            f -5 -6 -2 -1
+            f 4 1 5 8",
+        )
+        .unwrap();
+
+        cube.abs.is_valid().unwrap();
+        assert_eq!(cube.el_counts(), vec![1, 8, 12, 6, 1].into());
+    }
+
+    #[test]
+    #[should_panic(expected = "InvalidIndex(1)")]
+    fn invalid_index() {
+        parse_obj("v 0 0 0\nf 1 2 3").unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "Empty")]
+    fn empty() {
+        parse_obj("v 0 0 0").unwrap();
+    }
+}
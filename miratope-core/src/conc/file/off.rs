@@ -1,17 +1,23 @@
 //! Contains the code that opens an OFF file and parses it into a polytope.
 
-use std::{collections::HashMap, io::Result as IoResult, path::Path, str::FromStr};
+use std::{
+    collections::HashMap,
+    io::Result as IoResult,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
+#[cfg(feature = "serde-serialize")]
+use crate::conc::metadata::Metadata;
 use crate::{
     abs::{
-        elements::{AbstractBuilder, SubelementList},
+        elements::{AbstractBuilder, ElementRef, SubelementList},
         rank::Rank,
     },
-    conc::{Concrete, ElementList, Point, Polytope, RankVec, Subelements},
+    conc::{Concrete, ConcretePolytope, ElementList, Point, Polytope, RankVec, Subelements},
     COMPONENTS, ELEMENT_NAMES,
 };
 
-use petgraph::{graph::NodeIndex, visit::Dfs, Graph};
 use vec_like::VecLike;
 
 /// A position in a file.
@@ -215,6 +221,19 @@ impl<'a> Iterator for TokenIter<'a> {
     }
 }
 
+/// The header of an OFF file, which can be read without building the entire
+/// polytope.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OffHeader {
+    /// The rank of the polytope.
+    pub rank: Rank,
+
+    /// The number of elements of each rank, from the vertices up to the
+    /// facets. The edge count isn't guaranteed to be correct, as many OFF
+    /// files just store a zero in its place.
+    pub el_counts: Vec<usize>,
+}
+
 /// An auxiliary struct that reads through an OFF file and builds a concrete
 /// polytope out of it.
 pub struct OffReader<'a> {
@@ -399,8 +418,48 @@ impl<'a> OffReader<'a> {
             .flatten()
     }*/
 
-    /// Builds a concrete polytope from the OFF reader.
-    pub fn build(mut self) -> OffResult<Concrete> {
+    /// Reads only the header of the OFF file, consisting of its rank and its
+    /// element counts. This is much faster than building the entire polytope.
+    pub fn header(mut self) -> OffResult<OffHeader> {
+        let rank = self.rank()?;
+
+        // Deals with dumb degenerate cases.
+        let el_counts = match rank.try_usize() {
+            None => Vec::new(),
+            Some(0) => vec![1],
+            Some(1) => vec![2],
+            Some(r) => {
+                let mut el_nums = self.el_nums(rank)?;
+                el_nums.truncate(r);
+                el_nums
+            }
+        };
+
+        Ok(OffHeader { rank, el_counts })
+    }
+
+    /// Builds a concrete polytope from the OFF reader, together with any
+    /// [`Metadata`](crate::conc::metadata::Metadata) stored in it. Metadata is only read when the
+    /// `serde-serialize` feature is enabled.
+    pub fn build(self) -> OffResult<Concrete> {
+        #[cfg(feature = "serde-serialize")]
+        {
+            let metadata = Metadata::from_off_src(self.src());
+            let mut polytope = self.build_polytope()?;
+
+            if let Some(metadata) = metadata {
+                polytope.set_metadata(metadata);
+            }
+
+            Ok(polytope)
+        }
+
+        #[cfg(not(feature = "serde-serialize"))]
+        self.build_polytope()
+    }
+
+    /// Builds a concrete polytope from the OFF reader, ignoring any metadata.
+    fn build_polytope(mut self) -> OffResult<Concrete> {
         // Reads the rank of the polytope.
         let rank = self.rank()?;
 
@@ -541,6 +600,11 @@ impl<'a> OffWriter<'a> {
             el_counts.swap(Rank::new(1), Rank::new(2));
         }
 
+        // Polygons store their components in place of their edges.
+        if rank == Rank::new(2) {
+            el_counts[Rank::new(1)] = el_counts[Rank::new(2)];
+        }
+
         for r in Rank::range_iter(0, rank) {
             self.off.push_str(&el_counts[r].to_string());
             self.off.push(' ');
@@ -592,7 +656,7 @@ impl<'a> OffWriter<'a> {
 
             // Maps an OFF index into a graph index.
             let mut hash_edges = HashMap::new();
-            let mut graph = Graph::new_undirected();
+            let mut vertices = Vec::new();
 
             // Maps the vertex indices to consecutive integers from 0.
             for &edge_idx in &face.subs {
@@ -607,7 +671,7 @@ impl<'a> OffWriter<'a> {
                             hash_edges.insert(vertex_idx, idx);
                             hash_edge.push(idx);
 
-                            graph.add_node(vertex_idx);
+                            vertices.push(vertex_idx);
                         }
                     }
                 }
@@ -622,20 +686,27 @@ impl<'a> OffWriter<'a> {
             );
 
             // Adds the edges to the graph.
+            let mut adjacency = vec![Vec::new(); vertices.len()];
             for &edge_idx in &face.subs.0 {
                 let edge = &edges[edge_idx];
-                graph.add_edge(
-                    NodeIndex::new(*hash_edges.get(&edge.subs[0]).unwrap()),
-                    NodeIndex::new(*hash_edges.get(&edge.subs[1]).unwrap()),
-                    (),
-                );
+                let v = *hash_edges.get(&edge.subs[0]).unwrap();
+                let w = *hash_edges.get(&edge.subs[1]).unwrap();
+                adjacency[v].push(w);
+                adjacency[w].push(v);
             }
 
-            // Retrieves the cycle of vertices.
-            let mut dfs = Dfs::new(&graph, NodeIndex::new(0));
-            while let Some(nx) = dfs.next(&graph) {
+            // Retrieves the cycle of vertices through a depth-first search.
+            let mut visited = vec![false; vertices.len()];
+            let mut stack = vec![0];
+            while let Some(v) = stack.pop() {
+                if visited[v] {
+                    continue;
+                }
+                visited[v] = true;
+
                 self.off.push(' ');
-                self.off.push_str(&graph[nx].to_string());
+                self.off.push_str(&vertices[v].to_string());
+                stack.extend(adjacency[v].iter().filter(|&&w| !visited[w]));
             }
             self.off.push('\n');
         }
@@ -683,6 +754,12 @@ impl<'a> OffWriter<'a> {
             );
         }
 
+        // Metadata is always written, so that it survives a reload.
+        #[cfg(feature = "serde-serialize")]
+        if let Some(metadata) = self.polytope.metadata() {
+            self.off += &metadata.to_off_comment();
+        }
+
         // Writes header.
         if rank != Rank::new(3) {
             self.off += &rank.to_string();
@@ -724,6 +801,50 @@ impl Concrete {
     pub fn to_path(&self, fp: &impl AsRef<Path>, opt: OffOptions) -> IoResult<()> {
         std::fs::write(fp, self.to_off(opt))
     }
+
+    /// Writes every element of a given rank into its own OFF file inside of a
+    /// folder, which is created if it doesn't exist. Each element is flattened
+    /// into its own subspace and recentered.
+    ///
+    /// Files are named `type-{t}-{idx}.off`, where `t` is the index of the
+    /// [element type](Concrete::element_type_indices) of the element and `idx`
+    /// is its index, so that the elements in the same orbit are listed
+    /// together. Returns the paths of the written files, in order of index.
+    pub fn export_elements(&self, rank: Rank, dir: &impl AsRef<Path>) -> IoResult<Vec<PathBuf>> {
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)?;
+
+        let types = self.element_type_indices();
+        let types = match types.get(rank) {
+            Some(types) => types,
+            None => return Ok(Vec::new()),
+        };
+
+        // Indices are padded so that the files sort correctly.
+        let type_width = types.iter().max().map_or(1, |t| t.to_string().len());
+        let idx_width = types.len().saturating_sub(1).to_string().len();
+
+        let mut paths = Vec::with_capacity(types.len());
+        for (idx, t) in types.iter().enumerate() {
+            let mut element = self
+                .element(ElementRef::new(rank, idx))
+                .expect("Element not found.");
+            element.flatten();
+            element.recenter();
+
+            let path = dir.join(format!(
+                "type-{:0tw$}-{:0iw$}.off",
+                t,
+                idx,
+                tw = type_width,
+                iw = idx_width
+            ));
+            element.to_path(&path, Default::default())?;
+            paths.push(path);
+        }
+
+        Ok(paths)
+    }
 }
 
 #[cfg(test)]
@@ -784,6 +905,16 @@ mod tests {
     }
     */
 
+    #[test]
+    /// Checks that a polygon is written with its component count in place of
+    /// its edge count, and that it has the correct amount of elements.
+    fn polygon_nums() {
+        let peg = Concrete::polygon(5);
+        let off = peg.to_off(OffOptions { comments: false });
+        assert!(off.starts_with("2OFF\n5 1 \n"), "Wrong header: {}", off);
+        test_shape(peg, vec![1, 5, 5, 1])
+    }
+
     #[test]
     /// Checks that a tetrahedron has the correct amount of elements.
     fn tet_nums() {
@@ -829,6 +960,37 @@ mod tests {
         test_shape(tet, vec![1, 4, 6, 4, 1])
     }
 
+    #[test]
+    /// Checks that the faces of a triangular prism get exported into their own
+    /// files, with the triangles and the squares told apart.
+    fn export_elements() {
+        use std::collections::HashMap;
+
+        let dir = std::env::temp_dir().join(format!("miratope-export-{}", std::process::id()));
+        let trip = Concrete::polygon(3).prism();
+        let paths = trip.export_elements(Rank::new(2), &dir).unwrap();
+        assert_eq!(paths.len(), 5);
+
+        // Maps every element type to the vertex counts of its faces.
+        let mut types = HashMap::new();
+        for path in &paths {
+            let name = path.file_name().unwrap().to_string_lossy().into_owned();
+            let t = name.split('-').nth(1).unwrap().to_string();
+            let face = Concrete::from_path(path).unwrap();
+            assert_eq!(face.dim(), Some(2), "Exported face wasn't flattened.");
+
+            types
+                .entry(t)
+                .or_insert_with(Vec::new)
+                .push(face.vertex_count());
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let mut types: Vec<_> = types.into_values().collect();
+        types.sort();
+        assert_eq!(types, vec![vec![3, 3], vec![4, 4, 4]]);
+    }
+
     #[test]
     #[should_panic(expected = "Empty")]
     fn empty() {
@@ -0,0 +1,208 @@
+//! Contains the code that builds a polytope from a piece of text, as might be
+//! pasted from the clipboard.
+//!
+//! The text can either be the contents of an OFF file, a list of coordinates,
+//! or a Coxeter diagram in inline ASCII notation. In the latter two cases, the
+//! result is the convex hull of the corresponding points. Coxeter diagrams
+//! are only read when the `cd` feature is enabled.
+
+use super::off::OffError;
+#[cfg(feature = "cd")]
+use crate::group::{
+    cd::{Cd, Node},
+    Group,
+};
+use crate::{
+    conc::{convex, Concrete},
+    geometry::Point,
+    group::cd::CdError,
+};
+
+/// Any error encountered while reading a polytope from text.
+#[derive(Clone, Copy, Debug)]
+pub enum TextError {
+    /// The text is empty.
+    Empty,
+
+    /// An error while reading the text as an OFF file.
+    OffError(OffError),
+
+    /// An error while reading the text as a Coxeter diagram.
+    CdError(CdError),
+
+    /// The Coxeter diagram has snub nodes, which aren't supported.
+    Snub,
+
+    /// The Coxeter diagram has a rational edge, so it doesn't describe a
+    /// convex polytope.
+    Star,
+
+    /// The Coxeter diagram doesn't describe a finite polytope.
+    Infinite,
+
+    /// The text isn't a list of points, and Coxeter diagrams can't be read
+    /// since the `cd` feature is disabled.
+    Unsupported,
+}
+
+impl std::fmt::Display for TextError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Empty => write!(f, "text is empty"),
+            Self::OffError(err) => write!(f, "OFF error: {}", err),
+            Self::CdError(err) => write!(f, "text is neither a list of points nor a CD: {}", err),
+            Self::Snub => write!(f, "CDs with snub nodes aren't supported"),
+            Self::Star => write!(f, "CDs with rational edges aren't supported"),
+            Self::Infinite => write!(f, "CD doesn't describe a finite polytope"),
+            Self::Unsupported => write!(f, "text is not a list of points"),
+        }
+    }
+}
+
+impl std::error::Error for TextError {}
+
+/// [`OffError`] is a type of [`TextError`].
+impl From<OffError> for TextError {
+    fn from(err: OffError) -> Self {
+        Self::OffError(err)
+    }
+}
+
+/// [`CdError`] is a type of [`TextError`].
+impl From<CdError> for TextError {
+    fn from(err: CdError) -> Self {
+        Self::CdError(err)
+    }
+}
+
+/// The result of reading a polytope from text.
+pub type TextResult<T> = Result<T, TextError>;
+
+/// Returns whether the text should be read as an OFF file, i.e. whether its
+/// first line that isn't a comment starts with `OFF`, `4OFF`, etc.
+pub fn is_off(src: &str) -> bool {
+    let first_word = src
+        .lines()
+        .map(str::trim_start)
+        .find(|line| !line.is_empty() && !line.starts_with('#'))
+        .and_then(|line| line.split_whitespace().next());
+
+    matches!(first_word, Some(word) if word.ends_with("OFF"))
+}
+
+/// Attempts to read a list of points, one per row. Coordinates may be
+/// separated by whitespace or commas, and rows by newlines, semicolons, or
+/// closing brackets. This means that lists like `[[1, 0], [0, 1]]` are also
+/// accepted.
+///
+/// Returns `None` if the text isn't a list of points of a single dimension.
+pub fn parse_points(src: &str) -> Option<Vec<Point>> {
+    let mut points = Vec::new();
+
+    for row in src.split(&['\n', ';', ']', ')'][..]) {
+        let coords = row
+            .split(|c: char| c.is_whitespace() || matches!(c, ',' | '[' | '('))
+            .filter(|token| !token.is_empty())
+            .map(str::parse)
+            .collect::<Result<Vec<_>, _>>()
+            .ok()?;
+
+        if !coords.is_empty() {
+            points.push(Point::from_vec(coords));
+        }
+    }
+
+    let dim = points.first()?.len();
+    if points.iter().all(|p| p.len() == dim) {
+        Some(points)
+    } else {
+        None
+    }
+}
+
+/// Builds the convex polytope described by a Coxeter diagram, as the convex
+/// hull of the orbit of its generator point.
+#[cfg(feature = "cd")]
+pub fn parse_cd(src: &str) -> TextResult<Concrete> {
+    let cd = Cd::parse(src)?;
+
+    if cd.node_iter().any(|node| matches!(node, Node::Snub(_))) {
+        return Err(TextError::Snub);
+    }
+
+    let cox = cd.cox();
+    if cox.as_ref().iter().any(|x| x.fract() != 0.0) {
+        return Err(TextError::Star);
+    }
+
+    let generator = cd.generator().ok_or(TextError::Infinite)?;
+    let group = Group::cox_group(cox).ok_or(TextError::Infinite)?;
+    Ok(group.into_polytope(generator))
+}
+
+/// Coxeter diagrams can't be read without the `cd` feature.
+#[cfg(not(feature = "cd"))]
+pub fn parse_cd(_src: &str) -> TextResult<Concrete> {
+    Err(TextError::Unsupported)
+}
+
+/// Reads a polytope from some text that isn't an OFF file. This is first
+/// attempted to be read as a list of points, and then as a Coxeter diagram.
+pub fn parse_text(src: &str) -> TextResult<Concrete> {
+    let src = src.trim();
+
+    if src.is_empty() {
+        Err(TextError::Empty)
+    } else if let Some(points) = parse_points(src) {
+        Ok(convex::convex_hull(points))
+    } else {
+        parse_cd(src)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{conc::file::FromFile, Polytope};
+
+    /// Checks that some text is read into a polytope with the expected
+    /// element counts.
+    fn test(src: &str, el_counts: Vec<usize>) {
+        let poly = Concrete::from_text(src).unwrap();
+        poly.abs.is_valid().unwrap();
+        assert_eq!(poly.el_counts(), el_counts.into());
+    }
+
+    #[test]
+    fn points() {
+        test("0 0\n1 0\n0 1\n1 1", vec![1, 4, 4, 1]);
+        test("[[1, 0, 0], [0, 1, 0], [0, 0, 1]]", vec![1, 3, 3, 1]);
+        test("(1, 1); (2, 2)", vec![1, 2, 1]);
+    }
+
+    #[test]
+    #[cfg(feature = "cd")]
+    fn cd() {
+        test("x4o3o", vec![1, 8, 12, 6, 1]);
+        test("x3o3x", vec![1, 12, 24, 14, 1]);
+    }
+
+    #[test]
+    fn off() {
+        let tet = Concrete::simplex(crate::abs::rank::Rank::new(3)).to_off(Default::default());
+        test(&tet, vec![1, 4, 6, 4, 1]);
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(matches!(parse_text("  "), Err(TextError::Empty)));
+    }
+
+    #[test]
+    #[cfg(feature = "cd")]
+    fn invalid_cd() {
+        assert!(matches!(parse_text("s4s3s"), Err(TextError::Snub)));
+        assert!(matches!(parse_text("x5/2o"), Err(TextError::Star)));
+        assert!(matches!(parse_text("1 2\n3"), Err(TextError::CdError(_))));
+    }
+}
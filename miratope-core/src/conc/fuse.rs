@@ -0,0 +1,151 @@
+//! Fuses the vertices of a polytope that lie within some distance of one
+//! another, along with the elements that end up being the same. This cleans up
+//! the near-duplicate vertices that often show up in imported files and in the
+//! results of degenerate operations.
+
+use std::collections::HashMap;
+
+use super::Concrete;
+use crate::{
+    abs::{
+        elements::{AbstractBuilder, SubelementList, Subelements},
+        rank::Rank,
+        AbstractResult,
+    },
+    geometry::PointSet,
+    Float, Polytope,
+};
+
+use vec_like::VecLike;
+
+impl Concrete {
+    /// Merges every set of vertices of the polytope within a distance of `eps`
+    /// of one another into a single vertex, keeping the position of the first
+    /// of them. Then, rank by rank from the edges up, elements that end up
+    /// with the same subelements are merged, and elements that end up with
+    /// fewer than two subelements, like the edges between merged vertices,
+    /// are removed.
+    ///
+    /// The lattice is rebuilt even if it turns out to be invalid, in which case
+    /// the first error found is returned. Polytopes of rank less than 1 are
+    /// left as they are.
+    pub fn fuse(&mut self, eps: Float) -> AbstractResult<()> {
+        let rank = self.rank();
+        if rank < Rank::new(1) {
+            return Ok(());
+        }
+
+        let (set, vertex_map) = PointSet::dedup(self.vertices.drain(..), eps);
+        self.vertices = set.into_points();
+
+        let mut abs = AbstractBuilder::with_capacity(rank);
+        abs.push_min();
+        abs.push_vertices(self.vertices.len());
+
+        // The index of every element of the previous rank in the new polytope,
+        // or `None` if it was removed.
+        let mut map: Vec<_> = vertex_map.into_iter().map(Some).collect();
+        for r in Rank::range_iter(Rank::new(1), rank) {
+            let mut subelements = SubelementList::new();
+            let mut indices = HashMap::new();
+
+            map = self.abs[r]
+                .iter()
+                .map(|el| {
+                    let mut subs: Vec<_> = el.subs.iter().filter_map(|&s| map[s]).collect();
+                    subs.sort_unstable();
+                    subs.dedup();
+                    if subs.len() < 2 {
+                        return None;
+                    }
+
+                    let len = subelements.len();
+                    let idx = *indices.entry(subs.clone()).or_insert(len);
+                    if idx == len {
+                        subelements.push(Subelements(subs));
+                    }
+                    Some(idx)
+                })
+                .collect();
+
+            abs.push(subelements);
+        }
+
+        abs.push_max();
+        self.abs = abs.build();
+        self.abs.is_valid()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        conc::{file::FromFile, ConcretePolytope},
+        Consts,
+    };
+
+    #[test]
+    fn loose_faces() {
+        // A cube in which every face has its own vertices, slightly off.
+        let mut off = String::from("OFF 24 6 0\n");
+        let faces = [
+            [0, 1, 3, 2],
+            [4, 5, 7, 6],
+            [0, 1, 5, 4],
+            [2, 3, 7, 6],
+            [0, 2, 6, 4],
+            [1, 3, 7, 5],
+        ];
+        for (f, face) in faces.iter().enumerate() {
+            for &v in face {
+                let offset = f as Float * 1e-12;
+                off.push_str(&format!(
+                    "{} {} {}\n",
+                    (v & 1) as Float + offset,
+                    ((v >> 1) & 1) as Float,
+                    ((v >> 2) & 1) as Float - offset
+                ));
+            }
+        }
+        for f in 0..6 {
+            let v = 4 * f;
+            off.push_str(&format!("4 {} {} {} {}\n", v, v + 1, v + 2, v + 3));
+        }
+
+        let mut cube = Concrete::from_off(&off).unwrap();
+        assert_eq!(cube.vertex_count(), 24);
+        cube.fuse(1e-6).unwrap();
+        assert_eq!(
+            cube.el_counts(),
+            Concrete::hypercube(Rank::new(3)).el_counts()
+        );
+    }
+
+    #[test]
+    fn degenerate_truncation() {
+        // Truncating by nothing repeats every vertex, and collapses the
+        // triangles at the vertices into points.
+        let mut cube = Concrete::hypercube(Rank::new(3));
+        cube.abs_sort();
+        let mut truncate = cube.truncate_with(0.0);
+        assert_eq!(truncate.vertex_count(), 24);
+
+        truncate.fuse(Float::EPS).unwrap();
+        assert_eq!(truncate.el_counts(), cube.el_counts());
+        assert!(truncate
+            .edge_lengths()
+            .into_iter()
+            .all(|len| (len - 1.0).abs() < Float::EPS));
+    }
+
+    #[test]
+    fn invalid() {
+        // Fusing two vertices of a triangle leaves a single edge.
+        let mut triangle = Concrete::polygon(3);
+        triangle.vertices[1] = triangle.vertices[0].clone();
+        assert!(triangle.fuse(Float::EPS).is_err());
+        assert_eq!(triangle.vertex_count(), 2);
+        assert_eq!(triangle.el_count(Rank::new(1)), 1);
+    }
+}
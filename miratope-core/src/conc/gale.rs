@@ -0,0 +1,223 @@
+//! Computes [Gale diagrams](https://en.wikipedia.org/wiki/Gale_transform) of
+//! the vertex sets of polytopes, which are most useful for polytopes with few
+//! more vertices than dimensions.
+
+use std::fmt::Write;
+
+use super::{Concrete, ConcretePolytope};
+use crate::{
+    geometry::{Point, Vector},
+    Consts, Float,
+};
+
+/// The size of the plots of Gale diagrams, in pixels.
+const PLOT_SIZE: Float = 400.0;
+
+/// The Gale diagram of a set of points. For `n` points spanning a space of
+/// dimension `d`, these are `n` vectors in dimension `n - d - 1`, such that
+/// their linear dependencies are the affine dependencies of the original
+/// points, and the other way around.
+#[derive(Clone, Debug)]
+pub struct GaleDiagram {
+    /// The vectors of the diagram, in the same order as the original points.
+    pub vectors: Vec<Vector>,
+
+    /// The dimension of the vectors.
+    dim: usize,
+}
+
+impl GaleDiagram {
+    /// Computes the Gale diagram of a set of points, or returns `None` if
+    /// there are no points.
+    pub fn new(points: &[Point]) -> Option<Self> {
+        let n = points.len();
+        let dim = points.first()?.len();
+
+        // The rows of the matrix whose kernel we want: the coordinates of the
+        // points, and a row of ones.
+        let rows = (0..dim)
+            .map(|i| Vector::from_iterator(n, points.iter().map(|p| p[i])))
+            .chain(std::iter::once(Vector::from_element(n, 1.0)));
+
+        // An orthonormal basis for the row space.
+        let mut row_space: Vec<Vector> = Vec::new();
+        for row in rows {
+            if let Some(v) = orthogonalize(row, &row_space) {
+                row_space.push(v);
+            }
+        }
+
+        // Completes it into a basis for the whole space. The new vectors form
+        // a basis for the kernel.
+        let mut kernel: Vec<Vector> = Vec::new();
+        for i in 0..n {
+            let mut e = Vector::zeros(n);
+            e[i] = 1.0;
+
+            let basis: Vec<_> = row_space.iter().chain(kernel.iter()).cloned().collect();
+            if let Some(v) = orthogonalize(e, &basis) {
+                kernel.push(v);
+            }
+        }
+
+        let dim = kernel.len();
+        let vectors = (0..n)
+            .map(|i| Vector::from_iterator(dim, kernel.iter().map(|v| v[i])))
+            .collect();
+
+        Some(Self { vectors, dim })
+    }
+
+    /// Returns the dimension of the vectors of the diagram.
+    pub fn dim(&self) -> usize {
+        self.dim
+    }
+
+    /// Plots the diagram as an SVG image, with every vector drawn as a segment
+    /// from the origin labeled by the index of its point. Only diagrams of
+    /// dimension 1 or 2 can be plotted.
+    pub fn to_svg(&self) -> Option<String> {
+        if self.dim == 0 || self.dim > 2 {
+            return None;
+        }
+
+        let max = self
+            .vectors
+            .iter()
+            .map(|v| v.norm())
+            .fold(Float::EPS, Float::max);
+        let half = PLOT_SIZE / 2.0;
+        let scale = 0.8 * half / max;
+
+        let mut svg = String::new();
+        writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{0}" viewBox="0 0 {0} {0}">"#,
+            PLOT_SIZE
+        )
+        .unwrap();
+        writeln!(svg, r#"<rect width="100%" height="100%" fill="white"/>"#).unwrap();
+        writeln!(
+            svg,
+            r#"<circle cx="{0}" cy="{0}" r="3" fill="black"/>"#,
+            half
+        )
+        .unwrap();
+
+        for (idx, v) in self.vectors.iter().enumerate() {
+            // Vectors of a 1D diagram are drawn horizontally.
+            let x = half + scale * v[0];
+            let y = half - scale * v.get(1).copied().unwrap_or(0.0);
+
+            writeln!(
+                svg,
+                r#"<line x1="{0}" y1="{0}" x2="{1:.2}" y2="{2:.2}" stroke="gray"/>"#,
+                half, x, y
+            )
+            .unwrap();
+            writeln!(
+                svg,
+                r#"<circle cx="{:.2}" cy="{:.2}" r="4" fill="steelblue"/>"#,
+                x, y
+            )
+            .unwrap();
+            writeln!(
+                svg,
+                r#"<text x="{:.2}" y="{:.2}" font-size="12">{}</text>"#,
+                x + 6.0,
+                y - 6.0,
+                idx
+            )
+            .unwrap();
+        }
+
+        svg.push_str("</svg>\n");
+        Some(svg)
+    }
+}
+
+/// Removes the components of a vector along an orthonormal basis, and
+/// normalizes it. Returns `None` if nothing is left.
+fn orthogonalize(mut v: Vector, basis: &[Vector]) -> Option<Vector> {
+    // Done twice for numerical stability.
+    for _ in 0..2 {
+        for b in basis {
+            let dot = v.dot(b);
+            v -= b * dot;
+        }
+    }
+
+    v.try_normalize(Float::EPS)
+}
+
+impl Concrete {
+    /// Computes the Gale diagram of the vertices of the polytope, or returns
+    /// `None` if it has no vertices. For a polytope with `d + k` vertices in
+    /// dimension `d`, this consists of vectors in dimension `k - 1`.
+    pub fn gale_diagram(&self) -> Option<GaleDiagram> {
+        GaleDiagram::new(self.vertices())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{abs::rank::Rank, Polytope};
+
+    use approx::abs_diff_eq;
+
+    /// Checks that the vectors of a Gale diagram give the affine dependencies
+    /// of the points.
+    fn test_dependencies(poly: &Concrete, dim: usize) {
+        let gale = poly.gale_diagram().unwrap();
+        assert_eq!(gale.dim(), dim);
+        assert_eq!(gale.vectors.len(), poly.vertex_count());
+
+        for i in 0..dim {
+            // The sum of the coefficients is zero.
+            let sum: Float = gale.vectors.iter().map(|v| v[i]).sum();
+            assert!(abs_diff_eq!(sum, 0.0, epsilon = Float::EPS));
+
+            // The weighted sum of the points is zero.
+            let mut weighted = Point::zeros(poly.dim_or());
+            for (v, p) in gale.vectors.iter().zip(&poly.vertices) {
+                weighted += p * v[i];
+            }
+            assert!(abs_diff_eq!(weighted.norm(), 0.0, epsilon = Float::EPS));
+        }
+    }
+
+    #[test]
+    fn simplex() {
+        test_dependencies(&Concrete::simplex(Rank::new(3)), 0);
+    }
+
+    #[test]
+    fn square() {
+        let square = Concrete::polygon(4);
+        test_dependencies(&square, 1);
+
+        // Adjacent vertices get opposite signs.
+        let gale = square.gale_diagram().unwrap();
+        for i in 0..4 {
+            let (a, b) = (gale.vectors[i][0], gale.vectors[(i + 1) % 4][0]);
+            assert!(a * b < 0.0);
+        }
+    }
+
+    #[test]
+    fn octahedron() {
+        let oct = Concrete::orthoplex(Rank::new(3));
+        test_dependencies(&oct, 2);
+
+        let svg = oct.gale_diagram().unwrap().to_svg().unwrap();
+        assert_eq!(svg.matches("<line").count(), 6);
+    }
+
+    #[test]
+    fn cube() {
+        let cube = Concrete::hypercube(Rank::new(3));
+        test_dependencies(&cube, 4);
+        assert!(cube.gale_diagram().unwrap().to_svg().is_none());
+    }
+}
@@ -0,0 +1,99 @@
+//! Builds the concrete [kis](https://en.wikipedia.org/wiki/Conway_polyhedron_notation)
+//! of a polytope. See [`Abstract::kis`](crate::abs::Abstract::kis) for the
+//! combinatorial side of the construction.
+
+use super::{Concrete, ConcretePolytope};
+use crate::{
+    abs::{elements::ElementRef, rank::Rank},
+    geometry::{Point, Subspace, Vector},
+    Float, Polytope,
+};
+
+impl Concrete {
+    /// Raises a pyramid over every facet of the polytope at once. Each apex is
+    /// placed over the centroid of the vertices of its facet, at the given
+    /// height in the direction away from the gravicenter of the polytope.
+    /// Negative heights dig the pyramids inwards instead.
+    ///
+    /// If a facet passes through the gravicenter, its apex is placed at its
+    /// centroid.
+    pub fn kis(&self, height: Float) -> Self {
+        let abs = self.abs.kis();
+        let center = match self.gravicenter() {
+            Some(center) => center,
+            None => return Self::new(self.vertices.clone(), abs),
+        };
+
+        let facet_rank = self.rank().minus_one();
+        let mut vertices = self.vertices.clone();
+        if self.rank() >= Rank::new(1) {
+            for idx in 0..self.facet_count() {
+                let facet_vertices = self
+                    .element_vertices_ref(ElementRef::new(facet_rank, idx))
+                    .unwrap();
+
+                let mut centroid = Point::zeros(center.len());
+                for &v in &facet_vertices {
+                    centroid += v;
+                }
+                centroid /= facet_vertices.len() as Float;
+
+                let normal = Subspace::from_points(facet_vertices.into_iter())
+                    .normal(&center)
+                    .unwrap_or_else(|| Vector::zeros(center.len()));
+                vertices.push(centroid - normal * height);
+            }
+        }
+
+        Self::new(vertices, abs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Consts;
+
+    use approx::abs_diff_eq;
+
+    /// The kis of a square is an octagon, which is regular for the right
+    /// height.
+    #[test]
+    fn square() {
+        let square = Concrete::polygon(4);
+        let circumradius = square.vertices[0].norm();
+        let inradius = circumradius * (Float::PI / 4.0).cos();
+        let kis = square.kis(circumradius - inradius);
+
+        assert_eq!(kis.vertex_count(), 8);
+        for v in &kis.vertices {
+            assert!(abs_diff_eq!(v.norm(), circumradius, epsilon = Float::EPS));
+        }
+    }
+
+    /// Pyramids of height zero leave the volume unchanged, and raising them
+    /// adds their volumes.
+    #[test]
+    fn cube() {
+        let mut cube = Concrete::hypercube(Rank::new(3));
+        cube.abs_sort();
+        let volume = cube.volume().unwrap().abs();
+        assert!(abs_diff_eq!(volume, 1.0, epsilon = Float::EPS));
+
+        let mut flat = cube.kis(0.0);
+        flat.abs_sort();
+        assert!(abs_diff_eq!(
+            flat.volume().unwrap().abs(),
+            volume,
+            epsilon = Float::EPS
+        ));
+
+        let mut kis = cube.kis(0.5);
+        kis.abs_sort();
+        assert!(abs_diff_eq!(
+            kis.volume().unwrap().abs(),
+            2.0,
+            epsilon = Float::EPS
+        ));
+    }
+}
@@ -0,0 +1,80 @@
+//! Measures the elements of a polytope, such as the lengths of its edges, the
+//! areas of its faces or the volumes of its cells.
+
+use super::{Concrete, ConcretePolytope};
+use crate::{
+    abs::{elements::ElementRef, rank::Rank},
+    Float, Polytope,
+};
+
+use vec_like::VecLike;
+
+impl Concrete {
+    /// Returns the hypervolume of an element within its own span, such as the
+    /// length of an edge or the area of a face. Vertices have measure 1.
+    ///
+    /// Returns `None` if the element doesn't exist, or if its volume is
+    /// undefined in the sense of [`ConcretePolytope::volume`].
+    pub fn element_measure(&self, rank: Rank, idx: usize) -> Option<Float> {
+        let mut element = self.element(ElementRef::new(rank, idx))?;
+        element.abs_sort();
+        element.volume()
+    }
+
+    /// Returns the measures of all of the elements of a given rank, in order.
+    /// Returns `None` if the rank is out of bounds.
+    pub fn element_measures(&self, rank: Rank) -> Option<Vec<Option<Float>>> {
+        let count = self.abs.ranks.get(rank)?.len();
+        Some(
+            (0..count)
+                .map(|idx| self.element_measure(rank, idx))
+                .collect(),
+        )
+    }
+
+    /// Returns the total measure of the elements of a given rank, such as the
+    /// surface area of a polyhedron for rank 2. Returns `None` if the rank is
+    /// out of bounds, or if the measure of any element is undefined.
+    pub fn total_measure(&self, rank: Rank) -> Option<Float> {
+        self.element_measures(rank)?.into_iter().sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Consts;
+
+    use approx::abs_diff_eq;
+
+    #[test]
+    fn cube() {
+        let cube = Concrete::hypercube(Rank::new(3));
+        let totals = [8.0, 12.0, 6.0, 1.0];
+        for (r, &total) in totals.iter().enumerate() {
+            let rank = Rank::from(r);
+            for measure in cube.element_measures(rank).unwrap() {
+                assert!(abs_diff_eq!(measure.unwrap(), 1.0, epsilon = Float::EPS));
+            }
+
+            let measure = cube.total_measure(rank).unwrap();
+            assert!(abs_diff_eq!(measure, total, epsilon = Float::EPS));
+        }
+
+        assert_eq!(cube.element_measure(Rank::new(2), 6), None);
+        assert_eq!(cube.total_measure(Rank::new(5)), None);
+    }
+
+    #[test]
+    fn pentagonal_prism() {
+        // The bases have unit circumradius, and the prism has unit height.
+        let prism = Concrete::polygon(5).prism();
+        let pentagon = 5.0 * (Float::TAU / 5.0).sin() / 2.0;
+        let edge = 2.0 * (Float::PI / 5.0).sin();
+
+        // Twice the area of the bases, plus five rectangles.
+        let area = prism.total_measure(Rank::new(2)).unwrap();
+        let expected = 2.0 * pentagon + 5.0 * edge;
+        assert!(abs_diff_eq!(area, expected, epsilon = Float::EPS));
+    }
+}
@@ -0,0 +1,147 @@
+//! Declares the [`Metadata`] that can be attached to a [`Concrete`] polytope.
+
+use std::collections::BTreeMap;
+
+use super::{attributes::ElementAttributes, Concrete};
+
+#[cfg(feature = "serde-serialize")]
+use serde::{Deserialize, Serialize};
+
+/// The prefix of the comment line in which an OFF file stores the metadata of
+/// a polytope.
+pub const OFF_METADATA_PREFIX: &str = "# @metadata ";
+
+/// Information about a polytope that isn't needed to describe it
+/// geometrically, but that we don't want to lose whenever we save and reload
+/// it. All fields are optional.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub struct Metadata {
+    /// The name of the polytope, as given by the user.
+    pub name: Option<String>,
+
+    /// Where the polytope came from, e.g. a file path or a website.
+    pub source: Option<String>,
+
+    /// The person who found or built the polytope.
+    pub author: Option<String>,
+
+    /// A description of how the polytope was built, e.g. `"pyramid of cube"`.
+    pub construction: Option<String>,
+
+    /// A list of tags, used to categorize polytopes.
+    pub tags: Vec<String>,
+
+    /// Any other key-value pairs.
+    pub other: BTreeMap<String, String>,
+
+    /// The attributes of the individual elements of the polytope.
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(default, skip_serializing_if = "ElementAttributes::is_empty")
+    )]
+    pub attributes: ElementAttributes,
+}
+
+impl Metadata {
+    /// Returns whether no field of the metadata is set.
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+
+    /// Adds a tag, unless it already exists.
+    pub fn add_tag(&mut self, tag: String) {
+        if !self.tags.contains(&tag) {
+            self.tags.push(tag);
+        }
+    }
+
+    /// Removes a tag, returns whether it existed.
+    pub fn remove_tag(&mut self, tag: &str) -> bool {
+        let len = self.tags.len();
+        self.tags.retain(|t| t != tag);
+        len != self.tags.len()
+    }
+
+    /// Serializes the metadata into a single OFF comment line, including the
+    /// trailing newline.
+    #[cfg(feature = "serde-serialize")]
+    pub fn to_off_comment(&self) -> String {
+        format!(
+            "{}{}\n",
+            OFF_METADATA_PREFIX,
+            ron::to_string(self).unwrap_or_default()
+        )
+    }
+
+    /// Reads the metadata from the source of an OFF file, if there is any.
+    #[cfg(feature = "serde-serialize")]
+    pub fn from_off_src(src: &str) -> Option<Self> {
+        src.lines()
+            .find_map(|line| line.trim_start().strip_prefix(OFF_METADATA_PREFIX))
+            .and_then(|ron| ron::from_str(ron).ok())
+    }
+}
+
+impl Concrete {
+    /// Returns a reference to the metadata of the polytope, if any.
+    pub fn metadata(&self) -> Option<&Metadata> {
+        self.metadata.as_deref()
+    }
+
+    /// Returns a mutable reference to the metadata of the polytope, creating
+    /// an empty one if it doesn't exist yet.
+    pub fn metadata_mut(&mut self) -> &mut Metadata {
+        self.metadata.get_or_insert_with(Default::default)
+    }
+
+    /// Sets the metadata of the polytope. Empty metadata gets removed.
+    pub fn set_metadata(&mut self, metadata: Metadata) {
+        self.metadata = (!metadata.is_empty()).then(|| Box::new(metadata));
+    }
+
+    /// Removes the metadata of the polytope, and returns it.
+    pub fn take_metadata(&mut self) -> Option<Metadata> {
+        self.metadata.take().map(|metadata| *metadata)
+    }
+}
+
+#[cfg(all(test, feature = "serde-serialize"))]
+mod tests {
+    use super::Metadata;
+    use crate::{
+        conc::{file::FromFile, Concrete},
+        Polytope,
+    };
+
+    /// Checks that metadata survives being written to and read from an OFF
+    /// file.
+    #[test]
+    fn off_roundtrip() {
+        let mut metadata = Metadata {
+            name: Some("Tetrahedral \"pyramid\"\nwith a newline".to_string()),
+            author: Some("Anonymous".to_string()),
+            construction: Some("pyramid of triangle".to_string()),
+            ..Default::default()
+        };
+        metadata.add_tag("CRF".to_string());
+        metadata.add_tag("CRF".to_string());
+        metadata
+            .other
+            .insert("found".to_string(), "2021".to_string());
+
+        let mut tet = Concrete::simplex(crate::abs::rank::Rank::new(3));
+        tet.set_metadata(metadata.clone());
+
+        for &comments in &[false, true] {
+            let off = tet.to_off(crate::conc::file::off::OffOptions { comments });
+            let reloaded = Concrete::from_off(&off).unwrap();
+            assert_eq!(reloaded.metadata(), Some(&metadata));
+        }
+
+        // Polytopes without metadata don't gain any.
+        tet.take_metadata();
+        let reloaded = Concrete::from_off(&tet.to_off(Default::default())).unwrap();
+        assert_eq!(reloaded.metadata(), None);
+    }
+}
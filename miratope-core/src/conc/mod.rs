@@ -1,8 +1,38 @@
 //! Declares the [`Concrete`] polytope type and all associated data structures.
 
+pub mod attributes;
+pub mod blend;
+pub mod chamfer;
+pub mod convex;
+pub mod coplanar;
 pub mod cycle;
+pub mod distortion;
 pub mod element_types;
+pub mod excavate;
+pub mod extrude;
+pub mod faceting;
+pub mod fan;
 pub mod file;
+pub mod fuse;
+pub mod gale;
+pub mod kis;
+pub mod measure;
+pub mod metadata;
+pub mod numerical;
+pub mod optimize;
+pub mod orient;
+pub mod repair;
+pub mod sequence;
+pub mod shelling;
+pub mod signature;
+pub mod simplicial;
+pub mod stellation;
+pub mod symmetry;
+pub mod tiling;
+pub mod toroid;
+pub mod truncate;
+pub mod views;
+pub mod wythoff;
 
 use std::collections::{HashMap, HashSet};
 
@@ -23,6 +53,8 @@ use crate::{
 };
 
 use approx::{abs_diff_eq, abs_diff_ne};
+use metadata::Metadata;
+#[cfg(feature = "parallel")]
 use rayon::prelude::*;
 use vec_like::*;
 
@@ -35,6 +67,9 @@ pub struct Concrete {
 
     /// The underlying abstract polytope.
     pub abs: Abstract,
+
+    /// Any additional information about the polytope.
+    pub metadata: Option<Box<Metadata>>,
 }
 
 impl std::ops::Index<Rank> for Concrete {
@@ -73,8 +108,60 @@ impl Concrete {
             }
         }
 
-        // With no further info, we create a generic name for the polytope.
-        Self { vertices, abs }
+        Self {
+            vertices,
+            abs,
+            metadata: None,
+        }
+    }
+
+    /// Sorts the vertices of the polytope in fuzzy lexicographic order,
+    /// updating the underlying abstract polytope accordingly.
+    pub fn sort_vertices(&mut self) {
+        let mut order: Vec<_> = (0..self.vertices.len()).collect();
+        order.sort_by_cached_key(|&idx| PointOrd::new(self.vertices[idx].clone()));
+
+        // perm[i] is the new index of the vertex with index i.
+        let mut perm = vec![0; order.len()];
+        for (new_idx, &idx) in order.iter().enumerate() {
+            perm[idx] = new_idx;
+        }
+
+        self.vertices = order
+            .into_iter()
+            .map(|idx| self.vertices[idx].clone())
+            .collect();
+
+        if self.rank() >= Rank::new(0) {
+            self.abs.permute(Rank::new(0), &perm);
+        }
+    }
+
+    /// Returns a point for every element of the polytope other than the
+    /// minimal one, indexed by rank and then by index. Vertices map to themselves, and every other
+    /// element maps to the average of the points of its subelements.
+    fn element_centers(&self) -> Vec<Vec<Point>> {
+        let dim = self.dim_or();
+        let mut element_vertices = vec![self.vertices.clone()];
+
+        for r in Rank::range_inclusive_iter(Rank::new(1), self.rank()) {
+            let mut rank_vertices = Vec::new();
+
+            for el in &self[r] {
+                let mut p = Point::zeros(dim);
+                let subs = &el.subs;
+
+                for &sub in subs {
+                    p += &element_vertices[r.into_usize() - 1][sub];
+                }
+
+                rank_vertices.push(p / subs.len() as Float);
+            }
+
+            element_vertices.push(rank_vertices);
+        }
+
+        element_vertices
     }
 }
 
@@ -89,6 +176,11 @@ impl Polytope for Concrete {
         &mut self.abs
     }
 
+    /// Returns the coordinates of the vertices.
+    fn points(&self) -> Option<&[Point]> {
+        Some(&self.vertices)
+    }
+
     /// Builds the unique polytope of rank −1.
     fn nullitope() -> Self {
         Self::new(Vec::new(), Abstract::nullitope())
@@ -171,30 +263,25 @@ impl Polytope for Concrete {
         ))
     }
 
+    /// Returns the rectate of a polytope, whose vertices are the midpoints of
+    /// the edges of the original.
+    fn rectify(&self) -> Self {
+        let vertices = match self.abs.ranks.get(Rank::new(1)) {
+            Some(edges) => edges
+                .iter()
+                .map(|edge| (&self.vertices[edge.subs[0]] + &self.vertices[edge.subs[1]]) / 2.0)
+                .collect(),
+            None => Vec::new(),
+        };
+
+        Self::new(vertices, self.abs.medial())
+    }
+
     // TODO: A method that builds an omnitruncate together with a map from flags
     // to vertices? We got some math details to figure out.
     fn omnitruncate(&self) -> Self {
         let (abs, flags) = self.abs.omnitruncate_and_flags();
-        let dim = self.dim().unwrap();
-
-        // Maps each element to the polytope to some vertex.
-        let mut element_vertices = vec![self.vertices.clone()];
-        for r in Rank::range_inclusive_iter(Rank::new(1), self.rank()) {
-            let mut rank_vertices = Vec::new();
-
-            for el in &self[r] {
-                let mut p = Point::zeros(dim);
-                let subs = &el.subs;
-
-                for &sub in subs {
-                    p += &element_vertices[r.into_usize() - 1][sub];
-                }
-
-                rank_vertices.push(p / subs.len() as Float);
-            }
-
-            element_vertices.push(rank_vertices);
-        }
+        let element_vertices = self.element_centers();
 
         let vertices: Vec<_> = flags
             .into_iter()
@@ -228,6 +315,7 @@ impl Polytope for Concrete {
             duoprism_vertices(&p.vertices, &q.vertices),
             Abstract::duoprism(&p.abs, &q.abs),
         )
+        .with_product_attributes(p, q, false, true)
     }
 
     /// Builds a [duotegum](https://polytope.miraheze.org/wiki/Tegum_product)
@@ -243,6 +331,7 @@ impl Polytope for Concrete {
             duoprism_vertices(&p.vertices, &q.vertices),
             Abstract::duocomb(&p.abs, &q.abs),
         )
+        .with_product_attributes(p, q, false, false)
     }
 
     /// Builds a [ditope](https://polytope.miraheze.org/wiki/Ditope) of a given
@@ -478,6 +567,95 @@ pub trait ConcretePolytope: Polytope {
         self
     }
 
+    /// Returns the principal axes of the vertices of the polytope, as the
+    /// columns of an orthogonal matrix. These are the eigenvectors of the
+    /// covariance matrix of the vertices, sorted by decreasing variance.
+    /// Returns `None` in the case of the nullitope.
+    ///
+    /// Whenever two axes have the same variance, any orthonormal basis of their
+    /// span is a valid choice, so the axes are only defined up to this and up
+    /// to sign.
+    fn principal_axes(&self) -> Option<Matrix> {
+        let gravicenter = self.gravicenter()?;
+        let dim = gravicenter.len();
+
+        // Builds the covariance matrix.
+        let mut covariance = Matrix::zeros(dim, dim);
+        for v in self.vertices() {
+            let diff = v - &gravicenter;
+            covariance += &diff * diff.transpose();
+        }
+
+        let eigen = covariance.symmetric_eigen();
+
+        // Sorts the eigenvectors by decreasing eigenvalue.
+        let mut order: Vec<_> = (0..dim).collect();
+        order.sort_by(|&i, &j| {
+            let (x, y) = (eigen.eigenvalues[i], eigen.eigenvalues[j]);
+
+            if abs_diff_eq!(x, y, epsilon = Float::EPS) {
+                std::cmp::Ordering::Equal
+            } else {
+                y.partial_cmp(&x).unwrap()
+            }
+        });
+
+        let columns: Vec<_> = order
+            .into_iter()
+            .map(|i| eigen.eigenvectors.column(i).into_owned())
+            .collect();
+        Some(Matrix::from_columns(&columns))
+    }
+
+    /// Moves the polytope into a canonical position. The polytope is recentered
+    /// so that its gravicenter lies at the origin, it's rotated so that its
+    /// [principal axes](Self::principal_axes) become the coordinate axes, and
+    /// its vertices are sorted in lexicographic order.
+    ///
+    /// The direction of each axis is chosen so that the polytope is skewed
+    /// towards its positive side. If the polytope is symmetric along an axis,
+    /// the first non-zero coordinate of the axis is made positive instead.
+    /// Because of this, the resulting transformation might be a reflection.
+    fn orient_canonically(&mut self) {
+        let axes = if let Some(axes) = self.principal_axes() {
+            axes
+        } else {
+            return;
+        };
+
+        self.recenter();
+        let mut transform = axes.transpose();
+
+        // Chooses the direction of each axis.
+        for mut axis in transform.row_iter_mut() {
+            let skewness: Float = self
+                .vertices()
+                .iter()
+                .map(|v| axis.transpose().dot(v).powi(3))
+                .sum();
+
+            let flip = if abs_diff_eq!(skewness, 0.0, epsilon = Float::EPS) {
+                matches!(
+                    axis.iter().find(|x| abs_diff_ne!(**x, 0.0, epsilon = Float::EPS)),
+                    Some(x) if *x < 0.0
+                )
+            } else {
+                skewness < 0.0
+            };
+
+            if flip {
+                axis.neg_mut();
+            }
+        }
+
+        for v in self.vertices_mut() {
+            let new_v = &transform * v as &_;
+            *v = new_v;
+        }
+
+        self.con_mut().sort_vertices();
+    }
+
     /// Calculates the circumsphere of a polytope. Returns `None` if the
     /// polytope isn't circumscribable.
     fn circumsphere(&self) -> Option<Hypersphere> {
@@ -509,7 +687,7 @@ pub trait ConcretePolytope: Polytope {
         }
 
         Some(Hypersphere {
-            squared_radius: (&center - first_vertex).norm(),
+            squared_radius: (&center - first_vertex).norm_squared(),
             center,
         })
     }
@@ -747,6 +925,18 @@ pub trait ConcretePolytope: Polytope {
     /// Computes the volume of a polytope by adding up the contributions of all
     /// flags. Returns `None` if the volume is undefined.
     ///
+    /// Every flag spans a simplex with the gravicenter of the vertices, whose
+    /// signed volume is weighted by the orientation of the flag. This fan
+    /// decomposition works just as well for non-convex polytopes, where it
+    /// counts every point as many times as the polytope winds around it. For
+    /// instance, the central pentagon of a pentagram gets counted twice.
+    /// Compounds get the sum of the absolute volumes of their components, and
+    /// non-orientable polytopes, which don't have a consistent winding number,
+    /// don't get a volume at all.
+    ///
+    /// Degenerate polytopes have volume 0, and skew polytopes don't have a
+    /// volume.
+    ///
     /// # Panics
     /// This method will panic if the polytope is not sorted.
     fn volume(&self) -> Option<Float> {
@@ -773,6 +963,12 @@ pub trait ConcretePolytope: Polytope {
             _ => {}
         }
 
+        // The volume doesn't depend on the apex of the fan, but placing it at
+        // the gravicenter avoids cancellation errors when the polytope lies
+        // far away from the origin.
+        let center = flat_vertices.iter().sum::<Point>() / flat_vertices.len() as Float;
+        let flat_vertices: Vec<_> = flat_vertices.iter().map(|v| v - &center).collect();
+
         // Maps every element of the polytope to one of its vertices.
         let mut vertex_map = Vec::new();
 
@@ -816,7 +1012,7 @@ pub trait ConcretePolytope: Polytope {
                         debug_assert!(new, "A flag is in two different components.");
 
                         // For each flag, there's a simplex defined by any vertices in its
-                        // elements and the origin. We add up the volumes of all of these
+                        // elements and the gravicenter. We add up the volumes of all of these
                         // simplices times the sign of the flag that generated them.
                         component_volume += oriented_flag.orientation.sign()
                             * Matrix::from_iterator(
@@ -929,8 +1125,12 @@ impl ConcretePolytope for Concrete {
             let facet_count = self.el_count(rank_minus_one);
             let indices: Vec<_> = (0..facet_count).collect();
 
+            #[cfg(feature = "parallel")]
+            let indices = indices.into_par_iter();
+            #[cfg(not(feature = "parallel"))]
+            let indices = indices.into_iter();
+
             projections = indices
-                .into_par_iter()
                 .map(|idx| {
                     Subspace::from_points(
                         self.element_vertices_ref(ElementRef::new(rank_minus_one, idx))
@@ -957,6 +1157,10 @@ impl ConcretePolytope for Concrete {
 
         // Takes the abstract dual.
         self.abs.dual_mut();
+        if let Some(attributes) = self.attributes() {
+            let attributes = attributes.dual(rank);
+            self.set_attributes(attributes);
+        }
 
         Ok(())
     }
@@ -1020,6 +1224,7 @@ impl ConcretePolytope for Concrete {
             duopyramid_vertices(&p.vertices, &q.vertices, p_offset, q_offset, height, false),
             Abstract::duopyramid(&p.abs, &q.abs),
         )
+        .with_product_attributes(p, q, true, true)
     }
 
     /// Builds a [duotegum](https://polytope.miraheze.org/wiki/Tegum_product)
@@ -1029,6 +1234,7 @@ impl ConcretePolytope for Concrete {
             duopyramid_vertices(&p.vertices, &q.vertices, p_offset, q_offset, 0.0, true),
             Abstract::duotegum(&p.abs, &q.abs),
         )
+        .with_product_attributes(p, q, true, false)
     }
 
     /// Projects the vertices of the polytope into the lowest dimension possible.
@@ -1064,6 +1270,9 @@ impl ConcretePolytope for Concrete {
         // intersections with the slicing hyperplane.
         let mut hash_element = HashMap::new();
 
+        // The index of the element that each new element is a section of.
+        let mut origins = vec![Vec::new()];
+
         // Determines the vertices of the cross-section.
         for (idx, edge) in self[Rank::new(1)].iter().enumerate() {
             let segment = Segment(&self.vertices[edge.subs[0]], &self.vertices[edge.subs[1]]);
@@ -1072,6 +1281,7 @@ impl ConcretePolytope for Concrete {
             if let Some(p) = slice.intersect(segment) {
                 hash_element.insert(idx, vertices.len());
                 vertices.push(p);
+                origins[0].push(idx);
             }
         }
 
@@ -1089,6 +1299,7 @@ impl ConcretePolytope for Concrete {
         for r in Rank::range_iter(2, self.rank()) {
             let mut new_hash_element = HashMap::new();
             let mut new_els = SubelementList::new();
+            let mut new_origins = Vec::new();
 
             for (idx, el) in self[r].iter().enumerate() {
                 let mut new_subs = Subelements::new();
@@ -1102,10 +1313,12 @@ impl ConcretePolytope for Concrete {
                 if !new_subs.is_empty() {
                     new_hash_element.insert(idx, new_els.len());
                     new_els.push(new_subs);
+                    new_origins.push(idx);
                 }
             }
 
             ranks.push(new_els);
+            origins.push(new_origins);
             hash_element = new_hash_element;
         }
 
@@ -1150,6 +1363,8 @@ impl ConcretePolytope for Concrete {
                         let v0 = subs.pop().unwrap();
                         let v1 = subs.pop().unwrap();
                         new_edges.push(Subelements(vec![v0, v1]));
+                        let origin = origins[1][edge_idx];
+                        origins[1].push(origin);
 
                         for &sup in &edge_sups[edge_idx] {
                             faces[sup].push(edge_num);
@@ -1170,16 +1385,21 @@ impl ConcretePolytope for Concrete {
             abs.push(subelements);
         }
 
-        Self::new(vertices, abs.build())
+        let mut section = Self::new(vertices, abs.build());
+        if let Some(attributes) = self.attributes() {
+            section.set_attributes(attributes.cross_section(self.rank(), &origins));
+        }
+        section
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::{Concrete, ConcretePolytope};
-    use crate::{abs::rank::Rank, Consts, Float, Polytope};
+    use crate::{abs::rank::Rank, geometry::Matrix, Consts, Float, Polytope};
 
     use approx::abs_diff_eq;
+    use nalgebra::dvector;
 
     /// Tests that a polytope has an expected volume.
     fn test_volume(poly: &mut Concrete, volume: Option<Float>) {
@@ -1279,6 +1499,34 @@ mod tests {
         }
     }
 
+    /// Checks that products and omnitruncates are written out the same way
+    /// every time, whether they're built in parallel or not.
+    #[test]
+    fn deterministic() {
+        let build = || {
+            let mut cube = Concrete::hypercube(Rank::new(3));
+            cube.abs_sort();
+            let duoprism = Concrete::duoprism(&Concrete::polygon(5), &cube);
+
+            [
+                cube.omnitruncate().to_off(Default::default()),
+                duoprism.to_off(Default::default()),
+            ]
+        };
+
+        let expected = build();
+        assert_eq!(build(), expected);
+
+        #[cfg(feature = "parallel")]
+        {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(1)
+                .build()
+                .unwrap();
+            assert_eq!(pool.install(build), expected);
+        }
+    }
+
     #[test]
     fn duotegum() {
         let mut polygons = Vec::new();
@@ -1339,6 +1587,26 @@ mod tests {
         }
     }
 
+    #[test]
+    fn far_away() {
+        // A cube far away from the origin.
+        let mut cube = Concrete::hypercube(Rank::new(3));
+        let offset = dvector![1e6, -2e6, 3e6];
+        for v in &mut cube.vertices {
+            *v += &offset;
+        }
+        test_volume(&mut cube, Some(1.0));
+
+        // A compound of two squares, far away from each other.
+        let square = Concrete::polygon(4);
+        let mut squares = square.clone();
+        for v in &mut squares.vertices {
+            v[0] += 1e6;
+        }
+        squares.comp_append(square);
+        test_volume(&mut squares, Some(2.0 * polygon_area(4, 1)));
+    }
+
     #[test]
     fn orthoplex() {
         for n in 0..=5 {
@@ -1348,4 +1616,82 @@ mod tests {
             );
         }
     }
+
+    /// Checks the circumradii of the hypercubes, which are √*n* / 2 for unit
+    /// edges.
+    #[test]
+    fn circumsphere() {
+        for n in 1..=5 {
+            let sphere = Concrete::hypercube(Rank::new(n)).circumsphere().unwrap();
+            assert!(abs_diff_eq!(
+                sphere.squared_radius,
+                n as Float / 4.0,
+                epsilon = Float::EPS
+            ));
+        }
+    }
+
+    /// The rectate of a cube is a cuboctahedron, with all vertices at the same
+    /// distance from the center, and all edges of the same length.
+    #[test]
+    fn rectify() {
+        let co = Concrete::hypercube(Rank::new(3)).rectify();
+        co.abs.is_valid().unwrap();
+        assert_eq!(co.el_counts(), vec![1, 12, 24, 14, 1].into());
+
+        let len = Float::SQRT_2 / 2.0;
+        for v in &co.vertices {
+            assert!(abs_diff_eq!(v.norm(), len, epsilon = Float::EPS));
+        }
+        for edge in &co[Rank::new(1)] {
+            let dist = (&co.vertices[edge.subs[0]] - &co.vertices[edge.subs[1]]).norm();
+            assert!(abs_diff_eq!(dist, len, epsilon = Float::EPS));
+        }
+
+        assert_eq!(Concrete::dyad().rectify().vertices, vec![dvector![0.0]]);
+    }
+
+    /// Checks that the components of a stella octangula only keep their own
+    /// vertices.
+    #[test]
+    fn components() {
+        let tet = Concrete::simplex(Rank::new(3));
+        let inv = tet.clone().apply(&-Matrix::identity(3, 3));
+        let stella = Concrete::compound(vec![tet.clone(), inv.clone()]);
+
+        let components: Vec<_> = stella.components().collect();
+        assert_eq!(components.len(), 2);
+        for (component, expected) in components.iter().zip([tet, inv]) {
+            component.abs.is_valid().unwrap();
+            assert_eq!(component.vertex_count(), 4);
+            for v in &component.vertices {
+                assert!(expected.vertices.contains(v));
+            }
+        }
+    }
+
+    /// Checks that a rotated and translated rectangle gets moved back into the
+    /// same canonical position as the original.
+    #[test]
+    fn orient_canonically() {
+        let rect = Concrete::duoprism(&Concrete::dyad_with(2.0), &Concrete::dyad());
+        let (sin, cos) = (0.3 as Float).sin_cos();
+
+        let mut p = rect.clone();
+        let mut q = rect.apply(&Matrix::from_row_slice(2, 2, &[cos, -sin, sin, cos]));
+        q.recenter_with(&dvector![1.0, -2.0]);
+
+        // The longest axis of the rectangle should be the first principal axis.
+        let axes = q.principal_axes().unwrap();
+        assert!(abs_diff_eq!(axes[(0, 0)].abs(), cos, epsilon = Float::EPS));
+        assert!(abs_diff_eq!(axes[(1, 0)].abs(), sin, epsilon = Float::EPS));
+
+        p.orient_canonically();
+        q.orient_canonically();
+        q.abs.is_valid().unwrap();
+
+        for (v, w) in p.vertices.iter().zip(q.vertices.iter()) {
+            assert!(abs_diff_eq!(v, w, epsilon = Float::EPS));
+        }
+    }
 }
@@ -0,0 +1,302 @@
+//! Reports on the numerical health of a polytope, so that one can tell apart
+//! results that are weird because of the math from those that are weird
+//! because of floating point errors.
+//!
+//! All lengths are measured relative to the size of the polytope, so that the
+//! report doesn't depend on its scale.
+
+use std::fmt::Display;
+
+use super::{Concrete, ConcretePolytope};
+use crate::{
+    abs::{elements::ElementRef, rank::Rank},
+    geometry::{Matrix, Point},
+    Consts, Float, Polytope,
+};
+
+use vec_like::VecLike;
+
+/// The relative distance under which two vertices are reported as nearly
+/// coincident.
+pub const CLOSE_VERTICES: Float = 1e-6;
+
+/// The ratio between the least and greatest spreads of the vertices of a
+/// facet under which it's reported as degenerate. Spreads are measured through
+/// their squares, so this is about the best resolution we can get.
+pub const DEGENERATE: Float = 1.5e-8;
+
+/// The ratio between the greatest and least spreads of the vertices of a
+/// facet above which it's reported as badly conditioned.
+pub const CONDITION_LIMIT: Float = 1e6;
+
+/// A report on the numerical health of a polytope, as returned by
+/// [`Concrete::numerical_report`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NumericalReport {
+    /// The greatest distance from the gravicenter to a vertex, which all
+    /// lengths are relative to.
+    pub scale: Float,
+
+    /// The pairs of vertices that are nearly or exactly coincident, along
+    /// with their relative distance.
+    pub close_vertices: Vec<(usize, usize, Float)>,
+
+    /// The facets whose vertices span less dimensions than their rank, up to
+    /// floating point error. See [`DEGENERATE`].
+    pub degenerate_facets: Vec<usize>,
+
+    /// The facets that aren't degenerate, but whose vertices are spread much
+    /// more in some directions of their subspace than in others, along with
+    /// the ratio between the greatest and least spreads. Normals and distances
+    /// computed from these facets are unreliable.
+    pub ill_conditioned_facets: Vec<(usize, Float)>,
+
+    /// The greatest relative distance from a vertex of a facet to the
+    /// hyperplane that best fits the facet. Facets built exactly flat are off
+    /// only by rounding errors, so a larger value means that either these
+    /// errors have piled up through many transformations, or that the facets
+    /// weren't flat to begin with.
+    pub flatness_error: Float,
+}
+
+impl NumericalReport {
+    /// Returns whether nothing suspicious was found.
+    pub fn is_healthy(&self) -> bool {
+        self.close_vertices.is_empty()
+            && self.degenerate_facets.is_empty()
+            && self.ill_conditioned_facets.is_empty()
+            && self.flatness_error < Float::EPS
+    }
+}
+
+impl Display for NumericalReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_healthy() {
+            return writeln!(f, "no numerical issues found");
+        }
+
+        for (i, j, dist) in &self.close_vertices {
+            writeln!(
+                f,
+                "vertices {} and {} are at relative distance {:e}",
+                i, j, dist
+            )?;
+        }
+        for facet in &self.degenerate_facets {
+            writeln!(f, "facet {} is degenerate", facet)?;
+        }
+        for (facet, condition) in &self.ill_conditioned_facets {
+            writeln!(f, "facet {} has condition number {:e}", facet, condition)?;
+        }
+        if self.flatness_error >= Float::EPS {
+            writeln!(
+                f,
+                "facets are off their hyperplanes by up to {:e}",
+                self.flatness_error
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Concrete {
+    /// Returns the pairs of vertices closer than a given distance, along with
+    /// their distance.
+    fn close_vertices(&self, tolerance: Float) -> Vec<(usize, usize, Float)> {
+        let vertices = &self.vertices;
+
+        // Sweeps the vertices in order of their first coordinate.
+        let mut order: Vec<_> = (0..vertices.len()).collect();
+        order.sort_by(|&i, &j| vertices[i][0].partial_cmp(&vertices[j][0]).unwrap());
+
+        let mut pairs = Vec::new();
+        for (k, &i) in order.iter().enumerate() {
+            for &j in &order[k + 1..] {
+                if vertices[j][0] - vertices[i][0] >= tolerance {
+                    break;
+                }
+
+                let dist = (&vertices[i] - &vertices[j]).norm();
+                if dist < tolerance {
+                    pairs.push((i.min(j), i.max(j), dist));
+                }
+            }
+        }
+
+        pairs.sort_by_key(|&(i, j, _)| (i, j));
+        pairs
+    }
+
+    /// Returns the spreads of a set of points of a given rank along each of the
+    /// directions of their subspace, from greatest to least, along with the
+    /// greatest distance from a point to the subspace that best fits them.
+    fn spreads(&self, vertices: &[usize], rank: usize) -> (Vec<Float>, Float) {
+        let dim = self.vertices[vertices[0]].len();
+
+        let mut centroid = Point::zeros(dim);
+        for &v in vertices {
+            centroid += &self.vertices[v];
+        }
+        centroid /= vertices.len() as Float;
+
+        // The eigenvectors of the covariance matrix are the main directions
+        // of the points, and the eigenvalues are their squared spreads.
+        let points = Matrix::from_fn(vertices.len(), dim, |i, j| {
+            self.vertices[vertices[i]][j] - centroid[j]
+        });
+        let eigen = (points.transpose() * points).symmetric_eigen();
+        let mut order: Vec<_> = (0..dim).collect();
+        order.sort_by(|&i, &j| {
+            eigen.eigenvalues[j]
+                .partial_cmp(&eigen.eigenvalues[i])
+                .unwrap()
+        });
+
+        // The main directions of the points span the best fitting subspace.
+        let basis: Vec<Point> = order
+            .iter()
+            .take(rank)
+            .map(|&i| eigen.eigenvectors.column(i).into_owned())
+            .collect();
+
+        let error = vertices
+            .iter()
+            .map(|&v| {
+                let p = &self.vertices[v] - &centroid;
+                let mut rejection = p.clone();
+                for b in &basis {
+                    rejection -= b * p.dot(b);
+                }
+                rejection.norm()
+            })
+            .fold(0.0, Float::max);
+
+        let mut spreads: Vec<_> = order
+            .iter()
+            .take(rank)
+            .map(|&i| eigen.eigenvalues[i].max(0.0).sqrt())
+            .collect();
+        spreads.resize(rank, 0.0);
+
+        (spreads, error)
+    }
+
+    /// Checks the polytope for the usual symptoms of floating point trouble:
+    /// nearly coincident vertices, degenerate or badly conditioned facets, and
+    /// facets that have drifted off their hyperplanes. See
+    /// [`NumericalReport`] for the details.
+    ///
+    /// Returns `None` in the case of the nullitope.
+    pub fn numerical_report(&self) -> Option<NumericalReport> {
+        let gravicenter = self.gravicenter()?;
+        let scale = self
+            .vertices
+            .iter()
+            .map(|v| (v - &gravicenter).norm())
+            .fold(0.0, Float::max);
+
+        // A polytope that's a single point can't have any other issues.
+        if scale == 0.0 {
+            return Some(NumericalReport {
+                close_vertices: self.close_vertices(Float::MIN_POSITIVE),
+                ..Default::default()
+            });
+        }
+
+        let close_vertices = self
+            .close_vertices(CLOSE_VERTICES * scale)
+            .into_iter()
+            .map(|(i, j, dist)| (i, j, dist / scale))
+            .collect();
+
+        let mut report = NumericalReport {
+            scale,
+            close_vertices,
+            ..Default::default()
+        };
+
+        // Facets only make sense from polygons on.
+        let rank = self.rank();
+        if rank < Rank::new(2) {
+            return Some(report);
+        }
+        let facet_rank = rank.into_usize() - 1;
+
+        for idx in 0..self.abs[rank.minus_one()].len() {
+            let vertices = self
+                .abs
+                .element_vertices(ElementRef::new(rank.minus_one(), idx))
+                .unwrap();
+            let (spreads, error) = self.spreads(&vertices, facet_rank);
+            report.flatness_error = report.flatness_error.max(error / scale);
+
+            let greatest = spreads[0];
+            let least = spreads[facet_rank - 1];
+            if least <= DEGENERATE * greatest {
+                report.degenerate_facets.push(idx);
+            } else if greatest / least > CONDITION_LIMIT {
+                report.ill_conditioned_facets.push((idx, greatest / least));
+            }
+        }
+
+        Some(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn healthy() {
+        let report = Concrete::hypercube(Rank::new(4))
+            .numerical_report()
+            .unwrap();
+        assert!(report.is_healthy(), "{}", report);
+        assert!((report.scale - 1.0).abs() < Float::EPS);
+
+        assert!(Concrete::nullitope().numerical_report().is_none());
+        assert!(Concrete::point().numerical_report().unwrap().is_healthy());
+    }
+
+    #[test]
+    fn close_vertices() {
+        // A pentagon with two nearly coincident vertices.
+        let mut pentagon = Concrete::polygon(5);
+        pentagon.vertices[1] = &pentagon.vertices[0] + Point::from(vec![1e-8, 0.0]);
+
+        let report = pentagon.numerical_report().unwrap();
+        assert_eq!(report.close_vertices.len(), 1);
+        assert_eq!(report.close_vertices[0].0, 0);
+        assert_eq!(report.close_vertices[0].1, 1);
+        assert!(!report.is_healthy());
+    }
+
+    #[test]
+    fn facets() {
+        // Squashing a cube makes two of its faces badly conditioned, and
+        // flattening it makes them degenerate.
+        for (height, degenerate) in [(1e-7, false), (0.0, true)] {
+            let mut cube = Concrete::hypercube(Rank::new(3));
+            for v in &mut cube.vertices {
+                v[2] *= height;
+            }
+
+            let report = cube.numerical_report().unwrap();
+            let count = if degenerate {
+                report.degenerate_facets.len()
+            } else {
+                report.ill_conditioned_facets.len()
+            };
+            assert_eq!(count, 4, "{}", report);
+        }
+
+        // Moving a vertex of a cube takes its faces off their planes.
+        let mut cube = Concrete::hypercube(Rank::new(3));
+        cube.vertices[0][0] += 1e-6;
+        let report = cube.numerical_report().unwrap();
+        assert!(report.flatness_error > 1e-7);
+        assert!(!report.is_healthy());
+    }
+}
@@ -0,0 +1,143 @@
+//! Solves linear programs over a polytope, treating it as a feasible region.
+//!
+//! The maximum of a linear function over a polytope is always attained at
+//! some vertex. Small polytopes are simply scanned vertex by vertex. Large
+//! polytopes are instead walked along their edges, always moving to the best
+//! adjacent vertex, just like the simplex method does, which only visits a
+//! small portion of the vertices in practice.
+
+use super::{Concrete, ConcretePolytope};
+use crate::{abs::rank::Rank, geometry::Vector, Consts, Float, Polytope};
+
+/// The number of vertices from which [`Concrete::maximize`] walks along the
+/// edges of the polytope instead of scanning every vertex.
+const WALK_THRESHOLD: usize = 10_000;
+
+impl Concrete {
+    /// Returns the value of a linear objective at a vertex.
+    fn objective_at(&self, objective: &Vector, vertex: usize) -> Float {
+        objective.dot(&self.vertices[vertex])
+    }
+
+    /// Maximizes a linear objective over a convex polytope. Returns the index
+    /// of a vertex where the maximum is attained, together with the maximum.
+    ///
+    /// Returns `None` if the polytope has no vertices, or if the objective
+    /// doesn't have the same dimension as the polytope.
+    pub fn maximize(&self, objective: &Vector) -> Option<(usize, Float)> {
+        if self.vertex_count() < WALK_THRESHOLD {
+            self.maximize_scan(objective)
+        } else {
+            self.maximize_from(0, objective)
+        }
+    }
+
+    /// Minimizes a linear objective over a convex polytope. See
+    /// [`Self::maximize`].
+    pub fn minimize(&self, objective: &Vector) -> Option<(usize, Float)> {
+        let (vertex, value) = self.maximize(&-objective)?;
+        Some((vertex, -value))
+    }
+
+    /// Maximizes a linear objective over a polytope by checking every vertex.
+    /// This works whether or not the polytope is convex.
+    pub fn maximize_scan(&self, objective: &Vector) -> Option<(usize, Float)> {
+        if self.dim()? != objective.len() {
+            return None;
+        }
+
+        (0..self.vertex_count())
+            .map(|v| (v, self.objective_at(objective, v)))
+            .fold(None, |best, (v, value)| match best {
+                Some((_, max)) if max >= value => best,
+                _ => Some((v, value)),
+            })
+    }
+
+    /// Maximizes a linear objective over a convex polytope by walking along
+    /// its edges from a given vertex, always moving to the adjacent vertex
+    /// where the objective is largest, until no adjacent vertex improves on
+    /// the current one.
+    ///
+    /// On a nonconvex polytope, this might get stuck at a local maximum.
+    /// Returns `None` if the starting vertex doesn't exist, or if the
+    /// objective doesn't have the same dimension as the polytope.
+    pub fn maximize_from(&self, start: usize, objective: &Vector) -> Option<(usize, Float)> {
+        if start >= self.vertex_count() || self.dim()? != objective.len() {
+            return None;
+        }
+
+        let mut vertex = start;
+        let mut value = self.objective_at(objective, vertex);
+        if self.rank() < Rank::new(1) {
+            return Some((vertex, value));
+        }
+
+        loop {
+            let mut next = None;
+            for &edge in &self[Rank::new(0)][vertex].sups {
+                for &v in &self[Rank::new(1)][edge].subs {
+                    let new_value = self.objective_at(objective, v);
+                    if new_value > value + Float::EPS {
+                        value = new_value;
+                        next = Some(v);
+                    }
+                }
+            }
+
+            match next {
+                Some(v) => vertex = v,
+                None => return Some((vertex, value)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use nalgebra::dvector;
+
+    #[test]
+    fn cube() {
+        let cube = Concrete::hypercube(Rank::new(3));
+        let objective = dvector![1.0, 2.0, -3.0];
+
+        let (vertex, value) = cube.maximize(&objective).unwrap();
+        assert!((value - 3.0).abs() < Float::EPS);
+        assert_eq!(
+            cube.vertices[vertex],
+            dvector![0.5, 0.5, -0.5],
+            "wrong maximizer"
+        );
+
+        let (_, value) = cube.minimize(&objective).unwrap();
+        assert!((value + 3.0).abs() < Float::EPS);
+    }
+
+    /// Walking along the edges gives the same maximum as scanning the
+    /// vertices, from whichever vertex we start.
+    #[test]
+    fn walk() {
+        let poly = Concrete::duoprism(&Concrete::polygon(7), &Concrete::polygon(9));
+        let objective = dvector![0.3, -1.2, 0.7, 2.1];
+        let (_, max) = poly.maximize_scan(&objective).unwrap();
+
+        for start in 0..poly.vertex_count() {
+            let (vertex, value) = poly.maximize_from(start, &objective).unwrap();
+            assert!((value - max).abs() < Float::EPS);
+            assert!((poly.objective_at(&objective, vertex) - max).abs() < Float::EPS);
+        }
+    }
+
+    #[test]
+    fn invalid() {
+        let square = Concrete::polygon(4);
+        assert!(square.maximize(&dvector![1.0, 0.0, 0.0]).is_none());
+        assert!(square.maximize_from(4, &dvector![1.0, 0.0]).is_none());
+        assert!(Concrete::nullitope()
+            .maximize(&dvector![1.0, 0.0])
+            .is_none());
+    }
+}
@@ -0,0 +1,152 @@
+//! Moves a polytope so that one of its elements faces "up", which is useful
+//! when attaching polytopes to one another, unfolding nets, or rendering.
+
+use super::{Concrete, ConcretePolytope};
+use crate::{
+    abs::elements::ElementRef,
+    geometry::{Matrix, Point, Subspace, Vector},
+    Consts, Float,
+};
+
+/// Returns a rotation that sends a unit vector to another one, by rotating
+/// the plane they span. Antipodal vectors get rotated through the plane that
+/// contains the first coordinate axis, or reflected in a single dimension.
+fn rotation_between(from: &Vector, to: &Vector) -> Matrix {
+    let dim = from.len();
+    let cos = from.dot(to);
+    let perp = to - from * cos;
+    let sin = perp.norm();
+
+    let other = perp.try_normalize(Float::EPS).unwrap_or_else(|| {
+        let mut axis = Vector::zeros(dim);
+        if dim > 1 {
+            axis[0] = 1.0;
+        }
+        axis
+    });
+
+    Matrix::identity(dim, dim)
+        + (&other * from.transpose() - from * other.transpose()) * sin
+        + (from * from.transpose() + &other * other.transpose()) * (cos - 1.0)
+}
+
+impl Concrete {
+    /// Moves the polytope through an isometry, so that the centroid of a given
+    /// element lies on the positive side of the last coordinate axis, and the
+    /// subspace spanned by the element is perpendicular to it. The gravicenter
+    /// of the polytope ends up right below the element, so that a facet ends
+    /// up on top of the polytope, and a vertex at its apex.
+    ///
+    /// If unsuccessful, leaves the polytope unchanged and returns `false`. This
+    /// happens when the element doesn't exist, or when its subspace passes
+    /// through the gravicenter.
+    pub fn orient_to(&mut self, el: ElementRef) -> bool {
+        let vertices = match self.abs.element_vertices(el) {
+            Some(vertices) if !vertices.is_empty() => vertices,
+            _ => return false,
+        };
+        let center = match self.gravicenter() {
+            Some(center) => center,
+            None => return false,
+        };
+
+        // The direction from the gravicenter to the element, perpendicular to
+        // the element.
+        let subspace = Subspace::from_points(vertices.iter().map(|&v| &self.vertices[v]));
+        let up = match subspace.normal(&center) {
+            Some(normal) => -normal,
+            None => return false,
+        };
+
+        let dim = center.len();
+        let mut axis = Vector::zeros(dim);
+        axis[dim - 1] = 1.0;
+        let rotation = rotation_between(&up, &axis);
+
+        let mut centroid = Point::zeros(dim);
+        for &v in &vertices {
+            centroid += &self.vertices[v];
+        }
+        centroid /= vertices.len() as Float;
+
+        // Only the horizontal offset of the element's centroid gets removed.
+        let mut offset = &rotation * (centroid - &center);
+        offset[dim - 1] = 0.0;
+
+        for v in &mut self.vertices {
+            *v = &rotation * (&*v - &center) - &offset;
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{abs::rank::Rank, Polytope};
+
+    /// Orients a polytope towards an element, and checks that the element ends
+    /// up horizontal at a given height, centered on the last axis, and that
+    /// the edge lengths don't change.
+    fn test(poly: &Concrete, el: ElementRef, height: Float) {
+        let mut oriented = poly.clone();
+        assert!(oriented.orient_to(el));
+
+        let vertices = oriented.abs.element_vertices(el).unwrap();
+        let mut centroid = Point::zeros(oriented.dim().unwrap());
+        for &v in &vertices {
+            let last = oriented.vertices[v][centroid.len() - 1];
+            assert!((last - height).abs() < Float::EPS);
+            centroid += &oriented.vertices[v];
+        }
+        centroid /= vertices.len() as Float;
+        for &x in centroid.iter().take(centroid.len() - 1) {
+            assert!(x.abs() < Float::EPS);
+        }
+
+        for edge in &poly[Rank::new(1)] {
+            let (v, w) = (edge.subs[0], edge.subs[1]);
+            let old = (&poly.vertices[v] - &poly.vertices[w]).norm();
+            let new = (&oriented.vertices[v] - &oriented.vertices[w]).norm();
+            assert!((old - new).abs() < Float::EPS);
+        }
+    }
+
+    #[test]
+    fn cube() {
+        let cube = Concrete::hypercube(Rank::new(3));
+        for idx in 0..6 {
+            test(&cube, ElementRef::new(Rank::new(2), idx), 0.5);
+        }
+        test(&cube, ElementRef::new(Rank::new(1), 5), Float::SQRT_2 / 2.0);
+        test(
+            &cube,
+            ElementRef::new(Rank::new(0), 3),
+            (3.0 as Float).sqrt() / 2.0,
+        );
+
+        // The cube contains its own gravicenter, and has no seventh face.
+        let mut clone = cube.clone();
+        assert!(!clone.orient_to(ElementRef::new(Rank::new(3), 0)));
+        assert!(!clone.orient_to(ElementRef::new(Rank::new(2), 6)));
+        assert_eq!(clone.vertices, cube.vertices);
+    }
+
+    #[test]
+    fn antipodal() {
+        // The bottom vertex of a dyad has to be flipped upside down.
+        let dyad = Concrete::dyad();
+        test(&dyad, ElementRef::new(Rank::new(0), 0), 0.5);
+        test(&dyad, ElementRef::new(Rank::new(0), 1), 0.5);
+
+        let square = Concrete::polygon(4);
+        for idx in 0..4 {
+            test(
+                &square,
+                ElementRef::new(Rank::new(1), idx),
+                Float::SQRT_2 / 2.0,
+            );
+        }
+    }
+}
@@ -0,0 +1,396 @@
+//! Rebuilds the element lattice of a polytope from its faces, which is useful
+//! for meshes imported from formats that only store vertices and faces.
+
+use std::collections::HashMap;
+
+use super::Concrete;
+use crate::{
+    abs::{
+        elements::{AbstractBuilder, SubelementList, Subelements},
+        rank::Rank,
+        AbstractResult,
+    },
+    Polytope,
+};
+
+use vec_like::VecLike;
+
+/// An edge, stored as the sorted pair of its vertices.
+type Edge = [usize; 2];
+
+/// Returns the faces at a vertex grouped into classes, so that faces are in
+/// the same class whenever they can be reached from one another by crossing
+/// edges through the vertex. These are the components of the vertex figure.
+fn vertex_figure_components(
+    v: usize,
+    faces: &[Vec<Edge>],
+    vertex_faces: &[usize],
+    edge_faces: &HashMap<Edge, Vec<usize>>,
+) -> Vec<Vec<usize>> {
+    let mut visited: HashMap<usize, bool> = vertex_faces.iter().map(|&f| (f, false)).collect();
+    let mut components = Vec::new();
+
+    for &f in vertex_faces {
+        if visited[&f] {
+            continue;
+        }
+
+        visited.insert(f, true);
+        let mut component = vec![f];
+        let mut stack = vec![f];
+
+        while let Some(f) = stack.pop() {
+            for edge in faces[f].iter().filter(|edge| edge.contains(&v)) {
+                for &g in &edge_faces[edge] {
+                    if !visited[&g] {
+                        visited.insert(g, true);
+                        component.push(g);
+                        stack.push(g);
+                    }
+                }
+            }
+        }
+
+        components.push(component);
+    }
+
+    components
+}
+
+/// Splits a set of faces into classes, so that faces are in the same class
+/// whenever they can be reached from one another by crossing edges. The edges
+/// of every face are given by index, along with the faces on each edge.
+fn face_components(
+    faces: &[usize],
+    face_subs: &SubelementList,
+    edge_faces: &[Vec<usize>],
+) -> Vec<Subelements> {
+    let mut visited: HashMap<usize, bool> = faces.iter().map(|&f| (f, false)).collect();
+    let mut components = Vec::new();
+
+    for &f in faces {
+        if visited[&f] {
+            continue;
+        }
+
+        visited.insert(f, true);
+        let mut component = Subelements(vec![f]);
+        let mut stack = vec![f];
+
+        while let Some(f) = stack.pop() {
+            for &e in &face_subs[f] {
+                for &g in &edge_faces[e] {
+                    if visited.get(&g) == Some(&false) {
+                        visited.insert(g, true);
+                        component.push(g);
+                        stack.push(g);
+                    }
+                }
+            }
+        }
+
+        component.sort();
+        components.push(component);
+    }
+
+    components
+}
+
+impl Concrete {
+    /// Rebuilds the vertices, edges and 3-elements of a polytope of rank at
+    /// least 3 out of the vertices of the edges of its faces, and then checks
+    /// that the result is [valid](crate::abs::Abstract::is_valid). This is
+    /// mostly useful for surface meshes, such as those read from OBJ files.
+    /// Polytopes of lower rank are only validated.
+    ///
+    /// Specifically, this method:
+    ///
+    /// - Merges edges with the same vertices, and removes degenerate edges.
+    /// - Splits every vertex whose vertex figure is disconnected, such as the
+    ///   tip shared by two cones, into one vertex for each component.
+    /// - Removes the vertices that aren't on any face.
+    /// - Splits every 3-element into the pieces in which its faces are
+    ///   connected through edges, and makes the elements that contained it
+    ///   contain all of its pieces instead. A polyhedron just gets all of the
+    ///   faces, so that its pieces become its components.
+    ///
+    /// The lattice is rebuilt even if it turns out to be invalid, in which case
+    /// the first error found is returned. Edges shared by more than two faces
+    /// can't be repaired, for instance.
+    pub fn rebuild_lattice_from_faces(&mut self) -> AbstractResult<()> {
+        let rank = self.rank();
+        if rank < Rank::new(3) {
+            return self.abs.is_valid();
+        }
+
+        // Reads the edges of every face as pairs of vertices.
+        let edges = &self.abs[Rank::new(1)];
+        let mut faces: Vec<Vec<Edge>> = self.abs[Rank::new(2)]
+            .iter()
+            .map(|face| {
+                let mut face_edges: Vec<_> = face
+                    .subs
+                    .iter()
+                    .filter_map(|&e| match edges[e].subs.0.as_slice() {
+                        &[v0, v1] if v0 != v1 => Some([v0.min(v1), v0.max(v1)]),
+                        _ => None,
+                    })
+                    .collect();
+                face_edges.sort_unstable();
+                face_edges.dedup();
+                face_edges
+            })
+            .collect();
+
+        let mut vertex_faces = vec![Vec::new(); self.vertices.len()];
+        let mut edge_faces: HashMap<Edge, Vec<usize>> = HashMap::new();
+        for (f, face) in faces.iter().enumerate() {
+            for &edge in face {
+                edge_faces.entry(edge).or_default().push(f);
+                for &v in &edge {
+                    if vertex_faces[v].last() != Some(&f) {
+                        vertex_faces[v].push(f);
+                    }
+                }
+            }
+        }
+
+        // Every component of a vertex figure other than the first gets a new
+        // copy of the vertex. These are only applied after all components
+        // have been found, so that the edges are looked up consistently.
+        let mut splits = Vec::new();
+        for v in 0..self.vertices.len() {
+            let components = vertex_figure_components(v, &faces, &vertex_faces[v], &edge_faces);
+            for component in components.into_iter().skip(1) {
+                let new_v = self.vertices.len();
+                self.vertices.push(self.vertices[v].clone());
+                vertex_faces.push(Vec::new());
+                splits.push((v, new_v, component));
+            }
+        }
+
+        for (v, new_v, component) in splits {
+            for f in component {
+                for edge in faces[f].iter_mut() {
+                    for u in edge.iter_mut().filter(|u| **u == v) {
+                        *u = new_v;
+                    }
+                    edge.sort_unstable();
+                }
+                vertex_faces[new_v].push(f);
+            }
+        }
+
+        // Removes the vertices that aren't on any face.
+        let mut new_indices = vec![usize::MAX; self.vertices.len()];
+        let mut vertices = Vec::new();
+        for (v, vertex) in self.vertices.drain(..).enumerate() {
+            if !vertex_faces[v].is_empty() {
+                new_indices[v] = vertices.len();
+                vertices.push(vertex);
+            }
+        }
+        self.vertices = vertices;
+
+        // Rebuilds the edges and faces.
+        let mut edges = SubelementList::new();
+        let mut face_subs = SubelementList::with_capacity(faces.len());
+        let mut hash_edges = HashMap::new();
+        for face in &mut faces {
+            let mut subs = Subelements::with_capacity(face.len());
+            for edge in face.iter_mut() {
+                for v in edge.iter_mut() {
+                    *v = new_indices[*v];
+                }
+
+                let len = edges.len();
+                let idx = *hash_edges.entry(*edge).or_insert(len);
+                if idx == len {
+                    edges.push(Subelements(edge.to_vec()));
+                }
+                subs.push(idx);
+            }
+            face_subs.push(subs);
+        }
+
+        let mut edge_faces = vec![Vec::new(); edges.len()];
+        for (f, subs) in face_subs.iter().enumerate() {
+            for &e in subs {
+                edge_faces[e].push(f);
+            }
+        }
+
+        let mut abs = AbstractBuilder::with_capacity(rank);
+        abs.push_min();
+        abs.push_vertices(self.vertices.len());
+        abs.push(edges);
+
+        if rank == Rank::new(3) {
+            abs.push(face_subs);
+            abs.push_max();
+            self.abs = abs.build();
+            return self.abs.is_valid();
+        }
+
+        // Splits the 3-elements into their pieces, and records the pieces that
+        // each of them turned into.
+        let mut cells = SubelementList::new();
+        let mut pieces = Vec::with_capacity(self.abs[Rank::new(3)].len());
+        for el in self.abs[Rank::new(3)].iter() {
+            let start = cells.len();
+            for component in face_components(&el.subs.0, &face_subs, &edge_faces) {
+                cells.push(component);
+            }
+            pieces.push(start..cells.len());
+        }
+
+        abs.push(face_subs);
+        abs.push(cells);
+        abs.push(SubelementList::from(
+            self.abs[Rank::new(4)]
+                .iter()
+                .map(|el| Subelements(el.subs.iter().flat_map(|&c| pieces[c].clone()).collect()))
+                .collect::<Vec<_>>(),
+        ));
+        for r in Rank::range_inclusive_iter(5, rank) {
+            abs.push(SubelementList::from(
+                self.abs[r]
+                    .iter()
+                    .map(|el| el.subs.clone())
+                    .collect::<Vec<_>>(),
+            ));
+        }
+
+        self.abs = abs.build();
+        self.abs.is_valid()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{abs::AbstractError, conc::file::FromFile};
+
+    /// Builds a cube in which every face has its own copy of its edges, and
+    /// with an extra vertex on no face.
+    fn loose_cube() -> Concrete {
+        let mut cube = Concrete::hypercube(Rank::new(3));
+        let mut edges = SubelementList::new();
+        let mut faces = SubelementList::new();
+
+        for face in cube[Rank::new(2)].iter() {
+            let mut subs = Subelements::new();
+            for &e in &face.subs {
+                subs.push(edges.len());
+                edges.push(cube[Rank::new(1)][e].subs.clone());
+            }
+            faces.push(subs);
+        }
+
+        cube.vertices.push(cube.vertices[0].clone() * 2.0);
+        let mut abs = AbstractBuilder::new();
+        abs.push_min();
+        abs.push_vertices(9);
+        abs.push(edges);
+        abs.push(faces);
+        abs.push_max();
+
+        Concrete::new(cube.vertices, abs.build())
+    }
+
+    #[test]
+    fn cube() {
+        let mut cube = loose_cube();
+        assert!(cube.abs.is_valid().is_err());
+        cube.rebuild_lattice_from_faces().unwrap();
+        assert_eq!(cube.el_counts(), vec![1, 8, 12, 6, 1].into());
+        assert_eq!(cube.vertices.len(), 8);
+
+        // Rebuilding a valid polytope doesn't change its element counts.
+        let mut tes = Concrete::hypercube(Rank::new(4));
+        tes.rebuild_lattice_from_faces().unwrap();
+        assert_eq!(tes.el_counts(), vec![1, 16, 32, 24, 8, 1].into());
+    }
+
+    /// A tesseract in which two opposite cubes were read as a single cell gets
+    /// them split again.
+    #[test]
+    fn merged_cells() {
+        let mut tes = Concrete::hypercube(Rank::new(4));
+        let cells = &tes[Rank::new(3)];
+        let opposite = (1..cells.len())
+            .find(|&c| cells[c].subs.iter().all(|f| !cells[0].subs.contains(f)))
+            .unwrap();
+
+        let mut merged = SubelementList::new();
+        for (c, cell) in cells.iter().enumerate() {
+            if c == 0 {
+                let mut subs = cell.subs.clone();
+                subs.0.extend(cells[opposite].subs.iter());
+                merged.push(subs);
+            } else if c != opposite {
+                merged.push(cell.subs.clone());
+            }
+        }
+
+        let mut abs = AbstractBuilder::new();
+        abs.push_min();
+        abs.push_vertices(16);
+        for r in [1, 2] {
+            abs.push(SubelementList::from(
+                tes[Rank::new(r)]
+                    .iter()
+                    .map(|el| el.subs.clone())
+                    .collect::<Vec<_>>(),
+            ));
+        }
+        abs.push(merged);
+        abs.push_max();
+        tes.abs = abs.build();
+        assert!(matches!(
+            tes.abs.validate(),
+            Err(AbstractError::Connected(section)) if section.hi.rank == Rank::new(3)
+        ));
+
+        tes.rebuild_lattice_from_faces().unwrap();
+        assert_eq!(tes.el_counts(), vec![1, 16, 32, 24, 8, 1].into());
+        tes.abs.validate().unwrap();
+    }
+
+    /// Two tetrahedra sharing a vertex become a compound of two tetrahedra.
+    #[test]
+    fn pinched_vertex() {
+        let mut tets = Concrete::from_off(
+            "OFF 7 8 0
+            0 0 0  1 0 0  0 1 0  0 0 1  -1 0 0  0 -1 0  0 0 -1
+            3 0 1 2  3 0 1 3  3 0 2 3  3 1 2 3
+            3 0 4 5  3 0 4 6  3 0 5 6  3 4 5 6",
+        )
+        .unwrap();
+        assert!(matches!(
+            tets.abs.validate(),
+            Err(AbstractError::Connected(section)) if section.lo.rank == Rank::new(0)
+        ));
+
+        tets.rebuild_lattice_from_faces().unwrap();
+        assert_eq!(tets.el_counts(), vec![1, 8, 12, 8, 1].into());
+        assert_eq!(tets.vertices[0], tets.vertices[7]);
+        assert_eq!(tets.abs.facet_components().len(), 2);
+    }
+
+    /// Three triangles sharing an edge can't be repaired.
+    #[test]
+    fn non_manifold() {
+        let mut book = Concrete::from_off(
+            "OFF 5 3 0
+            0 0 0  0 0 1  1 0 0  0 1 0  -1 -1 0
+            3 0 1 2  3 0 1 3  3 0 1 4",
+        )
+        .unwrap();
+
+        assert!(matches!(
+            book.rebuild_lattice_from_faces(),
+            Err(AbstractError::Dyadic { .. })
+        ));
+    }
+}
@@ -0,0 +1,371 @@
+//! Declares [`Sequence`], a family of realizations of the same abstract
+//! polytope over a parameter, which is the common ground for morphs,
+//! truncation sweeps, and playing back how an optimization converges.
+
+use std::{fs, io, path::Path};
+
+use super::Concrete;
+use crate::{abs::Abstract, geometry::Point, Float, Polytope};
+
+use vec_like::VecLike;
+
+/// Represents an error while building a [`Sequence`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SequenceError {
+    /// A keyframe had the wrong number of vertices.
+    VertexCount {
+        /// The number of vertices of the abstract polytope.
+        expected: usize,
+
+        /// The number of vertices of the keyframe.
+        found: usize,
+    },
+
+    /// A keyframe had vertices of the wrong dimension.
+    Dimension {
+        /// The dimension of the vertices of the other keyframes.
+        expected: usize,
+
+        /// The dimension of the vertices of the keyframe.
+        found: usize,
+    },
+
+    /// A keyframe had a parameter that wasn't finite, or that another keyframe
+    /// already had.
+    Parameter(Float),
+
+    /// A polytope had a different abstract structure than the sequence.
+    Structure,
+}
+
+impl std::fmt::Display for SequenceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::VertexCount { expected, found } => {
+                write!(f, "expected {} vertices, found {}", expected, found)
+            }
+            Self::Dimension { expected, found } => {
+                write!(
+                    f,
+                    "expected vertices of dimension {}, found {}",
+                    expected, found
+                )
+            }
+            Self::Parameter(t) => write!(f, "invalid or repeated parameter {}", t),
+            Self::Structure => write!(f, "abstract structure doesn't match"),
+        }
+    }
+}
+
+impl std::error::Error for SequenceError {}
+
+/// The result of building a [`Sequence`].
+pub type SequenceResult<T> = Result<T, SequenceError>;
+
+/// The positions of the vertices of a [`Sequence`] at a given parameter.
+#[derive(Clone, Debug)]
+pub struct Keyframe {
+    /// The parameter of the keyframe.
+    pub t: Float,
+
+    /// The vertices at this parameter.
+    pub vertices: Vec<Point>,
+}
+
+/// A family of concrete polytopes over a parameter `t`, which share the same
+/// abstract structure. The vertices are given at some keyframes, and are
+/// interpolated linearly in between. Before the first keyframe and after the
+/// last, the vertices stay in place.
+#[derive(Clone, Debug)]
+pub struct Sequence {
+    /// The abstract polytope shared by every member of the sequence.
+    abs: Abstract,
+
+    /// The keyframes, sorted by parameter.
+    keyframes: Vec<Keyframe>,
+}
+
+/// Returns whether two abstract polytopes have the same elements, with the
+/// same indices.
+fn same_structure(p: &Abstract, q: &Abstract) -> bool {
+    p.rank() == q.rank()
+        && p.ranks.iter().zip(q.ranks.iter()).all(|(p_els, q_els)| {
+            p_els.len() == q_els.len()
+                && p_els
+                    .iter()
+                    .zip(q_els.iter())
+                    .all(|(p_el, q_el)| p_el.subs == q_el.subs)
+        })
+}
+
+impl Sequence {
+    /// Initializes a sequence without keyframes for an abstract polytope.
+    pub fn new(abs: Abstract) -> Self {
+        Self {
+            abs,
+            keyframes: Vec::new(),
+        }
+    }
+
+    /// Initializes a sequence with a single keyframe out of a polytope.
+    pub fn from_polytope(t: Float, poly: Concrete) -> SequenceResult<Self> {
+        let mut sequence = Self::new(poly.abs);
+        sequence.add_keyframe(t, poly.vertices)?;
+        Ok(sequence)
+    }
+
+    /// Builds a sequence out of the values of a function at evenly spaced
+    /// parameters from `start` to `end`, both included. This is the way to
+    /// record operations with a continuous parameter, such as
+    /// [`Concrete::truncate_with`].
+    ///
+    /// # Panics
+    /// This method will panic if fewer than two samples are requested.
+    pub fn from_fn<F: FnMut(Float) -> Concrete>(
+        start: Float,
+        end: Float,
+        samples: usize,
+        mut f: F,
+    ) -> SequenceResult<Self> {
+        assert!(samples >= 2, "A sequence needs at least two samples.");
+
+        let mut sequence: Option<Self> = None;
+        for i in 0..samples {
+            let t = start + (end - start) * i as Float / (samples - 1) as Float;
+            let poly = f(t);
+
+            match &mut sequence {
+                Some(sequence) => sequence.add_polytope(t, poly)?,
+                None => sequence = Some(Self::from_polytope(t, poly)?),
+            }
+        }
+
+        Ok(sequence.unwrap())
+    }
+
+    /// Returns the abstract polytope shared by the sequence.
+    pub fn abs(&self) -> &Abstract {
+        &self.abs
+    }
+
+    /// Returns the keyframes, sorted by parameter.
+    pub fn keyframes(&self) -> &[Keyframe] {
+        &self.keyframes
+    }
+
+    /// Returns the parameters of the first and last keyframes, if any.
+    pub fn range(&self) -> Option<(Float, Float)> {
+        Some((self.keyframes.first()?.t, self.keyframes.last()?.t))
+    }
+
+    /// Adds a keyframe with some vertices, which should be given in the same
+    /// order as those of the abstract polytope.
+    pub fn add_keyframe(&mut self, t: Float, vertices: Vec<Point>) -> SequenceResult<()> {
+        let expected = self.abs.vertex_count();
+        if vertices.len() != expected {
+            return Err(SequenceError::VertexCount {
+                expected,
+                found: vertices.len(),
+            });
+        }
+
+        if let Some(expected) = self.dim() {
+            if let Some(v) = vertices.iter().find(|v| v.len() != expected) {
+                return Err(SequenceError::Dimension {
+                    expected,
+                    found: v.len(),
+                });
+            }
+        }
+
+        if !t.is_finite() {
+            return Err(SequenceError::Parameter(t));
+        }
+        let idx = match self
+            .keyframes
+            .binary_search_by(|keyframe| keyframe.t.partial_cmp(&t).unwrap())
+        {
+            Ok(_) => return Err(SequenceError::Parameter(t)),
+            Err(idx) => idx,
+        };
+
+        self.keyframes.insert(idx, Keyframe { t, vertices });
+        Ok(())
+    }
+
+    /// Adds a polytope as a keyframe, as long as it has the same abstract
+    /// structure as the sequence.
+    pub fn add_polytope(&mut self, t: Float, poly: Concrete) -> SequenceResult<()> {
+        if !same_structure(&self.abs, &poly.abs) {
+            return Err(SequenceError::Structure);
+        }
+
+        self.add_keyframe(t, poly.vertices)
+    }
+
+    /// Returns the dimension of the vertices of the sequence, if there are
+    /// any.
+    fn dim(&self) -> Option<usize> {
+        Some(self.keyframes.first()?.vertices.first()?.len())
+    }
+
+    /// Returns the vertices at a given parameter, interpolated between the
+    /// closest keyframes. Returns `None` if there are no keyframes.
+    pub fn vertices_at(&self, t: Float) -> Option<Vec<Point>> {
+        let first = self.keyframes.first()?;
+        let last = self.keyframes.last().unwrap();
+
+        if t <= first.t {
+            return Some(first.vertices.clone());
+        } else if t >= last.t {
+            return Some(last.vertices.clone());
+        }
+
+        // The first keyframe after t.
+        let idx = self.keyframes.partition_point(|keyframe| keyframe.t <= t);
+        let (prev, next) = (&self.keyframes[idx - 1], &self.keyframes[idx]);
+        let s = (t - prev.t) / (next.t - prev.t);
+
+        Some(
+            prev.vertices
+                .iter()
+                .zip(&next.vertices)
+                .map(|(v, w)| v + (w - v) * s)
+                .collect(),
+        )
+    }
+
+    /// Returns the polytope at a given parameter. Returns `None` if there are
+    /// no keyframes.
+    pub fn at(&self, t: Float) -> Option<Concrete> {
+        Some(Concrete::new(self.vertices_at(t)?, self.abs.clone()))
+    }
+
+    /// Returns the polytopes at a number of evenly spaced parameters, from the
+    /// first keyframe to the last, along with their parameters.
+    pub fn sample(&self, frames: usize) -> Vec<(Float, Concrete)> {
+        let (start, end) = match self.range() {
+            Some(range) => range,
+            None => return Vec::new(),
+        };
+
+        (0..frames)
+            .map(|i| {
+                let t = if frames == 1 {
+                    start
+                } else {
+                    start + (end - start) * i as Float / (frames - 1) as Float
+                };
+                (t, self.at(t).unwrap())
+            })
+            .collect()
+    }
+
+    /// Calls a function on every frame of an evenly spaced sample of the
+    /// sequence, along with its index and parameter. This is the hook through
+    /// which frames get rendered or saved.
+    pub fn export<E, F: FnMut(usize, Float, &Concrete) -> Result<(), E>>(
+        &self,
+        frames: usize,
+        mut f: F,
+    ) -> Result<(), E> {
+        for (i, (t, poly)) in self.sample(frames).into_iter().enumerate() {
+            f(i, t, &poly)?;
+        }
+
+        Ok(())
+    }
+
+    /// Saves an evenly spaced sample of the sequence into a directory, as OFF
+    /// files named `frame_0000.off`, `frame_0001.off`, and so on.
+    pub fn export_off<T: AsRef<Path>>(&self, dir: T, frames: usize) -> io::Result<()> {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir)?;
+
+        self.export(frames, |i, _, poly| {
+            fs::write(
+                dir.join(format!("frame_{:04}.off", i)),
+                poly.to_off(Default::default()),
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{abs::rank::Rank, conc::ConcretePolytope, Consts};
+
+    #[test]
+    fn interpolate() {
+        let square = Concrete::polygon(4);
+        let mut sequence = Sequence::from_polytope(0.0, square.clone()).unwrap();
+        let mut big_square = square.clone();
+        big_square.scale(3.0);
+        sequence.add_keyframe(2.0, big_square.vertices).unwrap();
+
+        // Halfway through, the square has doubled in size.
+        let middle = sequence.at(1.0).unwrap();
+        for (v, w) in middle.vertices.iter().zip(&square.vertices) {
+            assert!((v - w * 2.0).norm() < Float::EPS);
+        }
+
+        // Parameters out of range are clamped.
+        assert_eq!(sequence.at(-1.0).unwrap().vertices, square.vertices);
+        assert_eq!(sequence.sample(5).len(), 5);
+        assert_eq!(sequence.sample(5)[4].0, 2.0);
+    }
+
+    #[test]
+    fn errors() {
+        let mut sequence = Sequence::from_polytope(0.0, Concrete::polygon(4)).unwrap();
+        assert_eq!(
+            sequence.add_keyframe(1.0, Concrete::polygon(5).vertices),
+            Err(SequenceError::VertexCount {
+                expected: 4,
+                found: 5
+            })
+        );
+        assert_eq!(
+            sequence.add_keyframe(0.0, Concrete::polygon(4).vertices),
+            Err(SequenceError::Parameter(0.0))
+        );
+        assert_eq!(
+            sequence.add_keyframe(1.0, vec![Point::zeros(3); 4]),
+            Err(SequenceError::Dimension {
+                expected: 2,
+                found: 3
+            })
+        );
+        assert_eq!(
+            sequence.add_polytope(1.0, Concrete::hypercube(Rank::new(2))),
+            Err(SequenceError::Structure)
+        );
+    }
+
+    #[test]
+    fn truncation_sweep() {
+        let mut cube = Concrete::hypercube(Rank::new(3));
+        cube.abs_sort();
+
+        let sequence = Sequence::from_fn(0.0, 1.0, 3, |t| cube.truncate_with(t)).unwrap();
+        assert_eq!(sequence.keyframes().len(), 3);
+
+        // Interpolating linearly between the keyframes recovers the
+        // truncation, since its vertices move linearly.
+        let truncate = sequence.at(0.25).unwrap();
+        let expected = cube.truncate_with(0.25);
+        for (v, w) in truncate.vertices.iter().zip(&expected.vertices) {
+            assert!((v - w).norm() < Float::EPS);
+        }
+
+        let mut count = 0;
+        sequence
+            .export(4, |_, _, poly| {
+                count += 1;
+                poly.abs.is_valid()
+            })
+            .unwrap();
+        assert_eq!(count, 4);
+    }
+}
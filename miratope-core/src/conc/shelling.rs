@@ -0,0 +1,128 @@
+//! Builds line shellings of convex polytopes, following the construction of
+//! Bruggesser and Mani.
+
+use super::{Concrete, ConcretePolytope};
+use crate::{
+    abs::{elements::ElementRef, rank::Rank},
+    geometry::{Subspace, Vector},
+    Consts, Float, Polytope,
+};
+
+use vec_like::VecLike;
+
+impl Concrete {
+    /// Returns a [line shelling](https://en.wikipedia.org/wiki/Shelling_(topology))
+    /// of the facets of a convex polytope, as a list of their indices.
+    ///
+    /// A line through the gravicenter with the given direction is followed to
+    /// infinity, and then back from the opposite side. The facets are listed in
+    /// the order in which their hyperplanes are crossed. Returns `None` if the
+    /// polytope isn't full-dimensional, or if the line is parallel to some
+    /// facet, in which case a more generic direction should be used.
+    pub fn line_shelling(&self, direction: &Vector) -> Option<Vec<usize>> {
+        let center = self.gravicenter()?;
+        let facet_rank = self.rank().minus_one();
+        if self.rank() < Rank::new(1) || self.dim()? != facet_rank.plus_one_usize() {
+            return None;
+        }
+
+        // The parameter along the line where each facet hyperplane is crossed.
+        let mut crossings = Vec::with_capacity(self.facet_count());
+        for idx in 0..self[facet_rank].len() {
+            let vertices = self.element_vertices_ref(ElementRef::new(facet_rank, idx))?;
+            let hyperplane = Subspace::from_points(vertices.into_iter());
+            if !hyperplane.is_hyperplane() {
+                return None;
+            }
+
+            // The outer normal of the facet, and the distance to it.
+            let normal = -hyperplane.normal(&center)?;
+            let distance = hyperplane.distance(&center);
+            let speed = normal.dot(direction);
+            if speed.abs() < Float::EPS {
+                return None;
+            }
+
+            crossings.push((idx, distance / speed));
+        }
+
+        // Facets crossed on the way out come first, then those crossed on the
+        // way back in.
+        let (mut outward, mut inward): (Vec<_>, Vec<_>) =
+            crossings.into_iter().partition(|&(_, t)| t > 0.0);
+        outward.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+        inward.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+
+        Some(
+            outward
+                .into_iter()
+                .chain(inward)
+                .map(|(idx, _)| idx)
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::Point;
+
+    use nalgebra::dvector;
+
+    /// Checks the line shelling of a cube.
+    #[test]
+    fn cube() {
+        let cube = Concrete::hypercube(Rank::new(3));
+        let order = cube.line_shelling(&dvector![1.0, 0.2, 0.03]).unwrap();
+
+        // The facets are crossed in the order +x, +y, +z, -z, -y, -x.
+        let normals: Vec<_> = order
+            .iter()
+            .map(|&idx| {
+                let face = ElementRef::new(Rank::new(2), idx);
+                let vertices = cube.element_vertices_ref(face).unwrap();
+                let mut center = Point::zeros(3);
+                for v in &vertices {
+                    center += *v;
+                }
+                center / vertices.len() as Float
+            })
+            .map(|c| {
+                let axis = c.iamax();
+                (axis, c[axis] > 0.0)
+            })
+            .collect();
+
+        assert_eq!(
+            normals,
+            vec![
+                (0, true),
+                (1, true),
+                (2, true),
+                (2, false),
+                (1, false),
+                (0, false)
+            ]
+        );
+        assert!(cube.abs.is_shelling(&order));
+
+        // The line can't be parallel to any facet.
+        assert_eq!(cube.line_shelling(&dvector![1.0, 0.0, 0.0]), None);
+    }
+
+    /// Checks that line shellings are shellings.
+    #[test]
+    fn shellings() {
+        for poly in [
+            Concrete::hypercube(Rank::new(4)),
+            Concrete::orthoplex(Rank::new(4)),
+            Concrete::duoprism(&Concrete::polygon(5), &Concrete::polygon(7)),
+        ] {
+            let order = poly
+                .line_shelling(&dvector![0.9, 0.31, 0.17, 0.07])
+                .unwrap();
+            assert!(poly.abs.is_shelling(&order));
+        }
+    }
+}
@@ -0,0 +1,283 @@
+//! Reads off the configurations of elements around the elements of a
+//! polytope, such as the vertex configuration `3.4.3.4` of a cuboctahedron,
+//! or the signature `tet.oct.tet.oct` of the cells around an edge of a
+//! rectified pentachoron, which can then be compared against the tables of
+//! uniform polytopes.
+
+use std::{collections::HashMap, fmt::Display};
+
+use super::{cycle::CycleBuilder, tiling::VertexConfig, Concrete};
+use crate::{
+    abs::{elements::ElementRef, rank::Rank},
+    Polytope,
+};
+
+use vec_like::VecLike;
+
+/// The signature of an element of rank three less than the polytope, listing
+/// the types of the facets around it in cyclic order, such as
+/// `tet.oct.tet.oct`. Polygons are written as their number of sides, and
+/// other facets by the acronym of the [well-known polytope](crate::abs::fingerprint::KNOWN_POLYTOPES)
+/// they're recognized as, or by their fingerprint otherwise.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Signature(pub Vec<String>);
+
+impl Display for Signature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0.join("."))
+    }
+}
+
+impl Signature {
+    /// Returns the rotation or reflection of the signature that comes first
+    /// lexicographically.
+    pub fn canonical(&self) -> Self {
+        let labels = &self.0;
+        let n = labels.len();
+
+        (0..n)
+            .flat_map(|start| {
+                [false, true].iter().map(move |&reversed| {
+                    (0..n)
+                        .map(|i| {
+                            labels[if reversed {
+                                (start + n - i) % n
+                            } else {
+                                (start + i) % n
+                            }]
+                            .clone()
+                        })
+                        .collect::<Vec<_>>()
+                })
+            })
+            .min()
+            .map_or_else(|| self.clone(), Self)
+    }
+}
+
+/// Tallies up a list of configurations, returning each distinct one with the
+/// number of times it appears, in increasing order.
+fn tally<T: Ord + std::hash::Hash>(configs: impl Iterator<Item = T>) -> Vec<(T, usize)> {
+    let mut counts = HashMap::new();
+    for config in configs {
+        *counts.entry(config).or_insert(0) += 1;
+    }
+
+    let mut counts: Vec<_> = counts.into_iter().collect();
+    counts.sort();
+    counts
+}
+
+impl Concrete {
+    /// Returns the facets around an element of rank two less than the
+    /// facets, in cyclic order. Returns `None` if the element doesn't exist,
+    /// or if the facets around it don't form a single cycle.
+    pub(crate) fn facet_cycle(&self, el: ElementRef) -> Option<Vec<usize>> {
+        let ridge_rank = el.rank.plus_one();
+        if ridge_rank.plus_one().plus_one() != self.rank() {
+            return None;
+        }
+
+        // Each ridge through the element joins two facets around it.
+        let mut builder = CycleBuilder::new();
+        let mut degrees = HashMap::new();
+        for &ridge in &self.abs.get_element(el)?.sups {
+            let facets = &self.abs[ridge_rank][ridge].sups;
+            if facets.len() != 2 {
+                return None;
+            }
+
+            builder.push(facets[0], facets[1]);
+            for &facet in facets {
+                *degrees.entry(facet).or_insert(0) += 1;
+            }
+        }
+
+        if builder.is_empty() || degrees.values().any(|&degree| degree != 2) {
+            return None;
+        }
+
+        let mut cycles = builder.cycles();
+        if cycles.len() == 1 {
+            Some(cycles.swap_remove(0).iter().copied().collect())
+        } else {
+            None
+        }
+    }
+
+    /// Returns the vertex configuration at a vertex of a polyhedron, listing
+    /// the number of sides of the faces around it in cyclic order. The
+    /// configuration is written in its [canonical](VertexConfig::canonical)
+    /// form.
+    ///
+    /// Returns `None` if the polytope isn't a polyhedron, if the vertex
+    /// doesn't exist, or if the faces around it don't form a single cycle.
+    pub fn vertex_configuration(&self, idx: usize) -> Option<VertexConfig> {
+        if self.rank() != Rank::new(3) {
+            return None;
+        }
+
+        let faces = self.facet_cycle(ElementRef::new(Rank::new(0), idx))?;
+        let config = faces
+            .into_iter()
+            .map(|face| self.abs[Rank::new(2)][face].subs.len() as u32)
+            .collect();
+
+        Some(VertexConfig(config).canonical())
+    }
+
+    /// Returns every distinct vertex configuration of a polyhedron, together
+    /// with the number of vertices that have it, in increasing order. A
+    /// uniform polyhedron has a single configuration.
+    ///
+    /// Returns `None` if any vertex has no configuration.
+    pub fn vertex_configurations(&self) -> Option<Vec<(VertexConfig, usize)>> {
+        let configs = (0..self.vertex_count())
+            .map(|idx| self.vertex_configuration(idx))
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(tally(configs.into_iter()))
+    }
+
+    /// Returns the type of a facet, as written in a [`Signature`].
+    fn facet_label(&self, idx: usize) -> Option<String> {
+        let facet = self.abs.facet(idx)?;
+        if facet.rank() == Rank::new(2) {
+            return Some(facet.vertex_count().to_string());
+        }
+
+        Some(match facet.identify() {
+            Some(known) => known.acronym.to_string(),
+            None => facet.fingerprint().to_string(),
+        })
+    }
+
+    /// Returns the signature of an element of rank three less than the
+    /// polytope, listing the types of the facets around it in cyclic order.
+    /// In a polychoron, these are the cells around an edge. The signature is
+    /// written in its [canonical](Signature::canonical) form.
+    ///
+    /// Returns `None` if the element doesn't exist or has the wrong rank, or
+    /// if the facets around it don't form a single cycle.
+    pub fn element_signature(&self, el: ElementRef) -> Option<Signature> {
+        let labels = self
+            .facet_cycle(el)?
+            .into_iter()
+            .map(|facet| self.facet_label(facet))
+            .collect::<Option<_>>()?;
+
+        Some(Signature(labels).canonical())
+    }
+
+    /// Returns every distinct signature of the elements of rank three less
+    /// than the polytope, together with the number of elements that have it,
+    /// in increasing order. See [`Self::element_signature`].
+    ///
+    /// Returns `None` if the polytope has rank less than 3, or if any element
+    /// has no signature.
+    pub fn element_signatures(&self) -> Option<Vec<(Signature, usize)>> {
+        if self.rank() < Rank::new(3) {
+            return None;
+        }
+
+        let rank = Rank::new(self.rank().into_isize() - 3);
+        let labels = (0..self.facet_count())
+            .map(|idx| self.facet_label(idx))
+            .collect::<Option<Vec<_>>>()?;
+
+        let signatures = (0..self.el_count(rank))
+            .map(|idx| {
+                let facets = self.facet_cycle(ElementRef::new(rank, idx))?;
+                let labels = facets.into_iter().map(|f| labels[f].clone()).collect();
+                Some(Signature(labels).canonical())
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(tally(signatures.into_iter()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the vertex configurations of a polyhedron as strings.
+    fn configs(poly: &Concrete) -> Vec<(String, usize)> {
+        poly.vertex_configurations()
+            .unwrap()
+            .into_iter()
+            .map(|(config, count)| (config.to_string(), count))
+            .collect()
+    }
+
+    #[test]
+    fn uniform() {
+        let cube = Concrete::hypercube(Rank::new(3));
+        assert_eq!(configs(&cube), vec![("4.4.4".to_string(), 8)]);
+
+        let prism = Concrete::polygon(5).prism();
+        assert_eq!(configs(&prism), vec![("4.4.5".to_string(), 10)]);
+
+        let antiprism = Concrete::polygon(4).antiprism();
+        assert_eq!(configs(&antiprism), vec![("3.3.3.4".to_string(), 8)]);
+    }
+
+    #[test]
+    fn pyramid() {
+        let pyramid = Concrete::polygon(4).pyramid();
+        assert_eq!(
+            configs(&pyramid),
+            vec![("3.3.3.3".to_string(), 1), ("3.3.4".to_string(), 4)]
+        );
+
+        // The configuration can be parsed back.
+        let config = pyramid.vertex_configuration(0).unwrap();
+        assert_eq!(VertexConfig::parse(&config.to_string()).unwrap(), config);
+    }
+
+    #[test]
+    fn canonical() {
+        let config = VertexConfig(vec![4, 3, 4, 5]).canonical();
+        assert_eq!(config.to_string(), "3.4.5.4");
+        assert_eq!(VertexConfig(vec![8, 6, 4]).canonical().to_string(), "4.6.8");
+    }
+
+    #[test]
+    fn signatures() {
+        let tesseract = Concrete::hypercube(Rank::new(4));
+        let signatures: Vec<_> = tesseract
+            .element_signatures()
+            .unwrap()
+            .into_iter()
+            .map(|(signature, count)| (signature.to_string(), count))
+            .collect();
+        assert_eq!(signatures, vec![("cube.cube.cube".to_string(), 32)]);
+
+        // The edges of a triangular duoprism are either on a triangle or on a
+        // square, and the cells around them are all triangular prisms.
+        let duoprism = Concrete::duoprism(&Concrete::polygon(3), &Concrete::polygon(3));
+        assert_eq!(
+            duoprism.element_signatures().unwrap(),
+            vec![(Signature(vec!["trip".to_string(); 3]), 18)]
+        );
+
+        // The signatures of a polyhedron are its vertex configurations.
+        let prism = Concrete::polygon(5).prism();
+        let vertex = ElementRef::new(Rank::new(0), 0);
+        assert_eq!(
+            prism.element_signature(vertex).unwrap().to_string(),
+            "4.4.5"
+        );
+        assert!(Concrete::polygon(5).element_signatures().is_none());
+    }
+
+    #[test]
+    fn invalid() {
+        let cube = Concrete::hypercube(Rank::new(3));
+        assert!(cube.vertex_configuration(8).is_none());
+        assert!(Concrete::hypercube(Rank::new(4))
+            .vertex_configuration(0)
+            .is_none());
+        assert!(Concrete::polygon(4).vertex_configurations().is_none());
+    }
+}
@@ -0,0 +1,268 @@
+//! Decomposes the solid body of a polytope into simplices, and exports the
+//! result in the mesh formats used by finite element software.
+
+use std::fmt::Write;
+
+use super::Concrete;
+use crate::{
+    abs::rank::Rank,
+    geometry::{Matrix, Point},
+    Float, Polytope,
+};
+
+use vec_like::VecLike;
+
+/// A [simplicial complex](https://en.wikipedia.org/wiki/Simplicial_complex)
+/// made out of simplices of a single dimension, given by the indices of their
+/// vertices.
+#[derive(Clone, Debug)]
+pub struct SimplicialComplex {
+    /// The vertices of the complex.
+    pub vertices: Vec<Point>,
+
+    /// The simplices of the complex, as lists of indices into the vertices.
+    pub simplices: Vec<Vec<usize>>,
+}
+
+impl SimplicialComplex {
+    /// Returns the dimension of the space the vertices live in, or `None` if
+    /// there are no vertices.
+    pub fn dim(&self) -> Option<usize> {
+        Some(self.vertices.first()?.len())
+    }
+
+    /// Returns the volume of a simplex, within its own span.
+    fn simplex_volume(&self, simplex: &[usize]) -> Float {
+        let rank = match simplex.len().checked_sub(1) {
+            Some(rank) => rank,
+            None => return 0.0,
+        };
+        let dim = self.dim().unwrap_or_default();
+
+        // The edges from the first vertex to all others.
+        let first = &self.vertices[simplex[0]];
+        let edges = Matrix::from_fn(rank, dim, |i, j| {
+            self.vertices[simplex[i + 1]][j] - first[j]
+        });
+
+        let factorial: Float = (1..=rank).map(|k| k as Float).product();
+        (edges.clone() * edges.transpose())
+            .determinant()
+            .abs()
+            .sqrt()
+            / factorial
+    }
+
+    /// Returns the sum of the volumes of all of the simplices.
+    pub fn volume(&self) -> Float {
+        self.simplices
+            .iter()
+            .map(|simplex| self.simplex_volume(simplex))
+            .sum()
+    }
+
+    /// Returns the coordinates of a vertex, padded with zeros up to three
+    /// dimensions.
+    fn coords_3d(vertex: &Point) -> [Float; 3] {
+        let mut coords = [0.0; 3];
+        for (coord, &x) in coords.iter_mut().zip(vertex.iter()) {
+            *coord = x;
+        }
+        coords
+    }
+
+    /// Exports a complex of tetrahedra in 3D space into the `.node` and `.ele`
+    /// files read by [TetGen](https://wias-berlin.de/software/tetgen/), in
+    /// that order. Returns `None` if the complex isn't of this form.
+    pub fn to_tetgen(&self) -> Option<(String, String)> {
+        if self.dim()? != 3 || self.simplices.iter().any(|simplex| simplex.len() != 4) {
+            return None;
+        }
+
+        let mut node = String::new();
+        writeln!(node, "{} 3 0 0", self.vertices.len()).unwrap();
+        for (idx, vertex) in self.vertices.iter().enumerate() {
+            writeln!(
+                node,
+                "{} {} {} {}",
+                idx + 1,
+                vertex[0],
+                vertex[1],
+                vertex[2]
+            )
+            .unwrap();
+        }
+
+        let mut ele = String::new();
+        writeln!(ele, "{} 4 0", self.simplices.len()).unwrap();
+        for (idx, simplex) in self.simplices.iter().enumerate() {
+            write!(ele, "{}", idx + 1).unwrap();
+            for &v in simplex {
+                write!(ele, " {}", v + 1).unwrap();
+            }
+            writeln!(ele).unwrap();
+        }
+
+        Some((node, ele))
+    }
+
+    /// Exports the complex into the ASCII version 2.2 of the
+    /// [Gmsh](https://gmsh.info/) mesh format. Returns `None` unless the
+    /// complex is made out of segments, triangles, or tetrahedra in at most
+    /// three dimensions.
+    pub fn to_gmsh(&self) -> Option<String> {
+        if self.dim()? > 3 {
+            return None;
+        }
+
+        // The element types of segments, triangles, and tetrahedra.
+        let element_type = match self.simplices.first().map(Vec::len) {
+            Some(2) => 1,
+            Some(3) => 2,
+            Some(4) => 4,
+            _ => return None,
+        };
+
+        let mut msh = String::new();
+        writeln!(msh, "$MeshFormat\n2.2 0 8\n$EndMeshFormat").unwrap();
+
+        writeln!(msh, "$Nodes\n{}", self.vertices.len()).unwrap();
+        for (idx, vertex) in self.vertices.iter().enumerate() {
+            let [x, y, z] = Self::coords_3d(vertex);
+            writeln!(msh, "{} {} {} {}", idx + 1, x, y, z).unwrap();
+        }
+        writeln!(msh, "$EndNodes").unwrap();
+
+        writeln!(msh, "$Elements\n{}", self.simplices.len()).unwrap();
+        for (idx, simplex) in self.simplices.iter().enumerate() {
+            write!(msh, "{} {} 0", idx + 1, element_type).unwrap();
+            for &v in simplex {
+                write!(msh, " {}", v + 1).unwrap();
+            }
+            writeln!(msh).unwrap();
+        }
+        writeln!(msh, "$EndElements").unwrap();
+
+        Some(msh)
+    }
+}
+
+impl Concrete {
+    /// Decomposes the solid body of the polytope into simplices of its same
+    /// rank, without adding any new vertices.
+    ///
+    /// Every element is split by coning its lowest-indexed vertex over the
+    /// simplices of the facets that don't contain it. This is a valid
+    /// triangulation whenever all elements of the polytope are convex.
+    pub fn triangulate_interior(&self) -> SimplicialComplex {
+        let vertices = self.vertices.clone();
+        let rank = self.rank();
+
+        let mut simplices = Vec::new();
+        if rank == Rank::new(-1) {
+            return SimplicialComplex {
+                vertices,
+                simplices,
+            };
+        }
+
+        // The sorted vertices of the elements of the previous rank, along
+        // with their simplices.
+        let mut el_vertices: Vec<Vec<usize>> = (0..self.vertex_count()).map(|v| vec![v]).collect();
+        let mut el_simplices: Vec<Vec<Vec<usize>>> =
+            (0..self.vertex_count()).map(|v| vec![vec![v]]).collect();
+
+        for r in Rank::range_inclusive_iter(1, rank) {
+            let mut new_vertices = Vec::with_capacity(self.el_count(r));
+            let mut new_simplices = Vec::with_capacity(self.el_count(r));
+
+            for el in self[r].iter() {
+                let mut vertices: Vec<usize> = el
+                    .subs
+                    .iter()
+                    .flat_map(|&sub| el_vertices[sub].iter().copied())
+                    .collect();
+                vertices.sort_unstable();
+                vertices.dedup();
+
+                // Cones the lowest vertex over the facets that miss it.
+                let pivot = vertices[0];
+                let mut simplices = Vec::new();
+                for &sub in el.subs.iter() {
+                    if el_vertices[sub].binary_search(&pivot).is_err() {
+                        for simplex in &el_simplices[sub] {
+                            let mut cone = Vec::with_capacity(simplex.len() + 1);
+                            cone.push(pivot);
+                            cone.extend_from_slice(simplex);
+                            simplices.push(cone);
+                        }
+                    }
+                }
+
+                new_vertices.push(vertices);
+                new_simplices.push(simplices);
+            }
+
+            el_vertices = new_vertices;
+            el_simplices = new_simplices;
+        }
+
+        simplices = el_simplices.into_iter().flatten().collect();
+        SimplicialComplex {
+            vertices,
+            simplices,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{conc::ConcretePolytope, Consts};
+
+    use approx::abs_diff_eq;
+
+    /// Checks that the triangulation of a polytope has the expected number of
+    /// simplices, and that it has the same volume.
+    fn test(mut poly: Concrete, simplex_count: usize) -> SimplicialComplex {
+        let complex = poly.triangulate_interior();
+        assert_eq!(complex.simplices.len(), simplex_count);
+
+        poly.abs_sort();
+        let volume: Float = poly.volume().unwrap().abs();
+        assert!(abs_diff_eq!(complex.volume(), volume, epsilon = Float::EPS));
+        complex
+    }
+
+    #[test]
+    fn polygon() {
+        let complex = test(Concrete::polygon(7), 5);
+        assert!(complex.to_tetgen().is_none());
+
+        let msh = complex.to_gmsh().unwrap();
+        assert!(msh.contains("$Nodes\n7\n"));
+        assert!(msh.contains("$Elements\n5\n"));
+    }
+
+    #[test]
+    fn polyhedra() {
+        test(Concrete::simplex(Rank::new(3)), 1);
+        test(Concrete::orthoplex(Rank::new(3)), 4);
+
+        let complex = test(Concrete::hypercube(Rank::new(3)), 6);
+        let (node, ele) = complex.to_tetgen().unwrap();
+        assert_eq!(node.lines().count(), 9);
+        assert_eq!(ele.lines().count(), 7);
+        assert!(ele.lines().skip(1).all(|line| line.split(' ').count() == 5));
+    }
+
+    #[test]
+    fn polychora() {
+        let complex = test(Concrete::hypercube(Rank::new(4)), 24);
+        assert!(complex.to_gmsh().is_none());
+        test(
+            Concrete::duoprism(&Concrete::polygon(5), &Concrete::polygon(6)),
+            72,
+        );
+    }
+}
@@ -0,0 +1,654 @@
+//! Builds the [stellations](https://polytope.miraheze.org/wiki/Stellation) of
+//! polyhedra and polychora.
+//!
+//! The hyperplanes through the facets of a polytope cut space into convex
+//! cells, which make up its stellation diagram. We find the vertices of this
+//! arrangement by intersecting the hyperplanes, and then walk from the cell
+//! containing the center of the polytope to every other cell through their
+//! facets. Each cell is stored as the side of every hyperplane it lies on,
+//! together with the vertices of the arrangement on its boundary. A large box
+//! around the diagram lets us tell the bounded cells apart from the rest.
+//!
+//! Any set of bounded cells can then be turned into a polytope, whose facets
+//! are the pieces of the hyperplanes that separate the chosen cells from the
+//! others. For instance, the 59 stellations of the icosahedron are all unions
+//! of orbits of cells in its diagram.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use super::{symmetry::SymmetryGroup, Concrete, ConcretePolytope};
+use crate::{
+    abs::{
+        elements::{AbstractBuilder, ElementRef, SubelementList, Subelements},
+        rank::Rank,
+        AbstractError,
+    },
+    geometry::{Matrix, Point, PointSet, Subspace, Vector},
+    Consts, Float, Polytope,
+};
+
+use itertools::Itertools;
+use vec_like::VecLike;
+
+/// Represents an error while building a stellation diagram or a stellation.
+#[derive(Debug)]
+pub enum StellationError {
+    /// The polytope wasn't a polyhedron or a polychoron.
+    Rank(Rank),
+
+    /// The polytope didn't span its space, or its center lay on the hyperplane
+    /// of one of its facets.
+    Degenerate,
+
+    /// No cells were chosen.
+    Empty,
+
+    /// The chosen cells didn't form a valid polytope, as happens when two of
+    /// them only meet at a ridge or at a vertex.
+    Invalid(AbstractError),
+}
+
+impl std::fmt::Display for StellationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Rank(rank) => write!(
+                f,
+                "only polytopes of rank 3 or 4 can be stellated, found rank {}",
+                rank
+            ),
+            Self::Degenerate => write!(f, "the polytope is degenerate"),
+            Self::Empty => write!(f, "no cells were chosen"),
+            Self::Invalid(err) => write!(f, "the cells don't form a polytope: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for StellationError {}
+
+/// The result of building a stellation diagram or a stellation.
+pub type StellationResult<T> = Result<T, StellationError>;
+
+/// A hyperplane in a stellation diagram, made out of the points `x` such that
+/// `normal · x = pos`.
+#[derive(Clone, Debug)]
+struct Plane {
+    /// The unit normal of the hyperplane, which points away from the center of
+    /// the polytope.
+    normal: Vector,
+
+    /// The distance from the origin to the hyperplane.
+    pos: Float,
+}
+
+impl Plane {
+    /// Returns the signed distance from a point to the hyperplane, which is
+    /// positive on the side away from the center of the polytope.
+    fn distance(&self, p: &Point) -> Float {
+        self.normal.dot(p) - self.pos
+    }
+
+    /// Returns whether two hyperplanes are the same.
+    fn eq(&self, other: &Self) -> bool {
+        (self.pos - other.pos).abs() < Float::EPS
+            && (&self.normal - &other.normal).norm() < Float::EPS
+    }
+}
+
+/// Returns the point where some hyperplanes meet, or `None` if they don't
+/// meet at a single point.
+fn intersect(planes: &[&Plane]) -> Option<Point> {
+    let n = planes.len();
+    let matrix = Matrix::from_fn(n, n, |i, j| planes[i].normal[j]);
+    if matrix.determinant().abs() < Float::EPS {
+        return None;
+    }
+
+    let pos = Vector::from_iterator(n, planes.iter().map(|plane| plane.pos));
+    matrix.lu().solve(&pos)
+}
+
+/// A bounded cell of a stellation diagram.
+#[derive(Clone, Debug)]
+pub struct Cell {
+    /// Whether the cell lies on the far side of each hyperplane of the
+    /// diagram, as seen from the center of the polytope.
+    pub outside: Vec<bool>,
+
+    /// The indices of the vertices of the cell.
+    pub vertices: Vec<usize>,
+
+    /// The indices of the hyperplanes through the facets of the cell.
+    pub facets: Vec<usize>,
+}
+
+impl Cell {
+    /// Returns the number of hyperplanes that separate the cell from the
+    /// center of the polytope. The cell containing the center is the only one
+    /// in layer 0, and it's the polytope itself if it's convex.
+    pub fn layer(&self) -> usize {
+        self.outside.iter().filter(|&&outside| outside).count()
+    }
+}
+
+/// The stellation diagram of a polyhedron or polychoron, i.e. the arrangement
+/// of the hyperplanes through its facets, together with its bounded cells.
+#[derive(Clone, Debug)]
+pub struct Stellation {
+    /// The hyperplanes through the facets of the polytope, followed by those
+    /// of the bounding box.
+    planes: Vec<Plane>,
+
+    /// The number of hyperplanes through facets of the polytope.
+    facet_planes: usize,
+
+    /// The points where the hyperplanes meet.
+    vertices: Vec<Point>,
+
+    /// For every vertex, the side of every hyperplane it lies on: -1 for the
+    /// side of the center, 1 for the other side, and 0 on the hyperplane.
+    sides: Vec<Vec<i8>>,
+
+    /// The bounded cells of the diagram.
+    cells: Vec<Cell>,
+
+    /// The index of every bounded cell, by the sides of the hyperplanes it
+    /// lies on.
+    cell_index: HashMap<Vec<bool>, usize>,
+}
+
+impl Stellation {
+    /// Builds the stellation diagram of a polyhedron or a polychoron. Facets on
+    /// the same hyperplane are only counted once.
+    pub fn new(poly: &Concrete) -> StellationResult<Self> {
+        let rank = poly.rank();
+        let dim = match rank.try_usize() {
+            Some(dim) if dim == 3 || dim == 4 => dim,
+            _ => return Err(StellationError::Rank(rank)),
+        };
+        if poly.dim() != Some(dim) {
+            return Err(StellationError::Degenerate);
+        }
+        trace_span!(
+            "stellation diagram",
+            rank = dim,
+            facets = poly.facet_count()
+        );
+
+        // Finds the hyperplanes through the facets.
+        let center = poly.gravicenter().ok_or(StellationError::Degenerate)?;
+        let facet_rank = rank.minus_one();
+        let mut planes: Vec<Plane> = Vec::new();
+        for idx in 0..poly.facet_count() {
+            let vertices = poly
+                .element_vertices_ref(ElementRef::new(facet_rank, idx))
+                .ok_or(StellationError::Degenerate)?;
+            let subspace = Subspace::from_points(vertices.into_iter());
+            if !subspace.is_hyperplane() {
+                return Err(StellationError::Degenerate);
+            }
+
+            let normal = -subspace
+                .normal(&center)
+                .ok_or(StellationError::Degenerate)?;
+            let plane = Plane {
+                pos: normal.dot(&subspace.offset),
+                normal,
+            };
+            if !planes.iter().any(|other| other.eq(&plane)) {
+                planes.push(plane);
+            }
+        }
+
+        // Intersects the hyperplanes through the facets.
+        let facet_planes = planes.len();
+        let mut set = PointSet::new(Float::EPS);
+        for combination in (0..facet_planes).combinations(dim) {
+            let combination: Vec<_> = combination.iter().map(|&i| &planes[i]).collect();
+            if let Some(p) = intersect(&combination) {
+                set.insert(p);
+            }
+        }
+
+        // Adds a box that contains every one of these points, and intersects
+        // its facets with everything else.
+        let size = set.points().iter().map(|p| p.amax()).fold(1.0, Float::max) * 2.0;
+        for i in 0..dim {
+            for &sign in &[1.0, -1.0] {
+                let mut normal = Vector::zeros(dim);
+                normal[i] = sign;
+                planes.push(Plane { normal, pos: size });
+            }
+        }
+
+        for combination in (0..planes.len()).combinations(dim) {
+            if combination[dim - 1] >= facet_planes {
+                let combination: Vec<_> = combination.iter().map(|&i| &planes[i]).collect();
+                if let Some(p) = intersect(&combination) {
+                    set.insert(p);
+                }
+            }
+        }
+
+        let vertices = set.into_points();
+        let sides = vertices
+            .iter()
+            .map(|v| {
+                planes
+                    .iter()
+                    .map(|plane| {
+                        let distance = plane.distance(v);
+                        if distance > Float::EPS {
+                            1
+                        } else if distance < -Float::EPS {
+                            -1
+                        } else {
+                            0
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let mut stellation = Self {
+            planes,
+            facet_planes,
+            vertices,
+            sides,
+            cells: Vec::new(),
+            cell_index: HashMap::new(),
+        };
+        stellation.find_cells();
+
+        trace_event!(
+            vertices = stellation.vertices.len(),
+            cells = stellation.cells.len(),
+            "built stellation diagram"
+        );
+        Ok(stellation)
+    }
+
+    /// Returns the dimension of the diagram.
+    fn dim(&self) -> usize {
+        self.planes[0].normal.len()
+    }
+
+    /// Returns the vertices on the boundary of the cell that lies on the given
+    /// sides of the hyperplanes through the facets, and inside of the box.
+    fn cell_vertices(&self, outside: &[bool]) -> Vec<usize> {
+        (0..self.vertices.len())
+            .filter(|&v| {
+                self.sides[v].iter().enumerate().all(|(i, &side)| {
+                    let expected = if outside.get(i) == Some(&true) { 1 } else { -1 };
+                    side == 0 || side == expected
+                })
+            })
+            .collect()
+    }
+
+    /// Returns whether a set of vertices spans a subspace of a given rank.
+    fn spans(&self, vertices: &[usize], rank: usize) -> bool {
+        vertices.len() > rank
+            && Subspace::from_points(vertices.iter().map(|&v| &self.vertices[v])).rank() == rank
+    }
+
+    /// Returns the vertices in a set that lie on a hyperplane.
+    fn on_plane(&self, vertices: &[usize], plane: usize) -> Vec<usize> {
+        vertices
+            .iter()
+            .copied()
+            .filter(|&v| self.sides[v][plane] == 0)
+            .collect()
+    }
+
+    /// Walks through every cell of the diagram within the box, starting from
+    /// the one containing the center, and stores the bounded ones.
+    fn find_cells(&mut self) {
+        let dim = self.dim();
+        let core = vec![false; self.facet_planes];
+        let mut found = HashSet::new();
+        found.insert(core.clone());
+        let mut queue = VecDeque::new();
+        queue.push_back(core);
+
+        while let Some(outside) = queue.pop_front() {
+            let vertices = self.cell_vertices(&outside);
+            let facets: Vec<_> = (0..self.planes.len())
+                .filter(|&i| self.spans(&self.on_plane(&vertices, i), dim - 1))
+                .collect();
+
+            for &i in &facets {
+                if i < self.facet_planes {
+                    let mut neighbor = outside.clone();
+                    neighbor[i] = !neighbor[i];
+                    if found.insert(neighbor.clone()) {
+                        queue.push_back(neighbor);
+                    }
+                }
+            }
+
+            // Cells that reach the box are unbounded.
+            if facets.iter().all(|&i| i < self.facet_planes) {
+                self.cell_index.insert(outside.clone(), self.cells.len());
+                self.cells.push(Cell {
+                    outside,
+                    vertices,
+                    facets,
+                });
+            }
+        }
+    }
+
+    /// Returns the vertices of the diagram. These include the corners of the
+    /// bounding box and other points where it meets the hyperplanes, which
+    /// don't belong to any bounded cell.
+    pub fn vertices(&self) -> &[Point] {
+        &self.vertices
+    }
+
+    /// Returns the bounded cells of the diagram, in order of discovery. The
+    /// first one contains the center of the polytope.
+    pub fn cells(&self) -> &[Cell] {
+        &self.cells
+    }
+
+    /// Returns the number of bounded cells of the diagram.
+    pub fn cell_count(&self) -> usize {
+        self.cells.len()
+    }
+
+    /// Returns the indices of the cells in a given layer or below. Building
+    /// the polytope with these cells gives the successive stellations in
+    /// which each layer is added on top of the previous one.
+    pub fn up_to_layer(&self, layer: usize) -> Vec<usize> {
+        (0..self.cells.len())
+            .filter(|&idx| self.cells[idx].layer() <= layer)
+            .collect()
+    }
+
+    /// Sorts the cells into their orbits under a group of symmetries of the
+    /// polytope. Every orbit is sorted, and orbits are sorted by their first
+    /// cell. Choosing whole orbits gives the stellations with every symmetry
+    /// in the group.
+    pub fn cell_orbits(&self, group: &SymmetryGroup) -> Vec<Vec<usize>> {
+        // Every cell is determined by its centroid.
+        let centroids = self.cells.iter().map(|cell| {
+            cell.vertices
+                .iter()
+                .map(|&v| &self.vertices[v] - &group.center)
+                .sum::<Point>()
+                / cell.vertices.len() as Float
+        });
+        let (set, _) = PointSet::dedup(centroids, Float::EPS);
+
+        let mut orbit_of = vec![None; self.cells.len()];
+        let mut orbits = Vec::new();
+        for (idx, centroid) in set.points().iter().enumerate() {
+            if orbit_of[idx].is_some() {
+                continue;
+            }
+
+            let mut orbit: Vec<_> = group
+                .matrices
+                .iter()
+                .filter_map(|m| set.find(&(m * centroid)))
+                .collect();
+            orbit.sort_unstable();
+            orbit.dedup();
+            for &cell in &orbit {
+                orbit_of[cell] = Some(orbits.len());
+            }
+            orbits.push(orbit);
+        }
+
+        orbits
+    }
+
+    /// Builds the polytope made out of the union of some cells of the diagram.
+    /// Its facets are the pieces of the hyperplanes between the chosen cells
+    /// and the rest. Pieces on the same hyperplane aren't merged, see
+    /// [`Concrete::merge_coplanar_facets`] to do so.
+    ///
+    /// # Panics
+    /// This method will panic if any of the indices is out of bounds.
+    pub fn stellate(&self, cells: &[usize]) -> StellationResult<Concrete> {
+        if cells.is_empty() {
+            return Err(StellationError::Empty);
+        }
+
+        let mut chosen = vec![false; self.cells.len()];
+        for &idx in cells {
+            chosen[idx] = true;
+        }
+
+        // Finds the pieces of the facets.
+        let mut elements = Vec::new();
+        for (cell, _) in self.cells.iter().zip(&chosen).filter(|(_, &c)| c) {
+            for &i in &cell.facets {
+                let mut neighbor = cell.outside.clone();
+                neighbor[i] = !neighbor[i];
+
+                if !matches!(self.cell_index.get(&neighbor), Some(&idx) if chosen[idx]) {
+                    elements.push(self.on_plane(&cell.vertices, i));
+                }
+            }
+        }
+
+        // Finds the elements of each rank from the facets down, as the sets of
+        // their vertices.
+        let dim = self.dim();
+        let mut element_lists = Vec::with_capacity(dim);
+        for r in (1..dim).rev() {
+            let mut subelements = SubelementList::new();
+            let mut lower = Vec::new();
+            let mut indices = HashMap::new();
+
+            for el in &elements {
+                let mut subs = Subelements::new();
+                for i in 0..self.facet_planes {
+                    let face = self.on_plane(el, i);
+                    if face.len() == el.len() || !self.spans(&face, r - 1) {
+                        continue;
+                    }
+
+                    let len = lower.len();
+                    let idx = *indices.entry(face.clone()).or_insert(len);
+                    if idx == len {
+                        lower.push(face);
+                    }
+                    if !subs.contains(&idx) {
+                        subs.push(idx);
+                    }
+                }
+
+                subelements.push(subs);
+            }
+
+            element_lists.push(subelements);
+            elements = lower;
+        }
+
+        let vertices: Vec<_> = elements
+            .into_iter()
+            .map(|v| self.vertices[v[0]].clone())
+            .collect();
+
+        let mut abs = AbstractBuilder::with_capacity(Rank::from(dim));
+        abs.push_min();
+        abs.push_vertices(vertices.len());
+        for subelements in element_lists.into_iter().rev() {
+            abs.push(subelements);
+        }
+        abs.push_max();
+
+        let poly = Concrete::new(vertices, abs.build());
+        poly.abs.validate().map_err(StellationError::Invalid)?;
+        Ok(poly)
+    }
+}
+
+impl Concrete {
+    /// Builds the stellation diagram of a polyhedron or a polychoron. See
+    /// [`Stellation`] for more details.
+    pub fn stellation_diagram(&self) -> StellationResult<Stellation> {
+        Stellation::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conc::convex::convex_hull;
+
+    /// The golden ratio.
+    const PHI: Float = 1.618_033_988_749_895;
+
+    /// Returns the points with the given coordinates up to cyclic permutations
+    /// and changes of sign.
+    fn cyclic(coords: [Float; 3]) -> Vec<Point> {
+        let mut points = Vec::new();
+        for shift in 0..3 {
+            for signs in 0..8 {
+                let p: Vec<_> = (0..3)
+                    .map(|i| {
+                        let x = coords[(i + shift) % 3];
+                        if signs & (1 << i) == 0 {
+                            x
+                        } else {
+                            -x
+                        }
+                    })
+                    .collect();
+                points.push(Point::from(p));
+            }
+        }
+
+        PointSet::dedup(points, Float::EPS).0.into_points()
+    }
+
+    /// Checks the number of elements of a stellation, and that it's a closed
+    /// surface.
+    fn test(poly: &Concrete, el_counts: &[usize]) {
+        let counts: Vec<_> = (0..el_counts.len())
+            .map(|r| poly.el_count(Rank::from(r)))
+            .collect();
+        assert_eq!(counts, el_counts);
+        let euler: isize = el_counts
+            .iter()
+            .enumerate()
+            .map(|(r, &count)| {
+                if r % 2 == 0 {
+                    count as isize
+                } else {
+                    -(count as isize)
+                }
+            })
+            .sum();
+        assert_eq!(euler, if el_counts.len() == 3 { 2 } else { 0 });
+    }
+
+    #[test]
+    fn cube() {
+        // Opposite faces of a cube are parallel, so it has no stellations.
+        let cube = Concrete::hypercube(Rank::new(3));
+        let diagram = cube.stellation_diagram().unwrap();
+        assert_eq!(diagram.cell_count(), 1);
+        test(&diagram.stellate(&[0]).unwrap(), &[8, 12, 6]);
+    }
+
+    #[test]
+    fn octahedron() {
+        let octahedron = Concrete::orthoplex(Rank::new(3));
+        let diagram = octahedron.stellation_diagram().unwrap();
+        assert_eq!(diagram.cell_count(), 9);
+        assert_eq!(diagram.up_to_layer(1).len(), 9);
+
+        // The stella octangula, as a solid.
+        let cells: Vec<_> = (0..9).collect();
+        test(&diagram.stellate(&cells).unwrap(), &[14, 36, 24]);
+
+        // A single spike is a tetrahedron, but the spikes on their own only
+        // meet at the edges of the octahedron.
+        test(&diagram.stellate(&[1]).unwrap(), &[4, 6, 4]);
+        let spikes: Vec<_> = (1..9).collect();
+        assert!(matches!(
+            diagram.stellate(&spikes),
+            Err(StellationError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn dodecahedron() {
+        let mut points = cyclic([1.0, 1.0, 1.0]);
+        points.extend(cyclic([0.0, 1.0 / PHI, PHI]));
+        let dodecahedron = convex_hull(points);
+        let diagram = dodecahedron.stellation_diagram().unwrap();
+
+        let mut layers = vec![0; 4];
+        for cell in diagram.cells() {
+            layers[cell.layer()] += 1;
+        }
+        assert_eq!(layers, [1, 12, 30, 20]);
+
+        // The small stellated and the great stellated dodecahedra, as solids.
+        test(
+            &diagram.stellate(&diagram.up_to_layer(1)).unwrap(),
+            &[32, 90, 60],
+        );
+        test(
+            &diagram.stellate(&diagram.up_to_layer(3)).unwrap(),
+            &[32, 90, 60],
+        );
+
+        let group = dodecahedron.symmetries().unwrap();
+        assert_eq!(diagram.cell_orbits(&group).len(), 4);
+    }
+
+    #[test]
+    fn icosahedron() {
+        let icosahedron = convex_hull(cyclic([0.0, 1.0, PHI]));
+        let diagram = icosahedron.stellation_diagram().unwrap();
+
+        // These are the cells A through H, e₁, f₁, and g₁ in the notation of
+        // The Fifty-Nine Icosahedra.
+        let group = icosahedron.symmetries().unwrap();
+        let orbits = diagram.cell_orbits(&group);
+        let sizes: Vec<_> = orbits.iter().map(Vec::len).collect();
+        assert_eq!(sizes, [1, 20, 30, 60, 60, 20, 12, 120, 60, 30, 60]);
+        assert_eq!(diagram.cell_count(), 473);
+
+        // The first stellation, and the great icosahedron, which is the union
+        // of every cell. Only 9 triangles of each face of the latter are seen
+        // from outside.
+        test(
+            &diagram.stellate(&diagram.up_to_layer(1)).unwrap(),
+            &[32, 90, 60],
+        );
+        let cells: Vec<_> = (0..diagram.cell_count()).collect();
+        test(&diagram.stellate(&cells).unwrap(), &[92, 270, 180]);
+    }
+
+    #[test]
+    fn orthoplex() {
+        let orthoplex = Concrete::orthoplex(Rank::new(4));
+        let diagram = orthoplex.stellation_diagram().unwrap();
+        assert_eq!(diagram.up_to_layer(1).len(), 17);
+
+        // A pyramid gets placed on every tetrahedron.
+        let stellation = diagram.stellate(&diagram.up_to_layer(1)).unwrap();
+        test(&stellation, &[24, 88, 128, 64]);
+    }
+
+    #[test]
+    fn errors() {
+        assert!(matches!(
+            Concrete::polygon(5).stellation_diagram(),
+            Err(StellationError::Rank(_))
+        ));
+
+        let diagram = Concrete::simplex(Rank::new(3))
+            .stellation_diagram()
+            .unwrap();
+        assert_eq!(diagram.cell_count(), 1);
+        assert!(matches!(diagram.stellate(&[]), Err(StellationError::Empty)));
+    }
+}
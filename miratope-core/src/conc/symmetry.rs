@@ -0,0 +1,382 @@
+//! Computes the [symmetry group](https://polytope.miraheze.org/wiki/Symmetry)
+//! of a concrete polytope, i.e. the group of isometries that map it to itself.
+//!
+//! Every such isometry fixes the gravicenter and permutes the vertices. If the
+//! vertices span the whole space, it's then determined by the images of a few
+//! vertices that form a basis, so we try every assignment of these that
+//! preserves distances and the local invariants of each vertex, and keep those
+//! that extend to a symmetry of the whole polytope.
+
+use std::collections::HashSet;
+
+use super::{Concrete, ConcretePolytope};
+use crate::{
+    abs::{elements::ElementRef, rank::Rank},
+    geometry::{Matrix, Point, Subspace},
+    group::Group,
+    Consts, Float, Polytope,
+};
+
+use vec_like::VecLike;
+
+/// The symmetry group of a concrete polytope, stored both as orthogonal
+/// matrices about its gravicenter and as the permutations they induce on the
+/// vertices.
+#[derive(Clone, Debug)]
+pub struct SymmetryGroup {
+    /// The gravicenter of the polytope, which every symmetry fixes.
+    pub center: Point,
+
+    /// The symmetries as orthogonal matrices, acting on the vertices once the
+    /// gravicenter is moved to the origin. The first one is always the
+    /// identity.
+    pub matrices: Vec<Matrix>,
+
+    /// The permutations of the vertices, where the symmetry with index `i`
+    /// sends vertex `j` to vertex `vertex_perms[i][j]`.
+    pub vertex_perms: Vec<Vec<usize>>,
+}
+
+impl SymmetryGroup {
+    /// Returns the number of symmetries.
+    pub fn order(&self) -> usize {
+        self.matrices.len()
+    }
+
+    /// Returns the number of symmetries that preserve orientation.
+    pub fn rotation_order(&self) -> usize {
+        self.matrices
+            .iter()
+            .filter(|m| m.determinant() > 0.0)
+            .count()
+    }
+
+    /// Returns whether every vertex can be sent to every other vertex by a
+    /// symmetry.
+    pub fn is_vertex_transitive(&self) -> bool {
+        self.vertex_perms
+            .iter()
+            .map(|perm| perm.first())
+            .collect::<HashSet<_>>()
+            .len()
+            == self.vertex_perms[0].len()
+    }
+
+    /// Converts the symmetries into a matrix [`Group`].
+    pub fn into_group(self) -> Group {
+        let dim = self.center.len();
+        Group::new(dim, self.matrices.into_iter())
+    }
+}
+
+/// The local invariants of a vertex, which any symmetry must preserve.
+struct VertexInvariant {
+    /// The squared distance to the gravicenter.
+    norm_sq: Float,
+
+    /// The lengths of the edges through the vertex, sorted.
+    edge_lengths: Vec<Float>,
+}
+
+impl VertexInvariant {
+    /// Returns whether two vertices could be sent to one another.
+    fn matches(&self, other: &Self) -> bool {
+        (self.norm_sq - other.norm_sq).abs() < Float::EPS
+            && self.edge_lengths.len() == other.edge_lengths.len()
+            && self
+                .edge_lengths
+                .iter()
+                .zip(&other.edge_lengths)
+                .all(|(x, y)| (x - y).abs() < Float::EPS)
+    }
+}
+
+/// The data shared by the backtracking search for symmetries.
+struct SymmetrySearch {
+    /// The vertices, with the gravicenter moved to the origin.
+    vertices: Vec<Point>,
+
+    /// The indices of the vertices that form a basis of the space.
+    basis: Vec<usize>,
+
+    /// For every vertex, the vertices it could be sent to.
+    candidates: Vec<Vec<usize>>,
+
+    /// The sorted vertex sets of the elements of every rank from the edges to
+    /// the facets, which every symmetry must permute.
+    elements: Vec<Vec<Vec<usize>>>,
+
+    /// The same vertex sets as [`Self::elements`], for quick lookup.
+    element_sets: Vec<HashSet<Vec<usize>>>,
+}
+
+impl SymmetrySearch {
+    /// Returns the vertex at a given position, if any.
+    fn vertex_at(&self, p: &Point, candidates: &[usize]) -> Option<usize> {
+        candidates
+            .iter()
+            .copied()
+            .find(|&idx| (&self.vertices[idx] - p).norm() < Float::EPS)
+    }
+
+    /// Attempts to extend the images of the basis vertices into a symmetry of
+    /// the polytope.
+    fn extend(&self, images: &[usize]) -> Option<(Matrix, Vec<usize>)> {
+        let dim = self.basis.len();
+        if dim == 0 {
+            return Some((Matrix::zeros(0, 0), vec![0; self.vertices.len()]));
+        }
+
+        let from = Matrix::from_columns(
+            &self
+                .basis
+                .iter()
+                .map(|&idx| self.vertices[idx].clone())
+                .collect::<Vec<_>>(),
+        );
+        let to = Matrix::from_columns(
+            &images
+                .iter()
+                .map(|&idx| self.vertices[idx].clone())
+                .collect::<Vec<_>>(),
+        );
+        let matrix = to * from.try_inverse()?;
+        if (matrix.transpose() * &matrix - Matrix::identity(dim, dim)).norm() > Float::EPS.sqrt() {
+            return None;
+        }
+
+        // The images of the vertices must be vertices.
+        let mut perm = Vec::with_capacity(self.vertices.len());
+        let mut used = vec![false; self.vertices.len()];
+        for (idx, v) in self.vertices.iter().enumerate() {
+            let image = self.vertex_at(&(&matrix * v), &self.candidates[idx])?;
+            if used[image] {
+                return None;
+            }
+            used[image] = true;
+            perm.push(image);
+        }
+
+        // The images of the elements must be elements.
+        for (elements, set) in self.elements.iter().zip(&self.element_sets) {
+            for el in elements {
+                let mut vertices: Vec<_> = el.iter().map(|&v| perm[v]).collect();
+                vertices.sort_unstable();
+
+                if !set.contains(&vertices) {
+                    return None;
+                }
+            }
+        }
+
+        Some((matrix, perm))
+    }
+
+    /// Assigns images to the basis vertices from a given one onwards, keeping
+    /// their inner products, and collects every symmetry found.
+    fn search(&self, images: &mut Vec<usize>, found: &mut Vec<(Matrix, Vec<usize>)>) {
+        let depth = images.len();
+        if depth == self.basis.len() {
+            if let Some(symmetry) = self.extend(images) {
+                found.push(symmetry);
+            }
+            return;
+        }
+
+        let v = &self.vertices[self.basis[depth]];
+        for &image in &self.candidates[self.basis[depth]] {
+            let w = &self.vertices[image];
+            let consistent = self.basis[..depth]
+                .iter()
+                .zip(images.iter())
+                .all(|(&b, &i)| {
+                    (v.dot(&self.vertices[b]) - w.dot(&self.vertices[i])).abs() < Float::EPS
+                });
+
+            if consistent {
+                images.push(image);
+                self.search(images, found);
+                images.pop();
+            }
+        }
+    }
+}
+
+impl Concrete {
+    /// Computes the symmetries of the polytope, i.e. the isometries that send
+    /// it to itself. Returns `None` if the polytope has no vertices, or if
+    /// its vertices don't span the whole space, as then the symmetry group is
+    /// infinite. Consider calling [`ConcretePolytope::flatten`] beforehand.
+    ///
+    /// Only the vertex sets of the elements are checked, so a symmetry may
+    /// swap elements with the same vertices, as happens in some compounds.
+    pub fn symmetries(&self) -> Option<SymmetryGroup> {
+        let center = self.gravicenter()?;
+        let vertices: Vec<_> = self.vertices.iter().map(|v| v - &center).collect();
+        let dim = center.len();
+        trace_span!("symmetries", vertices = vertices.len(), dim);
+
+        // Finds the basis vertices.
+        let mut subspace = Subspace::new(Point::zeros(dim));
+        let mut basis = Vec::with_capacity(dim);
+        for (idx, v) in vertices.iter().enumerate() {
+            if subspace.add(v).is_some() {
+                basis.push(idx);
+            }
+        }
+        if basis.len() != dim {
+            return None;
+        }
+
+        // Finds the vertices each vertex could be sent to.
+        let mut edge_lengths = vec![Vec::new(); vertices.len()];
+        for (v, w, len) in self.edge_lengths_with_endpoints() {
+            edge_lengths[v].push(len);
+            edge_lengths[w].push(len);
+        }
+        let invariants: Vec<_> = vertices
+            .iter()
+            .zip(edge_lengths)
+            .map(|(v, mut edge_lengths)| {
+                edge_lengths.sort_unstable_by(|x, y| x.partial_cmp(y).unwrap());
+                VertexInvariant {
+                    norm_sq: v.norm_squared(),
+                    edge_lengths,
+                }
+            })
+            .collect();
+        let candidates = invariants
+            .iter()
+            .map(|inv| {
+                (0..invariants.len())
+                    .filter(|&idx| inv.matches(&invariants[idx]))
+                    .collect()
+            })
+            .collect();
+
+        let rank = self.rank();
+        let elements: Vec<Vec<_>> = (1..rank.try_usize().unwrap_or(0))
+            .map(|r| {
+                (0..self.el_count(Rank::from(r)))
+                    .map(|idx| {
+                        let mut vertices = self
+                            .abs
+                            .element_vertices(ElementRef::new(Rank::from(r), idx))
+                            .unwrap();
+                        vertices.sort_unstable();
+                        vertices
+                    })
+                    .collect()
+            })
+            .collect();
+        let element_sets = elements
+            .iter()
+            .map(|els| els.iter().cloned().collect())
+            .collect();
+
+        let search = SymmetrySearch {
+            vertices,
+            basis,
+            candidates,
+            elements,
+            element_sets,
+        };
+        let mut found = Vec::new();
+        search.search(&mut Vec::with_capacity(dim), &mut found);
+        trace_event!(order = found.len(), "found symmetries");
+
+        // Puts the identity first.
+        let identity = found
+            .iter()
+            .position(|(_, perm)| perm.iter().enumerate().all(|(i, &j)| i == j))?;
+        found.swap(0, identity);
+
+        let (matrices, vertex_perms) = found.into_iter().unzip();
+        Some(SymmetryGroup {
+            center,
+            matrices,
+            vertex_perms,
+        })
+    }
+
+    /// Computes the symmetry group of the polytope as a matrix group acting on
+    /// the space with its gravicenter at the origin. Returns `None` in the
+    /// same cases as [`Self::symmetries`].
+    pub fn symmetry_group(&self) -> Option<Group> {
+        Some(self.symmetries()?.into_group())
+    }
+
+    /// Returns the endpoints and lengths of every edge.
+    fn edge_lengths_with_endpoints(&self) -> Vec<(usize, usize, Float)> {
+        self.ranks()
+            .get(Rank::new(1))
+            .map(|edges| {
+                edges
+                    .iter()
+                    .map(|edge| {
+                        let (v, w) = (edge.subs[0], edge.subs[1]);
+                        (v, w, (&self.vertices[v] - &self.vertices[w]).norm())
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Checks the order of the symmetry group of a polytope.
+    fn test(poly: &Concrete, order: usize) -> SymmetryGroup {
+        let group = poly.symmetries().unwrap();
+        assert_eq!(group.order(), order);
+        assert!(group.vertex_perms[0]
+            .iter()
+            .enumerate()
+            .all(|(i, &j)| i == j));
+        group
+    }
+
+    #[test]
+    fn polygons() {
+        for n in 3..8 {
+            test(&Concrete::polygon(n), 2 * n);
+        }
+
+        // A rectangle only has the symmetries of a dyad squared.
+        let mut rectangle =
+            Concrete::duoprism(&Concrete::dyad_with(1.0), &Concrete::dyad_with(2.0));
+        assert_eq!(test(&rectangle, 4).rotation_order(), 2);
+
+        // Flattening a polygon in 3D allows us to find its symmetries.
+        rectangle.vertices.iter_mut().for_each(|v| {
+            *v = Point::from(vec![v[0], v[1], 0.0]);
+        });
+        assert!(rectangle.symmetries().is_none());
+        rectangle.flatten();
+        test(&rectangle, 4);
+    }
+
+    #[test]
+    fn polyhedra() {
+        let cube = Concrete::hypercube(Rank::new(3));
+        let group = test(&cube, 48);
+        assert_eq!(group.rotation_order(), 24);
+        assert!(group.is_vertex_transitive());
+
+        test(&Concrete::simplex(Rank::new(3)), 24);
+        test(&Concrete::orthoplex(Rank::new(3)), 48);
+        test(&Concrete::polygon(5).prism_with(1.0), 20);
+
+        // The square pyramid isn't vertex-transitive.
+        let pyramid = Concrete::polygon(4).pyramid();
+        assert!(!test(&pyramid, 8).is_vertex_transitive());
+    }
+
+    #[test]
+    fn tesseract() {
+        let group = test(&Concrete::hypercube(Rank::new(4)), 384);
+        assert_eq!(group.into_group().order(), 384);
+    }
+}
@@ -0,0 +1,494 @@
+//! Generates patches of the uniform tilings of the Euclidean and hyperbolic
+//! planes from their vertex configurations.
+//!
+//! Every tiling is built through the Wythoff construction on the triangle
+//! group with orbifold symbol `*pq2`, whose Euler characteristic tells apart
+//! the Euclidean tilings from the hyperbolic ones. Points are stored in
+//! homogeneous coordinates, on the plane z = 1 in the Euclidean case and on
+//! the hyperboloid x² + y² − z² = −1 in the hyperbolic case. Hyperbolic
+//! tilings are then drawn in the Beltrami–Klein model, where geodesics are
+//! straight lines, so that every tile is an actual polygon.
+
+use std::fmt::Display;
+
+use super::Concrete;
+use crate::{
+    abs::Abstract,
+    geometry::{Matrix, Point, PointSet},
+    group::orbifold::Orbifold,
+    Consts, Float, Polytope,
+};
+
+/// Represents an error while generating a tiling.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TilingError {
+    /// The vertex configuration couldn't be parsed.
+    ParseError {
+        /// The position at which the reader found the error.
+        pos: usize,
+    },
+
+    /// The vertex configuration describes a polyhedron rather than a tiling.
+    Spherical,
+
+    /// The vertex configuration doesn't come from the Wythoff construction on
+    /// a group `*pq2`. This includes the snub tilings and the tilings that
+    /// aren't uniform.
+    NotWythoffian,
+}
+
+impl Display for TilingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Self::ParseError { pos } => write!(f, "parsing failed at position {}", pos),
+            Self::Spherical => write!(f, "vertex configuration describes a polyhedron"),
+            Self::NotWythoffian => write!(f, "vertex configuration isn't Wythoffian"),
+        }
+    }
+}
+
+impl std::error::Error for TilingError {}
+
+/// The result of generating a tiling.
+pub type TilingResult<T> = Result<T, TilingError>;
+
+/// The geometry of the plane a tiling lives in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Geometry {
+    /// The Euclidean plane.
+    Euclidean,
+
+    /// The hyperbolic plane.
+    Hyperbolic,
+}
+
+/// The vertex configuration of a uniform tiling, listing the number of sides
+/// of the polygons around each vertex in cyclic order.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct VertexConfig(pub Vec<u32>);
+
+impl Display for VertexConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, sides) in self.0.iter().enumerate() {
+            if i != 0 {
+                write!(f, ".")?;
+            }
+            write!(f, "{}", sides)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl VertexConfig {
+    /// Parses a vertex configuration such as `4.8.8`, `(3,4,6,4)` or
+    /// `(7^3)`. Entries may be separated by dots or commas, and repeated
+    /// entries may be written with a caret. Whitespace is ignored.
+    pub fn parse(input: &str) -> TilingResult<Self> {
+        let mut config = Vec::new();
+
+        // Each entry, with the position of its first character.
+        let mut entries = Vec::new();
+        let mut entry = (0, String::new());
+        for (pos, c) in input.char_indices() {
+            match c {
+                c if c.is_whitespace() => {}
+                '(' if pos == 0 => {}
+                ')' if pos + 1 == input.len() => {}
+                '.' | ',' => {
+                    entries.push(std::mem::replace(&mut entry, (pos + 1, String::new())));
+                }
+                c => {
+                    if entry.1.is_empty() {
+                        entry.0 = pos;
+                    }
+                    entry.1.push(c);
+                }
+            }
+        }
+        entries.push(entry);
+
+        for (pos, entry) in entries {
+            let err = TilingError::ParseError { pos };
+            let (sides, count) = match entry.split_once('^') {
+                Some((sides, count)) => (sides, count.parse().map_err(|_| err)?),
+                None => (entry.as_str(), 1),
+            };
+            let sides: u32 = sides.parse().map_err(|_| err)?;
+            if sides < 3 || count == 0 {
+                return Err(err);
+            }
+
+            config.resize(config.len() + count, sides);
+        }
+
+        Ok(Self(config))
+    }
+
+    /// Returns the rotation or reflection of the configuration that comes
+    /// first lexicographically, which is how configurations are usually
+    /// written down, as in `3.4.4.4` or `4.6.8`.
+    pub fn canonical(&self) -> Self {
+        let config = &self.0;
+        let n = config.len();
+
+        (0..n)
+            .flat_map(|start| {
+                [false, true].iter().map(move |&reversed| {
+                    (0..n)
+                        .map(|i| {
+                            config[if reversed {
+                                (start + n - i) % n
+                            } else {
+                                (start + i) % n
+                            }]
+                        })
+                        .collect::<Vec<_>>()
+                })
+            })
+            .min()
+            .map_or_else(|| self.clone(), Self)
+    }
+
+    /// Finds the Schläfli symbol {p, q} and the ringed nodes of the Coxeter
+    /// diagram `o-p-o-q-o` that give the tiling through the Wythoff
+    /// construction.
+    fn wythoff(&self) -> TilingResult<(u32, u32, [bool; 3])> {
+        let config = &self.0;
+        let n = config.len();
+        if n >= 3 && config.iter().all(|&x| x == config[0]) {
+            return Ok((config[0], n as u32, [true, false, false]));
+        }
+
+        // Tries every rotation and reflection of the configuration.
+        for start in 0..n {
+            for reversed in [false, true] {
+                let seq: Vec<_> = (0..n)
+                    .map(|i| {
+                        config[if reversed {
+                            (start + n - i) % n
+                        } else {
+                            (start + i) % n
+                        }]
+                    })
+                    .collect();
+
+                let wythoff = match *seq.as_slice() {
+                    [p, q, p2, q2] if p == p2 && q == q2 => (p, q, [false, true, false]),
+                    [q, a, b] if a == b && a % 2 == 0 => (a / 2, q, [true, true, false]),
+                    [p, 4, q, 4] => (p, q, [true, false, true]),
+                    [4, a, b] if a % 2 == 0 && b % 2 == 0 => (a / 2, b / 2, [true, true, true]),
+                    _ => continue,
+                };
+
+                if wythoff.0 >= 2 && wythoff.1 >= 2 {
+                    return Ok(wythoff);
+                }
+            }
+        }
+
+        Err(TilingError::NotWythoffian)
+    }
+
+    /// Returns the orbifold symbol `*pq2` of the symmetry group of the
+    /// tiling.
+    pub fn symmetry(&self) -> TilingResult<Orbifold> {
+        let (p, q, _) = self.wythoff()?;
+        Ok(Orbifold {
+            gyrations: Vec::new(),
+            corners: Some(vec![p, q, 2]),
+            crosses: 0,
+        })
+    }
+
+    /// Returns the geometry of the plane the tiling lives in, or an error if
+    /// the configuration describes a polyhedron instead.
+    pub fn geometry(&self) -> TilingResult<Geometry> {
+        let euler = self.symmetry()?.euler_characteristic();
+
+        if euler > Float::EPS {
+            Err(TilingError::Spherical)
+        } else if euler > -Float::EPS {
+            Ok(Geometry::Euclidean)
+        } else {
+            Ok(Geometry::Hyperbolic)
+        }
+    }
+}
+
+/// The triangle group `*pq2` acting on either plane. The mirrors are ordered
+/// as the nodes of the Coxeter diagram `o-p-o-q-o`, so that the first two
+/// meet at the origin and the first and last are perpendicular.
+struct Triangle {
+    /// The geometry of the plane.
+    geometry: Geometry,
+
+    /// The linear functionals whose values on a point give (the hyperbolic
+    /// sines of) its distances to the mirrors, as rows.
+    functionals: Matrix,
+
+    /// The reflections on each mirror.
+    mirrors: [Matrix; 3],
+}
+
+impl Triangle {
+    /// Builds the triangle group `*pq2`. In the Euclidean case, the right
+    /// angled vertex of the fundamental triangle is at a given distance from
+    /// the origin.
+    fn new(p: u32, q: u32, geometry: Geometry, dist: Float) -> Self {
+        let (sin, cos) = (Float::PI / p as Float).sin_cos();
+        let a = (Float::PI / q as Float).cos() / sin;
+
+        // The last mirror is perpendicular to the x-axis.
+        let (functional, direction) = match geometry {
+            Geometry::Euclidean => ([-1.0, 0.0, dist], [-1.0, 0.0, 0.0]),
+            Geometry::Hyperbolic => {
+                let c = (a * a - 1.0).sqrt();
+                ([-a, 0.0, c], [-a, 0.0, -c])
+            }
+        };
+
+        let functionals = [[0.0, 1.0, 0.0], [sin, -cos, 0.0], functional];
+        let directions = [[0.0, 1.0, 0.0], [sin, -cos, 0.0], direction];
+
+        let reflection = |i: usize| {
+            Matrix::identity(3, 3)
+                - Matrix::from_fn(3, 3, |row, col| {
+                    2.0 * directions[i][row] * functionals[i][col]
+                })
+        };
+
+        Self {
+            geometry,
+            functionals: Matrix::from_fn(3, 3, |row, col| functionals[row][col]),
+            mirrors: [reflection(0), reflection(1), reflection(2)],
+        }
+    }
+
+    /// Returns the point in the fundamental triangle whose distances to the
+    /// mirrors are proportional to the given values.
+    fn point(&self, dists: [Float; 3]) -> Point {
+        let p = self
+            .functionals
+            .clone()
+            .lu()
+            .solve(&Point::from_column_slice(&dists))
+            .expect("the mirrors of a triangle group are independent");
+
+        match self.geometry {
+            Geometry::Euclidean => &p / p[2],
+            Geometry::Hyperbolic => {
+                let norm = (p[2] * p[2] - p[0] * p[0] - p[1] * p[1]).sqrt();
+                &p / norm.copysign(p[2])
+            }
+        }
+    }
+
+    /// Returns the distance between two points.
+    fn distance(&self, p: &Point, q: &Point) -> Float {
+        match self.geometry {
+            Geometry::Euclidean => (p - q).norm(),
+            Geometry::Hyperbolic => {
+                let dot = p[2] * q[2] - p[0] * q[0] - p[1] * q[1];
+                dot.max(1.0).acosh()
+            }
+        }
+    }
+}
+
+impl Concrete {
+    /// Generates the tiles of the uniform tiling with a given vertex
+    /// configuration whose centers lie within a given distance from the
+    /// origin. The Euclidean tilings have unit edges, while the hyperbolic
+    /// ones are drawn in the Beltrami–Klein model, with distances measured in
+    /// the hyperbolic plane.
+    ///
+    /// Each tile is returned with the index of its type, which is 0 for the
+    /// tiles centered at the origin, 1 for those centered at the vertices of
+    /// {p, q}, and 2 for those centered at its edges.
+    pub fn uniform_tiling(config: &str, radius: Float) -> TilingResult<Vec<(Self, usize)>> {
+        let config = VertexConfig::parse(config)?;
+        let geometry = config.geometry()?;
+        let (p, q, ringed) = config.wythoff()?;
+        let dists = ringed.map(|r| if r { 1.0 } else { 0.0 });
+
+        // Scales the Euclidean tilings so that their edges have unit length.
+        let mut triangle = Triangle::new(p, q, geometry, 1.0);
+        if geometry == Geometry::Euclidean {
+            let seed = triangle.point(dists);
+            let idx = ringed.iter().position(|&r| r).unwrap();
+            let edge = triangle.distance(&seed, &(&triangle.mirrors[idx] * &seed));
+            triangle = Triangle::new(p, q, geometry, 1.0 / edge);
+        }
+
+        let seed = triangle.point(dists);
+        let origin = triangle.point([0.0, 0.0, 1.0]);
+        let incenter = triangle.point([1.0, 1.0, 1.0]);
+
+        // Every tile centered within the radius contains a fundamental
+        // triangle whose incenter is within this distance from the origin.
+        let margin = (0..3)
+            .map(|k| {
+                let mut dists = [0.0; 3];
+                dists[k] = 1.0;
+                triangle.distance(&incenter, &triangle.point(dists))
+            })
+            .fold(0.0, Float::max);
+
+        // The isometries mapping the fundamental triangle near the origin.
+        let mut found = PointSet::new(Float::EPS.sqrt());
+        found.insert(incenter.clone());
+        let mut isometries = vec![Matrix::identity(3, 3)];
+        let mut idx = 0;
+        while idx < isometries.len() {
+            for mirror in &triangle.mirrors {
+                let isometry = &isometries[idx] * mirror;
+                let image = &isometry * &incenter;
+                if triangle.distance(&origin, &image) <= radius + margin && found.insert(image).1 {
+                    isometries.push(isometry);
+                }
+            }
+
+            idx += 1;
+        }
+
+        // The tiles around the fundamental triangle, with their centers.
+        let mut tiles = Vec::new();
+        for (label, &(i, j)) in [(0, 1), (1, 2), (0, 2)].iter().enumerate() {
+            let mut vertices: Vec<Point> = Vec::new();
+            let mut isometry = Matrix::identity(3, 3);
+            let m = [p, q, 2][label] as usize;
+
+            for k in 0..2 * m {
+                let vertex = &isometry * &seed;
+                match vertices.last() {
+                    Some(v) if (v - &vertex).norm() < Float::EPS.sqrt() => {}
+                    _ => vertices.push(vertex),
+                }
+                isometry *= &triangle.mirrors[if k % 2 == 0 { i } else { j }];
+            }
+            if vertices.len() > 1
+                && (&vertices[0] - vertices.last().unwrap()).norm() < Float::EPS.sqrt()
+            {
+                vertices.pop();
+            }
+
+            if vertices.len() >= 3 {
+                let mut dists = [1.0; 3];
+                dists[i] = 0.0;
+                dists[j] = 0.0;
+                tiles.push((label, vertices, triangle.point(dists)));
+            }
+        }
+
+        // Places a copy of each tile for every isometry.
+        let mut centers = PointSet::new(Float::EPS.sqrt());
+        let mut patch = Vec::new();
+        for isometry in &isometries {
+            for (label, vertices, center) in &tiles {
+                let center = isometry * center;
+                if triangle.distance(&origin, &center) > radius || !centers.insert(center).1 {
+                    continue;
+                }
+
+                let vertices = vertices
+                    .iter()
+                    .map(|v| {
+                        let v = isometry * v;
+                        Point::from_column_slice(&[v[0] / v[2], v[1] / v[2]])
+                    })
+                    .collect::<Vec<_>>();
+                let n = vertices.len();
+                patch.push((Self::new(vertices, Abstract::polygon(n)), *label));
+            }
+        }
+
+        Ok(patch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the number of tiles of each type in a patch.
+    fn counts(patch: &[(Concrete, usize)]) -> [usize; 3] {
+        let mut counts = [0; 3];
+        for (_, label) in patch {
+            counts[*label] += 1;
+        }
+        counts
+    }
+
+    #[test]
+    fn parse() {
+        let config = VertexConfig::parse("(3^4,6)").unwrap();
+        assert_eq!(config.0, vec![3, 3, 3, 3, 6]);
+        assert_eq!(VertexConfig::parse("4.8.8").unwrap().0, vec![4, 8, 8]);
+        assert_eq!(
+            VertexConfig::parse("4.a.8"),
+            Err(TilingError::ParseError { pos: 2 })
+        );
+        assert_eq!(
+            VertexConfig::parse("4.2.8"),
+            Err(TilingError::ParseError { pos: 2 })
+        );
+    }
+
+    #[test]
+    fn geometry() {
+        let geometry = |input| VertexConfig::parse(input).unwrap().geometry();
+        assert_eq!(geometry("4.8.8"), Ok(Geometry::Euclidean));
+        assert_eq!(geometry("3.4.6.4"), Ok(Geometry::Euclidean));
+        assert_eq!(geometry("(7^3)"), Ok(Geometry::Hyperbolic));
+        assert_eq!(geometry("4.6.14"), Ok(Geometry::Hyperbolic));
+        assert_eq!(geometry("3.8.8"), Err(TilingError::Spherical));
+        assert_eq!(geometry("4.6.8"), Err(TilingError::Spherical));
+        assert_eq!(geometry("3^4.6"), Err(TilingError::NotWythoffian));
+    }
+
+    #[test]
+    fn euclidean() {
+        // The truncated square tiling has as many octagons as squares.
+        let patch = Concrete::uniform_tiling("4.8.8", 10.0).unwrap();
+        let [octagons, squares, edges] = counts(&patch);
+        assert_eq!(edges, 0);
+        assert!(octagons > 40 && (octagons as Float / squares as Float - 1.0).abs() < 0.2);
+
+        // Every edge has unit length.
+        for (tile, _) in &patch {
+            let n = tile.vertex_count();
+            for k in 0..n {
+                let edge = (&tile.vertices[k] - &tile.vertices[(k + 1) % n]).norm();
+                assert!((edge - 1.0).abs() < 1e-6);
+            }
+        }
+
+        // The square tiling within a radius of 1.5 from the center of a tile.
+        let patch = Concrete::uniform_tiling("4^4", 1.5).unwrap();
+        assert_eq!(counts(&patch), [9, 0, 0]);
+
+        // The rhombitrihexagonal tiling has three squares for each hexagon.
+        let patch = Concrete::uniform_tiling("3.4.6.4", 12.0).unwrap();
+        let [triangles, hexagons, squares] = counts(&patch);
+        assert!((squares as Float / hexagons as Float - 3.0).abs() < 0.3);
+        assert!((triangles as Float / hexagons as Float - 2.0).abs() < 0.3);
+    }
+
+    #[test]
+    fn hyperbolic() {
+        // The order-3 heptagonal tiling around a heptagon.
+        let patch = Concrete::uniform_tiling("7.7.7", 1.2).unwrap();
+        assert_eq!(counts(&patch), [8, 0, 0]);
+        for (tile, _) in &patch {
+            assert_eq!(tile.vertex_count(), 7);
+            assert!(tile.vertices.iter().all(|v| v.norm() < 1.0));
+        }
+
+        // The truncated order-4 hexagonal tiling has dodecagons and squares.
+        let patch = Concrete::uniform_tiling("4.12.12", 3.0).unwrap();
+        for (tile, label) in &patch {
+            assert_eq!(tile.vertex_count(), [12, 4, 0][*label]);
+        }
+    }
+}
@@ -0,0 +1,266 @@
+//! Builds rings of prisms and the toroidal polyhedra that bound them, as well
+//! as some [Stewart toroids](https://polytope.miraheze.org/wiki/Stewart_toroid),
+//! i.e. toroidal polyhedra with regular faces.
+
+use super::{convex::convex_hull, Concrete};
+use crate::{
+    abs::complex::Complex,
+    geometry::{Point, PointSet},
+    Consts, Float,
+};
+
+/// Returns the vertices of a regular `n`-gon with unit edge length, centered
+/// on the z-axis at a given height, with a vertex at a given angle.
+fn axial_polygon(n: usize, angle: Float, height: Float) -> Vec<Point> {
+    let radius = 0.5 / (Float::PI / n as Float).sin();
+    (0..n)
+        .map(|k| {
+            let (sin, cos) = (angle + Float::TAU * k as Float / n as Float).sin_cos();
+            vec![radius * cos, radius * sin, height].into()
+        })
+        .collect()
+}
+
+/// Returns the points obtained from a point by every permutation of its
+/// coordinates and every change of sign, scaled by some factor.
+fn permutations(point: [Float; 3], scale: Float) -> Vec<Point> {
+    const PERMS: [[usize; 3]; 6] = [
+        [0, 1, 2],
+        [1, 2, 0],
+        [2, 0, 1],
+        [0, 2, 1],
+        [2, 1, 0],
+        [1, 0, 2],
+    ];
+
+    let mut points = PointSet::new(Float::EPS);
+    for perm in PERMS {
+        for signs in 0..8 {
+            let point: Point = (0..3)
+                .map(|i| {
+                    let sign = if signs & (1 << i) == 0 { 1.0 } else { -1.0 };
+                    sign * point[perm[i]] * scale
+                })
+                .collect::<Vec<_>>()
+                .into();
+
+            points.insert(point);
+        }
+    }
+
+    points.into_points()
+}
+
+impl Concrete {
+    /// Returns the vertices of a ring of `n` prisms with regular `m`-gonal
+    /// bases of unit circumradius, indexed as in [`Complex::prism_ring`]. The
+    /// bases lie on planes through the z-axis, with their centers at a given
+    /// distance from it.
+    fn prism_ring_vertices(n: usize, m: usize, radius: Float) -> Vec<Point> {
+        let mut vertices = Vec::with_capacity(n * m);
+
+        for i in 0..n {
+            let (sin_i, cos_i) = (Float::TAU * i as Float / n as Float).sin_cos();
+            for j in 0..m {
+                let (sin_j, cos_j) = (Float::TAU * j as Float / m as Float).sin_cos();
+                let dist = radius + cos_j;
+                vertices.push(vec![dist * cos_i, dist * sin_i, sin_j].into());
+            }
+        }
+
+        vertices
+    }
+
+    /// Builds a ring of `n` prisms with regular `m`-gonal bases of unit
+    /// circumradius, whose centers lie at a given distance from the z-axis.
+    /// The radius should be greater than 1 for the prisms not to intersect.
+    ///
+    /// The maximal element of the result has every prism as a subelement, so
+    /// it won't be a [valid](crate::abs::Abstract::is_valid) polytope.
+    ///
+    /// # Panics
+    /// This method will panic if either `n` or `m` is less than 3.
+    pub fn prism_ring(n: usize, m: usize, radius: Float) -> Self {
+        Self::new(
+            Self::prism_ring_vertices(n, m, radius),
+            Complex::prism_ring(n, m).into_abstract(),
+        )
+    }
+
+    /// Builds the toroidal polyhedron that bounds a ring of prisms, as in
+    /// [`Self::prism_ring`]. Its faces are `n * m` isosceles trapezoids.
+    ///
+    /// # Panics
+    /// This method will panic if either `n` or `m` is less than 3.
+    pub fn prism_torus(n: usize, m: usize, radius: Float) -> Self {
+        Self::new(
+            Self::prism_ring_vertices(n, m, radius),
+            Complex::prism_ring(n, m).boundary().into_abstract(),
+        )
+    }
+
+    /// Blends a polytope with another along the only pair of facets of both
+    /// that coincide, allowing the result to be non-convex.
+    fn blend_coinciding(&self, other: &Self) -> Self {
+        let (facet_p, facet_q) = self.coinciding_facets(other)[0];
+        Self::blend_with(self, other, facet_p, facet_q, true).unwrap()
+    }
+
+    /// Builds the tunnel used for the Stewart toroids, which runs along the
+    /// z-axis. It starts at a regular `2n`-gon with unit edge length at a
+    /// given height, with a vertex at a given angle, and consists of an
+    /// `n`-gonal cupola, followed by an `n`-gonal prism or antiprism, and by
+    /// another `n`-gonal cupola that ends in a `2n`-gon.
+    fn cupola_tunnel(n: usize, angle: Float, height: Float, antiprism: bool) -> Self {
+        let n_float = n as Float;
+        let apothem = |k: Float| 0.5 / (Float::PI / k).tan();
+        let cupola_height = (1.0 - (apothem(2.0 * n_float) - apothem(n_float)).powi(2)).sqrt();
+
+        // The twist between the bases of the middle prism or antiprism, and
+        // its height.
+        let (twist, middle_height) = if antiprism {
+            let radius = 0.5 / (Float::PI / n_float).sin();
+            let chord = 2.0 * radius * (Float::PI / (2.0 * n_float)).sin();
+            (Float::PI / n_float, (1.0 - chord * chord).sqrt())
+        } else {
+            (0.0, 1.0)
+        };
+
+        // The angle of a vertex of the top of the first cupola.
+        let near_angle = angle + 1.5 * Float::PI / n_float;
+        let near_height = height + cupola_height;
+        let near_polygon = axial_polygon(n, near_angle, near_height);
+        let far_angle = near_angle + twist;
+        let far_height = near_height + middle_height;
+        let far_polygon = axial_polygon(n, far_angle, far_height);
+
+        let mut near_cupola = axial_polygon(2 * n, angle, height);
+        near_cupola.extend(near_polygon.iter().cloned());
+        let mut middle = near_polygon;
+        middle.extend(far_polygon.iter().cloned());
+        let mut far_cupola = far_polygon;
+        far_cupola.extend(axial_polygon(
+            2 * n,
+            far_angle - 1.5 * Float::PI / n_float,
+            far_height + cupola_height,
+        ));
+
+        convex_hull(near_cupola)
+            .blend_coinciding(&convex_hull(middle))
+            .blend_coinciding(&convex_hull(far_cupola))
+    }
+
+    /// Builds the Stewart toroid obtained by drilling a tunnel of two
+    /// triangular cupolas and an octahedron between two opposite hexagons of a
+    /// truncated octahedron with unit edge length. These hexagons are
+    /// perpendicular to the z-axis.
+    pub fn tunneled_truncated_octahedron() -> Self {
+        // Rotates the axis through (1, 1, 1) into the z-axis.
+        let (sqrt_6, sqrt_2) = ((6.0 as Float).sqrt(), Float::SQRT_2);
+        let points = permutations([0.0, 1.0, 2.0], 1.0 / sqrt_2)
+            .into_iter()
+            .map(|p| {
+                vec![
+                    (p[0] - p[1]) / sqrt_2,
+                    (p[0] + p[1] - 2.0 * p[2]) / sqrt_6,
+                    (p[0] + p[1] + p[2]) / Float::SQRT_3,
+                ]
+                .into()
+            })
+            .collect();
+
+        let mut poly = convex_hull(points);
+        poly.drill(&Self::cupola_tunnel(3, 0.0, -sqrt_6 / 2.0, true))
+            .unwrap();
+        poly
+    }
+
+    /// Builds the Stewart toroid obtained by drilling a tunnel of two square
+    /// cupolas and a cube between two opposite octagons of a truncated cube
+    /// with unit edge length. These octagons are perpendicular to the z-axis.
+    pub fn tunneled_truncated_cube() -> Self {
+        let xi = Float::SQRT_2 - 1.0;
+        let mut poly = convex_hull(permutations([xi, 1.0, 1.0], 0.5 / xi));
+        poly.drill(&Self::cupola_tunnel(
+            4,
+            Float::PI / 8.0,
+            -(1.0 + Float::SQRT_2) / 2.0,
+            false,
+        ))
+        .unwrap();
+        poly
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        abs::{elements::ElementRef, rank::Rank, Abstract},
+        conc::ConcretePolytope,
+        geometry::Subspace,
+        Polytope,
+    };
+
+    #[test]
+    fn prism_torus() {
+        let torus = Concrete::prism_torus(6, 4, 2.0);
+        torus.abs.is_valid().unwrap();
+        assert_eq!(torus.el_counts(), vec![1, 24, 48, 24, 1].into());
+
+        // Every face is planar.
+        for idx in 0..torus.el_count(Rank::new(2)) {
+            let vertices = torus
+                .element_vertices_ref(ElementRef::new(Rank::new(2), idx))
+                .unwrap();
+            assert_eq!(vertices.len(), 4);
+            assert_eq!(Subspace::from_points(vertices.into_iter()).rank(), 2);
+        }
+
+        let ring = Concrete::prism_ring(6, 4, 2.0);
+        assert_eq!(ring.el_counts(), vec![1, 24, 48, 30, 6, 1].into());
+    }
+
+    /// Checks that a polyhedron is a valid torus with regular faces and the
+    /// given element counts.
+    fn assert_stewart_toroid(mut poly: Concrete, el_counts: Vec<usize>) {
+        poly.abs.is_valid().unwrap();
+        assert_eq!(poly.el_counts(), el_counts.into());
+        assert_eq!(poly.genus(), Some(1));
+
+        for idx in 0..poly.el_count(Rank::new(1)) {
+            let vertices = poly
+                .element_vertices_ref(ElementRef::new(Rank::new(1), idx))
+                .unwrap();
+            assert!(((vertices[0] - vertices[1]).norm() - 1.0).abs() < Float::EPS);
+        }
+
+        for idx in 0..poly.el_count(Rank::new(2)) {
+            let vertices = poly
+                .element_vertices_ref(ElementRef::new(Rank::new(2), idx))
+                .unwrap();
+            assert_eq!(Subspace::from_points(vertices.into_iter()).rank(), 2);
+        }
+    }
+
+    #[test]
+    fn stewart_toroids() {
+        assert_stewart_toroid(
+            Concrete::tunneled_truncated_octahedron(),
+            vec![1, 30, 60, 30, 1],
+        );
+        assert_stewart_toroid(Concrete::tunneled_truncated_cube(), vec![1, 32, 64, 32, 1]);
+    }
+
+    #[test]
+    fn genus() {
+        assert_eq!(Concrete::prism_torus(5, 3, 2.0).genus(), Some(1));
+        assert_eq!(Concrete::hypercube(Rank::new(3)).genus(), Some(0));
+        assert_eq!(Concrete::hypercube(Rank::new(4)).genus(), None);
+
+        // The hemicube is a projective plane, which has a single cross-cap.
+        let mut hemicube = Abstract::hypercube(Rank::new(3)).petrial().unwrap();
+        hemicube.abs_sort();
+        assert_eq!(hemicube.genus(), Some(1));
+    }
+}
@@ -0,0 +1,144 @@
+//! Builds truncations and Stott expansions of a polytope with a continuous
+//! parameter, so that a polytope can be morphed into its truncate, rectate, or
+//! expanded forms. See [`Abstract::truncate`](crate::abs::Abstract::truncate)
+//! for the combinatorial side of the constructions.
+
+use super::Concrete;
+use crate::{Float, Polytope};
+
+impl Concrete {
+    /// Builds the Wythoffian of the polytope with the vertices and the
+    /// elements of a given rank ringed. Every vertex of the result corresponds
+    /// to a vertex of the polytope together with an element of the given rank
+    /// containing it, and is placed along the way from the former to the
+    /// center of the latter, at a fraction `t` of it.
+    ///
+    /// # Panics
+    /// This method will panic if the polytope has rank less than 2, if the
+    /// rank is out of range, or if the polytope isn't sorted.
+    fn stott_with(&self, rank: usize, t: Float) -> Self {
+        let (abs, flags) = self.abs.truncate_and_flags(&[0, rank]);
+        let centers = self.element_centers();
+
+        let vertices = flags
+            .into_iter()
+            .map(|flag| {
+                let vertex = &centers[0][flag[0]];
+                vertex + (&centers[rank][flag[rank]] - vertex) * t
+            })
+            .collect();
+
+        Self::new(vertices, abs)
+    }
+
+    /// Builds the truncate of the polytope, where every vertex is cut off at a
+    /// fraction `t / 2` of the way along the edges. A value of 0 returns the
+    /// original polytope with every vertex repeated, and a value of 1 returns
+    /// its rectate with every vertex repeated, so that morphs between them
+    /// keep the same combinatorics throughout.
+    ///
+    /// The truncated cube with unit edges is built from the cube with edge
+    /// `1 + √2` with `t` equal to `2 - √2`.
+    ///
+    /// # Panics
+    /// This method will panic if the polytope has rank less than 2, or if it
+    /// isn't sorted.
+    pub fn truncate_with(&self, t: Float) -> Self {
+        self.stott_with(1, t)
+    }
+
+    /// Builds the Stott expansion of the polytope, where every facet is shrunk
+    /// towards its center by a factor of `1 - t`, and the gaps between them
+    /// are filled by prisms. A value of 0 returns the original polytope with
+    /// every vertex repeated, and a value of 1 collapses every facet into its
+    /// center.
+    ///
+    /// Up to scale, this is the same as moving the facets outwards. The
+    /// rhombicuboctahedron with unit edges is built from the cube with edge
+    /// `1 + √2` with `t` equal to `2 - √2`.
+    ///
+    /// # Panics
+    /// This method will panic if the polytope has rank less than 2, or if it
+    /// isn't sorted.
+    pub fn expand_with(&self, t: Float) -> Self {
+        let rank = self.rank().try_usize().unwrap_or(0);
+        assert!(rank >= 2, "Can't expand a polytope of rank less than 2.");
+        self.stott_with(rank - 1, t)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{abs::rank::Rank, conc::ConcretePolytope, geometry::PointSet, Consts};
+
+    use vec_like::VecLike;
+
+    /// Returns the sorted edge lengths of a polytope.
+    fn edge_lengths(poly: &Concrete) -> Vec<Float> {
+        let mut lengths: Vec<Float> = poly[Rank::new(1)]
+            .iter()
+            .map(|edge| (&poly.vertices[edge.subs[0]] - &poly.vertices[edge.subs[1]]).norm())
+            .collect();
+        lengths.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        lengths
+    }
+
+    /// Returns the number of distinct vertices of a polytope.
+    fn distinct_vertices(poly: &Concrete) -> usize {
+        PointSet::dedup(poly.vertices.iter().cloned(), Float::EPS)
+            .0
+            .len()
+    }
+
+    #[test]
+    fn truncate() {
+        let mut cube = Concrete::hypercube(Rank::new(3));
+        cube.abs_sort();
+
+        // The truncated cube with unit edges.
+        let mut big_cube = cube.clone();
+        big_cube.scale(1.0 + Float::SQRT_2);
+        let truncate = big_cube.truncate_with(2.0 - Float::SQRT_2);
+        truncate.abs.is_valid().unwrap();
+        assert_eq!(truncate.el_counts(), vec![1, 24, 36, 14, 1].into());
+        let lengths = edge_lengths(&truncate);
+        assert!((lengths[0] - 1.0).abs() < Float::EPS);
+        assert!((lengths[35] - 1.0).abs() < Float::EPS);
+
+        // The endpoints collapse into the cube and the cuboctahedron.
+        assert_eq!(distinct_vertices(&cube.truncate_with(0.0)), 8);
+        let rectate = cube.truncate_with(1.0);
+        assert_eq!(distinct_vertices(&rectate), 12);
+        for v in &rectate.vertices {
+            assert!((v.norm() - Float::SQRT_2 / 2.0).abs() < Float::EPS);
+        }
+    }
+
+    #[test]
+    fn expand() {
+        let mut cube = Concrete::hypercube(Rank::new(3));
+        cube.abs_sort();
+
+        // The rhombicuboctahedron with unit edges.
+        let mut big_cube = cube.clone();
+        big_cube.scale(1.0 + Float::SQRT_2);
+        let expanded = big_cube.expand_with(2.0 - Float::SQRT_2);
+        expanded.abs.is_valid().unwrap();
+        assert_eq!(expanded.el_counts(), vec![1, 24, 48, 26, 1].into());
+        let lengths = edge_lengths(&expanded);
+        assert!((lengths[0] - 1.0).abs() < Float::EPS);
+        assert!((lengths[47] - 1.0).abs() < Float::EPS);
+
+        // Collapsing the faces gives the octahedron.
+        assert_eq!(distinct_vertices(&cube.expand_with(1.0)), 6);
+
+        // In a polygon, this is the same as truncating.
+        let mut pentagon = Concrete::polygon(5);
+        pentagon.abs_sort();
+        assert_eq!(
+            pentagon.expand_with(0.5).vertices,
+            pentagon.truncate_with(0.5).vertices
+        );
+    }
+}
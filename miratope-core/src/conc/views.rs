@@ -0,0 +1,361 @@
+//! Finds the directions from which a polytope looks the most symmetric, which
+//! are used to set up standard viewpoints.
+//!
+//! Rotation axes are found by testing whether the vertex set of a polyhedron
+//! is invariant under rotations about the axes through the centroids of its
+//! vertices, edges and faces. Coxeter planes are found from the Petrie
+//! polygons of a polytope of any rank.
+
+use crate::{
+    abs::rank::Rank,
+    conc::{Concrete, ConcretePolytope},
+    geometry::{Matrix, Point, PointSet, Vector},
+    Consts, Float, Polytope,
+};
+
+use gcd::Gcd;
+use vec_like::*;
+
+/// The tolerance with which points are compared, relative to the size of the
+/// polytope.
+const TOLERANCE: Float = 1e-6;
+
+/// An axis about which a polytope has rotational symmetry.
+#[derive(Clone, Debug)]
+pub struct RotationAxis {
+    /// A unit vector in the direction of the axis.
+    pub direction: Vector,
+
+    /// The order of the rotational symmetry about the axis. The polytope is
+    /// invariant under a rotation by `2π / order` about the axis.
+    pub order: usize,
+}
+
+/// Returns the dot product of two vectors in 3D.
+fn dot(u: [Float; 3], v: [Float; 3]) -> Float {
+    u[0] * v[0] + u[1] * v[1] + u[2] * v[2]
+}
+
+/// Rotates a point by a given angle about a unit axis, using
+/// [Rodrigues' formula](https://en.wikipedia.org/wiki/Rodrigues%27_rotation_formula).
+fn rotate(v: [Float; 3], axis: [Float; 3], angle: Float) -> [Float; 3] {
+    let (sin, cos) = angle.sin_cos();
+    let [a, b, c] = axis;
+    let cross = [
+        b * v[2] - c * v[1],
+        c * v[0] - a * v[2],
+        a * v[1] - b * v[0],
+    ];
+    let proj = dot(axis, v) * (1.0 - cos);
+
+    [
+        v[0] * cos + cross[0] * sin + a * proj,
+        v[1] * cos + cross[1] * sin + b * proj,
+        v[2] * cos + cross[2] * sin + c * proj,
+    ]
+}
+
+/// Splits some sorted values into groups of values that are equal up to a
+/// given tolerance, and returns the size of each group.
+fn group_sizes<I: Iterator<Item = Float>>(values: I, tolerance: Float) -> Vec<usize> {
+    let mut sizes = Vec::new();
+    let mut last = None;
+
+    for value in values {
+        match last {
+            Some(last) if value - last <= tolerance => *sizes.last_mut().unwrap() += 1,
+            _ => sizes.push(1),
+        }
+        last = Some(value);
+    }
+
+    sizes
+}
+
+impl Concrete {
+    /// Returns the vertices of the polytope relative to its gravicenter, if
+    /// it's 3D, together with their greatest distance to it.
+    fn centered_vertices_3d(&self) -> Option<(Vec<[Float; 3]>, Float)> {
+        if self.dim() != Some(3) {
+            return None;
+        }
+
+        let center = self.gravicenter()?;
+        let vertices: Vec<_> = self
+            .vertices
+            .iter()
+            .map(|v| [v[0] - center[0], v[1] - center[1], v[2] - center[2]])
+            .collect();
+        let radius = vertices
+            .iter()
+            .map(|&v| dot(v, v).sqrt())
+            .fold(0.0, Float::max);
+
+        Some((vertices, radius))
+    }
+
+    /// Returns the order of the rotational symmetry of a set of points about
+    /// a unit axis, or 1 if there's none.
+    fn axis_order(
+        vertices: &[[Float; 3]],
+        vertex_set: &PointSet,
+        axis: [Float; 3],
+        tolerance: Float,
+    ) -> usize {
+        // Points at the same height and distance from the axis form orbits,
+        // so the order must divide the size of each of these groups.
+        let mut coords: Vec<_> = vertices
+            .iter()
+            .filter_map(|&v| {
+                let h = dot(axis, v);
+                let r = (dot(v, v) - h * h).max(0.0).sqrt();
+                if r > tolerance {
+                    Some((h, r))
+                } else {
+                    None
+                }
+            })
+            .collect();
+        coords.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        let mut order = 0;
+        let mut start = 0;
+        for size in group_sizes(coords.iter().map(|&(h, _)| h), tolerance) {
+            let ring = &mut coords[start..start + size];
+            ring.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+            for ring_size in group_sizes(ring.iter().map(|&(_, r)| r), tolerance) {
+                order = order.gcd(ring_size);
+            }
+            start += size;
+        }
+
+        // Tests every possible order, from largest to smallest.
+        (2..=order)
+            .rev()
+            .filter(|k| order % k == 0)
+            .find(|&k| {
+                let angle = Float::TAU / k as Float;
+                vertices.iter().all(|&v| {
+                    let p = Point::from_row_slice(&rotate(v, axis, angle));
+                    vertex_set.find(&p).is_some()
+                })
+            })
+            .unwrap_or(1)
+    }
+
+    /// Returns the axes of rotational symmetry of the vertices of a polyhedron
+    /// that pass through its gravicenter and the centroid of one of its
+    /// vertices, edges or faces, sorted by decreasing order. Only one of the
+    /// two opposite directions of each axis is returned.
+    ///
+    /// Returns an empty list if the polytope isn't 3D.
+    pub fn rotation_axes(&self) -> Vec<RotationAxis> {
+        let (vertices, radius) = match self.centered_vertices_3d() {
+            Some(res) => res,
+            None => return Vec::new(),
+        };
+        let tolerance = TOLERANCE * radius.max(Float::EPS);
+
+        let mut vertex_set = PointSet::new(tolerance);
+        for &v in &vertices {
+            vertex_set.insert(Point::from_row_slice(&v));
+        }
+
+        // The directions that were already tested.
+        let mut directions = PointSet::new(TOLERANCE);
+        let mut axes = Vec::new();
+
+        for rank in 0..3 {
+            let rank = Rank::new(rank);
+            if self.rank() <= rank {
+                break;
+            }
+
+            for idx in 0..self.el_count(rank) {
+                // The centroid of the element.
+                let element = match rank.into_usize() {
+                    0 => vec![idx],
+                    1 => self[rank][idx].subs.0.clone(),
+                    _ => {
+                        let mut element: Vec<_> = self[rank][idx]
+                            .subs
+                            .iter()
+                            .flat_map(|&e| self[Rank::new(1)][e].subs.iter().copied())
+                            .collect();
+                        element.sort_unstable();
+                        element.dedup();
+                        element
+                    }
+                };
+                let mut centroid = [0.0; 3];
+                for &v in &element {
+                    for (c, x) in centroid.iter_mut().zip(vertices[v].iter()) {
+                        *c += x / element.len() as Float;
+                    }
+                }
+
+                let norm = dot(centroid, centroid).sqrt();
+                if norm < tolerance {
+                    continue;
+                }
+
+                // Picks the direction whose first nonzero coordinate is positive.
+                let mut axis = [centroid[0] / norm, centroid[1] / norm, centroid[2] / norm];
+                if axis.iter().find(|c| c.abs() > TOLERANCE).unwrap() < &0.0 {
+                    axis = [-axis[0], -axis[1], -axis[2]];
+                }
+
+                if !directions.insert(Point::from_row_slice(&axis)).1 {
+                    continue;
+                }
+
+                let order = Self::axis_order(&vertices, &vertex_set, axis, tolerance);
+                if order > 1 {
+                    axes.push(RotationAxis {
+                        direction: Vector::from_row_slice(&axis),
+                        order,
+                    });
+                }
+            }
+        }
+
+        axes.sort_by_key(|axis| std::cmp::Reverse(axis.order));
+        axes
+    }
+
+    /// Returns an orthonormal basis of a
+    /// [Coxeter plane](https://en.wikipedia.org/wiki/Coxeter_plane) of the
+    /// polytope, in which one of its Petrie polygons projects onto a regular
+    /// polygon. This is only guaranteed to be the case for regular polytopes,
+    /// but gives a symmetric view for many others.
+    ///
+    /// The plane is spanned by the first Fourier component of the vertices of
+    /// the Petrie polygon from the first flag. Returns `None` if this Petrie
+    /// polygon is invalid, or if it's flat.
+    pub fn coxeter_plane(&mut self) -> Option<(Vector, Vector)> {
+        let center = self.gravicenter()?;
+        let petrie = self.petrie_polygon()?;
+        let n = petrie.vertices.len();
+        if n < 3 {
+            return None;
+        }
+
+        let mut u = Vector::zeros(center.len());
+        let mut w = Vector::zeros(center.len());
+        for (j, v) in petrie.vertices.iter().enumerate() {
+            let (sin, cos) = (Float::TAU * j as Float / n as Float).sin_cos();
+            let v = v - &center;
+            u += &v * cos;
+            w += &v * sin;
+        }
+
+        let u = u.try_normalize(Float::EPS)?;
+        let w = (&w - &u * u.dot(&w)).try_normalize(Float::EPS)?;
+        Some((u, w))
+    }
+
+    /// Returns an orthogonal matrix that rotates a
+    /// [Coxeter plane](Self::coxeter_plane) of the polytope onto the plane of
+    /// the first two coordinates, or `None` if it can't be found.
+    pub fn coxeter_plane_rotation(&mut self) -> Option<Matrix> {
+        let (u, w) = self.coxeter_plane()?;
+        let dim = u.len();
+
+        // Completes the basis of the plane into an orthonormal basis.
+        let mut basis = vec![u, w];
+        for i in 0..dim {
+            if basis.len() == dim {
+                break;
+            }
+
+            let mut v = Vector::zeros(dim);
+            v[i] = 1.0;
+            for b in &basis {
+                v -= b * b.dot(&v);
+            }
+
+            if let Some(v) = v.try_normalize(Float::EPS.sqrt()) {
+                basis.push(v);
+            }
+        }
+
+        let mut matrix = Matrix::zeros(dim, dim);
+        for (i, b) in basis.iter().enumerate() {
+            matrix.set_row(i, &b.transpose());
+        }
+        Some(matrix)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        abs::rank::Rank,
+        conc::{Concrete, ConcretePolytope},
+        Consts, Float, Polytope,
+    };
+
+    use approx::abs_diff_eq;
+
+    /// Returns the orders of the rotation axes of a polytope.
+    fn orders(poly: &Concrete) -> Vec<usize> {
+        poly.rotation_axes().iter().map(|axis| axis.order).collect()
+    }
+
+    #[test]
+    fn cube_axes() {
+        let cube = Concrete::hypercube(Rank::new(3));
+        assert_eq!(orders(&cube), vec![4, 4, 4, 3, 3, 3, 3, 2, 2, 2, 2, 2, 2]);
+    }
+
+    #[test]
+    fn triangular_prism_axes() {
+        let prism = Concrete::uniform_prism(3, 1);
+        assert_eq!(orders(&prism), vec![3, 2, 2, 2]);
+    }
+
+    #[test]
+    fn polygon_axes() {
+        assert!(Concrete::polygon(5).rotation_axes().is_empty());
+    }
+
+    /// The Coxeter plane of the cube is orthogonal to one of its diagonals.
+    #[test]
+    fn cube_coxeter_plane() {
+        let mut cube = Concrete::hypercube(Rank::new(3));
+        let (u, w) = cube.coxeter_plane().unwrap();
+        let normal = u.cross(&w);
+
+        for c in normal.iter() {
+            assert!(abs_diff_eq!(
+                c.abs(),
+                1.0 / (3.0 as Float).sqrt(),
+                epsilon = Float::EPS
+            ));
+        }
+    }
+
+    /// The Coxeter plane projection of the tesseract is a regular octagon.
+    #[test]
+    fn tesseract_coxeter_plane() {
+        let mut tesseract = Concrete::hypercube(Rank::new(4));
+        let m = tesseract.coxeter_plane_rotation().unwrap();
+        assert!(abs_diff_eq!(
+            &m * m.transpose(),
+            crate::geometry::Matrix::identity(4, 4),
+            epsilon = Float::EPS
+        ));
+
+        let petrie = tesseract.petrie_polygon().unwrap().apply(&m);
+        let radii: Vec<_> = petrie
+            .vertices
+            .iter()
+            .map(|v| (v[0] * v[0] + v[1] * v[1]).sqrt())
+            .collect();
+
+        assert_eq!(radii.len(), 8);
+        for r in &radii {
+            assert!(abs_diff_eq!(*r, radii[0], epsilon = Float::EPS));
+        }
+    }
+}
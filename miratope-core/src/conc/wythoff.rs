@@ -0,0 +1,130 @@
+//! Builds isogonal polytopes from the orbit of a point under any finite group
+//! of matrices, not just those generated by reflections.
+
+use super::{convex::convex_hull, Concrete};
+use crate::{
+    geometry::{Matrix, Point, PointSet},
+    group::Group,
+    Consts, Float,
+};
+
+impl Concrete {
+    /// Returns the images of a seed point under the elements of a group,
+    /// indexed by the vertices of the polytope, where the image under an
+    /// element is sent to the image of the first vertex under it. Returns
+    /// `None` if two vertices coincide, if the group doesn't permute the
+    /// vertices transitively, or if two elements sending the first vertex to
+    /// the same place send the seed to different places.
+    fn transported_vertices(&self, elements: &[Matrix], seed: &Point) -> Option<Vec<Point>> {
+        let base = self.vertices.first()?;
+        let (vertex_set, _) = PointSet::dedup(self.vertices.iter().cloned(), Float::EPS);
+        if vertex_set.len() != self.vertices.len() {
+            return None;
+        }
+        let mut vertices: Vec<Option<Point>> = vec![None; self.vertices.len()];
+
+        for el in elements {
+            let image = el * base;
+            let idx = vertex_set.find(&image)?;
+            let new_vertex = el * seed;
+
+            match &vertices[idx] {
+                Some(vertex) => {
+                    if (vertex - &new_vertex).norm() > Float::EPS {
+                        return None;
+                    }
+                }
+                None => vertices[idx] = Some(new_vertex),
+            }
+        }
+
+        vertices.into_iter().collect()
+    }
+
+    /// Builds the isogonal polytope whose vertices are the orbit of a seed
+    /// point under a finite group of matrices. The group doesn't need to be
+    /// generated by reflections, so that chiral and swirl groups can be used.
+    ///
+    /// If the group acts transitively on the vertices of this polytope, and
+    /// the seed is fixed by the stabilizer of its first vertex, the result
+    /// has the same lattice as this polytope, with every vertex moved to the
+    /// image of the seed under the elements sending the first vertex to it.
+    /// This allows one to continuously deform an isogonal polytope into any
+    /// other with its element structure. Otherwise, the lattice is that of the
+    /// convex hull of the orbit.
+    ///
+    /// # Panics
+    /// This method will panic if the seed doesn't have the same dimension as
+    /// the matrices of the group.
+    pub fn wythoff_on(&self, group: &Group, seed: &Point) -> Self {
+        trace_span!("wythoff", vertices = self.vertices.len());
+        let elements = group.clone().elements();
+        trace_event!(order = elements.len(), "enumerated group");
+
+        match self.transported_vertices(&elements, seed) {
+            Some(vertices) => Self::new(vertices, self.abs.clone()),
+            None => convex_hull(group.clone().orbit(seed.clone())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{abs::rank::Rank, Polytope};
+
+    /// Returns the rotation group of a square, and the symmetry group of a
+    /// square.
+    fn square_groups() -> (Group, Group) {
+        let rotation = Matrix::from_row_slice(2, 2, &[0.0, -1.0, 1.0, 0.0]);
+        let reflection = Matrix::from_row_slice(2, 2, &[1.0, 0.0, 0.0, -1.0]);
+
+        (
+            Group::from_gens(2, vec![rotation.clone()]),
+            Group::from_gens(2, vec![rotation, reflection]),
+        )
+    }
+
+    #[test]
+    fn chiral() {
+        let (rotations, symmetries) = square_groups();
+        let square = Concrete::polygon(4);
+        let seed = Point::from(vec![1.0, 0.5]);
+
+        // The rotations of a square move it into a rotated square.
+        let rotated = square.wythoff_on(&rotations, &seed);
+        assert_eq!(rotated.el_counts(), vec![1, 4, 4, 1].into());
+        assert!((&rotated.vertices[0] - &seed).norm() < Float::EPS);
+        for v in &rotated.vertices {
+            assert!((v.norm() - seed.norm()).abs() < Float::EPS);
+        }
+
+        // The seed isn't fixed by the reflection fixing the first vertex, so
+        // the result is the hull of its orbit, which is an octagon.
+        let octagon = square.wythoff_on(&symmetries, &seed);
+        assert_eq!(octagon.el_counts(), vec![1, 8, 8, 1].into());
+    }
+
+    #[test]
+    fn cube() {
+        // The symmetry group of a cube, generated by a coordinate reflection
+        // and two coordinate permutations.
+        let mut gens = vec![Matrix::identity(3, 3); 3];
+        gens[0][(0, 0)] = -1.0;
+        gens[1].swap_rows(0, 1);
+        gens[2].swap_rows(1, 2);
+        let group = Group::from_gens(3, gens);
+
+        let cube = Concrete::hypercube(Rank::new(3));
+        let scaled = cube.wythoff_on(&group, &Point::from(vec![1.0, 1.0, 1.0]));
+        assert_eq!(scaled.el_counts(), vec![1, 8, 12, 6, 1].into());
+        for v in &scaled.vertices {
+            assert!((v.norm() - (3.0 as Float).sqrt()).abs() < Float::EPS);
+        }
+
+        // A generic seed gives a great rhombicuboctahedron.
+        let seed = Point::from(vec![1.0, 2.0, 3.0]);
+        let grco = cube.wythoff_on(&group, &seed);
+        assert_eq!(grco.el_counts(), vec![1, 48, 72, 26, 1].into());
+    }
+}
@@ -0,0 +1,314 @@
+//! Compares two polytopes and reports how they differ, which is useful both
+//! to look at two polytopes side by side and to check that some construction
+//! still gives the same result as before.
+
+use std::fmt::Display;
+
+use crate::{
+    abs::{automorphism::AutomorphismGroup, rank::Rank, Abstract},
+    geometry::Point,
+    Float, Polytope,
+};
+
+use vec_like::VecLike;
+
+/// The most flags that a polytope can have for [`PolytopeDiff`] to compare
+/// its automorphisms, which takes time proportional to the square of the
+/// number of flags.
+pub const ORBIT_FLAG_LIMIT: usize = 20_000;
+
+/// A quantity of two polytopes at a given rank.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RankDiff {
+    /// The rank.
+    pub rank: Rank,
+
+    /// The quantity for the first polytope.
+    pub left: usize,
+
+    /// The quantity for the second polytope.
+    pub right: usize,
+}
+
+impl RankDiff {
+    /// Returns how much larger the quantity is for the second polytope.
+    pub fn delta(&self) -> isize {
+        self.right as isize - self.left as isize
+    }
+}
+
+/// Statistics on how far each vertex of a polytope moved with respect to the
+/// corresponding vertex of another polytope.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Displacement {
+    /// The largest distance between corresponding vertices.
+    pub max: Float,
+
+    /// The average distance between corresponding vertices.
+    pub mean: Float,
+
+    /// The root mean square of the distances between corresponding vertices.
+    pub rms: Float,
+}
+
+/// A report on how two polytopes differ, returned by [`Polytope::diff`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct PolytopeDiff {
+    /// The number of elements of each rank of both polytopes, from the
+    /// vertices up to the facets.
+    pub el_counts: Vec<RankDiff>,
+
+    /// The number of orbits of elements of each rank under the automorphisms
+    /// of each polytope, from the vertices up to the facets. This is `None`
+    /// if either polytope isn't flag-connected or has more than
+    /// [`ORBIT_FLAG_LIMIT`] flags.
+    pub orbit_counts: Option<Vec<RankDiff>>,
+
+    /// Whether both polytopes are isomorphic, or `None` in the same cases as
+    /// for [`Self::orbit_counts`].
+    pub isomorphic: Option<bool>,
+
+    /// How far the vertices moved from the first polytope to the second.
+    /// This is only computed when both polytopes have coordinates in the
+    /// same number of dimensions and the exact same elements, in the same
+    /// order.
+    pub displacement: Option<Displacement>,
+}
+
+impl PolytopeDiff {
+    /// Compares two abstract polytopes, and the coordinates of their vertices
+    /// if these are given.
+    pub(crate) fn new(
+        left: &Abstract,
+        right: &Abstract,
+        left_points: Option<&[Point]>,
+        right_points: Option<&[Point]>,
+    ) -> Self {
+        let rank = left.rank().max(right.rank()).into_isize();
+        let el_counts = (0..rank)
+            .map(|r| {
+                let rank = Rank::new(r);
+                RankDiff {
+                    rank,
+                    left: left.el_count(rank),
+                    right: right.el_count(rank),
+                }
+            })
+            .collect();
+
+        let mut diff = Self {
+            el_counts,
+            orbit_counts: None,
+            isomorphic: None,
+            displacement: None,
+        };
+        diff.compare_automorphisms(left, right);
+
+        if let (Some(left_points), Some(right_points)) = (left_points, right_points) {
+            if same_elements(left, right) {
+                diff.displacement = displacement(left_points, right_points);
+            }
+        }
+
+        diff
+    }
+
+    /// Fills in the orbit counts and whether the polytopes are isomorphic.
+    fn compare_automorphisms(&mut self, left: &Abstract, right: &Abstract) {
+        let sort = |poly: &Abstract| {
+            if poly.flag_count() > ORBIT_FLAG_LIMIT {
+                return None;
+            }
+
+            let mut poly = poly.clone();
+            poly.abs_sort();
+            Some(poly)
+        };
+        let (left, right) = match (sort(left), sort(right)) {
+            (Some(left), Some(right)) => (left, right),
+            _ => return,
+        };
+
+        let (left_group, right_group) = match (left.automorphisms(), right.automorphisms()) {
+            (Some(left), Some(right)) => (left, right),
+            _ => return,
+        };
+
+        self.orbit_counts = self
+            .el_counts
+            .iter()
+            .map(|count| {
+                let orbits = |group: &AutomorphismGroup, poly: &Abstract| {
+                    if count.rank < poly.rank() {
+                        group.element_orbits(count.rank).map(|(_, count)| count)
+                    } else {
+                        Some(0)
+                    }
+                };
+
+                Some(RankDiff {
+                    rank: count.rank,
+                    left: orbits(&left_group, &left)?,
+                    right: orbits(&right_group, &right)?,
+                })
+            })
+            .collect();
+        self.isomorphic = left.is_isomorphic(&right);
+    }
+
+    /// Returns whether no differences were found between the polytopes.
+    pub fn is_empty(&self) -> bool {
+        let same = |diffs: &[RankDiff]| diffs.iter().all(|diff| diff.left == diff.right);
+
+        same(&self.el_counts)
+            && self.orbit_counts.as_deref().into_iter().all(same)
+            && self.isomorphic != Some(false)
+            && self
+                .displacement
+                .into_iter()
+                .all(|displacement| displacement.max == 0.0)
+    }
+}
+
+impl Display for PolytopeDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for count in &self.el_counts {
+            write!(
+                f,
+                "rank {}: {} vs {} elements",
+                count.rank, count.left, count.right
+            )?;
+            if count.delta() != 0 {
+                write!(f, " ({:+})", count.delta())?;
+            }
+            writeln!(f)?;
+        }
+
+        if let Some(orbit_counts) = &self.orbit_counts {
+            for count in orbit_counts
+                .iter()
+                .filter(|count| count.left != count.right)
+            {
+                writeln!(
+                    f,
+                    "rank {}: {} vs {} orbits",
+                    count.rank, count.left, count.right
+                )?;
+            }
+        }
+
+        match self.isomorphic {
+            Some(true) => writeln!(f, "isomorphic")?,
+            Some(false) => writeln!(f, "not isomorphic")?,
+            None => {}
+        }
+
+        if let Some(displacement) = self.displacement {
+            writeln!(
+                f,
+                "vertex displacement: max {}, mean {}, rms {}",
+                displacement.max, displacement.mean, displacement.rms
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns whether two polytopes have exactly the same elements, in the same
+/// order, up to the order of the subelements of each element.
+fn same_elements(left: &Abstract, right: &Abstract) -> bool {
+    if left.el_counts() != right.el_counts() {
+        return false;
+    }
+
+    left.iter().zip(right.iter()).all(|(left, right)| {
+        left.iter().zip(right.iter()).all(|(left, right)| {
+            let mut left = left.subs.clone();
+            let mut right = right.subs.clone();
+            left.sort_unstable();
+            right.sort_unstable();
+            left == right
+        })
+    })
+}
+
+/// Returns the statistics on the distances between corresponding points, or
+/// `None` if there's a different number of points or they live in a different
+/// number of dimensions.
+fn displacement(left: &[Point], right: &[Point]) -> Option<Displacement> {
+    if left.is_empty() || left.len() != right.len() {
+        return None;
+    }
+
+    let mut max: Float = 0.0;
+    let mut sum = 0.0;
+    let mut square_sum = 0.0;
+    for (p, q) in left.iter().zip(right) {
+        if p.len() != q.len() {
+            return None;
+        }
+
+        let distance = (p - q).norm();
+        max = max.max(distance);
+        sum += distance;
+        square_sum += distance * distance;
+    }
+
+    let n = left.len() as Float;
+    Some(Displacement {
+        max,
+        mean: sum / n,
+        rms: (square_sum / n).sqrt(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conc::Concrete;
+
+    #[test]
+    fn identical() {
+        let cube = Concrete::hypercube(Rank::new(3));
+        let diff = cube.diff(&cube);
+        assert!(diff.is_empty(), "{}", diff);
+        assert_eq!(diff.isomorphic, Some(true));
+        assert_eq!(diff.displacement.unwrap().max, 0.0);
+    }
+
+    #[test]
+    fn counts() {
+        let cube = Abstract::hypercube(Rank::new(3));
+        let prism = Abstract::polygon(5).prism();
+        let diff = cube.diff(&prism);
+        assert!(!diff.is_empty());
+        assert_eq!(diff.isomorphic, Some(false));
+        assert!(diff.displacement.is_none());
+
+        let deltas: Vec<_> = diff.el_counts.iter().map(RankDiff::delta).collect();
+        assert_eq!(deltas, vec![2, 3, 1]);
+
+        // A cube has a single orbit of edges, and a pentagonal prism two.
+        let orbits = diff.orbit_counts.unwrap();
+        assert_eq!((orbits[1].left, orbits[1].right), (1, 2));
+    }
+
+    #[test]
+    fn displacement() {
+        let cube = Concrete::hypercube(Rank::new(3));
+        let mut moved = cube.clone();
+        moved.vertices[0][0] += 0.5;
+
+        let diff = cube.diff(&moved);
+        assert!(!diff.is_empty());
+        assert_eq!(diff.isomorphic, Some(true));
+        let displacement = diff.displacement.unwrap();
+        assert!((displacement.max - 0.5).abs() < Float::EPSILON);
+        assert!((displacement.mean - 0.5 / 8.0).abs() < Float::EPSILON);
+
+        // Polytopes with a different structure aren't compared vertex by
+        // vertex.
+        assert!(cube.diff(&cube.dual()).displacement.is_none());
+    }
+}
@@ -12,7 +12,7 @@ pub type VectorSlice<'a> = nalgebra::DVectorSlice<'a, Float>;
 /// An *n* by *n* matrix.
 pub type Matrix = nalgebra::DMatrix<Float>;
 
-use std::borrow::Cow;
+use std::{borrow::Cow, collections::HashMap};
 
 use crate::{Consts, Float};
 
@@ -338,6 +338,280 @@ impl<'a> Segment<'a> {
     }
 }
 
+/// The side length of the cells of a [`PointSet`], as a multiple of its
+/// epsilon. Larger cells make it less likely for a point to lie near the
+/// boundary of a cell, at the cost of comparing against more points per cell.
+const CELL_FACTOR: Float = 1024.0;
+
+/// The offset of the grid of a [`PointSet`], measured in cells. This is
+/// arbitrary, but chosen so that points with simple coordinates don't lie on
+/// the boundaries of cells.
+const CELL_OFFSET: Float = 0.377_215_06;
+
+/// A set of points in which any two points are further than some epsilon
+/// apart. Points are stored in a spatial hash, so that finding the point
+/// within epsilon of a given one takes constant time on average, instead of
+/// requiring a comparison against every other point.
+#[derive(Clone, Debug)]
+pub struct PointSet {
+    /// The points in the set, in the order they were inserted.
+    points: Vec<Point>,
+
+    /// The distance below which two points are considered equal.
+    eps: Float,
+
+    /// The indices of the points in each cell of the grid.
+    cells: HashMap<Vec<i64>, Vec<usize>>,
+}
+
+impl PointSet {
+    /// Initializes a new empty set of points, in which two points are
+    /// considered equal when their distance is less than `eps`.
+    pub fn new(eps: Float) -> Self {
+        Self {
+            points: Vec::new(),
+            eps,
+            cells: HashMap::new(),
+        }
+    }
+
+    /// Returns the number of points in the set.
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    /// Returns whether the set is empty.
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// Returns the points in the set, in the order they were inserted.
+    pub fn points(&self) -> &[Point] {
+        &self.points
+    }
+
+    /// Returns the points in the set, in the order they were inserted.
+    pub fn into_points(self) -> Vec<Point> {
+        self.points
+    }
+
+    /// Returns the coordinate of the cell of the grid containing a number.
+    fn cell_coord(&self, x: Float) -> i64 {
+        (x / (self.eps * CELL_FACTOR) + CELL_OFFSET).floor() as i64
+    }
+
+    /// Returns the cell of the grid containing a point.
+    fn cell(&self, p: &Point) -> Vec<i64> {
+        p.iter().map(|&x| self.cell_coord(x)).collect()
+    }
+
+    /// Returns every cell of the grid that intersects the cube of side `2 *
+    /// eps` centered at a point. This is usually a single cell.
+    fn nearby_cells(&self, p: &Point) -> Vec<Vec<i64>> {
+        let mut cells = vec![Vec::with_capacity(p.len())];
+
+        for &x in p.iter() {
+            let (lo, hi) = (self.cell_coord(x - self.eps), self.cell_coord(x + self.eps));
+            if lo == hi {
+                for cell in &mut cells {
+                    cell.push(lo);
+                }
+            } else {
+                let mut new_cells = cells.clone();
+                for cell in &mut cells {
+                    cell.push(lo);
+                }
+                for cell in &mut new_cells {
+                    cell.push(hi);
+                }
+                cells.append(&mut new_cells);
+            }
+        }
+
+        cells
+    }
+
+    /// Returns the index of a point in the set within epsilon of a given
+    /// point, if it exists.
+    pub fn find(&self, p: &Point) -> Option<usize> {
+        self.nearby_cells(p).into_iter().find_map(|cell| {
+            self.cells
+                .get(&cell)?
+                .iter()
+                .copied()
+                .find(|&idx| (&self.points[idx] - p).norm() < self.eps)
+        })
+    }
+
+    /// Inserts a point into the set, unless there's already a point within
+    /// epsilon of it. Returns the index of the point in the set, along with
+    /// whether it was newly inserted.
+    pub fn insert(&mut self, p: Point) -> (usize, bool) {
+        if let Some(idx) = self.find(&p) {
+            return (idx, false);
+        }
+
+        let idx = self.points.len();
+        self.cells.entry(self.cell(&p)).or_default().push(idx);
+        self.points.push(p);
+        (idx, true)
+    }
+
+    /// Removes near-duplicate points from a list, keeping the first of every
+    /// set of points within epsilon of one another. Returns the set of the
+    /// remaining points, along with the index in it of every original point.
+    pub fn dedup<T: IntoIterator<Item = Point>>(points: T, eps: Float) -> (Self, Vec<usize>) {
+        let mut set = Self::new(eps);
+        let indices = points.into_iter().map(|p| set.insert(p).0).collect();
+        (set, indices)
+    }
+}
+
+/// A [k-d tree](https://en.wikipedia.org/wiki/K-d_tree) over a list of
+/// points, which allows finding the points closest to any given one without
+/// comparing against all of them.
+///
+/// The tree is stored implicitly: the points in every range of `order` are
+/// split by the point at its middle, with the points before it having a
+/// smaller coordinate along the splitting axis, and those after it a larger
+/// one. The splitting axis cycles through the coordinates with the depth.
+#[derive(Clone, Debug)]
+pub struct KdTree {
+    /// The points in the tree, in the order they were given.
+    points: Vec<Point>,
+
+    /// The indices of the points, arranged as described above.
+    order: Vec<usize>,
+}
+
+impl KdTree {
+    /// Builds a k-d tree from a list of points, which should all have the same
+    /// dimension.
+    pub fn new(points: Vec<Point>) -> Self {
+        let mut order: Vec<_> = (0..points.len()).collect();
+        Self::build(&points, &mut order, 0);
+        Self { points, order }
+    }
+
+    /// Arranges the indices in a range so that they form a k-d tree at a
+    /// given depth.
+    fn build(points: &[Point], order: &mut [usize], depth: usize) {
+        if order.len() <= 1 {
+            return;
+        }
+
+        let axis = depth % points[order[0]].len();
+        let mid = order.len() / 2;
+        order.select_nth_unstable_by(mid, |&i, &j| {
+            points[i][axis]
+                .partial_cmp(&points[j][axis])
+                .expect("Coordinates must not be NaN.")
+        });
+
+        let (left, right) = order.split_at_mut(mid);
+        Self::build(points, left, depth + 1);
+        Self::build(points, &mut right[1..], depth + 1);
+    }
+
+    /// Returns the number of points in the tree.
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    /// Returns whether the tree is empty.
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// Returns the points in the tree, in the order they were given.
+    pub fn points(&self) -> &[Point] {
+        &self.points
+    }
+
+    /// Returns the index of the point in the tree closest to a given point,
+    /// along with their distance. Returns `None` if the tree is empty.
+    pub fn nearest(&self, p: &Point) -> Option<(usize, Float)> {
+        let mut best = None;
+        self.nearest_in(p, 0, self.len(), 0, &mut best);
+        best
+    }
+
+    /// Updates the closest point found so far with the points in a range of
+    /// the tree at a given depth.
+    fn nearest_in(
+        &self,
+        p: &Point,
+        lo: usize,
+        hi: usize,
+        depth: usize,
+        best: &mut Option<(usize, Float)>,
+    ) {
+        if lo >= hi {
+            return;
+        }
+
+        let mid = lo + (hi - lo) / 2;
+        let idx = self.order[mid];
+        let dist = (&self.points[idx] - p).norm();
+        if !matches!(*best, Some((_, best_dist)) if best_dist <= dist) {
+            *best = Some((idx, dist));
+        }
+
+        let axis = depth % p.len();
+        let diff = p[axis] - self.points[idx][axis];
+        let (near, far) = if diff < 0.0 {
+            ((lo, mid), (mid + 1, hi))
+        } else {
+            ((mid + 1, hi), (lo, mid))
+        };
+
+        self.nearest_in(p, near.0, near.1, depth + 1, best);
+        if !matches!(*best, Some((_, best_dist)) if best_dist <= diff.abs()) {
+            self.nearest_in(p, far.0, far.1, depth + 1, best);
+        }
+    }
+
+    /// Returns the sorted indices of the points in the tree at distance at
+    /// most `radius` from a given point.
+    pub fn within_radius(&self, p: &Point, radius: Float) -> Vec<usize> {
+        let mut found = Vec::new();
+        self.within_radius_in(p, radius, 0, self.len(), 0, &mut found);
+        found.sort_unstable();
+        found
+    }
+
+    /// Adds the points at distance at most `radius` from a given point in a
+    /// range of the tree at a given depth.
+    fn within_radius_in(
+        &self,
+        p: &Point,
+        radius: Float,
+        lo: usize,
+        hi: usize,
+        depth: usize,
+        found: &mut Vec<usize>,
+    ) {
+        if lo >= hi {
+            return;
+        }
+
+        let mid = lo + (hi - lo) / 2;
+        let idx = self.order[mid];
+        if (&self.points[idx] - p).norm() <= radius {
+            found.push(idx);
+        }
+
+        let axis = depth % p.len();
+        let diff = p[axis] - self.points[idx][axis];
+        if diff <= radius {
+            self.within_radius_in(p, radius, lo, mid, depth + 1, found);
+        }
+        if diff >= -radius {
+            self.within_radius_in(p, radius, mid + 1, hi, depth + 1, found);
+        }
+    }
+}
+
 /// A matrix with a given number of rows and columns.
 type MatrixMxN<R, C> = nalgebra::Matrix<Float, R, C, VecStorage<Float, R, C>>;
 
@@ -472,6 +746,56 @@ mod tests {
         assert_abs_diff_eq!((p - q).norm(), 0.0, epsilon = Float::EPS)
     }
 
+    #[test]
+    /// Removes near-duplicate points, including some that lie on different
+    /// sides of the boundaries of cells.
+    fn point_set() {
+        let eps = 1e-6;
+        let offset = eps * (CELL_FACTOR * (1.0 - CELL_OFFSET));
+        let points = vec![
+            dvector![0.0, 0.0],
+            dvector![offset - eps / 4.0, 1.0],
+            dvector![eps / 2.0, 0.0],
+            dvector![offset + eps / 4.0, 1.0],
+            dvector![1.0, 1.0],
+        ];
+
+        let (set, indices) = PointSet::dedup(points, eps);
+        assert_eq!(set.len(), 3);
+        assert_eq!(indices, vec![0, 1, 0, 1, 2]);
+        assert_eq!(set.find(&dvector![1.0, 1.0 - eps / 2.0]), Some(2));
+        assert_eq!(set.find(&dvector![1.0, 1.0 - 2.0 * eps]), None);
+    }
+
+    #[test]
+    /// Compares the queries on a k-d tree against a brute force search.
+    fn kd_tree() {
+        // A simple deterministic sequence of points in the unit cube.
+        let mut seed: u64 = 1;
+        let mut next = || {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+            (seed >> 11) as Float / (1u64 << 53) as Float
+        };
+        let points: Vec<Point> = (0..200).map(|_| dvector![next(), next(), next()]).collect();
+        let tree = KdTree::new(points.clone());
+
+        for _ in 0..50 {
+            let p = dvector![next(), next(), next()];
+            let dists: Vec<_> = points.iter().map(|q| (q - &p).norm()).collect();
+
+            let (idx, dist) = tree.nearest(&p).unwrap();
+            let min = dists.iter().cloned().fold(Float::INFINITY, Float::min);
+            assert_eq!(dist, min);
+            assert_eq!(dists[idx], min);
+
+            let radius = 0.2;
+            let expected: Vec<_> = (0..points.len()).filter(|&i| dists[i] <= radius).collect();
+            assert_eq!(tree.within_radius(&p, radius), expected);
+        }
+
+        assert_eq!(KdTree::new(Vec::new()).nearest(&dvector![0.0]), None);
+    }
+
     #[test]
     /// Reciprocates points about spheres.
     pub fn reciprocate() {
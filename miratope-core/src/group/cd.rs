@@ -1,13 +1,18 @@
 //! Contains methods to parse and generate Coxeter diagrams and matrices.
 
-use std::{collections::VecDeque, fmt::Display, iter, mem, str::FromStr};
+#[cfg(feature = "cd")]
+use std::{collections::VecDeque, iter, str::FromStr};
+use std::{fmt::Display, mem};
 
+#[cfg(feature = "cd")]
+use crate::geometry::{Point, Vector};
 use crate::{
-    geometry::{Matrix, MatrixOrd, Point, Vector},
+    geometry::{Matrix, MatrixOrd},
     Consts, Float, FloatOrd,
 };
 
 use nalgebra::{dmatrix, Dynamic, VecStorage};
+#[cfg(feature = "cd")]
 use petgraph::{
     graph::{Edge as GraphEdge, Graph, Node as GraphNode, NodeIndex},
     Undirected,
@@ -135,6 +140,7 @@ impl CoxMatrix {
     }
 
     /// Parses a [`Cd`] and turns it into a Coxeter matrix.
+    #[cfg(feature = "cd")]
     pub fn parse(input: &str) -> CdResult<Self> {
         Cd::parse(input).map(|cd| cd.cox())
     }
@@ -189,7 +195,9 @@ impl CoxMatrix {
     }
 
     /// Returns an upper triangular matrix whose columns are unit normal vectors
-    /// for the hyperplanes described by the Coxeter matrix.
+    /// for the hyperplanes described by the Coxeter matrix. The normals point
+    /// into the fundamental domain, so that the angle between the ith and jth
+    /// ones is π - π / m, where m is the (i, j) entry of the matrix.
     pub fn normals(&self) -> Option<Matrix> {
         let dim = self.dim();
         let mut mat = Matrix::zeros(dim, dim);
@@ -202,7 +210,7 @@ impl CoxMatrix {
             for (j, n_j) in prev_gens.column_iter().enumerate() {
                 // All other entries in the dot product are zero.
                 let dot = n_i.rows_range(0..=j).dot(&n_j.rows_range(0..=j));
-                n_i[j] = ((Float::PI / self[(i, j)]).cos() - dot) / n_j[j];
+                n_i[j] = (-(Float::PI / self[(i, j)]).cos() - dot) / n_j[j];
             }
 
             // If the vector doesn't fit in spherical space.
@@ -375,6 +383,7 @@ impl Display for Edge {
 ///
 /// This is necessary since we can't figure out what node a virtual node like
 /// `*-a` is referring to unless we've read the entire diagram already.
+#[cfg(feature = "cd")]
 #[derive(Clone, Copy)]
 pub enum NodeRef {
     /// The index of a node.
@@ -384,6 +393,7 @@ pub enum NodeRef {
     Negative(usize),
 }
 
+#[cfg(feature = "cd")]
 impl NodeRef {
     /// Initializes a new node reference from an index. The `neg` parameter
     /// determines if indexing should be [`Negative`](Self::Negative) or
@@ -407,6 +417,7 @@ impl NodeRef {
 }
 
 /// Stores the [`NodeRef`]s of both ends of an edge, along with its value.
+#[cfg(feature = "cd")]
 pub struct EdgeRef {
     /// The reference to the first node in the edge.
     first: NodeRef,
@@ -418,6 +429,7 @@ pub struct EdgeRef {
     edge: Edge,
 }
 
+#[cfg(feature = "cd")]
 impl EdgeRef {
     /// Initializes a new edge reference from its fields.
     pub fn new(first: NodeRef, other: NodeRef, edge: Edge) -> Self {
@@ -460,6 +472,7 @@ impl EdgeRef {
 ///
 /// * A single integer, like `3` or `15`.
 /// * Two integers separated by a backslash, like `5/2` or `7/3`.
+#[cfg(feature = "cd")]
 pub struct CdBuilder<'a> {
     /// The Coxeter diagram in inline ASCII notation.
     diagram: &'a str,
@@ -485,6 +498,7 @@ pub struct CdBuilder<'a> {
 }
 
 /// Operations that are commonly done to parse CDs.
+#[cfg(feature = "cd")]
 impl<'a> CdBuilder<'a> {
     /// Initializes a new CD builder from a string.
     fn new(diagram: &'a str) -> Self {
@@ -758,9 +772,11 @@ impl<'a> CdBuilder<'a> {
 /// perpendicular.
 ///
 /// To actually build a Coxeter diagram, we use a [`CdBuilder`].
+#[cfg(feature = "cd")]
 #[derive(Default)]
 pub struct Cd(Graph<Node, Edge, Undirected>);
 
+#[cfg(feature = "cd")]
 impl Cd {
     /// Initializes a new Coxeter diagram with no nodes nor edges.
     pub fn new() -> Self {
@@ -899,23 +915,29 @@ impl Cd {
     }
 
     /// Returns a point in the position specified by the Coxeter diagram,
-    /// using the set of mirrors generated by [`CoxMatrix::normals`].    
+    /// using the set of mirrors generated by [`CoxMatrix::normals`].
     pub fn generator(&self) -> Option<Point> {
         let normals = self.cox().normals()?;
-        let mut vector = self.node_vector();
+
+        // The distance from the generator to each hyperplane is half the node
+        // value, and the normals are stored as columns.
+        let mut vector = self.node_vector() / 2.0;
 
         normals
-            .solve_upper_triangular_mut(&mut vector)
+            .transpose()
+            .solve_lower_triangular_mut(&mut vector)
             .then(|| vector)
     }
 }
 
+#[cfg(feature = "cd")]
 impl From<Cd> for CoxMatrix {
     fn from(cd: Cd) -> Self {
         cd.cox()
     }
 }
 
+#[cfg(feature = "cd")]
 impl Display for Cd {
     /// Prints the node and edge count, along with the value each node and edge contains
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -937,9 +959,10 @@ impl Display for Cd {
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "cd"))]
 mod tests {
     use super::*;
+    use approx::abs_diff_eq;
     use nalgebra::dmatrix;
 
     /// Returns a ringed node at half-unit distance.
@@ -1107,6 +1130,33 @@ mod tests {
         )
     }
 
+    #[test]
+    /// Tests that the generators of some diagrams with unit ringed nodes lie
+    /// at the circumradius of the corresponding unit edge polytopes.
+    fn circumradius() {
+        for (diagram, radius) in [
+            ("x", 0.5),
+            ("x3o", 1.0 / Float::SQRT_3),
+            ("x4o", Float::SQRT_2 / 2.0),
+            ("x4o3o", Float::SQRT_3 / 2.0),
+            ("o3x3o", Float::SQRT_2 / 2.0),
+            ("x3x3x", (10.0 as Float).sqrt() / 2.0),
+            ("x3o3o3o", (0.4 as Float).sqrt()),
+        ] {
+            let circumradius = Cd::parse(diagram).unwrap().circumradius().unwrap();
+            assert!(
+                abs_diff_eq!(circumradius, radius, epsilon = Float::EPS),
+                "Expected circumradius {} for {}, found {}.",
+                radius,
+                diagram,
+                circumradius
+            );
+        }
+
+        // The triangular tiling doesn't have a circumradius.
+        assert_eq!(Cd::parse("x3o3o *a3*c").unwrap().circumradius(), None);
+    }
+
     #[test]
     #[should_panic(expected = "MismatchedParenthesis { pos: 6 }")]
     fn mismatched_parenthesis() {
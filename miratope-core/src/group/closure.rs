@@ -0,0 +1,192 @@
+//! Generates finite groups of matrices in a way that's robust to floating
+//! point errors.
+//!
+//! Multiplying floating point matrices accumulates errors, which can cause a
+//! group to never close, as elements fail to match their previous copies. To
+//! avoid this, every product is snapped back onto the orthogonal matrices, or
+//! onto the integer matrices when all generators have integer entries, as is
+//! the case for crystallographic groups in their standard bases. Elements are
+//! then matched up to a tolerance through a [`PointSet`].
+
+use super::Group;
+use crate::{
+    geometry::{Matrix, Point, PointSet},
+    Consts, Float,
+};
+
+/// Any error encountered while generating a group.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GroupError {
+    /// The generator with the given index isn't a square matrix of the
+    /// dimension of the group.
+    Dimension(usize),
+
+    /// The generator with the given index isn't orthogonal, so it can't
+    /// belong to a finite group acting isometrically.
+    NotOrthogonal(usize),
+
+    /// The group has more elements than the given maximum order, which
+    /// happens in particular when it's infinite.
+    Infinite(usize),
+}
+
+impl std::fmt::Display for GroupError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Dimension(idx) => write!(f, "generator {} has the wrong dimension", idx),
+            Self::NotOrthogonal(idx) => write!(f, "generator {} isn't orthogonal", idx),
+            Self::Infinite(max_order) => write!(
+                f,
+                "group has more than {} elements, so it's probably infinite",
+                max_order
+            ),
+        }
+    }
+}
+
+impl std::error::Error for GroupError {}
+
+/// The result of generating a group.
+pub type GroupResult<T> = Result<T, GroupError>;
+
+/// The tolerance used to check orthogonality and to match elements. This is
+/// much larger than [`Float::EPS`], as the errors it accounts for are
+/// compounded over many products.
+fn tolerance() -> Float {
+    Float::EPS.sqrt()
+}
+
+/// Returns whether every entry of a matrix is within tolerance of an integer.
+fn is_integral(mat: &Matrix) -> bool {
+    mat.iter().all(|x| (x - x.round()).abs() < tolerance())
+}
+
+/// Returns the orthogonal matrix obtained by applying the Gram–Schmidt
+/// process to the columns of a matrix. For a matrix that's almost orthogonal,
+/// this is very close to it.
+fn orthonormalize(mut mat: Matrix) -> Matrix {
+    for j in 0..mat.ncols() {
+        for k in 0..j {
+            let proj = mat.column(j).dot(&mat.column(k));
+            let col_k = mat.column(k).clone_owned();
+            mat.column_mut(j).axpy(-proj, &col_k, 1.0);
+        }
+
+        let norm = mat.column(j).norm();
+        mat.column_mut(j).unscale_mut(norm);
+    }
+
+    mat
+}
+
+impl Group {
+    /// Generates the finite group of orthogonal matrices with the given
+    /// generators, snapping every product back onto the orthogonal matrices
+    /// (or onto the integer matrices if every generator has integer entries)
+    /// so that errors don't accumulate.
+    ///
+    /// Returns an error if some generator doesn't have the given dimension or
+    /// isn't orthogonal, or if the group has more than `max_order` elements,
+    /// as happens when it's infinite.
+    pub fn closure(dim: usize, gens: Vec<Matrix>, max_order: usize) -> GroupResult<Self> {
+        let identity = Matrix::identity(dim, dim);
+        for (idx, gen) in gens.iter().enumerate() {
+            if gen.nrows() != dim || gen.ncols() != dim {
+                return Err(GroupError::Dimension(idx));
+            }
+            if (gen.transpose() * gen - &identity).norm() > tolerance() {
+                return Err(GroupError::NotOrthogonal(idx));
+            }
+        }
+
+        let integral = gens.iter().all(is_integral);
+        let snap = |mat: Matrix| {
+            if integral {
+                mat.map(Float::round)
+            } else {
+                orthonormalize(mat)
+            }
+        };
+        let gens: Vec<_> = gens.into_iter().map(snap).collect();
+
+        // The elements found so far, in BFS order, also stored as points so
+        // that they can be matched.
+        let mut found = PointSet::new(tolerance());
+        let mut elements = Vec::new();
+        found.insert(Point::from_column_slice(identity.as_slice()));
+        elements.push(identity);
+
+        let mut idx = 0;
+        while idx < elements.len() {
+            for gen in &gens {
+                let el = snap(&elements[idx] * gen);
+                if found.insert(Point::from_column_slice(el.as_slice())).1 {
+                    if elements.len() == max_order {
+                        return Err(GroupError::Infinite(max_order));
+                    }
+                    elements.push(el);
+                }
+            }
+
+            idx += 1;
+        }
+
+        Ok(Self::new(dim, elements.into_iter()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::group::{cd::CoxMatrix, refl_mat};
+
+    /// Returns the matrix of a rotation by some angle in the plane.
+    fn rotation(angle: Float) -> Matrix {
+        let (sin, cos) = angle.sin_cos();
+        Matrix::from_row_slice(2, 2, &[cos, -sin, sin, cos])
+    }
+
+    #[test]
+    fn float() {
+        let normals = CoxMatrix::from_lin_diagram(vec![5.0, 3.0, 3.0])
+            .normals()
+            .unwrap();
+        let gens = normals.column_iter().map(refl_mat).collect();
+        assert_eq!(Group::closure(4, gens, 20000).unwrap().order(), 14400);
+
+        for n in 3..20 {
+            let gens = vec![rotation(Float::TAU / n as Float)];
+            assert_eq!(Group::closure(2, gens, 100).unwrap().order(), n);
+        }
+    }
+
+    #[test]
+    fn crystallographic() {
+        let mut gens = vec![Matrix::identity(3, 3); 3];
+        gens[0][(0, 0)] = -1.0;
+        gens[1].swap_rows(0, 1);
+        gens[2].swap_rows(1, 2);
+
+        let elements = Group::closure(3, gens, 100).unwrap().elements();
+        assert_eq!(elements.len(), 48);
+        for el in elements {
+            assert!(el.iter().all(|x| *x == x.round()));
+        }
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            Group::closure(2, vec![rotation(1.0)], 1000).err(),
+            Some(GroupError::Infinite(1000))
+        );
+        assert_eq!(
+            Group::closure(2, vec![rotation(1.0) * 2.0], 1000).err(),
+            Some(GroupError::NotOrthogonal(0))
+        );
+        assert_eq!(
+            Group::closure(3, vec![Matrix::identity(3, 3), rotation(1.0)], 1000).err(),
+            Some(GroupError::Dimension(1))
+        );
+    }
+}
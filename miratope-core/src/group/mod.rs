@@ -1,17 +1,23 @@
 //! Contains methods to generate many symmetry groups.
 
 pub mod cd;
+pub mod closure;
+pub mod orbifold;
+pub mod space;
 
 use std::{
-    collections::{BTreeMap, BTreeSet, VecDeque},
+    collections::{BTreeMap, VecDeque},
     iter,
 };
 
 use crate::{
-    geometry::{Matrix, MatrixOrd, Point, PointOrd, VectorSlice},
+    conc::{convex, Concrete},
+    geometry::{Matrix, MatrixOrd, Point, PointOrd, PointSet, VectorSlice},
     Consts, Float,
 };
-use cd::{Cd, CdResult, CoxMatrix};
+use cd::CoxMatrix;
+#[cfg(feature = "cd")]
+use cd::{Cd, CdResult};
 
 use approx::relative_eq;
 use nalgebra::{Dynamic, Quaternion, VecStorage};
@@ -241,6 +247,7 @@ impl Group {
     }
 
     /// Parses a [`Cd`] and turns it into a group.
+    #[cfg(feature = "cd")]
     pub fn parse(input: &str) -> CdResult<Option<Self>> {
         Cd::parse(input).map(|cd| Self::cox_group(cd.cox()))
     }
@@ -250,6 +257,7 @@ impl Group {
     /// # Panics
     /// Panics if either the Coxeter diagram is invalid, or if doesn't describe
     /// a valid group.
+    #[cfg(feature = "cd")]
     pub fn parse_unwrap(input: &str) -> Self {
         Self::parse(input).unwrap().unwrap()
     }
@@ -413,21 +421,22 @@ impl Group {
 
     /// Generates the orbit of a point under a given symmetry group.
     pub fn orbit(self, p: Point) -> Vec<Point> {
-        let mut points = BTreeSet::new();
+        let mut points = PointSet::new(Float::EPS);
 
         for m in self {
-            points.insert(PointOrd::new(m * &p));
+            points.insert(m * &p);
         }
 
-        points.into_iter().map(|x| x.0).collect()
+        let mut points = points.into_points();
+        points.sort_by_cached_key(|p| PointOrd::new(p.clone()));
+        points
     }
 
-    // Generates a polytope as the convex hull of the orbit of a point under a
-    // given symmetry group.
-    /* pub fn into_polytope(self, _: Point) -> Concrete {
-        todo!()
-        // convex::convex_hull(self.orbit(p))
-    } */
+    /// Generates a polytope as the convex hull of the orbit of a point under a
+    /// given symmetry group.
+    pub fn into_polytope(self, p: Point) -> Concrete {
+        convex::convex_hull(self.orbit(p))
+    }
 }
 
 /// The result of trying to get the next element in a group.
@@ -732,6 +741,7 @@ mod tests {
     /// Tests the H*n* symmetries, which correspond to the symmetries of a
     /// regular dodecahedron and a regular hecatonicosachoron.
     #[test]
+    #[cfg(feature = "cd")]
     fn h() {
         test(Group::parse_unwrap("o5o3o"), 120, 60, &"H3");
         test(Group::parse_unwrap("o5o3o3o"), 14400, 7200, &"H4");
@@ -739,6 +749,7 @@ mod tests {
 
     /// Tests the E6 symmetry group.
     #[test]
+    #[cfg(feature = "cd")]
     fn e6() {
         test(Group::parse_unwrap("o3o3o3o3o *c3o"), 51840, 25920, &"E6");
     }
@@ -746,7 +757,7 @@ mod tests {
     /// Tests the E7 symmetry group. This is very expensive, so we enable it
     /// only on release mode.
     #[test]
-    #[cfg(not(debug_assertions))]
+    #[cfg(all(not(debug_assertions), feature = "cd"))]
     fn e7() {
         test(
             Group::parse_unwrap("o3o3o3o3o3o *c3o"),
@@ -759,7 +770,7 @@ mod tests {
     #[test]
     /// Tests the direct product of A3 with itself.
     fn a3xa3() {
-        let a3 = Group::parse_unwrap("o3o3o");
+        let a3 = Group::a(3);
         let g = Group::direct_product(a3.clone(), a3.clone());
         test(g, 576, 288, &"A3×A3");
     }
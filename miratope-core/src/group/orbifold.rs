@@ -0,0 +1,464 @@
+//! Parses Conway's orbifold notation for the point groups in two and three
+//! dimensions.
+//!
+//! An orbifold symbol lists the orders of the gyration points, then a star
+//! followed by the orders of the kaleidoscopic corners, then a cross for every
+//! crosscap. For instance, `*432` is the full octahedral group, `3*2` is the
+//! pyritohedral group and `22n` is the dihedral group of order `2n`. Numbers
+//! with more than one digit are written within parentheses, as in `*22(12)`.
+//!
+//! The symbols `n` and `*n` describe bad orbifolds, which don't correspond to
+//! any spherical group. These are instead read as the cyclic and dihedral
+//! groups of the plane, while every other symbol gives a group in 3D.
+
+use std::fmt::Display;
+
+use super::{cd::CoxMatrix, Group};
+use crate::{geometry::Matrix, Consts, Float};
+
+/// Represents an error while parsing an orbifold symbol.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OrbifoldError {
+    /// A parenthesis was opened but not closed.
+    MismatchedParenthesis {
+        /// The position at which the reader found the error.
+        pos: usize,
+    },
+
+    /// A number couldn't be parsed.
+    ParseError {
+        /// The position at which the reader found the error.
+        pos: usize,
+    },
+
+    /// An invalid symbol was found.
+    InvalidSymbol {
+        /// The position at which the reader found the error.
+        pos: usize,
+    },
+
+    /// The symbol is valid, but doesn't describe a point group.
+    NotSpherical,
+}
+
+impl Display for OrbifoldError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            // A parenthesis was opened but not closed.
+            Self::MismatchedParenthesis { pos } => {
+                write!(f, "mismatched parenthesis at position {}", pos)
+            }
+
+            // A number couldn't be parsed.
+            Self::ParseError { pos } => write!(f, "parsing failed at position {}", pos),
+
+            // An invalid symbol was found.
+            Self::InvalidSymbol { pos } => write!(f, "invalid symbol found at position {}", pos),
+
+            // The symbol doesn't describe a point group.
+            Self::NotSpherical => write!(f, "orbifold doesn't describe a point group"),
+        }
+    }
+}
+
+impl std::error::Error for OrbifoldError {}
+
+/// The result of parsing an orbifold symbol.
+pub type OrbifoldResult<T> = Result<T, OrbifoldError>;
+
+/// A parsed orbifold symbol.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Orbifold {
+    /// The orders of the gyration points, before the star.
+    pub gyrations: Vec<u32>,
+
+    /// The orders of the kaleidoscopic corners after the star, or `None` if
+    /// there's no star.
+    pub corners: Option<Vec<u32>>,
+
+    /// The number of crosscaps.
+    pub crosses: usize,
+}
+
+/// The shape of a point group, as determined by its orbifold symbol. Orders of
+/// 1 have already been discarded.
+enum Shape {
+    /// The cyclic group of the plane with a given order.
+    Cyclic2(u32),
+
+    /// The dihedral group of the plane with a given number of mirrors.
+    Dihedral2(u32),
+
+    /// `nn`, the rotations about an axis.
+    Cyclic(u32),
+
+    /// `*nn`, the symmetries of a pyramid.
+    Pyramidal(u32),
+
+    /// `n*`, the rotations about an axis together with the perpendicular
+    /// mirror.
+    Horizontal(u32),
+
+    /// `n×`, the powers of a rotoreflection.
+    Rotoreflection(u32),
+
+    /// `22n`, the rotations of a prism.
+    Dihedral(u32),
+
+    /// `*22n`, the symmetries of a prism.
+    Prismatic(u32),
+
+    /// `2*n`, the symmetries of an antiprism.
+    Antiprismatic(u32),
+
+    /// `332`, the rotations of a tetrahedron.
+    Tetrahedral,
+
+    /// `*332`, the symmetries of a tetrahedron.
+    FullTetrahedral,
+
+    /// `3*2`, the symmetries of a pyritohedron.
+    Pyritohedral,
+
+    /// `432`, the rotations of a cube.
+    Octahedral,
+
+    /// `*432`, the symmetries of a cube.
+    FullOctahedral,
+
+    /// `532`, the rotations of a dodecahedron.
+    Icosahedral,
+
+    /// `*532`, the symmetries of a dodecahedron.
+    FullIcosahedral,
+}
+
+impl Orbifold {
+    /// Parses an orbifold symbol. The star may be written as `*`, and the
+    /// cross as `×`, `x` or `X`. Whitespace is ignored.
+    pub fn parse(input: &str) -> OrbifoldResult<Self> {
+        let mut orbifold = Self::default();
+        let mut chars = input.char_indices();
+
+        while let Some((pos, c)) = chars.next() {
+            let num = match c {
+                c if c.is_whitespace() => continue,
+                '*' => {
+                    if orbifold.corners.is_some() || orbifold.crosses != 0 {
+                        return Err(OrbifoldError::NotSpherical);
+                    }
+                    orbifold.corners = Some(Vec::new());
+                    continue;
+                }
+                'x' | 'X' | '×' => {
+                    orbifold.crosses += 1;
+                    continue;
+                }
+                '(' => {
+                    let mut digits = String::new();
+                    loop {
+                        match chars.next() {
+                            Some((_, ')')) => break,
+                            Some((_, c)) => digits.push(c),
+                            None => return Err(OrbifoldError::MismatchedParenthesis { pos }),
+                        }
+                    }
+
+                    digits
+                        .trim()
+                        .parse()
+                        .map_err(|_| OrbifoldError::ParseError { pos })?
+                }
+                c => c.to_digit(10).ok_or(OrbifoldError::InvalidSymbol { pos })?,
+            };
+
+            if num == 0 {
+                return Err(OrbifoldError::ParseError { pos });
+            }
+            if orbifold.crosses != 0 {
+                return Err(OrbifoldError::NotSpherical);
+            }
+
+            match &mut orbifold.corners {
+                Some(corners) => corners.push(num),
+                None => orbifold.gyrations.push(num),
+            }
+        }
+
+        Ok(orbifold)
+    }
+
+    /// Returns whether the symbol is one of the bad orbifolds `n` or `*n`,
+    /// which are read as groups of the plane.
+    fn is_planar(&self) -> bool {
+        self.crosses == 0
+            && match &self.corners {
+                None => self.gyrations.len() == 1,
+                Some(corners) => self.gyrations.is_empty() && corners.len() == 1,
+            }
+    }
+
+    /// Determines which point group the symbol describes.
+    fn shape(&self) -> OrbifoldResult<Shape> {
+        if self.is_planar() {
+            return Ok(match &self.corners {
+                None => Shape::Cyclic2(self.gyrations[0]),
+                Some(corners) => Shape::Dihedral2(corners[0]),
+            });
+        }
+
+        // Points of order 1 are just regular points.
+        let sorted = |list: &[u32]| {
+            let mut list: Vec<_> = list.iter().copied().filter(|&n| n != 1).collect();
+            list.sort_unstable();
+            list
+        };
+        let gyrations = sorted(&self.gyrations);
+
+        Ok(match (&self.corners, self.crosses) {
+            (None, 0) => match gyrations.as_slice() {
+                [] => Shape::Cyclic(1),
+                &[m, n] if m == n => Shape::Cyclic(n),
+                &[2, 2, n] => Shape::Dihedral(n),
+                [2, 3, 3] => Shape::Tetrahedral,
+                [2, 3, 4] => Shape::Octahedral,
+                [2, 3, 5] => Shape::Icosahedral,
+                _ => return Err(OrbifoldError::NotSpherical),
+            },
+            (None, 1) => match gyrations.as_slice() {
+                [] => Shape::Rotoreflection(1),
+                &[n] => Shape::Rotoreflection(n),
+                _ => return Err(OrbifoldError::NotSpherical),
+            },
+            (Some(corners), 0) => match (gyrations.as_slice(), sorted(corners).as_slice()) {
+                ([], []) => Shape::Horizontal(1),
+                ([], &[m, n]) if m == n => Shape::Pyramidal(n),
+                ([], &[2, 2, n]) => Shape::Prismatic(n),
+                ([], [2, 3, 3]) => Shape::FullTetrahedral,
+                ([], [2, 3, 4]) => Shape::FullOctahedral,
+                ([], [2, 3, 5]) => Shape::FullIcosahedral,
+                (&[n], []) => Shape::Horizontal(n),
+                ([2], &[n]) => Shape::Antiprismatic(n),
+                ([3], [2]) => Shape::Pyritohedral,
+                _ => return Err(OrbifoldError::NotSpherical),
+            },
+            _ => return Err(OrbifoldError::NotSpherical),
+        })
+    }
+
+    /// Returns the dimension of the group described by the symbol.
+    pub fn dim(&self) -> usize {
+        if self.is_planar() {
+            2
+        } else {
+            3
+        }
+    }
+
+    /// Returns the Euler characteristic of the orbifold. This is positive for
+    /// the point groups, zero for the groups of the Euclidean plane, and
+    /// negative for those of the hyperbolic plane.
+    pub fn euler_characteristic(&self) -> Float {
+        let mut euler = 2.0 - self.crosses as Float;
+        for &n in &self.gyrations {
+            euler -= (n - 1) as Float / n as Float;
+        }
+        if let Some(corners) = &self.corners {
+            euler -= 1.0;
+            for &n in corners {
+                euler -= (n - 1) as Float / (2 * n) as Float;
+            }
+        }
+
+        euler
+    }
+
+    /// Returns the order of the group described by the symbol, or `None` if
+    /// it doesn't describe a point group.
+    ///
+    /// For symbols in 3D, this is computed through Conway's magic theorem:
+    /// the order is 2 divided by the Euler characteristic of the orbifold.
+    pub fn order(&self) -> Option<usize> {
+        self.shape().ok()?;
+        if self.is_planar() {
+            return Some(match &self.corners {
+                None => self.gyrations[0] as usize,
+                Some(corners) => 2 * corners[0] as usize,
+            });
+        }
+
+        Some((2.0 / self.euler_characteristic()).round() as usize)
+    }
+
+    /// Builds the group described by the symbol.
+    pub fn group(&self) -> OrbifoldResult<Group> {
+        let shape = self.shape()?;
+        let gens = match shape {
+            Shape::Cyclic2(n) => vec![rotation_2d(n)],
+            Shape::Dihedral2(n) => vec![rotation_2d(n), diagonal(&[1.0, -1.0])],
+            Shape::Cyclic(n) => vec![rotation_z(n)],
+            Shape::Pyramidal(n) => vec![rotation_z(n), diagonal(&[1.0, -1.0, 1.0])],
+            Shape::Horizontal(n) => vec![rotation_z(n), diagonal(&[1.0, 1.0, -1.0])],
+            Shape::Rotoreflection(n) => vec![rotation_z(2 * n) * diagonal(&[1.0, 1.0, -1.0])],
+            Shape::Dihedral(n) => vec![rotation_z(n), diagonal(&[1.0, -1.0, -1.0])],
+            Shape::Prismatic(n) => vec![
+                rotation_z(n),
+                diagonal(&[1.0, -1.0, -1.0]),
+                diagonal(&[1.0, 1.0, -1.0]),
+            ],
+            Shape::Antiprismatic(n) => vec![
+                rotation_z(2 * n) * diagonal(&[1.0, 1.0, -1.0]),
+                diagonal(&[1.0, -1.0, -1.0]),
+            ],
+            Shape::Tetrahedral => tetrahedral(),
+            Shape::FullTetrahedral => {
+                let mut gens = tetrahedral();
+                gens.push(permutation([1, 0, 2]));
+                gens
+            }
+            Shape::Pyritohedral => {
+                let mut gens = tetrahedral();
+                gens.push(-Matrix::identity(3, 3));
+                gens
+            }
+            Shape::Octahedral | Shape::FullOctahedral => {
+                let mut gens = vec![permutation([1, 2, 0]), rotation_z(4)];
+                if let Shape::FullOctahedral = shape {
+                    gens.push(-Matrix::identity(3, 3));
+                }
+                gens
+            }
+            Shape::Icosahedral | Shape::FullIcosahedral => {
+                let group = icosahedral().ok_or(OrbifoldError::NotSpherical)?;
+                return Ok(if let Shape::Icosahedral = shape {
+                    group.rotations()
+                } else {
+                    group
+                });
+            }
+        };
+
+        let max_order = self.order().unwrap_or_default();
+        Group::closure(self.dim(), gens, max_order).map_err(|_| OrbifoldError::NotSpherical)
+    }
+}
+
+/// Returns the rotation of the plane of a given order.
+fn rotation_2d(n: u32) -> Matrix {
+    let (sin, cos) = (Float::TAU / n as Float).sin_cos();
+    Matrix::from_row_slice(2, 2, &[cos, -sin, sin, cos])
+}
+
+/// Returns the rotation of a given order about the z-axis.
+fn rotation_z(n: u32) -> Matrix {
+    let (sin, cos) = (Float::TAU / n as Float).sin_cos();
+    Matrix::from_row_slice(3, 3, &[cos, -sin, 0.0, sin, cos, 0.0, 0.0, 0.0, 1.0])
+}
+
+/// Returns a diagonal matrix with the given entries.
+fn diagonal(entries: &[Float]) -> Matrix {
+    Matrix::from_diagonal(&crate::geometry::Vector::from_column_slice(entries))
+}
+
+/// Returns the matrix that sends every coordinate to the given index.
+fn permutation(perm: [usize; 3]) -> Matrix {
+    let mut mat = Matrix::zeros(3, 3);
+    for (i, &j) in perm.iter().enumerate() {
+        mat[(j, i)] = 1.0;
+    }
+    mat
+}
+
+/// Returns generators for the rotations of the tetrahedron whose vertices are
+/// alternate vertices of the cube.
+fn tetrahedral() -> Vec<Matrix> {
+    vec![permutation([1, 2, 0]), diagonal(&[-1.0, -1.0, 1.0])]
+}
+
+/// Returns the full icosahedral group, as the Coxeter group of the
+/// dodecahedron.
+fn icosahedral() -> Option<Group> {
+    let normals = CoxMatrix::from_lin_diagram(vec![5.0, 3.0]).normals()?;
+    let gens = normals.column_iter().map(super::refl_mat).collect();
+    Group::closure(3, gens, 120).ok()
+}
+
+impl Group {
+    /// Parses an orbifold symbol and turns it into a group.
+    pub fn parse_orbifold(input: &str) -> OrbifoldResult<Self> {
+        Orbifold::parse(input)?.group()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tests that a symbol gives a group with a given order and number of
+    /// rotations, and that the magic theorem agrees.
+    fn test(input: &str, order: usize, rot_order: usize) {
+        let orbifold = Orbifold::parse(input).unwrap();
+        assert_eq!(orbifold.order(), Some(order), "{}", input);
+
+        let group = orbifold.group().unwrap().cache();
+        assert_eq!(group.clone().order(), order, "{}", input);
+        assert_eq!(group.rotations().order(), rot_order, "{}", input);
+    }
+
+    #[test]
+    fn polyhedral() {
+        test("332", 12, 12);
+        test("*332", 24, 12);
+        test("3*2", 24, 12);
+        test("432", 24, 24);
+        test("*432", 48, 24);
+        test("532", 60, 60);
+        test("*532", 120, 60);
+    }
+
+    #[test]
+    fn axial() {
+        for n in 1..8 {
+            let n2 = 2 * n;
+            test(&format!("({})({})", n, n), n, n);
+            test(&format!("*({})({})", n, n), n2, n);
+            test(&format!("({})*", n), n2, n);
+            test(&format!("({})x", n), n2, n);
+            test(&format!("22({})", n), n2, n2);
+            test(&format!("*22({})", n), 2 * n2, n2);
+            test(&format!("2*({})", n), 2 * n2, n2);
+        }
+
+        test("*", 2, 1);
+        test("×", 2, 1);
+        test("*22(12)", 48, 24);
+    }
+
+    #[test]
+    fn planar() {
+        let group = Group::parse_orbifold("5").unwrap();
+        assert_eq!(group.dim, 2);
+        assert_eq!(group.order(), 5);
+        test("*6", 12, 6);
+    }
+
+    #[test]
+    fn errors() {
+        let err = |input| {
+            Orbifold::parse(input)
+                .and_then(|orbifold| orbifold.group())
+                .err()
+        };
+
+        assert_eq!(err("*4a2"), Some(OrbifoldError::InvalidSymbol { pos: 2 }));
+        assert_eq!(
+            err("*2(10"),
+            Some(OrbifoldError::MismatchedParenthesis { pos: 2 })
+        );
+        assert_eq!(err("(ab)"), Some(OrbifoldError::ParseError { pos: 0 }));
+        assert_eq!(err("*442"), Some(OrbifoldError::NotSpherical));
+        assert_eq!(err("23"), Some(OrbifoldError::NotSpherical));
+        assert_eq!(err("**"), Some(OrbifoldError::NotSpherical));
+        assert_eq!(err("xx"), Some(OrbifoldError::NotSpherical));
+    }
+}
@@ -0,0 +1,508 @@
+//! Crystallographic groups, which act on Euclidean space by isometries that
+//! combine a finite point group with the translations of a lattice.
+//!
+//! A space group is stored as the basis of its lattice of translations,
+//! together with one isometry for every coset of the translation subgroup.
+//! Symmorphic groups have a set of coset representatives that all fix the
+//! origin, while the remaining groups need glides or screws. The 17 wallpaper
+//! groups are provided in full, and symmorphic groups in any dimension can be
+//! built from a point group that preserves a lattice.
+
+use super::Group;
+use crate::{
+    conc::{Concrete, ConcretePolytope},
+    geometry::{Matrix, Point, PointSet, Vector},
+    Consts, Float,
+};
+
+/// The tolerance used to match isometries and to check that lattice
+/// coordinates are integral.
+fn tolerance() -> Float {
+    Float::EPS.sqrt()
+}
+
+/// Returns whether every entry of a vector is within tolerance of an integer.
+fn is_integral(v: &Vector) -> bool {
+    v.iter().all(|x| (x - x.round()).abs() < tolerance())
+}
+
+/// An isometry of Euclidean space, given by its linear part and the
+/// translation applied afterwards.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Isometry {
+    /// The orthogonal matrix of the linear part.
+    pub linear: Matrix,
+
+    /// The translation applied after the linear part.
+    pub translation: Vector,
+}
+
+impl Isometry {
+    /// Initializes an isometry from its linear part and its translation.
+    pub fn new(linear: Matrix, translation: Vector) -> Self {
+        Self {
+            linear,
+            translation,
+        }
+    }
+
+    /// The identity isometry in a given dimension.
+    pub fn identity(dim: usize) -> Self {
+        Self::linear(Matrix::identity(dim, dim))
+    }
+
+    /// An isometry that fixes the origin.
+    pub fn linear(linear: Matrix) -> Self {
+        let dim = linear.nrows();
+        Self::new(linear, Vector::zeros(dim))
+    }
+
+    /// The dimension of the space the isometry acts on.
+    pub fn dim(&self) -> usize {
+        self.translation.len()
+    }
+
+    /// Applies the isometry to a point.
+    pub fn apply(&self, p: &Point) -> Point {
+        &self.linear * p + &self.translation
+    }
+
+    /// Returns the isometry that applies `other` and then `self`.
+    pub fn compose(&self, other: &Self) -> Self {
+        Self::new(
+            &self.linear * &other.linear,
+            &self.linear * &other.translation + &self.translation,
+        )
+    }
+
+    /// Returns the same isometry followed by a translation.
+    pub fn translated(&self, t: &Vector) -> Self {
+        Self::new(self.linear.clone(), &self.translation + t)
+    }
+}
+
+/// Returns the matrix of a rotation of the plane by a number of degrees.
+fn rotation(degrees: Float) -> Matrix {
+    let (sin, cos) = degrees.to_radians().sin_cos();
+    Matrix::from_row_slice(2, 2, &[cos, -sin, sin, cos])
+}
+
+/// Returns the matrix of a reflection of the plane along a line through the
+/// origin, at a number of degrees from the x-axis.
+fn reflection(degrees: Float) -> Matrix {
+    let (sin, cos) = (2.0 * degrees).to_radians().sin_cos();
+    Matrix::from_row_slice(2, 2, &[cos, sin, sin, -cos])
+}
+
+/// A crystallographic group, stored as the basis of its lattice of
+/// translations and a representative isometry for every coset of it.
+#[derive(Clone, Debug)]
+pub struct SpaceGroup {
+    /// The basis of the lattice of translations, as columns.
+    lattice: Matrix,
+
+    /// The inverse of the lattice basis, which converts vectors into lattice
+    /// coordinates.
+    lattice_inv: Matrix,
+
+    /// A representative for every coset of the translation subgroup. The
+    /// first one is always the identity.
+    cosets: Vec<Isometry>,
+}
+
+impl SpaceGroup {
+    /// Initializes a space group from the basis of its lattice and a list of
+    /// coset representatives, starting with the identity. Returns `None` if
+    /// the basis is singular, or if the representatives don't close up into a
+    /// group that preserves the lattice.
+    pub fn new(lattice: Matrix, cosets: Vec<Isometry>) -> Option<Self> {
+        let dim = lattice.nrows();
+        let lattice_inv = lattice.clone().try_inverse()?;
+        let group = Self {
+            lattice,
+            lattice_inv,
+            cosets,
+        };
+
+        let identity = Isometry::identity(dim);
+        if group.cosets.first().map(|iso| group.coset_of(iso)) != Some(group.coset_of(&identity)) {
+            return None;
+        }
+
+        for iso in &group.cosets {
+            if iso.dim() != dim || iso.linear.ncols() != dim {
+                return None;
+            }
+
+            // The linear part must map lattice vectors to lattice vectors.
+            let conj = &group.lattice_inv * &iso.linear * &group.lattice;
+            if !conj
+                .column_iter()
+                .all(|col| is_integral(&col.clone_owned()))
+            {
+                return None;
+            }
+
+            for other in &group.cosets {
+                group.coset_of(&iso.compose(other))?;
+            }
+        }
+
+        Some(group)
+    }
+
+    /// Builds the symmorphic space group generated by a point group and the
+    /// translations of a lattice, whose basis is given by columns. Returns
+    /// `None` if the point group doesn't preserve the lattice.
+    pub fn symmorphic(point_group: Group, lattice: Matrix) -> Option<Self> {
+        let mut elements = point_group.elements();
+
+        // Makes sure that the identity comes first.
+        let dim = lattice.nrows();
+        let identity = Matrix::identity(dim, dim);
+        let idx = elements
+            .iter()
+            .position(|el| (el - &identity).norm() < tolerance())?;
+        elements.swap(0, idx);
+
+        Self::new(
+            lattice,
+            elements.into_iter().map(Isometry::linear).collect(),
+        )
+    }
+
+    /// Returns one of the 17 wallpaper groups, given by its name in the
+    /// notation of the International Tables for Crystallography, such as
+    /// `"p4m"` or `"cmm"`. The lattices have unit translations along the
+    /// x-axis, and are oblique, rectangular, centered rectangular, square or
+    /// hexagonal as needed.
+    pub fn wallpaper(name: &str) -> Option<Self> {
+        let oblique = Matrix::from_row_slice(2, 2, &[1.0, 0.3, 0.0, 1.2]);
+        let rectangular = Matrix::from_row_slice(2, 2, &[1.0, 0.0, 0.0, 1.5]);
+        let centered = Matrix::from_row_slice(2, 2, &[1.0, 0.5, 0.0, 0.75]);
+        let square = Matrix::identity(2, 2);
+        let hexagonal = Matrix::from_row_slice(2, 2, &[1.0, 0.5, 0.0, Float::SQRT_3 / 2.0]);
+
+        // Coset representatives that fix the origin.
+        let linear = |mats: Vec<Matrix>| mats.into_iter().map(Isometry::linear).collect();
+
+        // Coset representatives that fix the origin, followed by copies of
+        // other linear maps followed by a glide translation.
+        let glide = |fixed: Vec<Matrix>, glided: Vec<Matrix>, t: [Float; 2]| {
+            let t = Vector::from_column_slice(&t);
+            fixed
+                .into_iter()
+                .map(Isometry::linear)
+                .chain(glided.into_iter().map(|mat| Isometry::new(mat, t.clone())))
+                .collect()
+        };
+
+        let p2 = || vec![rotation(0.0), rotation(180.0)];
+        let p3 = || vec![rotation(0.0), rotation(120.0), rotation(240.0)];
+        let p4 = || {
+            vec![
+                rotation(0.0),
+                rotation(90.0),
+                rotation(180.0),
+                rotation(270.0),
+            ]
+        };
+        let p6 = || {
+            (0..6)
+                .map(|k| rotation(60.0 * k as Float))
+                .collect::<Vec<_>>()
+        };
+        let with = |mut mats: Vec<Matrix>, mirrors: &[Float]| {
+            mats.extend(mirrors.iter().copied().map(reflection));
+            mats
+        };
+
+        let (lattice, cosets) = match name {
+            "p1" => (oblique, linear(vec![rotation(0.0)])),
+            "p2" => (oblique, linear(p2())),
+            "pm" => (rectangular, linear(vec![rotation(0.0), reflection(0.0)])),
+            "pg" => (
+                rectangular,
+                glide(vec![rotation(0.0)], vec![reflection(0.0)], [0.5, 0.0]),
+            ),
+            "cm" => (centered, linear(vec![rotation(0.0), reflection(0.0)])),
+            "pmm" => (rectangular, linear(with(p2(), &[0.0, 90.0]))),
+            "pmg" => (
+                rectangular,
+                glide(p2(), vec![reflection(0.0), reflection(90.0)], [0.5, 0.0]),
+            ),
+            "pgg" => (
+                rectangular,
+                glide(p2(), vec![reflection(0.0), reflection(90.0)], [0.5, 0.75]),
+            ),
+            "cmm" => (centered, linear(with(p2(), &[0.0, 90.0]))),
+            "p4" => (square, linear(p4())),
+            "p4m" => (square, linear(with(p4(), &[0.0, 45.0, 90.0, 135.0]))),
+            "p4g" => (
+                square,
+                glide(
+                    p4(),
+                    with(Vec::new(), &[0.0, 45.0, 90.0, 135.0]),
+                    [0.5, 0.5],
+                ),
+            ),
+            "p3" => (hexagonal, linear(p3())),
+            "p3m1" => (hexagonal, linear(with(p3(), &[30.0, 90.0, 150.0]))),
+            "p31m" => (hexagonal, linear(with(p3(), &[0.0, 60.0, 120.0]))),
+            "p6" => (hexagonal, linear(p6())),
+            "p6m" => (
+                hexagonal,
+                linear(with(p6(), &[0.0, 30.0, 60.0, 90.0, 120.0, 150.0])),
+            ),
+            _ => return None,
+        };
+
+        Self::new(lattice, cosets)
+    }
+
+    /// The dimension of the space the group acts on.
+    pub fn dim(&self) -> usize {
+        self.lattice.nrows()
+    }
+
+    /// The basis of the lattice of translations, as columns.
+    pub fn lattice(&self) -> &Matrix {
+        &self.lattice
+    }
+
+    /// The representatives for the cosets of the translation subgroup. The
+    /// first one is always the identity.
+    pub fn cosets(&self) -> &[Isometry] {
+        &self.cosets
+    }
+
+    /// Returns the point group of the space group, which consists of the
+    /// linear parts of its isometries.
+    pub fn point_group(&self) -> Group {
+        let elements: Vec<_> = self.cosets.iter().map(|iso| iso.linear.clone()).collect();
+        Group::new(self.dim(), elements.into_iter())
+    }
+
+    /// Returns whether the group is symmorphic, meaning that every coset of
+    /// the translation subgroup contains an isometry that fixes the origin.
+    ///
+    /// A group whose isometries all fix some other common point is symmorphic
+    /// too, but won't be detected as such.
+    pub fn is_symmorphic(&self) -> bool {
+        self.cosets
+            .iter()
+            .all(|iso| is_integral(&(&self.lattice_inv * &iso.translation)))
+    }
+
+    /// Returns the index of the coset of the translation subgroup that an
+    /// isometry belongs to, or `None` if it doesn't belong to the group.
+    pub fn coset_of(&self, iso: &Isometry) -> Option<usize> {
+        self.cosets.iter().position(|coset| {
+            (&coset.linear - &iso.linear).norm() < tolerance()
+                && is_integral(&(&self.lattice_inv * (&iso.translation - &coset.translation)))
+        })
+    }
+
+    /// Returns every lattice vector that might translate a point within the
+    /// given distance from the origin to within the given radius of it.
+    fn translations(&self, dist: Float, radius: Float) -> Vec<Vector> {
+        // The Frobenius norm bounds the operator norm, so every lattice
+        // vector of length at most `dist + radius` has coordinates at most
+        // this bound.
+        let bound = ((dist + radius) * self.lattice_inv.norm()).ceil() as i64;
+        let dim = self.dim();
+
+        let mut coords = vec![-bound; dim];
+        let mut translations = Vec::new();
+        loop {
+            let t = &self.lattice * Vector::from_iterator(dim, coords.iter().map(|&x| x as Float));
+            if t.norm() <= dist + radius + tolerance() {
+                translations.push(t);
+            }
+
+            // Advances the coordinates like an odometer.
+            let mut idx = 0;
+            loop {
+                if idx == dim {
+                    return translations;
+                }
+                if coords[idx] < bound {
+                    coords[idx] += 1;
+                    break;
+                }
+                coords[idx] = -bound;
+                idx += 1;
+            }
+        }
+    }
+
+    /// Returns every isometry in the group that maps a given point to within
+    /// a given radius of the origin, together with the index of its coset.
+    fn isometries_near(&self, p: &Point, radius: Float) -> Vec<(Isometry, usize)> {
+        let mut isometries = Vec::new();
+
+        for (idx, coset) in self.cosets.iter().enumerate() {
+            let image = coset.apply(p);
+            for t in self.translations(image.norm(), radius) {
+                if (&image + &t).norm() <= radius {
+                    isometries.push((coset.translated(&t), idx));
+                }
+            }
+        }
+
+        isometries
+    }
+
+    /// Returns the points of the orbit of a point within a given radius of
+    /// the origin. Each point is returned with the index of the coset of the
+    /// translation subgroup that first maps the point to it, which can be
+    /// used to color it by symmetry.
+    pub fn orbit(&self, p: &Point, radius: Float) -> Vec<(Point, usize)> {
+        let mut points = PointSet::new(tolerance());
+        let mut orbit = Vec::new();
+
+        for (iso, idx) in self.isometries_near(p, radius) {
+            let image = iso.apply(p);
+            if points.insert(image.clone()).1 {
+                orbit.push((image, idx));
+            }
+        }
+
+        orbit
+    }
+
+    /// Generates a patch of a periodic honeycomb, made out of the copies of
+    /// a tile whose centers lie within a given radius of the origin, where
+    /// the center of a polytope is taken as the average of its vertices.
+    ///
+    /// Each copy is returned with the index of the coset of the translation
+    /// subgroup that first maps the tile to it, which can be used to color
+    /// the honeycomb by symmetry. Copies that coincide, as happens when the
+    /// tile is itself symmetric, are only returned once.
+    pub fn patch(&self, tile: &Concrete, radius: Float) -> Vec<(Concrete, usize)> {
+        let vertices = tile.vertices();
+        if vertices.is_empty() {
+            return Vec::new();
+        }
+
+        let center = vertices.iter().sum::<Point>() / vertices.len() as Float;
+        let mut centers = PointSet::new(tolerance());
+        let mut patch = Vec::new();
+
+        for (iso, idx) in self.isometries_near(&center, radius) {
+            if centers.insert(iso.apply(&center)).1 {
+                let mut copy = tile.clone();
+                for v in copy.vertices_mut() {
+                    *v = iso.apply(v);
+                }
+                patch.push((copy, idx));
+            }
+        }
+
+        patch
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Polytope;
+
+    /// The names of the wallpaper groups, with their point group orders and
+    /// whether they're symmorphic.
+    const WALLPAPER: [(&str, usize, bool); 17] = [
+        ("p1", 1, true),
+        ("p2", 2, true),
+        ("pm", 2, true),
+        ("pg", 2, false),
+        ("cm", 2, true),
+        ("pmm", 4, true),
+        ("pmg", 4, false),
+        ("pgg", 4, false),
+        ("cmm", 4, true),
+        ("p4", 4, true),
+        ("p4m", 8, true),
+        ("p4g", 8, false),
+        ("p3", 3, true),
+        ("p3m1", 6, true),
+        ("p31m", 6, true),
+        ("p6", 6, true),
+        ("p6m", 12, true),
+    ];
+
+    #[test]
+    fn wallpaper() {
+        for (name, order, symmorphic) in WALLPAPER {
+            let group = SpaceGroup::wallpaper(name).unwrap();
+            assert_eq!(group.cosets().len(), order, "{}", name);
+            assert_eq!(group.is_symmorphic(), symmorphic, "{}", name);
+            assert_eq!(group.point_group().order(), order, "{}", name);
+        }
+
+        assert!(SpaceGroup::wallpaper("p5").is_none());
+    }
+
+    #[test]
+    fn orbit() {
+        // A generic point has one image per coset in each unit cell, so the
+        // number of points in a big disk is roughly proportional to it.
+        let p = Point::from_column_slice(&[0.1234, 0.0567]);
+        let radius = 20.0;
+        for (name, order, _) in WALLPAPER {
+            let group = SpaceGroup::wallpaper(name).unwrap();
+            let area = group.lattice().determinant().abs();
+            let expected = Float::PI * radius * radius / area * order as Float;
+            let found = group.orbit(&p, radius).len() as Float;
+            assert!((found / expected - 1.0).abs() < 0.05, "{}", name);
+        }
+
+        // The origin is fixed by the whole point group of p4m.
+        let group = SpaceGroup::wallpaper("p4m").unwrap();
+        let orbit = group.orbit(&Point::zeros(2), 1.5);
+        assert_eq!(orbit.len(), 9);
+        assert!(orbit.iter().all(|(_, idx)| *idx == 0));
+    }
+
+    #[test]
+    fn symmorphic() {
+        let cubic = SpaceGroup::symmorphic(Group::b(3), Matrix::identity(3, 3)).unwrap();
+        assert_eq!(cubic.cosets().len(), 48);
+        assert!(cubic.is_symmorphic());
+
+        // The rotations of a triangle don't preserve the square lattice.
+        assert!(SpaceGroup::symmorphic(Group::a(2), Matrix::identity(2, 2)).is_none());
+    }
+
+    #[test]
+    fn patch() {
+        // The square tiling, whose squares are all translates of each other.
+        let group = SpaceGroup::wallpaper("p4m").unwrap();
+        let mut square = Concrete::polygon(4);
+        square.scale(Float::SQRT_2.recip());
+        for v in square.vertices_mut() {
+            *v += Vector::from_column_slice(&[0.5, 0.5]);
+        }
+
+        let patch = group.patch(&square, 3.0);
+        assert_eq!(patch.len(), 32);
+        assert!(patch
+            .iter()
+            .all(|(tile, idx)| tile.vertex_count() == 4 && *idx == 0));
+
+        // A fundamental domain of p4m, whose copies get every color.
+        let mut triangle = Concrete::polygon(3);
+        *triangle.vertices_mut() = vec![
+            Point::from_column_slice(&[0.0, 0.0]),
+            Point::from_column_slice(&[0.5, 0.0]),
+            Point::from_column_slice(&[0.5, 0.5]),
+        ];
+        let center = Point::from_column_slice(&[1.0 / 3.0, 1.0 / 6.0]);
+
+        let patch = group.patch(&triangle, 3.0);
+        assert_eq!(patch.len(), group.orbit(&center, 3.0).len());
+        for idx in 0..8 {
+            let count = patch.iter().filter(|(_, i)| *i == idx).count();
+            assert!(count * 8 >= patch.len() * 3 / 4, "{}", idx);
+        }
+    }
+}
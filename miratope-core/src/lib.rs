@@ -8,22 +8,74 @@
 //! to build and name [`Abstract`] and [`Concrete`](conc::Concrete) polytopes
 //! alike.
 //!
+//! The most commonly used items are gathered in the [`prelude`], which is the
+//! recommended way to import them.
+//!
+//! # Features
+//! The following features are enabled by default, and can be turned off to
+//! build a smaller crate:
+//!
+//! - `cd`: parsing [Coxeter diagrams](group::cd), and flag graphs. Pulls in
+//!   `petgraph`.
+//! - `parallel`: running the heavy operations on several threads. Pulls in
+//!   `rayon`.
+//! - `serde-serialize`: serializing polytopes, reading and writing the
+//!   metadata of OFF files, checkpoints, and folder indices. Pulls in `serde`
+//!   and `ron`.
+//!
+//! Triangulating faces for rendering lives in the `miratope` crate, so this
+//! one never depends on `lyon`.
+//!
 //! If you're interested in actually rendering polytopes, you might want to take
 //! a look at the [`miratope`](https://crates.io/crates/miratope) crate instead.
 
+/// Enters a [`tracing`](https://docs.rs/tracing) span at the info level until
+/// the end of the current block, so that subscribers can time the code in it.
+/// Does nothing unless the `tracing` feature is enabled.
+macro_rules! trace_span {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!($($arg)*).entered();
+    };
+}
+
+/// Emits a [`tracing`](https://docs.rs/tracing) event at the debug level,
+/// usually to report the size of some result. Does nothing unless the
+/// `tracing` feature is enabled.
+macro_rules! trace_event {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::debug!($($arg)*);
+    };
+}
+
 pub mod abs;
+#[cfg(feature = "serde-serialize")]
+pub mod checkpoint;
 pub mod conc;
+pub mod diff;
 pub mod geometry;
 pub mod group;
+pub mod limits;
+pub mod ops;
+#[cfg(feature = "extended-precision")]
+pub mod precision;
+pub mod prelude;
+pub mod rng;
+#[cfg(feature = "serde-serialize")]
+pub mod versioned;
 
 use std::iter;
 
 use abs::{
-    elements::{ElementList, ElementRef, SectionRef},
+    elements::{ElementHash, ElementList, ElementRef, SectionRef},
     flag::{Flag, FlagIter, OrientedFlag, OrientedFlagIter},
     rank::{Rank, RankVec},
     Abstract,
 };
+use diff::PolytopeDiff;
+use geometry::Point;
+use limits::{SizeLimit, SizeResult};
 
 use vec_like::VecLike;
 
@@ -100,6 +152,14 @@ impl std::fmt::Display for DualError {
     }
 }
 
+impl DualError {
+    /// Returns the index of the facet that passes through the inversion
+    /// center.
+    pub fn facet(&self) -> usize {
+        self.0
+    }
+}
+
 impl std::error::Error for DualError {}
 
 /// Gets the precalculated value for n!.
@@ -147,6 +207,18 @@ pub trait Polytope: Sized + Clone {
         self.ranks().rank()
     }
 
+    /// Returns the coordinates of the vertices of the polytope, or `None` if
+    /// the polytope is only abstract.
+    fn points(&self) -> Option<&[Point]> {
+        None
+    }
+
+    /// Compares the polytope with another one, and reports how they differ.
+    /// See [`PolytopeDiff`] for what gets compared.
+    fn diff(&self, other: &Self) -> PolytopeDiff {
+        PolytopeDiff::new(self.abs(), other.abs(), self.points(), other.points())
+    }
+
     /// Returns the number of elements of a given rank.
     fn el_count(&self, rank: Rank) -> usize {
         self.abs()
@@ -168,6 +240,36 @@ pub trait Polytope: Sized + Clone {
         counts
     }
 
+    /// Returns the [f-vector](https://en.wikipedia.org/wiki/Polyhedral_combinatorics#Equalities_and_inequalities)
+    /// of the polytope, i.e. the number of elements of each rank from the
+    /// vertices up to the facets. Unlike [`Self::el_counts`], this excludes the
+    /// minimal and maximal elements.
+    fn f_vector(&self) -> Vec<usize> {
+        let abs = self.abs();
+        Rank::range_iter(Rank::new(0), abs.rank())
+            .map(|r| abs[r].len())
+            .collect()
+    }
+
+    /// Returns the [Euler characteristic](https://polytope.miraheze.org/wiki/Euler_characteristic)
+    /// of the polytope, i.e. the alternating sum of the entries of its
+    /// [f-vector](Self::f_vector). This is computed with 128-bit integers, so
+    /// that it can't overflow.
+    fn euler_characteristic(&self) -> i128 {
+        self.f_vector()
+            .into_iter()
+            .enumerate()
+            .map(|(r, count)| {
+                let count = count as i128;
+                if r % 2 == 0 {
+                    count
+                } else {
+                    -count
+                }
+            })
+            .sum()
+    }
+
     /// The number of vertices on the polytope.
     fn vertex_count(&self) -> usize {
         self.el_count(Rank::new(0))
@@ -241,10 +343,18 @@ pub trait Polytope: Sized + Clone {
 
     /// Gets the section defined by two elements with given ranks and indices as
     /// a polytope, or returns `None` in case no section is defined by these
-    /// elements.
+    /// elements, i.e. if the lower element isn't a subelement of the higher one.
     fn section(&self, section: SectionRef) -> DualResult<Option<Self>> {
+        // The index of the lower element within the higher one.
+        let lo = match ElementHash::new(self.abs(), section.hi)
+            .and_then(|hash| hash.get_idx(section.lo))
+        {
+            Some(idx) => ElementRef::new(section.lo.rank, idx),
+            None => return Ok(None),
+        };
+
         Ok(if let Some(el) = self.element(section.hi) {
-            el.element_fig(section.lo)?
+            el.element_fig(lo)?
         } else {
             None
         })
@@ -278,6 +388,31 @@ pub trait Polytope: Sized + Clone {
         }
     }
 
+    /// Splits a compound polytope into its components, i.e. the polytopes
+    /// whose proper elements form the maximal connected sets under incidence.
+    /// A polytope that isn't a compound is returned as is. Concrete components
+    /// only keep the vertices they use.
+    ///
+    /// See [`Abstract::facet_components`] for the facets of each component.
+    fn components(&self) -> std::vec::IntoIter<Self> {
+        let rank = self.rank();
+        let components = self.abs().facet_components();
+        if components.len() < 2 {
+            return vec![self.clone()].into_iter();
+        }
+
+        // Each component is the maximal element with only its own facets.
+        let mut poly = self.clone();
+        components
+            .into_iter()
+            .map(|facets| {
+                poly.abs_mut().max_mut().subs = facets.into();
+                poly.element(ElementRef::new(rank, 0)).unwrap()
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
     /// Builds a Petrial in place. Returns `true` if successful. Does not modify
     /// the original polytope otherwise.
     fn petrial_mut(&mut self) -> bool;
@@ -307,17 +442,14 @@ pub trait Polytope: Sized + Clone {
 
         let mut flag = Flag::with_capacity(rank_usize);
         let mut idx = 0;
-        flag.push(0);
 
         let abs = self.abs();
-        for r in Rank::range_iter(1, rank) {
-            idx = abs
-                .get_element(ElementRef::new(r.minus_one(), idx))
-                .unwrap()
-                .sups[0];
+        for r in Rank::range_inclusive_iter(1, rank).rev() {
+            idx = abs.get_element(ElementRef::new(r, idx)).unwrap().subs[0];
             flag.push(idx);
         }
 
+        flag.reverse();
         Some(flag)
     }
 
@@ -333,6 +465,29 @@ pub trait Polytope: Sized + Clone {
         FlagIter::new(self.abs())
     }
 
+    /// Returns the number of [`Flag`]s of a polytope, without listing them.
+    /// This is the number of paths from the minimal to the maximal element
+    /// in the Hasse diagram, which is found one rank at a time. Unlike
+    /// [`Self::flags`], this doesn't require the polytope to be sorted.
+    fn flag_count(&self) -> usize {
+        let abs = self.abs();
+        if abs.rank() == Rank::new(-1) {
+            return 0;
+        }
+
+        // The number of flags in the section under each element of the
+        // previous rank.
+        let mut counts = vec![1];
+        for elements in abs.iter().skip(1) {
+            counts = elements
+                .iter()
+                .map(|el| el.subs.iter().map(|&sub| counts[sub]).sum())
+                .collect();
+        }
+
+        counts.into_iter().sum()
+    }
+
     /// Returns an iterator over all [`OrientedFlag`]s of a polytope.
     fn flag_events(&self) -> OrientedFlagIter {
         OrientedFlagIter::new(self.abs())
@@ -341,6 +496,21 @@ pub trait Polytope: Sized + Clone {
     /// Returns the omnitruncate of a polytope.
     fn omnitruncate(&self) -> Self;
 
+    /// Returns the omnitruncate of a polytope, or an error if it would have
+    /// more elements than a given limit allows.
+    ///
+    /// # Panics
+    /// This method will panic if the polytope isn't sorted.
+    fn try_omnitruncate(&self, limit: SizeLimit) -> SizeResult<Self> {
+        limit.check(&self.abs().estimate_omnitruncate_size())?;
+        Ok(self.omnitruncate())
+    }
+
+    /// Returns the [rectate](https://polytope.miraheze.org/wiki/Rectification)
+    /// of a polytope, whose vertices correspond to the edges of the original,
+    /// in the same order. See [`Abstract::medial`] for the details.
+    fn rectify(&self) -> Self;
+
     /// Builds a [duopyramid](https://polytope.miraheze.org/wiki/Pyramid_product)
     /// from two polytopes.
     fn duopyramid(p: &Self, q: &Self) -> Self;
@@ -357,6 +527,54 @@ pub trait Polytope: Sized + Clone {
     /// from two polytopes.
     fn duocomb(p: &Self, q: &Self) -> Self;
 
+    /// Builds a duopyramid from two polytopes, or returns an error if it would
+    /// have more elements than a given limit allows.
+    fn try_duopyramid(p: &Self, q: &Self, limit: SizeLimit) -> SizeResult<Self> {
+        limit.check(&Abstract::estimate_product_size(
+            p.abs(),
+            q.abs(),
+            true,
+            true,
+        ))?;
+        Ok(Self::duopyramid(p, q))
+    }
+
+    /// Builds a duoprism from two polytopes, or returns an error if it would
+    /// have more elements than a given limit allows.
+    fn try_duoprism(p: &Self, q: &Self, limit: SizeLimit) -> SizeResult<Self> {
+        limit.check(&Abstract::estimate_product_size(
+            p.abs(),
+            q.abs(),
+            false,
+            true,
+        ))?;
+        Ok(Self::duoprism(p, q))
+    }
+
+    /// Builds a duotegum from two polytopes, or returns an error if it would
+    /// have more elements than a given limit allows.
+    fn try_duotegum(p: &Self, q: &Self, limit: SizeLimit) -> SizeResult<Self> {
+        limit.check(&Abstract::estimate_product_size(
+            p.abs(),
+            q.abs(),
+            true,
+            false,
+        ))?;
+        Ok(Self::duotegum(p, q))
+    }
+
+    /// Builds a duocomb from two polytopes, or returns an error if it would
+    /// have more elements than a given limit allows.
+    fn try_duocomb(p: &Self, q: &Self, limit: SizeLimit) -> SizeResult<Self> {
+        limit.check(&Abstract::estimate_product_size(
+            p.abs(),
+            q.abs(),
+            false,
+            false,
+        ))?;
+        Ok(Self::duocomb(p, q))
+    }
+
     /// Builds a [ditope](https://polytope.miraheze.org/wiki/Ditope) of a given
     /// polytope.
     fn ditope(&self) -> Self {
@@ -406,6 +624,30 @@ pub trait Polytope: Sized + Clone {
         true
     }
 
+    /// Returns the [genus](https://polytope.miraheze.org/wiki/Genus) of a
+    /// polyhedron, i.e. the number of handles of the surface it forms if it's
+    /// orientable, or the number of cross-caps otherwise. Returns `None` if the
+    /// polytope isn't a polyhedron, or if its Euler characteristic doesn't
+    /// correspond to a connected surface.
+    fn genus(&mut self) -> Option<usize> {
+        if self.rank() != Rank::new(3) {
+            return None;
+        }
+
+        let deficit = 2 - self.euler_characteristic();
+        if deficit < 0 {
+            return None;
+        }
+
+        if !self.orientable() {
+            Some(deficit as usize)
+        } else if deficit % 2 == 0 {
+            Some(deficit as usize / 2)
+        } else {
+            None
+        }
+    }
+
     /// Builds a [pyramid](https://polytope.miraheze.org/wiki/Pyramid) from a
     /// given base.
     fn pyramid(&self) -> Self {
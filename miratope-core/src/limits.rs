@@ -0,0 +1,261 @@
+//! Guards the operations whose results can grow explosively, such as
+//! omnitruncates and products, so that they can fail gracefully instead of
+//! running out of memory.
+//!
+//! Each guarded operation first estimates the element counts of its result,
+//! which is much cheaper than building it, and then checks the total against a
+//! [`SizeLimit`].
+
+use std::collections::HashSet;
+
+use crate::{
+    abs::{
+        rank::{Rank, RankVec},
+        Abstract,
+    },
+    Polytope,
+};
+
+#[cfg(feature = "serde-serialize")]
+use serde::{Deserialize, Serialize};
+use vec_like::VecLike;
+
+/// The number of elements allowed by default.
+pub const DEFAULT_MAX_ELEMENTS: usize = 1_000_000;
+
+/// Represents an operation that was stopped because its result would have
+/// been too large.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SizeError {
+    /// The estimated number of elements of the result.
+    pub estimate: usize,
+
+    /// The maximum number of elements that was allowed.
+    pub limit: usize,
+}
+
+impl std::fmt::Display for SizeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "result would have {} elements, over the limit of {}",
+            self.estimate, self.limit
+        )
+    }
+}
+
+impl std::error::Error for SizeError {}
+
+/// The result of an operation guarded by a [`SizeLimit`].
+pub type SizeResult<T> = Result<T, SizeError>;
+
+/// A budget for the number of elements of the result of an operation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub struct SizeLimit {
+    /// The maximum number of elements, counting those of every rank.
+    pub max_elements: usize,
+}
+
+impl Default for SizeLimit {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_ELEMENTS)
+    }
+}
+
+impl SizeLimit {
+    /// A limit that allows every operation.
+    pub const UNLIMITED: Self = Self::new(usize::MAX);
+
+    /// Initializes a limit with a given maximum number of elements.
+    pub const fn new(max_elements: usize) -> Self {
+        Self { max_elements }
+    }
+
+    /// Checks whether a polytope with the given element counts fits within
+    /// the limit.
+    pub fn check(&self, el_counts: &RankVec<usize>) -> SizeResult<()> {
+        let estimate = el_counts
+            .iter()
+            .fold(0usize, |total, &count| total.saturating_add(count));
+
+        if estimate <= self.max_elements {
+            Ok(())
+        } else {
+            Err(SizeError {
+                estimate,
+                limit: self.max_elements,
+            })
+        }
+    }
+}
+
+impl Abstract {
+    /// Returns the element counts of the [omnitruncate](Polytope::omnitruncate)
+    /// of the polytope, without building it. Counts that overflow get
+    /// saturated.
+    ///
+    /// The elements of rank `k` of the omnitruncate of a polytope of rank `n`
+    /// are in one to one correspondence with the chains of `n - k` proper
+    /// elements of the polytope. This makes the count exact for polytopes whose
+    /// sections are all connected, and a lower bound otherwise.
+    pub fn estimate_omnitruncate_size(&self) -> RankVec<usize> {
+        let rank = self.rank();
+        let n = match rank.try_usize() {
+            Some(n) if n > 0 => n,
+            _ => return self.el_counts(),
+        };
+
+        // The number of chains of each length ending at each proper element,
+        // where chains[r][idx][m] counts those with m + 1 elements.
+        let mut chains: Vec<Vec<Vec<usize>>> = Vec::with_capacity(n);
+        for r in 0..n {
+            let elements = &self[Rank::from(r)];
+            let mut rank_chains = Vec::with_capacity(elements.len());
+
+            for el in elements {
+                let mut lengths = vec![0usize; n];
+                lengths[0] = 1;
+
+                // Adds the chains ending at every element below this one.
+                let mut below = HashSet::new();
+                let mut stack: Vec<_> = el.subs.iter().map(|&s| (r, s)).collect();
+                while let Some((s_rank, s)) = stack.pop() {
+                    if s_rank == 0 || !below.insert((s_rank - 1, s)) {
+                        continue;
+                    }
+
+                    let sub_lengths = &chains[s_rank - 1][s];
+                    for m in 1..n {
+                        lengths[m] = lengths[m].saturating_add(sub_lengths[m - 1]);
+                    }
+
+                    stack.extend(
+                        self[Rank::from(s_rank - 1)][s]
+                            .subs
+                            .iter()
+                            .map(|&t| (s_rank - 1, t)),
+                    );
+                }
+
+                rank_chains.push(lengths);
+            }
+
+            chains.push(rank_chains);
+        }
+
+        let mut counts = RankVec::with_rank_capacity(rank);
+        counts.push(1);
+        for k in 0..n {
+            counts.push(chains.iter().flatten().fold(0usize, |total, lengths| {
+                total.saturating_add(lengths[n - 1 - k])
+            }));
+        }
+        counts.push(1);
+
+        counts
+    }
+
+    /// Returns the element counts of the [product](Self::product) of two
+    /// polytopes, without building it. Counts that overflow get saturated.
+    pub fn estimate_product_size(p: &Self, q: &Self, min: bool, max: bool) -> RankVec<usize> {
+        let p_counts = p.el_counts();
+        let q_counts = q.el_counts();
+
+        // The ranks of the elements of p and q that get multiplied.
+        let p_hi = p.rank() - Rank::new(!max as isize);
+        let q_hi = q.rank() - Rank::new(!max as isize);
+        let low = Rank::new(-(min as isize));
+
+        let rank =
+            p.rank() + q.rank().plus_one() - Rank::new(!min as isize) - Rank::new(!max as isize);
+        let mut counts = RankVec::with_rank_capacity(rank);
+        for prod_rank in Rank::range_inclusive_iter(-1, rank) {
+            let mut count = 0usize;
+
+            for p_rank in Rank::range_inclusive_iter(low, p_hi) {
+                if let Some(q_rank) = prod_rank.try_sub(p_rank + Rank::new(min as isize)) {
+                    if q_rank >= low && q_rank <= q_hi {
+                        count =
+                            count.saturating_add(p_counts[p_rank].saturating_mul(q_counts[q_rank]));
+                    }
+                }
+            }
+
+            counts.push(count);
+        }
+
+        // The minimal and maximal elements that get added manually.
+        if !min {
+            counts[Rank::new(-1)] = 1;
+        }
+        if !max {
+            counts[rank] = 1;
+        }
+
+        counts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Checks that the estimate for the omnitruncate of a polytope matches its
+    /// actual element counts.
+    fn test_omnitruncate(mut poly: Abstract) {
+        poly.abs_sort();
+        let estimate = poly.estimate_omnitruncate_size();
+        assert_eq!(estimate, poly.omnitruncate().el_counts());
+    }
+
+    #[test]
+    fn omnitruncate() {
+        test_omnitruncate(Abstract::point());
+        test_omnitruncate(Abstract::dyad());
+        test_omnitruncate(Abstract::polygon(5));
+        test_omnitruncate(Abstract::hypercube(Rank::new(3)));
+        test_omnitruncate(Abstract::simplex(Rank::new(4)));
+        test_omnitruncate(Abstract::duoprism(
+            &Abstract::polygon(3),
+            &Abstract::polygon(4),
+        ));
+    }
+
+    #[test]
+    fn product() {
+        let p = Abstract::polygon(3);
+        let q = Abstract::hypercube(Rank::new(3));
+        for min in [false, true] {
+            for max in [false, true] {
+                assert_eq!(
+                    Abstract::estimate_product_size(&p, &q, min, max),
+                    Abstract::product(&p, &q, min, max).el_counts(),
+                    "min {}, max {}",
+                    min,
+                    max
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn limit() {
+        let mut cube = Abstract::hypercube(Rank::new(4));
+        cube.abs_sort();
+
+        // The omnitruncated tesseract has 384 vertices.
+        assert_eq!(
+            cube.try_omnitruncate(SizeLimit::new(100)).unwrap_err(),
+            SizeError {
+                estimate: 1 + 384 + 768 + 464 + 80 + 1,
+                limit: 100
+            }
+        );
+        assert!(cube.try_omnitruncate(SizeLimit::default()).is_ok());
+
+        let square = Abstract::polygon(4);
+        assert!(Abstract::try_duoprism(&square, &square, SizeLimit::new(10)).is_err());
+        assert!(Abstract::try_duoprism(&square, &square, SizeLimit::UNLIMITED).is_ok());
+    }
+}
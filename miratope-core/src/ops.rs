@@ -0,0 +1,325 @@
+//! Applies a pipeline of operations to many polytopes at once, such as
+//! computing the duals and volumes of a whole directory of OFF files.
+//!
+//! Every polytope goes through the pipeline on its own thread. A polytope that
+//! can't be loaded, or on which some operation fails or even panics, doesn't
+//! stop the others: its error gets recorded next to its name instead.
+
+use std::{
+    any::Any,
+    fs, io,
+    panic::{self, AssertUnwindSafe},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    conc::{file::FromFile, Concrete, ConcretePolytope},
+    limits::{SizeError, SizeLimit},
+    DualError, Float, Polytope,
+};
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// A step in a pipeline.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Op {
+    /// Converts the polytope into its dual.
+    Dual,
+
+    /// Makes a pyramid out of the polytope.
+    Pyramid,
+
+    /// Makes a prism out of the polytope.
+    Prism,
+
+    /// Makes a tegum out of the polytope.
+    Tegum,
+
+    /// Converts the polytope into its antiprism.
+    Antiprism,
+
+    /// Converts the polytope into its Petrial.
+    Petrial,
+
+    /// Converts the polytope into its rectate.
+    Rectify,
+
+    /// Converts the polytope into its omnitruncate, as long as it fits within
+    /// the limit.
+    Omnitruncate(SizeLimit),
+
+    /// Recenters the polytope.
+    Recenter,
+
+    /// Records the volume of the polytope, and leaves it unchanged.
+    Volume,
+}
+
+impl Op {
+    /// Applies the operation to a polytope, recording any value that it
+    /// measures.
+    fn apply(self, poly: &mut Concrete, values: &mut Vec<(Op, Option<Float>)>) -> BatchResult<()> {
+        match self {
+            Self::Dual => poly.try_dual_mut()?,
+            Self::Pyramid => *poly = poly.pyramid(),
+            Self::Prism => *poly = poly.prism(),
+            Self::Tegum => *poly = poly.tegum(),
+            Self::Antiprism => *poly = poly.try_antiprism()?,
+            Self::Petrial => {
+                if !poly.petrial_mut() {
+                    return Err(BatchError::Petrial);
+                }
+            }
+            Self::Rectify => *poly = poly.rectify(),
+            Self::Omnitruncate(limit) => {
+                poly.abs_sort();
+                *poly = poly.try_omnitruncate(limit)?;
+            }
+            Self::Recenter => poly.recenter(),
+            Self::Volume => {
+                poly.abs_sort();
+                values.push((self, poly.volume()));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Represents an error while running a polytope through a pipeline.
+#[derive(Debug)]
+pub enum BatchError {
+    /// The polytope couldn't be loaded from its file.
+    File(String),
+
+    /// A facet passed through the inversion center in a dual or antiprism.
+    Dual(DualError),
+
+    /// The Petrial of the polytope wasn't valid.
+    Petrial,
+
+    /// The result of an operation would have been too large.
+    Size(SizeError),
+
+    /// Something panicked while the polytope went through the pipeline.
+    Panic(String),
+}
+
+impl BatchError {
+    /// Builds an error from the payload of a panic.
+    fn panic(payload: Box<dyn Any + Send>) -> Self {
+        let msg = if let Some(msg) = payload.downcast_ref::<&str>() {
+            (*msg).to_string()
+        } else if let Some(msg) = payload.downcast_ref::<String>() {
+            msg.clone()
+        } else {
+            String::from("unknown panic")
+        };
+
+        Self::Panic(msg)
+    }
+}
+
+impl std::fmt::Display for BatchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::File(err) => write!(f, "couldn't load polytope: {}", err),
+            Self::Dual(err) => write!(f, "dual failed: {}", err),
+            Self::Petrial => write!(f, "Petrial is not a valid polytope"),
+            Self::Size(err) => write!(f, "operation too large: {}", err),
+            Self::Panic(msg) => write!(f, "panicked: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for BatchError {}
+
+/// [`DualError`] is a type of [`BatchError`].
+impl From<DualError> for BatchError {
+    fn from(err: DualError) -> Self {
+        Self::Dual(err)
+    }
+}
+
+/// [`SizeError`] is a type of [`BatchError`].
+impl From<SizeError> for BatchError {
+    fn from(err: SizeError) -> Self {
+        Self::Size(err)
+    }
+}
+
+/// The result of running a polytope through a pipeline.
+pub type BatchResult<T> = Result<T, BatchError>;
+
+/// A polytope to run through a pipeline.
+#[derive(Clone, Debug)]
+pub enum Input {
+    /// A polytope to be loaded from a file.
+    Path(PathBuf),
+
+    /// A polytope that's already loaded, along with a name for it.
+    Polytope(String, Concrete),
+}
+
+impl Input {
+    /// Returns the inputs for all of the polytope files directly within a
+    /// directory, sorted by path.
+    pub fn dir<T: AsRef<Path>>(dir: T) -> io::Result<Vec<Self>> {
+        let mut paths = Vec::new();
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            let ext = path.extension().and_then(|ext| ext.to_str());
+            if matches!(ext, Some("off" | "ggb" | "obj")) {
+                paths.push(path);
+            }
+        }
+
+        paths.sort();
+        Ok(paths.into_iter().map(Self::Path).collect())
+    }
+
+    /// Returns the name of the input.
+    pub fn name(&self) -> String {
+        match self {
+            Self::Path(path) => path.display().to_string(),
+            Self::Polytope(name, _) => name.clone(),
+        }
+    }
+
+    /// Loads the polytope.
+    fn load(self) -> BatchResult<Concrete> {
+        match self {
+            Self::Path(path) => {
+                Concrete::from_path(&path).map_err(|err| BatchError::File(err.to_string()))
+            }
+            Self::Polytope(_, poly) => Ok(poly),
+        }
+    }
+}
+
+impl From<Concrete> for Input {
+    fn from(poly: Concrete) -> Self {
+        let name = poly
+            .metadata()
+            .and_then(|metadata| metadata.name.clone())
+            .unwrap_or_default();
+        Self::Polytope(name, poly)
+    }
+}
+
+/// What comes out of running a polytope through a pipeline.
+#[derive(Clone, Debug)]
+pub struct BatchOutput {
+    /// The resulting polytope.
+    pub polytope: Concrete,
+
+    /// The values measured along the way, in order.
+    pub values: Vec<(Op, Option<Float>)>,
+}
+
+impl BatchOutput {
+    /// Writes the resulting polytope into an OFF file.
+    pub fn save_off<T: AsRef<Path>>(&self, path: T) -> io::Result<()> {
+        fs::write(path, self.polytope.to_off(Default::default()))
+    }
+}
+
+/// The outcome of running a single polytope through a pipeline.
+#[derive(Debug)]
+pub struct BatchItem {
+    /// The name of the input.
+    pub name: String,
+
+    /// What came out of the pipeline, or the error that stopped it.
+    pub result: BatchResult<BatchOutput>,
+}
+
+/// Runs a single polytope through a pipeline. A panic along the way gets
+/// caught and recorded as an error, so that it doesn't bring down the rest of
+/// the batch.
+fn run(ops: &[Op], input: Input) -> BatchItem {
+    let name = input.name();
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        input.load().and_then(|mut polytope| {
+            let mut values = Vec::new();
+            for &op in ops {
+                op.apply(&mut polytope, &mut values)?;
+            }
+
+            Ok(BatchOutput { polytope, values })
+        })
+    }))
+    .unwrap_or_else(|payload| Err(BatchError::panic(payload)));
+
+    BatchItem { name, result }
+}
+
+/// Runs every input through a pipeline of operations, in parallel if the
+/// `parallel` feature is enabled. Returns the outcome of each input, in the
+/// same order as they were given.
+pub fn batch(ops: &[Op], inputs: Vec<Input>) -> Vec<BatchItem> {
+    trace_span!("batch", ops = ops.len(), inputs = inputs.len());
+
+    #[cfg(feature = "parallel")]
+    let inputs = inputs.into_par_iter();
+    #[cfg(not(feature = "parallel"))]
+    let inputs = inputs.into_iter();
+
+    inputs.map(|input| run(ops, input)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{abs::rank::Rank, Consts};
+
+    #[test]
+    fn batch() {
+        let cube = Concrete::hypercube(Rank::new(3));
+        let mut off_cube = cube.clone();
+        off_cube.vertices.iter_mut().for_each(|v| v[0] += 0.5);
+
+        let inputs = vec![
+            Input::Polytope("cube".to_string(), cube),
+            Input::Polytope("offset cube".to_string(), off_cube),
+            Input::Path(PathBuf::from("missing.off")),
+        ];
+        let items = super::batch(&[Op::Dual, Op::Volume], inputs);
+        assert_eq!(items.len(), 3);
+
+        // The dual of the cube is an octahedron with edge 2√2.
+        assert_eq!(items[0].name, "cube");
+        let output = items[0].result.as_ref().unwrap();
+        assert_eq!(output.polytope.el_counts(), vec![1, 6, 12, 8, 1].into());
+        let volume = output.values[0].1.unwrap();
+        assert!((volume - 32.0 / 3.0).abs() < Float::EPS);
+
+        // A facet of the offset cube passes through the center.
+        assert!(matches!(items[1].result, Err(BatchError::Dual(_))));
+        assert!(matches!(items[2].result, Err(BatchError::File(_))));
+    }
+
+    #[test]
+    fn limit() {
+        let items = super::batch(
+            &[Op::Omnitruncate(SizeLimit::new(10))],
+            vec![Concrete::hypercube(Rank::new(3)).into()],
+        );
+        assert!(matches!(items[0].result, Err(BatchError::Size(_))));
+    }
+
+    #[test]
+    fn panic() {
+        // A square whose face has an edge that doesn't exist.
+        let mut poly = Concrete::hypercube(Rank::new(2));
+        poly.abs[Rank::new(2)][0].subs[0] = 10;
+
+        let items = super::batch(
+            &[Op::Volume],
+            vec![poly.into(), Concrete::hypercube(Rank::new(2)).into()],
+        );
+        assert!(matches!(items[0].result, Err(BatchError::Panic(_))));
+        assert!(items[1].result.is_ok());
+    }
+}
@@ -0,0 +1,428 @@
+//! Extended precision versions of some of the geometric routines that visibly
+//! lose precision with [`Float`], such as computing circumspheres and the
+//! normals of Coxeter groups in high ranks.
+//!
+//! The extra precision comes from double-double arithmetic, which represents
+//! a number as the unevaluated sum of two [`Float`]s, and roughly doubles the
+//! number of significant digits. Every operation here takes a [`Precision`],
+//! so that the slower arithmetic is only used where it's needed. The results
+//! are rounded back to [`Float`]s at the end.
+
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+use crate::{
+    conc::{Concrete, ConcretePolytope},
+    geometry::{Hypersphere, Matrix, Point},
+    group::cd::CoxMatrix,
+    Consts, Float,
+};
+
+/// The precision with which to carry out a computation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Precision {
+    /// Uses [`Float`]s directly.
+    Double,
+
+    /// Uses double-double arithmetic.
+    DoubleDouble,
+}
+
+/// A number represented as the unevaluated sum of two [`Float`]s, the second
+/// of which is at most half an ulp of the first.
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+pub struct DoubleDouble {
+    /// The leading part of the number.
+    pub hi: Float,
+
+    /// The rounding error of the leading part.
+    pub lo: Float,
+}
+
+/// Adds two floats exactly, returning the rounded sum and its error.
+fn two_sum(a: Float, b: Float) -> DoubleDouble {
+    let hi = a + b;
+    let b_virtual = hi - a;
+    let lo = (a - (hi - b_virtual)) + (b - b_virtual);
+    DoubleDouble { hi, lo }
+}
+
+/// Adds two floats exactly, assuming that the first one is the largest in
+/// absolute value.
+fn quick_two_sum(a: Float, b: Float) -> DoubleDouble {
+    let hi = a + b;
+    DoubleDouble {
+        hi,
+        lo: b - (hi - a),
+    }
+}
+
+/// Multiplies two floats exactly, returning the rounded product and its
+/// error.
+fn two_prod(a: Float, b: Float) -> DoubleDouble {
+    let hi = a * b;
+    DoubleDouble {
+        hi,
+        lo: a.mul_add(b, -hi),
+    }
+}
+
+impl From<Float> for DoubleDouble {
+    fn from(x: Float) -> Self {
+        Self { hi: x, lo: 0.0 }
+    }
+}
+
+impl From<DoubleDouble> for Float {
+    fn from(x: DoubleDouble) -> Self {
+        x.hi + x.lo
+    }
+}
+
+impl DoubleDouble {
+    /// The number zero.
+    pub const ZERO: Self = Self { hi: 0.0, lo: 0.0 };
+
+    /// The number one.
+    pub const ONE: Self = Self { hi: 1.0, lo: 0.0 };
+
+    /// The number π, to double-double precision.
+    pub const PI: Self = Self {
+        hi: std::f64::consts::PI,
+        lo: 1.2246467991473532e-16,
+    };
+
+    /// Returns the absolute value of the number.
+    pub fn abs(self) -> Self {
+        if self.hi < 0.0 {
+            -self
+        } else {
+            self
+        }
+    }
+
+    /// Returns the square root of the number, or NaN if it's negative.
+    pub fn sqrt(self) -> Self {
+        if self.hi <= 0.0 {
+            return if self.hi == 0.0 {
+                Self::ZERO
+            } else {
+                Float::NAN.into()
+            };
+        }
+
+        // A single Newton step doubles the precision of the estimate.
+        let estimate = self.hi.sqrt();
+        let y = Self::from(estimate);
+        y + (self - y * y) * Self::from(0.5 / estimate)
+    }
+
+    /// Returns the cosine of the number, which should be at most π in
+    /// absolute value.
+    pub fn cos(self) -> Self {
+        let x2 = self * self;
+        let mut term = Self::ONE;
+        let mut sum = Self::ONE;
+
+        // The Taylor series converges quickly within the range.
+        for k in 1..40 {
+            term = -(term * x2) / Self::from(((2 * k - 1) * (2 * k)) as Float);
+            sum = sum + term;
+
+            if term.hi.abs() < Float::EPSILON * Float::EPSILON * sum.hi.abs() {
+                break;
+            }
+        }
+
+        sum
+    }
+}
+
+impl Neg for DoubleDouble {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self {
+            hi: -self.hi,
+            lo: -self.lo,
+        }
+    }
+}
+
+impl Add for DoubleDouble {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        let s = two_sum(self.hi, rhs.hi);
+        let t = two_sum(self.lo, rhs.lo);
+        let s = quick_two_sum(s.hi, s.lo + t.hi);
+        quick_two_sum(s.hi, s.lo + t.lo)
+    }
+}
+
+impl Sub for DoubleDouble {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        self + -rhs
+    }
+}
+
+impl Mul for DoubleDouble {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        let p = two_prod(self.hi, rhs.hi);
+        quick_two_sum(p.hi, p.lo + (self.hi * rhs.lo + self.lo * rhs.hi))
+    }
+}
+
+impl Div for DoubleDouble {
+    type Output = Self;
+
+    fn div(self, rhs: Self) -> Self {
+        // Long division, one float at a time.
+        let q1 = self.hi / rhs.hi;
+        let r = self - rhs * q1.into();
+        let q2 = r.hi / rhs.hi;
+        let r = r - rhs * q2.into();
+        let q3 = r.hi / rhs.hi;
+
+        quick_two_sum(q1, q2) + q3.into()
+    }
+}
+
+/// A vector of double-doubles.
+type DdVector = Vec<DoubleDouble>;
+
+/// Converts a point into a vector of double-doubles.
+fn to_dd(p: &Point) -> DdVector {
+    p.iter().map(|&x| x.into()).collect()
+}
+
+/// Rounds a vector of double-doubles into a point.
+fn to_point(v: &[DoubleDouble]) -> Point {
+    Point::from_iterator(v.len(), v.iter().map(|&x| x.into()))
+}
+
+/// Returns the dot product of two vectors.
+fn dot(v: &[DoubleDouble], w: &[DoubleDouble]) -> DoubleDouble {
+    v.iter()
+        .zip(w)
+        .fold(DoubleDouble::ZERO, |acc, (&x, &y)| acc + x * y)
+}
+
+/// Returns the difference of two vectors.
+fn sub(v: &[DoubleDouble], w: &[DoubleDouble]) -> DdVector {
+    v.iter().zip(w).map(|(&x, &y)| x - y).collect()
+}
+
+/// Adds a multiple of a vector to another.
+fn add_scaled(v: &mut [DoubleDouble], k: DoubleDouble, w: &[DoubleDouble]) {
+    for (x, &y) in v.iter_mut().zip(w) {
+        *x = *x + k * y;
+    }
+}
+
+impl Concrete {
+    /// Calculates the circumsphere of a polytope with a given precision, just
+    /// as [`ConcretePolytope::circumsphere`] does. Returns `None` if the
+    /// polytope isn't circumscribable.
+    pub fn circumsphere_with(&self, precision: Precision) -> Option<Hypersphere> {
+        if precision == Precision::Double {
+            return self.circumsphere();
+        }
+
+        let mut vertices = self.vertices.iter().map(to_dd);
+        let first_vertex = vertices.next()?;
+        let mut center = first_vertex.clone();
+
+        // An orthonormal basis for the span of the vertices found so far.
+        let mut basis: Vec<DdVector> = Vec::new();
+
+        for vertex in vertices {
+            let offset = sub(&vertex, &first_vertex);
+            let mut basis_vector = offset.clone();
+            for b in &basis {
+                let k = dot(&basis_vector, b);
+                add_scaled(&mut basis_vector, -k, b);
+            }
+
+            let norm = dot(&basis_vector, &basis_vector).sqrt();
+            let center_first = sub(&center, &first_vertex);
+            let center_vertex = sub(&center, &vertex);
+            let first_distance = dot(&center_first, &center_first);
+            let distance = dot(&center_vertex, &center_vertex);
+
+            // If the new vertex does not lie on the hyperplane of the others,
+            // moves the circumcenter along the new direction.
+            if Float::from(norm) > Float::EPS {
+                for x in &mut basis_vector {
+                    *x = *x / norm;
+                }
+
+                let shift = (distance - first_distance)
+                    / (DoubleDouble::from(2.0) * dot(&offset, &basis_vector));
+                add_scaled(&mut center, shift, &basis_vector);
+                basis.push(basis_vector);
+            }
+            // If the new vertex lies on the others' hyperplane, but is not at
+            // the correct distance from the first vertex:
+            else if (Float::from(distance.sqrt()) - Float::from(first_distance.sqrt())).abs()
+                > Float::EPS
+            {
+                return None;
+            }
+        }
+
+        let radius = sub(&center, &first_vertex);
+        Some(Hypersphere {
+            squared_radius: dot(&radius, &radius).into(),
+            center: to_point(&center),
+        })
+    }
+
+    /// Calculates the gravicenter of a polytope with a given precision, just
+    /// as [`ConcretePolytope::gravicenter`] does. Recentering a polytope in
+    /// order to put it in a canonical position depends on it.
+    pub fn gravicenter_with(&self, precision: Precision) -> Option<Point> {
+        if precision == Precision::Double {
+            return self.gravicenter();
+        }
+
+        let dim = self.dim()?;
+        let mut sum = vec![DoubleDouble::ZERO; dim];
+        for v in &self.vertices {
+            add_scaled(&mut sum, DoubleDouble::ONE, &to_dd(v));
+        }
+
+        let count = DoubleDouble::from(self.vertices.len() as Float);
+        Some(Point::from_iterator(
+            dim,
+            sum.into_iter().map(|x| (x / count).into()),
+        ))
+    }
+}
+
+impl CoxMatrix {
+    /// Returns an upper triangular matrix whose columns are unit normal vectors
+    /// for the hyperplanes described by the Coxeter matrix, computed with a
+    /// given precision, just as [`Self::normals`] does.
+    pub fn normals_with(&self, precision: Precision) -> Option<Matrix> {
+        if precision == Precision::Double {
+            return self.normals();
+        }
+
+        let dim = self.dim();
+        let mut normals: Vec<DdVector> = Vec::with_capacity(dim);
+
+        for i in 0..dim {
+            let mut n_i = vec![DoubleDouble::ZERO; dim];
+
+            for (j, n_j) in normals.iter().enumerate() {
+                // All other entries in the dot product are zero.
+                let dot = dot(&n_i[0..j], &n_j[0..j]);
+                let cos = -(DoubleDouble::PI / self[(i, j)].into()).cos();
+                n_i[j] = (cos - dot) / n_j[j];
+            }
+
+            // If the vector doesn't fit in spherical space.
+            let norm_sq = dot(&n_i, &n_i);
+            if Float::from(norm_sq) >= 1.0 - Float::EPS {
+                return None;
+            }
+            n_i[i] = (DoubleDouble::ONE - norm_sq).sqrt();
+
+            normals.push(n_i);
+        }
+
+        Some(Matrix::from_fn(dim, dim, |row, col| {
+            normals[col][row].into()
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        abs::{rank::Rank, Abstract},
+        Polytope,
+    };
+
+    use approx::abs_diff_eq;
+
+    #[test]
+    fn arithmetic() {
+        let third = DoubleDouble::ONE / DoubleDouble::from(3.0);
+        let one = third * DoubleDouble::from(3.0);
+        assert!((one - DoubleDouble::ONE).hi.abs() < 1e-30);
+
+        // The square root of 2, squared.
+        let two = DoubleDouble::from(2.0);
+        let root = two.sqrt();
+        assert!((root * root - two).hi.abs() < 1e-30);
+
+        // The error of 0.1 in floating point gets captured in the low part.
+        let tenth = DoubleDouble::ONE / DoubleDouble::from(10.0);
+        assert_eq!(tenth.hi, 0.1);
+        assert_ne!(tenth.lo, 0.0);
+
+        // cos(π / 3) = 1 / 2.
+        let cos = (DoubleDouble::PI / DoubleDouble::from(3.0)).cos();
+        assert!((cos - DoubleDouble::from(0.5)).hi.abs() < 1e-30);
+    }
+
+    #[test]
+    fn circumsphere() {
+        for precision in [Precision::Double, Precision::DoubleDouble] {
+            let cube = Concrete::hypercube(Rank::new(5));
+            let sphere = cube.circumsphere_with(precision).unwrap();
+            assert!(abs_diff_eq!(
+                sphere.squared_radius,
+                5.0 / 4.0,
+                epsilon = Float::EPS
+            ));
+            assert!(sphere.center.norm() < Float::EPS);
+
+            // A kite that isn't cyclic.
+            let kite = Concrete::new(
+                vec![
+                    Point::from(vec![1.0, 0.0]),
+                    Point::from(vec![0.0, 1.0]),
+                    Point::from(vec![-2.0, 0.0]),
+                    Point::from(vec![0.0, -1.0]),
+                ],
+                Abstract::polygon(4),
+            );
+            assert!(kite.circumsphere_with(precision).is_none());
+        }
+    }
+
+    #[test]
+    fn gravicenter() {
+        let mut cube = Concrete::hypercube(Rank::new(3));
+        for v in &mut cube.vertices {
+            v[0] += 1e8;
+        }
+
+        let center = cube.gravicenter_with(Precision::DoubleDouble).unwrap();
+        assert_eq!(center, Point::from(vec![1e8, 0.0, 0.0]));
+    }
+
+    #[test]
+    #[cfg(feature = "cd")]
+    fn normals() {
+        // The Coxeter matrices of E8 and H4.
+        for cd in ["o3o3o3o3o3o3o *c3o", "o5o3o3o"] {
+            let cox = crate::group::cd::Cd::parse(cd).unwrap().cox();
+            let double = cox.normals().unwrap();
+            let extended = cox.normals_with(Precision::DoubleDouble).unwrap();
+            assert!(abs_diff_eq!(double, extended, epsilon = Float::EPS));
+
+            // Every normal has unit length.
+            for col in extended.column_iter() {
+                assert!(abs_diff_eq!(col.norm(), 1.0, epsilon = 1e-15));
+            }
+        }
+    }
+}
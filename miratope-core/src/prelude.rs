@@ -0,0 +1,49 @@
+//! Re-exports the types and traits that almost any code using this crate
+//! needs, so that they can all be brought into scope at once:
+//!
+//! ```
+//! use miratope_core::prelude::*;
+//!
+//! let cube = Concrete::hypercube(Rank::new(3));
+//! assert_eq!(cube.facet_count(), 6);
+//! ```
+//!
+//! The modules these items live in are still being reorganized. Importing
+//! them from here instead keeps downstream code working when they move, as
+//! every item in the prelude will keep being exported from it.
+
+#[doc(no_inline)]
+pub use crate::{
+    abs::{
+        elements::{ElementRef, SectionRef},
+        rank::{Rank, RankVec},
+        Abstract,
+    },
+    conc::{Concrete, ConcretePolytope},
+    geometry::{Hyperplane, Hypersphere, Matrix, Point, Subspace},
+    group::{cd::CoxMatrix, Group},
+    Consts, DualResult, Float, Polytope,
+};
+
+#[cfg(feature = "cd")]
+#[doc(no_inline)]
+pub use crate::group::cd::Cd;
+
+#[doc(no_inline)]
+pub use vec_like::VecLike;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prelude() {
+        let cube = Concrete::hypercube(Rank::new(3));
+        let facet = cube.element(ElementRef::new(Rank::new(2), 0)).unwrap();
+        assert_eq!(facet.vertex_count(), 4);
+
+        let group = Group::cox_group(CoxMatrix::b(3)).unwrap();
+        assert_eq!(group.order(), 48);
+        assert!(cube.circumsphere().is_some());
+    }
+}
@@ -0,0 +1,274 @@
+//! Tags serialized data with the version of its format, and upgrades data
+//! saved in older versions of the format, so that changes to the way things
+//! are stored don't make saved files unreadable.
+//!
+//! Versioned data is stored as RON of the form `(version: 2, data: ...)`.
+//! Files without a version tag, which were saved before tags were introduced,
+//! are read as version 1. When reading a file of an older version, its data is
+//! parsed into a [`Value`] and run through every [`Migration`] from its
+//! version up to the current one.
+
+use ron::Value;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+/// A function that upgrades data from one version of a format to the next.
+/// Returns a message if the data can't be upgraded.
+pub type Migration = fn(Value) -> Result<Value, String>;
+
+/// A type that's saved to disk with a version tag.
+pub trait Versioned: Serialize + DeserializeOwned {
+    /// The current version of the format. This starts at 1, and should be
+    /// increased every time the format changes, along with adding a migration.
+    const VERSION: u32;
+
+    /// The migrations between consecutive versions of the format, so that the
+    /// entry with index `i` upgrades data from version `i + 1` to version
+    /// `i + 2`. There should be `VERSION - 1` of them.
+    fn migrations() -> &'static [Migration] {
+        &[]
+    }
+}
+
+/// Represents an error while reading versioned data.
+#[derive(Clone, Debug)]
+pub enum VersionError {
+    /// The data was saved with a newer version of the format.
+    Newer {
+        /// The version of the data.
+        found: u32,
+
+        /// The latest version that can be read.
+        supported: u32,
+    },
+
+    /// A migration failed.
+    Migration {
+        /// The version that the data was being upgraded from.
+        version: u32,
+
+        /// The reason why the migration failed.
+        message: String,
+    },
+
+    /// The data couldn't be parsed.
+    Ron(ron::Error),
+}
+
+impl From<ron::Error> for VersionError {
+    fn from(err: ron::Error) -> Self {
+        Self::Ron(err)
+    }
+}
+
+impl std::fmt::Display for VersionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Newer { found, supported } => write!(
+                f,
+                "data has version {}, but only versions up to {} are supported",
+                found, supported
+            ),
+            Self::Migration { version, message } => {
+                write!(f, "could not upgrade from version {}: {}", version, message)
+            }
+            Self::Ron(err) => write!(f, "could not parse data: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for VersionError {}
+
+/// The result of reading versioned data.
+pub type VersionResult<T> = Result<T, VersionError>;
+
+/// The version tag of some data, read without parsing the data itself.
+#[derive(Deserialize)]
+struct Header {
+    /// The version of the data, or 0 if it's missing.
+    #[serde(default)]
+    version: u32,
+}
+
+/// Data together with its version, for serialization.
+#[derive(Serialize)]
+struct TaggedRef<'a, T> {
+    /// The version of the data.
+    version: u32,
+
+    /// The data itself.
+    data: &'a T,
+}
+
+/// Data together with its version, for deserialization.
+#[derive(Deserialize)]
+struct Tagged<T> {
+    /// The version of the data.
+    version: u32,
+
+    /// The data itself.
+    data: T,
+}
+
+/// Serializes some data as RON, tagged with the current version of its format.
+pub fn to_ron<T: Versioned>(data: &T) -> ron::Result<String> {
+    ron::to_string(&TaggedRef {
+        version: T::VERSION,
+        data,
+    })
+}
+
+/// Deserializes some data from RON, upgrading it to the current version of
+/// its format if needed.
+pub fn from_ron<T: Versioned>(src: &str) -> VersionResult<T> {
+    // Data that isn't a struct with a version can't be tagged.
+    let version = ron::from_str::<Header>(src).map_or(0, |header| header.version);
+    if version > T::VERSION {
+        return Err(VersionError::Newer {
+            found: version,
+            supported: T::VERSION,
+        });
+    }
+
+    // Untagged data is read as the first version.
+    if version == T::VERSION {
+        return Ok(ron::from_str::<Tagged<T>>(src)?.data);
+    } else if version == 0 && T::VERSION == 1 {
+        return Ok(ron::from_str(src)?);
+    }
+
+    let (mut version, mut value) = if version == 0 {
+        (1, ron::from_str(src)?)
+    } else {
+        let tagged: Tagged<Value> = ron::from_str(src)?;
+        (tagged.version, tagged.data)
+    };
+
+    let migrations = T::migrations();
+    assert_eq!(
+        migrations.len() as u32 + 1,
+        T::VERSION,
+        "There must be a migration between every two consecutive versions."
+    );
+
+    while version < T::VERSION {
+        value = migrations[version as usize - 1](value)
+            .map_err(|message| VersionError::Migration { version, message })?;
+        version += 1;
+    }
+
+    Ok(value.into_rust()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The first version of a test format.
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Old {
+        name: String,
+    }
+
+    impl Versioned for Old {
+        const VERSION: u32 = 1;
+    }
+
+    /// The third version of a test format, where the name became a label and
+    /// then a list of labels.
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct New {
+        labels: Vec<String>,
+    }
+
+    /// Renames the `name` field to `label`.
+    fn rename(value: Value) -> Result<Value, String> {
+        match value {
+            Value::Map(map) => Ok(Value::Map(
+                map.iter()
+                    .map(|(key, value)| match key {
+                        Value::String(key) if key == "name" => {
+                            (Value::String("label".to_string()), value.clone())
+                        }
+                        _ => (key.clone(), value.clone()),
+                    })
+                    .collect(),
+            )),
+            _ => Err("expected a struct".to_string()),
+        }
+    }
+
+    /// Turns the `label` field into a list of labels.
+    fn listify(value: Value) -> Result<Value, String> {
+        match value {
+            Value::Map(mut map) => {
+                let key = Value::String("label".to_string());
+                let label = map.remove(&key).ok_or("missing label")?;
+                map.insert(Value::String("labels".to_string()), Value::Seq(vec![label]));
+                Ok(Value::Map(map))
+            }
+            _ => Err("expected a struct".to_string()),
+        }
+    }
+
+    impl Versioned for New {
+        const VERSION: u32 = 3;
+
+        fn migrations() -> &'static [Migration] {
+            &[rename, listify]
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let new = New {
+            labels: vec!["cube".to_string()],
+        };
+        let ron = to_ron(&new).unwrap();
+        assert!(ron.contains("version:3"));
+        assert_eq!(from_ron::<New>(&ron).unwrap(), new);
+    }
+
+    #[test]
+    fn migrate() {
+        let old = Old {
+            name: "cube".to_string(),
+        };
+        let expected = New {
+            labels: vec!["cube".to_string()],
+        };
+
+        // Both tagged and untagged files get upgraded.
+        assert_eq!(from_ron::<New>(&to_ron(&old).unwrap()).unwrap(), expected);
+        assert_eq!(
+            from_ron::<New>(&ron::to_string(&old).unwrap()).unwrap(),
+            expected
+        );
+        assert_eq!(
+            from_ron::<New>("(version: 2, data: (label: \"cube\"))").unwrap(),
+            expected
+        );
+        assert_eq!(
+            from_ron::<Old>(&ron::to_string(&old).unwrap()).unwrap(),
+            old
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert!(matches!(
+            from_ron::<Old>("(version: 2, data: (name: \"cube\"))"),
+            Err(VersionError::Newer {
+                found: 2,
+                supported: 1
+            })
+        ));
+        assert!(matches!(
+            from_ron::<New>("(version: 2, data: (name: \"cube\"))"),
+            Err(VersionError::Migration { version: 2, .. })
+        ));
+        assert!(matches!(
+            from_ron::<New>("(version: 3, data: 5)"),
+            Err(VersionError::Ron(_))
+        ));
+    }
+}
@@ -123,6 +123,10 @@ impl Polytope for NamedAbstract {
         Self::new_generic(self.abs.omnitruncate())
     }
 
+    fn rectify(&self) -> Self {
+        Self::new_generic(self.abs.rectify())
+    }
+
     fn prism(&self) -> Self {
         Self::new(self.abs().prism(), self.name.clone().prism())
     }
@@ -4,6 +4,7 @@ use std::mem;
 use super::NamedPolytope;
 use crate::name::{Con, ConData, Name, NameData, Regular};
 
+use miratope_core::conc::file::obj::ObjResult;
 use miratope_core::conc::file::off::{OffError, OffResult};
 use miratope_core::conc::file::FromFile;
 use miratope_core::conc::ConcretePolytope;
@@ -37,6 +38,16 @@ impl FromFile for NamedConcrete {
     fn from_ggb(file: std::fs::File) -> miratope_core::conc::file::ggb::GgbResult<Self> {
         Ok(Self::new_generic(Concrete::from_ggb(file)?))
     }
+
+    fn from_obj(src: &str) -> ObjResult<Self> {
+        Ok(Self::new_generic(Concrete::from_obj(src)?))
+    }
+}
+
+impl From<Concrete> for NamedConcrete {
+    fn from(con: Concrete) -> Self {
+        Self::new_generic(con)
+    }
 }
 
 impl NamedConcrete {
@@ -77,6 +88,10 @@ impl Polytope for NamedConcrete {
         &mut self.con.abs
     }
 
+    fn points(&self) -> Option<&[Point]> {
+        Some(&self.con.vertices)
+    }
+
     fn nullitope() -> Self {
         Self::new(Concrete::nullitope(), Name::Nullitope)
     }
@@ -115,8 +130,10 @@ impl Polytope for NamedConcrete {
         Ok(())
     }
 
-    fn comp_append(&mut self, _p: Self) {
-        todo!()
+    fn comp_append(&mut self, p: Self) {
+        // Compound names are TBA.
+        self.con.comp_append(p.con);
+        self.set_generic();
     }
 
     fn element(&self, el: miratope_core::abs::elements::ElementRef) -> Option<Self> {
@@ -139,6 +156,10 @@ impl Polytope for NamedConcrete {
         Self::new_generic(self.con.omnitruncate())
     }
 
+    fn rectify(&self) -> Self {
+        Self::new_generic(self.con.rectify())
+    }
+
     fn prism(&self) -> Self {
         Self::new(self.con().prism(), self.name.clone().prism())
     }
@@ -0,0 +1,370 @@
+//! Canonical data for the classical uniform polytopes, used to check that
+//! polytopes built by [Miratope](https://github.com/OfficialURL/miratope-rs)
+//! or loaded from files are what they're supposed to be.
+//!
+//! Every entry stores the element counts, circumradius, and volume of a
+//! polytope with unit edge length. Polytopes are compared against an entry
+//! through [`verify_against_golden`], which scales the expected values by the
+//! edge length of the polytope, so that polytopes of any size can be checked.
+
+use std::f64::consts::FRAC_1_SQRT_2;
+
+use miratope_core::{
+    abs::rank::Rank,
+    conc::{Concrete, ConcretePolytope},
+    Float, Polytope,
+};
+
+/// The relative error allowed when comparing circumradii and volumes.
+pub const TOLERANCE: Float = 1e-6;
+
+/// The canonical data of a polytope with unit edge length.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Golden {
+    /// The name of the polytope.
+    pub name: &'static str,
+
+    /// The number of elements of each rank, from the vertices up to the
+    /// facets.
+    pub el_counts: &'static [usize],
+
+    /// The circumradius of the polytope.
+    pub circumradius: Float,
+
+    /// The volume of the polytope, within its own span.
+    pub volume: Float,
+}
+
+impl Golden {
+    /// Returns the rank of the polytope.
+    pub fn rank(&self) -> Rank {
+        Rank::from(self.el_counts.len())
+    }
+}
+
+/// Shorthand to declare an entry of [`GOLDEN`].
+const fn golden(
+    name: &'static str,
+    el_counts: &'static [usize],
+    circumradius: Float,
+    volume: Float,
+) -> Golden {
+    Golden {
+        name,
+        el_counts,
+        circumradius,
+        volume,
+    }
+}
+
+/// The canonical data of the classical uniform polytopes, all with unit edge
+/// length.
+pub const GOLDEN: &[Golden] = &[
+    // Polygons.
+    golden(
+        "triangle",
+        &[3, 3],
+        0.577_350_269_189_625_8,
+        0.433_012_701_892_219_3,
+    ),
+    golden("square", &[4, 4], FRAC_1_SQRT_2, 1.0),
+    golden(
+        "pentagon",
+        &[5, 5],
+        0.850_650_808_352_039_9,
+        1.720_477_400_588_967,
+    ),
+    golden("hexagon", &[6, 6], 1.0, 2.598_076_211_353_316),
+    // Polyhedra.
+    golden(
+        "tetrahedron",
+        &[4, 6, 4],
+        0.612_372_435_695_794_5,
+        0.117_851_130_197_757_93,
+    ),
+    golden("cube", &[8, 12, 6], 0.866_025_403_784_438_6, 1.0),
+    golden(
+        "octahedron",
+        &[6, 12, 8],
+        FRAC_1_SQRT_2,
+        0.471_404_520_791_031_73,
+    ),
+    golden(
+        "dodecahedron",
+        &[20, 30, 12],
+        1.401_258_538_444_073_4,
+        7.663_118_960_624_632,
+    ),
+    golden(
+        "icosahedron",
+        &[12, 30, 20],
+        0.951_056_516_295_153_5,
+        2.181_694_990_624_912_6,
+    ),
+    golden(
+        "triangular prism",
+        &[6, 9, 5],
+        0.763_762_615_825_973_3,
+        0.433_012_701_892_219_3,
+    ),
+    golden(
+        "truncated tetrahedron",
+        &[12, 18, 8],
+        1.172_603_939_955_857_4,
+        2.710_575_994_548_432,
+    ),
+    golden("cuboctahedron", &[12, 24, 14], 1.0, 2.357_022_603_955_158_5),
+    golden(
+        "icosidodecahedron",
+        &[30, 60, 32],
+        1.618_033_988_749_895,
+        13.835_525_936_249_404,
+    ),
+    // Polychora.
+    golden(
+        "pentachoron",
+        &[5, 10, 10, 5],
+        0.632_455_532_033_675_9,
+        0.023_292_374_765_622_81,
+    ),
+    golden("tesseract", &[16, 32, 24, 8], 1.0, 1.0),
+    golden(
+        "hexadecachoron",
+        &[8, 24, 32, 16],
+        FRAC_1_SQRT_2,
+        0.166_666_666_666_666_66,
+    ),
+    golden("icositetrachoron", &[24, 96, 96, 24], 1.0, 2.0),
+    golden(
+        "hexacosichoron",
+        &[120, 720, 1200, 600],
+        1.618_033_988_749_895,
+        26.475_424_859_373_685,
+    ),
+    golden(
+        "hecatonicosachoron",
+        &[600, 1200, 720, 120],
+        3.702_459_173_643_832_4,
+        787.856_981_034_338,
+    ),
+    // Polytera.
+    golden(
+        "hexateron",
+        &[6, 15, 20, 15, 6],
+        0.645_497_224_367_902_8,
+        0.003_608_439_182_435_160_5,
+    ),
+    golden(
+        "penteract",
+        &[32, 80, 80, 40, 10],
+        1.118_033_988_749_895,
+        1.0,
+    ),
+    golden(
+        "triacontaditeron",
+        &[10, 40, 80, 80, 32],
+        FRAC_1_SQRT_2,
+        0.047_140_452_079_103_17,
+    ),
+];
+
+/// Returns the canonical data of the polytope with a given name, if there is
+/// any. The name is case insensitive.
+pub fn golden_data(name: &str) -> Option<&'static Golden> {
+    GOLDEN
+        .iter()
+        .find(|golden| golden.name.eq_ignore_ascii_case(name))
+}
+
+/// Represents a mismatch between a polytope and its canonical data.
+#[derive(Clone, Debug, PartialEq)]
+pub enum GoldenError {
+    /// There's no canonical data for a polytope with the given name.
+    Unknown(String),
+
+    /// The element counts don't match.
+    ElementCounts {
+        /// The expected element counts.
+        expected: Vec<usize>,
+
+        /// The element counts of the polytope.
+        found: Vec<usize>,
+    },
+
+    /// The polytope has no edges to measure its size by, or they have zero
+    /// length.
+    NoEdges,
+
+    /// The circumradius doesn't match, or the polytope has no circumsphere.
+    Circumradius {
+        /// The expected circumradius, scaled by the edge length.
+        expected: Float,
+
+        /// The circumradius of the polytope, if it has any.
+        found: Option<Float>,
+    },
+
+    /// The volume doesn't match, or the polytope has no volume.
+    Volume {
+        /// The expected volume, scaled by the edge length.
+        expected: Float,
+
+        /// The volume of the polytope, if it has any.
+        found: Option<Float>,
+    },
+}
+
+impl std::fmt::Display for GoldenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Unknown(name) => write!(f, "no canonical data for \"{}\"", name),
+            Self::ElementCounts { expected, found } => write!(
+                f,
+                "expected element counts {:?}, found {:?}",
+                expected, found
+            ),
+            Self::NoEdges => write!(f, "polytope has no edges with nonzero length"),
+            Self::Circumradius { expected, found } => match found {
+                Some(found) => write!(f, "expected circumradius {}, found {}", expected, found),
+                None => write!(f, "expected circumradius {}, found none", expected),
+            },
+            Self::Volume { expected, found } => match found {
+                Some(found) => write!(f, "expected volume {}, found {}", expected, found),
+                None => write!(f, "expected volume {}, found none", expected),
+            },
+        }
+    }
+}
+
+impl std::error::Error for GoldenError {}
+
+/// The result of checking a polytope against its canonical data.
+pub type GoldenResult<T> = Result<T, GoldenError>;
+
+/// Returns whether two values agree up to [`TOLERANCE`].
+fn approx_eq(found: Float, expected: Float) -> bool {
+    (found - expected).abs() <= TOLERANCE * expected.abs().max(1.0)
+}
+
+/// Checks a polytope against the canonical data with a given name. The
+/// expected circumradius and volume are scaled according to the length of the
+/// first edge of the polytope.
+pub fn verify_against_golden(poly: &Concrete, name: &str) -> GoldenResult<()> {
+    let golden = golden_data(name).ok_or_else(|| GoldenError::Unknown(name.to_string()))?;
+
+    // The element counts, without the minimal and maximal elements.
+    let mut found: Vec<_> = poly.el_counts().into_iter().skip(1).collect();
+    found.pop();
+    if found != golden.el_counts {
+        return Err(GoldenError::ElementCounts {
+            expected: golden.el_counts.to_vec(),
+            found,
+        });
+    }
+
+    let edge = match poly.edge_len(0) {
+        Some(edge) if edge > 0.0 => edge,
+        _ => return Err(GoldenError::NoEdges),
+    };
+
+    let expected = golden.circumradius * edge;
+    let found = poly.circumsphere().map(|sphere| sphere.radius());
+    if !matches!(found, Some(found) if approx_eq(found, expected)) {
+        return Err(GoldenError::Circumradius { expected, found });
+    }
+
+    // Volumes scale with the edge length to the power of the rank.
+    let expected = golden.volume * edge.powi(golden.rank().into_usize() as i32);
+    let mut poly = poly.clone();
+    poly.abs_sort();
+    let found = poly.volume().map(Float::abs);
+    if !matches!(found, Some(found) if approx_eq(found, expected)) {
+        return Err(GoldenError::Volume { expected, found });
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use miratope_core::conc::file::FromFile;
+
+    /// Loads a polytope from the library.
+    fn load(path: &str) -> Concrete {
+        let path = format!("{}/../lib/{}", env!("CARGO_MANIFEST_DIR"), path);
+        Concrete::from_path(&path).unwrap_or_else(|err| panic!("{}: {}", path, err))
+    }
+
+    /// Asserts that a polytope matches its canonical data.
+    fn assert_golden(poly: &Concrete, name: &str) {
+        if let Err(err) = verify_against_golden(poly, name) {
+            panic!("{} failed verification: {}", name, err);
+        }
+    }
+
+    /// Checks that the polytopes that are built from scratch match their data,
+    /// whatever their size.
+    #[test]
+    fn built() {
+        for (n, name) in [
+            (3, "triangle"),
+            (4, "square"),
+            (5, "pentagon"),
+            (6, "hexagon"),
+        ] {
+            assert_golden(&Concrete::polygon(n), name);
+        }
+
+        for (rank, [hypercube, orthoplex]) in [
+            (3, ["cube", "octahedron"]),
+            (4, ["tesseract", "hexadecachoron"]),
+            (5, ["penteract", "triacontaditeron"]),
+        ] {
+            let rank = Rank::new(rank);
+            assert_golden(&Concrete::hypercube(rank), hypercube);
+            assert_golden(&Concrete::orthoplex(rank), orthoplex);
+        }
+
+        assert_golden(&Concrete::uniform_prism(3, 1), "triangular prism");
+    }
+
+    /// Checks that the polytopes in the library match their data.
+    #[test]
+    fn library() {
+        for (path, name) in [
+            ("3D/uniform/cat1/convex/tet.off", "tetrahedron"),
+            ("3D/uniform/cat1/convex/ike.off", "icosahedron"),
+            ("3D/uniform/cat1/convex/doe.off", "dodecahedron"),
+            ("3D/uniform/cat2/normal/tut.off", "truncated tetrahedron"),
+            ("3D/uniform/cat3/rectates/co.off", "cuboctahedron"),
+            ("3D/uniform/cat3/rectates/id.off", "icosidodecahedron"),
+            ("4D/regular/Pentachoron.off", "pentachoron"),
+            ("4D/regular/Icositetrachoron.off", "icositetrachoron"),
+            ("4D/regular/Hexacosichoron.off", "hexacosichoron"),
+            ("5D/Uniform/Hexateron.off", "hexateron"),
+        ] {
+            assert_golden(&load(path), name);
+        }
+    }
+
+    /// Checks that mismatches are reported.
+    #[test]
+    fn mismatch() {
+        let mut cube = Concrete::hypercube(Rank::new(3));
+        assert!(matches!(
+            verify_against_golden(&cube, "dodecahedron"),
+            Err(GoldenError::ElementCounts { .. })
+        ));
+        assert_eq!(
+            verify_against_golden(&cube, "tetrakis hexahedron"),
+            Err(GoldenError::Unknown("tetrakis hexahedron".to_string()))
+        );
+
+        // A cuboid has the element counts of a cube, but not its proportions.
+        for v in cube.vertices.iter_mut() {
+            v[2] *= 2.0;
+        }
+        assert!(verify_against_golden(&cube, "cube").is_err());
+    }
+}
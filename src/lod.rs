@@ -0,0 +1,218 @@
+//! Contains the level of detail system, which keeps huge polytopes interactive
+//! by only drawing the elements that are large enough on screen to be seen.
+//!
+//! Edges shorter than a given number of pixels and faces smaller than a given
+//! size are skipped, as is anything outside of the view of the camera. Since
+//! this depends on the camera, the meshes of huge polytopes get rebuilt every
+//! so often while the camera moves. Their faces only get triangulated once,
+//! however, and each rebuild only picks out the triangles that are drawn.
+
+use bevy::math::{Mat4, Vec3, Vec4};
+use miratope_core::{abs::rank::Rank, conc::Concrete, Polytope};
+use vec_like::VecLike;
+
+/// The settings of the level of detail system.
+#[derive(Clone, Copy, PartialEq)]
+pub struct LodSettings {
+    /// Whether the level of detail system is enabled.
+    pub enabled: bool,
+
+    /// The number of edges from which a polytope counts as huge. Only huge
+    /// polytopes have their level of detail reduced.
+    pub min_edges: usize,
+
+    /// The length in pixels below which edges aren't drawn.
+    pub min_edge_length: f32,
+
+    /// The size in pixels below which faces aren't drawn.
+    pub min_face_size: f32,
+
+    /// Whether elements outside of the view of the camera are skipped.
+    pub frustum_culling: bool,
+
+    /// The time in seconds between rebuilds of the meshes while the camera
+    /// moves.
+    pub interval: f64,
+}
+
+impl Default for LodSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            min_edges: 20_000,
+            min_edge_length: 2.0,
+            min_face_size: 4.0,
+            frustum_culling: true,
+            interval: 0.25,
+        }
+    }
+}
+
+impl LodSettings {
+    /// Returns whether the level of detail of a polytope gets reduced.
+    pub fn applies(&self, poly: &Concrete) -> bool {
+        self.enabled && poly.el_count(Rank::new(1)) >= self.min_edges
+    }
+}
+
+/// Projects points of an object onto the screen.
+pub struct Viewport {
+    /// The transform from the coordinates of the object into clip space.
+    model_view_proj: Mat4,
+
+    /// The width of the screen in pixels.
+    width: f32,
+
+    /// The height of the screen in pixels.
+    height: f32,
+}
+
+impl Viewport {
+    /// Initializes a new viewport, given the transform of an object, the
+    /// transform of the camera, the vertical field of view of the camera in
+    /// radians, its clipping planes, and the size of the screen in pixels.
+    pub fn new(
+        model: Mat4,
+        camera: Mat4,
+        fov: f32,
+        near: f32,
+        far: f32,
+        width: f32,
+        height: f32,
+    ) -> Self {
+        let projection = Mat4::perspective_rh(fov, width / height.max(1.0), near, far);
+
+        Self {
+            model_view_proj: projection * camera.inverse() * model,
+            width,
+            height,
+        }
+    }
+
+    /// Projects a point onto the screen, and returns its coordinates in
+    /// pixels, or `None` if it's behind the camera.
+    pub fn project(&self, v: [f32; 3]) -> Option<[f32; 2]> {
+        let clip: Vec4 = self.model_view_proj * Vec3::from(v).extend(1.0);
+
+        if clip.w > f32::EPSILON {
+            Some([
+                (clip.x / clip.w + 1.0) / 2.0 * self.width,
+                (1.0 - clip.y / clip.w) / 2.0 * self.height,
+            ])
+        } else {
+            None
+        }
+    }
+}
+
+/// A bounding box on the screen, in pixels.
+struct ScreenBox {
+    /// The smallest coordinates of the box.
+    min: [f32; 2],
+
+    /// The largest coordinates of the box.
+    max: [f32; 2],
+}
+
+impl ScreenBox {
+    /// Returns the bounding box of some points on the screen, or `None` if any
+    /// of them is behind the camera.
+    fn new<I: Iterator<Item = Option<[f32; 2]>>>(points: I) -> Option<Self> {
+        let mut min = [f32::INFINITY; 2];
+        let mut max = [f32::NEG_INFINITY; 2];
+
+        for p in points {
+            let [x, y] = p?;
+            min = [min[0].min(x), min[1].min(y)];
+            max = [max[0].max(x), max[1].max(y)];
+        }
+
+        Some(Self { min, max })
+    }
+
+    /// Returns the size of the box, as the length of its longest side.
+    fn size(&self) -> f32 {
+        (self.max[0] - self.min[0]).max(self.max[1] - self.min[1])
+    }
+
+    /// Returns whether the box intersects the screen.
+    fn on_screen(&self, viewport: &Viewport) -> bool {
+        self.max[0] >= 0.0
+            && self.max[1] >= 0.0
+            && self.min[0] <= viewport.width
+            && self.min[1] <= viewport.height
+    }
+}
+
+/// Returns whether an element whose vertices have a given bounding box should
+/// be drawn. Elements that cross behind the camera are always drawn.
+fn is_drawn(
+    screen_box: Option<ScreenBox>,
+    min_size: f32,
+    viewport: &Viewport,
+    settings: &LodSettings,
+) -> bool {
+    match screen_box {
+        Some(screen_box) => {
+            screen_box.size() >= min_size
+                && (!settings.frustum_culling || screen_box.on_screen(viewport))
+        }
+        None => true,
+    }
+}
+
+/// Returns whether each edge and each face of a polytope is large enough on
+/// screen to be drawn, given the coordinates of its vertices in 3D.
+pub fn drawn_elements(
+    poly: &Concrete,
+    vertices: &[[f32; 3]],
+    viewport: &Viewport,
+    settings: &LodSettings,
+) -> (Vec<bool>, Vec<bool>) {
+    let screen: Vec<_> = vertices.iter().map(|&v| viewport.project(v)).collect();
+
+    let edges: Vec<_> = poly
+        .abs
+        .ranks
+        .get(Rank::new(1))
+        .map(|edges| {
+            edges
+                .iter()
+                .map(|edge| {
+                    let screen_box = ScreenBox::new(edge.subs.iter().map(|&v| screen[v]));
+                    is_drawn(screen_box, settings.min_edge_length, viewport, settings)
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let faces = match (
+        poly.abs.ranks.get(Rank::new(1)),
+        poly.abs.ranks.get(Rank::new(2)),
+    ) {
+        (Some(edge_list), Some(faces)) => faces
+            .iter()
+            .map(|face| {
+                let screen_box = ScreenBox::new(
+                    face.subs
+                        .iter()
+                        .flat_map(|&e| edge_list[e].subs.iter())
+                        .map(|&v| screen[v]),
+                );
+                is_drawn(screen_box, settings.min_face_size, viewport, settings)
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    (edges, faces)
+}
+
+/// Hides the colors of the elements that aren't drawn.
+pub fn hide<T>(colors: &mut [Option<T>], drawn: &[bool]) {
+    for (color, &drawn) in colors.iter_mut().zip(drawn) {
+        if !drawn {
+            *color = None;
+        }
+    }
+}
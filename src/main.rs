@@ -63,15 +63,20 @@ use bevy::render::{camera::PerspectiveProjection, pipeline::PipelineDescriptor};
 use bevy_egui::EguiPlugin;
 use miratope_core::conc::file::FromFile;
 use miratope_lang::poly::conc::NamedConcrete;
-use no_cull_pipeline::PbrNoBackfaceBundle;
 
+use palette::ColorSettings;
 use ui::{
-    camera::{CameraInputEvent, ProjectionType},
+    camera::CameraInputEvent,
+    scene::{self, Active},
     MiratopePlugins,
 };
 
+mod lod;
 mod mesh;
 mod no_cull_pipeline;
+mod palette;
+mod projection;
+mod render;
 mod ui;
 
 /// The link to the GitHub issues.
@@ -98,6 +103,7 @@ fn setup(
     mut materials: ResMut<Assets<StandardMaterial>>,
     mut shaders: ResMut<Assets<Shader>>,
     mut pipelines: ResMut<Assets<PipelineDescriptor>>,
+    color_settings: Res<ColorSettings>,
 ) {
     // Default polytope.
     let poly = NamedConcrete::from_off(include_str!("default.off")).unwrap();
@@ -114,39 +120,21 @@ fn setup(
         Color::rgb_u8(126, 192, 255).into(),
     );
 
-    // Wireframe material.
-    let wf_material = materials.set(WIREFRAME_UNSELECTED_MATERIAL, Color::rgb_u8(0, 0, 0).into());
-
-    // Mesh material.
-    let mesh_material = materials.add(StandardMaterial {
-        base_color: Color::rgb_u8(255, 255, 255),
-        metallic: 0.2,
-        ..Default::default()
-    });
-
     // Camera configuration.
     let mut cam_anchor = Default::default();
     let mut cam = Default::default();
     CameraInputEvent::reset(&mut cam_anchor, &mut cam);
 
-    commands
-        .spawn()
-        // Mesh
-        .insert_bundle(PbrNoBackfaceBundle {
-            mesh: meshes.add(mesh::mesh(&poly.con, ProjectionType::Perspective)),
-            material: mesh_material,
-            ..Default::default()
-        })
-        // Wireframe
-        .with_children(|cb| {
-            cb.spawn().insert_bundle(PbrNoBackfaceBundle {
-                mesh: meshes.add(mesh::wireframe(&poly.con, ProjectionType::Perspective)),
-                material: wf_material,
-                ..Default::default()
-            });
-        })
-        // Polytope
-        .insert(poly);
+    // The default polytope is the only one in the scene.
+    let poly = scene::spawn_polytope(
+        &mut commands,
+        &mut meshes,
+        &mut materials,
+        poly,
+        Default::default(),
+        color_settings.default_scheme.clone(),
+    );
+    commands.entity(poly).insert(Active);
 
     // Camera anchor
     commands
@@ -178,5 +166,3 @@ fn setup(
 
 const WIREFRAME_SELECTED_MATERIAL: HandleUntyped =
     HandleUntyped::weak_from_u64(StandardMaterial::TYPE_UUID, 0x82A3A5DD3A34CC21);
-const WIREFRAME_UNSELECTED_MATERIAL: HandleUntyped =
-    HandleUntyped::weak_from_u64(StandardMaterial::TYPE_UUID, 0x82A3A5DD3A34CC22);
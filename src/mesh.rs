@@ -1,26 +1,221 @@
 //! Contains the methods that take a polytope and turn it into a mesh.
 
-use std::collections::HashMap;
+use std::{borrow::Cow, collections::HashMap};
 
-use crate::ui::camera::ProjectionType;
+use crate::projection::{Projection, Reduction};
 
 use bevy::{
-    prelude::Mesh,
+    prelude::{shape, Color, Mesh},
     render::{mesh::Indices, pipeline::PrimitiveTopology},
 };
 use lyon::{math::point, path::Path, tessellation::*};
 use miratope_core::{
-    abs::{elements::ElementList, rank::Rank},
+    abs::{
+        elements::{ElementList, ElementRef},
+        rank::Rank,
+    },
     conc::{
         cycle::{Cycle, CycleBuilder},
         Concrete, ConcretePolytope,
     },
-    geometry::{Point, Subspace, Vector},
+    geometry::{Point, Subspace},
     Consts, Float, Polytope,
 };
 
 use vec_like::*;
 
+/// How much the surface of a polytope shrinks in hidden-line mode, relative
+/// to its size. This needs to be large enough for the depth buffer to tell the
+/// surface and the edges apart.
+const OCCLUDER_SHRINK: f32 = 0.02;
+
+/// An error that keeps a polytope from being turned into a mesh.
+#[derive(Clone, Copy, Debug)]
+pub enum MeshError {
+    /// The polytope has more vertices than can be indexed by a mesh.
+    TooManyVertices(usize),
+
+    /// Some vertex has a different number of coordinates than the others.
+    Dimension {
+        /// The index of the vertex.
+        idx: usize,
+
+        /// The number of coordinates of the vertex.
+        len: usize,
+
+        /// The number of coordinates of the first vertex.
+        dim: usize,
+    },
+
+    /// Some vertex has a coordinate that's infinite or NaN.
+    NonFinite(usize),
+
+    /// Some element has a subelement that doesn't exist.
+    Index {
+        /// The element at fault.
+        el: ElementRef,
+
+        /// The invalid index.
+        index: usize,
+    },
+
+    /// Some edge doesn't have exactly two vertices.
+    Edge {
+        /// The index of the edge.
+        idx: usize,
+
+        /// The number of vertices of the edge.
+        len: usize,
+    },
+
+    /// Some face isn't made out of cycles, i.e. some vertex isn't in exactly
+    /// two of its edges.
+    Face {
+        /// The index of the face.
+        idx: usize,
+
+        /// The vertex at fault.
+        vertex: usize,
+
+        /// The number of edges of the face with the vertex.
+        count: usize,
+    },
+
+    /// Some face couldn't be triangulated.
+    Tessellation(usize),
+}
+
+impl std::fmt::Display for MeshError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TooManyVertices(count) => write!(
+                f,
+                "the polytope has {} vertices, but at most {} can be drawn",
+                count,
+                u32::MAX
+            ),
+            Self::Dimension { idx, len, dim } => write!(
+                f,
+                "vertex {} has {} coordinates, but the first vertex has {}",
+                idx, len, dim
+            ),
+            Self::NonFinite(idx) => {
+                write!(
+                    f,
+                    "vertex {} has a coordinate that isn't a finite number",
+                    idx
+                )
+            }
+            Self::Index { el, index } => write!(
+                f,
+                "{} has a subelement with index {}, but it doesn't exist",
+                el, index
+            ),
+            Self::Edge { idx, len } => {
+                write!(f, "edge {} has {} vertices, expected 2", idx, len)
+            }
+            Self::Face { idx, vertex, count } => write!(
+                f,
+                "face {} has vertex {} in {} of its edges, expected 2",
+                idx, vertex, count
+            ),
+            Self::Tessellation(idx) => write!(f, "face {} couldn't be triangulated", idx),
+        }
+    }
+}
+
+impl std::error::Error for MeshError {}
+
+/// The result of turning a polytope into a mesh.
+pub type MeshResult<T> = Result<T, MeshError>;
+
+/// Checks that a polytope can be turned into a mesh, i.e. that its vertices
+/// all have the same number of finite coordinates, that its edges and faces
+/// only refer to elements that exist, that its edges have two vertices each,
+/// and that its faces are made out of cycles.
+///
+/// Meshes are only ever built from polytopes that pass this check, so that
+/// invalid polytopes can be reported rather than crash the application.
+pub fn check(poly: &Concrete) -> MeshResult<()> {
+    let vertex_count = poly.vertex_count();
+    if vertex_count > u32::MAX as usize {
+        return Err(MeshError::TooManyVertices(vertex_count));
+    }
+
+    if let Some(first) = poly.vertices.first() {
+        let dim = first.len();
+
+        for (idx, v) in poly.vertices.iter().enumerate() {
+            if v.len() != dim {
+                return Err(MeshError::Dimension {
+                    idx,
+                    len: v.len(),
+                    dim,
+                });
+            }
+
+            if v.iter().any(|c| !c.is_finite()) {
+                return Err(MeshError::NonFinite(idx));
+            }
+        }
+    }
+
+    // Checks that the edges and faces only refer to elements that exist.
+    for r in 1..=2 {
+        let rank = Rank::new(r);
+        let sub_count = poly.el_count(rank.minus_one());
+
+        if let Some(elements) = poly.abs.ranks.get(rank) {
+            for (idx, el) in elements.iter().enumerate() {
+                if let Some(&index) = el.subs.iter().find(|&&sub| sub >= sub_count) {
+                    return Err(MeshError::Index {
+                        el: ElementRef::new(rank, idx),
+                        index,
+                    });
+                }
+            }
+        }
+    }
+
+    let empty_els = ElementList::new();
+    let edges = poly.abs.ranks.get(Rank::new(1)).unwrap_or(&empty_els);
+
+    for (idx, edge) in edges.iter().enumerate() {
+        let len = edge.subs.len();
+        if len != 2 {
+            return Err(MeshError::Edge { idx, len });
+        }
+    }
+
+    if let Some(faces) = poly.abs.ranks.get(Rank::new(2)) {
+        for (idx, face) in faces.iter().enumerate() {
+            let mut counts = HashMap::new();
+            for &e in &face.subs {
+                for &v in &edges[e].subs {
+                    *counts.entry(v).or_insert(0) += 1;
+                }
+            }
+
+            if let Some((&vertex, &count)) = counts.iter().find(|(_, &count)| count != 2) {
+                return Err(MeshError::Face { idx, vertex, count });
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the length of an edge of a polytope, or `None` if the edge doesn't
+/// exist or if it doesn't have exactly two vertices that exist. Unlike most
+/// other functions here, this doesn't need the polytope to pass [`check`].
+pub fn edge_length(poly: &Concrete, idx: usize) -> Option<Float> {
+    let edge = poly.abs.ranks.get(Rank::new(1))?.get(idx)?;
+    match *edge.subs.0.as_slice() {
+        [v0, v1] => Some((poly.vertices.get(v0)? - poly.vertices.get(v1)?).norm()),
+        _ => None,
+    }
+}
+
 /// Attempts to turn the cycle into a 2D path, which can then be given to
 /// the tessellator. Uses the specified vertex list to grab the coordinates
 /// of the vertices on the path.
@@ -98,14 +293,18 @@ struct Triangulation {
     extra_vertices: Vec<Point>,
 
     /// Indices of the vertices that make up the triangles.
-    triangles: Vec<u16>,
+    triangles: Vec<u32>,
+
+    /// The index of the face that each triangle belongs to.
+    faces: Vec<usize>,
 }
 
 impl Triangulation {
-    /// Creates a new triangulation from a polytope.
-    fn new(polytope: &Concrete) -> Triangulation {
+    /// Creates a new triangulation from a polytope, which must pass [`check`].
+    fn new(polytope: &Concrete) -> MeshResult<Triangulation> {
         let mut extra_vertices = Vec::new();
         let mut triangles = Vec::new();
+        let mut face_indices = Vec::new();
 
         let empty_els = ElementList::new();
 
@@ -116,27 +315,22 @@ impl Triangulation {
         let edges = elements_or(Rank::new(1));
         let faces = elements_or(Rank::new(2));
 
-        let concrete_vertex_len = polytope.vertices.len() as u16;
+        let concrete_vertex_len = polytope.vertices.len() as u32;
 
         // We render each face separately.
-        for face in faces {
+        for (face_idx, face) in faces.iter().enumerate() {
             let mut vertex_loop = CycleBuilder::with_capacity(face.subs.len());
 
             // We first figure out the vertices in order.
-            for [v0, v1] in face.subs.iter().map(|&i| {
+            for &i in &face.subs {
                 let subs = &edges[i].subs;
-                let len = subs.len();
-
-                debug_assert_eq!(len, 2, "Edge has {} subelements, expected 2.", len);
-                [subs[0], subs[1]]
-            }) {
-                vertex_loop.push(v0, v1);
+                vertex_loop.push(subs[0], subs[1]);
             }
 
             // We tesselate this path.
             let cycles = vertex_loop.cycles();
             if let Some(path) = path(&cycles, &polytope.vertices) {
-                let mut geometry: VertexBuffers<_, u16> = VertexBuffers::new();
+                let mut geometry: VertexBuffers<_, u32> = VertexBuffers::new();
 
                 // Configures all of the options of the tessellator.
                 FillTessellator::new()
@@ -150,7 +344,7 @@ impl Triangulation {
                             vertex.sources().next().unwrap()
                         }),
                     )
-                    .unwrap();
+                    .map_err(|_| MeshError::Tessellation(face_idx))?;
 
                 // Maps EndpointIds to the indices in the original vertex list.
                 let mut id_to_idx = Vec::new();
@@ -165,16 +359,21 @@ impl Triangulation {
                 let mut vertex_hash = HashMap::new();
 
                 for (new_id, vertex_source) in geometry.vertices.into_iter().enumerate() {
-                    let new_id = new_id as u16;
+                    let new_id = new_id as u32;
 
                     match vertex_source {
                         // This is one of the concrete vertices of the polytope.
                         VertexSource::Endpoint { id } => {
-                            vertex_hash.insert(new_id, id_to_idx[id.to_usize()] as u16);
+                            vertex_hash.insert(new_id, id_to_idx[id.to_usize()] as u32);
                         }
 
                         // This is a new vertex that has been added to the tesselation.
                         VertexSource::Edge { from, to, t } => {
+                            let total = concrete_vertex_len as usize + extra_vertices.len() + 1;
+                            if total > u32::MAX as usize {
+                                return Err(MeshError::TooManyVertices(total));
+                            }
+
                             let from = &polytope.vertices[id_to_idx[from.to_usize()]];
                             let to = &polytope.vertices[id_to_idx[to.to_usize()]];
 
@@ -182,7 +381,7 @@ impl Triangulation {
                             let p = from * (1.0 - t) + to * t;
 
                             vertex_hash
-                                .insert(new_id, concrete_vertex_len + extra_vertices.len() as u16);
+                                .insert(new_id, concrete_vertex_len + extra_vertices.len() as u32);
 
                             extra_vertices.push(p);
                         }
@@ -197,13 +396,16 @@ impl Triangulation {
                 {
                     triangles.push(new_idx);
                 }
+
+                face_indices.resize(triangles.len() / 3, face_idx);
             }
         }
 
-        Self {
+        Ok(Self {
             extra_vertices,
             triangles,
-        }
+            faces: face_indices,
+        })
     }
 }
 
@@ -229,70 +431,90 @@ fn normals(vertices: &[[f32; 3]]) -> Vec<[f32; 3]> {
 }
 
 /// Returns an empty mesh.
-fn empty_mesh() -> Mesh {
+pub fn empty_mesh() -> Mesh {
     let mut mesh = Mesh::new(PrimitiveTopology::LineList);
     mesh.set_attribute(Mesh::ATTRIBUTE_NORMAL, vec![[0.0; 3]]);
     mesh.set_attribute(Mesh::ATTRIBUTE_POSITION, vec![[0.0; 3]]);
     mesh.set_attribute(Mesh::ATTRIBUTE_UV_0, vec![[0.0; 2]]);
-    mesh.set_indices(Some(Indices::U16(Vec::new())));
+    mesh.set_attribute(Mesh::ATTRIBUTE_COLOR, vec![[1.0; 4]]);
+    mesh.set_indices(Some(Indices::U32(Vec::new())));
+
+    mesh
+}
+
+/// Returns a sphere of radius 1 centered at the origin, used to draw the
+/// vertices of polytopes.
+pub fn sphere() -> Mesh {
+    let mut mesh = Mesh::from(shape::Icosphere {
+        radius: 1.0,
+        subdivisions: 2,
+    });
+
+    // The colors are given by the materials.
+    let count = mesh.count_vertices();
+    mesh.set_attribute(Mesh::ATTRIBUTE_COLOR, vec![[1.0; 4]; count]);
+
+    mesh
+}
+
+/// Returns an open cylinder of radius 1 going from the origin to the point
+/// `(0, 1, 0)`, used to draw the edges of polytopes.
+pub fn tube(segments: usize) -> Mesh {
+    let mut positions = Vec::with_capacity(2 * (segments + 1));
+    let mut normals = Vec::with_capacity(2 * (segments + 1));
+    let mut uvs = Vec::with_capacity(2 * (segments + 1));
+
+    for i in 0..=segments {
+        let t = i as f32 / segments as f32;
+        let (sin, cos) = (t * std::f32::consts::TAU).sin_cos();
+
+        for y in 0..2 {
+            positions.push([cos, y as f32, sin]);
+            normals.push([cos, 0.0, sin]);
+            uvs.push([t, y as f32]);
+        }
+    }
+
+    let mut indices = Vec::with_capacity(6 * segments);
+    for i in 0..segments as u32 {
+        let (a, b, c, d) = (2 * i, 2 * i + 1, 2 * i + 2, 2 * i + 3);
+        indices.extend_from_slice(&[a, b, c, c, b, d]);
+    }
+
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+    mesh.set_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+    mesh.set_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    mesh.set_attribute(Mesh::ATTRIBUTE_COLOR, vec![[1.0; 4]; positions.len()]);
+    mesh.set_indices(Some(Indices::U32(indices)));
+    mesh.set_attribute(Mesh::ATTRIBUTE_POSITION, positions);
 
     mesh
 }
 
 /// Gets the coordinates of the vertices, after projecting down into 3D.
-fn vertex_coords<'a, T: Iterator<Item = &'a Point>>(
+pub fn vertex_coords<'a, T: Iterator<Item = &'a Point>>(
     poly: &Concrete,
     vertices: T,
-    projection_type: ProjectionType,
+    projection_type: Projection,
 ) -> Vec<[f32; 3]> {
-    let dim = poly.dim_or();
-
-    // If the polytope is at most 3D, we just embed it into 3D space.
-    if projection_type.is_orthogonal() || dim <= 3 {
-        vertices
-            .map(|point| {
-                let mut iter = point.iter().take(3).map(|&c| c as f32);
-                let x = iter.next().unwrap_or(0.0);
-                let y = iter.next().unwrap_or(0.0);
-                let z = iter.next().unwrap_or(0.0);
-                [x, y, z]
-            })
-            .collect()
-    }
-    // Else, we project it down.
-    else {
-        // Distance from the projection planes.
-        let mut direction = Vector::zeros(dim);
-        direction[3] = 1.0;
-
-        let (min, max) = poly.minmax(&direction).unwrap();
-        let dist = (min as f32 - 1.0).abs().max(max as f32 + 1.0).abs();
-
-        vertices
-            .map(|point| {
-                let factor: f32 = point.iter().skip(3).map(|&x| x as f32 + dist).product();
-
-                // We scale the first three coordinates accordingly.
-                let mut iter = point.iter().copied().take(3).map(|c| c as f32 / factor);
-                let x = iter.next().unwrap();
-                let y = iter.next().unwrap();
-                let z = iter.next().unwrap();
-                [x, y, z]
-            })
-            .collect()
-    }
+    let reduction = Reduction::new(poly, projection_type);
+    vertices.map(|point| reduction.apply(point)).collect()
 }
 
-/// Builds the mesh of a polytope.
-pub fn mesh(poly: &Concrete, projection_type: ProjectionType) -> Mesh {
-    // If there's no vertices, returns an empty mesh.
-    if poly.vertex_count() == 0 {
-        return empty_mesh();
-    }
+/// Triangulates the faces of a polytope and projects it into 3D. Returns the
+/// projected vertices, the indices of the vertices of each triangle, and the
+/// index of the face each triangle belongs to.
+///
+/// The polytope must have at least one vertex.
+pub fn triangles(
+    poly: &Concrete,
+    projection_type: Projection,
+) -> MeshResult<(Vec<[f32; 3]>, Vec<u32>, Vec<usize>)> {
+    check(poly)?;
 
     // Triangulates the polytope's faces, projects the vertices of both the
     // polytope and the triangulation.
-    let triangulation = Triangulation::new(poly);
+    let triangulation = Triangulation::new(poly)?;
     let vertices = vertex_coords(
         &poly,
         poly.vertices
@@ -301,53 +523,188 @@ pub fn mesh(poly: &Concrete, projection_type: ProjectionType) -> Mesh {
         projection_type,
     );
 
-    // Builds the actual mesh.
-    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
-    mesh.set_attribute(Mesh::ATTRIBUTE_UV_0, vec![[0.0, 1.0]; vertices.len()]);
-    mesh.set_attribute(Mesh::ATTRIBUTE_NORMAL, normals(&vertices));
-    mesh.set_attribute(Mesh::ATTRIBUTE_POSITION, vertices);
-    mesh.set_indices(Some(Indices::U16(triangulation.triangles)));
-
-    mesh
+    Ok((vertices, triangulation.triangles, triangulation.faces))
 }
 
-/// Builds the wireframe of a polytope.
-pub fn wireframe(poly: &Concrete, projection_type: ProjectionType) -> Mesh {
-    let vertex_count = poly.vertex_count();
-
-    // If there's no vertices, returns an empty mesh.
-    if vertex_count == 0 {
-        return empty_mesh();
-    }
+/// Projects a polytope into 3D. Returns the projected vertices, and the
+/// indices of the vertices of each edge.
+///
+/// The polytope must have at least one vertex.
+pub fn edges(
+    poly: &Concrete,
+    projection_type: Projection,
+) -> MeshResult<(Vec<[f32; 3]>, Vec<u32>)> {
+    check(poly)?;
 
     let edges = poly.abs.ranks.get(Rank::new(1));
     let edge_count = poly.el_count(Rank::new(1));
 
-    // We add a single vertex so that Miratope doesn't crash.
     let vertices = vertex_coords(&poly, poly.vertices.iter(), projection_type);
     let mut indices = Vec::with_capacity(edge_count * 2);
 
     // Adds the edges to the wireframe.
     if let Some(edges) = edges {
         for edge in edges {
-            debug_assert_eq!(
-                edge.subs.len(),
-                2,
-                "Edge must have exactly 2 elements, found {}.",
-                edge.subs.len()
-            );
-
-            indices.push(edge.subs[0] as u16);
-            indices.push(edge.subs[1] as u16);
+            indices.push(edge.subs[0] as u32);
+            indices.push(edge.subs[1] as u32);
+        }
+    }
+
+    Ok((vertices, indices))
+}
+
+/// Converts a color into the components of a vertex color attribute.
+fn vertex_color(color: Color) -> [f32; 4] {
+    color.into()
+}
+
+/// The faces of a polytope, triangulated and projected into 3D. Since
+/// triangulating is the slow part of building a mesh, this can be kept around
+/// to build meshes with different colors.
+pub struct Surface {
+    /// The projected vertices of the polytope, followed by those added by the
+    /// triangulation.
+    vertices: Vec<[f32; 3]>,
+
+    /// The number of vertices of the polytope.
+    vertex_count: usize,
+
+    /// The indices of the vertices of each triangle, in groups of three.
+    triangles: Vec<u32>,
+
+    /// The index of the face that each triangle belongs to.
+    faces: Vec<usize>,
+}
+
+impl Surface {
+    /// Triangulates the faces of a polytope and projects them into 3D.
+    pub fn new(poly: &Concrete, projection_type: Projection) -> MeshResult<Self> {
+        let vertex_count = poly.vertex_count();
+
+        // If there's no vertices, there's nothing to triangulate.
+        if vertex_count == 0 {
+            return Ok(Self {
+                vertices: Vec::new(),
+                vertex_count,
+                triangles: Vec::new(),
+                faces: Vec::new(),
+            });
+        }
+
+        let (vertices, triangles, faces) = triangles(poly, projection_type)?;
+        Ok(Self {
+            vertices,
+            vertex_count,
+            triangles,
+            faces,
+        })
+    }
+
+    /// Builds the mesh of the polytope, given the color of each of its faces,
+    /// or `None` for the faces that are hidden.
+    ///
+    /// Every triangle gets its own vertices, so that it can be colored
+    /// independently of its neighbors.
+    pub fn mesh(&self, face_colors: &[Option<Color>]) -> Mesh {
+        self.build(face_colors, 1.0)
+    }
+
+    /// Builds the surface that hides the edges behind the polytope in
+    /// hidden-line mode, given the color of each of its faces, or `None` for
+    /// the faces that are hidden.
+    ///
+    /// The surface is shrunk slightly towards the center of the polytope, so
+    /// that it doesn't hide the edges that bound it.
+    pub fn occluder(&self, face_colors: &[Option<Color>]) -> Mesh {
+        self.build(face_colors, 1.0 - OCCLUDER_SHRINK)
+    }
+
+    /// Builds the mesh of the faces of the polytope, scaled by some factor
+    /// around the centroid of its vertices.
+    fn build(&self, face_colors: &[Option<Color>], scale: f32) -> Mesh {
+        // If there's no vertices, returns an empty mesh.
+        if self.vertex_count == 0 {
+            return empty_mesh();
+        }
+
+        let mut vertices = Cow::Borrowed(self.vertices.as_slice());
+        if (scale - 1.0).abs() > f32::EPSILON {
+            // Only the vertices of the polytope count towards the centroid,
+            // not the ones added by the triangulation.
+            let count = self.vertex_count;
+            let mut centroid = [0.0; 3];
+            for v in &self.vertices[..count] {
+                for (c, x) in centroid.iter_mut().zip(v) {
+                    *c += x / count as f32;
+                }
+            }
+
+            for v in vertices.to_mut().iter_mut() {
+                for (x, c) in v.iter_mut().zip(&centroid) {
+                    *x = c + (*x - c) * scale;
+                }
+            }
+        }
+
+        let mut positions = Vec::with_capacity(self.triangles.len());
+        let mut colors = Vec::with_capacity(self.triangles.len());
+
+        for (triangle, &face) in self.triangles.chunks_exact(3).zip(self.faces.iter()) {
+            if let Some(color) = face_colors[face] {
+                for &idx in triangle {
+                    positions.push(vertices[idx as usize]);
+                    colors.push(vertex_color(color));
+                }
+            }
+        }
+
+        // Builds the actual mesh.
+        let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+        mesh.set_attribute(Mesh::ATTRIBUTE_UV_0, vec![[0.0, 1.0]; positions.len()]);
+        mesh.set_attribute(Mesh::ATTRIBUTE_NORMAL, normals(&positions));
+        mesh.set_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+        mesh.set_indices(Some(Indices::U32((0..positions.len() as u32).collect())));
+        mesh.set_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+
+        mesh
+    }
+}
+
+/// Builds the wireframe of a polytope, given the color of each of its edges,
+/// or `None` for the edges that are hidden.
+///
+/// Every edge gets its own vertices, so that it can be colored independently
+/// of its neighbors.
+pub fn wireframe(
+    poly: &Concrete,
+    projection_type: Projection,
+    edge_colors: &[Option<Color>],
+) -> MeshResult<Mesh> {
+    // If there's no vertices, returns an empty mesh.
+    if poly.vertex_count() == 0 {
+        return Ok(empty_mesh());
+    }
+
+    let (vertices, indices) = edges(poly, projection_type)?;
+    let mut positions = Vec::with_capacity(indices.len());
+    let mut colors = Vec::with_capacity(indices.len());
+
+    for (edge, &color) in indices.chunks_exact(2).zip(edge_colors.iter()) {
+        if let Some(color) = color {
+            for &idx in edge {
+                positions.push(vertices[idx as usize]);
+                colors.push(vertex_color(color));
+            }
         }
     }
 
     // Sets the mesh attributes.
     let mut mesh = Mesh::new(PrimitiveTopology::LineList);
-    mesh.set_attribute(Mesh::ATTRIBUTE_NORMAL, normals(&vertices));
-    mesh.set_attribute(Mesh::ATTRIBUTE_POSITION, vertices);
-    mesh.set_attribute(Mesh::ATTRIBUTE_UV_0, vec![[0.0; 2]; vertex_count]);
-    mesh.set_indices(Some(Indices::U16(indices)));
+    mesh.set_attribute(Mesh::ATTRIBUTE_NORMAL, normals(&positions));
+    mesh.set_attribute(Mesh::ATTRIBUTE_UV_0, vec![[0.0; 2]; positions.len()]);
+    mesh.set_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+    mesh.set_indices(Some(Indices::U32((0..positions.len() as u32).collect())));
+    mesh.set_attribute(Mesh::ATTRIBUTE_POSITION, positions);
 
-    mesh
+    Ok(mesh)
 }
@@ -45,6 +45,7 @@ struct PointLight {
 layout(location = 0) in vec3 v_WorldPosition;
 layout(location = 1) in vec3 v_WorldNormal;
 layout(location = 2) in vec2 v_Uv;
+layout(location = 4) in vec4 v_Color;
 
 #ifdef STANDARDMATERIAL_NORMAL_MAP
 layout(location = 3) in vec4 v_WorldTangent;
@@ -278,7 +279,8 @@ vec3 reinhard_extended_luminance(vec3 color, float max_white_l) {
 #endif
 
 void main() {
-    vec4 output_color = base_color;
+    // The color of each element is stored in its vertices.
+    vec4 output_color = base_color * v_Color;
 #ifdef STANDARDMATERIAL_BASE_COLOR_TEXTURE
     output_color *= texture(sampler2D(StandardMaterial_base_color_texture,
                                       StandardMaterial_base_color_texture_sampler),
@@ -0,0 +1,430 @@
+//! Contains the palettes and color schemes used to color the elements of a
+//! polytope.
+//!
+//! Faces, edges and vertices are colored independently. Each of them can be
+//! drawn in a single color, or colored with a palette according to their
+//! class, their depth, or their distance to the origin.
+
+use bevy::render::color::Color;
+use miratope_core::{
+    abs::{elements::ElementRef, rank::Rank},
+    conc::{Concrete, ConcretePolytope},
+    geometry::Point,
+    Consts, Float, Polytope,
+};
+use serde::{Deserialize, Serialize};
+use vec_like::VecLike;
+
+/// The key under which a color scheme is stored in the metadata of a
+/// polytope.
+pub const METADATA_KEY: &str = "color_scheme";
+
+/// The built-in palettes, as their names and the hex codes of their colors.
+/// Most of these come from [ColorBrewer](https://colorbrewer2.org).
+const BUILTIN: &[(&str, &[u32])] = &[
+    (
+        "Set1",
+        &[
+            0xe41a1c, 0x377eb8, 0x4daf4a, 0x984ea3, 0xff7f00, 0xffff33, 0xa65628, 0xf781bf,
+            0x999999,
+        ],
+    ),
+    (
+        "Set2",
+        &[
+            0x66c2a5, 0xfc8d62, 0x8da0cb, 0xe78ac3, 0xa6d854, 0xffd92f, 0xe5c494, 0xb3b3b3,
+        ],
+    ),
+    (
+        "Dark2",
+        &[
+            0x1b9e77, 0xd95f02, 0x7570b3, 0xe7298a, 0x66a61e, 0xe6ab02, 0xa6761d, 0x666666,
+        ],
+    ),
+    (
+        "Paired",
+        &[
+            0xa6cee3, 0x1f78b4, 0xb2df8a, 0x33a02c, 0xfb9a99, 0xe31a1c, 0xfdbf6f, 0xff7f00,
+            0xcab2d6, 0x6a3d9a, 0xffff99, 0xb15928,
+        ],
+    ),
+    (
+        "Pastel1",
+        &[
+            0xfbb4ae, 0xb3cde3, 0xccebc5, 0xdecbe4, 0xfed9a6, 0xffffcc, 0xe5d8bd, 0xfddaec,
+            0xf2f2f2,
+        ],
+    ),
+    (
+        "Blues",
+        &[
+            0xf7fbff, 0xdeebf7, 0xc6dbef, 0x9ecae1, 0x6baed6, 0x4292c6, 0x2171b5, 0x08519c,
+            0x08306b,
+        ],
+    ),
+    (
+        "YlOrRd",
+        &[
+            0xffffcc, 0xffeda0, 0xfed976, 0xfeb24c, 0xfd8d3c, 0xfc4e2a, 0xe31a1c, 0xbd0026,
+            0x800026,
+        ],
+    ),
+    (
+        "Spectral",
+        &[
+            0x9e0142, 0xd53e4f, 0xf46d43, 0xfdae61, 0xfee08b, 0xffffbf, 0xe6f598, 0xabdda4,
+            0x66c2a5, 0x3288bd, 0x5e4fa2,
+        ],
+    ),
+    (
+        "Viridis",
+        &[
+            0x440154, 0x472d7b, 0x3b528b, 0x2c728e, 0x21918c, 0x28ae80, 0x5ec962, 0xaddc30,
+            0xfde725,
+        ],
+    ),
+];
+
+/// Converts sRGB components into a Bevy color.
+pub fn to_color([r, g, b]: [u8; 3]) -> Color {
+    Color::rgb_u8(r, g, b)
+}
+
+/// A named list of colors.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Palette {
+    /// The name of the palette.
+    pub name: String,
+
+    /// The colors of the palette, as sRGB components.
+    pub colors: Vec<[u8; 3]>,
+}
+
+impl Palette {
+    /// Initializes a new palette with a given name and colors.
+    pub fn new(name: String, colors: Vec<[u8; 3]>) -> Self {
+        Self { name, colors }
+    }
+
+    /// Initializes a new palette from the hex codes of its colors.
+    fn from_hex(name: &str, hex: &[u32]) -> Self {
+        Self::new(
+            name.to_string(),
+            hex.iter()
+                .map(|&c| [(c >> 16) as u8, (c >> 8) as u8, c as u8])
+                .collect(),
+        )
+    }
+
+    /// Returns the built-in palettes.
+    pub fn builtin() -> Vec<Self> {
+        BUILTIN
+            .iter()
+            .map(|(name, hex)| Self::from_hex(name, hex))
+            .collect()
+    }
+
+    /// Returns the color with a given index, cycling through the palette.
+    pub fn color(&self, idx: usize) -> Color {
+        if self.colors.is_empty() {
+            Color::WHITE
+        } else {
+            to_color(self.colors[idx % self.colors.len()])
+        }
+    }
+
+    /// Returns the color at a given position of the palette, interpolating
+    /// between its colors. The position is clamped between 0 and 1.
+    pub fn sample(&self, t: f32) -> Color {
+        let len = self.colors.len();
+        if len <= 1 {
+            return self.color(0);
+        }
+
+        let pos = t.clamp(0.0, 1.0) * (len - 1) as f32;
+        let idx = (pos.floor() as usize).min(len - 2);
+        let t = pos - idx as f32;
+
+        let (a, b) = (self.colors[idx], self.colors[idx + 1]);
+        let lerp = |i: usize| (a[i] as f32 * (1.0 - t) + b[i] as f32 * t).round() as u8;
+        to_color([lerp(0), lerp(1), lerp(2)])
+    }
+}
+
+/// The ways in which the elements of a given rank can be colored.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ColorMode {
+    /// Every element gets the same color.
+    Uniform,
+
+    /// Elements get a color from a palette according to their class: vertices
+    /// by their degree, edges by their length, and any other elements by their
+    /// number of subelements.
+    ByClass,
+
+    /// Elements get a color from a palette according to the depth of their
+    /// centroid, i.e. their fourth coordinate, or their last one in three
+    /// dimensions or less.
+    ByDepth,
+
+    /// Elements get a color from a palette according to the distance of their
+    /// centroid to the origin.
+    ByDistance,
+}
+
+impl ColorMode {
+    /// All of the color modes.
+    pub const ALL: [Self; 4] = [
+        Self::Uniform,
+        Self::ByClass,
+        Self::ByDepth,
+        Self::ByDistance,
+    ];
+
+    /// Returns the name of the color mode.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Uniform => "Uniform",
+            Self::ByClass => "By class",
+            Self::ByDepth => "By depth",
+            Self::ByDistance => "By distance",
+        }
+    }
+}
+
+/// The way in which the elements of a given rank are colored.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ElementColoring {
+    /// The color mode.
+    pub mode: ColorMode,
+
+    /// The color used in uniform mode, as sRGB components.
+    pub color: [u8; 3],
+
+    /// The name of the palette used in every other mode.
+    pub palette: String,
+}
+
+impl ElementColoring {
+    /// Colors every element in a single color.
+    pub fn uniform(color: [u8; 3]) -> Self {
+        Self {
+            mode: ColorMode::Uniform,
+            color,
+            palette: "Set1".to_string(),
+        }
+    }
+
+    /// Returns the color of every element of a given rank of a polytope.
+    pub fn colors(&self, poly: &Concrete, rank: Rank, settings: &ColorSettings) -> Vec<Color> {
+        let count = poly.el_count(rank);
+
+        // A missing palette falls back to the uniform color.
+        let palette = settings
+            .palette(&self.palette)
+            .unwrap_or_else(|| Palette::new(String::new(), vec![self.color]));
+
+        match self.mode {
+            ColorMode::Uniform => vec![to_color(self.color); count],
+
+            ColorMode::ByClass => classes(poly, rank)
+                .into_iter()
+                .map(|class| palette.color(class))
+                .collect(),
+
+            ColorMode::ByDepth => {
+                let coord = match poly.dim_or() {
+                    0 => return vec![palette.sample(0.5); count],
+                    dim if dim >= 4 => 3,
+                    dim => dim - 1,
+                };
+
+                normalize(centroids(poly, rank).map(|c| c[coord]))
+                    .into_iter()
+                    .map(|t| palette.sample(t))
+                    .collect()
+            }
+
+            ColorMode::ByDistance => normalize(centroids(poly, rank).map(|c| c.norm()))
+                .into_iter()
+                .map(|t| palette.sample(t))
+                .collect(),
+        }
+    }
+}
+
+/// Returns the centroids of the elements of a given rank of a polytope.
+fn centroids(poly: &Concrete, rank: Rank) -> impl Iterator<Item = Point> + '_ {
+    (0..poly.el_count(rank)).map(move |idx| {
+        let vertices = poly
+            .abs
+            .element_vertices(ElementRef::new(rank, idx))
+            .unwrap_or_default();
+        let mut centroid = Point::zeros(poly.dim_or());
+
+        for &v in &vertices {
+            centroid += &poly.vertices[v];
+        }
+
+        if !vertices.is_empty() {
+            centroid /= vertices.len() as Float;
+        }
+
+        centroid
+    })
+}
+
+/// Rescales some values so that they range from 0 to 1. If they're all equal,
+/// they're all mapped to 1/2.
+fn normalize<I: Iterator<Item = Float>>(values: I) -> Vec<f32> {
+    let values: Vec<_> = values.collect();
+    let min = values.iter().copied().fold(Float::INFINITY, Float::min);
+    let max = values.iter().copied().fold(Float::NEG_INFINITY, Float::max);
+
+    values
+        .into_iter()
+        .map(|x| {
+            if max - min < Float::EPS {
+                0.5
+            } else {
+                ((x - min) / (max - min)) as f32
+            }
+        })
+        .collect()
+}
+
+/// Sorts the elements of a given rank of a polytope into classes, and returns
+/// the class of each of them. Classes are numbered in increasing order of the
+/// quantity that distinguishes them.
+fn classes(poly: &Concrete, rank: Rank) -> Vec<usize> {
+    let elements = match poly.abs.ranks.get(rank) {
+        Some(elements) => elements,
+        None => return Vec::new(),
+    };
+
+    // The quantity that determines the class of each element. Lengths are
+    // rounded so that they can be compared exactly, and malformed edges get a
+    // class of their own.
+    let keys: Vec<i64> = match rank.into_isize() {
+        0 => elements.iter().map(|el| el.sups.len() as i64).collect(),
+        1 => (0..elements.len())
+            .map(|idx| {
+                crate::mesh::edge_length(poly, idx).map_or(-1, |len| (len * 1e6).round() as i64)
+            })
+            .collect(),
+        _ => elements.iter().map(|el| el.subs.len() as i64).collect(),
+    };
+
+    let mut distinct = keys.clone();
+    distinct.sort_unstable();
+    distinct.dedup();
+
+    keys.into_iter()
+        .map(|key| distinct.binary_search(&key).unwrap())
+        .collect()
+}
+
+/// The colors with which the elements of a polytope are drawn.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ColorScheme {
+    /// The coloring of the faces.
+    pub faces: ElementColoring,
+
+    /// The coloring of the edges.
+    pub edges: ElementColoring,
+
+    /// The coloring of the vertices.
+    pub vertices: ElementColoring,
+}
+
+impl Default for ColorScheme {
+    fn default() -> Self {
+        Self {
+            faces: ElementColoring::uniform([255, 255, 255]),
+            edges: ElementColoring::uniform([0, 0, 0]),
+            vertices: ElementColoring::uniform([0, 0, 0]),
+        }
+    }
+}
+
+impl ColorScheme {
+    /// Returns the color of every face of a polytope.
+    pub fn face_colors(&self, poly: &Concrete, settings: &ColorSettings) -> Vec<Color> {
+        self.faces.colors(poly, Rank::new(2), settings)
+    }
+
+    /// Returns the color of every edge of a polytope.
+    pub fn edge_colors(&self, poly: &Concrete, settings: &ColorSettings) -> Vec<Color> {
+        self.edges.colors(poly, Rank::new(1), settings)
+    }
+
+    /// Returns the color of every vertex of a polytope.
+    pub fn vertex_colors(&self, poly: &Concrete, settings: &ColorSettings) -> Vec<Color> {
+        self.vertices.colors(poly, Rank::new(0), settings)
+    }
+
+    /// Stores the color scheme in the metadata of a polytope, so that it gets
+    /// saved together with it. The default color scheme isn't stored.
+    pub fn write_metadata(&self, poly: &mut Concrete) {
+        if self != &Self::default() {
+            if let Ok(ron) = ron::to_string(self) {
+                poly.metadata_mut()
+                    .other
+                    .insert(METADATA_KEY.to_string(), ron);
+            }
+        }
+    }
+
+    /// Removes a color scheme from the metadata of a polytope, and returns it,
+    /// if there was any.
+    pub fn take_metadata(poly: &mut Concrete) -> Option<Self> {
+        let mut metadata = poly.take_metadata()?;
+        let ron = metadata.other.remove(METADATA_KEY);
+        poly.set_metadata(metadata);
+
+        ron::from_str(&ron?).ok()
+    }
+}
+
+/// The palettes defined by the user, together with the color scheme given to
+/// new polytopes.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct ColorSettings {
+    /// The palettes defined by the user. These take precedence over built-in
+    /// palettes with the same name.
+    pub palettes: Vec<Palette>,
+
+    /// The color scheme given to new polytopes.
+    pub default_scheme: ColorScheme,
+}
+
+impl ColorSettings {
+    /// Returns the palette with a given name, if it exists.
+    pub fn palette(&self, name: &str) -> Option<Palette> {
+        self.palettes
+            .iter()
+            .find(|palette| palette.name == name)
+            .cloned()
+            .or_else(|| {
+                Palette::builtin()
+                    .into_iter()
+                    .find(|palette| palette.name == name)
+            })
+    }
+
+    /// Returns the names of all palettes, built-in or not.
+    pub fn palette_names(&self) -> Vec<String> {
+        let mut names: Vec<_> = Palette::builtin()
+            .into_iter()
+            .map(|palette| palette.name)
+            .collect();
+
+        for palette in &self.palettes {
+            if !names.contains(&palette.name) {
+                names.push(palette.name.clone());
+            }
+        }
+
+        names
+    }
+}
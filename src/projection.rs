@@ -0,0 +1,163 @@
+//! Contains the code that projects polytopes of any dimension down into 3D.
+//!
+//! Polytopes are first written in some orthonormal basis, either the
+//! coordinate axes or the principal axes of their vertices. Then, every
+//! coordinate beyond the third is removed one at a time, starting from the
+//! last. Orthogonal projections simply drop the coordinate, while perspective
+//! projections scale the rest of the coordinates as seen from an eye placed on
+//! the axis of the coordinate.
+
+use crate::ui::camera::ProjectionType;
+
+use miratope_core::{
+    conc::{Concrete, ConcretePolytope},
+    geometry::{Matrix, Point},
+    Consts, Float,
+};
+
+/// The basis a polytope is written in before being projected.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ProjectionBasis {
+    /// The coordinate axes, so that the first three coordinates are shown.
+    Coordinates,
+
+    /// The principal axes of the vertices, so that the three directions in
+    /// which the polytope is the most spread out are shown.
+    Principal,
+}
+
+/// How polytopes of more than three dimensions get projected into 3D.
+/// Polytopes of at most three dimensions are always shown as they are.
+#[derive(Clone, Copy, PartialEq)]
+pub struct Projection {
+    /// Whether the extra dimensions are projected orthogonally or in
+    /// perspective.
+    pub kind: ProjectionType,
+
+    /// The basis the polytope is written in before being projected.
+    pub basis: ProjectionBasis,
+
+    /// The distance from the eye to the center of the polytope in each
+    /// perspective projection, as a multiple of the radius of the polytope.
+    /// This must be larger than 1, so that the eye lies outside of it.
+    pub distance: Float,
+}
+
+impl Default for Projection {
+    fn default() -> Self {
+        Self {
+            kind: ProjectionType::Perspective,
+            basis: ProjectionBasis::Coordinates,
+            distance: 2.0,
+        }
+    }
+}
+
+impl Projection {
+    /// The smallest distance from the eye to the polytope, as a multiple of its
+    /// radius.
+    pub const MIN_DISTANCE: Float = 1.1;
+
+    /// Switches between orthogonal and perspective projection.
+    pub fn flip(&mut self) {
+        self.kind.flip();
+    }
+
+    /// Returns whether the projection is orthogonal.
+    pub fn is_orthogonal(&self) -> bool {
+        self.kind.is_orthogonal()
+    }
+}
+
+/// Returns the largest norm of the first `len` coordinates of some points.
+fn radius(points: &[Point], len: usize) -> Float {
+    points
+        .iter()
+        .map(|p| p.rows(0, len).norm())
+        .fold(0.0, Float::max)
+}
+
+/// Projects the points of a given polytope down into 3D. The perspective
+/// projections depend on the size of the polytope, so the same reduction must
+/// be used for all of the points drawn for it.
+pub struct Reduction {
+    /// The matrix that writes a point in the projection basis, or `None` if
+    /// the coordinate axes are used.
+    basis: Option<Matrix>,
+
+    /// The distance from the eye in each perspective projection, starting from
+    /// the last coordinate. This is empty for orthogonal projections.
+    distances: Vec<Float>,
+}
+
+impl Reduction {
+    /// Builds the reduction of a polytope.
+    pub fn new(poly: &Concrete, projection: Projection) -> Self {
+        let dim = poly.dim_or();
+        if dim <= 3 {
+            return Self {
+                basis: None,
+                distances: Vec::new(),
+            };
+        }
+
+        let basis = match projection.basis {
+            ProjectionBasis::Coordinates => None,
+            ProjectionBasis::Principal => poly.principal_axes().map(|axes| axes.transpose()),
+        };
+
+        let mut distances = Vec::new();
+        if !projection.is_orthogonal() {
+            let distance = projection.distance.max(Self::MIN_DISTANCE);
+
+            // Each eye is placed according to the size of what's left of the
+            // polytope after the previous projections.
+            let mut points: Vec<_> = poly
+                .vertices
+                .iter()
+                .map(|v| match &basis {
+                    Some(m) => m * v,
+                    None => v.clone(),
+                })
+                .collect();
+
+            for k in (3..dim).rev() {
+                let eye = (distance * radius(&points, k + 1)).max(Float::EPS);
+                for p in points.iter_mut() {
+                    let factor = eye / (eye - p[k]);
+                    for c in p.iter_mut().take(k) {
+                        *c *= factor;
+                    }
+                }
+
+                distances.push(eye);
+            }
+        }
+
+        Self { basis, distances }
+    }
+
+    /// Projects a point into 3D.
+    pub fn apply(&self, p: &Point) -> [f32; 3] {
+        let mut q = match &self.basis {
+            Some(m) => m * p,
+            None => p.clone(),
+        };
+
+        // Each perspective projection removes the last coordinate left.
+        let dim = q.len();
+        for (k, &eye) in (0..dim).rev().zip(&self.distances) {
+            let factor = eye / (eye - q[k]);
+            for c in q.iter_mut().take(k) {
+                *c *= factor;
+            }
+        }
+
+        let mut iter = q.iter().map(|&c| c as f32);
+        [
+            iter.next().unwrap_or(0.0),
+            iter.next().unwrap_or(0.0),
+            iter.next().unwrap_or(0.0),
+        ]
+    }
+}
@@ -0,0 +1,462 @@
+//! A small software renderer, used to render the scene offscreen at any
+//! resolution, independently of the window size.
+//!
+//! Faces are drawn with flat shading, lit from the camera, and edges and
+//! vertices are drawn as thick lines and dots on top of them. In hidden-line
+//! mode, faces are drawn in the background color instead, so that they only
+//! hide the edges behind them. Every image is rendered at a multiple of the
+//! requested resolution, and then downsampled to get rid of aliasing.
+
+use std::path::Path;
+
+use bevy::{
+    math::{Mat4, Vec3, Vec4},
+    render::color::Color,
+};
+
+/// The depth bias that makes edges get drawn on top of the faces they bound.
+const EDGE_BIAS: f32 = 1e-4;
+
+/// The most pixels that an image can have after supersampling. Every pixel of
+/// the canvas takes 20 bytes, so this caps its size at about 1.3 GB.
+pub const MAX_PIXELS: u64 = 1 << 26;
+
+/// Represents an error while rendering an image.
+#[derive(Debug)]
+pub enum RenderError {
+    /// The image would have more than [`MAX_PIXELS`] pixels after
+    /// supersampling.
+    TooLarge {
+        /// The width of the image after supersampling.
+        width: u64,
+
+        /// The height of the image after supersampling.
+        height: u64,
+    },
+
+    /// The image couldn't be saved.
+    Image(image::ImageError),
+}
+
+impl std::fmt::Display for RenderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TooLarge { width, height } => write!(
+                f,
+                "the image would be {}×{} pixels after supersampling, more than the {} allowed",
+                width, height, MAX_PIXELS
+            ),
+            Self::Image(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for RenderError {}
+
+/// [`image::ImageError`] is a type of [`RenderError`].
+impl From<image::ImageError> for RenderError {
+    fn from(err: image::ImageError) -> Self {
+        Self::Image(err)
+    }
+}
+
+/// The result of rendering an image.
+pub type RenderResult<T> = Result<T, RenderError>;
+
+/// The options with which an image is rendered.
+#[derive(Clone, Copy)]
+pub struct RenderOptions {
+    /// The width of the image in pixels.
+    pub width: u32,
+
+    /// The height of the image in pixels.
+    pub height: u32,
+
+    /// The image is rendered at this many times its resolution in each
+    /// direction, and then downsampled.
+    pub supersampling: u32,
+
+    /// The width of the edges in pixels, before supersampling.
+    pub edge_width: f32,
+
+    /// The diameter of the vertices in pixels, before supersampling. Vertices
+    /// aren't drawn if this is zero.
+    pub vertex_size: f32,
+
+    /// The background color. Ignored if the background is transparent.
+    pub background: Color,
+
+    /// Whether the background is transparent.
+    pub transparent: bool,
+
+    /// Whether faces are drawn in the background color, so that only the
+    /// edges in front of them are seen.
+    pub hidden_lines: bool,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            width: 1920,
+            height: 1080,
+            supersampling: 2,
+            edge_width: 1.5,
+            vertex_size: 0.0,
+            background: Color::WHITE,
+            transparent: true,
+            hidden_lines: false,
+        }
+    }
+}
+
+impl RenderOptions {
+    /// Returns the width and height of the image after supersampling.
+    pub fn canvas_size(&self) -> (u64, u64) {
+        let factor = self.supersampling.max(1) as u64;
+        (
+            self.width.max(1) as u64 * factor,
+            self.height.max(1) as u64 * factor,
+        )
+    }
+
+    /// Checks that the image isn't too large to be rendered.
+    pub fn check_size(&self) -> RenderResult<()> {
+        let (width, height) = self.canvas_size();
+        if width * height > MAX_PIXELS {
+            Err(RenderError::TooLarge { width, height })
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Something to be drawn into an image.
+pub struct RenderObject {
+    /// The transform from the coordinates of the object into world space.
+    pub model: Mat4,
+
+    /// The vertices of the object.
+    pub vertices: Vec<[f32; 3]>,
+
+    /// The indices of the vertices of each triangle, in groups of three.
+    pub triangles: Vec<u32>,
+
+    /// The indices of the vertices of each edge, in groups of two.
+    pub edges: Vec<u32>,
+
+    /// The color of each triangle. Triangles without a color are hidden.
+    pub triangle_colors: Vec<Option<Color>>,
+
+    /// The color of each edge. Edges without a color are hidden.
+    pub edge_colors: Vec<Option<Color>>,
+
+    /// The color of each vertex. Vertices without a color are hidden.
+    pub vertex_colors: Vec<Option<Color>>,
+}
+
+/// A vertex after being projected onto the screen.
+#[derive(Clone, Copy)]
+struct ScreenVertex {
+    /// The horizontal coordinate in pixels.
+    x: f32,
+
+    /// The vertical coordinate in pixels.
+    y: f32,
+
+    /// The depth of the vertex. Smaller values are closer to the camera.
+    z: f32,
+}
+
+/// An image together with its depth buffer. Colors are stored with
+/// premultiplied alpha.
+struct Canvas {
+    /// The width of the canvas in pixels.
+    width: usize,
+
+    /// The height of the canvas in pixels.
+    height: usize,
+
+    /// The color of every pixel, row by row.
+    color: Vec<[f32; 4]>,
+
+    /// The depth of every pixel, row by row.
+    depth: Vec<f32>,
+}
+
+impl Canvas {
+    /// Initializes a new canvas filled with a given color.
+    fn new(width: usize, height: usize, background: [f32; 4]) -> Self {
+        Self {
+            width,
+            height,
+            color: vec![background; width * height],
+            depth: vec![f32::INFINITY; width * height],
+        }
+    }
+
+    /// Sets a pixel to a color if it passes the depth test.
+    fn set(&mut self, x: usize, y: usize, z: f32, color: [f32; 4]) {
+        let idx = y * self.width + x;
+        if z < self.depth[idx] {
+            self.depth[idx] = z;
+            self.color[idx] = color;
+        }
+    }
+
+    /// Returns the range of pixels that intersect an interval, clamped to the
+    /// given size.
+    fn pixel_range(min: f32, max: f32, size: usize) -> std::ops::Range<usize> {
+        let min = min.floor().max(0.0) as usize;
+        let max = (max.ceil().max(0.0) as usize).min(size);
+        min..max.max(min)
+    }
+
+    /// Draws a triangle with a given color.
+    fn triangle(&mut self, a: ScreenVertex, b: ScreenVertex, c: ScreenVertex, color: [f32; 4]) {
+        let area = (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x);
+        if area.abs() < f32::EPSILON {
+            return;
+        }
+
+        let xs = Self::pixel_range(a.x.min(b.x).min(c.x), a.x.max(b.x).max(c.x), self.width);
+        let ys = Self::pixel_range(a.y.min(b.y).min(c.y), a.y.max(b.y).max(c.y), self.height);
+
+        for y in ys {
+            let py = y as f32 + 0.5;
+
+            for x in xs.clone() {
+                let px = x as f32 + 0.5;
+
+                // The barycentric coordinates of the pixel center.
+                let wa = ((b.x - px) * (c.y - py) - (b.y - py) * (c.x - px)) / area;
+                let wb = ((c.x - px) * (a.y - py) - (c.y - py) * (a.x - px)) / area;
+                let wc = 1.0 - wa - wb;
+
+                if wa >= 0.0 && wb >= 0.0 && wc >= 0.0 {
+                    self.set(x, y, wa * a.z + wb * b.z + wc * c.z, color);
+                }
+            }
+        }
+    }
+
+    /// Draws a segment of a given width and color.
+    fn segment(&mut self, a: ScreenVertex, b: ScreenVertex, width: f32, color: [f32; 4]) {
+        let half_width = width / 2.0;
+        let (dx, dy) = (b.x - a.x, b.y - a.y);
+        let len_sq = dx * dx + dy * dy;
+
+        let xs = Self::pixel_range(
+            a.x.min(b.x) - half_width,
+            a.x.max(b.x) + half_width,
+            self.width,
+        );
+        let ys = Self::pixel_range(
+            a.y.min(b.y) - half_width,
+            a.y.max(b.y) + half_width,
+            self.height,
+        );
+
+        for y in ys {
+            let py = y as f32 + 0.5;
+
+            for x in xs.clone() {
+                let px = x as f32 + 0.5;
+
+                // The closest point on the segment to the pixel center.
+                let t = if len_sq < f32::EPSILON {
+                    0.0
+                } else {
+                    (((px - a.x) * dx + (py - a.y) * dy) / len_sq).clamp(0.0, 1.0)
+                };
+
+                let (ex, ey) = (a.x + t * dx - px, a.y + t * dy - py);
+                if ex * ex + ey * ey <= half_width * half_width {
+                    let z = a.z + t * (b.z - a.z) - EDGE_BIAS;
+                    self.set(x, y, z, color);
+                }
+            }
+        }
+    }
+
+    /// Downsamples the canvas by a given factor, and returns its pixels as
+    /// RGBA bytes with straight alpha.
+    fn into_rgba8(self, factor: usize) -> Vec<u8> {
+        let (width, height) = (self.width / factor, self.height / factor);
+        let mut bytes = Vec::with_capacity(width * height * 4);
+        let samples = (factor * factor) as f32;
+
+        for y in 0..height {
+            for x in 0..width {
+                let mut sum = [0.0; 4];
+
+                for sy in 0..factor {
+                    for sx in 0..factor {
+                        let color = self.color[(y * factor + sy) * self.width + x * factor + sx];
+                        for (s, c) in sum.iter_mut().zip(color.iter()) {
+                            *s += c;
+                        }
+                    }
+                }
+
+                let alpha = sum[3] / samples;
+                for &c in &sum[0..3] {
+                    let c = if alpha > 0.0 {
+                        c / samples / alpha
+                    } else {
+                        0.0
+                    };
+                    bytes.push((c.clamp(0.0, 1.0) * 255.0).round() as u8);
+                }
+                bytes.push((alpha.clamp(0.0, 1.0) * 255.0).round() as u8);
+            }
+        }
+
+        bytes
+    }
+}
+
+/// Converts a color into premultiplied RGBA components, scaling its
+/// brightness by a given factor.
+fn premultiply(color: Color, brightness: f32) -> [f32; 4] {
+    let a = color.a();
+    [
+        color.r() * brightness * a,
+        color.g() * brightness * a,
+        color.b() * brightness * a,
+        a,
+    ]
+}
+
+/// Renders a set of objects as seen by a camera, and returns the pixels of the
+/// image as RGBA bytes.
+///
+/// The camera is given by its transform in world space, together with its
+/// vertical field of view in radians and its clipping planes. Returns an error
+/// without allocating anything if the image is [too large](MAX_PIXELS).
+pub fn render(
+    objects: &[RenderObject],
+    camera: Mat4,
+    fov: f32,
+    near: f32,
+    far: f32,
+    options: &RenderOptions,
+) -> RenderResult<Vec<u8>> {
+    options.check_size()?;
+
+    let factor = options.supersampling.max(1) as usize;
+    let width = options.width.max(1) as usize * factor;
+    let height = options.height.max(1) as usize * factor;
+
+    let background = if options.transparent {
+        [0.0; 4]
+    } else {
+        premultiply(options.background, 1.0)
+    };
+    let mut canvas = Canvas::new(width, height, background);
+
+    let view = camera.inverse();
+    let projection = Mat4::perspective_rh(fov, width as f32 / height as f32, near, far);
+
+    for object in objects {
+        let model_view = view * object.model;
+
+        // The vertices in view space, and projected onto the screen. Vertices
+        // behind the camera can't be projected.
+        let view_vertices: Vec<_> = object
+            .vertices
+            .iter()
+            .map(|&v| model_view * Vec3::from(v).extend(1.0))
+            .collect();
+        let screen_vertices: Vec<_> = view_vertices
+            .iter()
+            .map(|&v| {
+                let clip: Vec4 = projection * v;
+                (clip.w > near).then(|| ScreenVertex {
+                    x: (clip.x / clip.w + 1.0) / 2.0 * width as f32,
+                    y: (1.0 - clip.y / clip.w) / 2.0 * height as f32,
+                    z: clip.z / clip.w,
+                })
+            })
+            .collect();
+
+        // Draws the faces.
+        for (tri, &face_color) in object
+            .triangles
+            .chunks_exact(3)
+            .zip(&object.triangle_colors)
+        {
+            let face_color = match face_color {
+                Some(face_color) => face_color,
+                None => continue,
+            };
+            let (i, j, k) = (tri[0] as usize, tri[1] as usize, tri[2] as usize);
+
+            if let (Some(a), Some(b), Some(c)) =
+                (screen_vertices[i], screen_vertices[j], screen_vertices[k])
+            {
+                // Faces are lit from the camera.
+                let (va, vb, vc) = (
+                    view_vertices[i].truncate(),
+                    view_vertices[j].truncate(),
+                    view_vertices[k].truncate(),
+                );
+                let normal = (vb - va).cross(vc - va);
+                let brightness = 0.25 + 0.75 * normal.z.abs() / normal.length().max(f32::EPSILON);
+
+                let color = if options.hidden_lines {
+                    background
+                } else {
+                    premultiply(face_color, brightness)
+                };
+                canvas.triangle(a, b, c, color);
+            }
+        }
+
+        // Draws the edges.
+        let edge_width = options.edge_width * factor as f32;
+        for (edge, &edge_color) in object.edges.chunks_exact(2).zip(&object.edge_colors) {
+            if let (Some(a), Some(b), Some(edge_color)) = (
+                screen_vertices[edge[0] as usize],
+                screen_vertices[edge[1] as usize],
+                edge_color,
+            ) {
+                canvas.segment(a, b, edge_width, premultiply(edge_color, 1.0));
+            }
+        }
+
+        // Draws the vertices, as segments of length zero.
+        let vertex_size = options.vertex_size * factor as f32;
+        if vertex_size > 0.0 {
+            for (&v, &vertex_color) in screen_vertices.iter().zip(&object.vertex_colors) {
+                if let (Some(v), Some(vertex_color)) = (v, vertex_color) {
+                    canvas.segment(v, v, vertex_size, premultiply(vertex_color, 1.0));
+                }
+            }
+        }
+    }
+
+    Ok(canvas.into_rgba8(factor))
+}
+
+/// Renders a set of objects as seen by a camera, and saves the image as a PNG
+/// file.
+pub fn render_to_path<P: AsRef<Path>>(
+    path: P,
+    objects: &[RenderObject],
+    camera: Mat4,
+    fov: f32,
+    near: f32,
+    far: f32,
+    options: &RenderOptions,
+) -> RenderResult<()> {
+    let bytes = render(objects, camera, fov, near, far, options)?;
+
+    image::save_buffer(
+        path,
+        &bytes,
+        options.width.max(1),
+        options.height.max(1),
+        image::ColorType::Rgba8,
+    )?;
+    Ok(())
+}
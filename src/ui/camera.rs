@@ -14,7 +14,6 @@ pub struct InputPlugin;
 impl Plugin for InputPlugin {
     fn build(&self, app: &mut AppBuilder) {
         app.add_event::<CameraInputEvent>()
-            .insert_resource(ProjectionType::Perspective)
             // We register inputs after the library has been shown, so that we
             // know whether mouse input should register.
             .add_system(add_cam_input_events.system().after("show_library"))
@@ -22,7 +21,8 @@ impl Plugin for InputPlugin {
     }
 }
 
-#[derive(Clone, Copy)]
+/// How the dimensions beyond the third get projected away.
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub enum ProjectionType {
     /// We're projecting orthogonally.
     Orthogonal,
@@ -72,6 +72,16 @@ pub enum CameraInputEvent {
 
     /// Resets the camera to its default state.
     Reset,
+
+    /// Moves the anchor, and optionally the camera, to a given viewpoint.
+    SetView {
+        /// The new transform of the anchor.
+        anchor: Transform,
+
+        /// The new transform of the camera relative to the anchor, or `None`
+        /// if it stays the same.
+        camera: Option<Transform>,
+    },
 }
 
 impl std::ops::Mul<f32> for CameraInputEvent {
@@ -138,6 +148,12 @@ impl CameraInputEvent {
             Self::Roll(roll) => Self::roll(roll, anchor_tf),
             Self::Zoom(zoom) => Self::zoom(zoom, cam_tf),
             Self::Reset => Self::reset(anchor_tf, cam_tf),
+            Self::SetView { anchor, camera } => {
+                *anchor_tf = anchor;
+                if let Some(camera) = camera {
+                    *cam_tf = camera;
+                }
+            }
         }
     }
 }
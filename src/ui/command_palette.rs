@@ -0,0 +1,438 @@
+//! Contains the command palette, a searchable list of everything that can be
+//! done from the top panel, opened with Ctrl+P.
+//!
+//! Commands run through the same code as the buttons of the top panel, so that
+//! both always behave the same.
+
+use super::{
+    main_window::WireframeMode,
+    operations::{
+        AntiprismWindow, DualWindow, DuocombWindow, DuoprismWindow, DuopyramidWindow,
+        DuotegumWindow, Operation, PrismWindow, PyramidWindow, TegumWindow, Window,
+    },
+    scene::Active,
+    top_panel::{
+        advanced, EguiWindows, FileDialogState, SectionDirection, SectionState, ToolWindows,
+    },
+};
+use crate::projection::Projection;
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+use miratope_core::limits::SizeLimit;
+use miratope_lang::{poly::conc::NamedConcrete, SelectedLanguage};
+
+/// The maximum height of the list of commands, in points.
+const LIST_HEIGHT: f32 = 300.0;
+
+/// The plugin in charge of the command palette.
+pub struct CommandPalettePlugin;
+
+impl Plugin for CommandPalettePlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.insert_resource(CommandPalette::default())
+            // Windows must be the first thing shown.
+            .add_system(show_command_palette.system().before("show_top_panel"));
+    }
+}
+
+/// Something that can be run from the command palette.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    /// Opens a file.
+    Open,
+
+    /// Builds a polytope from pasted text.
+    Paste,
+
+    /// Saves the active polytope into a file.
+    Save,
+
+    /// Opens the window to render the current view into an image.
+    RenderImage,
+
+    /// Applies an operation to the active polytope.
+    Run(Operation),
+
+    /// Opens the window to make duals.
+    DualWindow,
+
+    /// Opens the window to make pyramids.
+    PyramidWindow,
+
+    /// Opens the window to make prisms.
+    PrismWindow,
+
+    /// Opens the window to make tegums.
+    TegumWindow,
+
+    /// Opens the window to make antiprisms.
+    AntiprismWindow,
+
+    /// Opens the window to make duopyramids.
+    DuopyramidWindow,
+
+    /// Opens the window to make duoprisms.
+    DuoprismWindow,
+
+    /// Opens the window to make duotegums.
+    DuotegumWindow,
+
+    /// Opens the window to make duocombs.
+    DuocombWindow,
+
+    /// Opens the element browser.
+    Elements,
+
+    /// Toggles cross-section mode.
+    CrossSection,
+
+    /// Opens the list of polytopes on screen.
+    Scene,
+
+    /// Opens the window with the camera bookmarks.
+    Viewpoints,
+
+    /// Opens the window to hide elements of the active polytope.
+    Visibility,
+
+    /// Opens the window with the element labels.
+    Labels,
+
+    /// Opens the level of detail settings.
+    LevelOfDetail,
+
+    /// Opens the settings for drawing vertices as spheres and edges as tubes.
+    SpheresAndTubes,
+
+    /// Opens the window with the problems found with the polytopes on screen.
+    Diagnostics,
+
+    /// Opens the settings of how polytopes are projected into 3D.
+    Projection,
+
+    /// Switches between perspective and orthogonal projection.
+    OrthogonalProjection,
+
+    /// Switches whether lines behind faces are hidden.
+    HiddenLines,
+}
+
+/// Returns every command, together with the name under which it's listed.
+fn commands() -> Vec<(String, Command)> {
+    let mut commands = vec![
+        ("File: Open".to_string(), Command::Open),
+        ("File: Paste".to_string(), Command::Paste),
+        ("File: Save".to_string(), Command::Save),
+        ("File: Render image…".to_string(), Command::RenderImage),
+    ];
+
+    for &op in Operation::ALL.iter() {
+        commands.push((format!("Polytope: {}", op.name()), Command::Run(op)));
+    }
+
+    for (name, command) in [
+        (DualWindow::NAME, Command::DualWindow),
+        (PyramidWindow::NAME, Command::PyramidWindow),
+        (PrismWindow::NAME, Command::PrismWindow),
+        (TegumWindow::NAME, Command::TegumWindow),
+        (AntiprismWindow::NAME, Command::AntiprismWindow),
+        (DuopyramidWindow::NAME, Command::DuopyramidWindow),
+        (DuoprismWindow::NAME, Command::DuoprismWindow),
+        (DuotegumWindow::NAME, Command::DuotegumWindow),
+        (DuocombWindow::NAME, Command::DuocombWindow),
+    ] {
+        commands.push((format!("Polytope: {}…", name), command));
+    }
+
+    commands.extend(vec![
+        ("Polytope: Cross-section".to_string(), Command::CrossSection),
+        ("Polytope: Browse elements…".to_string(), Command::Elements),
+        ("View: Scene".to_string(), Command::Scene),
+        ("View: Viewpoints".to_string(), Command::Viewpoints),
+        ("View: Visibility".to_string(), Command::Visibility),
+        ("View: Labels".to_string(), Command::Labels),
+        ("View: Level of detail".to_string(), Command::LevelOfDetail),
+        ("View: Diagnostics".to_string(), Command::Diagnostics),
+        ("View: Projection".to_string(), Command::Projection),
+        (
+            "View: Spheres and tubes".to_string(),
+            Command::SpheresAndTubes,
+        ),
+        (
+            "View: Orthogonal projection".to_string(),
+            Command::OrthogonalProjection,
+        ),
+        (
+            "View: Hide lines behind faces".to_string(),
+            Command::HiddenLines,
+        ),
+    ]);
+
+    commands
+}
+
+/// Returns how well a query matches some text, or `None` if the characters of
+/// the query don't all appear in the text in order. Consecutive characters and
+/// characters at the start of words make for higher scores.
+fn fuzzy_score(query: &str, text: &str) -> Option<u32> {
+    let mut query = query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| c.to_ascii_lowercase())
+        .peekable();
+
+    let mut score = 0;
+    let mut prev = ' ';
+    let mut prev_matched = false;
+
+    for c in text.chars() {
+        let next = match query.peek() {
+            Some(&next) => next,
+            None => break,
+        };
+
+        if c.to_ascii_lowercase() == next {
+            query.next();
+            score += 1;
+
+            if prev_matched {
+                score += 4;
+            }
+            if !prev.is_alphanumeric() {
+                score += 3;
+            }
+
+            prev_matched = true;
+        } else {
+            prev_matched = false;
+        }
+
+        prev = c;
+    }
+
+    if query.peek().is_none() {
+        Some(score)
+    } else {
+        None
+    }
+}
+
+/// The state of the command palette.
+pub struct CommandPalette {
+    /// Whether the palette is open.
+    open: bool,
+
+    /// The text being searched for.
+    query: String,
+
+    /// The position of the highlighted command in the list of matches.
+    selected: usize,
+
+    /// Every command, together with its name.
+    commands: Vec<(String, Command)>,
+}
+
+impl Default for CommandPalette {
+    fn default() -> Self {
+        Self {
+            open: false,
+            query: String::new(),
+            selected: 0,
+            commands: commands(),
+        }
+    }
+}
+
+impl CommandPalette {
+    /// Opens the palette with an empty search.
+    pub fn open(&mut self) {
+        self.open = true;
+        self.query.clear();
+        self.selected = 0;
+    }
+
+    /// Returns the indices of the commands that match the search, best matches
+    /// first.
+    fn matches(&self) -> Vec<usize> {
+        let mut matches: Vec<_> = self
+            .commands
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, (name, _))| fuzzy_score(&self.query, name).map(|score| (idx, score)))
+            .collect();
+
+        // The sort is stable, so that ties are listed in their usual order.
+        matches.sort_by_key(|&(_, score)| std::cmp::Reverse(score));
+        matches.into_iter().map(|(idx, _)| idx).collect()
+    }
+}
+
+/// The system that shows the command palette, and runs the chosen command.
+#[allow(clippy::too_many_arguments)]
+pub fn show_command_palette(
+    egui_ctx: Res<EguiContext>,
+    keyboard: Res<Input<KeyCode>>,
+    mut palette: ResMut<CommandPalette>,
+    mut query: Query<&mut NamedConcrete, With<Active>>,
+    mut file_dialog_state: ResMut<FileDialogState>,
+    selected_language: Res<SelectedLanguage>,
+    mut section_state: ResMut<SectionState>,
+    mut section_direction: ResMut<SectionDirection>,
+    mut projection_type: ResMut<Projection>,
+    mut wireframe_mode: ResMut<WireframeMode>,
+    size_limit: Res<SizeLimit>,
+
+    // The windows that commands can open.
+    (
+        mut dual_window,
+        mut pyramid_window,
+        mut prism_window,
+        mut tegum_window,
+        mut antiprism_window,
+        mut duopyramid_window,
+        mut duoprism_window,
+        mut duotegum_window,
+        mut duocomb_window,
+    ): EguiWindows,
+    (
+        mut paste_window,
+        mut scene_window,
+        mut render_window,
+        mut visibility_window,
+        mut lod_window,
+        mut spheres_and_tubes_window,
+        mut labels_window,
+        mut viewpoints_window,
+        mut diagnostics,
+        mut projection_window,
+        mut elements_window,
+    ): ToolWindows,
+) {
+    // Ctrl+P opens or closes the palette.
+    if advanced(&keyboard) && keyboard.just_pressed(KeyCode::P) {
+        if palette.open {
+            palette.open = false;
+        } else {
+            palette.open();
+        }
+    }
+
+    if !palette.open {
+        return;
+    }
+
+    let matches = palette.matches();
+    let CommandPalette {
+        open,
+        query: search,
+        selected,
+        commands,
+    } = &mut *palette;
+
+    let mut chosen = None;
+    let mut still_open = true;
+
+    egui::Window::new("Command palette")
+        .open(&mut still_open)
+        .collapsible(false)
+        .resizable(false)
+        .show(egui_ctx.ctx(), |ui| {
+            let response = ui.text_edit_singleline(search);
+            if response.changed() {
+                *selected = 0;
+            }
+            response.request_focus();
+
+            // The highlighted command is moved with the arrow keys, and run
+            // with Enter.
+            let (down, up, enter, escape) = {
+                let input = ui.input();
+                (
+                    input.key_pressed(egui::Key::ArrowDown),
+                    input.key_pressed(egui::Key::ArrowUp),
+                    input.key_pressed(egui::Key::Enter),
+                    input.key_pressed(egui::Key::Escape),
+                )
+            };
+
+            if down && *selected + 1 < matches.len() {
+                *selected += 1;
+            }
+            if up && *selected > 0 {
+                *selected -= 1;
+            }
+            if enter {
+                chosen = matches.get(*selected).copied();
+            }
+            if escape {
+                *open = false;
+            }
+
+            egui::ScrollArea::from_max_height(LIST_HEIGHT).show(ui, |ui| {
+                for (pos, &idx) in matches.iter().enumerate() {
+                    if ui
+                        .selectable_label(pos == *selected, &commands[idx].0)
+                        .clicked()
+                    {
+                        chosen = Some(idx);
+                    }
+                }
+
+                if matches.is_empty() {
+                    ui.label("No matching commands.");
+                }
+            });
+        });
+
+    if !still_open {
+        *open = false;
+    }
+
+    let command = match chosen {
+        Some(idx) => commands[idx].1,
+        None => return,
+    };
+    *open = false;
+
+    match command {
+        Command::Open => file_dialog_state.open(),
+        Command::Paste => paste_window.open(),
+        Command::Save => {
+            if let Some(p) = query.iter_mut().next() {
+                file_dialog_state.save(selected_language.parse(&p.name));
+            }
+        }
+        Command::RenderImage => render_window.open(),
+        Command::Run(op) => {
+            if let Some(mut p) = query.iter_mut().next() {
+                op.apply(&mut p, *size_limit);
+            }
+        }
+        Command::DualWindow => dual_window.open(),
+        Command::PyramidWindow => pyramid_window.open(),
+        Command::PrismWindow => prism_window.open(),
+        Command::TegumWindow => tegum_window.open(),
+        Command::AntiprismWindow => antiprism_window.open(),
+        Command::DuopyramidWindow => duopyramid_window.open(),
+        Command::DuoprismWindow => duoprism_window.open(),
+        Command::DuotegumWindow => duotegum_window.open(),
+        Command::DuocombWindow => duocomb_window.open(),
+        Command::Elements => elements_window.open(),
+        Command::CrossSection => {
+            if let Some(mut p) = query.iter_mut().next() {
+                section_state.toggle(&mut p, &mut section_direction);
+            }
+        }
+        Command::Scene => scene_window.open(),
+        Command::Viewpoints => viewpoints_window.open(),
+        Command::Visibility => visibility_window.open(),
+        Command::Labels => labels_window.open(),
+        Command::LevelOfDetail => lod_window.open(),
+        Command::SpheresAndTubes => spheres_and_tubes_window.open(),
+        Command::Diagnostics => diagnostics.open(),
+        Command::Projection => projection_window.open(),
+        Command::OrthogonalProjection => projection_type.flip(),
+        Command::HiddenLines => wireframe_mode.flip(),
+    }
+}
@@ -0,0 +1,449 @@
+//! Contains the comparison mode, which shows two polytopes of the scene in a
+//! split view, together with a report of how they differ.
+//!
+//! Each polytope is drawn by its own camera onto its own half of the screen.
+//! Both cameras follow the main camera, so that the two halves are always
+//! seen from the same point of view, and each polytope stays centered in its
+//! half.
+
+use bevy::{
+    prelude::*,
+    render::{
+        camera::{
+            camera_system, ActiveCameras, Camera, CameraProjection, DepthCalculation,
+            PerspectiveProjection, RenderLayers, VisibleEntities,
+        },
+        pass::{
+            LoadOp, Operations, PassDescriptor, RenderPassDepthStencilAttachmentDescriptor,
+            TextureAttachment,
+        },
+        render_graph::{
+            base::{self, MainPass},
+            CameraNode, PassNode, RenderGraph, WindowSwapChainNode, WindowTextureNode,
+        },
+    },
+    transform::TransformSystem,
+};
+use bevy_egui::{egui, EguiContext};
+use miratope_core::{diff::PolytopeDiff, Polytope};
+use miratope_lang::{poly::conc::NamedConcrete, SelectedLanguage};
+
+/// The name of the camera that draws the left half of the split view.
+const LEFT_CAMERA: &str = "comparison_left";
+
+/// The name of the camera that draws the right half of the split view.
+const RIGHT_CAMERA: &str = "comparison_right";
+
+/// The name of the render graph node of the camera of the left half.
+const LEFT_CAMERA_NODE: &str = "comparison_left_camera";
+
+/// The name of the render graph node of the camera of the right half.
+const RIGHT_CAMERA_NODE: &str = "comparison_right_camera";
+
+/// The name of the render pass that draws both halves of the split view.
+const COMPARISON_PASS: &str = "comparison_pass";
+
+/// The render layer of the polytope on the left. The main camera only sees
+/// the default layer, so it doesn't draw either polytope.
+const LEFT_LAYER: u8 = 1;
+
+/// The render layer of the polytope on the right.
+const RIGHT_LAYER: u8 = 2;
+
+/// The plugin in charge of the comparison mode.
+pub struct ComparisonPlugin;
+
+impl Plugin for ComparisonPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        add_comparison_pass(app);
+
+        app.insert_resource(Comparison::default())
+            .add_system(update_comparison.system())
+            // The side panel must be shown after the top panel.
+            .add_system(show_comparison_panel.system().after("show_top_panel"))
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                camera_system::<SplitProjection>.system(),
+            )
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                sync_split_cameras
+                    .system()
+                    .after(TransformSystem::TransformPropagate),
+            );
+    }
+}
+
+/// Adds the render pass that draws the two halves of the split view on top of
+/// the main pass, and before the UI.
+fn add_comparison_pass(app: &mut AppBuilder) {
+    let msaa = app
+        .world()
+        .get_resource::<Msaa>()
+        .cloned()
+        .unwrap_or_default();
+    let world = app.world_mut();
+
+    {
+        let mut active_cameras = world.get_resource_mut::<ActiveCameras>().unwrap();
+        active_cameras.add(LEFT_CAMERA);
+        active_cameras.add(RIGHT_CAMERA);
+    }
+
+    let mut graph = world.get_resource_mut::<RenderGraph>().unwrap();
+    graph.add_system_node(LEFT_CAMERA_NODE, CameraNode::new(LEFT_CAMERA));
+    graph.add_system_node(RIGHT_CAMERA_NODE, CameraNode::new(RIGHT_CAMERA));
+
+    // The pass draws over whatever the main pass drew, but has its own depth.
+    let mut pass = PassNode::<&MainPass>::new(PassDescriptor {
+        color_attachments: vec![msaa.color_attachment_descriptor(
+            TextureAttachment::Input("color_attachment".to_string()),
+            TextureAttachment::Input("color_resolve_target".to_string()),
+            Operations {
+                load: LoadOp::Load,
+                store: true,
+            },
+        )],
+        depth_stencil_attachment: Some(RenderPassDepthStencilAttachmentDescriptor {
+            attachment: TextureAttachment::Input("depth".to_string()),
+            depth_ops: Some(Operations {
+                load: LoadOp::Clear(1.0),
+                store: true,
+            }),
+            stencil_ops: None,
+        }),
+        sample_count: msaa.samples,
+    });
+    pass.add_camera(LEFT_CAMERA);
+    pass.add_camera(RIGHT_CAMERA);
+    graph.add_node(COMPARISON_PASS, pass);
+
+    // Reuses the textures of the main pass.
+    let swap_chain_slot = if msaa.samples > 1 {
+        graph
+            .add_slot_edge(
+                base::node::MAIN_SAMPLED_COLOR_ATTACHMENT,
+                WindowTextureNode::OUT_TEXTURE,
+                COMPARISON_PASS,
+                "color_attachment",
+            )
+            .unwrap();
+
+        "color_resolve_target"
+    } else {
+        "color_attachment"
+    };
+
+    graph
+        .add_slot_edge(
+            base::node::PRIMARY_SWAP_CHAIN,
+            WindowSwapChainNode::OUT_TEXTURE,
+            COMPARISON_PASS,
+            swap_chain_slot,
+        )
+        .unwrap();
+    graph
+        .add_slot_edge(
+            base::node::MAIN_DEPTH_TEXTURE,
+            WindowTextureNode::OUT_TEXTURE,
+            COMPARISON_PASS,
+            "depth",
+        )
+        .unwrap();
+
+    for node in [LEFT_CAMERA_NODE, RIGHT_CAMERA_NODE, base::node::MAIN_PASS] {
+        graph.add_node_edge(node, COMPARISON_PASS).unwrap();
+    }
+    graph
+        .add_node_edge(COMPARISON_PASS, bevy_egui::node::EGUI_PASS)
+        .unwrap();
+}
+
+/// A perspective projection whose center is moved to the center of one half
+/// of the screen, used by the cameras of the split view.
+#[derive(Clone, Debug)]
+pub struct SplitProjection {
+    /// The projection onto the half of the screen.
+    perspective: PerspectiveProjection,
+
+    /// -1 for the left half of the screen, 1 for the right half.
+    side: f32,
+}
+
+impl CameraProjection for SplitProjection {
+    fn get_projection_matrix(&self) -> Mat4 {
+        // Each half of the screen is a unit wide in clip space.
+        Mat4::from_translation(Vec3::new(self.side / 2.0, 0.0, 0.0))
+            * self.perspective.get_projection_matrix()
+    }
+
+    fn update(&mut self, width: f32, height: f32) {
+        self.perspective.update(width / 2.0, height);
+    }
+
+    fn depth_calculation(&self) -> DepthCalculation {
+        self.perspective.depth_calculation()
+    }
+}
+
+/// Moves the cameras of the split view to wherever the main camera is.
+#[allow(clippy::type_complexity)]
+pub fn sync_split_cameras(
+    main_camera: Query<&GlobalTransform, With<PerspectiveProjection>>,
+    mut split_cameras: Query<
+        (&mut Transform, &mut GlobalTransform),
+        (With<SplitProjection>, Without<PerspectiveProjection>),
+    >,
+) {
+    if let Some(&main) = main_camera.iter().next() {
+        for (mut transform, mut global_transform) in split_cameras.iter_mut() {
+            *transform = Transform {
+                translation: main.translation,
+                rotation: main.rotation,
+                scale: main.scale,
+            };
+            *global_transform = main;
+        }
+    }
+}
+
+/// The state of the comparison mode.
+#[derive(Default)]
+pub struct Comparison {
+    /// The two polytopes being compared, if any.
+    pair: Option<(Entity, Entity)>,
+
+    /// The two polytopes that are being shown in the split view, if any.
+    applied: Option<(Entity, Entity)>,
+
+    /// The transform and visibility of every polytope before the comparison
+    /// started, so that they can be restored afterwards.
+    saved: Vec<(Entity, Transform, bool)>,
+
+    /// The cameras of the split view.
+    cameras: Vec<Entity>,
+
+    /// The report on how the polytopes differ, computed once per comparison.
+    report: Option<PolytopeDiff>,
+}
+
+impl Comparison {
+    /// Starts comparing two polytopes of the scene.
+    pub fn start(&mut self, left: Entity, right: Entity) {
+        self.pair = Some((left, right));
+        self.report = None;
+    }
+
+    /// Stops the comparison mode.
+    pub fn stop(&mut self) {
+        self.pair = None;
+        self.report = None;
+    }
+
+    /// Returns whether the comparison mode is active.
+    pub fn is_active(&self) -> bool {
+        self.pair.is_some()
+    }
+}
+
+/// Sets the visibility of a polytope and its wireframe.
+fn set_visible(
+    visible: &mut Visible,
+    children: &Children,
+    wfs_vis: &mut Query<&mut Visible, Without<NamedConcrete>>,
+    is_visible: bool,
+) {
+    visible.is_visible = is_visible;
+
+    for child in children.iter() {
+        if let Ok(mut wf_vis) = wfs_vis.get_mut(*child) {
+            wf_vis.is_visible = is_visible;
+        }
+    }
+}
+
+/// Moves a polytope and everything drawn with it onto a given render layer,
+/// or back onto the default one.
+fn set_layer(commands: &mut Commands, entity: Entity, children: &Children, layer: Option<u8>) {
+    for entity in std::iter::once(entity).chain(children.iter().copied()) {
+        match layer {
+            Some(layer) => commands.entity(entity).insert(RenderLayers::layer(layer)),
+            None => commands.entity(entity).remove::<RenderLayers>(),
+        };
+    }
+}
+
+/// Shows the polytopes being compared in the split view and hides every other
+/// polytope, or restores the scene when the comparison stops.
+#[allow(clippy::type_complexity)]
+pub fn update_comparison(
+    mut commands: Commands,
+    mut comparison: ResMut<Comparison>,
+    mut polies: Query<(Entity, &mut Transform, &mut Visible, &Children), With<NamedConcrete>>,
+    mut wfs_vis: Query<&mut Visible, Without<NamedConcrete>>,
+    main_camera: Query<&PerspectiveProjection>,
+) {
+    // Restores the scene to how it was before, if the comparison stopped or
+    // changed.
+    if comparison.applied.is_some() && comparison.applied != comparison.pair {
+        for (entity, old_transform, is_visible) in std::mem::take(&mut comparison.saved) {
+            if let Ok((_, mut transform, mut visible, children)) = polies.get_mut(entity) {
+                *transform = old_transform;
+                set_visible(&mut visible, children, &mut wfs_vis, is_visible);
+                set_layer(&mut commands, entity, children, None);
+            }
+        }
+
+        for camera in std::mem::take(&mut comparison.cameras) {
+            commands.entity(camera).despawn();
+        }
+
+        comparison.applied = None;
+    }
+
+    let (left, right) = match comparison.pair {
+        Some(pair) => pair,
+        None => return,
+    };
+
+    // The comparison stops if any polytope was removed. Otherwise, we wait for
+    // the polytopes to be spawned.
+    if polies.get_mut(left).is_err() || polies.get_mut(right).is_err() {
+        if comparison.applied.is_some() {
+            comparison.stop();
+        }
+        return;
+    }
+
+    if comparison.applied.is_some() {
+        return;
+    }
+
+    let mut saved = Vec::new();
+    for (entity, mut transform, mut visible, children) in polies.iter_mut() {
+        saved.push((entity, *transform, visible.is_visible));
+
+        if entity == left || entity == right {
+            let layer = if entity == left {
+                LEFT_LAYER
+            } else {
+                RIGHT_LAYER
+            };
+            *transform = Transform::default();
+            set_visible(&mut visible, children, &mut wfs_vis, true);
+            set_layer(&mut commands, entity, children, Some(layer));
+        } else {
+            set_visible(&mut visible, children, &mut wfs_vis, false);
+        }
+    }
+
+    // The cameras see the scene like the main camera does.
+    let perspective = main_camera.iter().next().cloned().unwrap_or_default();
+    for (name, side, layer) in [
+        (LEFT_CAMERA, -1.0, LEFT_LAYER),
+        (RIGHT_CAMERA, 1.0, RIGHT_LAYER),
+    ] {
+        comparison.cameras.push(
+            commands
+                .spawn_bundle((
+                    Camera {
+                        name: Some(name.to_string()),
+                        ..Default::default()
+                    },
+                    SplitProjection {
+                        perspective: perspective.clone(),
+                        side,
+                    },
+                    VisibleEntities::default(),
+                    RenderLayers::layer(layer),
+                    Transform::default(),
+                    GlobalTransform::default(),
+                ))
+                .id(),
+        );
+    }
+
+    comparison.saved = saved;
+    comparison.applied = comparison.pair;
+}
+
+/// The system that shows the side panel with the comparison report.
+pub fn show_comparison_panel(
+    egui_ctx: Res<EguiContext>,
+    mut comparison: ResMut<Comparison>,
+    polies: Query<&NamedConcrete>,
+    selected_language: Res<SelectedLanguage>,
+) {
+    let (left, right) = match comparison.pair {
+        Some((left, right)) => match (polies.get(left), polies.get(right)) {
+            (Ok(left), Ok(right)) => (left, right),
+            _ => return,
+        },
+        None => return,
+    };
+
+    let mut stop = false;
+
+    egui::SidePanel::right("comparison_panel").show(egui_ctx.ctx(), |ui| {
+        ui.heading("Comparison");
+        ui.label(format!("Left: {}", selected_language.parse(&left.name)));
+        ui.label(format!("Right: {}", selected_language.parse(&right.name)));
+        ui.separator();
+
+        let report = comparison.report.get_or_insert_with(|| left.diff(right));
+
+        // The element counts of both polytopes, and their differences.
+        egui::Grid::new("comparison_grid")
+            .striped(true)
+            .show(ui, |ui| {
+                ui.label("Rank");
+                ui.label("Left");
+                ui.label("Right");
+                ui.label("Δ");
+                ui.end_row();
+
+                for count in &report.el_counts {
+                    ui.label(count.rank.to_string());
+                    ui.label(count.left.to_string());
+                    ui.label(count.right.to_string());
+                    ui.label(match count.delta() {
+                        0 => "=".to_string(),
+                        diff => format!("{:+}", diff),
+                    });
+                    ui.end_row();
+                }
+            });
+
+        // The element orbits that don't match.
+        if let Some(orbit_counts) = &report.orbit_counts {
+            for count in orbit_counts.iter().filter(|count| count.delta() != 0) {
+                ui.label(format!(
+                    "Rank {} orbits: {} vs {}",
+                    count.rank, count.left, count.right
+                ));
+            }
+        }
+
+        match report.isomorphic {
+            Some(true) => ui.label("Isomorphic"),
+            Some(false) => ui.label("Not isomorphic"),
+            None => ui.label("Isomorphism not checked"),
+        };
+
+        if let Some(displacement) = report.displacement {
+            ui.label(format!(
+                "Vertex displacement: max {:.6}, mean {:.6}",
+                displacement.max, displacement.mean
+            ));
+        }
+
+        ui.separator();
+        if ui.button("Stop comparing").clicked() {
+            stop = true;
+        }
+    });
+
+    if stop {
+        comparison.stop();
+    }
+}
@@ -12,6 +12,12 @@ use bevy_egui::{egui, EguiContext};
 use directories::ProjectDirs;
 use serde::{Deserialize, Serialize};
 
+use super::viewpoints::CameraBookmarks;
+use crate::palette::ColorSettings;
+use miratope_core::{
+    limits::SizeLimit,
+    versioned::{self, Versioned},
+};
 use miratope_lang::SelectedLanguage;
 
 /// The default path in which we look for the Miratope library.
@@ -42,6 +48,9 @@ impl Plugin for ConfigPlugin {
             .insert_resource(config.selected_language)
             .insert_resource(config.background_color.clear_color())
             .insert_resource(config.light_mode.visuals())
+            .insert_resource(config.color_settings)
+            .insert_resource(config.camera_bookmarks)
+            .insert_resource(config.size_limit)
             .add_system(update_visuals.system())
             .add_system_to_stage(CoreStage::Last, save_config.system());
     }
@@ -132,6 +141,24 @@ pub struct Config {
 
     /// Whether light mode is enabled.
     pub light_mode: LightMode,
+
+    /// The palettes defined by the user, and the default color scheme.
+    #[serde(default)]
+    pub color_settings: ColorSettings,
+
+    /// The saved positions of the camera.
+    #[serde(default)]
+    pub camera_bookmarks: CameraBookmarks,
+
+    /// The largest polytopes that operations are allowed to build.
+    #[serde(default)]
+    pub size_limit: SizeLimit,
+}
+
+/// Configuration files from before version tags were introduced are read as
+/// the first version.
+impl Versioned for Config {
+    const VERSION: u32 = 1;
 }
 
 impl Config {
@@ -146,7 +173,7 @@ impl Config {
 
     /// Attempts to read the configuration from a given path.
     pub fn from_path<T: AsRef<OsStr>>(config_path: T) -> Option<Self> {
-        ron::from_str(&fs::read_to_string(config_path.as_ref()).ok()?).ok()
+        versioned::from_ron(&fs::read_to_string(config_path.as_ref()).ok()?).ok()
     }
 
     pub fn save<T: AsRef<OsStr>>(&self, config_path: T) {
@@ -154,7 +181,7 @@ impl Config {
             // If the file could be created, we write to it.
             Ok(mut file) => {
                 if file
-                    .write(ron::to_string(self).unwrap().as_bytes())
+                    .write(versioned::to_ron(self).unwrap().as_bytes())
                     .is_err()
                 {
                     eprintln!("Could not write to the configuration file!");
@@ -209,6 +236,9 @@ fn save_config(
     selected_language: Res<SelectedLanguage>,
     background_color: Res<ClearColor>,
     visuals: Res<egui::Visuals>,
+    color_settings: Res<ColorSettings>,
+    camera_bookmarks: Res<CameraBookmarks>,
+    size_limit: Res<SizeLimit>,
 ) {
     // If the application is being exited:
     if exit.iter().next().is_some() {
@@ -217,6 +247,9 @@ fn save_config(
             selected_language: *selected_language,
             background_color: BgColor::new(background_color.as_ref()),
             light_mode: LightMode(!visuals.dark_mode),
+            color_settings: color_settings.clone(),
+            camera_bookmarks: camera_bookmarks.clone(),
+            size_limit: *size_limit,
         };
 
         config.save(&config_path.0);
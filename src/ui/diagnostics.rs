@@ -0,0 +1,109 @@
+//! Reports the problems found with the polytopes on screen, such as polytopes
+//! that can't be drawn, in a window rather than by crashing the application.
+
+use std::collections::HashMap;
+
+use crate::mesh::MeshError;
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+use miratope_core::abs::AbstractError;
+use miratope_lang::poly::conc::NamedConcrete;
+
+/// The plugin in charge of the diagnostics window.
+pub struct DiagnosticsPlugin;
+
+impl Plugin for DiagnosticsPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.insert_resource(Diagnostics::default())
+            // Windows must be the first thing shown.
+            .add_system(show_diagnostics.system().before("show_top_panel"));
+    }
+}
+
+/// A problem found with a polytope.
+pub enum Diagnostic {
+    /// The polytope can't be drawn, or can only be partially drawn.
+    Mesh(MeshError),
+
+    /// The polytope isn't a valid abstract polytope. This is only checked in
+    /// debug builds.
+    Abstract(AbstractError),
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Mesh(err) => write!(f, "Can't be drawn: {}.", err),
+            Self::Abstract(err) => write!(f, "Isn't valid: {}.", err),
+        }
+    }
+}
+
+/// The problems found with the polytopes on screen.
+#[derive(Default)]
+pub struct Diagnostics {
+    /// The name of each polytope with problems, together with the problems.
+    problems: HashMap<Entity, (String, Vec<Diagnostic>)>,
+
+    /// Whether the window is open. It opens by itself whenever a problem is
+    /// found.
+    open: bool,
+}
+
+impl Diagnostics {
+    /// Sets the problems found with a polytope, replacing the ones found
+    /// before.
+    pub fn set(&mut self, entity: Entity, name: String, problems: Vec<Diagnostic>) {
+        if problems.is_empty() {
+            self.problems.remove(&entity);
+            return;
+        }
+
+        for problem in &problems {
+            eprintln!("{}: {}", name, problem);
+        }
+
+        self.problems.insert(entity, (name, problems));
+        self.open = true;
+    }
+
+    /// Opens the window.
+    pub fn open(&mut self) {
+        self.open = true;
+    }
+}
+
+/// The system that shows the diagnostics window.
+pub fn show_diagnostics(
+    egui_ctx: Res<EguiContext>,
+    mut diagnostics: ResMut<Diagnostics>,
+    polies: Query<(), With<NamedConcrete>>,
+) {
+    // Forgets about the polytopes that were removed.
+    if diagnostics.problems.keys().any(|&e| polies.get(e).is_err()) {
+        diagnostics.problems.retain(|&e, _| polies.get(e).is_ok());
+    }
+
+    let Diagnostics { problems, open } = &mut *diagnostics;
+    if !*open {
+        return;
+    }
+
+    egui::Window::new("Diagnostics")
+        .open(open)
+        .resizable(false)
+        .show(egui_ctx.ctx(), |ui| {
+            if problems.is_empty() {
+                ui.label("No problems found.");
+            }
+
+            for (name, problems) in problems.values() {
+                ui.add(egui::Label::new(name).strong());
+
+                for problem in problems {
+                    ui.label(problem.to_string());
+                }
+            }
+        });
+}
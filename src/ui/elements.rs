@@ -0,0 +1,198 @@
+//! Contains the element browser, which extracts a single element or section of
+//! the active polytope, either to replace the polytope or to save it into a
+//! file of its own.
+
+use super::{scene::Active, top_panel::FileDialogState};
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+use miratope_core::{
+    abs::{
+        elements::{ElementRef, SectionRef},
+        rank::Rank,
+    },
+    conc::ConcretePolytope,
+    Polytope,
+};
+use miratope_lang::{poly::conc::NamedConcrete, SelectedLanguage};
+
+/// The plugin in charge of the element browser.
+pub struct ElementsPlugin;
+
+impl Plugin for ElementsPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.insert_resource(ElementsWindow::default())
+            // Windows must be the first thing shown.
+            .add_system(show_elements_window.system().before("show_top_panel"));
+    }
+}
+
+/// An element picked in the element browser.
+#[derive(Clone, Copy, Default)]
+struct Picked {
+    /// The rank of the element.
+    rank: isize,
+
+    /// The index of the element.
+    idx: usize,
+}
+
+impl Picked {
+    /// Returns a reference to the element.
+    fn el(self) -> ElementRef {
+        ElementRef::new(Rank::new(self.rank), self.idx)
+    }
+}
+
+/// The window of the element browser.
+#[derive(Default)]
+pub struct ElementsWindow {
+    /// Whether the window is open.
+    open: bool,
+
+    /// The element that's extracted, or the upper element of the section.
+    hi: Picked,
+
+    /// Whether a section is extracted rather than a single element.
+    section: bool,
+
+    /// The lower element of the section.
+    lo: Picked,
+}
+
+impl ElementsWindow {
+    /// Opens the window.
+    pub fn open(&mut self) {
+        self.open = true;
+    }
+
+    /// Returns a short description of what's extracted, used to name files.
+    fn description(&self) -> String {
+        if self.section {
+            format!(
+                "section {} {} to {} {}",
+                self.lo.rank, self.lo.idx, self.hi.rank, self.hi.idx
+            )
+        } else {
+            format!("element {} {}", self.hi.rank, self.hi.idx)
+        }
+    }
+
+    /// Extracts the picked element or section from a polytope. Prints out the
+    /// reason and returns `None` if it can't be done.
+    fn extract(&self, poly: &NamedConcrete) -> Option<NamedConcrete> {
+        let mut extracted = if self.section {
+            let section = SectionRef::new(self.lo.el(), self.hi.el());
+
+            match poly.section(section) {
+                Ok(Some(section)) => section,
+                Ok(None) => {
+                    eprintln!("Section failed: the {} doesn't exist.", section);
+                    return None;
+                }
+                Err(err) => {
+                    eprintln!("Section failed: {}", err);
+                    return None;
+                }
+            }
+        } else {
+            let el = self.hi.el();
+
+            match poly.element(el) {
+                Some(element) => element,
+                None => {
+                    eprintln!("Element failed: the {} doesn't exist.", el);
+                    return None;
+                }
+            }
+        };
+
+        extracted.flatten();
+        extracted.recenter();
+        Some(extracted)
+    }
+}
+
+/// Shows the drag values that pick an element of a polytope with at most a
+/// given rank.
+fn pick_element(ui: &mut egui::Ui, poly: &NamedConcrete, picked: &mut Picked, max_rank: isize) {
+    ui.horizontal(|ui| {
+        ui.label("Rank");
+        ui.add(egui::DragValue::new(&mut picked.rank).clamp_range(-1..=max_rank));
+
+        let count = poly.el_count(Rank::new(picked.rank));
+        ui.label("index");
+        ui.add(egui::DragValue::new(&mut picked.idx).clamp_range(0..=count.saturating_sub(1)));
+        ui.label(format!("of {}", count));
+    });
+}
+
+/// The system that shows the element browser.
+pub fn show_elements_window(
+    egui_ctx: Res<EguiContext>,
+    mut elements_window: ResMut<ElementsWindow>,
+    mut query: Query<&mut NamedConcrete, With<Active>>,
+    mut file_dialog_state: ResMut<FileDialogState>,
+    selected_language: Res<SelectedLanguage>,
+) {
+    if !elements_window.open {
+        return;
+    }
+
+    let mut p = match query.iter_mut().next() {
+        Some(p) => p,
+        None => return,
+    };
+
+    let rank = p.rank().into_isize();
+    let mut window = std::mem::take(&mut *elements_window);
+    let ElementsWindow {
+        open,
+        hi,
+        section,
+        lo,
+    } = &mut window;
+    let mut make_active = false;
+    let mut export = false;
+
+    egui::Window::new("Elements")
+        .open(open)
+        .resizable(false)
+        .show(egui_ctx.ctx(), |ui| {
+            pick_element(ui, &p, hi, rank);
+
+            ui.checkbox(section, "Section down to");
+            if *section {
+                pick_element(ui, &p, lo, hi.rank);
+            }
+
+            ui.horizontal(|ui| {
+                // Replaces the active polytope by the element.
+                if ui.button("Make active").clicked() {
+                    make_active = true;
+                }
+
+                // Saves the element into a file of its own.
+                if ui.button("Export as OFF…").clicked() {
+                    export = true;
+                }
+            });
+        });
+
+    if make_active {
+        if let Some(extracted) = window.extract(&p) {
+            *p = extracted;
+        }
+    } else if export {
+        if let Some(extracted) = window.extract(&p) {
+            let name = format!(
+                "{} {}",
+                selected_language.parse(&p.name),
+                window.description()
+            );
+            file_dialog_state.export(extracted.con, name);
+        }
+    }
+
+    *elements_window = window;
+}
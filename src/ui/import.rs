@@ -0,0 +1,122 @@
+//! Contains the code that imports polytopes from files dropped onto the window
+//! and from text pasted by the user.
+
+use std::path::Path;
+
+use super::scene::Active;
+use bevy::{prelude::*, window::FileDragAndDrop};
+use bevy_egui::{egui, EguiContext};
+use miratope_core::conc::{file::FromFile, ConcretePolytope};
+use miratope_lang::poly::conc::NamedConcrete;
+
+/// The plugin in charge of importing polytopes.
+pub struct ImportPlugin;
+
+impl Plugin for ImportPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.insert_resource(PasteWindow::default())
+            .add_system(drag_and_drop.system())
+            // Windows must be the first thing shown.
+            .add_system(show_paste_window.system().before("show_top_panel"));
+    }
+}
+
+/// Loads a polytope from a file path into the active polytope, and recenters
+/// it. Prints an error message if this fails.
+pub fn load_path(p: &mut NamedConcrete, path: &Path) {
+    match NamedConcrete::from_path(&path) {
+        Ok(mut q) => {
+            // Remembers where the polytope came from.
+            q.con
+                .metadata_mut()
+                .source
+                .get_or_insert_with(|| path.display().to_string());
+
+            *p = q;
+            p.recenter();
+        }
+        Err(err) => eprintln!("File open failed: {}", err),
+    }
+}
+
+/// Loads a polytope from some text into the active polytope, and recenters
+/// it. Returns whether this succeeded, printing an error message otherwise.
+pub fn load_text(p: &mut NamedConcrete, src: &str) -> bool {
+    match NamedConcrete::from_text(src) {
+        Ok(q) => {
+            *p = q;
+            p.recenter();
+            true
+        }
+        Err(err) => {
+            eprintln!("Import failed: {}", err);
+            false
+        }
+    }
+}
+
+/// The system that loads any files dropped onto the window.
+pub fn drag_and_drop(
+    mut events: EventReader<FileDragAndDrop>,
+    mut query: Query<&mut NamedConcrete, With<Active>>,
+) {
+    for event in events.iter() {
+        if let FileDragAndDrop::DroppedFile { path_buf, .. } = event {
+            if let Some(mut p) = query.iter_mut().next() {
+                load_path(&mut p, path_buf);
+            }
+        }
+    }
+}
+
+/// The window in which the user can paste an OFF file, a list of coordinates,
+/// or a Coxeter diagram, to build a polytope from it.
+#[derive(Default)]
+pub struct PasteWindow {
+    /// Whether the window is open.
+    open: bool,
+
+    /// The text that's been pasted.
+    text: String,
+}
+
+impl PasteWindow {
+    /// Opens the window.
+    pub fn open(&mut self) {
+        self.open = true;
+    }
+}
+
+/// The system that shows the paste window.
+pub fn show_paste_window(
+    egui_ctx: Res<EguiContext>,
+    mut query: Query<&mut NamedConcrete, With<Active>>,
+    mut paste_window: ResMut<PasteWindow>,
+) {
+    let PasteWindow { open, text } = &mut *paste_window;
+    let mut import = false;
+
+    egui::Window::new("Paste")
+        .open(open)
+        .resizable(true)
+        .show(egui_ctx.ctx(), |ui| {
+            ui.label("Paste an OFF file, a list of coordinates, or a Coxeter diagram:");
+            ui.add(egui::TextEdit::multiline(text).code_editor());
+
+            ui.horizontal(|ui| {
+                import = ui.button("Import").clicked();
+
+                if ui.button("Clear").clicked() {
+                    text.clear();
+                }
+            });
+        });
+
+    if import {
+        if let Some(mut p) = query.iter_mut().next() {
+            if load_text(&mut p, text) {
+                paste_window.open = false;
+            }
+        }
+    }
+}
@@ -0,0 +1,316 @@
+//! Shows labels with the indices or coordinates of the elements of the active
+//! polytope next to them, which is useful when debugging constructions.
+//!
+//! Labels are only shown for the elements that pass the visibility filter of
+//! the polytope, and that have indices within a chosen range.
+
+use super::{
+    scene::Active,
+    visibility::{ShownElements, VisibilityFilter},
+};
+use crate::{lod::Viewport, projection::Projection};
+
+use bevy::{prelude::*, render::camera::PerspectiveProjection};
+use bevy_egui::{egui, EguiContext};
+use miratope_core::{
+    abs::rank::Rank,
+    conc::{Concrete, ConcretePolytope},
+    geometry::Point,
+    Float, Polytope,
+};
+use miratope_lang::poly::conc::NamedConcrete;
+use vec_like::VecLike;
+
+/// The maximum number of labels shown at once.
+const MAX_LABELS: usize = 2000;
+
+/// The plugin in charge of the element labels.
+pub struct LabelsPlugin;
+
+impl Plugin for LabelsPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.insert_resource(LabelsWindow::default())
+            // Windows must be the first thing shown.
+            .add_system(show_labels.system().before("show_top_panel"));
+    }
+}
+
+/// The settings that determine which labels are shown.
+#[derive(Clone, Copy, PartialEq)]
+pub struct LabelSettings {
+    /// Whether vertices, edges and faces are labeled, respectively.
+    pub ranks: [bool; 3],
+
+    /// Whether labels show coordinates rather than indices. The coordinates
+    /// of edges and faces are those of their centroids.
+    pub coordinates: bool,
+
+    /// The smallest index of a labeled element.
+    pub min_index: usize,
+
+    /// The largest index of a labeled element.
+    pub max_index: usize,
+}
+
+impl Default for LabelSettings {
+    fn default() -> Self {
+        Self {
+            ranks: [true, false, false],
+            coordinates: false,
+            min_index: 0,
+            max_index: 99,
+        }
+    }
+}
+
+/// A label, placed at the centroid of the element it labels.
+struct Label {
+    /// The position of the label, in the 3D coordinates of the polytope.
+    position: [f32; 3],
+
+    /// The text of the label.
+    text: String,
+}
+
+/// The labels of a polytope, together with what they were computed from.
+struct LabelCache {
+    /// The entity of the labeled polytope.
+    entity: Entity,
+
+    /// The settings with which the labels were computed.
+    settings: LabelSettings,
+
+    /// The labels.
+    labels: Vec<Label>,
+
+    /// Whether some labels were left out because there were too many.
+    truncated: bool,
+}
+
+/// The window with the settings of the labels.
+#[derive(Default)]
+pub struct LabelsWindow {
+    /// Whether the window is open. Labels are only shown while it is.
+    open: bool,
+
+    /// The settings of the labels.
+    settings: LabelSettings,
+
+    /// The labels of the active polytope.
+    cache: Option<LabelCache>,
+}
+
+impl LabelsWindow {
+    /// Opens the window.
+    pub fn open(&mut self) {
+        self.open = true;
+    }
+}
+
+/// Formats the coordinates of a point.
+fn format_point(point: &Point) -> String {
+    let coords: Vec<_> = point.iter().map(|c| format!("{:.3}", c)).collect();
+    format!("({})", coords.join(", "))
+}
+
+/// Returns the indices of the vertices of an element of rank at most 2.
+fn element_vertices(poly: &Concrete, rank: usize, idx: usize) -> Vec<usize> {
+    match rank {
+        0 => vec![idx],
+        1 => poly[Rank::new(1)][idx].subs.iter().copied().collect(),
+        _ => {
+            let mut vertices: Vec<_> = poly[Rank::new(2)][idx]
+                .subs
+                .iter()
+                .flat_map(|&e| poly[Rank::new(1)][e].subs.iter().copied())
+                .collect();
+            vertices.sort_unstable();
+            vertices.dedup();
+            vertices
+        }
+    }
+}
+
+/// Computes the labels of a polytope. Returns them, together with whether any
+/// were left out because there were too many.
+fn compute_labels(
+    poly: &Concrete,
+    shown: &ShownElements,
+    settings: &LabelSettings,
+    projection_type: Projection,
+) -> (Vec<Label>, bool) {
+    let mut labels = Vec::new();
+    if poly.vertex_count() == 0 || crate::mesh::check(poly).is_err() {
+        return (labels, false);
+    }
+
+    let vertices = crate::mesh::vertex_coords(poly, poly.vertices.iter(), projection_type);
+    let dim = poly.dim_or();
+
+    for (rank, prefix) in ["v", "e", "f"].iter().enumerate() {
+        let count = poly.el_count(Rank::from(rank));
+        if !settings.ranks[rank] || count == 0 {
+            continue;
+        }
+
+        let indices = shown.filter(rank, (0..count).collect());
+        let max_index = settings.max_index.min(count - 1);
+
+        for idx in (settings.min_index..=max_index).filter_map(|idx| indices[idx]) {
+            if labels.len() == MAX_LABELS {
+                return (labels, true);
+            }
+
+            let element = element_vertices(poly, rank, idx);
+            let n = element.len().max(1);
+
+            let mut position = [0.0; 3];
+            for &v in &element {
+                for (p, x) in position.iter_mut().zip(&vertices[v]) {
+                    *p += x / n as f32;
+                }
+            }
+
+            let text = if settings.coordinates {
+                let mut centroid = Point::zeros(dim);
+                for &v in &element {
+                    centroid += &poly.vertices[v];
+                }
+                format_point(&(centroid / n as Float))
+            } else {
+                format!("{}{}", prefix, idx)
+            };
+
+            labels.push(Label { position, text });
+        }
+    }
+
+    (labels, false)
+}
+
+/// The system that shows the labels window, and the labels of the active
+/// polytope while it's open.
+#[allow(clippy::type_complexity)]
+pub fn show_labels(
+    egui_ctx: Res<EguiContext>,
+    mut labels_window: ResMut<LabelsWindow>,
+    windows: Res<Windows>,
+    projection_type: Res<Projection>,
+    active: Query<(Entity, &NamedConcrete, &GlobalTransform, &VisibilityFilter), With<Active>>,
+    changed: Query<
+        (),
+        (
+            With<Active>,
+            Or<(Changed<NamedConcrete>, Changed<VisibilityFilter>)>,
+        ),
+    >,
+    cameras: Query<(&GlobalTransform, &PerspectiveProjection)>,
+) {
+    let LabelsWindow {
+        open,
+        settings,
+        cache,
+    } = &mut *labels_window;
+
+    if !*open {
+        return;
+    }
+
+    let (entity, poly, transform, filter) = match active.iter().next() {
+        Some(entry) => entry,
+        None => return,
+    };
+
+    // Recomputes the labels if anything they depend on changed.
+    let outdated = match cache {
+        Some(cache) => cache.entity != entity || cache.settings != *settings,
+        None => true,
+    };
+    if outdated || changed.iter().next().is_some() || projection_type.is_changed() {
+        let (labels, truncated) = compute_labels(
+            &poly.con,
+            &filter.shown(&poly.con),
+            settings,
+            *projection_type,
+        );
+
+        *cache = Some(LabelCache {
+            entity,
+            settings: *settings,
+            labels,
+            truncated,
+        });
+    }
+
+    let cache = cache.as_ref().unwrap();
+    let mut new_settings = *settings;
+
+    egui::Window::new("Labels")
+        .open(open)
+        .resizable(false)
+        .show(egui_ctx.ctx(), |ui| {
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut new_settings.ranks[0], "Vertices");
+                ui.checkbox(&mut new_settings.ranks[1], "Edges");
+                ui.checkbox(&mut new_settings.ranks[2], "Faces");
+            });
+
+            ui.horizontal(|ui| {
+                ui.radio_value(&mut new_settings.coordinates, false, "Indices");
+                ui.radio_value(&mut new_settings.coordinates, true, "Coordinates");
+            });
+
+            ui.horizontal(|ui| {
+                ui.label("Only elements");
+                ui.add(egui::DragValue::new(&mut new_settings.min_index));
+                ui.label("to");
+                ui.add(egui::DragValue::new(&mut new_settings.max_index));
+            });
+
+            if new_settings.max_index < new_settings.min_index {
+                new_settings.max_index = new_settings.min_index;
+            }
+
+            if cache.truncated {
+                ui.label(format!("Only the first {} labels are shown.", MAX_LABELS));
+            }
+        });
+
+    *settings = new_settings;
+
+    // Labels are drawn on top of the polytope, but below every window.
+    let (camera, perspective) = match cameras.iter().next() {
+        Some(camera) => camera,
+        None => return,
+    };
+    let window = match windows.get_primary() {
+        Some(window) => window,
+        None => return,
+    };
+    // The UI isn't scaled, so each point is a physical pixel.
+    let viewport = Viewport::new(
+        transform.compute_matrix(),
+        camera.compute_matrix(),
+        perspective.fov,
+        perspective.near,
+        perspective.far,
+        window.physical_width() as f32,
+        window.physical_height() as f32,
+    );
+
+    let ctx = egui_ctx.ctx();
+    let painter = ctx.layer_painter(egui::LayerId::background());
+    let color = ctx.style().visuals.text_color();
+
+    for label in &cache.labels {
+        if let Some([x, y]) = viewport.project(label.position) {
+            painter.text(
+                egui::pos2(x, y),
+                egui::Align2::LEFT_BOTTOM,
+                &label.text,
+                egui::TextStyle::Small,
+                color,
+            );
+        }
+    }
+}
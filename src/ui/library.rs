@@ -4,12 +4,22 @@ use std::{
     ffi::{OsStr, OsString},
     fs, io,
     path::{Path, PathBuf},
+    sync::{
+        mpsc::{self, Receiver, TryRecvError},
+        Mutex,
+    },
 };
 
-use super::config::LibPath;
+use super::{config::LibPath, scene::Active};
 use miratope_core::{
     abs::rank::Rank,
-    conc::{file::FromFile, ConcretePolytope},
+    conc::{
+        file::{
+            index::{IndexQuery, LibraryIndex},
+            FromFile,
+        },
+        ConcretePolytope,
+    },
     Polytope,
 };
 use miratope_lang::{
@@ -29,17 +39,21 @@ pub struct LibraryPlugin;
 impl Plugin for LibraryPlugin {
     fn build(&self, app: &mut AppBuilder) {
         // This must run after the Config resource has been added.
-        let lib_path = &app.world().get_resource::<LibPath>().unwrap();
-        let library = Library::new_folder(lib_path);
+        let lib_path = app.world().get_resource::<LibPath>().unwrap().clone();
+        let library = Library::new_folder(&lib_path);
+        let index_state = LibraryIndexState::new(&lib_path);
 
         // The library must be shown after the top panel, to avoid incorrect
         // positioning.
-        app.insert_resource(library).add_system(
-            show_library
-                .system()
-                .label("show_library")
-                .after("show_top_panel"),
-        );
+        app.insert_resource(library)
+            .insert_resource(index_state)
+            .add_system(update_library_index.system())
+            .add_system(
+                show_library
+                    .system()
+                    .label("show_library")
+                    .after("show_top_panel"),
+            );
     }
 }
 
@@ -490,11 +504,155 @@ impl Library {
     }
 }
 
+/// The maximum number of search results shown at once.
+const MAX_SEARCH_RESULTS: usize = 200;
+
+/// Stores the index of the library, which allows us to search through it
+/// without having to open every file. The index is updated in a background
+/// thread when the application starts.
+pub struct LibraryIndexState {
+    /// The latest version of the index, if any.
+    index: Option<LibraryIndex>,
+
+    /// Receives the updated index from the background thread, as long as it's
+    /// still running.
+    receiver: Option<Mutex<Receiver<LibraryIndex>>>,
+
+    /// The current search query.
+    query: IndexQuery,
+}
+
+impl LibraryIndexState {
+    /// Loads the index stored in the library folder, and starts updating it in
+    /// the background.
+    pub fn new(lib_path: &LibPath) -> Self {
+        let root = PathBuf::from(lib_path.as_ref());
+        let index = LibraryIndex::load(&root);
+
+        let (sender, receiver) = mpsc::channel();
+        let mut new_index = index.clone().unwrap_or_default();
+
+        std::thread::spawn(move || match new_index.update(&root) {
+            Ok(()) => {
+                if let Err(err) = new_index.save(&root) {
+                    eprintln!("Library index could not be saved: {}", err);
+                }
+
+                // The receiver might have been dropped, which is fine.
+                let _ = sender.send(new_index);
+            }
+            Err(err) => eprintln!("Library indexing failed: {}", err),
+        });
+
+        Self {
+            index,
+            receiver: Some(Mutex::new(receiver)),
+            query: Default::default(),
+        }
+    }
+
+    /// Whether the index is still being updated.
+    pub fn is_updating(&self) -> bool {
+        self.receiver.is_some()
+    }
+
+    /// Checks whether the background thread has finished updating the index.
+    pub fn poll(&mut self) {
+        let res = match &self.receiver {
+            Some(receiver) => receiver.lock().unwrap().try_recv(),
+            None => return,
+        };
+
+        match res {
+            Ok(index) => {
+                self.index = Some(index);
+                self.receiver = None;
+            }
+            Err(TryRecvError::Disconnected) => self.receiver = None,
+            Err(TryRecvError::Empty) => {}
+        }
+    }
+
+    /// Shows the search bar.
+    pub fn show_search(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Search:");
+            ui.text_edit_singleline(&mut self.query.text);
+        });
+
+        ui.horizontal(|ui| {
+            let mut filter_rank = self.query.rank.is_some();
+            ui.checkbox(&mut filter_rank, "Rank:");
+
+            let mut rank = self.query.rank.unwrap_or_else(|| Rank::new(3));
+            ui.add(
+                egui::DragValue::new(&mut rank)
+                    .speed(0.05)
+                    .clamp_range(-1..=20),
+            );
+            self.query.rank = filter_rank.then(|| rank);
+        });
+
+        if self.is_updating() {
+            ui.label("Indexing library…");
+        }
+    }
+
+    /// Shows the files matching the current query. Returns the action selected
+    /// by the user, if any.
+    pub fn show_results(&self, ui: &mut Ui, lib_path: PathBuf) -> ShowResult {
+        let index = if let Some(index) = &self.index {
+            index
+        } else {
+            ui.label("The library hasn't been indexed yet.");
+            return ShowResult::None;
+        };
+
+        let mut res = ShowResult::None;
+        let mut count = 0;
+
+        for entry in index.search(&self.query).take(MAX_SEARCH_RESULTS) {
+            let el_counts = entry
+                .el_counts
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            if ui
+                .button(format!("{} ({})", entry.name, el_counts))
+                .on_hover_text(entry.path.to_string_lossy())
+                .clicked()
+            {
+                res = ShowResult::Load(lib_path.join(&entry.path).into_os_string());
+            }
+
+            count += 1;
+        }
+
+        if count == 0 {
+            ui.label("No results.");
+        } else if count == MAX_SEARCH_RESULTS {
+            ui.label("Too many results, only showing the first few.");
+        }
+
+        res
+    }
+}
+
+/// Checks whether the library index has finished updating.
+fn update_library_index(mut index_state: ResMut<LibraryIndexState>) {
+    if index_state.is_updating() {
+        index_state.poll();
+    }
+}
+
 /// The system that shows the Miratope library.
 fn show_library(
     egui_ctx: Res<EguiContext>,
-    mut query: Query<&mut NamedConcrete>,
+    mut query: Query<&mut NamedConcrete, With<Active>>,
     mut library: ResMut<Option<Library>>,
+    mut index_state: ResMut<LibraryIndexState>,
     lib_path: Res<LibPath>,
     selected_language: Res<SelectedLanguage>,
 ) {
@@ -504,10 +662,21 @@ fn show_library(
             .default_width(350.0)
             .max_width(450.0)
             .show(egui_ctx.ctx(), |ui| {
+                index_state.show_search(ui);
+                ui.separator();
+
                 egui::containers::ScrollArea::auto_sized().show(ui, |ui| {
                     let lib_path = PathBuf::from(lib_path.as_ref());
 
-                    match library.show(ui, lib_path, *selected_language) {
+                    // Shows the search results instead of the library if the
+                    // user is searching for something.
+                    let res = if index_state.query.is_empty() {
+                        library.show(ui, lib_path, *selected_language)
+                    } else {
+                        index_state.show_results(ui, lib_path)
+                    };
+
+                    match res {
                         // No action needs to be taken.
                         ShowResult::None => {}
 
@@ -515,7 +684,12 @@ fn show_library(
                         ShowResult::Load(file) => {
                             if let Some(mut p) = query.iter_mut().next() {
                                 match NamedConcrete::from_path(&file) {
-                                    Ok(q) => *p = q,
+                                    Ok(mut q) => {
+                                        q.con.metadata_mut().source.get_or_insert_with(|| {
+                                            Path::new(&file).display().to_string()
+                                        });
+                                        *p = q;
+                                    }
                                     Err(err) => eprintln!("File open failed: {}", err),
                                 }
                             }
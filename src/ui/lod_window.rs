@@ -0,0 +1,92 @@
+//! Contains the window with the settings of the level of detail system.
+
+use crate::lod::LodSettings;
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+
+/// The plugin in charge of the level of detail settings.
+pub struct LodWindowPlugin;
+
+impl Plugin for LodWindowPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.insert_resource(LodSettings::default())
+            .insert_resource(LodWindow::default())
+            // Windows must be the first thing shown.
+            .add_system(show_lod_window.system().before("show_top_panel"));
+    }
+}
+
+/// The window with the settings of the level of detail system.
+#[derive(Default)]
+pub struct LodWindow {
+    /// Whether the window is open.
+    open: bool,
+}
+
+impl LodWindow {
+    /// Opens the window.
+    pub fn open(&mut self) {
+        self.open = true;
+    }
+}
+
+/// The system that shows the level of detail window.
+pub fn show_lod_window(
+    egui_ctx: Res<EguiContext>,
+    mut lod_window: ResMut<LodWindow>,
+    mut lod_settings: ResMut<LodSettings>,
+) {
+    let mut settings = *lod_settings;
+
+    egui::Window::new("Level of detail")
+        .open(&mut lod_window.open)
+        .resizable(false)
+        .show(egui_ctx.ctx(), |ui| {
+            ui.checkbox(&mut settings.enabled, "Enabled");
+
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::DragValue::new(&mut settings.min_edges)
+                        .speed(100.0)
+                        .clamp_range(0..=usize::MAX),
+                );
+                ui.label("Edges for a polytope to count as huge");
+            });
+
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::DragValue::new(&mut settings.min_edge_length)
+                        .speed(0.1)
+                        .clamp_range(0.0..=100.0),
+                );
+                ui.label("Minimum edge length (px)");
+            });
+
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::DragValue::new(&mut settings.min_face_size)
+                        .speed(0.1)
+                        .clamp_range(0.0..=100.0),
+                );
+                ui.label("Minimum face size (px)");
+            });
+
+            ui.checkbox(&mut settings.frustum_culling, "Skip elements off screen");
+
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::DragValue::new(&mut settings.interval)
+                        .speed(0.01)
+                        .clamp_range(0.0..=5.0),
+                );
+                ui.label("Seconds between updates");
+            });
+        });
+
+    // The settings are only written back if they change, so that polytopes
+    // aren't redrawn every frame.
+    if settings != *lod_settings {
+        *lod_settings = settings;
+    }
+}
@@ -1,8 +1,22 @@
 //! The systems that update the main window.
 
-use super::{camera::ProjectionType, top_panel::SectionState};
+use std::collections::HashMap;
 
-use bevy::prelude::*;
+use super::{
+    diagnostics::{Diagnostic, Diagnostics},
+    scene::Active,
+    spheres_and_tubes::DrawnElement,
+    top_panel::SectionState,
+    visibility::VisibilityFilter,
+};
+use crate::{
+    lod::{self, LodSettings, Viewport},
+    mesh::{MeshResult, Surface},
+    palette::{ColorScheme, ColorSettings},
+    projection::Projection,
+};
+
+use bevy::{prelude::*, render::camera::PerspectiveProjection};
 use bevy_egui::EguiSettings;
 use miratope_lang::{poly::conc::NamedConcrete, SelectedLanguage};
 
@@ -12,28 +26,88 @@ pub struct MainWindowPlugin;
 
 impl Plugin for MainWindowPlugin {
     fn build(&self, app: &mut AppBuilder) {
-        app.add_system_to_stage(CoreStage::PreUpdate, update_visible.system())
+        app.insert_resource(WireframeMode::Normal)
+            .add_system_to_stage(CoreStage::PreUpdate, update_visible.system())
             .add_system(update_scale_factor.system())
             .add_system_to_stage(CoreStage::PostUpdate, update_changed_polytopes.system());
     }
 }
 
+/// Returns the title of the window when a given polytope is active. If the
+/// polytope is recognized as a well-known polytope, its name gets added to the
+/// title, and it's also printed out if `verbose` is set.
+pub fn window_title(
+    selected_language: &SelectedLanguage,
+    poly: &NamedConcrete,
+    verbose: bool,
+) -> String {
+    let name = selected_language.parse(&poly.name);
+
+    match poly.con.abs.identify() {
+        Some(known) => {
+            if verbose {
+                println!("Recognized as the {} ({}).", known.name, known.acronym);
+            }
+
+            if name.eq_ignore_ascii_case(known.name) {
+                name
+            } else {
+                format!("{} ({})", name, known.name)
+            }
+        }
+        None => name,
+    }
+}
+
+/// How the wireframes of polytopes are drawn.
+#[derive(Clone, Copy, PartialEq)]
+pub enum WireframeMode {
+    /// Every edge is drawn, whether or not it's behind a face.
+    Normal,
+
+    /// Only the edges in front of every face are drawn, and the faces are
+    /// drawn in the background color, as in most classical illustrations of
+    /// polytopes.
+    HiddenLine,
+}
+
+impl WireframeMode {
+    /// Flips the wireframe mode.
+    pub fn flip(&mut self) {
+        match self {
+            Self::Normal => *self = Self::HiddenLine,
+            Self::HiddenLine => *self = Self::Normal,
+        }
+    }
+
+    /// Returns whether the wireframe mode is `HiddenLine`.
+    pub fn is_hidden_line(&self) -> bool {
+        match self {
+            Self::Normal => false,
+            Self::HiddenLine => true,
+        }
+    }
+}
+
+/// Toggles the visibility of the active polytope and its wireframe.
 pub fn update_visible(
     keyboard: Res<Input<KeyCode>>,
-    mut polies_vis: Query<&mut Visible, With<NamedConcrete>>,
+    mut polies_vis: Query<(&mut Visible, &Children), (With<NamedConcrete>, With<Active>)>,
     mut wfs_vis: Query<&mut Visible, Without<NamedConcrete>>,
 ) {
-    if keyboard.just_pressed(KeyCode::V) {
-        if let Some(mut visible) = polies_vis.iter_mut().next() {
+    if let Some((mut visible, children)) = polies_vis.iter_mut().next() {
+        if keyboard.just_pressed(KeyCode::V) {
             let vis = visible.is_visible;
             visible.is_visible = !vis;
         }
-    }
 
-    if keyboard.just_pressed(KeyCode::B) {
-        if let Some(mut visible) = wfs_vis.iter_mut().next() {
-            let vis = visible.is_visible;
-            visible.is_visible = !vis;
+        if keyboard.just_pressed(KeyCode::B) {
+            for child in children.iter() {
+                if let Ok(mut visible) = wfs_vis.get_mut(*child) {
+                    let vis = visible.is_visible;
+                    visible.is_visible = !vis;
+                }
+            }
         }
     }
 }
@@ -45,40 +119,229 @@ pub fn update_scale_factor(mut egui_settings: ResMut<EguiSettings>, windows: Res
     }
 }
 
-/// Updates polytopes after an operation.
+/// The state of the level of detail system.
+#[derive(Default)]
+pub struct LodState {
+    /// The time at which the meshes of huge polytopes were last rebuilt
+    /// because the camera moved.
+    last_rebuild: f64,
+
+    /// Whether the camera moved since then.
+    pending: bool,
+
+    /// The triangulated faces of every huge polytope. Only which faces are
+    /// drawn changes as the camera moves, so they're only triangulated again
+    /// when the polytope or the projection type change.
+    surfaces: HashMap<Entity, MeshResult<Surface>>,
+}
+
+/// Updates polytopes after an operation, after their colors or visibility
+/// filters are changed, or after the projection type or the wireframe mode
+/// are changed. Huge polytopes also get updated every so often while the
+/// camera moves.
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
 pub fn update_changed_polytopes(
     mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
 
-    polies: Query<(&NamedConcrete, &Handle<Mesh>, &Children), Changed<NamedConcrete>>,
-    wfs: Query<&Handle<Mesh>, Without<NamedConcrete>>,
+    polies: Query<(
+        Entity,
+        &NamedConcrete,
+        &GlobalTransform,
+        &ColorScheme,
+        &VisibilityFilter,
+        &Handle<Mesh>,
+        &Handle<StandardMaterial>,
+        &Children,
+    )>,
+    changed: Query<
+        Entity,
+        Or<(
+            Changed<NamedConcrete>,
+            Changed<ColorScheme>,
+            Changed<VisibilityFilter>,
+        )>,
+    >,
+    active: Query<&NamedConcrete, (With<Active>, Or<(Changed<NamedConcrete>, Added<Active>)>)>,
+    wfs: Query<&Handle<Mesh>, (Without<NamedConcrete>, Without<DrawnElement>)>,
 
     mut windows: ResMut<Windows>,
     mut section_state: ResMut<SectionState>,
     selected_language: Res<SelectedLanguage>,
-    orthogonal: Res<ProjectionType>,
+
+    // How polytopes are drawn.
+    (orthogonal, wireframe_mode, color_settings, background_color): (
+        Res<Projection>,
+        Res<WireframeMode>,
+        Res<ColorSettings>,
+        Res<ClearColor>,
+    ),
+
+    // The level of detail of huge polytopes depends on the camera.
+    time: Res<Time>,
+    lod_settings: Res<LodSettings>,
+    mut lod_state: Local<LodState>,
+    cameras: Query<(&GlobalTransform, &PerspectiveProjection)>,
+    moved_cameras: Query<(), (With<PerspectiveProjection>, Changed<GlobalTransform>)>,
+    mut diagnostics: ResMut<Diagnostics>,
 ) {
-    for (poly, mesh_handle, children) in polies.iter() {
-        if cfg!(debug_assertions) {
+    // Changing the projection type, the wireframe mode, the palettes or the
+    // level of detail settings requires redrawing every polytope. Hidden faces
+    // are drawn in the background color, so they're redrawn if it changes.
+    let hidden_line = wireframe_mode.is_hidden_line();
+    let redraw_all = orthogonal.is_changed()
+        || wireframe_mode.is_changed()
+        || color_settings.is_changed()
+        || lod_settings.is_changed()
+        || (hidden_line && background_color.is_changed());
+
+    // Huge polytopes are redrawn every so often while the camera moves.
+    if lod_settings.enabled && moved_cameras.iter().next().is_some() {
+        lod_state.pending = true;
+    }
+
+    let now = time.seconds_since_startup();
+    let redraw_lod = lod_state.pending && now - lod_state.last_rebuild >= lod_settings.interval;
+    if redraw_lod {
+        lod_state.pending = false;
+        lod_state.last_rebuild = now;
+    }
+
+    // Forgets the triangulations of polytopes that were changed, removed, or
+    // aren't huge anymore.
+    lod_state.surfaces.retain(|&entity, _| {
+        !orthogonal.is_changed()
+            && changed.get(entity).is_err()
+            && matches!(polies.get(entity), Ok((_, poly, ..)) if lod_settings.applies(&poly.con))
+    });
+
+    let camera = cameras.iter().next();
+    let window_size = windows
+        .get_primary()
+        .map(|window| (window.width(), window.height()));
+
+    for (entity, poly, transform, colors, filter, mesh_handle, material_handle, children) in
+        polies.iter()
+    {
+        let poly_changed = changed.get(entity).is_ok();
+        let lod_applies = lod_settings.applies(&poly.con);
+
+        if !redraw_all && !poly_changed && !(redraw_lod && lod_applies) {
+            continue;
+        }
+
+        // Problems with the polytope get reported rather than crash the
+        // application.
+        let mut problems = Vec::new();
+        if cfg!(debug_assertions) && (redraw_all || poly_changed) {
             println!("Polytope updated");
-            poly.con.abs.is_valid().unwrap();
+
+            if let Err(err) = poly.con.abs.is_valid() {
+                problems.push(Diagnostic::Abstract(err));
+            }
         }
 
-        *meshes.get_mut(mesh_handle).unwrap() = crate::mesh::mesh(&poly.con, *orthogonal);
+        // Polytopes that can't be drawn are left empty.
+        if let Err(err) = crate::mesh::check(&poly.con) {
+            problems.push(Diagnostic::Mesh(err));
+            diagnostics.set(entity, selected_language.parse(&poly.name), problems);
 
-        // Sets the window's name to the polytope's name.
-        windows
-            .get_primary_mut()
-            .unwrap()
-            .set_title(selected_language.parse(&poly.name));
+            *meshes.get_mut(mesh_handle).unwrap() = crate::mesh::empty_mesh();
+            for child in children.iter() {
+                if let Ok(wf_handle) = wfs.get_component::<Handle<Mesh>>(*child) {
+                    *meshes.get_mut(wf_handle).unwrap() = crate::mesh::empty_mesh();
+                }
+            }
+
+            continue;
+        }
+
+        let shown = filter.shown(&poly.con);
+        let mut face_colors = shown.filter(2, colors.face_colors(&poly.con, &color_settings));
+        let mut edge_colors = shown.filter(1, colors.edge_colors(&poly.con, &color_settings));
+
+        // Hides the elements of huge polytopes that are too small to be seen.
+        if let (true, Some((camera, perspective)), Some((width, height))) =
+            (lod_applies, camera, window_size)
+        {
+            let viewport = Viewport::new(
+                transform.compute_matrix(),
+                camera.compute_matrix(),
+                perspective.fov,
+                perspective.near,
+                perspective.far,
+                width,
+                height,
+            );
+            let vertices =
+                crate::mesh::vertex_coords(&poly.con, poly.con.vertices.iter(), *orthogonal);
+            let (drawn_edges, drawn_faces) =
+                lod::drawn_elements(&poly.con, &vertices, &viewport, &lod_settings);
+
+            // Small faces still need to hide the edges behind them.
+            if !hidden_line {
+                lod::hide(&mut face_colors, &drawn_faces);
+            }
+            lod::hide(&mut edge_colors, &drawn_edges);
+        }
+
+        // Huge polytopes keep their triangulation between rebuilds.
+        let new_surface;
+        let surface = if lod_applies {
+            lod_state
+                .surfaces
+                .entry(entity)
+                .or_insert_with(|| Surface::new(&poly.con, *orthogonal))
+        } else {
+            new_surface = Surface::new(&poly.con, *orthogonal);
+            &new_surface
+        };
+
+        if hidden_line {
+            for color in face_colors.iter_mut().flatten() {
+                *color = background_color.0;
+            }
+        }
+
+        // Some faces might still fail to be triangulated, in which case only
+        // the wireframe is drawn.
+        *meshes.get_mut(mesh_handle).unwrap() = match surface {
+            Ok(surface) if hidden_line => surface.occluder(&face_colors),
+            Ok(surface) => surface.mesh(&face_colors),
+            Err(err) => {
+                problems.push(Diagnostic::Mesh(*err));
+                crate::mesh::empty_mesh()
+            }
+        };
+
+        // Faces in hidden-line mode aren't lit, so that they blend in with
+        // the background.
+        if matches!(materials.get(material_handle), Some(material) if material.unlit != hidden_line)
+        {
+            materials.get_mut(material_handle).unwrap().unlit = hidden_line;
+        }
 
         // Updates all wireframes.
         for child in children.iter() {
             if let Ok(wf_handle) = wfs.get_component::<Handle<Mesh>>(*child) {
                 *meshes.get_mut(wf_handle).unwrap() =
-                    crate::mesh::wireframe(&poly.con, *orthogonal);
+                    crate::mesh::wireframe(&poly.con, *orthogonal, &edge_colors)
+                        .unwrap_or_else(|_| crate::mesh::empty_mesh());
             }
         }
 
+        if redraw_all || poly_changed {
+            diagnostics.set(entity, selected_language.parse(&poly.name), problems);
+        }
+    }
+
+    if let Some(poly) = active.iter().next() {
+        // Sets the window's name to the active polytope's name.
+        windows
+            .get_primary_mut()
+            .unwrap()
+            .set_title(window_title(&selected_language, poly, true));
+
         // We reset the cross-section view if we didn't use it to change the polytope.
         if !section_state.is_changed() {
             section_state.close();
@@ -1,4 +1,5 @@
-use bevy::prelude::Query;
+use super::scene::Active;
+use bevy::prelude::{Query, With};
 use bevy_egui::egui;
 use miratope_lang::{lang::En, poly::conc::NamedConcrete};
 
@@ -23,7 +24,7 @@ impl Memory {
     }
 
     /// Shows the memory menu in a specified Ui.
-    pub fn show(&mut self, ui: &mut egui::Ui, query: &mut Query<&mut NamedConcrete>) {
+    pub fn show(&mut self, ui: &mut egui::Ui, query: &mut Query<&mut NamedConcrete, With<Active>>) {
         use miratope_lang::Language;
 
         egui::menu::menu(ui, "Memory", |ui| {
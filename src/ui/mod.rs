@@ -5,12 +5,25 @@ use bevy_egui::egui::{self, Ui, Widget};
 use miratope_core::{geometry::Point, Consts, Float};
 
 pub mod camera;
+pub mod command_palette;
+pub mod comparison;
 pub mod config;
+pub mod diagnostics;
+pub mod elements;
+pub mod import;
+pub mod labels;
 pub mod library;
+pub mod lod_window;
 pub mod main_window;
 pub mod memory;
 pub mod operations;
+pub mod projection_window;
+pub mod render_window;
+pub mod scene;
+pub mod spheres_and_tubes;
 pub mod top_panel;
+pub mod viewpoints;
+pub mod visibility;
 
 /// All of the plugins specific to Miratope.
 pub struct MiratopePlugins;
@@ -19,11 +32,24 @@ impl bevy::prelude::PluginGroup for MiratopePlugins {
     fn build(&mut self, group: &mut bevy::app::PluginGroupBuilder) {
         group
             .add(camera::InputPlugin)
+            .add(command_palette::CommandPalettePlugin)
+            .add(comparison::ComparisonPlugin)
             .add(config::ConfigPlugin)
+            .add(diagnostics::DiagnosticsPlugin)
+            .add(elements::ElementsPlugin)
+            .add(import::ImportPlugin)
+            .add(labels::LabelsPlugin)
             .add(operations::OperationsPlugin)
             .add(library::LibraryPlugin)
+            .add(lod_window::LodWindowPlugin)
             .add(main_window::MainWindowPlugin)
-            .add(top_panel::TopPanelPlugin);
+            .add(projection_window::ProjectionWindowPlugin)
+            .add(render_window::RenderWindowPlugin)
+            .add(scene::ScenePlugin)
+            .add(spheres_and_tubes::SpheresAndTubesPlugin)
+            .add(top_panel::TopPanelPlugin)
+            .add(viewpoints::ViewpointsPlugin)
+            .add(visibility::VisibilityPlugin);
     }
 }
 
@@ -3,12 +3,14 @@
 //! All windows are loaded in parallel, before the top panel and the library are
 //! shown on screen.
 
-use std::marker::PhantomData;
+use std::{marker::PhantomData, ops::DerefMut};
 
-use super::{memory::Memory, PointWidget};
+use super::{memory::Memory, scene::Active, PointWidget};
 use miratope_core::{
+    abs::Abstract,
     conc::ConcretePolytope,
     geometry::{Hypersphere, Point},
+    limits::SizeLimit,
     Float, Polytope,
 };
 
@@ -51,6 +53,244 @@ impl Plugin for OperationsPlugin {
     }
 }
 
+/// An operation that acts on the active polytope right away, without showing
+/// a window first. These are shared by the top panel and the command palette.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    /// Converts the polytope into its dual.
+    Dual,
+
+    /// Makes a pyramid out of the polytope.
+    Pyramid,
+
+    /// Makes a prism out of the polytope.
+    Prism,
+
+    /// Makes a tegum out of the polytope.
+    Tegum,
+
+    /// Converts the polytope into its antiprism.
+    Antiprism,
+
+    /// Converts the polytope into its Petrial.
+    Petrial,
+
+    /// Converts the polytope into its Petrie polygon.
+    PetriePolygon,
+
+    /// Converts the polytope into its ditope.
+    Ditope,
+
+    /// Converts the polytope into its hosotope.
+    Hosotope,
+
+    /// Converts the polytope into its omnitruncate.
+    Omnitruncate,
+
+    /// Recenters the polytope.
+    Recenter,
+
+    /// Converts the polytope into one of its facets.
+    Facet,
+
+    /// Converts the polytope into one of its vertex figures.
+    Verf,
+
+    /// Prints the element types of the polytope.
+    Counts,
+
+    /// Prints the circumsphere of the polytope.
+    Circumsphere,
+
+    /// Prints whether the polytope is orientable.
+    Orientability,
+
+    /// Prints the volume of the polytope.
+    Volume,
+
+    /// Prints the number of flags of the polytope.
+    FlagCount,
+
+    /// Prints the vertex configurations of a polyhedron, or the signatures of
+    /// the elements of rank three less than the polytope in general.
+    Signatures,
+
+    /// Prints the numerical issues found in the polytope.
+    NumericalReport,
+}
+
+impl Operation {
+    /// Every operation, in the order they're listed in the top panel.
+    pub const ALL: [Self; 20] = [
+        Self::Dual,
+        Self::Pyramid,
+        Self::Prism,
+        Self::Tegum,
+        Self::Antiprism,
+        Self::Petrial,
+        Self::PetriePolygon,
+        Self::Ditope,
+        Self::Hosotope,
+        Self::Omnitruncate,
+        Self::Recenter,
+        Self::Facet,
+        Self::Verf,
+        Self::Counts,
+        Self::Circumsphere,
+        Self::Orientability,
+        Self::Volume,
+        Self::FlagCount,
+        Self::Signatures,
+        Self::NumericalReport,
+    ];
+
+    /// The name of the operation, as shown on screen.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Dual => "Dual",
+            Self::Pyramid => "Pyramid",
+            Self::Prism => "Prism",
+            Self::Tegum => "Tegum",
+            Self::Antiprism => "Antiprism",
+            Self::Petrial => "Petrial",
+            Self::PetriePolygon => "Petrie polygon",
+            Self::Ditope => "Ditope",
+            Self::Hosotope => "Hosotope",
+            Self::Omnitruncate => "Omnitruncate",
+            Self::Recenter => "Recenter",
+            Self::Facet => "Facet",
+            Self::Verf => "Verf",
+            Self::Counts => "Counts",
+            Self::Circumsphere => "Circumsphere",
+            Self::Orientability => "Orientability",
+            Self::Volume => "Volume",
+            Self::FlagCount => "Flag count",
+            Self::Signatures => "Signatures",
+            Self::NumericalReport => "Numerical report",
+        }
+    }
+
+    /// Applies the operation to a polytope. Operations that only compute some
+    /// property of the polytope print it to the console.
+    ///
+    /// This takes anything that dereferences into a polytope, so that the
+    /// operations that don't modify it don't mark it as changed either.
+    /// Operations that can blow up in size are refused if their result would
+    /// go over the given limit.
+    pub fn apply<T: DerefMut<Target = NamedConcrete>>(self, p: &mut T, limit: SizeLimit) {
+        match self {
+            Self::Dual => match p.try_dual_mut() {
+                Ok(_) => println!("Dual succeeded."),
+                Err(err) => eprintln!("Dual failed: {}", err),
+            },
+            Self::Pyramid => **p = p.pyramid(),
+            Self::Prism => **p = p.prism(),
+            Self::Tegum => **p = p.tegum(),
+            Self::Antiprism => match p.try_antiprism() {
+                Ok(q) => **p = q,
+                Err(err) => eprintln!("Antiprism failed: {}", err),
+            },
+            Self::Petrial => {
+                if p.petrial_mut() {
+                    println!("Petrial succeeded.");
+                } else {
+                    eprintln!("Petrial failed.");
+                }
+            }
+            Self::PetriePolygon => match p.petrie_polygon() {
+                Some(q) => {
+                    **p = q;
+                    println!("Petrie polygon succeeded.")
+                }
+                None => eprintln!("Petrie polygon failed."),
+            },
+            Self::Ditope => {
+                p.ditope_mut();
+                println!("Ditope succeeded!");
+            }
+            Self::Hosotope => {
+                p.hosotope_mut();
+                println!("Hosotope succeeded!");
+            }
+            Self::Omnitruncate => {
+                p.abs_sort();
+
+                match p.try_omnitruncate(limit) {
+                    Ok(q) => **p = q,
+                    Err(err) => eprintln!("Omnitruncate failed: {}", err),
+                }
+            }
+            Self::Recenter => p.recenter(),
+            Self::Facet => {
+                println!("Facet");
+
+                if let Some(mut facet) = p.facet(0) {
+                    facet.flatten();
+                    facet.recenter();
+                    **p = facet;
+
+                    println!("Facet succeeded.")
+                } else {
+                    eprintln!("Facet failed: no facets.")
+                }
+            }
+            Self::Verf => {
+                println!("Verf");
+
+                match p.verf(0) {
+                    Ok(Some(mut verf)) => {
+                        verf.flatten();
+                        verf.recenter();
+                        **p = verf;
+
+                        println!("Verf succeeded.")
+                    }
+                    Ok(None) => eprintln!("Verf failed: no vertices."),
+                    Err(err) => eprintln!("Verf failed: {}", err),
+                }
+            }
+            Self::Counts => p.con().print_element_types(),
+            Self::Circumsphere => match p.circumsphere() {
+                Some(sphere) => println!(
+                    "The circumradius is {} and the circumcenter is {}.",
+                    sphere.radius(),
+                    sphere.center
+                ),
+                None => println!("The polytope has no circumsphere."),
+            },
+            Self::Orientability => {
+                if p.orientable() {
+                    println!("The polytope is orientable.");
+                } else {
+                    println!("The polytope is not orientable.");
+                }
+            }
+            Self::Volume => {
+                p.abs_sort();
+
+                if let Some(vol) = p.volume() {
+                    println!("The volume is {}.", vol);
+                } else {
+                    println!("The polytope has no volume.");
+                }
+            }
+            Self::FlagCount => println!("The polytope has {} flags.", p.flag_count()),
+            Self::Signatures => match p.con().element_signatures() {
+                Some(signatures) => {
+                    for (signature, count) in signatures {
+                        println!("{} × {}", count, signature);
+                    }
+                }
+                None => println!("The polytope has no signatures."),
+            },
+            Self::NumericalReport => match p.con().numerical_report() {
+                Some(report) => print!("{}", report),
+                None => println!("The polytope has no vertices."),
+            },
+        }
+    }
+}
+
 /// A widget consisting of a Reset button and an Ok button, right-aligned.
 pub struct OkReset<'a> {
     result: &'a mut ShowResult,
@@ -137,7 +377,7 @@ macro_rules! impl_show {
         fn show_system(
             mut self_: ResMut<Self>,
             egui_ctx: Res<EguiContext>,
-            mut query: Query<&mut NamedConcrete>,
+            mut query: Query<&mut NamedConcrete, With<Active>>,
         ) where
             Self: 'static,
         {
@@ -222,11 +462,11 @@ pub trait UpdateWindow: Window {
     /// updated.
     fn update_system(
         mut self_: ResMut<Self>,
-        query: Query<(&NamedConcrete, &Handle<Mesh>, &Children), Changed<NamedConcrete>>,
+        query: Query<&NamedConcrete, (With<Active>, Or<(Changed<NamedConcrete>, Added<Active>)>)>,
     ) where
         Self: 'static,
     {
-        if let Some((poly, _, _)) = query.iter().next() {
+        if let Some(poly) = query.iter().next() {
             self_.update(poly.con.dim_or());
         }
     }
@@ -271,6 +511,14 @@ impl Default for Slot {
 /// A window for any duo-something. All of these depend on the [`Memory`] but
 /// don't need to be updated when the polytope changes.
 pub trait DuoWindow: Window {
+    /// Whether the product keeps the minimal elements of the factors, as in
+    /// [`Abstract::product`].
+    const MIN: bool;
+
+    /// Whether the product keeps the maximal elements of the factors, as in
+    /// [`Abstract::product`].
+    const MAX: bool;
+
     /// The duo-operation to apply.
     fn operation(&self, p: &NamedConcrete, q: &NamedConcrete) -> NamedConcrete;
 
@@ -306,10 +554,16 @@ pub trait DuoWindow: Window {
         [dim_or(p), dim_or(q)]
     }
 
-    /// Applies the action of the window to the polytope.
-    fn action(&self, polytope: &mut NamedConcrete, memory: &Res<Memory>) {
+    /// Applies the action of the window to the polytope, unless the result
+    /// would go over the given limit.
+    fn action(&self, polytope: &mut NamedConcrete, memory: &Res<Memory>, limit: SizeLimit) {
         if let [Some(p), Some(q)] = self.polytopes(polytope, memory) {
-            *polytope = self.operation(p, q);
+            let size = Abstract::estimate_product_size(p.abs(), q.abs(), Self::MIN, Self::MAX);
+
+            match limit.check(&size) {
+                Ok(()) => *polytope = self.operation(p, q),
+                Err(err) => eprintln!("{} failed: {}", Self::NAME, err),
+            }
         }
     }
 
@@ -419,15 +673,16 @@ pub trait DuoWindow: Window {
     fn show_system(
         mut self_: ResMut<Self>,
         egui_ctx: Res<EguiContext>,
-        mut query: Query<&mut NamedConcrete>,
+        mut query: Query<&mut NamedConcrete, With<Active>>,
         memory: Res<Memory>,
+        limit: Res<SizeLimit>,
     ) where
         Self: 'static,
     {
         for mut polytope in query.iter_mut() {
             match self_.show(egui_ctx.ctx(), &polytope, &memory) {
                 ShowResult::Ok => {
-                    self_.action(polytope.as_mut(), &memory);
+                    self_.action(polytope.as_mut(), &memory, *limit);
                     self_.close()
                 }
                 ShowResult::Close => self_.close(),
@@ -860,6 +1115,9 @@ impl Window for DuopyramidWindow {
 }
 
 impl DuoWindow for DuopyramidWindow {
+    const MIN: bool = true;
+    const MAX: bool = true;
+
     fn operation(&self, p: &NamedConcrete, q: &NamedConcrete) -> NamedConcrete {
         let [p_offset, q_offset] = &self.offsets;
         NamedConcrete::duopyramid_with(p, q, p_offset, q_offset, self.height)
@@ -913,6 +1171,9 @@ impl Window for DuoprismWindow {
 }
 
 impl DuoWindow for DuoprismWindow {
+    const MIN: bool = false;
+    const MAX: bool = true;
+
     fn operation(&self, p: &NamedConcrete, q: &NamedConcrete) -> NamedConcrete {
         NamedConcrete::duoprism(p, q)
     }
@@ -962,6 +1223,9 @@ impl Window for DuotegumWindow {
 }
 
 impl DuoWindow for DuotegumWindow {
+    const MIN: bool = true;
+    const MAX: bool = false;
+
     fn operation(&self, p: &NamedConcrete, q: &NamedConcrete) -> NamedConcrete {
         let [p_offset, q_offset] = &self.offsets;
         NamedConcrete::duotegum_with(p, q, p_offset, q_offset)
@@ -1010,6 +1274,9 @@ impl Window for DuocombWindow {
 }
 
 impl DuoWindow for DuocombWindow {
+    const MIN: bool = false;
+    const MAX: bool = false;
+
     fn operation(&self, p: &NamedConcrete, q: &NamedConcrete) -> NamedConcrete {
         NamedConcrete::duocomb(p, q)
     }
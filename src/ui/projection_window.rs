@@ -0,0 +1,90 @@
+//! Contains the window with the settings of how polytopes of more than three
+//! dimensions get projected into 3D.
+
+use super::camera::ProjectionType;
+use crate::projection::{Projection, ProjectionBasis};
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+
+/// The plugin in charge of the projection settings.
+pub struct ProjectionWindowPlugin;
+
+impl Plugin for ProjectionWindowPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.insert_resource(Projection::default())
+            .insert_resource(ProjectionWindow::default())
+            // Windows must be the first thing shown.
+            .add_system(show_projection_window.system().before("show_top_panel"));
+    }
+}
+
+/// The window with the projection settings.
+#[derive(Default)]
+pub struct ProjectionWindow {
+    /// Whether the window is open.
+    open: bool,
+}
+
+impl ProjectionWindow {
+    /// Opens the window.
+    pub fn open(&mut self) {
+        self.open = true;
+    }
+}
+
+/// The system that shows the projection window.
+pub fn show_projection_window(
+    egui_ctx: Res<EguiContext>,
+    mut projection_window: ResMut<ProjectionWindow>,
+    mut projection: ResMut<Projection>,
+) {
+    let mut settings = *projection;
+
+    egui::Window::new("Projection")
+        .open(&mut projection_window.open)
+        .resizable(false)
+        .show(egui_ctx.ctx(), |ui| {
+            ui.label("Extra dimensions:");
+            ui.horizontal(|ui| {
+                ui.radio_value(
+                    &mut settings.kind,
+                    ProjectionType::Perspective,
+                    "Perspective",
+                );
+                ui.radio_value(&mut settings.kind, ProjectionType::Orthogonal, "Orthogonal");
+            });
+
+            ui.label("Projected onto:");
+            ui.horizontal(|ui| {
+                ui.radio_value(
+                    &mut settings.basis,
+                    ProjectionBasis::Coordinates,
+                    "Coordinate axes",
+                );
+                ui.radio_value(
+                    &mut settings.basis,
+                    ProjectionBasis::Principal,
+                    "Principal axes",
+                );
+            });
+
+            // The distance of the eye only matters in perspective.
+            if !settings.is_orthogonal() {
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::DragValue::new(&mut settings.distance)
+                            .speed(0.01)
+                            .clamp_range(Projection::MIN_DISTANCE..=100.0),
+                    );
+                    ui.label("Distance of the eye, in radii");
+                });
+            }
+        });
+
+    // The settings are only written back if they change, so that polytopes
+    // aren't redrawn every frame.
+    if settings != *projection {
+        *projection = settings;
+    }
+}
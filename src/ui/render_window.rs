@@ -0,0 +1,222 @@
+//! Contains the window that renders the current view into an image file.
+
+use super::{main_window::WireframeMode, top_panel::FileDialogToken, visibility::VisibilityFilter};
+use crate::{
+    palette::{ColorScheme, ColorSettings},
+    projection::Projection,
+    render::{self, RenderObject, RenderOptions},
+};
+
+use bevy::{prelude::*, render::camera::PerspectiveProjection};
+use bevy_egui::{egui, EguiContext};
+use miratope_core::Polytope;
+use miratope_lang::poly::conc::NamedConcrete;
+
+/// The plugin in charge of rendering images.
+pub struct RenderWindowPlugin;
+
+impl Plugin for RenderWindowPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.insert_resource(RenderWindow::default())
+            // Windows must be the first thing shown.
+            .add_system(show_render_window.system().before("show_top_panel"));
+    }
+}
+
+/// The window with the options to render an image.
+#[derive(Default)]
+pub struct RenderWindow {
+    /// Whether the window is open.
+    open: bool,
+
+    /// The options with which the image is rendered.
+    options: RenderOptions,
+}
+
+impl RenderWindow {
+    /// Opens the window.
+    pub fn open(&mut self) {
+        self.open = true;
+    }
+}
+
+/// Gathers everything visible in the scene into objects to be rendered.
+fn render_objects(
+    polies: &Query<(
+        &NamedConcrete,
+        &GlobalTransform,
+        &Visible,
+        &ColorScheme,
+        &VisibilityFilter,
+        &Children,
+    )>,
+    wfs_vis: &Query<&Visible, Without<NamedConcrete>>,
+    projection_type: Projection,
+    color_settings: &ColorSettings,
+) -> Vec<RenderObject> {
+    let mut objects = Vec::new();
+
+    for (poly, transform, visible, colors, filter, children) in polies.iter() {
+        if poly.vertex_count() == 0 {
+            continue;
+        }
+
+        // The wireframe is visible if any of its children is.
+        let edges_visible = children
+            .iter()
+            .any(|child| matches!(wfs_vis.get(*child), Ok(vis) if vis.is_visible));
+
+        if !visible.is_visible && !edges_visible {
+            continue;
+        }
+
+        // The vertices of the wireframe are the first vertices of the mesh.
+        // Polytopes that can't be drawn are left out.
+        let (vertices, triangles, faces, edges) = match (
+            crate::mesh::triangles(&poly.con, projection_type),
+            crate::mesh::edges(&poly.con, projection_type),
+        ) {
+            (Ok((vertices, triangles, faces)), Ok((_, edges))) => {
+                (vertices, triangles, faces, edges)
+            }
+            _ => continue,
+        };
+
+        let shown = filter.shown(&poly.con);
+
+        let mut triangle_colors = Vec::new();
+        if visible.is_visible {
+            let face_colors = shown.filter(2, colors.face_colors(&poly.con, color_settings));
+            triangle_colors = faces.into_iter().map(|face| face_colors[face]).collect();
+        }
+
+        // Vertices are shown together with the wireframe.
+        let (edge_colors, vertex_colors) = if edges_visible {
+            (
+                shown.filter(1, colors.edge_colors(&poly.con, color_settings)),
+                shown.filter(0, colors.vertex_colors(&poly.con, color_settings)),
+            )
+        } else {
+            (Vec::new(), Vec::new())
+        };
+
+        objects.push(RenderObject {
+            model: transform.compute_matrix(),
+            vertices,
+            triangles,
+            edges,
+            triangle_colors,
+            edge_colors,
+            vertex_colors,
+        });
+    }
+
+    objects
+}
+
+/// The system that shows the render window, and renders the image if
+/// requested.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn show_render_window(
+    egui_ctx: Res<EguiContext>,
+    mut render_window: ResMut<RenderWindow>,
+    file_dialog: NonSend<FileDialogToken>,
+    background_color: Res<ClearColor>,
+    projection_type: Res<Projection>,
+    wireframe_mode: Res<WireframeMode>,
+    color_settings: Res<ColorSettings>,
+    cameras: Query<(&GlobalTransform, &PerspectiveProjection)>,
+    polies: Query<(
+        &NamedConcrete,
+        &GlobalTransform,
+        &Visible,
+        &ColorScheme,
+        &VisibilityFilter,
+        &Children,
+    )>,
+    wfs_vis: Query<&Visible, Without<NamedConcrete>>,
+) {
+    let RenderWindow { open, options } = &mut *render_window;
+    let mut render = false;
+
+    egui::Window::new("Render image")
+        .open(open)
+        .resizable(false)
+        .show(egui_ctx.ctx(), |ui| {
+            ui.horizontal(|ui| {
+                ui.add(egui::DragValue::new(&mut options.width).clamp_range(1..=16384));
+                ui.label("×");
+                ui.add(egui::DragValue::new(&mut options.height).clamp_range(1..=16384));
+                ui.label("Resolution");
+            });
+
+            ui.horizontal(|ui| {
+                ui.add(egui::DragValue::new(&mut options.supersampling).clamp_range(1..=8));
+                ui.label("Supersampling");
+            });
+
+            // Images that are too large to fit in memory can't be rendered.
+            let size_check = options.check_size();
+            if let Err(err) = &size_check {
+                ui.label(format!("Can't render: {}.", err));
+            }
+
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::DragValue::new(&mut options.edge_width)
+                        .speed(0.1)
+                        .clamp_range(0.0..=32.0),
+                );
+                ui.label("Edge width");
+            });
+
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::DragValue::new(&mut options.vertex_size)
+                        .speed(0.1)
+                        .clamp_range(0.0..=32.0),
+                );
+                ui.label("Vertex size");
+            });
+
+            ui.checkbox(&mut options.transparent, "Transparent background");
+
+            if ui.button("Render…").clicked() {
+                match size_check {
+                    Ok(()) => render = true,
+                    Err(err) => eprintln!("Render failed: {}", err),
+                }
+            }
+        });
+
+    if !render {
+        return;
+    }
+
+    let (camera, perspective) = match cameras.iter().next() {
+        Some(camera) => camera,
+        None => {
+            eprintln!("Render failed: no camera found.");
+            return;
+        }
+    };
+
+    if let Some(path) = file_dialog.save_image("render.png") {
+        options.background = background_color.0;
+        options.hidden_lines = wireframe_mode.is_hidden_line();
+        let objects = render_objects(&polies, &wfs_vis, *projection_type, &color_settings);
+
+        match render::render_to_path(
+            &path,
+            &objects,
+            camera.compute_matrix(),
+            perspective.fov,
+            perspective.near,
+            perspective.far,
+            options,
+        ) {
+            Ok(()) => println!("Image saved to {}.", path.display()),
+            Err(err) => eprintln!("Render failed: {}", err),
+        }
+    }
+}
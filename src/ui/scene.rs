@@ -0,0 +1,542 @@
+//! Manages the scene, i.e. the list of all polytopes on screen, each with its
+//! own transform, visibility and colors.
+//!
+//! Exactly one of the polytopes in the scene is marked as [`Active`]. This is
+//! the polytope that gets modified by every operation in the top panel.
+
+use super::{comparison::Comparison, top_panel::BakeSection, visibility::VisibilityFilter};
+use crate::{
+    no_cull_pipeline::PbrNoBackfaceBundle,
+    palette::{ColorMode, ColorScheme, ColorSettings, ElementColoring, Palette, METADATA_KEY},
+};
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+use miratope_core::Polytope;
+use miratope_lang::{poly::conc::NamedConcrete, SelectedLanguage};
+
+/// The plugin in charge of the scene.
+pub struct ScenePlugin;
+
+impl Plugin for ScenePlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.insert_resource(SceneWindow::default())
+            // Windows must be the first thing shown.
+            .add_system(show_scene_window.system().before("show_top_panel"))
+            .add_system(load_color_schemes.system())
+            .add_system(spawn_baked_sections.system());
+    }
+}
+
+/// A marker for the active polytope in the scene, on which all operations
+/// act.
+pub struct Active;
+
+/// Spawns a polytope into the scene, together with its wireframe. Returns the
+/// entity of the polytope, which isn't made active.
+///
+/// The meshes start out empty, and are built once the polytope is added to
+/// the scene.
+pub fn spawn_polytope(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    poly: NamedConcrete,
+    transform: Transform,
+    colors: ColorScheme,
+) -> Entity {
+    // The colors of the elements are stored in the vertices of the meshes.
+    let mesh_material = materials.add(StandardMaterial {
+        base_color: Color::WHITE,
+        metallic: 0.2,
+        ..Default::default()
+    });
+    let wf_material = materials.add(Color::WHITE.into());
+
+    commands
+        .spawn()
+        // Mesh
+        .insert_bundle(PbrNoBackfaceBundle {
+            mesh: meshes.add(crate::mesh::empty_mesh()),
+            material: mesh_material,
+            transform,
+            ..Default::default()
+        })
+        // Wireframe
+        .with_children(|cb| {
+            cb.spawn().insert_bundle(PbrNoBackfaceBundle {
+                mesh: meshes.add(crate::mesh::empty_mesh()),
+                material: wf_material,
+                ..Default::default()
+            });
+        })
+        // Polytope
+        .insert(poly)
+        .insert(colors)
+        .insert(VisibilityFilter::default())
+        .id()
+}
+
+/// Shows a color picker for some sRGB components.
+fn color_edit(ui: &mut egui::Ui, color: &mut [u8; 3]) {
+    let [r, g, b] = *color;
+    let mut egui_color = egui::Color32::from_rgb(r, g, b);
+    egui::color_picker::color_edit_button_srgba(
+        ui,
+        &mut egui_color,
+        egui::color_picker::Alpha::Opaque,
+    );
+
+    *color = [egui_color.r(), egui_color.g(), egui_color.b()];
+}
+
+/// Shows the controls for the coloring of the elements of a given rank.
+fn coloring_edit(
+    ui: &mut egui::Ui,
+    id: (Entity, &str),
+    coloring: &mut ElementColoring,
+    palette_names: &[String],
+) {
+    ui.horizontal(|ui| {
+        egui::ComboBox::from_id_source(id)
+            .selected_text(coloring.mode.name())
+            .show_ui(ui, |ui| {
+                for &mode in ColorMode::ALL.iter() {
+                    ui.selectable_value(&mut coloring.mode, mode, mode.name());
+                }
+            });
+
+        if coloring.mode == ColorMode::Uniform {
+            color_edit(ui, &mut coloring.color);
+        } else {
+            egui::ComboBox::from_id_source((id, "palette"))
+                .selected_text(coloring.palette.clone())
+                .show_ui(ui, |ui| {
+                    for name in palette_names {
+                        ui.selectable_value(&mut coloring.palette, name.clone(), name);
+                    }
+                });
+        }
+
+        ui.label(id.1);
+    });
+}
+
+/// Shows the editor for the palettes defined by the user.
+fn palette_editor(ui: &mut egui::Ui, palettes: &mut Vec<Palette>) {
+    let mut removed = None;
+
+    for (idx, palette) in palettes.iter_mut().enumerate() {
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(&mut palette.name).desired_width(80.0));
+
+            for color in &mut palette.colors {
+                color_edit(ui, color);
+            }
+
+            if ui.button("+").clicked() {
+                let last = palette.colors.last().copied().unwrap_or([255, 255, 255]);
+                palette.colors.push(last);
+            }
+
+            if ui.button("−").clicked() {
+                palette.colors.pop();
+            }
+
+            if ui.button("Delete").clicked() {
+                removed = Some(idx);
+            }
+        });
+    }
+
+    if let Some(idx) = removed {
+        palettes.remove(idx);
+    }
+
+    if ui.button("New palette").clicked() {
+        palettes.push(Palette::new(
+            format!("Custom {}", palettes.len() + 1),
+            vec![[255, 255, 255], [0, 0, 0]],
+        ));
+    }
+}
+
+/// An action to be performed on the scene after showing the scene window.
+enum SceneAction {
+    /// Nothing happens.
+    None,
+
+    /// Makes a polytope active.
+    Activate(Entity),
+
+    /// Adds a copy of a polytope into the scene.
+    Duplicate(Entity),
+
+    /// Removes a polytope from the scene.
+    Remove(Entity),
+
+    /// Adds the compound of two polytopes into the scene.
+    Compound(Entity, Entity),
+
+    /// Adds the duoprism of two polytopes into the scene.
+    Duoprism(Entity, Entity),
+
+    /// Compares two polytopes in a split view.
+    Compare(Entity, Entity),
+
+    /// Adds the dual of a polytope into the scene, and compares them side by
+    /// side.
+    CompareDual(Entity),
+}
+
+/// The window that lists all polytopes in the scene.
+#[derive(Default)]
+pub struct SceneWindow {
+    /// Whether the window is open.
+    open: bool,
+
+    /// The polytopes selected to be combined, at most two.
+    selected: Vec<Entity>,
+}
+
+impl SceneWindow {
+    /// Opens the window.
+    pub fn open(&mut self) {
+        self.open = true;
+    }
+
+    /// Selects or deselects a polytope. If more than two polytopes are
+    /// selected, the oldest selection is dropped.
+    fn toggle(&mut self, entity: Entity) {
+        if let Some(idx) = self.selected.iter().position(|&e| e == entity) {
+            self.selected.remove(idx);
+        } else {
+            self.selected.push(entity);
+            if self.selected.len() > 2 {
+                self.selected.remove(0);
+            }
+        }
+    }
+}
+
+/// The system that shows the scene window and applies any changes made from
+/// it.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn show_scene_window(
+    mut commands: Commands,
+    egui_ctx: Res<EguiContext>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut scene_window: ResMut<SceneWindow>,
+    mut comparison: ResMut<Comparison>,
+    mut color_settings: ResMut<ColorSettings>,
+    selected_language: Res<SelectedLanguage>,
+    mut polies: Query<(
+        Entity,
+        &NamedConcrete,
+        &mut Transform,
+        &mut Visible,
+        &mut ColorScheme,
+        &Children,
+        Option<&Active>,
+    )>,
+    mut wfs_vis: Query<&mut Visible, Without<NamedConcrete>>,
+) {
+    let mut action = SceneAction::None;
+    let mut open = scene_window.open;
+
+    // Forgets about any selected polytopes that were removed.
+    scene_window.selected.retain(|&e| polies.get_mut(e).is_ok());
+
+    // The settings are only written back if they change, so that polytopes
+    // aren't redrawn every frame.
+    let mut new_settings = color_settings.clone();
+    let palette_names = new_settings.palette_names();
+
+    egui::Window::new("Scene")
+        .open(&mut open)
+        .resizable(true)
+        .show(egui_ctx.ctx(), |ui| {
+            let mut entries: Vec<_> = polies.iter_mut().collect();
+            entries.sort_by_key(|entry| entry.0);
+
+            for (entity, poly, mut transform, mut visible, mut colors, children, active) in entries
+            {
+                ui.horizontal(|ui| {
+                    // Makes the polytope active.
+                    if ui
+                        .selectable_label(active.is_some(), selected_language.parse(&poly.name))
+                        .clicked()
+                    {
+                        action = SceneAction::Activate(entity);
+                    }
+
+                    // Toggles the visibility of the polytope and its wireframe.
+                    let mut is_visible = visible.is_visible;
+                    if ui.checkbox(&mut is_visible, "Visible").clicked() {
+                        visible.is_visible = is_visible;
+
+                        for child in children.iter() {
+                            if let Ok(mut wf_vis) = wfs_vis.get_mut(*child) {
+                                wf_vis.is_visible = is_visible;
+                            }
+                        }
+                    }
+
+                    // Selects the polytope to combine it with another.
+                    let mut is_selected = scene_window.selected.contains(&entity);
+                    if ui.checkbox(&mut is_selected, "Select").clicked() {
+                        scene_window.toggle(entity);
+                    }
+                });
+
+                egui::CollapsingHeader::new("Options")
+                    .id_source(entity)
+                    .show(ui, |ui| {
+                        // The position and scale of the polytope.
+                        ui.horizontal(|ui| {
+                            let mut translation = transform.translation;
+                            ui.add(egui::DragValue::new(&mut translation.x).speed(0.01));
+                            ui.add(egui::DragValue::new(&mut translation.y).speed(0.01));
+                            ui.add(egui::DragValue::new(&mut translation.z).speed(0.01));
+                            ui.label("Position");
+
+                            if translation != transform.translation {
+                                transform.translation = translation;
+                            }
+                        });
+
+                        ui.horizontal(|ui| {
+                            let mut scale = transform.scale.x;
+                            ui.add(
+                                egui::DragValue::new(&mut scale)
+                                    .speed(0.01)
+                                    .clamp_range(0.01..=100.0),
+                            );
+                            ui.label("Scale");
+
+                            if (scale - transform.scale.x).abs() > f32::EPSILON {
+                                transform.scale = Vec3::splat(scale);
+                            }
+                        });
+
+                        // The colors of the polytope.
+                        let mut new_colors = colors.clone();
+                        coloring_edit(ui, (entity, "Faces"), &mut new_colors.faces, &palette_names);
+                        coloring_edit(ui, (entity, "Edges"), &mut new_colors.edges, &palette_names);
+                        coloring_edit(
+                            ui,
+                            (entity, "Vertices"),
+                            &mut new_colors.vertices,
+                            &palette_names,
+                        );
+
+                        if new_colors != *colors {
+                            *colors = new_colors;
+                        }
+
+                        if ui.button("Use colors for new polytopes").clicked() {
+                            new_settings.default_scheme = colors.clone();
+                        }
+
+                        ui.horizontal(|ui| {
+                            if ui.button("Duplicate").clicked() {
+                                action = SceneAction::Duplicate(entity);
+                            }
+
+                            if ui.button("Remove").clicked() {
+                                action = SceneAction::Remove(entity);
+                            }
+
+                            if ui.button("Compare with dual").clicked() {
+                                action = SceneAction::CompareDual(entity);
+                            }
+                        });
+                    });
+
+                ui.separator();
+            }
+
+            // Combines two selected polytopes.
+            ui.horizontal(|ui| {
+                if let [p, q] = scene_window.selected[..] {
+                    if ui.button("Compound").clicked() {
+                        action = SceneAction::Compound(p, q);
+                    }
+
+                    if ui.button("Duoprism").clicked() {
+                        action = SceneAction::Duoprism(p, q);
+                    }
+
+                    if ui.button("Compare").clicked() {
+                        action = SceneAction::Compare(p, q);
+                    }
+                } else {
+                    ui.label("Select two polytopes to combine them.");
+                }
+            });
+
+            ui.separator();
+            egui::CollapsingHeader::new("Palettes").show(ui, |ui| {
+                palette_editor(ui, &mut new_settings.palettes);
+            });
+        });
+
+    scene_window.open = open;
+
+    if new_settings.palettes != color_settings.palettes
+        || new_settings.default_scheme != color_settings.default_scheme
+    {
+        *color_settings = new_settings;
+    }
+
+    // Spawns a new polytope.
+    let mut spawn = |poly: NamedConcrete, transform: Transform, colors: ColorScheme| {
+        spawn_polytope(
+            &mut commands,
+            &mut meshes,
+            &mut materials,
+            poly,
+            transform,
+            colors,
+        )
+    };
+
+    // Clones a polytope in the scene.
+    let mut get = |e| {
+        polies
+            .get_mut(e)
+            .ok()
+            .map(|(_, poly, transform, _, colors, ..)| (poly.clone(), *transform, colors.clone()))
+    };
+
+    // Polytopes are added as new entries of the scene, and made active.
+    action = match action {
+        SceneAction::Duplicate(e) => match get(e) {
+            Some((poly, transform, colors)) => {
+                SceneAction::Activate(spawn(poly, transform, colors))
+            }
+            None => SceneAction::None,
+        },
+
+        SceneAction::Compound(p, q) => match (get(p), get(q)) {
+            (Some((mut p, ..)), Some((q, ..))) => {
+                if p.rank() == q.rank() {
+                    p.comp_append(q);
+                    let colors = color_settings.default_scheme.clone();
+                    SceneAction::Activate(spawn(p, Default::default(), colors))
+                } else {
+                    eprintln!("Compound failed: polytopes have different ranks.");
+                    SceneAction::None
+                }
+            }
+            _ => SceneAction::None,
+        },
+
+        SceneAction::Duoprism(p, q) => match (get(p), get(q)) {
+            (Some((p, ..)), Some((q, ..))) => SceneAction::Activate(spawn(
+                NamedConcrete::duoprism(&p, &q),
+                Default::default(),
+                color_settings.default_scheme.clone(),
+            )),
+            _ => SceneAction::None,
+        },
+
+        SceneAction::CompareDual(e) => match get(e) {
+            Some((poly, _, colors)) => match poly.try_dual() {
+                Ok(dual) => SceneAction::Compare(e, spawn(dual, Default::default(), colors)),
+                Err(err) => {
+                    eprintln!("Dual failed: {}", err);
+                    SceneAction::None
+                }
+            },
+            None => SceneAction::None,
+        },
+
+        action => action,
+    };
+
+    match action {
+        // Moves the marker to the new active polytope.
+        SceneAction::Activate(entity) => {
+            for (other, .., active) in polies.iter_mut() {
+                if active.is_some() {
+                    commands.entity(other).remove::<Active>();
+                }
+            }
+
+            commands.entity(entity).insert(Active);
+        }
+
+        // Removes a polytope, unless it's the last one.
+        SceneAction::Remove(entity) => {
+            let other = polies
+                .iter_mut()
+                .map(|entry| entry.0)
+                .find(|&e| e != entity);
+
+            if let Some(other) = other {
+                let was_active = matches!(polies.get_mut(entity), Ok((.., Some(_))));
+                commands.entity(entity).despawn_recursive();
+
+                if was_active {
+                    commands.entity(other).insert(Active);
+                }
+            } else {
+                eprintln!("Remove failed: the scene must have at least one polytope.");
+            }
+        }
+
+        // Starts the comparison mode.
+        SceneAction::Compare(p, q) => comparison.start(p, q),
+
+        _ => {}
+    }
+}
+
+/// Applies the color schemes stored in the metadata of any polytopes that
+/// were loaded or changed, and removes them from the metadata. They get stored
+/// again whenever the polytope is saved.
+pub fn load_color_schemes(
+    mut polies: Query<(&mut NamedConcrete, &mut ColorScheme), Changed<NamedConcrete>>,
+) {
+    for (mut poly, mut colors) in polies.iter_mut() {
+        let has_scheme = matches!(
+            poly.con.metadata(),
+            Some(metadata) if metadata.other.contains_key(METADATA_KEY)
+        );
+
+        if has_scheme {
+            if let Some(new_colors) = ColorScheme::take_metadata(&mut poly.con) {
+                *colors = new_colors;
+            }
+        }
+    }
+}
+
+/// Adds the cross-sections that were baked into the scene, with the same
+/// transform and colors as the active polytope. The active polytope stays the
+/// same, so that the cross-section view can keep going.
+pub fn spawn_baked_sections(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut events: EventReader<BakeSection>,
+    active: Query<(&Transform, &ColorScheme), With<Active>>,
+) {
+    for BakeSection(poly) in events.iter() {
+        let (transform, colors) = match active.iter().next() {
+            Some((transform, colors)) => (*transform, colors.clone()),
+            None => Default::default(),
+        };
+
+        spawn_polytope(
+            &mut commands,
+            &mut meshes,
+            &mut materials,
+            poly.clone(),
+            transform,
+            colors,
+        );
+    }
+}
@@ -0,0 +1,357 @@
+//! Draws the vertices of polytopes as spheres and their edges as tubes.
+//!
+//! Every sphere and every tube is its own entity, so each of them is a separate
+//! draw call. They do share a single sphere mesh and a single tube mesh, with
+//! their own transforms, so that rebuilding them only recomputes transforms,
+//! and the entities holding them get reused between rebuilds. Since this
+//! doesn't scale to huge polytopes, those are only drawn with lines.
+
+use std::collections::HashMap;
+
+use super::visibility::VisibilityFilter;
+use crate::{
+    no_cull_pipeline::PbrNoBackfaceBundle,
+    palette::{ColorScheme, ColorSettings},
+    projection::Projection,
+};
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+use miratope_core::{abs::rank::Rank, Polytope};
+use miratope_lang::poly::conc::NamedConcrete;
+use vec_like::VecLike;
+
+/// The number of sides of the tubes drawn for the edges.
+const TUBE_SEGMENTS: usize = 12;
+
+/// The plugin in charge of drawing vertices as spheres and edges as tubes.
+pub struct SpheresAndTubesPlugin;
+
+impl Plugin for SpheresAndTubesPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.insert_resource(SpheresAndTubesSettings::default())
+            .init_resource::<SharedMeshes>()
+            .insert_resource(SpheresAndTubesWindow::default())
+            // Windows must be the first thing shown.
+            .add_system(
+                show_spheres_and_tubes_window
+                    .system()
+                    .before("show_top_panel"),
+            )
+            .add_system_to_stage(CoreStage::PostUpdate, update_spheres_and_tubes.system());
+    }
+}
+
+/// The settings for drawing vertices as spheres and edges as tubes.
+#[derive(Clone, Copy, PartialEq)]
+pub struct SpheresAndTubesSettings {
+    /// Whether vertices and edges are drawn as spheres and tubes.
+    pub enabled: bool,
+
+    /// The radius of the spheres.
+    pub vertex_radius: f32,
+
+    /// The radius of the tubes.
+    pub edge_radius: f32,
+
+    /// The number of vertices and edges above which a polytope only gets
+    /// drawn with lines.
+    pub max_elements: usize,
+}
+
+impl Default for SpheresAndTubesSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            vertex_radius: 0.03,
+            edge_radius: 0.015,
+            max_elements: 10_000,
+        }
+    }
+}
+
+/// The meshes shared by every sphere and every tube.
+pub struct SharedMeshes {
+    /// The mesh of the spheres.
+    sphere: Handle<Mesh>,
+
+    /// The mesh of the tubes.
+    tube: Handle<Mesh>,
+}
+
+impl FromWorld for SharedMeshes {
+    fn from_world(world: &mut World) -> Self {
+        let mut meshes = world.get_resource_mut::<Assets<Mesh>>().unwrap();
+
+        Self {
+            sphere: meshes.add(crate::mesh::sphere()),
+            tube: meshes.add(crate::mesh::tube(TUBE_SEGMENTS)),
+        }
+    }
+}
+
+/// A marker for the spheres and tubes drawn for the elements of a polytope,
+/// which are children of the polytope.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum DrawnElement {
+    /// A sphere drawn for a vertex.
+    Vertex,
+
+    /// A tube drawn for an edge.
+    Edge,
+}
+
+/// The materials used by the spheres and tubes, one for every color.
+#[derive(Default)]
+pub struct MaterialCache(HashMap<[u32; 4], Handle<StandardMaterial>>);
+
+impl MaterialCache {
+    /// Returns the material with a given color, adding it if it doesn't exist.
+    fn get(
+        &mut self,
+        materials: &mut Assets<StandardMaterial>,
+        color: Color,
+    ) -> Handle<StandardMaterial> {
+        let key = color.as_rgba_f32().map(f32::to_bits);
+
+        self.0
+            .entry(key)
+            .or_insert_with(|| {
+                materials.add(StandardMaterial {
+                    base_color: color,
+                    metallic: 0.2,
+                    ..Default::default()
+                })
+            })
+            .clone()
+    }
+}
+
+/// Returns the transform of the sphere drawn for a vertex.
+fn vertex_transform(v: Vec3, radius: f32) -> Transform {
+    Transform::from_translation(v).with_scale(Vec3::splat(radius))
+}
+
+/// Returns the transform of the tube drawn for an edge, or `None` if the edge
+/// has no length.
+fn edge_transform(a: Vec3, b: Vec3, radius: f32) -> Option<Transform> {
+    let dir = b - a;
+    let len = dir.length();
+
+    if len > f32::EPSILON {
+        Some(Transform {
+            translation: a,
+            rotation: Quat::from_rotation_arc(Vec3::Y, dir / len),
+            scale: Vec3::new(radius, len, radius),
+        })
+    } else {
+        None
+    }
+}
+
+/// Rebuilds the spheres and tubes of the polytopes that change, or of every
+/// polytope if the settings, the palettes or the projection type change.
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+pub fn update_spheres_and_tubes(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut material_cache: Local<MaterialCache>,
+    shared_meshes: Res<SharedMeshes>,
+    settings: Res<SpheresAndTubesSettings>,
+    orthogonal: Res<Projection>,
+    color_settings: Res<ColorSettings>,
+
+    polies: Query<(
+        Entity,
+        &NamedConcrete,
+        &ColorScheme,
+        &VisibilityFilter,
+        Option<&Children>,
+    )>,
+    changed: Query<
+        Entity,
+        Or<(
+            Changed<NamedConcrete>,
+            Changed<ColorScheme>,
+            Changed<VisibilityFilter>,
+        )>,
+    >,
+    mut drawn: Query<(&DrawnElement, &mut Transform, &mut Handle<StandardMaterial>)>,
+) {
+    let redraw_all =
+        settings.is_changed() || orthogonal.is_changed() || color_settings.is_changed();
+
+    for (entity, poly, colors, filter, children) in polies.iter() {
+        if !redraw_all && changed.get(entity).is_err() {
+            continue;
+        }
+
+        // The spheres and tubes that can be reused.
+        let mut vertex_entities = Vec::new();
+        let mut edge_entities = Vec::new();
+        for &child in children.iter().flat_map(|children| children.iter()) {
+            match drawn.get(child) {
+                Ok((DrawnElement::Vertex, ..)) => vertex_entities.push(child),
+                Ok((DrawnElement::Edge, ..)) => edge_entities.push(child),
+                Err(_) => {}
+            }
+        }
+
+        // The transforms and colors of the new spheres and tubes.
+        let mut spheres = Vec::new();
+        let mut tubes = Vec::new();
+
+        // Polytopes that can't be drawn only get their spheres and tubes removed.
+        let element_count = poly.vertex_count() + poly.el_count(Rank::new(1));
+        if settings.enabled
+            && element_count <= settings.max_elements
+            && crate::mesh::check(&poly.con).is_ok()
+        {
+            let shown = filter.shown(&poly.con);
+            let vertex_colors = shown.filter(0, colors.vertex_colors(&poly.con, &color_settings));
+            let edge_colors = shown.filter(1, colors.edge_colors(&poly.con, &color_settings));
+
+            let vertices: Vec<Vec3> =
+                crate::mesh::vertex_coords(&poly.con, poly.con.vertices.iter(), *orthogonal)
+                    .into_iter()
+                    .map(Vec3::from)
+                    .collect();
+
+            for (&v, color) in vertices.iter().zip(vertex_colors) {
+                if let Some(color) = color {
+                    spheres.push((vertex_transform(v, settings.vertex_radius), color));
+                }
+            }
+
+            if let Some(edges) = poly.abs.ranks.get(Rank::new(1)) {
+                for (edge, color) in edges.iter().zip(edge_colors) {
+                    if let (Some(color), Some(transform)) = (
+                        color,
+                        edge_transform(
+                            vertices[edge.subs[0]],
+                            vertices[edge.subs[1]],
+                            settings.edge_radius,
+                        ),
+                    ) {
+                        tubes.push((transform, color));
+                    }
+                }
+            }
+        }
+
+        for (kind, mesh, entities, new_shapes) in [
+            (
+                DrawnElement::Vertex,
+                &shared_meshes.sphere,
+                vertex_entities,
+                spheres,
+            ),
+            (
+                DrawnElement::Edge,
+                &shared_meshes.tube,
+                edge_entities,
+                tubes,
+            ),
+        ] {
+            // Removes the spheres or tubes that are left over.
+            for &child in entities.iter().skip(new_shapes.len()) {
+                commands.entity(child).despawn_recursive();
+            }
+
+            let mut new_children = Vec::new();
+            for (idx, (transform, color)) in new_shapes.into_iter().enumerate() {
+                let material = material_cache.get(&mut materials, color);
+
+                // Reuses an existing entity if possible.
+                if let Some(&child) = entities.get(idx) {
+                    let (_, mut old_transform, mut old_material) = drawn.get_mut(child).unwrap();
+                    *old_transform = transform;
+                    *old_material = material;
+                } else {
+                    new_children.push(
+                        commands
+                            .spawn()
+                            .insert_bundle(PbrNoBackfaceBundle {
+                                mesh: mesh.clone(),
+                                material,
+                                transform,
+                                ..Default::default()
+                            })
+                            .insert(kind)
+                            .id(),
+                    );
+                }
+            }
+
+            commands.entity(entity).push_children(&new_children);
+        }
+    }
+}
+
+/// The window with the settings for drawing vertices as spheres and edges as
+/// tubes.
+#[derive(Default)]
+pub struct SpheresAndTubesWindow {
+    /// Whether the window is open.
+    open: bool,
+}
+
+impl SpheresAndTubesWindow {
+    /// Opens the window.
+    pub fn open(&mut self) {
+        self.open = true;
+    }
+}
+
+/// The system that shows the window with the settings for drawing vertices as
+/// spheres and edges as tubes.
+pub fn show_spheres_and_tubes_window(
+    egui_ctx: Res<EguiContext>,
+    mut spheres_and_tubes_window: ResMut<SpheresAndTubesWindow>,
+    mut spheres_and_tubes_settings: ResMut<SpheresAndTubesSettings>,
+) {
+    let mut settings = *spheres_and_tubes_settings;
+
+    egui::Window::new("Spheres and tubes")
+        .open(&mut spheres_and_tubes_window.open)
+        .resizable(false)
+        .show(egui_ctx.ctx(), |ui| {
+            ui.checkbox(
+                &mut settings.enabled,
+                "Draw vertices as spheres and edges as tubes",
+            );
+
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::DragValue::new(&mut settings.vertex_radius)
+                        .speed(0.001)
+                        .clamp_range(0.0..=10.0),
+                );
+                ui.label("Vertex radius");
+            });
+
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::DragValue::new(&mut settings.edge_radius)
+                        .speed(0.001)
+                        .clamp_range(0.0..=10.0),
+                );
+                ui.label("Edge radius");
+            });
+
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::DragValue::new(&mut settings.max_elements)
+                        .speed(1000.0)
+                        .clamp_range(0..=usize::MAX),
+                );
+                ui.label("Maximum vertices and edges");
+            });
+        });
+
+    // The settings are only written back if they change, so that polytopes
+    // aren't redrawn every frame.
+    if settings != *spheres_and_tubes_settings {
+        *spheres_and_tubes_settings = settings;
+    }
+}
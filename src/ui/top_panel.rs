@@ -2,7 +2,24 @@
 
 use std::{marker::PhantomData, path::PathBuf};
 
-use super::{camera::ProjectionType, memory::Memory, operations::*, UnitPointWidget};
+use super::{
+    diagnostics::Diagnostics,
+    elements::ElementsWindow,
+    import::{load_path, PasteWindow},
+    labels::LabelsWindow,
+    lod_window::LodWindow,
+    main_window::{window_title, WireframeMode},
+    memory::Memory,
+    operations::*,
+    projection_window::ProjectionWindow,
+    render_window::RenderWindow,
+    scene::{Active, SceneWindow},
+    spheres_and_tubes::SpheresAndTubesWindow,
+    viewpoints::ViewpointsWindow,
+    visibility::VisibilityWindow,
+    UnitPointWidget,
+};
+use crate::{palette::ColorScheme, projection::Projection};
 
 use bevy::prelude::*;
 use bevy_egui::{
@@ -10,8 +27,9 @@ use bevy_egui::{
     EguiContext,
 };
 use miratope_core::{
-    conc::{file::FromFile, ConcretePolytope},
+    conc::{metadata::Metadata, Concrete, ConcretePolytope},
     geometry::{Hyperplane, Point, Vector},
+    limits::SizeLimit,
     Float, Polytope,
 };
 use miratope_lang::{
@@ -30,6 +48,7 @@ impl Plugin for TopPanelPlugin {
             .insert_resource(Memory::default())
             .insert_resource(SectionDirection::default())
             .insert_resource(SectionState::default())
+            .add_event::<BakeSection>()
             .insert_non_send_resource(FileDialogToken::default())
             .add_system(file_dialog.system())
             // Windows must be the first thing shown.
@@ -71,6 +90,44 @@ impl SectionState {
     pub fn close(&mut self) {
         *self = Self::Inactive;
     }
+
+    /// Toggles the view. When it's made inactive, the original polytope is
+    /// restored.
+    pub fn toggle(&mut self, p: &mut NamedConcrete, section_direction: &mut SectionDirection) {
+        match self {
+            // The view is active, but will be inactivated.
+            Self::Active {
+                original_polytope, ..
+            } => {
+                *p = original_polytope.clone();
+                *self = Self::Inactive;
+            }
+
+            // The view is inactive, but will be activated.
+            Self::Inactive => {
+                p.flatten();
+
+                // The default direction is in the last coordinate axis.
+                let dim = p.dim_or();
+                let mut direction = Vector::zeros(dim);
+                if dim > 0 {
+                    direction[dim - 1] = 1.0;
+                }
+
+                let minmax = p.minmax(&direction).unwrap_or((-1.0, 1.0));
+                let original_polytope = p.clone();
+
+                *self = Self::Active {
+                    original_polytope,
+                    minmax,
+                    hyperplane_pos: (minmax.0 + minmax.1) / 2.0,
+                    flatten: true,
+                    lock: false,
+                };
+                section_direction.0 = direction;
+            }
+        }
+    }
 }
 
 impl Default for SectionState {
@@ -79,6 +136,10 @@ impl Default for SectionState {
     }
 }
 
+/// An event asking for the current cross-section to be added to the scene as a
+/// polytope of its own.
+pub struct BakeSection(pub NamedConcrete);
+
 /// Stores the direction in which the cross-sections are taken.
 pub struct SectionDirection(Vector);
 
@@ -106,13 +167,23 @@ impl FileDialogToken {
 
     /// Returns the path given by an open file dialog.
     fn pick_file(&self) -> Option<PathBuf> {
-        Self::new_file_dialog().pick_file()
+        Self::new_file_dialog()
+            .add_filter("OBJ file", &["obj"])
+            .pick_file()
     }
 
     /// Returns the path given by a save file dialog.
     fn save_file(&self, name: &str) -> Option<PathBuf> {
         Self::new_file_dialog().set_file_name(name).save_file()
     }
+
+    /// Returns the path given by a save file dialog for an image.
+    pub fn save_image(&self, name: &str) -> Option<PathBuf> {
+        FileDialog::new()
+            .add_filter("PNG file", &["png"])
+            .set_file_name(name)
+            .save_file()
+    }
 }
 
 /// The type of file dialog we're showing.
@@ -125,6 +196,10 @@ enum FileDialogMode {
 
     /// We're showing a file dialog to save a file.
     Save,
+
+    /// We're showing a file dialog to save a polytope other than the active
+    /// one, such as one of its elements.
+    Export(Box<Concrete>),
 }
 
 /// The file dialog is disabled by default.
@@ -156,39 +231,54 @@ impl FileDialogState {
         self.mode = FileDialogMode::Save;
         self.name = Some(name);
     }
+
+    /// Changes the file dialog mode to [`FileDialogMode::Export`], and loads
+    /// the polytope to save and the name of the file.
+    pub fn export(&mut self, poly: Concrete, name: String) {
+        self.mode = FileDialogMode::Export(Box::new(poly));
+        self.name = Some(name);
+    }
 }
 
 /// The system in charge of showing the file dialog.
 pub fn file_dialog(
-    mut query: Query<&mut NamedConcrete>,
+    mut query: Query<(&mut NamedConcrete, &ColorScheme), With<Active>>,
     file_dialog_state: Res<FileDialogState>,
     file_dialog: NonSend<FileDialogToken>,
 ) {
     if file_dialog_state.is_changed() {
-        match file_dialog_state.mode {
+        match &file_dialog_state.mode {
             // We want to save a file.
             FileDialogMode::Save => {
                 if let Some(path) = file_dialog.save_file(file_dialog_state.name.as_ref().unwrap())
                 {
-                    if let Some(p) = query.iter_mut().next() {
-                        if let Err(err) = p.con().to_path(&path, Default::default()) {
+                    if let Some((p, colors)) = query.iter_mut().next() {
+                        // The colors get saved together with the polytope.
+                        let mut con = p.con().clone();
+                        colors.write_metadata(&mut con);
+
+                        if let Err(err) = con.to_path(&path, Default::default()) {
                             eprintln!("File saving failed: {}", err);
                         }
                     }
                 }
             }
 
+            // We want to save a polytope that's not on screen.
+            FileDialogMode::Export(poly) => {
+                if let Some(path) = file_dialog.save_file(file_dialog_state.name.as_ref().unwrap())
+                {
+                    if let Err(err) = poly.to_path(&path, Default::default()) {
+                        eprintln!("File saving failed: {}", err);
+                    }
+                }
+            }
+
             // We want to open a file.
             FileDialogMode::Open => {
                 if let Some(path) = file_dialog.pick_file() {
-                    if let Some(mut p) = query.iter_mut().next() {
-                        match NamedConcrete::from_path(&path) {
-                            Ok(q) => {
-                                *p = q;
-                                p.recenter();
-                            }
-                            Err(err) => eprintln!("File open failed: {}", err),
-                        }
+                    if let Some((mut p, _)) = query.iter_mut().next() {
+                        load_path(&mut p, &path);
                     }
                 }
             }
@@ -217,24 +307,50 @@ pub type EguiWindows<'a> = (
     ResMut<'a, DuocombWindow>,
 );
 
+/// All of the windows that don't act on the polytope directly, as mutable
+/// resources.
+pub type ToolWindows<'a> = (
+    ResMut<'a, PasteWindow>,
+    ResMut<'a, SceneWindow>,
+    ResMut<'a, RenderWindow>,
+    ResMut<'a, VisibilityWindow>,
+    ResMut<'a, LodWindow>,
+    ResMut<'a, SpheresAndTubesWindow>,
+    ResMut<'a, LabelsWindow>,
+    ResMut<'a, ViewpointsWindow>,
+    ResMut<'a, Diagnostics>,
+    ResMut<'a, ProjectionWindow>,
+    ResMut<'a, ElementsWindow>,
+);
+
+/// All of the resources controlled by the preferences menu.
+pub type Preferences<'a> = (
+    ResMut<'a, SelectedLanguage>,
+    ResMut<'a, egui::Visuals>,
+    ResMut<'a, SizeLimit>,
+);
+
 /// The system that shows the top panel.
 #[allow(clippy::too_many_arguments)]
 pub fn show_top_panel(
     // Info about the application state.
     egui_ctx: Res<EguiContext>,
-    mut query: Query<&mut NamedConcrete>,
+    mut query: Query<&mut NamedConcrete, With<Active>>,
     mut windows: ResMut<Windows>,
     keyboard: Res<Input<KeyCode>>,
 
     // The Miratope resources controlled by the top panel.
     mut section_state: ResMut<SectionState>,
     mut section_direction: ResMut<SectionDirection>,
+    bake_events: EventWriter<BakeSection>,
     mut file_dialog_state: ResMut<FileDialogState>,
-    mut projection_type: ResMut<ProjectionType>,
+    mut projection_type: ResMut<Projection>,
+    mut wireframe_mode: ResMut<WireframeMode>,
     mut memory: ResMut<Memory>,
     mut background_color: ResMut<ClearColor>,
-    mut selected_language: ResMut<SelectedLanguage>,
-    mut visuals: ResMut<egui::Visuals>,
+
+    // The resources controlled by the preferences menu.
+    (mut selected_language, mut visuals, mut size_limit): Preferences,
 
     // The different windows that can be shown.
     (
@@ -248,6 +364,21 @@ pub fn show_top_panel(
         mut duotegum_window,
         mut duocomb_window,
     ): EguiWindows,
+
+    // The windows that don't act on the polytope directly.
+    (
+        mut paste_window,
+        mut scene_window,
+        mut render_window,
+        mut visibility_window,
+        mut lod_window,
+        mut spheres_and_tubes_window,
+        mut labels_window,
+        mut viewpoints_window,
+        mut diagnostics,
+        mut projection_window,
+        mut elements_window,
+    ): ToolWindows,
 ) {
     // The top bar.
     egui::TopBottomPanel::top("top_panel").show(egui_ctx.ctx(), |ui| {
@@ -259,6 +390,11 @@ pub fn show_top_panel(
                     file_dialog_state.open();
                 }
 
+                // Builds a polytope from pasted text.
+                if ui.button("Paste").clicked() {
+                    paste_window.open();
+                }
+
                 // Saves a file.
                 if ui.button("Save").clicked() {
                     if let Some(p) = query.iter_mut().next() {
@@ -268,6 +404,13 @@ pub fn show_top_panel(
 
                 ui.separator();
 
+                // Renders the current view into an image.
+                if ui.button("Render image…").clicked() {
+                    render_window.open();
+                }
+
+                ui.separator();
+
                 // Quits the application.
                 if ui.button("Exit").clicked() {
                     std::process::exit(0);
@@ -276,15 +419,61 @@ pub fn show_top_panel(
 
             // Configures the view.
             menu::menu(ui, "View", |ui| {
+                // Shows the list of polytopes on screen.
+                if ui.button("Scene").clicked() {
+                    scene_window.open();
+                }
+
+                // Moves the camera to saved or standard positions.
+                if ui.button("Viewpoints").clicked() {
+                    viewpoints_window.open();
+                }
+
+                // Hides some of the elements of the active polytope.
+                if ui.button("Visibility").clicked() {
+                    visibility_window.open();
+                }
+
+                // Labels the elements of the active polytope.
+                if ui.button("Labels").clicked() {
+                    labels_window.open();
+                }
+
+                // Configures how huge polytopes are drawn.
+                if ui.button("Level of detail").clicked() {
+                    lod_window.open();
+                }
+
+                // Configures how vertices and edges are drawn.
+                if ui.button("Spheres and tubes").clicked() {
+                    spheres_and_tubes_window.open();
+                }
+
+                // Shows the problems found with the polytopes on screen.
+                if ui.button("Diagnostics").clicked() {
+                    diagnostics.open();
+                }
+
+                // Configures how higher-dimensional polytopes are projected.
+                if ui.button("Projection").clicked() {
+                    projection_window.open();
+                }
+
                 let mut checked = projection_type.is_orthogonal();
 
                 if ui.checkbox(&mut checked, "Orthogonal projection").clicked() {
+                    // All polytopes get updated afterwards.
                     projection_type.flip();
+                }
 
-                    // Forces an update on all polytopes.
-                    if let Some(mut p) = query.iter_mut().next() {
-                        p.set_changed();
-                    }
+                let mut checked = wireframe_mode.is_hidden_line();
+
+                if ui
+                    .checkbox(&mut checked, "Hide lines behind faces")
+                    .clicked()
+                {
+                    // All polytopes get updated afterwards.
+                    wireframe_mode.flip();
                 }
             });
 
@@ -299,10 +488,7 @@ pub fn show_top_panel(
                             if advanced(&keyboard) {
                                 dual_window.open();
                             } else if let Some(mut p) = query.iter_mut().next() {
-                                match p.try_dual_mut() {
-                                    Ok(_) => println!("Dual succeeded."),
-                                    Err(err) => eprintln!("Dual failed: {}", err),
-                                }
+                                Operation::Dual.apply(&mut p, *size_limit);
                             }
                         }
 
@@ -313,7 +499,7 @@ pub fn show_top_panel(
                             if advanced(&keyboard) {
                                 pyramid_window.open();
                             } else if let Some(mut p) = query.iter_mut().next() {
-                                *p = p.pyramid();
+                                Operation::Pyramid.apply(&mut p, *size_limit);
                             }
                         }
 
@@ -322,7 +508,7 @@ pub fn show_top_panel(
                             if advanced(&keyboard) {
                                 prism_window.open();
                             } else if let Some(mut p) = query.iter_mut().next() {
-                                *p = p.prism();
+                                Operation::Prism.apply(&mut p, *size_limit);
                             }
                         }
 
@@ -331,7 +517,7 @@ pub fn show_top_panel(
                             if advanced(&keyboard) {
                                 tegum_window.open();
                             } else if let Some(mut p) = query.iter_mut().next() {
-                                *p = p.tegum();
+                                Operation::Tegum.apply(&mut p, *size_limit);
                             }
                         }
 
@@ -340,10 +526,7 @@ pub fn show_top_panel(
                             if advanced(&keyboard) {
                                 antiprism_window.open();
                             } else if let Some(mut p) = query.iter_mut().next() {
-                                match p.try_antiprism() {
-                                    Ok(q) => *p = q,
-                                    Err(err) => eprintln!("Antiprism failed: {}", err),
-                                }
+                                Operation::Antiprism.apply(&mut p, *size_limit);
                             }
                         }
 
@@ -352,24 +535,14 @@ pub fn show_top_panel(
                         // Converts the active polytope into its Petrial.
                         if ui.button("Petrial").clicked() {
                             if let Some(mut p) = query.iter_mut().next() {
-                                if p.petrial_mut() {
-                                    println!("Petrial succeeded.");
-                                } else {
-                                    eprintln!("Petrial failed.");
-                                }
+                                Operation::Petrial.apply(&mut p, *size_limit);
                             }
                         }
 
                         // Converts the active polytope into its Petrie polygon.
                         if ui.button("Petrie polygon").clicked() {
                             if let Some(mut p) = query.iter_mut().next() {
-                                match p.petrie_polygon() {
-                                    Some(q) => {
-                                        *p = q;
-                                        println!("Petrie polygon succeeded.")
-                                    }
-                                    None => eprintln!("Petrie polygon failed."),
-                                }
+                                Operation::PetriePolygon.apply(&mut p, *size_limit);
                             }
                         }
 
@@ -378,16 +551,14 @@ pub fn show_top_panel(
                         // Converts the active polytope into its ditope.
                         if ui.button("Ditope").clicked() {
                             if let Some(mut p) = query.iter_mut().next() {
-                                p.ditope_mut();
-                                println!("Ditope succeeded!");
+                                Operation::Ditope.apply(&mut p, *size_limit);
                             }
                         }
 
                         // Converts the active polytope into its hosotope.
                         if ui.button("Hosotope").clicked() {
                             if let Some(mut p) = query.iter_mut().next() {
-                                p.hosotope_mut();
-                                println!("Hosotope succeeded!");
+                                Operation::Hosotope.apply(&mut p, *size_limit);
                             }
                         }
                     });
@@ -417,8 +588,7 @@ pub fn show_top_panel(
 
                     if ui.button("Omnitruncate").clicked() {
                         if let Some(mut p) = query.iter_mut().next() {
-                            p.abs_sort();
-                            *p = p.omnitruncate();
+                            Operation::Omnitruncate.apply(&mut p, *size_limit);
                         }
                     }
 
@@ -427,7 +597,7 @@ pub fn show_top_panel(
                     // Recenters a polytope.
                     if ui.button("Recenter").clicked() {
                         if let Some(mut p) = query.iter_mut().next() {
-                            p.recenter();
+                            Operation::Recenter.apply(&mut p, *size_limit);
                         }
                     }
 
@@ -435,85 +605,48 @@ pub fn show_top_panel(
 
                     // Toggles cross-section mode.
                     if ui.button("Cross-section").clicked() {
-                        match section_state.as_mut() {
-                            // The view is active, but will be inactivated.
-                            SectionState::Active {
-                                original_polytope, ..
-                            } => {
-                                *query.iter_mut().next().unwrap() = original_polytope.clone();
-                                *section_state = SectionState::Inactive;
-                            }
-
-                            // The view is inactive, but will be activated.
-                            SectionState::Inactive => {
-                                let mut p = query.iter_mut().next().unwrap();
-                                p.flatten();
-
-                                // The default direction is in the last coordinate axis.
-                                let dim = p.dim_or();
-                                let mut direction = Vector::zeros(dim);
-                                if dim > 0 {
-                                    direction[dim - 1] = 1.0;
-                                }
-
-                                let minmax = p.minmax(&direction).unwrap_or((-1.0, 1.0));
-                                let original_polytope = p.clone();
-
-                                *section_state = SectionState::Active {
-                                    original_polytope,
-                                    minmax,
-                                    hyperplane_pos: (minmax.0 + minmax.1) / 2.0,
-                                    flatten: true,
-                                    lock: false,
-                                };
-                                section_direction.0 = direction;
-                            }
-                        };
+                        if let Some(mut p) = query.iter_mut().next() {
+                            section_state.toggle(&mut p, &mut section_direction);
+                        }
                     }
                 });
 
                 // Operates on the elements of the loaded polytope.
                 ui.collapsing("Elements", |ui| {
+                    // Extracts any element or section of the active polytope.
+                    if ui.button("Browse…").clicked() {
+                        elements_window.open();
+                    }
+
                     // Converts the active polytope into any of its facets.
                     if ui.button("Facet").clicked() {
                         if let Some(mut p) = query.iter_mut().next() {
-                            println!("Facet");
-
-                            if let Some(mut facet) = p.facet(0) {
-                                facet.flatten();
-                                facet.recenter();
-                                *p = facet;
-
-                                println!("Facet succeeded.")
-                            } else {
-                                eprintln!("Facet failed: no facets.")
-                            }
+                            Operation::Facet.apply(&mut p, *size_limit);
                         }
                     }
 
                     // Converts the active polytope into any of its verfs.
                     if ui.button("Verf").clicked() {
                         if let Some(mut p) = query.iter_mut().next() {
-                            println!("Verf");
-
-                            match p.verf(0) {
-                                Ok(Some(mut verf)) => {
-                                    verf.flatten();
-                                    verf.recenter();
-                                    *p = verf;
-
-                                    println!("Verf succeeded.")
-                                }
-                                Ok(None) => eprintln!("Verf failed: no vertices."),
-                                Err(err) => eprintln!("Verf failed: {}", err),
-                            }
+                            Operation::Verf.apply(&mut p, *size_limit);
                         }
                     }
 
                     // Outputs the element types, currently just prints to console.
                     if ui.button("Counts").clicked() {
-                        if let Some(p) = query.iter_mut().next() {
-                            p.con().print_element_types();
+                        if let Some(mut p) = query.iter_mut().next() {
+                            Operation::Counts.apply(&mut p, *size_limit);
+                        }
+                    }
+                });
+
+                // Shows and edits the metadata of the loaded polytope.
+                ui.collapsing("Metadata", |ui| {
+                    if let Some(mut p) = query.iter_mut().next() {
+                        let mut metadata = p.con.metadata().cloned().unwrap_or_default();
+
+                        if show_metadata(ui, &mut metadata) {
+                            p.con.set_metadata(metadata);
                         }
                     }
                 });
@@ -522,46 +655,44 @@ pub fn show_top_panel(
                 ui.collapsing("Properties", |ui| {
                     // Determines the circumsphere of the polytope.
                     if ui.button("Circumsphere").clicked() {
-                        if let Some(p) = query.iter_mut().next() {
-                            match p.circumsphere() {
-                                Some(sphere) => println!(
-                                    "The circumradius is {} and the circumcenter is {}.",
-                                    sphere.radius(),
-                                    sphere.center
-                                ),
-                                None => println!("The polytope has no circumsphere."),
-                            }
+                        if let Some(mut p) = query.iter_mut().next() {
+                            Operation::Circumsphere.apply(&mut p, *size_limit);
                         }
                     }
 
                     // Determines whether the polytope is orientable.
                     if ui.button("Orientability").clicked() {
                         if let Some(mut p) = query.iter_mut().next() {
-                            if p.orientable() {
-                                println!("The polytope is orientable.");
-                            } else {
-                                println!("The polytope is not orientable.");
-                            }
+                            Operation::Orientability.apply(&mut p, *size_limit);
                         }
                     }
 
                     // Gets the volume of the polytope.
                     if ui.button("Volume").clicked() {
                         if let Some(mut p) = query.iter_mut().next() {
-                            p.abs_sort();
-
-                            if let Some(vol) = p.volume() {
-                                println!("The volume is {}.", vol);
-                            } else {
-                                println!("The polytope has no volume.");
-                            }
+                            Operation::Volume.apply(&mut p, *size_limit);
                         }
                     }
 
                     // Gets the number of flags of the polytope.
                     if ui.button("Flag count").clicked() {
-                        if let Some(p) = query.iter_mut().next() {
-                            println!("The polytope has {} flags.", p.flags().count())
+                        if let Some(mut p) = query.iter_mut().next() {
+                            Operation::FlagCount.apply(&mut p, *size_limit);
+                        }
+                    }
+
+                    // Gets the vertex configurations or the signatures of the
+                    // polytope.
+                    if ui.button("Signatures").clicked() {
+                        if let Some(mut p) = query.iter_mut().next() {
+                            Operation::Signatures.apply(&mut p, *size_limit);
+                        }
+                    }
+
+                    // Looks for floating point issues in the polytope.
+                    if ui.button("Numerical report").clicked() {
+                        if let Some(mut p) = query.iter_mut().next() {
+                            Operation::NumericalReport.apply(&mut p, *size_limit);
                         }
                     }
                 });
@@ -588,7 +719,7 @@ pub fn show_top_panel(
                 }
             });
 
-            // Switch language.
+            // Language and other preferences.
             menu::menu(ui, "Preferences", |ui| {
                 ui.collapsing("Language", |ui| {
                     for lang in SelectedLanguage::iter() {
@@ -599,13 +730,37 @@ pub fn show_top_panel(
 
                     if selected_language.is_changed() {
                         if let Some(poly) = query.iter_mut().next() {
-                            windows
-                                .get_primary_mut()
-                                .unwrap()
-                                .set_title(selected_language.parse(&poly.name));
+                            windows.get_primary_mut().unwrap().set_title(window_title(
+                                &selected_language,
+                                &poly,
+                                false,
+                            ));
                         }
                     }
                 });
+
+                ui.collapsing("Size limit", |ui| {
+                    let mut max_elements = size_limit.max_elements;
+
+                    ui.horizontal(|ui| {
+                        ui.add(
+                            egui::DragValue::new(&mut max_elements)
+                                .speed(1000.0)
+                                .clamp_range(0..=usize::MAX),
+                        );
+                        ui.label("Elements an operation may build");
+                    });
+
+                    if ui.button("Reset").clicked() {
+                        max_elements = SizeLimit::default().max_elements;
+                    }
+
+                    // Only written back on change, so that it isn't marked as
+                    // changed every frame.
+                    if max_elements != size_limit.max_elements {
+                        size_limit.max_elements = max_elements;
+                    }
+                });
             });
 
             // General help.
@@ -652,17 +807,68 @@ pub fn show_top_panel(
         });
 
         // Shows secondary views below the menu bar.
-        show_views(ui, query, section_state, section_direction);
+        show_views(ui, query, section_state, section_direction, bake_events);
+    });
+}
+
+/// Shows editable fields for the metadata of a polytope. Returns whether any of
+/// them was changed.
+fn show_metadata(ui: &mut Ui, metadata: &mut Metadata) -> bool {
+    let mut changed = false;
+
+    egui::Grid::new("metadata_grid").show(ui, |ui| {
+        let fields = vec![
+            ("Name", &mut metadata.name),
+            ("Source", &mut metadata.source),
+            ("Author", &mut metadata.author),
+            ("Construction", &mut metadata.construction),
+        ];
+
+        for (label, field) in fields {
+            let mut text = field.clone().unwrap_or_default();
+
+            ui.label(label);
+            if ui.text_edit_singleline(&mut text).changed() {
+                *field = (!text.is_empty()).then(|| text);
+                changed = true;
+            }
+            ui.end_row();
+        }
+
+        // Tags are edited as a comma-separated list.
+        let mut tags = metadata.tags.join(", ");
+
+        ui.label("Tags");
+        if ui.text_edit_singleline(&mut tags).changed() {
+            metadata.tags = tags
+                .split(',')
+                .map(str::trim)
+                .filter(|tag| !tag.is_empty())
+                .map(String::from)
+                .collect();
+            changed = true;
+        }
+        ui.end_row();
+
+        // Any other entries are only shown.
+        for (key, value) in &metadata.other {
+            ui.label(key);
+            ui.label(value);
+            ui.end_row();
+        }
     });
+
+    changed
 }
 
 /// Shows any secondary views that are active. Currently, just shows the
 /// cross-section view.
 fn show_views(
     ui: &mut Ui,
-    mut query: Query<&mut NamedConcrete>,
+    mut query: Query<&mut NamedConcrete, With<Active>>,
     mut section_state: ResMut<SectionState>,
     mut section_direction: ResMut<SectionDirection>,
+    mut bake_events: EventWriter<BakeSection>,
 ) {
     // The cross-section settings.
     if let SectionState::Active {
@@ -715,6 +921,13 @@ fn show_views(
                 section_state.close();
             }
 
+            // Adds a copy of the current cross-section to the scene.
+            if ui.button("Bake").clicked() {
+                if let Some(p) = query.iter_mut().next() {
+                    bake_events.send(BakeSection(p.clone()));
+                }
+            }
+
             let mut new_flatten = flatten;
             ui.add(egui::Checkbox::new(&mut new_flatten, "Flatten"));
 
@@ -0,0 +1,275 @@
+//! Contains the camera bookmarks and the standard viewpoints of the active
+//! polytope, which look down its axes of rotational symmetry or onto its
+//! Coxeter plane.
+
+use super::{camera::CameraInputEvent, scene::Active};
+use crate::projection::Projection;
+
+use bevy::{prelude::*, render::camera::Camera};
+use bevy_egui::{egui, EguiContext};
+use miratope_core::{
+    conc::{views::RotationAxis, Concrete, ConcretePolytope},
+    geometry::Point,
+};
+use miratope_lang::poly::conc::NamedConcrete;
+use serde::{Deserialize, Serialize};
+
+/// The plugin in charge of the viewpoints window.
+pub struct ViewpointsPlugin;
+
+impl Plugin for ViewpointsPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.insert_resource(ViewpointsWindow::default())
+            // Windows must be the first thing shown.
+            .add_system(show_viewpoints_window.system().before("show_top_panel"));
+    }
+}
+
+/// A transform that can be stored in the configuration file.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct SavedTransform {
+    /// The translation of the transform.
+    translation: [f32; 3],
+
+    /// The rotation of the transform, as a quaternion.
+    rotation: [f32; 4],
+
+    /// The scale of the transform.
+    scale: [f32; 3],
+}
+
+impl From<Transform> for SavedTransform {
+    fn from(transform: Transform) -> Self {
+        Self {
+            translation: transform.translation.into(),
+            rotation: Vec4::from(transform.rotation).into(),
+            scale: transform.scale.into(),
+        }
+    }
+}
+
+impl From<SavedTransform> for Transform {
+    fn from(saved: SavedTransform) -> Self {
+        let [x, y, z, w] = saved.rotation;
+
+        Self {
+            translation: saved.translation.into(),
+            rotation: Quat::from_xyzw(x, y, z, w).normalize(),
+            scale: saved.scale.into(),
+        }
+    }
+}
+
+/// A saved position of the camera.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Bookmark {
+    /// The name of the bookmark.
+    pub name: String,
+
+    /// The transform of the camera anchor.
+    pub anchor: SavedTransform,
+
+    /// The transform of the camera relative to its anchor.
+    pub camera: SavedTransform,
+}
+
+impl Bookmark {
+    /// Returns the event that moves the camera to the bookmark.
+    fn event(&self) -> CameraInputEvent {
+        CameraInputEvent::SetView {
+            anchor: self.anchor.into(),
+            camera: Some(self.camera.into()),
+        }
+    }
+}
+
+/// The camera bookmarks, which are stored in the configuration file.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct CameraBookmarks(pub Vec<Bookmark>);
+
+/// The window with the camera bookmarks and the standard viewpoints.
+#[derive(Default)]
+pub struct ViewpointsWindow {
+    /// Whether the window is open.
+    open: bool,
+
+    /// The name of the next bookmark.
+    name: String,
+
+    /// The rotation axes of the active polytope, together with its entity.
+    axes: Option<(Entity, Vec<RotationAxis>)>,
+
+    /// How many times an axis view was chosen, used to cycle through the
+    /// axes with the same order.
+    cycle: usize,
+}
+
+impl ViewpointsWindow {
+    /// Opens the window.
+    pub fn open(&mut self) {
+        self.open = true;
+    }
+}
+
+/// Converts the first three coordinates of a point into a vector.
+fn vec3(p: &Point) -> Vec3 {
+    let mut iter = p.iter().map(|&c| c as f32);
+    Vec3::new(
+        iter.next().unwrap_or(0.0),
+        iter.next().unwrap_or(0.0),
+        iter.next().unwrap_or(0.0),
+    )
+}
+
+/// Returns the transform of an anchor that looks down a given direction of a
+/// polytope towards its gravicenter.
+fn anchor_towards(poly: &Concrete, transform: &GlobalTransform, direction: Vec3) -> Transform {
+    let center = poly.gravicenter().map(|c| vec3(&c)).unwrap_or_default();
+
+    Transform {
+        translation: transform.mul_vec3(center),
+        rotation: transform.rotation * Quat::from_rotation_arc(Vec3::Z, direction.normalize()),
+        scale: Vec3::ONE,
+    }
+}
+
+/// The system that shows the viewpoints window.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn show_viewpoints_window(
+    egui_ctx: Res<EguiContext>,
+    mut viewpoints_window: ResMut<ViewpointsWindow>,
+    mut bookmarks: ResMut<CameraBookmarks>,
+    mut projection_type: ResMut<Projection>,
+    mut active: Query<(Entity, &mut NamedConcrete, &GlobalTransform), With<Active>>,
+    cameras: Query<(&Transform, &Parent), With<Camera>>,
+    anchors: Query<&Transform, Without<Camera>>,
+    mut cam_inputs: EventWriter<CameraInputEvent>,
+) {
+    let ViewpointsWindow {
+        open,
+        name,
+        axes,
+        cycle,
+    } = &mut *viewpoints_window;
+
+    if !*open {
+        return;
+    }
+
+    let (entity, mut poly, transform) = match active.iter_mut().next() {
+        Some(entry) => entry,
+        None => return,
+    };
+
+    // Recomputes the rotation axes if the active polytope changed.
+    if poly.is_changed() || !matches!(axes, Some((e, _)) if *e == entity) {
+        *axes = Some((entity, poly.con.rotation_axes()));
+    }
+    let axes = &axes.as_ref().unwrap().1;
+
+    // The current transforms of the camera and its anchor.
+    let current = cameras
+        .iter()
+        .next()
+        .and_then(|(camera, parent)| anchors.get(parent.0).ok().map(|anchor| (*anchor, *camera)));
+
+    let mut event = None;
+    let mut coxeter_plane = false;
+
+    egui::Window::new("Viewpoints")
+        .open(open)
+        .resizable(false)
+        .show(egui_ctx.ctx(), |ui| {
+            ui.label("Standard views:");
+
+            // One button for every order of an axis.
+            ui.horizontal_wrapped(|ui| {
+                let mut orders: Vec<_> = axes.iter().map(|axis| axis.order).collect();
+                orders.dedup();
+
+                for order in orders {
+                    if ui.button(format!("{}-fold axis", order)).clicked() {
+                        let same_order: Vec<_> =
+                            axes.iter().filter(|axis| axis.order == order).collect();
+                        let axis = same_order[*cycle % same_order.len()];
+                        *cycle += 1;
+
+                        event = Some(CameraInputEvent::SetView {
+                            anchor: anchor_towards(&poly.con, transform, vec3(&axis.direction)),
+                            camera: None,
+                        });
+                    }
+                }
+
+                if ui.button("Coxeter plane").clicked() {
+                    coxeter_plane = true;
+                }
+            });
+
+            ui.separator();
+            ui.label("Bookmarks:");
+
+            let mut removed = None;
+            for (idx, bookmark) in bookmarks.0.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    if ui.button(&bookmark.name).clicked() {
+                        event = Some(bookmark.event());
+                    }
+
+                    if ui.small_button("Remove").clicked() {
+                        removed = Some(idx);
+                    }
+                });
+            }
+
+            if let Some(idx) = removed {
+                bookmarks.0.remove(idx);
+            }
+
+            ui.horizontal(|ui| {
+                ui.text_edit_singleline(name);
+
+                if ui.button("Add bookmark").clicked() {
+                    if let Some((anchor, camera)) = current {
+                        let name = if name.is_empty() {
+                            format!("View {}", bookmarks.0.len() + 1)
+                        } else {
+                            std::mem::take(name)
+                        };
+
+                        bookmarks.0.push(Bookmark {
+                            name,
+                            anchor: anchor.into(),
+                            camera: camera.into(),
+                        });
+                    }
+                }
+            });
+        });
+
+    // Rotates the polytope so that its Coxeter plane is seen from the front.
+    if coxeter_plane {
+        match poly.con.clone().coxeter_plane_rotation() {
+            Some(m) => {
+                let rotated = poly.con.clone().apply(&m);
+                poly.con = rotated;
+
+                // Higher-dimensional polytopes are projected down orthogonally,
+                // so that the plane isn't distorted.
+                if poly.con.dim_or() > 3 && !projection_type.is_orthogonal() {
+                    projection_type.flip();
+                }
+
+                event = Some(CameraInputEvent::SetView {
+                    anchor: anchor_towards(&poly.con, transform, Vec3::Z),
+                    camera: None,
+                });
+            }
+            None => eprintln!("Coxeter plane failed: no valid Petrie polygon found."),
+        }
+    }
+
+    if let Some(event) = event {
+        cam_inputs.send(event);
+    }
+}
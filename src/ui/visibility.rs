@@ -0,0 +1,316 @@
+//! Contains the visibility filters, which hide some of the elements of a
+//! polytope according to their type, so that dense projections become
+//! legible.
+//!
+//! Elements are classified into types by [`Concrete::element_type_indices`].
+//! Hiding a type of cell, or any higher element, also hides every element that
+//! only lies on hidden cells.
+
+use super::scene::Active;
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+use miratope_core::{
+    abs::rank::{Rank, RankVec},
+    conc::Concrete,
+    Polytope,
+};
+use miratope_lang::poly::conc::NamedConcrete;
+use vec_like::VecLike;
+
+/// The plugin in charge of the visibility filters.
+pub struct VisibilityPlugin;
+
+impl Plugin for VisibilityPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.insert_resource(VisibilityWindow::default())
+            .add_system(reset_filters.system())
+            // Windows must be the first thing shown.
+            .add_system(show_visibility_window.system().before("show_top_panel"));
+    }
+}
+
+/// The elements of a polytope in the scene that are hidden.
+#[derive(Clone, Default, PartialEq)]
+pub struct VisibilityFilter {
+    /// The hidden element types, as pairs of a rank and the index of a type.
+    hidden: Vec<(usize, usize)>,
+
+    /// The facet that's focused, if any. Only this facet and those adjacent
+    /// to it are shown.
+    focus: Option<usize>,
+}
+
+/// Whether each element of each rank of a polytope is shown, from the
+/// vertices up to the facets. Ranks that are missing are shown in full.
+pub struct ShownElements(Vec<Vec<bool>>);
+
+impl ShownElements {
+    /// Hides the colors of the elements of a given rank that aren't shown.
+    pub fn filter<T>(&self, rank: usize, colors: Vec<T>) -> Vec<Option<T>> {
+        match self.0.get(rank) {
+            Some(shown) => colors
+                .into_iter()
+                .zip(shown)
+                .map(|(color, &shown)| if shown { Some(color) } else { None })
+                .collect(),
+            None => colors.into_iter().map(Some).collect(),
+        }
+    }
+}
+
+impl VisibilityFilter {
+    /// Returns whether the filter shows every element.
+    pub fn is_empty(&self) -> bool {
+        self.hidden.is_empty() && self.focus.is_none()
+    }
+
+    /// Returns whether an element type of a given rank is hidden.
+    fn is_hidden(&self, rank: usize, ty: usize) -> bool {
+        self.hidden.contains(&(rank, ty))
+    }
+
+    /// Hides or shows an element type of a given rank.
+    fn set_hidden(&mut self, rank: usize, ty: usize, hidden: bool) {
+        if hidden {
+            if !self.is_hidden(rank, ty) {
+                self.hidden.push((rank, ty));
+            }
+        } else {
+            self.hidden.retain(|&entry| entry != (rank, ty));
+        }
+    }
+
+    /// Returns which elements of a polytope are shown by the filter.
+    pub fn shown(&self, poly: &Concrete) -> ShownElements {
+        let rank = match poly.rank().try_usize() {
+            Some(rank) if rank > 0 && !self.is_empty() => rank,
+            _ => return ShownElements(Vec::new()),
+        };
+
+        // Element types are only computed if some of them are hidden.
+        let types = if self.hidden.is_empty() {
+            None
+        } else {
+            Some(poly.element_type_indices())
+        };
+
+        // The facets adjacent to the focused one, including itself.
+        let facets = &poly[Rank::from(rank - 1)];
+        let focused: Option<Vec<bool>> =
+            self.focus
+                .filter(|&f| rank >= 2 && f < facets.len())
+                .map(|f| {
+                    let mut focused = vec![false; facets.len()];
+                    focused[f] = true;
+
+                    for &ridge in facets[f].subs.iter() {
+                        for &facet in poly[Rank::from(rank - 2)][ridge].sups.iter() {
+                            focused[facet] = true;
+                        }
+                    }
+
+                    focused
+                });
+
+        let mut shown = vec![Vec::new(); rank];
+
+        // Whether each element of the rank above lies on a shown element of
+        // rank 3 or more. The body of the polytope always counts as shown.
+        let mut reach = vec![true; poly.el_count(Rank::from(rank))];
+
+        for r in (0..rank).rev() {
+            let elements = &poly[Rank::from(r)];
+            let mut new_reach = Vec::with_capacity(elements.len());
+
+            for (idx, el) in elements.iter().enumerate() {
+                let type_shown = match &types {
+                    Some(types) => !self.is_hidden(r, types[Rank::from(r)][idx]),
+                    None => true,
+                };
+                let is_focused = match &focused {
+                    Some(focused) if r + 1 == rank => focused[idx],
+                    _ => true,
+                };
+
+                // Faces, edges and vertices don't hide anything below them.
+                let reaches =
+                    is_focused && (r < 3 || type_shown) && el.sups.iter().any(|&sup| reach[sup]);
+
+                new_reach.push(reaches);
+                shown[r].push(reaches && type_shown);
+            }
+
+            reach = new_reach;
+        }
+
+        ShownElements(shown)
+    }
+}
+
+/// Resets the filters of the polytopes that change, since their element types
+/// might not be the same anymore.
+pub fn reset_filters(mut filters: Query<&mut VisibilityFilter, Changed<NamedConcrete>>) {
+    for mut filter in filters.iter_mut() {
+        if !filter.is_empty() {
+            *filter = Default::default();
+        }
+    }
+}
+
+/// Returns the name of the elements of a given rank.
+fn rank_name(rank: usize) -> String {
+    match rank {
+        0 => "Vertices".to_string(),
+        1 => "Edges".to_string(),
+        2 => "Faces".to_string(),
+        3 => "Cells".to_string(),
+        _ => format!("{}-elements", rank),
+    }
+}
+
+/// Describes an element of a given rank, as a representative of its type.
+fn type_label(poly: &Concrete, rank: usize, idx: usize) -> String {
+    let el = &poly[Rank::from(rank)][idx];
+
+    match rank {
+        0 => format!("vertex of degree {}", el.sups.len()),
+        1 => match crate::mesh::edge_length(poly, idx) {
+            Some(len) => format!("edge of length {:.4}", len),
+            None => format!("malformed edge with {} vertices", el.subs.len()),
+        },
+        2 => format!("{}-gon", el.subs.len()),
+        _ => format!("element with {} facets", el.subs.len()),
+    }
+}
+
+/// The window with the visibility filter of the active polytope.
+#[derive(Default)]
+pub struct VisibilityWindow {
+    /// Whether the window is open.
+    open: bool,
+
+    /// The element types of the active polytope, together with its entity.
+    types: Option<(Entity, RankVec<Vec<usize>>)>,
+}
+
+impl VisibilityWindow {
+    /// Opens the window.
+    pub fn open(&mut self) {
+        self.open = true;
+    }
+}
+
+/// The system that shows the visibility window.
+#[allow(clippy::type_complexity)]
+pub fn show_visibility_window(
+    egui_ctx: Res<EguiContext>,
+    mut visibility_window: ResMut<VisibilityWindow>,
+    mut query: Query<(Entity, &NamedConcrete, &mut VisibilityFilter), With<Active>>,
+    changed: Query<(), (With<Active>, Changed<NamedConcrete>)>,
+) {
+    let VisibilityWindow { open, types } = &mut *visibility_window;
+    if !*open {
+        return;
+    }
+
+    let (entity, poly, mut filter) = match query.iter_mut().next() {
+        Some(entry) => entry,
+        None => return,
+    };
+
+    // Recomputes the element types if the active polytope changed.
+    if changed.iter().next().is_some() || !matches!(types, Some((e, _)) if *e == entity) {
+        *types = Some((entity, poly.con.element_type_indices()));
+    }
+
+    let types = &types.as_ref().unwrap().1;
+    let mut new_filter = filter.clone();
+
+    egui::Window::new("Visibility")
+        .open(open)
+        .resizable(true)
+        .show(egui_ctx.ctx(), |ui| {
+            let rank = match poly.rank().try_usize() {
+                Some(rank) if rank > 0 => rank,
+                _ => {
+                    ui.label("This polytope has no elements to filter.");
+                    return;
+                }
+            };
+
+            egui::ScrollArea::auto_sized().show(ui, |ui| {
+                for r in 0..rank {
+                    let rank_types = &types[Rank::from(r)];
+
+                    // The number of elements of each type, and a representative
+                    // of each.
+                    let mut counts = Vec::new();
+                    let mut examples = Vec::new();
+                    for (idx, &ty) in rank_types.iter().enumerate() {
+                        if ty >= counts.len() {
+                            counts.resize(ty + 1, 0);
+                            examples.resize(ty + 1, idx);
+                        }
+                        if counts[ty] == 0 {
+                            examples[ty] = idx;
+                        }
+                        counts[ty] += 1;
+                    }
+
+                    egui::CollapsingHeader::new(rank_name(r))
+                        .id_source(r)
+                        .show(ui, |ui| {
+                            // Shows or hides every element of the rank.
+                            let mut all_shown =
+                                (0..counts.len()).all(|ty| !new_filter.is_hidden(r, ty));
+                            if ui.checkbox(&mut all_shown, "Show all").clicked() {
+                                for ty in 0..counts.len() {
+                                    new_filter.set_hidden(r, ty, !all_shown);
+                                }
+                            }
+
+                            for (ty, (&count, &example)) in
+                                counts.iter().zip(examples.iter()).enumerate()
+                            {
+                                let mut shown = !new_filter.is_hidden(r, ty);
+                                let label =
+                                    format!("{} × {}", count, type_label(&poly.con, r, example));
+
+                                if ui.checkbox(&mut shown, label).clicked() {
+                                    new_filter.set_hidden(r, ty, !shown);
+                                }
+                            }
+                        });
+                }
+
+                ui.separator();
+
+                // Shows only a facet and its neighbors.
+                let facet_count = poly.el_count(Rank::from(rank - 1));
+                ui.horizontal(|ui| {
+                    let mut focused = new_filter.focus.is_some();
+                    let mut facet = new_filter.focus.unwrap_or(0);
+
+                    ui.checkbox(&mut focused, "Only show facet");
+                    ui.add(
+                        egui::DragValue::new(&mut facet)
+                            .clamp_range(0..=facet_count.saturating_sub(1)),
+                    );
+                    ui.label("and its neighbors");
+
+                    new_filter.focus = if focused { Some(facet) } else { None };
+                });
+
+                if ui.button("Reset").clicked() {
+                    new_filter = Default::default();
+                }
+            });
+        });
+
+    // The filter is only written back if it changes, so that the polytope
+    // isn't redrawn every frame.
+    if new_filter != *filter {
+        *filter = new_filter;
+    }
+}